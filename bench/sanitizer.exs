@@ -0,0 +1,43 @@
+# Per-call cost of the sanitizer NIFs on representative input.
+#
+#     mix run bench/sanitizer.exs
+#
+# Run it before and after a change to the `baudrate_sanitizer` crate; the
+# numbers are only comparable on the same machine.
+
+alias Baudrate.Sanitizer.Native
+
+status = """
+<p><span class="h-card"><a href="https://mastodon.social/@eff" class="u-url mention">@<span>eff</span></a></span> \
+Thanks for the write-up! See <a href="https://example.com/article?id=1" rel="nofollow noopener" target="_blank">\
+<span class="invisible">https://</span><span class="ellipsis">example.com/article</span></a> \
+<a href="https://mastodon.social/tags/privacy" class="mention hashtag" rel="tag">#<span>privacy</span></a></p>\
+<p>Second paragraph with <em>emphasis</em> and <code>code</code>.</p>\
+"""
+
+markdown = """
+<h2>Title</h2><p>Text with <strong>bold</strong>, <a href="https://example.com">a link</a> and \
+<img src="/e.png" class="custom-emoji" alt=":x:"></p>\
+<pre><code class="language-rust">fn main() {}</code></pre><table><tr><td>1</td></tr></table>\
+"""
+
+plain = "Just a short status without any markup > nothing to strip."
+
+cases = [
+  {"sanitize_federation/1 (status)", &Native.sanitize_federation/1, status},
+  {"sanitize_federation/1 (plain text)", &Native.sanitize_federation/1, plain},
+  {"sanitize_markdown/1", &Native.sanitize_markdown/1, markdown},
+  {"strip_tags/1", &Native.strip_tags/1, status},
+  {"normalize_feed_html/1", &Native.normalize_feed_html/1, markdown}
+]
+
+iterations = 20_000
+
+for {name, fun, input} <- cases do
+  for _ <- 1..500, do: fun.(input)
+
+  {microseconds, _} = :timer.tc(fn -> for _ <- 1..iterations, do: fun.(input) end)
+
+  per_call = :erlang.float_to_binary(microseconds / iterations, decimals: 2)
+  IO.puts(String.pad_trailing(name, 40) <> per_call <> " µs/call")
+end
@@ -80,6 +80,20 @@ config :baudrate, Baudrate.Federation,
   # 30 days in seconds (matches actor_cache_ttl convention)
   stale_actor_max_age: 2_592_000
 
+# Media placeholders: :blurhash (Mastodon-compatible) or :thumbhash.
+# Animations over the frame or decoded-size limit are not thumbnailed.
+config :baudrate, Baudrate.Media,
+  placeholder_format: :blurhash,
+  max_animation_frames: 1_000,
+  max_animation_bytes: 512 * 1024 * 1024
+
+# Sanitizer decision logging: :off, :warning, :info, or :debug.  Read when
+# the NIF is loaded; events go to Logger (target: :logger) or are kept for
+# Baudrate.Sanitizer.Log.drain/0 (target: :buffer).
+config :baudrate, Baudrate.Sanitizer.Log,
+  level: :off,
+  target: :logger
+
 # WebAuthn / FIDO2 — base configuration (attestation policy and flags).
 # origin and rp_id are environment-specific; set in dev.exs, test.exs, and runtime.exs.
 # attestation and user_verification default to "none" and "preferred" (strings) in Wax.Challenge.
@@ -28,6 +28,9 @@ config :baudrate, :sql_sandbox, true
 # Print only warnings and errors during test
 config :logger, level: :warning
 
+# Record sanitizer decisions for the logging tests without printing them
+config :baudrate, Baudrate.Sanitizer.Log, level: :debug, target: :buffer
+
 # Initialize plugs at runtime for faster test compilation
 config :phoenix, :plug_init_mode, :runtime
 
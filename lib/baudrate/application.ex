@@ -7,6 +7,8 @@ defmodule Baudrate.Application do
 
   @impl true
   def start(_type, _args) do
+    Baudrate.Native.log_report()
+
     children = [
       BaudrateWeb.Telemetry,
       Baudrate.Repo,
@@ -18,6 +20,8 @@ defmodule Baudrate.Application do
       Baudrate.Content.BoardCache,
       {Task.Supervisor, name: Baudrate.Federation.TaskSupervisor},
       Baudrate.Federation.DomainBlockCache,
+      Baudrate.Content.LinkPreview.Robots,
+      Baudrate.Sanitizer.Log,
       Baudrate.Federation.DeliveryWorker,
       Baudrate.Federation.StaleActorCleaner,
       Baudrate.Bots.FeedWorker,
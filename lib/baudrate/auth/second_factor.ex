@@ -57,14 +57,23 @@ defmodule Baudrate.Auth.SecondFactor do
 
   @doc """
   Generates a Base64-encoded SVG data URI for QR code display.
+
+  Returns `nil` if the QR code cannot be rendered, so that the page can
+  still show the text it encodes.
   """
+  @spec totp_qr_data_uri(String.t()) :: String.t() | nil
   def totp_qr_data_uri(uri) do
-    svg =
-      uri
-      |> EQRCode.encode()
-      |> EQRCode.svg(width: 264)
+    case Baudrate.Media.Native.qr_svg(uri) do
+      {:ok, svg} ->
+        "data:image/svg+xml;base64," <> Base.encode64(svg)
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("qr_svg/2", message)
+        nil
 
-    "data:image/svg+xml;base64," <> Base.encode64(svg)
+      {:error, _reason} ->
+        nil
+    end
   end
 
   @doc """
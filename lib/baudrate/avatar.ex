@@ -7,11 +7,14 @@ defmodule Baudrate.Avatar do
     * Magic bytes validation rejects disguised files
     * Images are decoded to raw pixels and re-encoded as WebP,
       destroying polyglot files and embedded exploits
-    * All EXIF/metadata is stripped (`strip: true`)
+    * No EXIF/metadata is carried over by the re-encode
     * File paths use server-generated random hex IDs; no user input in paths
-    * Uses `image` library (libvips NIF) — no CLI shelling, no command injection surface
+    * Uses the `baudrate_media` NIF (`Baudrate.Media.Thumbnail`), which decodes
+      with dimension and memory limits — no CLI shelling, no command injection surface
   """
 
+  alias Baudrate.Media.{Native, Thumbnail}
+
   @sizes [120, 48, 36, 24]
 
   @magic_bytes %{
@@ -26,23 +29,23 @@ defmodule Baudrate.Avatar do
 
   `upload_path` is the temporary file path from LiveView's `consume_uploaded_entries`.
   `crop_params` is a map with normalized percentage keys: `"x"`, `"y"`, `"width"`, `"height"`.
+  Each size is the center square of the crop, and images smaller than a size
+  are not upscaled.
 
   Returns `{:ok, avatar_id}` or `{:error, reason}`.
   """
   def process_upload(upload_path, crop_params) do
     with :ok <- validate_magic_bytes(upload_path),
-         {:ok, image} <- Image.open(upload_path, access: :random),
-         {:ok, {image, _meta}} <- Image.autorotate(image),
-         {:ok, cropped} <- apply_crop(image, crop_params) do
+         {:ok, bytes} <- File.read(upload_path),
+         {:ok, cropped} <- apply_crop(bytes, crop_params),
+         {:ok, thumbnails} <- thumbnails(cropped) do
       avatar_id = generate_avatar_id()
       avatar_dir = Path.join(avatar_dir(), avatar_id)
       File.mkdir_p!(avatar_dir)
 
       try do
-        for size <- @sizes do
-          dest = Path.join(avatar_dir, "#{size}.webp")
-          thumbnail = Image.thumbnail!(cropped, size, crop: :center)
-          Image.write!(thumbnail, dest, strip_metadata: true)
+        for {size, data} <- thumbnails do
+          File.write!(Path.join(avatar_dir, "#{size}.webp"), data)
         end
 
         {:ok, avatar_id}
@@ -51,6 +54,13 @@ defmodule Baudrate.Avatar do
           File.rm_rf!(avatar_dir)
           {:error, Exception.message(e)}
       end
+    else
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("Avatar.process_upload/2", message)
+        {:error, :process_failed}
+
+      {:error, reason} ->
+        {:error, reason}
     end
   end
 
@@ -128,26 +138,29 @@ defmodule Baudrate.Avatar do
     end
   end
 
-  defp apply_crop(image, %{"x" => x, "y" => y, "width" => w, "height" => h}) do
-    {img_width, img_height, _} = Image.shape(image)
+  defp apply_crop(bytes, %{"x" => x, "y" => y, "width" => w, "height" => h}) do
+    # The crop is lossless; the thumbnails are the only lossy encode.
+    [x, y, w, h] = Enum.map([x, y, w, h], &fraction/1)
 
-    # Convert normalized percentages to pixel values
-    crop_x = round(x * img_width) |> max(0) |> min(img_width - 1)
-    crop_y = round(y * img_height) |> max(0) |> min(img_height - 1)
-    crop_w = round(w * img_width) |> max(1) |> min(img_width - crop_x)
-    crop_h = round(h * img_height) |> max(1) |> min(img_height - crop_y)
-
-    Image.crop(image, crop_x, crop_y, crop_w, crop_h)
+    with {:ok, %{data: png}} <- Native.crop_image(bytes, x, y, w, h, :png, Thumbnail.limits()) do
+      {:ok, png}
+    end
   end
 
-  defp apply_crop(image, _no_crop) do
-    # No crop params — use the whole image, crop to center square
-    {img_width, img_height, _} = Image.shape(image)
-    side = min(img_width, img_height)
-    x = div(img_width - side, 2)
-    y = div(img_height - side, 2)
+  # No crop params — use the whole image; each thumbnail is its center square
+  defp apply_crop(bytes, _no_crop), do: {:ok, bytes}
 
-    Image.crop(image, x, y, side, side)
+  # Normalized percentages, clamped to 0.0..1.0 and sent as floats.
+  defp fraction(value) when is_number(value), do: value |> max(0) |> min(1) |> Kernel.*(1.0)
+  defp fraction(_value), do: 0.0
+
+  defp thumbnails(bytes) do
+    Enum.reduce_while(@sizes, {:ok, []}, fn size, {:ok, acc} ->
+      case Thumbnail.square_webp(bytes, size) do
+        {:ok, %{data: data}} -> {:cont, {:ok, [{size, data} | acc]}}
+        error -> {:halt, error}
+      end
+    end)
   end
 
   defp avatar_dir do
@@ -31,6 +31,7 @@ defmodule Baudrate.Bots.FaviconFetcher do
   alias Baudrate.Auth
   alias Baudrate.Bots
   alias Baudrate.Federation.HTTPClient
+  alias Baudrate.HtmlParser.Native, as: HtmlParser
 
   @max_favicon_size 2 * 1024 * 1024
 
@@ -175,37 +176,30 @@ defmodule Baudrate.Bots.FaviconFetcher do
     (html_candidates ++ standard_paths) |> Enum.uniq()
   end
 
-  # Extracts raster icon URLs from HTML link tags, skipping SVG.
-  # Priority: apple-touch-icon (largest first) > icon/shortcut icon (largest first).
+  # Extracts raster icon URLs from HTML link tags, skipping SVG. The NIF
+  # returns candidates already ordered apple-touch-icon (largest first) >
+  # icon/shortcut icon (largest first).
   defp extract_raster_icon_urls(html, site_url) do
-    links =
-      html
-      |> extract_link_tags()
-      |> Enum.reject(&svg_link?/1)
-
-    apple =
-      links
-      |> Enum.filter(fn l -> l.rel in ["apple-touch-icon", "apple-touch-icon-precomposed"] end)
-      |> Enum.sort_by(&parse_icon_size/1, :desc)
-
-    icon =
-      links
-      |> Enum.filter(fn l -> l.rel in ["icon", "shortcut icon"] end)
-      |> Enum.sort_by(&parse_icon_size/1, :desc)
-
-    (apple ++ icon)
-    |> Enum.map(fn l -> resolve_url(l.href, site_url) end)
+    case HtmlParser.extract_icons(html, site_url) do
+      icons when is_list(icons) ->
+        icons
+        |> Enum.reject(&svg_icon?/1)
+        |> Enum.map(& &1.url)
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("extract_icons/2", message)
+        []
+    end
   end
 
-  defp svg_link?(%{type: type, href: href}) do
+  defp svg_icon?(%{content_type: type, url: url}) do
     svg_type = is_binary(type) and String.contains?(type, "svg")
 
     svg_href =
-      is_binary(href) and
-        href
-        |> URI.parse()
-        |> Map.get(:path, "")
-        |> then(&(is_binary(&1) and String.ends_with?(String.downcase(&1), ".svg")))
+      url
+      |> URI.parse()
+      |> Map.get(:path)
+      |> then(&(is_binary(&1) and String.ends_with?(String.downcase(&1), ".svg")))
 
     svg_type or svg_href
   end
@@ -215,50 +209,6 @@ defmodule Baudrate.Bots.FaviconFetcher do
     %URI{scheme: uri.scheme, host: uri.host, port: uri.port} |> URI.to_string()
   end
 
-  defp extract_link_tags(html) do
-    ~r/<link\s+([^>]+)>/i
-    |> Regex.scan(html)
-    |> Enum.map(fn [_, attrs] ->
-      %{
-        rel: extract_attr(attrs, "rel") || "",
-        href: extract_attr(attrs, "href") || "",
-        sizes: extract_attr(attrs, "sizes") || "",
-        type: extract_attr(attrs, "type") || ""
-      }
-    end)
-    |> Enum.filter(fn l -> l.href != "" end)
-  end
-
-  defp extract_attr(attrs, name) do
-    case Regex.run(~r/#{name}=["']([^"']+)["']/i, attrs) do
-      [_, value] -> value
-      _ -> nil
-    end
-  end
-
-  defp parse_icon_size(%{sizes: sizes}) do
-    case Regex.run(~r/(\d+)x\d+/i, sizes) do
-      [_, n] ->
-        case Integer.parse(n) do
-          {size, ""} -> size
-          _ -> 0
-        end
-
-      _ ->
-        0
-    end
-  end
-
-  defp resolve_url(href, site_url) when is_binary(href) do
-    case URI.parse(href) do
-      %URI{scheme: nil} ->
-        URI.merge(URI.parse(site_url), href) |> URI.to_string()
-
-      _ ->
-        href
-    end
-  end
-
   defp download_favicon(url, referer) do
     case HTTPClient.get_html(url,
            max_size: @max_favicon_size,
@@ -6,10 +6,10 @@ defmodule Baudrate.Bots.FeedParser do
   backed by the [`feedparser-rs`](https://github.com/bug-ops/feedparser-rs) Rust
   library.  Raw field values returned by the NIF are then normalized here:
 
-  - Titles: HTML stripped via `Baudrate.Sanitizer.Native.strip_tags/1`, HTML
-    entities decoded, whitespace trimmed, truncated to 255 characters.
+  - Titles: HTML stripped via `Baudrate.Sanitizer.Native.html_to_text/1`,
+    HTML entities decoded, whitespace trimmed, truncated to 255 characters.
   - Body: full `content` block preferred over `summary`; sanitized and
-    normalized via `Baudrate.Sanitizer.Native.normalize_feed_html/1`
+    normalized via `Baudrate.Federation.Sanitizer.sanitize_feed/1`
     (removes empty paragraphs and excessive line-break runs after
     Ammonia strips disallowed elements).
   - Tags: de-duplicated list of plain-text category strings.
@@ -35,6 +35,7 @@ defmodule Baudrate.Bots.FeedParser do
     * `:link` — original source URL
     * `:tags` — list of category strings
     * `:published_at` — `DateTime` or nil
+    * `:updated_at` — `DateTime` or nil (same clamping as `:published_at`)
 
   Returns `{:ok, entries}` or `{:error, reason}`.
   """
@@ -54,6 +55,36 @@ defmodule Baudrate.Bots.FeedParser do
     end
   end
 
+  @doc """
+  Parses a feed binary and returns its metadata along with normalized entries.
+
+  Returns `{:ok, feed}` where `feed` is a map with keys:
+    * `:title` — plain text feed title (truncated to 255 chars), or nil
+    * `:link` — site URL, or nil
+    * `:description` — plain text description, or nil
+    * `:updated_at` — `DateTime` or nil
+    * `:entries` — normalized entries as returned by `parse/1`
+
+  Returns `{:error, reason}` on parse failure.
+  """
+  @spec parse_document(binary()) :: {:ok, map()} | {:error, term()}
+  def parse_document(data) when is_binary(data) do
+    case FeedParserNative.parse_feed_document(data) do
+      {:ok, %FeedParserNative.Feed{} = feed} ->
+        {:ok,
+         %{
+           title: normalize_feed_text(feed.title),
+           link: feed.link,
+           description: normalize_feed_text(feed.description),
+           updated_at: clamp_published_at(feed.updated_rfc3339),
+           entries: feed.entries |> Enum.map(&normalize_entry/1) |> Enum.reject(&is_nil/1)
+         }}
+
+      {:error, reason} ->
+        {:error, reason}
+    end
+  end
+
   # --- Entry normalization ---
 
   defp normalize_entry(%FeedParserNative.Entry{} = entry) do
@@ -69,7 +100,8 @@ defmodule Baudrate.Bots.FeedParser do
         body: normalize_body(entry.content, entry.summary),
         link: link,
         tags: normalize_tags(entry.tags),
-        published_at: clamp_published_at(entry.published_rfc3339)
+        published_at: clamp_published_at(entry.published_rfc3339),
+        updated_at: clamp_published_at(entry.updated_rfc3339)
       }
     end
   end
@@ -77,8 +109,13 @@ defmodule Baudrate.Bots.FeedParser do
   defp normalize_title(nil), do: "(untitled)"
 
   defp normalize_title(raw) when is_binary(raw) do
-    trimmed = String.trim(raw)
-    text = if trimmed == "", do: "(untitled)", else: trimmed
+    normalize_feed_text(raw) || "(untitled)"
+  end
+
+  defp normalize_feed_text(nil), do: nil
+
+  defp normalize_feed_text(raw) when is_binary(raw) do
+    text = String.trim(raw)
 
     # Strip HTML tags if present (Ammonia's strip_tags re-encodes entities, so
     # decode them afterwards).  Also decode entities on plain-text titles —
@@ -87,16 +124,18 @@ defmodule Baudrate.Bots.FeedParser do
     # entity decode must always run.
     text =
       if String.contains?(text, "<") do
-        text
-        |> Baudrate.Sanitizer.Native.strip_tags()
+        Baudrate.Sanitizer.Native.html_to_text(text)
       else
-        text
+        Baudrate.Sanitizer.Native.decode_html_entities(text)
       end
-      |> Baudrate.Sanitizer.Native.decode_html_entities()
 
     text
     |> String.trim()
     |> String.slice(0, @max_title_length)
+    |> case do
+      "" -> nil
+      s -> s
+    end
   end
 
   defp normalize_body(content, summary) do
@@ -107,7 +146,7 @@ defmodule Baudrate.Bots.FeedParser do
         true -> ""
       end
 
-    Baudrate.Sanitizer.Native.normalize_feed_html(raw)
+    Baudrate.Federation.Sanitizer.sanitize_feed(raw)
   end
 
   defp normalize_tags(tags) when is_list(tags) do
@@ -5,13 +5,28 @@ defmodule Baudrate.Bots.FeedParserNative do
   Wraps the [`feedparser-rs`](https://github.com/bug-ops/feedparser-rs) library
   which supports RSS 2.0, RSS 1.0 (RDF), Atom 1.0, and JSON Feed formats.
 
-  The single public function `parse_feed/1` accepts raw feed bytes and returns a
-  list of `Entry` structs with raw (unsanitized) field values.  Post-processing
+  `parse_feed/1` accepts raw feed bytes and returns a list of `Entry` structs
+  with raw (unsanitized) field values; `parse_feed_document/1` additionally
+  returns feed-level metadata in a `Feed` struct.  `parse_opml/1` reads
+  OPML subscription lists into a tree of `Outline` structs.
+
+  The crate also generates documents for outgoing feeds via `build_atom/2`
+  (Atom 1.0) and `build_json_feed/2` (JSON Feed 1.1), and escapes text for
+  the feeds rendered from templates with `xml_escape/1` and
+  `strip_invalid_xml_chars/1`.  Post-processing
   (HTML sanitization, title normalization, date clamping) is handled by
   `Baudrate.Bots.FeedParser`.
+
+  `native_info/0` reports what the loaded library supports; see
+  `Baudrate.Native`.
+
+  A panic inside a NIF is returned as `{:error, {:panic, message}}` (the
+  `t:panic/0` type) instead of being raised.
   """
 
-  use Rustler, otp_app: :baudrate, crate: "baudrate_feed_parser"
+  use RustlerPrecompiled, Baudrate.Native.precompiled(crate: "baudrate_feed_parser")
+
+  @type panic :: {:error, {:panic, String.t()}}
 
   defmodule Entry do
     @moduledoc "Raw feed entry returned by the `baudrate_feed_parser` NIF."
@@ -23,10 +38,63 @@ defmodule Baudrate.Bots.FeedParserNative do
             content: String.t() | nil,
             summary: String.t() | nil,
             tags: [String.t()],
-            published_rfc3339: String.t() | nil
+            published_rfc3339: String.t() | nil,
+            updated_rfc3339: String.t() | nil
+          }
+
+    defstruct [
+      :id,
+      :title,
+      :link,
+      :content,
+      :summary,
+      tags: [],
+      published_rfc3339: nil,
+      updated_rfc3339: nil
+    ]
+  end
+
+  defmodule Feed do
+    @moduledoc "Raw feed metadata and entries returned by `parse_feed_document/1`."
+
+    @type t :: %__MODULE__{
+            title: String.t() | nil,
+            link: String.t() | nil,
+            description: String.t() | nil,
+            updated_rfc3339: String.t() | nil,
+            entries: [Baudrate.Bots.FeedParserNative.Entry.t()]
           }
 
-    defstruct [:id, :title, :link, :content, :summary, tags: [], published_rfc3339: nil]
+    defstruct [:title, :link, :description, :updated_rfc3339, entries: []]
+  end
+
+  defmodule Outline do
+    @moduledoc """
+    OPML `<outline>` element returned by `parse_opml/1`.
+
+    Subscriptions carry an `xml_url`; folders carry `children`.
+    """
+
+    @type t :: %__MODULE__{
+            title: String.t() | nil,
+            xml_url: String.t() | nil,
+            html_url: String.t() | nil,
+            type: String.t() | nil,
+            children: [t()]
+          }
+
+    defstruct [:title, :xml_url, :html_url, :type, children: []]
+  end
+
+  defmodule Opml do
+    @moduledoc "OPML document returned by `parse_opml/1`."
+
+    @type t :: %__MODULE__{
+            title: String.t() | nil,
+            outlines: [Baudrate.Bots.FeedParserNative.Outline.t()]
+          }
+
+    defstruct [:title, outlines: []]
   end
 
   @doc """
@@ -36,6 +104,104 @@ defmodule Baudrate.Bots.FeedParserNative do
 
   Returns `{:ok, [%Entry{}]}` on success or `{:error, reason}` on parse failure.
   """
-  @spec parse_feed(binary()) :: {:ok, [Entry.t()]} | {:error, String.t()}
+  @spec parse_feed(binary()) :: {:ok, [Entry.t()]} | {:error, String.t()} | panic()
   def parse_feed(_data), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse a feed from raw bytes, including feed-level metadata.
+
+  Accepts the same formats as `parse_feed/1`.
+
+  Returns `{:ok, %Feed{}}` on success or `{:error, reason}` on parse failure.
+  """
+  @spec parse_feed_document(binary()) :: {:ok, Feed.t()} | {:error, String.t()} | panic()
+  def parse_feed_document(_data), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Build an Atom 1.0 document.
+
+  `feed_meta` requires `:title` and `:self_url`; `:id` (defaults to
+  `:self_url`), `:link`, `:subtitle`, `:language`, and `:updated`
+  (`DateTime`, defaults to now) are optional.
+
+  Each entry requires `:id` and `:title`; `:link`, `:published` and
+  `:updated` (`DateTime`), `:author`, `:summary` and `:content` (HTML,
+  embedded escaped as `type="html"`), and `:categories` are optional.
+
+  All text is XML-escaped and characters not allowed in XML 1.0 are dropped,
+  so the output is well-formed for any input. Raises `ArgumentError` when a
+  required key is missing.
+  """
+  @spec build_atom(map(), [map()]) :: String.t() | panic()
+  def build_atom(_feed_meta, _entries), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Build a JSON Feed 1.1 document.
+
+  `feed_meta` requires `:title`; `:home_page_url`, `:feed_url`,
+  `:description`, `:language`, `:icon`, `:favicon`, and `:authors` (list of
+  maps with `:name` / `:url` / `:avatar`) are optional.
+
+  Each entry requires `:id`; `:url`, `:external_url`, `:title`,
+  `:content_html`, `:content_text`, `:summary`, `:image`, `:language`,
+  `:published` / `:updated` (`DateTime`, emitted as `date_published` /
+  `date_modified`), `:authors`, `:tags`, and `:attachments` are optional.
+  Attachments are maps with `:url` and `:mime_type`, plus optional `:title`,
+  `:size_in_bytes`, and `:duration_in_seconds`.
+
+  `:content_html` is embedded as given, so callers must pass sanitized HTML.
+  Raises `ArgumentError` when a required key is missing.
+  """
+  @spec build_json_feed(map(), [map()]) :: String.t() | panic()
+  def build_json_feed(_feed_meta, _entries), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse an OPML subscription list.
+
+  Returns the `<head><title>` and the `<body>` outline tree.  Each outline's
+  `title` is its `text` attribute (falling back to `title`); `xml_url`,
+  `html_url`, and `type` come from the matching attributes, whose names are
+  matched case-insensitively.
+
+  The parser is lenient towards the malformed OPML that aggregators commonly
+  export: bare `&` and HTML entities in attributes, unclosed or mismatched
+  tags, and truncated documents.  Outlines left open are closed at the end
+  of the document.
+
+  Returns `{:ok, %Opml{}}`, or `{:error, reason}` when the input contains
+  neither an `<opml>` element nor any outline.
+  """
+  @spec parse_opml(binary()) :: {:ok, Opml.t()} | {:error, String.t()} | panic()
+  def parse_opml(_xml), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Escape `text` for XML element content and attribute values.
+
+  `&`, `<`, `>`, `"`, and `'` become entities.  Characters that XML 1.0
+  cannot represent at all, such as NUL and most C0 controls, are dropped:
+  a single one makes the whole document ill-formed.  Invalid UTF-8 is
+  replaced with U+FFFD.
+
+      iex> Baudrate.Bots.FeedParserNative.xml_escape(~s(Tom & "Jerry"\\u0007))
+      "Tom &amp; &quot;Jerry&quot;"
+  """
+  @spec xml_escape(binary()) :: String.t() | panic()
+  def xml_escape(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Drop the characters XML 1.0 cannot represent from `text`, as
+  `xml_escape/1` does, without escaping anything else.
+
+  For markup embedded in a CDATA section, where entities are not
+  interpreted but invalid characters still make the document ill-formed.
+  """
+  @spec strip_invalid_xml_chars(binary()) :: String.t() | panic()
+  def strip_invalid_xml_chars(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the crate version, enabled Cargo features, and the `{name, arity}`
+  of every NIF in the loaded library.
+  """
+  @spec native_info() :: Baudrate.Native.info() | panic()
+  def native_info, do: :erlang.nif_error(:nif_not_loaded)
 end
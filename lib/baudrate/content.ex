@@ -262,6 +262,9 @@ defmodule Baudrate.Content do
   # --- Article Tags ---
 
   defdelegate extract_tags(text), to: Tags
+  defdelegate extract_hashtags(text), to: Tags
+  defdelegate normalize_tag(tag), to: Tags
+  defdelegate normalize_hashtag(tag), to: Tags
   defdelegate sync_article_tags(article), to: Tags
 
   def articles_by_tag(tag, opts \\ []),
@@ -7,13 +7,16 @@ defmodule Baudrate.Content.ArticleImageStorage do
     * Magic bytes validation rejects disguised files
     * Images are decoded to raw pixels and re-encoded as WebP,
       destroying polyglot files and embedded exploits
-    * All EXIF/metadata is stripped (`strip_metadata: true`)
+    * No EXIF/metadata is carried over by the re-encode
     * Images smaller than 16x16px are rejected
     * Images are downscaled to max 1024px on the longest side
     * File paths use server-generated random hex IDs; no user input in paths
-    * Uses `image` library (libvips NIF) — no CLI shelling, no command injection surface
+    * Uses the `baudrate_media` NIF (`Baudrate.Media.Thumbnail`), which decodes
+      with dimension and memory limits — no CLI shelling, no command injection surface
   """
 
+  alias Baudrate.Media.{Native, Thumbnail}
+
   @max_dimension 1024
   @min_dimension 16
 
@@ -27,45 +30,36 @@ defmodule Baudrate.Content.ArticleImageStorage do
   Processes an uploaded image file and stores it as WebP on disk.
 
   Validates magic bytes, auto-rotates, downscales to max #{@max_dimension}px
-  on the longest side (aspect-preserving), re-encodes as WebP with metadata
-  stripped.
+  on the longest side (aspect-preserving), and re-encodes as WebP without
+  metadata.
 
   Returns `{:ok, %{filename, storage_path, width, height}}` or `{:error, reason}`.
   """
   def process_upload(upload_path) do
     with :ok <- validate_magic_bytes(upload_path),
-         {:ok, image} <- Image.open(upload_path, access: :random),
-         {:ok, {image, _meta}} <- Image.autorotate(image),
-         :ok <- validate_min_dimensions(image) do
-      {w, h, _bands} = Image.shape(image)
-
-      image =
-        if max(w, h) > @max_dimension do
-          Image.thumbnail!(image, @max_dimension)
-        else
-          image
-        end
-
+         {:ok, bytes} <- File.read(upload_path),
+         {:ok, probe} <- Native.probe_image(bytes),
+         :ok <- validate_min_dimensions(probe),
+         {:ok, webp} <- Thumbnail.resize_webp(bytes, @max_dimension, @max_dimension) do
       filename = generate_filename()
       File.mkdir_p!(upload_dir())
       dest = Path.join(upload_dir(), filename)
 
-      try do
-        Image.write!(image, dest, strip_metadata: true)
-        {final_w, final_h, _} = Image.shape(Image.open!(dest, access: :random))
-
-        {:ok,
-         %{
-           filename: filename,
-           storage_path: dest,
-           width: final_w,
-           height: final_h
-         }}
-      rescue
-        e ->
+      case File.write(dest, webp.data) do
+        :ok ->
+          {:ok, %{filename: filename, storage_path: dest, width: webp.width, height: webp.height}}
+
+        {:error, reason} ->
           File.rm(dest)
-          {:error, Exception.message(e)}
+          {:error, reason}
       end
+    else
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("ArticleImageStorage.process_upload/1", message)
+        {:error, :process_failed}
+
+      {:error, reason} ->
+        {:error, reason}
     end
   end
 
@@ -127,9 +121,7 @@ defmodule Baudrate.Content.ArticleImageStorage do
     end)
   end
 
-  defp validate_min_dimensions(image) do
-    {w, h, _} = Image.shape(image)
-
+  defp validate_min_dimensions(%{width: w, height: h}) do
     if w >= @min_dimension and h >= @min_dimension do
       :ok
     else
@@ -2,7 +2,8 @@ defmodule Baudrate.Content.ArticleTag do
   @moduledoc """
   Schema for article hashtags.
 
-  Each record links an article to a single lowercase tag string.
+  Each record links an article to a single tag string, in the canonical
+  form of `Baudrate.Content.normalize_tag/1`.
   Tags are extracted from article bodies on create/update and stored
   for efficient querying (e.g., the `/tags/:tag` browse page).
   """
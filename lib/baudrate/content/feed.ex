@@ -28,7 +28,7 @@ defmodule Baudrate.Content.Feed do
 
   Only includes local articles (those with a `user_id`), excludes soft-deleted
   articles, and deduplicates cross-posted articles. Results are ordered newest
-  first with user and boards preloaded.
+  first with user, boards, and article images preloaded.
   """
   def list_recent_public_articles(limit \\ 20) do
     from(a in Article,
@@ -43,7 +43,7 @@ defmodule Baudrate.Content.Feed do
       distinct: a.id,
       order_by: [desc: a.inserted_at, desc: a.id],
       limit: ^limit,
-      preload: [:user, :boards]
+      preload: [:user, :boards, :article_images]
     )
     |> Repo.all()
   end
@@ -68,7 +68,7 @@ defmodule Baudrate.Content.Feed do
               not is_nil(a.user_id),
           order_by: [desc: a.inserted_at, desc: a.id],
           limit: ^limit,
-          preload: [:user, :boards]
+          preload: [:user, :boards, :article_images]
         )
         |> Repo.all()
 
@@ -80,7 +80,7 @@ defmodule Baudrate.Content.Feed do
   Returns recent articles by a user that appear in at least one public board.
 
   Inherently local-only since it filters by `user_id`. Results are deduplicated
-  and ordered newest first with user and boards preloaded.
+  and ordered newest first with user, boards, and article images preloaded.
   """
   def list_recent_public_articles_by_user(user_id, limit \\ 20) do
     from(a in Article,
@@ -95,7 +95,7 @@ defmodule Baudrate.Content.Feed do
       distinct: a.id,
       order_by: [desc: a.inserted_at, desc: a.id],
       limit: ^limit,
-      preload: [:user, :boards]
+      preload: [:user, :boards, :article_images]
     )
     |> Repo.all()
   end
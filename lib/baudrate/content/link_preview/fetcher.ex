@@ -6,10 +6,11 @@ defmodule Baudrate.Content.LinkPreview.Fetcher do
     1. Check domain against `DomainBlockCache`
     2. Check DB for existing cached preview (7-day TTL, 24-hour failed retry delay)
     3. Check rate limits (per-domain + per-user)
-    4. Fetch HTML via `HTTPClient`
-    5. Parse OG/Twitter/fallback metadata with html5ever NIF
-    6. Proxy image if present (re-encode to WebP)
-    7. Upsert `LinkPreview` record
+    4. Check the site's robots.txt and crawl delay (`LinkPreview.Robots`)
+    5. Fetch HTML via `HTTPClient`
+    6. Parse OG/Twitter/fallback metadata with html5ever NIF
+    7. Proxy image if present (re-encode to WebP)
+    8. Upsert `LinkPreview` record
   """
 
   require Logger
@@ -17,7 +18,7 @@ defmodule Baudrate.Content.LinkPreview.Fetcher do
   import Ecto.Query
 
   alias Baudrate.Content.LinkPreview
-  alias Baudrate.Content.LinkPreview.ImageProxy
+  alias Baudrate.Content.LinkPreview.{ImageProxy, Robots}
   alias Baudrate.Federation.HTTPClient
   alias Baudrate.Federation.Validator
   alias Baudrate.Repo
@@ -151,6 +152,12 @@ defmodule Baudrate.Content.LinkPreview.Fetcher do
   defp maybe_check_user_rate(user_id), do: RateLimits.check_link_preview_user(user_id)
 
   defp fetch_metadata(url) do
+    with :ok <- Robots.check(url) do
+      fetch_html(url)
+    end
+  end
+
+  defp fetch_html(url) do
     case HTTPClient.get_html(url) do
       {:ok, %{body: body, headers: headers}} ->
         if html_content_type?(headers) do
@@ -176,12 +183,16 @@ defmodule Baudrate.Content.LinkPreview.Fetcher do
 
   defp html_content_type?(_), do: true
 
-  defp parse_og_metadata(html, _url) do
-    %{title: title, description: description, image_url: image_url, site_name: site_name} =
-      HtmlParser.parse_og_metadata(html)
+  defp parse_og_metadata(html, url) do
+    case HtmlParser.extract_card(html, url) do
+      %{title: title, description: description, image_url: image_url, site_name: site_name} ->
+        %{title: title, description: description, image_url: image_url, site_name: site_name}
+        |> sanitize_metadata()
 
-    %{title: title, description: description, image_url: image_url, site_name: site_name}
-    |> sanitize_metadata()
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("extract_card/2", message)
+        %{title: nil, description: nil, image_url: nil, site_name: nil}
+    end
   end
 
   defp sanitize_metadata(metadata) do
@@ -194,8 +205,18 @@ defmodule Baudrate.Content.LinkPreview.Fetcher do
   defp sanitize_text(nil, _max), do: nil
 
   defp sanitize_text(text, max) do
+    case Sanitizer.strip_tags(text) do
+      stripped when is_binary(stripped) ->
+        truncate_text(stripped, max)
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("strip_tags/1", message)
+        nil
+    end
+  end
+
+  defp truncate_text(text, max) do
     text
-    |> Sanitizer.strip_tags()
     |> strip_control_chars()
     |> String.trim()
     |> String.slice(0, max)
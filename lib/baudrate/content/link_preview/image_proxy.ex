@@ -5,7 +5,7 @@ defmodule Baudrate.Content.LinkPreview.ImageProxy do
   Security pipeline:
     1. Fetch via SSRF-safe `HTTPClient` (5 MB cap)
     2. Validate magic bytes (JPEG/PNG/GIF/WebP only)
-    3. Re-encode to WebP via libvips (strips EXIF, max 1200x630)
+    3. Re-encode to WebP via `Baudrate.Media.Thumbnail` (no EXIF, max 1200x630)
     4. Store locally — remote image never saved to disk raw
 
   The original remote image exists only in memory during processing.
@@ -14,6 +14,7 @@ defmodule Baudrate.Content.LinkPreview.ImageProxy do
   require Logger
 
   alias Baudrate.Federation.HTTPClient
+  alias Baudrate.Media.Thumbnail
 
   @max_image_size 5 * 1024 * 1024
   @max_width 1200
@@ -103,31 +104,25 @@ defmodule Baudrate.Content.LinkPreview.ImageProxy do
     serving_path = "/uploads/link_preview_images/#{hash_hex}.webp"
     abs = abs_path(serving_path)
 
-    File.mkdir_p!(Path.dirname(abs))
-
-    with {:ok, image} <- Image.from_binary(body),
-         {:ok, resized} <- resize_to_fit(image),
-         {:ok, _written} <- Image.write(resized, abs, strip_metadata: true) do
+    with :ok <- File.mkdir_p(Path.dirname(abs)),
+         {:ok, %{data: webp}} <- reencode(body),
+         :ok <- File.write(abs, webp) do
       {:ok, serving_path}
     end
-  rescue
-    e ->
-      Logger.warning("link_preview.image_reencode_failed: reason=#{Exception.message(e)}")
-      {:error, :reencode_failed}
   end
 
-  defp resize_to_fit(image) do
-    {width, height, _} = Image.shape(image)
-
-    cond do
-      width <= @max_width and height <= @max_height ->
-        {:ok, image}
+  defp reencode(body) do
+    case Thumbnail.resize_webp(body, @max_width, @max_height) do
+      {:ok, encoded} ->
+        {:ok, encoded}
 
-      width / height > @max_width / @max_height ->
-        {:ok, Image.thumbnail!(image, @max_width)}
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("ImageProxy.reencode/1", message)
+        {:error, :reencode_failed}
 
-      true ->
-        {:ok, Image.thumbnail!(image, "x#{@max_height}")}
+      {:error, reason} ->
+        Logger.warning("link_preview.image_reencode_failed: reason=#{inspect(reason)}")
+        {:error, :reencode_failed}
     end
   end
 
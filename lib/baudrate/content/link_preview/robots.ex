@@ -0,0 +1,149 @@
+defmodule Baudrate.Content.LinkPreview.Robots do
+  @moduledoc """
+  robots.txt checks for link preview fetches.
+
+  Each origin's `/robots.txt` is fetched once and kept in ETS for 24 hours,
+  the longest RFC 9309 lets a crawler cache it. Pages are checked against it
+  with `Baudrate.HtmlParser.Native.robots_allowed?/3` for the `Baudrate`
+  user agent, and a `Crawl-delay` it declares is kept between two fetches
+  from the origin.
+
+  Following RFC 9309, a robots.txt that is missing (any 4xx response)
+  allows everything, while one that cannot be fetched otherwise allows
+  nothing until it can; that result is not cached.
+
+  A sweeper runs every 10 minutes to remove expired entries.
+  """
+
+  use GenServer
+
+  require Logger
+
+  alias Baudrate.Federation.HTTPClient
+  alias Baudrate.HtmlParser.Native, as: HtmlParser
+
+  @table :link_preview_robots
+  @user_agent "Baudrate"
+  @ttl_seconds 24 * 60 * 60
+  @sweep_interval_ms 10 * 60 * 1000
+
+  # --- Public API ---
+
+  def start_link(opts \\ []) do
+    GenServer.start_link(__MODULE__, opts, name: __MODULE__)
+  end
+
+  @doc """
+  Checks whether `url` may be fetched, fetching its origin's robots.txt if
+  it is not cached.
+
+  Returns `:ok`, or `{:error, reason}` with `:robots_disallowed`,
+  `:crawl_delay` (the origin was fetched less than its `Crawl-delay` ago),
+  or `:robots_unreachable`. Each `:ok` counts as a fetch for the delay.
+  """
+  @spec check(String.t()) :: :ok | {:error, atom()}
+  def check(url) do
+    uri = URI.parse(url)
+    origin = %URI{scheme: uri.scheme, host: uri.host, port: uri.port}
+    robots_url = URI.to_string(%URI{origin | path: "/robots.txt"})
+    path = (uri.path || "/") <> if(uri.query, do: "?#{uri.query}", else: "")
+
+    with {:ok, robots_txt, fetched_at} <- lookup(robots_url),
+         :ok <- check_path(robots_txt, path),
+         :ok <- check_delay(robots_txt, fetched_at) do
+      :ets.update_element(@table, robots_url, {4, System.monotonic_time(:millisecond)})
+      :ok
+    end
+  end
+
+  # --- GenServer callbacks ---
+
+  @impl true
+  def init(_opts) do
+    table = :ets.new(@table, [:named_table, :set, :public, read_concurrency: true])
+    schedule_sweep()
+    {:ok, %{table: table}}
+  end
+
+  @impl true
+  def handle_info(:sweep, state) do
+    now = System.system_time(:second)
+
+    # Erlang match spec uses :"=<" for the less-than-or-equal guard operator
+    :ets.select_delete(@table, [
+      {{:_, :_, :"$1", :_}, [{:"=<", :"$1", now}], [true]}
+    ])
+
+    schedule_sweep()
+    {:noreply, state}
+  end
+
+  # --- Private ---
+
+  defp lookup(robots_url) do
+    now = System.system_time(:second)
+
+    case :ets.lookup(@table, robots_url) do
+      [{^robots_url, robots_txt, expires_at, fetched_at}] when expires_at > now ->
+        {:ok, robots_txt, fetched_at}
+
+      _ ->
+        with {:ok, robots_txt} <- fetch(robots_url) do
+          :ets.insert(@table, {robots_url, robots_txt, now + @ttl_seconds, nil})
+          {:ok, robots_txt, nil}
+        end
+    end
+  end
+
+  defp fetch(robots_url) do
+    case HTTPClient.get_html(robots_url) do
+      {:ok, %{body: body}} when is_binary(body) ->
+        {:ok, body}
+
+      {:error, {:http_error, status, _body}} when status in 400..499 ->
+        {:ok, ""}
+
+      {:error, reason} ->
+        Logger.info(
+          "link_preview.robots_unreachable: url=#{robots_url} reason=#{inspect(reason)}"
+        )
+
+        {:error, :robots_unreachable}
+    end
+  end
+
+  defp check_path(robots_txt, path) do
+    case HtmlParser.robots_allowed?(robots_txt, @user_agent, path) do
+      true ->
+        :ok
+
+      false ->
+        {:error, :robots_disallowed}
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("robots_allowed?/3", message)
+        :ok
+    end
+  end
+
+  defp check_delay(_robots_txt, nil), do: :ok
+
+  defp check_delay(robots_txt, fetched_at) do
+    case HtmlParser.robots_crawl_delay(robots_txt, @user_agent) do
+      delay when is_float(delay) ->
+        elapsed = System.monotonic_time(:millisecond) - fetched_at
+        if elapsed < delay * 1000, do: {:error, :crawl_delay}, else: :ok
+
+      nil ->
+        :ok
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("robots_crawl_delay/2", message)
+        :ok
+    end
+  end
+
+  defp schedule_sweep do
+    Process.send_after(self(), :sweep, @sweep_interval_ms)
+  end
+end
@@ -18,8 +18,15 @@ defmodule Baudrate.Content.LinkPreview.UrlExtractor do
     origin = BaudrateWeb.Endpoint.url()
 
     case HtmlParser.extract_first_url(html, origin) do
-      url when is_binary(url) -> {:ok, url}
-      nil -> :none
+      url when is_binary(url) ->
+        {:ok, url}
+
+      nil ->
+        :none
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("extract_first_url/2", message)
+        :none
     end
   end
 
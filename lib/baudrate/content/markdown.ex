@@ -4,14 +4,19 @@ defmodule Baudrate.Content.Markdown do
 
   The rendering pipeline is:
 
-  1. **Block normalization** — inserts blank lines between consecutive HTML
+  1. **Code fence annotation** — writes fenced code blocks with an info
+     string (```` ```rust title=main.rs {1,3-5} ````) as `<pre>` blocks
+     carrying `data-filename`, `data-highlight-lines`, and a `language-*`
+     class, which Earmark would otherwise drop; `diff` lines are wrapped in
+     `diff-ins` / `diff-del` spans
+  2. **Block normalization** — inserts blank lines between consecutive HTML
      block elements so Earmark treats each as a separate HTML block. Required
      for bot articles whose bodies are stored as sanitized HTML rather than
      Markdown (e.g. RSS feed content where paragraphs abut with no blank lines).
-  2. **Earmark** — Markdown → raw HTML
-  3. **Ammonia sanitizer** — strips unsafe HTML tags/attributes
-  4. **Hashtag linkification** — converts `#tag` to clickable links
-  5. **Mention linkification** — converts `@username` to clickable profile links
+  3. **Earmark** — Markdown → raw HTML
+  4. **Ammonia sanitizer** — strips unsafe HTML tags/attributes
+  5. **Hashtag linkification** — converts `#tag` to clickable links
+  6. **Mention linkification** — converts `@username` to clickable profile links
 
   The sanitizer only allows `href` on `<a>` tags. By running linkification
   *after* sanitization, the injected `<a>` tags are never stripped. Tag names
@@ -41,6 +46,7 @@ defmodule Baudrate.Content.Markdown do
 
   def to_html(text) when is_binary(text) do
     text
+    |> annotate_code_fences()
     |> normalize_html_blocks()
     |> Earmark.as_html!()
     |> sanitize_html()
@@ -48,8 +54,28 @@ defmodule Baudrate.Content.Markdown do
     |> linkify_mentions()
   end
 
+  # Fences are only annotated for highlighting, so the Markdown is rendered
+  # as written if that panics.
+  defp annotate_code_fences(text) do
+    case Baudrate.Text.Native.annotate_code_fences(text) do
+      annotated when is_binary(annotated) ->
+        annotated
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("annotate_code_fences/1", message)
+        text
+    end
+  end
+
   defp sanitize_html(html) do
-    Baudrate.Sanitizer.Native.sanitize_markdown(html)
+    case Baudrate.Sanitizer.Native.sanitize_markdown(html) do
+      sanitized when is_binary(sanitized) ->
+        sanitized
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("sanitize_markdown/1", message)
+        ""
+    end
   end
 
   # Inserts blank lines between consecutive block-level HTML elements so that
@@ -79,8 +105,8 @@ defmodule Baudrate.Content.Markdown do
       else
         Regex.replace(@hashtag_re, part, fn full, tag ->
           prefix = String.slice(full, 0, String.length(full) - String.length(tag) - 1)
-          downcased = String.downcase(tag)
-          ~s[#{prefix}<a href="/tags/#{downcased}" class="hashtag">##{tag}</a>]
+          canonical = Baudrate.Content.Tags.normalize_tag(tag) || String.downcase(tag)
+          ~s[#{prefix}<a href="/tags/#{canonical}" class="hashtag">##{tag}</a>]
         end)
       end
     end)
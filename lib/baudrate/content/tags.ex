@@ -19,13 +19,14 @@ defmodule Baudrate.Content.Tags do
   }
 
   @per_page 20
+  @max_tag_length 64
   @hashtag_re ~r/(?:^|(?<=\s|[^\w&]))#(\p{L}[\w]{0,63})/u
 
   @doc """
   Extracts hashtag strings from text.
 
   Strips code blocks and inline code before scanning. Returns a list of
-  unique lowercase tag strings.
+  unique tags in their canonical form (see `normalize_tag/1`).
 
   ## Examples
 
@@ -36,10 +37,23 @@ defmodule Baudrate.Content.Tags do
       []
   """
   @spec extract_tags(String.t() | nil) :: [String.t()]
-  def extract_tags(nil), do: []
-  def extract_tags(""), do: []
+  def extract_tags(text), do: text |> extract_hashtags() |> Enum.map(&elem(&1, 0))
 
-  def extract_tags(text) when is_binary(text) do
+  @doc """
+  Extracts hashtags from text as `{canonical, display}` pairs (see
+  `normalize_hashtag/1`), unique by canonical form; the first spelling of
+  each tag is kept for display.
+
+  ## Examples
+
+      iex> Baudrate.Content.extract_hashtags("#Café and #cafe")
+      [{"cafe", "Café"}]
+  """
+  @spec extract_hashtags(String.t() | nil) :: [{String.t(), String.t()}]
+  def extract_hashtags(nil), do: []
+  def extract_hashtags(""), do: []
+
+  def extract_hashtags(text) when is_binary(text) do
     cleaned =
       text
       |> String.replace(~r/```[\s\S]*?```/u, "")
@@ -47,8 +61,59 @@ defmodule Baudrate.Content.Tags do
 
     Regex.scan(@hashtag_re, cleaned, capture: :all_but_first)
     |> List.flatten()
-    |> Enum.map(&String.downcase/1)
-    |> Enum.uniq()
+    |> Enum.map(&normalize_hashtag/1)
+    |> Enum.reject(&is_nil/1)
+    |> Enum.uniq_by(&elem(&1, 0))
+  end
+
+  @doc """
+  Returns the canonical form of a hashtag, which tags are stored and matched
+  under, or `nil` if it has none of at most #{@max_tag_length} characters.
+
+  This is the form Mastodon uses, from
+  `Baudrate.Text.Native.normalize_hashtag/1`: `#CaféCon` and `#cafecon` are
+  the same tag.
+
+  ## Examples
+
+      iex> Baudrate.Content.normalize_tag("#CaféCon")
+      "cafecon"
+
+      iex> Baudrate.Content.normalize_tag("#123")
+      nil
+  """
+  @spec normalize_tag(String.t()) :: String.t() | nil
+  def normalize_tag(tag) when is_binary(tag) do
+    case normalize_hashtag(tag) do
+      {canonical, _display} -> canonical
+      nil -> nil
+    end
+  end
+
+  @doc """
+  Returns `{canonical, display}` for a hashtag: its canonical form, as
+  `normalize_tag/1` returns it, and the tag as written, without the `#`,
+  for pages and federated `Hashtag` names. `nil` when `normalize_tag/1`
+  would return `nil`.
+
+  ## Examples
+
+      iex> Baudrate.Content.normalize_hashtag("#CaféCon")
+      {"cafecon", "CaféCon"}
+  """
+  @spec normalize_hashtag(String.t()) :: {String.t(), String.t()} | nil
+  def normalize_hashtag(tag) when is_binary(tag) do
+    case Baudrate.Text.Native.normalize_hashtag(tag) do
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("normalize_hashtag/1", message)
+        nil
+
+      {canonical, _display} = pair ->
+        if String.length(canonical) <= @max_tag_length, do: pair
+
+      nil ->
+        nil
+    end
   end
 
   @doc """
@@ -139,7 +204,8 @@ defmodule Baudrate.Content.Tags do
   @spec search_tags(String.t(), keyword()) :: [String.t()]
   def search_tags(prefix, opts \\ []) do
     limit = Keyword.get(opts, :limit, 10)
-    pattern = Filters.sanitize_like(String.downcase(prefix)) <> "%"
+    prefix = normalize_tag(prefix) || String.downcase(prefix)
+    pattern = Filters.sanitize_like(prefix) <> "%"
 
     from(at in ArticleTag,
       where: like(at.tag, ^pattern),
@@ -7,14 +7,18 @@ defmodule Baudrate.Content.TitleDeriver do
   field.
   """
 
+  alias Baudrate.Sanitizer.Native
+
   @doc """
   Derives a title from an AP object map and body text.
 
   Article/Page objects use the `"name"` field. For Notes or objects
-  without a name, extracts the first line of the body text and truncates
-  it to 80 graphemes.
+  without a name, the body's first heading is used, or else the first
+  sentence of its first paragraph (or first line, for plain text),
+  truncated to 80 graphemes. See `Baudrate.Sanitizer.Native.derive_title/1`.
 
-  Returns `"Untitled"` when both the name and body are empty.
+  Returns `"Untitled"` when both the name and body are empty, or when
+  deriving the title panics.
   """
   @spec derive_title(map(), String.t() | nil) :: String.t()
   def derive_title(%{"name" => name}, _body) when is_binary(name) and name != "" do
@@ -22,14 +26,16 @@ defmodule Baudrate.Content.TitleDeriver do
   end
 
   def derive_title(_object, body) when is_binary(body) and body != "" do
-    body
-    |> String.split(~r/\n/, parts: 2)
-    |> hd()
-    |> String.trim()
-    |> truncate_title(80)
-    |> case do
-      "" -> "Untitled"
-      title -> title
+    case Native.derive_title(body) do
+      title when is_binary(title) ->
+        title
+
+      nil ->
+        "Untitled"
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("derive_title/1", message)
+        "Untitled"
     end
   end
 
@@ -38,7 +44,7 @@ defmodule Baudrate.Content.TitleDeriver do
   @doc """
   Derives a title from a plain body string (no AP object context).
 
-  Extracts the first line and truncates to 80 graphemes.
+  Uses the first heading, sentence, or line, truncated to 80 graphemes.
   """
   @spec derive_title_from_body(String.t() | nil) :: String.t()
   def derive_title_from_body(body), do: derive_title(%{}, body)
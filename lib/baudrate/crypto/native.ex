@@ -0,0 +1,292 @@
+defmodule Baudrate.Crypto.Native do
+  @moduledoc """
+  Rustler NIF bindings to the `baudrate_crypto` Rust crate.
+
+  Provides federation cryptography backed by the RustCrypto `rsa` and
+  `ed25519-dalek` crates:
+
+    * `sign_request/5` — build an HTTP Signature (draft-cavage-12) header value
+    * `verify_signature/5` — verify an inbound HTTP Signature
+    * `digest_sha256/1` / `verify_digest/2` — RFC 3230 `Digest` header values
+    * `generate_rsa_keypair/1` / `generate_ed25519_keypair/0` — actor keypairs
+    * `sign_object/3` / `verify_object_proof/2` — FEP-8b32 object integrity
+      proofs (`eddsa-jcs-2022`)
+    * `sign_proxy_url/2` / `verify_proxy_url/3` — HMAC-signed media proxy URLs
+    * `generate_id/1` / `id_timestamp/1` — k-sortable snowflake IDs
+    * `password_strength/2` — zxcvbn password strength estimate with feedback
+    * `pow_challenge/1` / `pow_verify/3` — hashcash-style proof of work for
+      anonymous forms such as registration
+    * `canonicalize_jsonld/2` — JSON-LD → canonical N-Quads (URDNA2015 /
+      RDFC-1.0), the input to Linked Data Signatures
+
+  Private keys are accepted as PEM: RSA in PKCS#1 (as written by
+  `Baudrate.Federation.KeyStore`) or PKCS#8, Ed25519 in PKCS#8. Errors are
+  returned as `{:error, atom}` tuples (`{:error, {atom, message}}` for
+  JSON-LD processing, where the message names the offending term or context).
+
+  `native_info/0` reports what the loaded library supports; see
+  `Baudrate.Native`.
+
+  A panic inside a NIF is returned as `{:error, {:panic, message}}` (the
+  `t:panic/0` type) instead of being raised.
+  """
+
+  use RustlerPrecompiled, Baudrate.Native.precompiled(crate: "baudrate_crypto")
+
+  @type panic :: {:error, {:panic, String.t()}}
+
+  @type password_strength :: %{
+          score: 0..4,
+          guesses: non_neg_integer(),
+          guesses_log10: float(),
+          crack_time: String.t(),
+          warning: String.t() | nil,
+          suggestions: [String.t()]
+        }
+
+  @doc """
+  Sign an outgoing request and return the `Signature` header value.
+
+  `headers` is an ordered list of `{name, value}` pairs to cover in addition
+  to the implicit `(request-target)` (built from `method` and `path`, which
+  must include any query string). The `(created)` and `(expires)`
+  pseudo-headers may be included with Unix timestamp values; they are signed
+  in place and also emitted as the `created` / `expires` parameters.
+
+  The algorithm label is always `hs2019`; the actual algorithm is derived
+  from the key (RSASSA-PKCS1-v1_5 SHA-256 or Ed25519).
+
+  Returns `{:ok, header}`, `{:error, :invalid_key_id}` (a `key_id` with a
+  `"`, `\\`, or control character, which cannot be quoted as it is),
+  `{:error, :invalid_private_key}`, or `{:error, :invalid_pseudo_header}`
+  (non-integer `(created)` / `(expires)`).
+  """
+  @spec sign_request(String.t(), String.t(), String.t(), String.t(), [{String.t(), String.t()}]) ::
+          {:ok, String.t()} | {:error, atom()} | panic()
+  def sign_request(_key_pem, _key_id, _method, _path, _headers),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Verify an inbound request's `Signature` header against `public_key_pem`.
+
+  `headers` is the list of request `{name, value}` pairs; `method` and `path`
+  (including any query string) rebuild `(request-target)`. Accepts the
+  `hs2019`, `rsa-sha256`, and `ed25519` algorithms. When the `headers`
+  parameter is absent only `date` is covered, matching
+  `Baudrate.Federation.HTTPSignature`. Checking the `Date` / `Digest` values
+  themselves remains the caller's responsibility.
+
+  Returns `:ok` or `{:error, reason}` where `reason` is one of:
+
+    * `:malformed_signature` — unparsable header, or `keyId` / `signature` missing
+    * `:unsupported_algorithm` — unknown `algorithm` parameter
+    * `:invalid_public_key` — `public_key_pem` could not be decoded
+    * `:key_mismatch` — `algorithm` does not match the key type (e.g. `ed25519`
+      with an RSA key); usually means a stale cached key
+    * `:expired` — `expires` parameter is in the past
+    * `:not_yet_valid` — `created` parameter is more than 300s in the future
+    * `:missing_header` — a covered header is absent from `headers`
+    * `:invalid_signature_encoding` — `signature` is not valid Base64
+    * `:invalid_signature` — the signature does not verify; if the key was
+      cached, refetching the actor and retrying may succeed
+  """
+  @spec verify_signature(
+          String.t(),
+          String.t(),
+          String.t(),
+          [{String.t(), String.t()}],
+          String.t()
+        ) :: :ok | {:error, atom()} | panic()
+  def verify_signature(_signature_header, _method, _path, _headers, _public_key_pem),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Generate an RSA keypair of `bits` size (2048–4096, multiple of 8).
+
+  Returns `{:ok, {public_pem, private_pem}}` using the same encodings as
+  `Baudrate.Federation.KeyStore.generate_keypair/0` (SubjectPublicKeyInfo
+  public key, PKCS#1 private key), or `{:error, :invalid_key_size}`.
+  Runs on a dirty CPU scheduler.
+  """
+  @spec generate_rsa_keypair(pos_integer()) ::
+          {:ok, {String.t(), String.t()}} | {:error, atom()} | panic()
+  def generate_rsa_keypair(_bits), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Generate an Ed25519 keypair.
+
+  Returns `{:ok, {public_pem, private_pem, public_multibase}}` — PEM-encoded
+  SubjectPublicKeyInfo / PKCS#8 keys plus the Multikey `publicKeyMultibase`
+  form (`z6Mk…`) used by FEP-521a actor keys and data integrity proofs.
+  """
+  @spec generate_ed25519_keypair() ::
+          {:ok, {String.t(), String.t(), String.t()}} | {:error, atom()} | panic()
+  def generate_ed25519_keypair, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Add an FEP-8b32 `eddsa-jcs-2022` Data Integrity proof to a JSON object.
+
+  `json` is the encoded object (any existing `proof` is replaced),
+  `key_pem` an Ed25519 PKCS#8 private key, and `verification_method` the
+  key's URI (e.g. `"https://host/users/alice#ed25519-key"` or a `did:key`).
+
+  Returns `{:ok, secured_json}`, `{:error, :invalid_json}` (not a JSON
+  object), or `{:error, :invalid_private_key}` (unreadable or non-Ed25519 key).
+  """
+  @spec sign_object(String.t(), String.t(), String.t()) ::
+          {:ok, String.t()} | {:error, atom()} | panic()
+  def sign_object(_json, _key_pem, _verification_method), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Verify the `eddsa-jcs-2022` proof on an encoded JSON object.
+
+  `public_key` is the signer's Ed25519 key as a Multikey multibase string
+  (`z6Mk…`) or PEM. When `nil`, the proof's `verificationMethod` must be a
+  self-describing `did:key`; otherwise resolve the method (usually an actor's
+  `assertionMethod` entry) and pass its key.
+
+  Returns `:ok` or `{:error, reason}` where `reason` is one of `:invalid_json`,
+  `:missing_proof`, `:unsupported_cryptosuite`, `:invalid_proof` (malformed
+  proof or `@context` mismatch), `:invalid_public_key`, or `:invalid_signature`.
+  """
+  @spec verify_object_proof(String.t(), String.t() | nil) :: :ok | {:error, atom()} | panic()
+  def verify_object_proof(json, public_key \\ nil)
+  def verify_object_proof(_json, _public_key), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Canonicalize a JSON-LD document to N-Quads using URDNA2015 (RDFC-1.0).
+
+  Remote contexts are never fetched: every `@context` URL the document (or
+  a nested context) references must be present in `context_cache`, a map of
+  context URL to the context document's JSON text. Blank nodes are relabelled
+  `_:c14n0`, `_:c14n1`, … and quads are sorted, so the output can be hashed
+  directly for Linked Data Signature verification.
+
+  Returns `{:ok, nquads}`, `{:error, {:invalid_jsonld, message}}` (malformed
+  JSON, invalid JSON-LD, or a context missing from the cache), or
+  `{:error, {:canonicalization_failed, message}}`.
+  """
+  @spec canonicalize_jsonld(String.t(), %{String.t() => String.t()}) ::
+          {:ok, String.t()} | {:error, {atom(), String.t()}} | panic()
+  def canonicalize_jsonld(_json, _context_cache), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the `Digest` header value for a request body, e.g.
+  `"SHA-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="`.
+  """
+  @spec digest_sha256(binary()) :: String.t() | panic()
+  def digest_sha256(_body), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Check a `Digest` header value against a request body.
+
+  The header may list several comma-separated instances
+  (`SHA-256=…,SHA-512=…`); the `SHA-256` one is compared in constant time.
+
+  Returns `:ok`, `{:error, :digest_mismatch}`, `{:error, :unsupported_algorithm}`
+  (no `SHA-256` instance), or `{:error, :malformed_digest}`.
+  """
+  @spec verify_digest(String.t(), binary()) :: :ok | {:error, atom()} | panic()
+  def verify_digest(_header, _body), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sign a remote media URL for the media proxy.
+
+  Returns the HMAC-SHA256 of `url` keyed by `secret`, encoded as unpadded
+  URL-safe base64 (43 characters) so it can be used directly as a path
+  segment, e.g. `/proxy/<signature>/<base64url(url)>`.
+  """
+  @spec sign_proxy_url(binary(), String.t()) :: String.t() | panic()
+  def sign_proxy_url(_secret, _url), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Check a media proxy signature produced by `sign_proxy_url/2`.
+
+  Comparison is constant-time; malformed signatures return `false`.
+  """
+  @spec verify_proxy_url(binary(), String.t(), String.t()) :: boolean() | panic()
+  def verify_proxy_url(_secret, _signature, _url), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Generate a k-sortable 63-bit snowflake ID.
+
+  The ID packs 41 bits of milliseconds since 2024-01-01 UTC, the 10-bit
+  `node_id` (0–1023), and a 12-bit per-millisecond sequence, so IDs sort by
+  creation time across nodes and fit a Postgres `bigint`. Each node must use
+  a distinct `node_id`; IDs from one node are strictly increasing.
+
+  If the system clock steps back by up to 5 seconds (or more than 4096 IDs
+  are requested in a millisecond), generation continues from the last
+  timestamp used. A larger step returns `{:error, :clock_skew}` until the
+  clock catches up.
+
+  Returns `{:ok, id}`, `{:error, :invalid_node_id}`, or
+  `{:error, :clock_skew}`.
+  """
+  @spec generate_id(0..1023) :: {:ok, pos_integer()} | {:error, atom()} | panic()
+  def generate_id(_node_id), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the creation time of a `generate_id/1` ID as Unix milliseconds,
+  e.g. for `DateTime.from_unix!(ms, :millisecond)`.
+
+  Returns `{:ok, unix_ms}` or `{:error, :invalid_id}` (zero or negative).
+  """
+  @spec id_timestamp(integer()) :: {:ok, pos_integer()} | {:error, atom()} | panic()
+  def id_timestamp(_id), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Estimate the strength of a password with zxcvbn.
+
+  `user_inputs` are words an attacker would try first — the username,
+  display name, instance name — so passwords built from them score lower.
+  Returns a map with:
+
+    * `:score` — 0 (too guessable) to 4 (very unguessable); 3 or more is a
+      reasonable requirement for accounts
+    * `:guesses` / `:guesses_log10` — estimated guesses needed
+    * `:crack_time` — human-readable time to crack offline against a slow
+      hash, e.g. `"3 hours"`
+    * `:warning` — what is wrong with the password, or `nil`
+    * `:suggestions` — how to improve it (English sentences)
+
+  Only the first 100 characters are evaluated. Runs on a dirty CPU scheduler.
+  """
+  @spec password_strength(String.t(), [String.t()]) :: password_strength() | panic()
+  def password_strength(password, user_inputs \\ [])
+  def password_strength(_password, _user_inputs), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Issue a proof-of-work challenge requiring `difficulty` (1–32) leading zero
+  bits, e.g. `"20:q2n5Xb0m4eAVyG3tosQx1A"`.
+
+  The client must find a nonce such that `SHA-256(challenge <> ":" <> nonce)`
+  starts with `difficulty` zero bits; each extra bit doubles the expected
+  work. Challenges are not signed or timestamped: store the issued challenge
+  server-side (e.g. in the session) and discard it once verified, so that a
+  solution cannot be replayed or a self-made challenge submitted.
+
+  Returns `{:ok, challenge}` or `{:error, :invalid_difficulty}`.
+  """
+  @spec pow_challenge(pos_integer()) :: {:ok, String.t()} | {:error, atom()} | panic()
+  def pow_challenge(_difficulty), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Check a client's solution to a challenge from `pow_challenge/1`.
+
+  `difficulty` is the server's requirement, not the one written in the
+  challenge. `nonce` must be 1–64 printable ASCII characters.
+
+  Returns `:ok`, `{:error, :insufficient_work}`, `{:error, :malformed_nonce}`,
+  or `{:error, :invalid_difficulty}`.
+  """
+  @spec pow_verify(String.t(), String.t(), pos_integer()) :: :ok | {:error, atom()} | panic()
+  def pow_verify(_challenge, _nonce, _difficulty), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the crate version, enabled Cargo features, and the `{name, arity}`
+  of every NIF in the loaded library.
+  """
+  @spec native_info() :: Baudrate.Native.info() | panic()
+  def native_info, do: :erlang.nif_error(:nif_not_loaded)
+end
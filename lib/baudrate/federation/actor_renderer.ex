@@ -144,12 +144,16 @@ defmodule Baudrate.Federation.ActorRenderer do
         %{"name" => name, "value" => value} -> name != "" and value != ""
         _ -> false
       end)
-      |> Enum.map(fn %{"name" => name, "value" => value} ->
-        %{
-          "type" => "PropertyValue",
-          "name" => name,
-          "value" => Phoenix.HTML.html_escape(value) |> Phoenix.HTML.safe_to_string()
-        }
+      |> Enum.flat_map(fn %{"name" => name, "value" => value} ->
+        # URLs are linked with rel="me", so that remote servers can verify them.
+        case Baudrate.Sanitizer.Native.build_profile_field_html(name, value) do
+          %{name: name, value: value} ->
+            [%{"type" => "PropertyValue", "name" => name, "value" => value}]
+
+          {:error, {:panic, message}} ->
+            Baudrate.Native.log_panic("build_profile_field_html/2", message)
+            []
+        end
       end)
 
     if rendered == [], do: nil, else: rendered
@@ -224,13 +224,7 @@ defmodule Baudrate.Federation.ActorResolver do
 
   defp extract_profile_fields(_), do: []
 
-  defp sanitize_field_name(nil), do: nil
-
-  defp sanitize_field_name(name) when is_binary(name) do
-    name
-    |> Sanitizer.sanitize_display_name()
-    |> String.slice(0, 255)
-  end
+  defp sanitize_field_name(name), do: Sanitizer.sanitize_inline(name, 255)
 
   defp extract_username_from_id(ap_id) do
     ap_id |> URI.parse() |> Map.get(:path, "") |> String.split("/") |> List.last() || "unknown"
@@ -15,7 +15,7 @@ defmodule Baudrate.Federation.HTTPSignature do
 
   **Signing** (outgoing requests):
     1. Build signing string from required headers
-    2. Sign with local actor's RSA private key
+    2. Sign with local actor's RSA private key (`Baudrate.Crypto.Native.sign_request/5`)
     3. Use `hs2019` algorithm label in Signature header
     4. Return `signature`, `date`, `digest` headers (NOT `host` —
        managed by `HTTPClient` for DNS-pinned connections)
@@ -23,6 +23,7 @@ defmodule Baudrate.Federation.HTTPSignature do
 
   require Logger
 
+  alias Baudrate.Crypto.Native, as: Crypto
   alias Baudrate.Federation.ActorResolver
 
   @required_signed_headers ["(request-target)", "host", "date", "digest"]
@@ -102,16 +103,14 @@ defmodule Baudrate.Federation.HTTPSignature do
     raw_body = conn.assigns[:raw_body] || ""
 
     case Plug.Conn.get_req_header(conn, "digest") do
-      ["SHA-256=" <> digest_b64 | _] ->
-        computed = :crypto.hash(:sha256, raw_body) |> Base.encode64()
-
-        if Plug.Crypto.secure_compare(computed, digest_b64) do
-          :ok
-        else
-          {:error, :digest_mismatch}
+      [header | _] ->
+        case Crypto.verify_digest(header, raw_body) do
+          :ok -> :ok
+          {:error, :digest_mismatch} = err -> err
+          {:error, _} -> {:error, :missing_digest}
         end
 
-      _ ->
+      [] ->
         {:error, :missing_digest}
     end
   end
@@ -160,27 +159,19 @@ defmodule Baudrate.Federation.HTTPSignature do
   def sign(method, url, body, private_key_pem, key_id) do
     uri = URI.parse(url)
     now = format_http_date(DateTime.utc_now())
-    digest = "SHA-256=" <> (body |> then(&:crypto.hash(:sha256, &1)) |> Base.encode64())
     host = uri.host
 
-    path = uri.path || "/"
-    query = if uri.query, do: "?#{uri.query}", else: ""
-    target = "#{String.downcase(to_string(method))} #{path}#{query}"
-
-    signing_string =
-      [
-        "(request-target): #{target}",
-        "host: #{host}",
-        "date: #{now}",
-        "digest: #{digest}"
-      ]
-      |> Enum.join("\n")
+    digest =
+      case Crypto.digest_sha256(body) do
+        digest when is_binary(digest) ->
+          digest
 
-    {:ok, private_key} = decode_private_key(private_key_pem)
-    signature = :public_key.sign(signing_string, :sha256, private_key) |> Base.encode64()
+        {:error, {:panic, message}} ->
+          raise Baudrate.Native.PanicError, call: "digest_sha256/1", message: message
+      end
 
-    sig_header =
-      ~s[keyId="#{key_id}",algorithm="hs2019",headers="(request-target) host date digest",signature="#{signature}"]
+    headers = [{"host", host}, {"date", now}, {"digest", digest}]
+    sig_header = signature_header(private_key_pem, key_id, method, uri, headers)
 
     %{
       "signature" => sig_header,
@@ -202,23 +193,8 @@ defmodule Baudrate.Federation.HTTPSignature do
     now = format_http_date(DateTime.utc_now())
     host = uri.host
 
-    path = uri.path || "/"
-    query = if uri.query, do: "?#{uri.query}", else: ""
-    target = "get #{path}#{query}"
-
-    signing_string =
-      [
-        "(request-target): #{target}",
-        "host: #{host}",
-        "date: #{now}"
-      ]
-      |> Enum.join("\n")
-
-    {:ok, private_key} = decode_private_key(private_key_pem)
-    signature = :public_key.sign(signing_string, :sha256, private_key) |> Base.encode64()
-
-    sig_header =
-      ~s[keyId="#{key_id}",algorithm="hs2019",headers="(request-target) host date",signature="#{signature}"]
+    headers = [{"host", host}, {"date", now}]
+    sig_header = signature_header(private_key_pem, key_id, :get, uri, headers)
 
     %{
       "signature" => sig_header,
@@ -306,11 +282,23 @@ defmodule Baudrate.Federation.HTTPSignature do
     _ -> {:error, :invalid_public_key}
   end
 
-  defp decode_private_key(pem) do
-    [entry | _] = :public_key.pem_decode(pem)
-    {:ok, :public_key.pem_entry_decode(entry)}
-  rescue
-    _ -> {:error, :invalid_private_key}
+  # The `Signature` header covering `(request-target)` and `headers`, in
+  # that order.
+  defp signature_header(private_key_pem, key_id, method, uri, headers) do
+    path = uri.path || "/"
+    query = if uri.query, do: "?#{uri.query}", else: ""
+    method = to_string(method)
+
+    case Crypto.sign_request(private_key_pem, key_id, method, path <> query, headers) do
+      {:ok, header} ->
+        header
+
+      {:error, {:panic, message}} ->
+        raise Baudrate.Native.PanicError, call: "sign_request/5", message: message
+
+      {:error, reason} ->
+        raise ArgumentError, "cannot sign the request: #{inspect(reason)}"
+    end
   end
 
   defp signature_max_age do
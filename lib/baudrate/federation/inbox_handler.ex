@@ -1097,8 +1097,7 @@ defmodule Baudrate.Federation.InboxHandler do
 
     case Validator.validate_content_size(raw_content) do
       :ok ->
-        body_html = Sanitizer.sanitize(raw_content)
-        body = strip_html(raw_content)
+        {body_html, body} = Sanitizer.sanitize_with_text(raw_content)
         {:ok, body, body_html}
 
       error ->
@@ -1134,30 +1133,6 @@ defmodule Baudrate.Federation.InboxHandler do
   defp derive_title(object, body),
     do: Baudrate.Content.TitleDeriver.derive_title(object, body)
 
-  defp strip_html(html) when is_binary(html) do
-    html
-    |> String.replace(~r/<br\s*\/?>/, "\n")
-    |> String.replace(~r/<\/p>\s*<p[^>]*>/, "\n\n")
-    |> Baudrate.Sanitizer.Native.strip_tags()
-    |> decode_html_entities()
-    |> String.trim()
-  end
-
-  defp strip_html(_), do: ""
-
-  defp decode_html_entities(text) do
-    text
-    |> String.replace("&amp;", "&")
-    |> String.replace("&lt;", "<")
-    |> String.replace("&gt;", ">")
-    |> String.replace("&quot;", "\"")
-    |> String.replace("&nbsp;", " ")
-    |> String.replace("&#160;", " ")
-    |> String.replace("&#39;", "'")
-    |> String.replace("&#x27;", "'")
-    |> String.replace("&apos;", "'")
-  end
-
   # --- Reply/target resolution helpers ---
 
   defp resolve_reply_target(%{"inReplyTo" => in_reply_to}) when is_binary(in_reply_to) do
@@ -1806,7 +1781,7 @@ defmodule Baudrate.Federation.InboxHandler do
           |> Enum.filter(fn att -> https_url?(att["url"]) end)
           |> Enum.map_join("", fn att ->
             url = att["url"]
-            alt = Baudrate.Sanitizer.Native.strip_tags(att["name"] || "")
+            alt = Baudrate.Sanitizer.Native.html_to_text(att["name"] || "")
 
             ~s(<p><img src="#{escape_attr(url)}" alt="#{escape_attr(alt)}" loading="lazy" /></p>)
           end)
@@ -1821,14 +1796,27 @@ defmodule Baudrate.Federation.InboxHandler do
   defp escape_attr(value) when is_binary(value) do
     value
     |> String.replace(~r/[\x00-\x08\x0B\x0C\x0E-\x1F\x7F]/, "")
-    |> String.replace("&", "&amp;")
-    |> String.replace("\"", "&quot;")
-    |> String.replace("<", "&lt;")
-    |> String.replace(">", "&gt;")
+    |> Baudrate.Sanitizer.Native.escape_html()
+    |> case do
+      escaped when is_binary(escaped) ->
+        escaped
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("escape_html/1", message)
+        ""
+    end
   end
 
   defp escape_attr(_), do: ""
 
+  # Poll options are plain text; remote ones may carry markup or entities.
+  defp poll_option_text(name, idx) do
+    case Sanitizer.sanitize_inline(name, 200) do
+      text when text in [nil, ""] -> "Option #{idx + 1}"
+      text -> text
+    end
+  end
+
   # Extracts poll data from a Question object or an Article with a Question attachment.
   defp extract_poll_from_object(object, ap_id) do
     poll_data = find_poll_in_object(object)
@@ -1843,7 +1831,7 @@ defmodule Baudrate.Federation.InboxHandler do
         |> Enum.with_index()
         |> Enum.map(fn {opt, idx} ->
           %{
-            text: opt["name"] || "Option #{idx + 1}",
+            text: poll_option_text(opt["name"], idx),
             position: idx,
             votes_count: get_in(opt, ["replies", "totalItems"]) || 0
           }
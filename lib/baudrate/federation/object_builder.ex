@@ -185,11 +185,11 @@ defmodule Baudrate.Federation.ObjectBuilder do
   defp extract_hashtags(nil), do: []
 
   defp extract_hashtags(body) do
-    Baudrate.Content.extract_tags(body)
-    |> Enum.map(fn tag ->
+    Baudrate.Content.extract_hashtags(body)
+    |> Enum.map(fn {tag, display} ->
       %{
         "type" => "Hashtag",
-        "name" => "##{tag}",
+        "name" => "##{display}",
         "href" => "#{base_url()}/tags/#{tag}"
       }
     end)
@@ -211,8 +211,7 @@ defmodule Baudrate.Federation.ObjectResolver do
 
     case Validator.validate_content_size(raw_content) do
       :ok ->
-        body_html = Sanitizer.sanitize(raw_content)
-        body = strip_html(raw_content)
+        {body_html, body} = Sanitizer.sanitize_with_text(raw_content)
         {:ok, body, body_html}
 
       error ->
@@ -243,30 +242,6 @@ defmodule Baudrate.Federation.ObjectResolver do
 
   defp prepend_content_warning(body, _object), do: body
 
-  defp strip_html(html) when is_binary(html) do
-    html
-    |> String.replace(~r/<br\s*\/?>/, "\n")
-    |> String.replace(~r/<\/p>\s*<p[^>]*>/, "\n\n")
-    |> Baudrate.Sanitizer.Native.strip_tags()
-    |> decode_html_entities()
-    |> String.trim()
-  end
-
-  defp strip_html(_), do: ""
-
-  defp decode_html_entities(text) do
-    text
-    |> String.replace("&amp;", "&")
-    |> String.replace("&lt;", "<")
-    |> String.replace("&gt;", ">")
-    |> String.replace("&quot;", "\"")
-    |> String.replace("&nbsp;", " ")
-    |> String.replace("&#160;", " ")
-    |> String.replace("&#39;", "'")
-    |> String.replace("&#x27;", "'")
-    |> String.replace("&apos;", "'")
-  end
-
   defp extract_source_url(object) do
     case object do
       %{"url" => url} when is_binary(url) -> url
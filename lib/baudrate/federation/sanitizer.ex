@@ -11,13 +11,19 @@ defmodule Baudrate.Federation.Sanitizer do
   `<span class="hashtag">`. These classes (plus `mention` and `invisible`)
   are preserved; all other class values are stripped.
 
-  `sanitize_display_name/1` uses `Baudrate.Sanitizer.Native.strip_tags/1`
+  `sanitize_display_name/1` uses `Baudrate.Sanitizer.Native.html_to_text/1`
   to strip HTML tags and a regex pass for control characters from remote
   actor display names to prevent XSS and homograph attacks.
 
   Applied **before database storage**, not at render time.
+
+  A NIF that panics on some input is logged, and the content is dropped: the
+  functions return `""` (or `nil`, `{"", ""}`, `{%{}, nil}`) rather than
+  unsanitized HTML or an error tuple.
   """
 
+  alias Baudrate.Sanitizer.Native
+
   @doc """
   Sanitizes incoming HTML from remote instances.
 
@@ -29,7 +35,59 @@ defmodule Baudrate.Federation.Sanitizer do
   def sanitize(""), do: ""
 
   def sanitize(html) when is_binary(html) do
-    Baudrate.Sanitizer.Native.sanitize_federation(html)
+    case Native.sanitize_federation(html) do
+      sanitized when is_binary(sanitized) -> sanitized
+      {:error, {:panic, message}} -> panicked("sanitize_federation/1", message, "")
+    end
+  end
+
+  @doc """
+  Like `sanitize/1`, first normalizing the markup quirks of the software
+  that sent `html` (see `Baudrate.Sanitizer.Native.sanitize_federation/2`).
+  """
+  @spec sanitize(String.t() | nil, Native.source() | nil) :: String.t()
+  def sanitize(html, nil), do: sanitize(html)
+  def sanitize(nil, _source), do: ""
+  def sanitize("", _source), do: ""
+
+  def sanitize(html, source) when is_binary(html) and is_atom(source) do
+    case Native.sanitize_federation(html, %{source: source}) do
+      {:ok, sanitized} -> sanitized
+      {:error, {:panic, message}} -> panicked("sanitize_federation/2", message, "")
+    end
+  end
+
+  @doc """
+  Like `sanitize/1`, also returning the plain text of the sanitized HTML,
+  taken from the same call.
+  """
+  @spec sanitize_with_text(String.t() | nil) :: {String.t(), String.t()}
+  def sanitize_with_text(nil), do: {"", ""}
+  def sanitize_with_text(""), do: {"", ""}
+
+  def sanitize_with_text(html) when is_binary(html) do
+    case Native.sanitize_federation(html, %{plaintext: true}) do
+      {:ok, result} -> result
+      {:error, {:panic, message}} -> panicked("sanitize_federation/2", message, {"", ""})
+    end
+  end
+
+  @doc """
+  Sanitizes the HTML body of an RSS/Atom entry followed by a feed bot.
+
+  Feed bodies are stored as article bodies and rendered as Markdown, so they
+  keep the Markdown allowlist, images included, rather than the stricter
+  federation one; see `Baudrate.Sanitizer.Native.normalize_feed_html/1`.
+  """
+  @spec sanitize_feed(String.t() | nil) :: String.t()
+  def sanitize_feed(nil), do: ""
+  def sanitize_feed(""), do: ""
+
+  def sanitize_feed(html) when is_binary(html) do
+    case Native.normalize_feed_html(html) do
+      sanitized when is_binary(sanitized) -> sanitized
+      {:error, {:panic, message}} -> panicked("normalize_feed_html/1", message, "")
+    end
   end
 
   @doc """
@@ -42,13 +100,79 @@ defmodule Baudrate.Federation.Sanitizer do
 
   def sanitize_display_name(name) when is_binary(name) do
     name
-    |> Baudrate.Sanitizer.Native.strip_tags()
-    |> Baudrate.Sanitizer.Native.decode_html_entities()
+    |> Native.html_to_text()
     |> String.replace(~r/[\x00-\x08\x0B\x0C\x0E-\x1F\x7F]/, "")
     |> String.trim()
     |> truncate_display_name(100)
   end
 
+  @doc """
+  Sanitizes a short plain-text field — a poll option, content warning, or
+  profile field name — to one line of at most `max_graphemes` graphemes.
+  See `Baudrate.Sanitizer.Native.sanitize_inline/2`.
+  """
+  @spec sanitize_inline(String.t() | nil, non_neg_integer()) :: String.t() | nil
+  def sanitize_inline(nil, _max_graphemes), do: nil
+
+  def sanitize_inline(text, max_graphemes) when is_binary(text) do
+    case Native.sanitize_inline(text, max_graphemes) do
+      line when is_binary(line) -> line
+      {:error, {:panic, message}} -> panicked("sanitize_inline/2", message, "")
+    end
+  end
+
+  @doc """
+  Renders the MFM (`$[jelly ...]`, `$[x2 ...]`) functions in sanitized
+  content from Misskey-family software as classed spans (`:html`), or
+  reduces them to their text (`:strip`). Call it on the output of
+  `sanitize/1`. See `Baudrate.Sanitizer.Native.render_mfm/2`.
+  """
+  @spec render_mfm(String.t(), :html | :strip) :: String.t()
+  def render_mfm(html, mode \\ :html) when is_binary(html) and mode in [:html, :strip] do
+    case Native.render_mfm(html, mode) do
+      rendered when is_binary(rendered) -> rendered
+      # `html` is sanitized already, so it is kept with its MFM as written.
+      {:error, {:panic, message}} -> panicked("render_mfm/2", message, html)
+    end
+  end
+
+  @doc """
+  Finds the post quoted by received `html` and returns `{url, html}` with
+  the quote line removed, or `nil`. Call it before `sanitize/1`; see
+  `Baudrate.Sanitizer.Native.extract_quote/1`.
+  """
+  @spec extract_quote(String.t() | nil) :: {String.t(), String.t()} | nil
+  def extract_quote(nil), do: nil
+
+  def extract_quote(html) when is_binary(html) do
+    case Native.extract_quote(html) do
+      %{url: url, html: html} -> {url, html}
+      nil -> nil
+      {:error, {:panic, message}} -> panicked("extract_quote/1", message, nil)
+    end
+  end
+
+  @doc """
+  Sanitizes each language of an ActivityStreams `contentMap` and returns
+  `{content_map, language}`, with the keys as canonical BCP 47 tags and the
+  primary language; see `Baudrate.Sanitizer.Native.sanitize_content_map/1`.
+  Anything but a map is treated as an empty one.
+  """
+  @spec sanitize_content_map(term()) :: {%{String.t() => String.t()}, String.t() | nil}
+  def sanitize_content_map(content_map) when is_map(content_map) do
+    case Native.sanitize_content_map(content_map) do
+      {:error, {:panic, message}} -> panicked("sanitize_content_map/1", message, {%{}, nil})
+      result -> result
+    end
+  end
+
+  def sanitize_content_map(_content_map), do: {%{}, nil}
+
+  defp panicked(call, message, default) do
+    Baudrate.Native.log_panic(call, message)
+    default
+  end
+
   defp truncate_display_name(name, max) do
     if String.length(name) > max, do: String.slice(name, 0, max), else: name
   end
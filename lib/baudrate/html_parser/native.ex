@@ -6,25 +6,74 @@ defmodule Baudrate.HtmlParser.Native do
   [html5ever](https://github.com/servo/html5ever) via the `scraper` crate:
 
     * `parse_og_metadata/1` — extract Open Graph / Twitter Card / fallback metadata
+    * `extract_card/2` — extract a link preview card with resolved, length-capped fields
     * `extract_first_url/2` — extract the first external URL from an HTML fragment
+    * `extract_icons/2` — find favicon / touch icon candidates in preference order
+    * `extract_oembed_links/1` — find oEmbed discovery endpoints
+    * `extract_rel_me/2` — find `rel="me"` links for profile link verification
+    * `robots_allowed?/3` / `robots_crawl_delay/2` — evaluate a fetched robots.txt
+
+  `native_info/0` reports what the loaded library supports; see
+  `Baudrate.Native`.
+
+  A panic inside a NIF is returned as `{:error, {:panic, message}}` (the
+  `t:panic/0` type) instead of being raised.
   """
 
-  use Rustler, otp_app: :baudrate, crate: "baudrate_html_parser"
+  use RustlerPrecompiled, Baudrate.Native.precompiled(crate: "baudrate_html_parser")
+
+  @type panic :: {:error, {:panic, String.t()}}
 
   defmodule OgMetadata do
     @moduledoc "Struct returned by `parse_og_metadata/1`."
     defstruct [:title, :description, :image_url, :site_name]
   end
 
+  defmodule Card do
+    @moduledoc "Struct returned by `extract_card/2`."
+    defstruct [
+      :title,
+      :description,
+      :image_url,
+      :site_name,
+      :canonical_url,
+      :twitter_card,
+      :twitter_site,
+      :twitter_creator
+    ]
+  end
+
   @doc """
   Parse an HTML document and extract OG / Twitter Card / fallback metadata.
 
   Returns a `%OgMetadata{}` struct with `:title`, `:description`, `:image_url`,
   and `:site_name` fields (all `String.t() | nil`).
   """
-  @spec parse_og_metadata(String.t()) :: OgMetadata.t()
+  @spec parse_og_metadata(String.t()) :: OgMetadata.t() | panic()
   def parse_og_metadata(_html), do: :erlang.nif_error(:nif_not_loaded)
 
+  defmodule Icon do
+    @moduledoc "Struct returned in the list from `extract_icons/2`."
+    defstruct [:url, :rel, :size, :content_type]
+  end
+
+  @doc """
+  Parse an HTML document and extract a link preview card.
+
+  Fields are taken from Open Graph tags first, then Twitter Card tags, then
+  `<title>` / `<meta name="description">`. `og:*` and `twitter:*` tags are
+  accepted with either `property` or `name` attributes.
+
+  `image_url` and `canonical_url` (`<link rel="canonical">`, falling back to
+  `og:url`) are resolved against `base_url` and dropped unless HTTP(S).
+  Text fields are whitespace-collapsed and capped to the `LinkPreview` column
+  limits (title 300, description 1000, site name 200 characters).
+
+  Returns a `%Card{}` struct; missing fields are `nil`.
+  """
+  @spec extract_card(String.t(), String.t()) :: Card.t() | panic()
+  def extract_card(_html, _base_url), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Parse an HTML fragment and extract the first external HTTP(S) URL.
 
@@ -33,6 +82,74 @@ defmodule Baudrate.HtmlParser.Native do
 
   Returns the URL string or `nil`.
   """
-  @spec extract_first_url(String.t(), String.t()) :: String.t() | nil
+  @spec extract_first_url(String.t(), String.t()) :: String.t() | nil | panic()
   def extract_first_url(_html, _origin), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse an HTML document and collect its `rel="me"` link targets.
+
+  Both `<a rel="me">` and `<link rel="me">` are considered (`rel` may hold
+  other tokens too). Only absolute HTTP(S) targets are returned, deduplicated
+  and in document order. The boolean is `true` when one of them points at
+  `expected_url`, ignoring scheme/host case, fragments, and a trailing `/`.
+
+  Returns `{matched?, targets}`.
+  """
+  @spec extract_rel_me(String.t(), String.t()) :: {boolean(), [String.t()]} | panic()
+  def extract_rel_me(_html, _expected_url), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse an HTML document and return its oEmbed discovery endpoints.
+
+  Looks for `<link rel="alternate">` elements whose `type` is
+  `application/json+oembed` or `text/xml+oembed` and whose `href` is an
+  absolute HTTP(S) URL. JSON endpoints come first since they are preferred.
+
+  Returns a list of `{:json | :xml, url}` tuples (empty when the page offers
+  no rich embed).
+  """
+  @spec extract_oembed_links(String.t()) :: [{:json | :xml, String.t()}] | panic()
+  def extract_oembed_links(_html), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse an HTML document and return its icon candidates.
+
+  Considers `<link>` elements with `rel` `icon`, `shortcut icon`,
+  `apple-touch-icon`, or `apple-touch-icon-precomposed` (the latter two are
+  reported as `rel: "apple-touch-icon"`, the former as `"icon"`). URLs are
+  resolved against `base_url` and kept only if HTTP(S).
+
+  Each `%Icon{}` has `:url`, `:rel`, `:size` (the largest declared width, or
+  `nil`), and `:content_type` (the lowercased `type` attribute, or `nil`).
+  The list is sorted by preference: touch icons first, then larger sizes.
+  """
+  @spec extract_icons(String.t(), String.t()) :: [Icon.t()] | panic()
+  def extract_icons(_html, _base_url), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Check whether `user_agent` may fetch `path` under a robots.txt body.
+
+  Implements RFC 9309: the group naming the user agent's product token
+  (`"Baudrate/1.0 (+https://…)"` → `baudrate`, case-insensitive) is used,
+  falling back to `*`. `*` wildcards and `$` end anchors are supported; the
+  longest matching rule wins and `Allow` wins ties. `path` should include the
+  query string. `/robots.txt` itself is always allowed. Only the first
+  500 KiB are read, as RFC 9309 allows; it runs on a dirty CPU scheduler.
+  """
+  @spec robots_allowed?(String.t(), String.t(), String.t()) :: boolean() | panic()
+  def robots_allowed?(_robots_txt, _user_agent, _path), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the `Crawl-delay` in seconds that a robots.txt body declares for
+  `user_agent` (same group selection as `robots_allowed?/3`), or `nil`.
+  """
+  @spec robots_crawl_delay(String.t(), String.t()) :: float() | nil | panic()
+  def robots_crawl_delay(_robots_txt, _user_agent), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the crate version, enabled Cargo features, and the `{name, arity}`
+  of every NIF in the loaded library.
+  """
+  @spec native_info() :: Baudrate.Native.info() | panic()
+  def native_info, do: :erlang.nif_error(:nif_not_loaded)
 end
@@ -0,0 +1,517 @@
+defmodule Baudrate.Media.Native do
+  @moduledoc """
+  Rustler NIF bindings to the `baudrate_media` Rust crate.
+
+  Media attachment processing. Images are handled with the `image` crate so
+  that untrusted uploads are decoded with explicit dimension and memory
+  limits, SVG with `resvg`, and audio metadata with `lofty`:
+
+    * `blurhash_encode/3` — blurhash placeholder string for an image
+    * `blurhash_decode/3` — render a blurhash as a PNG
+    * `thumbhash_encode/1` — base64 thumbhash string for an image
+    * `thumbhash_decode/1` — render a thumbhash as a PNG
+    * `resize_image/5` — downscale and re-encode an image
+    * `strip_metadata/2` — remove EXIF / XMP / IPTC metadata
+    * `auto_orient/1` — apply the EXIF orientation to the pixels
+    * `transcode_image/3` — lossy WebP / AVIF variants
+    * `probe_image/1` — header-only format, dimension, and animation probe
+    * `sniff_mime/1` — detect the MIME type from magic bytes
+    * `still_frame/1` — static PNG of an animation's first frame
+    * `crop_focal/7` — crop around a focal point to a preview size
+    * `crop_image/7` — cut out a region chosen as fractions of the image
+    * `dominant_colors/2` — most prominent colors, for placeholder backgrounds
+    * `phash/1` — 64-bit perceptual hash for near-duplicate detection
+    * `phash_batch/1` — `phash/1` for a list of images, in parallel
+    * `hamming_distance/2` — number of differing bits between two hashes
+    * `rasterize_svg/3` — render an SVG document as a PNG
+    * `probe_audio/1` — audio duration, tags, and embedded cover art
+    * `blur_image/3` — heavily blurred JPEG preview for sensitive media
+    * `animation_info/1` — frame count, loop count, and decoded size estimate
+    * `qr_svg/2` — render text (a URL, an `otpauth://` URI) as a QR code SVG
+
+  Decoding applies the EXIF orientation, so results are always upright.
+
+  Image functions accept encoded image bytes (JPEG, PNG, GIF, or WebP) and
+  return `{:error, reason}` with one of `:unsupported_format`,
+  `:image_too_large`, or `:decode_failed` when the input cannot be decoded.
+
+  SVG, audio, and AVIF support are optional Cargo features (`svg`, `audio`,
+  `avif`), all enabled by default. A library built without one still
+  exports its NIFs, which then return `{:error, :feature_disabled}`; a
+  source build can leave them out with
+
+      config :baudrate, Baudrate.Media.Native,
+        default_features: false,
+        features: ["svg"]
+
+  `native_info/0` reports what the loaded library supports; see
+  `Baudrate.Native`.
+
+  A panic inside a NIF is returned as `{:error, {:panic, message}}` (the
+  `t:panic/0` type) instead of being raised.
+  """
+
+  use RustlerPrecompiled, Baudrate.Native.precompiled(crate: "baudrate_media")
+
+  @type panic :: {:error, {:panic, String.t()}}
+
+  @type error :: :unsupported_format | :image_too_large | :decode_failed
+
+  @type output_format :: :jpeg | :png | :webp
+
+  @type encoded :: %{data: binary(), width: pos_integer(), height: pos_integer()}
+
+  @type animation_limits :: %{
+          optional(:max_frames) => non_neg_integer() | nil,
+          optional(:max_decoded_bytes) => non_neg_integer() | nil
+        }
+
+  @type qr_options :: %{
+          optional(:size) => 1..4096 | nil,
+          optional(:margin) => 0..16 | nil,
+          optional(:ec_level) => :low | :medium | :quartile | :high | nil,
+          optional(:color) => String.t() | nil,
+          optional(:background) => String.t() | nil
+        }
+
+  @doc """
+  Encode an image as a [blurhash](https://blurha.sh/) placeholder.
+
+  The image is downsampled before encoding, so cost is independent of the
+  input resolution beyond decoding. `components_x` and `components_y` must
+  be between 1 and 9; Mastodon uses 4 × 3.
+
+  Returns `{:ok, hash}`, or `{:error, :invalid_components}` / `{:error, error}`.
+  """
+  @spec blurhash_encode(binary(), pos_integer(), pos_integer()) ::
+          {:ok, String.t()} | {:error, :invalid_components | error()} | panic()
+  def blurhash_encode(_bytes, _components_x, _components_y),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render a blurhash as a `width` × `height` PNG.
+
+  Used to serve previews of sensitive media (and Open Graph images for them)
+  without client-side JavaScript. Both dimensions must be between 1 and 2048.
+
+  Returns `{:ok, png}`, or `{:error, :invalid_dimensions}` /
+  `{:error, :invalid_blurhash}`.
+  """
+  @spec blurhash_decode(String.t(), pos_integer(), pos_integer()) ::
+          {:ok, binary()} | {:error, :invalid_dimensions | :invalid_blurhash} | panic()
+  def blurhash_decode(_hash, _width, _height), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Encode an image as a base64 [thumbhash](https://evanw.github.io/thumbhash/).
+
+  Thumbhash has better color fidelity than blurhash and also encodes the
+  aspect ratio and alpha channel.
+
+  Returns `{:ok, hash}` or `{:error, error}`.
+  """
+  @spec thumbhash_encode(binary()) :: {:ok, String.t()} | {:error, error()} | panic()
+  def thumbhash_encode(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render a base64 thumbhash (padded or unpadded) as a PNG.
+
+  The output uses the thumbhash's native size: at most 32 pixels on the
+  longer side, with the encoded aspect ratio.
+
+  Returns `{:ok, png}` or `{:error, :invalid_thumbhash}`.
+  """
+  @spec thumbhash_decode(String.t()) :: {:ok, binary()} | {:error, :invalid_thumbhash} | panic()
+  def thumbhash_decode(_hash), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Downscale an image to fit within `max_width` × `max_height` and encode it
+  as `format` (`:jpeg`, `:png`, or lossless `:webp`).
+
+  The aspect ratio is preserved, images that already fit are not upscaled,
+  and resampling uses a Lanczos3 filter. The EXIF orientation is applied
+  before resizing.
+
+  `limits` may set `:max_frames` and `:max_decoded_bytes` (see
+  `animation_info/1`); animations over either are rejected with
+  `:too_many_frames` or `:image_too_large` before any frame is decoded.
+  Missing or `nil` limits are not enforced. `Baudrate.Media.Thumbnail`
+  passes the configured limits.
+
+  Returns `{:ok, %{data: bytes, width: w, height: h}}` with the final
+  dimensions, or `{:error, :invalid_dimensions}` / `{:error, error}`.
+  """
+  @spec resize_image(
+          binary(),
+          pos_integer(),
+          pos_integer(),
+          output_format(),
+          animation_limits()
+        ) ::
+          {:ok, encoded()}
+          | {:error, :invalid_dimensions | :too_many_frames | :encode_failed | error()}
+          | panic()
+  def resize_image(_bytes, _max_width, _max_height, _format, _limits \\ %{}),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Remove EXIF, XMP, IPTC, and comment metadata (including GPS coordinates)
+  from a JPEG, PNG, or WebP image, returning it in the same format.
+
+  Metadata is removed at the container level without re-encoding. The one
+  exception is an image with a non-identity EXIF orientation: it is rotated
+  to upright and re-encoded (WebP losslessly), since removing the EXIF block
+  would otherwise display it sideways.
+
+  The ICC color profile is kept unless `keep_icc` is `false`.
+
+  Returns `{:ok, bytes}` or `{:error, error}`.
+  """
+  @spec strip_metadata(binary(), boolean()) ::
+          {:ok, binary()} | {:error, :encode_failed | error()} | panic()
+  def strip_metadata(_bytes, _keep_icc \\ true), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Rotate or flip a JPEG, PNG, or WebP image so that it displays upright
+  without relying on its EXIF orientation tag.
+
+  Images that are already upright are returned unchanged. Others are
+  re-encoded in their original format (WebP losslessly) without an EXIF
+  block, keeping the ICC profile. `resize_image/5` and `strip_metadata/2`
+  already apply the orientation, so this is only needed when neither is
+  used.
+
+  Returns `{:ok, bytes}` or `{:error, error}`.
+  """
+  @spec auto_orient(binary()) :: {:ok, binary()} | {:error, :encode_failed | error()} | panic()
+  def auto_orient(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Re-encode an image as lossy `:webp` or `:avif` at `quality` (1–100).
+
+  Inputs larger than 32 MiB or 8192 pixels on either side are rejected using
+  only the image headers, before any pixels are decoded. The EXIF
+  orientation is applied and no metadata is carried over.
+
+  Returns `{:ok, %{data: bytes, width: w, height: h}}`, or `{:error, reason}`
+  with `:invalid_quality`, `:input_too_large`, `:encode_failed`, or one of
+  the decode errors. `:avif` output returns `:feature_disabled` when the
+  library was built without the `avif` feature.
+  """
+  @spec transcode_image(binary(), :webp | :avif, 1..100) ::
+          {:ok, encoded()}
+          | {:error,
+             :invalid_quality | :input_too_large | :encode_failed | :feature_disabled | error()}
+          | panic()
+  def transcode_image(_bytes, _format, _quality), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Probe an image from its headers only, without decoding any pixel data.
+
+  Returns `{:ok, %{format: format, width: w, height: h, animated: boolean,
+  decoded_bytes: n}}`, where `format` is `:jpeg`, `:png`, `:gif`, or
+  `:webp`, the dimensions are as displayed (after the EXIF orientation), and
+  `decoded_bytes` is the memory one decoded frame needs. Use it to reject
+  decompression bombs before calling any of the decoding functions.
+
+  Images over 16384 pixels on either side return
+  `{:error, :image_too_large}`; other errors are as for the decoding
+  functions.
+  """
+  @spec probe_image(binary()) ::
+          {:ok,
+           %{
+             format: :jpeg | :png | :gif | :webp,
+             width: pos_integer(),
+             height: pos_integer(),
+             animated: boolean(),
+             decoded_bytes: non_neg_integer()
+           }}
+          | {:error, error()}
+          | panic()
+  def probe_image(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Detect the MIME type of image, audio, or video content from its leading
+  bytes, following the WHATWG MIME Sniffing standard.
+
+  Use it instead of a remote `Content-Type` header when validating uploads
+  and proxied media. Besides the types the standard lists (for example
+  `"image/png"`, `"audio/mpeg"`, `"video/mp4"`, `"video/webm"`, and
+  `"application/ogg"`), `"image/avif"` and `"audio/flac"` are recognised.
+  Only the first 1445 bytes are examined.
+
+  Returns the type, or `nil` when the content matches none of the rules.
+  """
+  @spec sniff_mime(binary()) :: String.t() | nil | panic()
+  def sniff_mime(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Extract the first frame of an animated GIF, APNG, or WebP as a static PNG,
+  for timeline previews that only animate on hover.
+
+  GIF and WebP frames are composited onto the full canvas. For an APNG the
+  default image is used, which is either the first frame or the static
+  fallback image the file provides. Still images are simply re-encoded.
+
+  Returns `{:ok, %{data: png, width: w, height: h}}` or `{:error, reason}`.
+  """
+  @spec still_frame(binary()) :: {:ok, encoded()} | {:error, :encode_failed | error()} | panic()
+  def still_frame(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Crop an image around a Mastodon-style focal point and scale it to
+  `target_width` × `target_height`, encoded as `format` (default `:jpeg`).
+
+  `focal_x` and `focal_y` range from -1.0 to 1.0, with `(0.0, 0.0)` at the
+  center, `x` increasing to the right and `y` increasing upwards. The
+  largest region with the target aspect ratio is cut out, centered on the
+  focal point as far as the image edges allow, and then downscaled. Crops
+  smaller than the target are not upscaled. The EXIF orientation is applied
+  first. `limits` are enforced as by `resize_image/5`.
+
+  Returns `{:ok, %{data: bytes, width: w, height: h}}`, or `{:error, reason}`
+  with `:invalid_focal_point`, `:invalid_dimensions`, `:too_many_frames`, or
+  one of the decode errors.
+  """
+  @spec crop_focal(
+          binary(),
+          float(),
+          float(),
+          pos_integer(),
+          pos_integer(),
+          output_format(),
+          animation_limits()
+        ) ::
+          {:ok, encoded()}
+          | {:error,
+             :invalid_focal_point
+             | :invalid_dimensions
+             | :too_many_frames
+             | :encode_failed
+             | error()}
+          | panic()
+  def crop_focal(
+        _bytes,
+        _focal_x,
+        _focal_y,
+        _target_width,
+        _target_height,
+        _format \\ :jpeg,
+        _limits \\ %{}
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Cut out the region at `x`, `y` measuring `width` × `height` and encode it
+  as `format`. All four are floats from 0.0 to 1.0, as fractions of the
+  upright image (the EXIF orientation is applied first).
+
+  The region is clamped to the image and is at least one pixel on each
+  side. `limits` are enforced as by `resize_image/5`. Returns
+  `{:ok, %{data: bytes, width: w, height: h}}`, or `{:error, reason}` with
+  `:invalid_region`, `:too_many_frames`, `:encode_failed`, or one of the
+  decode errors.
+  """
+  @spec crop_image(
+          binary(),
+          float(),
+          float(),
+          float(),
+          float(),
+          output_format(),
+          animation_limits()
+        ) ::
+          {:ok, encoded()}
+          | {:error, :invalid_region | :too_many_frames | :encode_failed | error()}
+          | panic()
+  def crop_image(_bytes, _x, _y, _width, _height, _format, _limits \\ %{}),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return up to `count` (1–16) dominant colors of an image as `"#rrggbb"`
+  strings, most prominent first.
+
+  Used to paint placeholder backgrounds and theme preview cards before the
+  image itself loads. Colors are found by median-cut quantization of a
+  downsampled copy, ignoring mostly transparent pixels, so fewer colors
+  are returned for images with few distinct colors and none for fully
+  transparent ones.
+
+  Returns `{:ok, colors}`, or `{:error, :invalid_color_count}` /
+  `{:error, error}`.
+  """
+  @spec dominant_colors(binary(), 1..16) ::
+          {:ok, [String.t()]} | {:error, :invalid_color_count | error()} | panic()
+  def dominant_colors(_bytes, _count), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Compute the 64-bit perceptual hash (pHash) of an image, as an unsigned
+  integer.
+
+  Resized, recompressed, or slightly recolored copies of an image hash to
+  values a small `hamming_distance/2` apart, which lets moderation match
+  re-uploads of removed media. Mirroring, rotation, and heavy cropping are
+  not detected.
+
+  Returns `{:ok, hash}` or `{:error, error}`.
+  """
+  @spec phash(binary()) :: {:ok, non_neg_integer()} | {:error, error()} | panic()
+  def phash(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Compute `phash/1` for every image in `images`, returning one result per
+  image in the same order.
+
+  The images are hashed in parallel on the NIF's thread pool
+  (`BAUDRATE_NIF_THREADS` threads, default one per core) while the call
+  occupies a single dirty CPU scheduler.
+  """
+  @spec phash_batch([binary()]) ::
+          [{:ok, non_neg_integer()} | {:error, error()}] | panic()
+  def phash_batch(_images), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the number of bits (0–64) that differ between two `phash/1` values.
+
+  Distances up to about 10 usually indicate the same image.
+  """
+  @spec hamming_distance(non_neg_integer(), non_neg_integer()) :: 0..64 | panic()
+  def hamming_distance(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render an SVG document as a PNG scaled to fit within `width` × `height`
+  (each 1–2048), preserving its aspect ratio. Remote SVG avatars and custom
+  emoji are served as this PNG rather than as raw SVG.
+
+  The document is treated as untrusted: external and embedded images are
+  not loaded, text is not rendered, and DTDs and gzip-compressed SVGZ are
+  rejected. Sources over 1 MiB and documents with too many nodes, counted
+  both in the source and after expanding `<use>` references, are refused
+  before any rendering happens.
+
+  Returns `{:ok, %{data: png, width: w, height: h}}`, or `{:error, reason}`
+  with `:invalid_dimensions`, `:input_too_large`, `:invalid_svg`,
+  `:svg_too_complex`, or `:encode_failed`, or `:feature_disabled` without
+  the `svg` feature.
+  """
+  @spec rasterize_svg(binary(), 1..2048, 1..2048) ::
+          {:ok, encoded()}
+          | {:error,
+             :invalid_dimensions
+             | :input_too_large
+             | :invalid_svg
+             | :svg_too_complex
+             | :encode_failed
+             | :feature_disabled}
+          | panic()
+  def rasterize_svg(_bytes, _width, _height), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Read the stream properties, title, artist, album, and embedded cover art
+  of an audio file, for rendering audio posts.
+
+  Tags are read from ID3v1/v2 (MP3, AAC, WAV, AIFF), Vorbis comments
+  (FLAC, Ogg Vorbis, Opus), and MP4 atoms (M4A); missing fields are `nil`.
+  `format` is one of `:aac`, `:aiff`, `:flac`, `:mp3`, `:mp4`, `:opus`,
+  `:vorbis`, or `:wav`, and `bitrate` is in kbit/s. The cover is the front
+  cover picture if tagged as one, otherwise the first embedded picture, as
+  `%{data: bytes, mime_type: type}`; it should be validated like any other
+  untrusted image before use.
+
+  Returns `{:ok, map}`, or `{:error, :unsupported_format}` /
+  `{:error, :decode_failed}`, or `{:error, :feature_disabled}` without the
+  `audio` feature.
+  """
+  @spec probe_audio(binary()) ::
+          {:ok,
+           %{
+             format: :aac | :aiff | :flac | :mp3 | :mp4 | :opus | :vorbis | :wav,
+             duration_ms: non_neg_integer(),
+             bitrate: non_neg_integer() | nil,
+             sample_rate: pos_integer() | nil,
+             channels: pos_integer() | nil,
+             title: String.t() | nil,
+             artist: String.t() | nil,
+             album: String.t() | nil,
+             cover: %{data: binary(), mime_type: String.t() | nil} | nil
+           }}
+          | {:error, :unsupported_format | :decode_failed | :feature_disabled}
+          | panic()
+  def probe_audio(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render a heavily blurred, downscaled JPEG of an image, so that previews of
+  sensitive attachments never expose their content, even by URL.
+
+  The image is first downscaled to fit within `max_dim` × `max_dim`
+  (1–2048, never upscaled) and then blurred with standard deviation `sigma`
+  (greater than 0, at most 100) in pixels of the downscaled image. The blur
+  approximates a Gaussian, and its cost does not grow with `sigma`.
+
+  Returns `{:ok, %{data: jpeg, width: w, height: h}}`, or `{:error, reason}`
+  with `:invalid_sigma`, `:invalid_dimensions`, or one of the decode errors.
+  """
+  @spec blur_image(binary(), float(), 1..2048) ::
+          {:ok, encoded()}
+          | {:error, :invalid_sigma | :invalid_dimensions | :encode_failed | error()}
+          | panic()
+  def blur_image(_bytes, _sigma, _max_dim), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Read the frame count, loop count, and decoded size estimate of a GIF,
+  APNG, or animated WebP from its container structure, without decoding any
+  frames.
+
+  `loop_count` is the number of times the animation plays, with `0` meaning
+  forever. `decoded_bytes` is the memory needed to decode every frame as
+  8-bit RGBA at the canvas size. Still images, including other formats,
+  report a single frame played once.
+
+  Returns `{:ok, %{frames: n, loop_count: n, decoded_bytes: n}}` or
+  `{:error, error}`.
+  """
+  @spec animation_info(binary()) ::
+          {:ok,
+           %{
+             frames: pos_integer(),
+             loop_count: non_neg_integer(),
+             decoded_bytes: non_neg_integer()
+           }}
+          | {:error, error()}
+          | panic()
+  def animation_info(_bytes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render `data` as a QR code SVG document.
+
+  Options (all optional):
+
+    * `:size` — width and height in pixels (default `264`)
+    * `:margin` — quiet zone in modules (default `4`, the minimum readers
+      expect)
+    * `:ec_level` — error correction, `:low`, `:medium` (default),
+      `:quartile`, or `:high`
+    * `:color` — module color as `#rgb` / `#rrggbb` (default `"#000000"`)
+    * `:background` — background color (default `"#ffffff"`); `nil` leaves
+      it transparent
+
+  The document holds only a `<rect>` and a single `<path>`, with no styles,
+  scripts, or links, so it is safe to inline or serve as a data URI.
+
+  Returns `{:ok, svg}`, `{:error, :invalid_options}`, or
+  `{:error, :data_too_long}` (over 2953 bytes at `:low`, fewer at higher
+  levels).
+  """
+  @spec qr_svg(binary(), qr_options()) ::
+          {:ok, String.t()} | {:error, :invalid_options | :data_too_long} | panic()
+  def qr_svg(data, options \\ %{})
+  def qr_svg(_data, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the crate version, enabled Cargo features, and the `{name, arity}`
+  of every working NIF in the loaded library; NIFs of disabled features are
+  left out.
+  """
+  @spec native_info() :: Baudrate.Native.info() | panic()
+  def native_info, do: :erlang.nif_error(:nif_not_loaded)
+end
@@ -0,0 +1,68 @@
+defmodule Baudrate.Media.Placeholder do
+  @moduledoc """
+  Image placeholders in the format chosen for this deployment.
+
+  The format is set with:
+
+      config :baudrate, Baudrate.Media, placeholder_format: :thumbhash
+
+  and defaults to `:blurhash`, which Mastodon and most other Fediverse
+  software understand. `:thumbhash` has better color fidelity and keeps the
+  aspect ratio, but is only useful where clients can decode it.
+  """
+
+  alias Baudrate.Media.Native
+
+  @blurhash_components {4, 3}
+
+  @type format :: :blurhash | :thumbhash
+
+  @doc """
+  Returns the configured placeholder format.
+  """
+  @spec format() :: format()
+  def format do
+    Application.get_env(:baudrate, Baudrate.Media, [])
+    |> Keyword.get(:placeholder_format, :blurhash)
+  end
+
+  @doc """
+  Encodes `bytes` as a placeholder in the configured format.
+
+  Returns `{:ok, {format, hash}}` or `{:error, reason}`, where `reason` may
+  be `{:panic, message}` (see `Baudrate.Native`).
+  """
+  @spec encode(binary()) :: {:ok, {format(), String.t()}} | {:error, atom()} | Native.panic()
+  def encode(bytes), do: encode(bytes, format())
+
+  @doc """
+  Encodes `bytes` as a placeholder in the given format.
+  """
+  @spec encode(binary(), format()) ::
+          {:ok, {format(), String.t()}} | {:error, atom()} | Native.panic()
+  def encode(bytes, :blurhash) do
+    {x, y} = @blurhash_components
+
+    with {:ok, hash} <- Native.blurhash_encode(bytes, x, y) do
+      {:ok, {:blurhash, hash}}
+    end
+  end
+
+  def encode(bytes, :thumbhash) do
+    with {:ok, hash} <- Native.thumbhash_encode(bytes) do
+      {:ok, {:thumbhash, hash}}
+    end
+  end
+
+  @doc """
+  Renders a placeholder as a PNG.
+
+  Blurhashes carry no size, so they are rendered at `width` × `height`;
+  thumbhashes are rendered at their native size and the dimensions are
+  ignored.
+  """
+  @spec render(format(), String.t(), pos_integer(), pos_integer()) ::
+          {:ok, binary()} | {:error, atom()} | Native.panic()
+  def render(:blurhash, hash, width, height), do: Native.blurhash_decode(hash, width, height)
+  def render(:thumbhash, hash, _width, _height), do: Native.thumbhash_decode(hash)
+end
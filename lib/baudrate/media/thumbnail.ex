@@ -0,0 +1,76 @@
+defmodule Baudrate.Media.Thumbnail do
+  @moduledoc """
+  Image resizing with this deployment's animation limits.
+
+  Animations are rejected before decoding when they have more frames, or
+  would need more memory to decode, than configured with:
+
+      config :baudrate, Baudrate.Media,
+        max_animation_frames: 1_000,
+        max_animation_bytes: 512 * 1024 * 1024
+
+  Both default to the values shown. Set either to `nil` to disable it.
+
+  Stored images (article images, avatars, link preview images) are written
+  as lossy WebP at quality 80; see `resize_webp/3` and `square_webp/2`.
+  """
+
+  alias Baudrate.Media.Native
+
+  @default_max_frames 1_000
+  @default_max_bytes 512 * 1024 * 1024
+  @webp_quality 80
+
+  @doc """
+  Returns the configured limits, as accepted by `Native.resize_image/5`,
+  `Native.crop_focal/7`, and `Native.crop_image/7`.
+  """
+  @spec limits() :: Native.animation_limits()
+  def limits do
+    config = Application.get_env(:baudrate, Baudrate.Media, [])
+
+    %{
+      max_frames: Keyword.get(config, :max_animation_frames, @default_max_frames),
+      max_decoded_bytes: Keyword.get(config, :max_animation_bytes, @default_max_bytes)
+    }
+  end
+
+  @doc """
+  Downscales `bytes` to fit within `max_width` × `max_height` and encodes
+  it as `format`, enforcing the configured animation limits.
+
+  Returns `{:ok, %{data: bytes, width: w, height: h}}` or
+  `{:error, reason}`; see `Native.resize_image/5`.
+  """
+  @spec resize(binary(), pos_integer(), pos_integer(), Native.output_format()) ::
+          {:ok, Native.encoded()} | {:error, atom()} | Native.panic()
+  def resize(bytes, max_width, max_height, format) do
+    Native.resize_image(bytes, max_width, max_height, format, limits())
+  end
+
+  @doc """
+  Like `resize/4`, encoding the result as lossy WebP.
+
+  The image is resized losslessly first, so it is only compressed once.
+  """
+  @spec resize_webp(binary(), pos_integer(), pos_integer()) ::
+          {:ok, Native.encoded()} | {:error, atom()} | Native.panic()
+  def resize_webp(bytes, max_width, max_height) do
+    with {:ok, %{data: png}} <- resize(bytes, max_width, max_height, :png) do
+      Native.transcode_image(png, :webp, @webp_quality)
+    end
+  end
+
+  @doc """
+  Crops the largest centered square out of `bytes`, downscales it to at
+  most `size` × `size`, and encodes it as lossy WebP, enforcing the
+  configured animation limits.
+  """
+  @spec square_webp(binary(), pos_integer()) ::
+          {:ok, Native.encoded()} | {:error, atom()} | Native.panic()
+  def square_webp(bytes, size) do
+    with {:ok, %{data: png}} <- Native.crop_focal(bytes, 0.0, 0.0, size, size, :png, limits()) do
+      Native.transcode_image(png, :webp, @webp_quality)
+    end
+  end
+end
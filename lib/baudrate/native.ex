@@ -0,0 +1,168 @@
+defmodule Baudrate.Native do
+  @moduledoc """
+  Capability detection for the Rustler NIF modules.
+
+  Each `*.Native` module's `native_info/0` reports the version of the
+  loaded Rust library and the NIFs it exports. Features that depend on a
+  newer NIF can check `available?/3` and fall back instead of crashing when
+  an older library is deployed.
+
+  A library built before `native_info/0` existed cannot report anything, so
+  `info/1` returns `:error` and `available?/3` returns `false` for it.
+
+  ## Precompiled libraries
+
+  The NIF modules load their libraries through `RustlerPrecompiled`, with
+  the options from `precompiled/1`. By default every crate is compiled from
+  source with Cargo; setting `BAUDRATE_PRECOMPILED_NIFS=1` at compile time
+  downloads the release artifacts for the current target instead.
+
+  Precompiled artifacts are built with every Cargo feature. Source builds
+  may leave optional features out, whose NIFs then return
+  `{:error, :feature_disabled}` and are omitted from `native_info/0`;
+  `feature?/2` and `available?/3` reflect that, and `log_report/0` logs it
+  at startup.
+
+  ## Panics
+
+  A NIF that panics returns `{:error, {:panic, message}}`. Wrappers whose
+  specs promise a plain value match it, log it with `log_panic/2`, and
+  return a safe default such as `""`, so one malformed input cannot crash
+  the caller. Those without one raise `Baudrate.Native.PanicError`.
+  """
+
+  require Logger
+
+  @type info :: %{
+          version: String.t(),
+          features: [String.t()],
+          nifs: [{atom(), non_neg_integer()}]
+        }
+
+  @version Mix.Project.config()[:version]
+
+  @targets ~w(
+    aarch64-apple-darwin
+    x86_64-apple-darwin
+    aarch64-unknown-linux-gnu
+    x86_64-unknown-linux-gnu
+    aarch64-unknown-linux-musl
+    x86_64-unknown-linux-musl
+  )
+
+  @modules [
+    Baudrate.Bots.FeedParserNative,
+    Baudrate.Crypto.Native,
+    Baudrate.HtmlParser.Native,
+    Baudrate.Media.Native,
+    Baudrate.Sanitizer.Native,
+    Baudrate.Text.Native
+  ]
+
+  # Optional Cargo features and the calls that need them, for `log_report/0`.
+  @optional_features %{
+    Baudrate.Media.Native => [
+      {"audio", "probe_audio/1"},
+      {"avif", "transcode_image/3 to :avif"},
+      {"svg", "rasterize_svg/3"}
+    ]
+  }
+
+  @doc """
+  Return the `RustlerPrecompiled` options shared by the NIF modules, merged
+  with `opts`, which must include the `:crate`.
+  """
+  @spec precompiled(keyword()) :: keyword()
+  def precompiled(opts) do
+    Keyword.merge(
+      [
+        otp_app: :baudrate,
+        base_url: "https://github.com/hiroshiyui/baudrate/releases/download/v#{@version}",
+        version: @version,
+        targets: @targets,
+        nif_versions: ["2.15"],
+        force_build: System.get_env("BAUDRATE_PRECOMPILED_NIFS") not in ["1", "true"]
+      ],
+      opts
+    )
+  end
+
+  @doc "The NIF modules whose libraries are inspected by `info/0`."
+  @spec modules() :: [module()]
+  def modules, do: @modules
+
+  @doc "Return `info/1` for every NIF module, keyed by module."
+  @spec info() :: %{module() => {:ok, info()} | :error}
+  def info, do: Map.new(@modules, &{&1, info(&1)})
+
+  @doc """
+  Return the build information of the library loaded for `module`, or
+  `:error` when it does not provide `native_info/0`.
+  """
+  @spec info(module()) :: {:ok, info()} | :error
+  def info(module) do
+    case module.native_info() do
+      %{nifs: _} = info -> {:ok, info}
+      {:error, {:panic, _}} -> :error
+    end
+  rescue
+    # The module is unavailable when its library failed to load.
+    UndefinedFunctionError -> :error
+    # The Elixir stub raises when the loaded library lacks the NIF.
+    ErlangError -> :error
+  end
+
+  @doc "Return whether the library loaded for `module` exports `name/arity`."
+  @spec available?(module(), atom(), non_neg_integer()) :: boolean()
+  def available?(module, name, arity) do
+    case info(module) do
+      {:ok, %{nifs: nifs}} -> {name, arity} in nifs
+      :error -> false
+    end
+  end
+
+  @doc "Return whether the library loaded for `module` was built with `feature`."
+  @spec feature?(module(), String.t()) :: boolean()
+  def feature?(module, feature) do
+    case info(module) do
+      {:ok, %{features: features}} -> feature in features
+      :error -> false
+    end
+  end
+
+  @doc """
+  Log the `{:error, {:panic, message}}` that `call` returned, for callers
+  that fall back to a default value instead of passing it on.
+  """
+  @spec log_panic(String.t(), String.t()) :: :ok
+  def log_panic(call, message) do
+    Logger.error("#{call} panicked: #{message}")
+  end
+
+  @doc """
+  Log the version and features of every loaded library, and warn about each
+  library that cannot report them or lacks an optional feature.
+  """
+  @spec log_report() :: :ok
+  def log_report do
+    for module <- @modules do
+      case info(module) do
+        {:ok, %{version: version, features: features}} ->
+          Logger.info("#{inspect(module)} #{version} loaded, features: #{inspect(features)}")
+
+          for {feature, call} <- Map.get(@optional_features, module, []),
+              feature not in features do
+            Logger.warning(
+              "#{inspect(module)} was built without #{feature}; " <>
+                "#{call} returns {:error, :feature_disabled}"
+            )
+          end
+
+        :error ->
+          Logger.warning("#{inspect(module)} cannot report its capabilities")
+      end
+    end
+
+    :ok
+  end
+end
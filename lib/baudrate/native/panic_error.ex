@@ -0,0 +1,14 @@
+defmodule Baudrate.Native.PanicError do
+  @moduledoc """
+  Raised by callers that cannot fall back to a default when a NIF returns
+  `{:error, {:panic, message}}`, such as a whole feed document.
+  """
+
+  defexception [:call, :message]
+
+  @impl true
+  def exception(opts) do
+    call = Keyword.fetch!(opts, :call)
+    %__MODULE__{call: call, message: "#{call} panicked: #{Keyword.fetch!(opts, :message)}"}
+  end
+end
@@ -0,0 +1,117 @@
+defmodule Baudrate.Sanitizer.Log do
+  @moduledoc """
+  Reports what the HTML sanitizer removes.
+
+  Configured under `config :baudrate, Baudrate.Sanitizer.Log`:
+
+    * `:level` — `:off` (the default), `:warning` (rejected URLs and elements
+      removed with their content, such as `<script>`), `:info` (also input
+      that was not valid UTF-8), or `:debug` (also unwrapped elements and
+      removed attributes). The level is passed to the NIF when it is loaded,
+      so it must be set in boot-time configuration.
+    * `:target` — `:logger` (the default) to forward events to `Logger`
+      through this process, or `:buffer` to keep them in the NIF for
+      `drain/0`.
+
+  While logging is enabled, each sanitizer call parses its input a second
+  time to find what the policy removes, so leave it `:off` unless needed.
+
+  Events are maps with `:level`, `:event`, `:policy` (`:federation`,
+  `:markdown`, or `nil` for `strip_tags/1`), and `:tag`, `:attribute`, and
+  `:value` where they apply. Values are truncated to 256 bytes.
+  """
+
+  use GenServer
+
+  require Logger
+
+  alias Baudrate.Sanitizer.Native
+
+  @levels [:off, :warning, :info, :debug]
+
+  @type event :: %{
+          level: :warning | :info | :debug,
+          event:
+            :element_removed
+            | :element_unwrapped
+            | :attribute_removed
+            | :url_rejected
+            | :invalid_utf8,
+          policy: :federation | :markdown | nil,
+          tag: String.t() | nil,
+          attribute: String.t() | nil,
+          value: String.t() | nil
+        }
+
+  # --- Public API ---
+
+  def start_link(opts \\ []) do
+    GenServer.start_link(__MODULE__, opts, name: __MODULE__)
+  end
+
+  @doc """
+  The configured level, as passed to the NIF at load time.
+
+  Used as the `load_data_fun` of `Baudrate.Sanitizer.Native`.
+  """
+  @spec configured_level() :: :off | :warning | :info | :debug
+  def configured_level do
+    level = Keyword.get(config(), :level, :off)
+    if level in @levels, do: level, else: :off
+  end
+
+  @doc "Return and clear the events buffered while no process is attached."
+  @spec drain() :: [event()]
+  def drain do
+    case Native.drain_log() do
+      events when is_list(events) -> events
+      {:error, {:panic, _message}} -> []
+    end
+  end
+
+  # --- GenServer callbacks ---
+
+  @impl true
+  def init(opts) do
+    target = Keyword.get(opts, :target, Keyword.get(config(), :target, :logger))
+
+    if target == :logger and Native.log_level() in [:warning, :info, :debug] do
+      # Detach from the NIF on shutdown.
+      Process.flag(:trap_exit, true)
+      Native.set_log_target(self())
+      {:ok, %{}}
+    else
+      :ignore
+    end
+  end
+
+  @impl true
+  def handle_info({:baudrate_sanitizer_log, event}, state) do
+    Logger.log(event.level, fn -> format(event) end, sanitizer_event: event.event)
+    {:noreply, state}
+  end
+
+  @impl true
+  def terminate(_reason, _state) do
+    Native.set_log_target(nil)
+  end
+
+  # --- Private ---
+
+  defp config, do: Application.get_env(:baudrate, __MODULE__, [])
+
+  defp format(event) do
+    subject =
+      case event do
+        %{tag: nil} -> ""
+        %{attribute: nil, tag: tag} -> " <#{tag}>"
+        %{attribute: attribute, value: nil, tag: tag} -> " #{attribute} on <#{tag}>"
+        %{attribute: attribute, value: value, tag: tag} ->
+          " #{attribute}=#{inspect(value)} on <#{tag}>"
+      end
+
+    policy = if event.policy, do: " (#{event.policy} policy)", else: ""
+
+    "sanitizer: #{event.event}#{subject}#{policy}"
+  end
+end
@@ -6,30 +6,449 @@ defmodule Baudrate.Sanitizer.Native do
   [Ammonia](https://github.com/rust-ammonia/ammonia) (html5ever parser):
 
     * `sanitize_federation/1` — allowlist for incoming AP content
+    * `sanitize_federation_batch/1` — `sanitize_federation/1` for a list of
+      documents, in parallel
+    * `sanitize_async/3` — `sanitize_federation/1` in the background, with
+      the result sent as a message
+    * `sanitize_content_map/1` — `sanitize_federation/1` for every language
+      of an ActivityStreams `contentMap`, with its primary language
     * `sanitize_markdown/1` — allowlist for local Markdown rendering
     * `strip_tags/1` — strip all HTML tags, preserving text content
     * `normalize_feed_html/1` — sanitize RSS/Atom body HTML and remove
       common feed artefacts (empty paragraphs, excessive line breaks)
+    * `emojify/2` — replace `:shortcode:` tokens in sanitized HTML with
+      custom emoji `<img>` elements
+    * `extract_shortcodes/1` — list the custom emoji shortcodes used in content
+    * `stats/0` — load counters, polled by `BaudrateWeb.Telemetry`
+    * `describe_policy/1` — the effective allowlist of a built-in policy
+    * `set_allowed_classes/2` — classes allowed per tag at runtime, on top
+      of a policy's own
+    * `set_code_classes/2` — the patterns that find the language class of
+      `<code>`, such as `hljs language-rust` or `sourceCode rust`
+    * `stream_new/1`, `stream_push/2`, `stream_finish/1` — sanitize a
+      document pushed in chunks
+    * `spam_score/1` — cheap spam signals and a combined score
+    * `format_link_label/1` — Mastodon-style shortened link text markup
+    * `build_status_html/4` — outgoing status HTML from composer plain text,
+      with links, mentions, and hashtags
+    * `analyze_content/1` — `sanitize_federation/1` plus the plain text,
+      mentions, hashtags, links, and emoji shortcodes of the result
+    * `derive_title/1` — a title from the first heading or sentence of a body
+    * `sanitize_inline/2` — one line of plain text for poll options, content
+      warnings, and field names
+    * `image_accessibility/1` — alt text presence, length, and placeholders
+      for each image
+    * `render_mfm/2` — Misskey `$[fn ...]` effects as classed spans, or
+      stripped to their text
+    * `inspect_html/1` — structural diagnostics of a document, for
+      moderation
+    * `build_og_meta/1` — Open Graph and Twitter Card `<meta>` tags from
+      user content, ready to embed
+    * `build_preview_card/1` — link preview card HTML from an extracted
+      card, stable under `sanitize_markdown/1`
+    * `build_profile_field_html/2` — a profile field with its URLs linked
+      `rel="me"`, for link verification
+    * `extract_quote/1` — the URL of a quote post's quoted post, and the
+      content without its `RE:` line
+    * `compile_cw_rules/1`, `suggest_cw/2` — content-warning categories
+      whose keywords or patterns a post matches
+    * `decode_entities/1`, `escape_html/1` — character references decoded
+      with the parser's entity tables, and text escaped for HTML
+    * `set_log_target/1`, `drain_log/0`, `log_level/0` — the logging channel
+      used by `Baudrate.Sanitizer.Log`
 
-  Also provides a pure-Elixir helper:
+  Also provides a helper built on `decode_entities/1`:
 
     * `decode_html_entities/1` — decode the XML/HTML entities that Ammonia
       preserves in `strip_tags/1` output (`&amp;`, `&lt;`, `&gt;`, `&quot;`,
       `&apos;`/`&#39;`, `&nbsp;`). Call this after `strip_tags/1` to produce
       plain text safe for Phoenix HEEx templates (which apply their own HTML
       escaping).
+
+  Input without `<` or `&` (most short statuses) skips the HTML parse and
+  is only escaped, with the same result as the full sanitizer.
+
+  The four sanitizers accept any binary. Invalid UTF-8 (common in bodies
+  from misbehaving remote servers) is replaced with U+FFFD by the `/1`
+  forms; the `/2` forms take an `t:invalid_utf8/0` policy and return
+  `{:ok, html}` or, under `:error`, `{:error, :invalid_utf8}`, so that
+  ingestion can decide whether to keep or drop such content.
+
+  `native_info/0` reports what the loaded library supports; see
+  `Baudrate.Native`.
+
+  A panic inside a NIF is returned as `{:error, {:panic, message}}` (the
+  `t:panic/0` type) instead of being raised.
+  """
+
+  use RustlerPrecompiled,
+      Baudrate.Native.precompiled(
+        crate: "baudrate_sanitizer",
+        load_data_fun: {Baudrate.Sanitizer.Log, :configured_level}
+      )
+
+  @type panic :: {:error, {:panic, String.t()}}
+
+  @typedoc "Counters returned by `stats/0`."
+  @type stats :: %{
+          calls: %{
+            sanitize_federation: non_neg_integer(),
+            sanitize_markdown: non_neg_integer(),
+            strip_tags: non_neg_integer(),
+            normalize_feed_html: non_neg_integer(),
+            emojify: non_neg_integer(),
+            extract_shortcodes: non_neg_integer(),
+            analyze_content: non_neg_integer()
+          },
+          bytes_processed: non_neg_integer(),
+          elements_stripped: non_neg_integer(),
+          errors: non_neg_integer()
+        }
+
+  @typedoc "The result of `analyze_content/1`."
+  @type analysis ::
+          {sanitized_html :: String.t(), plaintext :: String.t(), mentions :: [String.t()],
+           hashtags :: [String.t()], urls :: [String.t()], emoji_shortcodes :: [String.t()]}
+
+  @typedoc "Class names by tag, for `set_allowed_classes/2`."
+  @type class_allowlist :: %{(atom() | String.t()) => [String.t()]}
+
+  @typedoc "An allowlist as returned by `describe_policy/1`."
+  @type policy_description :: %{
+          tags: [String.t()],
+          generic_attributes: [String.t()],
+          attributes: %{String.t() => [String.t()]},
+          classes: %{String.t() => [String.t()]},
+          url_schemes: [String.t()],
+          relative_urls: :deny | :pass_through,
+          fragment_links: boolean(),
+          link_rel: String.t(),
+          clean_content_tags: [String.t()],
+          strip_comments: boolean()
+        }
+
+  @typedoc "Signals returned by `spam_score/1`."
+  @type spam_signals :: %{
+          score: float(),
+          links: non_neg_integer(),
+          domains: non_neg_integer(),
+          mentions: non_neg_integer(),
+          uppercase_ratio: float(),
+          longest_run: non_neg_integer(),
+          repeated_runs: non_neg_integer(),
+          emoji: non_neg_integer(),
+          emoji_density: float(),
+          invisible: non_neg_integer(),
+          length: non_neg_integer()
+        }
+
+  @typedoc "One image as reported by `image_accessibility/1`."
+  @type image_accessibility :: %{
+          src: String.t() | nil,
+          has_alt: boolean(),
+          alt_length: non_neg_integer(),
+          placeholder: boolean()
+        }
+
+  @typedoc "Structural diagnostics returned by `inspect_html/1`."
+  @type html_report :: %{
+          bytes: non_neg_integer(),
+          elements: %{String.t() => pos_integer()},
+          element_count: non_neg_integer(),
+          max_depth: non_neg_integer(),
+          attribute_count: non_neg_integer(),
+          max_attributes: non_neg_integer(),
+          max_attribute_bytes: non_neg_integer(),
+          total_attribute_bytes: non_neg_integer(),
+          comments: non_neg_integer(),
+          foreign_elements: non_neg_integer(),
+          prefixed_names: non_neg_integer(),
+          parse_errors: non_neg_integer(),
+          parse_error_messages: [String.t()]
+        }
+
+  @typedoc """
+  An outgoing status built by `build_status_html/4`: its HTML, and the
+  profile URLs, canonical hashtags, and emoji shortcodes it uses, for the
+  Note's `tag` array.
+  """
+  @type status_html :: %{
+          html: String.t(),
+          mentions: [String.t()],
+          tags: [String.t()],
+          emoji: [String.t()]
+        }
+
+  @typedoc """
+  The fields of a link preview for `build_preview_card/1`, as
+  `Baudrate.Content.LinkPreview` stores them; any may be missing or `nil`.
+  `image_path` is the proxied copy of the page's image.
+  """
+  @type preview_card :: %{
+          optional(:url) => String.t() | nil,
+          optional(:title) => String.t() | nil,
+          optional(:description) => String.t() | nil,
+          optional(:site_name) => String.t() | nil,
+          optional(:domain) => String.t() | nil,
+          optional(:image_path) => String.t() | nil,
+          optional(atom()) => term()
+        }
+
+  @typedoc """
+  A profile field built by `build_profile_field_html/2`: its name as plain
+  text, its value as HTML, and the URLs linked in the value.
+  """
+  @type profile_field :: %{name: String.t(), value: String.t(), links: [String.t()]}
+
+  @typedoc "A quote found by `extract_quote/1`."
+  @type quote :: %{url: String.t(), html: String.t()}
+
+  @typedoc """
+  A content-warning rule for `compile_cw_rules/1`. `keywords` and
+  `patterns` default to `[]`; several rules may share a category.
+  """
+  @type cw_rule :: %{
+          required(:category) => String.t(),
+          optional(:keywords) => [String.t()],
+          optional(:patterns) => [String.t()]
+        }
+
+  @typedoc "Rules compiled by `compile_cw_rules/1`."
+  @opaque cw_rules :: reference()
+
+  @typedoc "A chunked sanitization started by `stream_new/1`."
+  @opaque stream :: reference()
+
+  @typedoc "Whether invalid UTF-8 is replaced with U+FFFD or rejected."
+  @type invalid_utf8 :: :lossy | :error
+
+  @typedoc "The software that sent a document, for `sanitize_federation/2` quirks."
+  @type source :: :mastodon | :pleroma | :akkoma | :misskey | :friendica | :lemmy
+
+  @typedoc """
+  Hosts whose `http://` links and images are upgraded to `https://`: exact
+  host names, or `*.example.com` for every subdomain of `example.com`.
+  Matching ignores case; URLs with a port other than 80 or with userinfo
+  are never upgraded.
+  """
+  @type https_hosts :: [String.t()]
+
+  @typedoc """
+  Options for `sanitize_federation/2`: the `t:invalid_utf8/0` policy
+  (default `:lossy`), whether to return the plain text too (default
+  `false`), the `t:source/0` whose quirks to normalize (default `nil`), the
+  `t:https_hosts/0` to upgrade (default `[]`), whether to drop tracking
+  images and hidden elements (`drop_hidden`, default `false`), how many
+  images to keep (`max_images`, default `nil`, no limit), the longest
+  word left unbroken (`break_long_words`, default `nil`, no breaks), and
+  whether to mark `>` lines as greentext (`greentext`, default `false`).
+  """
+  @type federation_options :: %{
+          optional(:invalid_utf8) => invalid_utf8(),
+          optional(:plaintext) => boolean(),
+          optional(:source) => source() | nil,
+          optional(:https_hosts) => https_hosts(),
+          optional(:drop_hidden) => boolean(),
+          optional(:max_images) => non_neg_integer() | nil,
+          optional(:break_long_words) => pos_integer() | nil,
+          optional(:greentext) => boolean()
+        }
+
+  @typedoc """
+  Options for `sanitize_markdown/2` and `normalize_feed_html/2`: the
+  `t:invalid_utf8/0` policy (default `:lossy`), the `t:https_hosts/0` to
+  upgrade (default `[]`), `drop_hidden` (default `false`), `max_images`
+  (default `nil`), and `break_long_words` (default `nil`), as for
+  `sanitize_federation/2`.
   """
+  @type options :: %{
+          optional(:invalid_utf8) => invalid_utf8(),
+          optional(:https_hosts) => https_hosts(),
+          optional(:drop_hidden) => boolean(),
+          optional(:max_images) => non_neg_integer() | nil,
+          optional(:break_long_words) => pos_integer() | nil
+        }
 
-  use Rustler, otp_app: :baudrate, crate: "baudrate_sanitizer"
+  @doc """
+  Sanitize incoming federation HTML with a strict allowlist.
+
+  The Mastodon link-shortening classes `invisible` and `ellipsis` are only
+  kept on spans inside an `<a>`; elsewhere they could hide text.
 
-  @doc "Sanitize incoming federation HTML with a strict allowlist."
-  @spec sanitize_federation(String.t()) :: String.t()
+  Presentational elements are rewritten before the allowlist applies, here
+  and in `sanitize_markdown/1`: `<b>`, `<i>`, `<s>`/`<strike>`, and `<tt>`
+  become `<strong>`, `<em>`, `<del>`, and `<code>`, and `<center>` becomes
+  a paragraph, since no policy keeps alignment.
+
+  `<time>` keeps its `datetime` attribute, so that clients can localize
+  it, only when the value is an RFC 3339 date, date-time, or time (or a
+  year or month alone) that exists: `2024-05-01T12:00:00Z`, `2024-05-01`,
+  `12:30`. Other values are removed and the element is kept.
+  """
+  @spec sanitize_federation(binary()) :: String.t() | panic()
   def sanitize_federation(_html), do: :erlang.nif_error(:nif_not_loaded)
 
-  @doc "Sanitize Earmark-rendered Markdown HTML with a permissive allowlist."
-  @spec sanitize_markdown(String.t()) :: String.t()
+  @doc """
+  Like `sanitize_federation/1`, with an explicit `t:invalid_utf8/0` policy or
+  a `t:federation_options/0` map.
+
+  With `plaintext: true`, returns `{:ok, {html, plaintext}}`, where
+  `plaintext` is the text of the sanitized HTML as `analyze_content/1`
+  returns it, so notifications and search indexing need no second
+  `strip_tags/1` pass.
+
+  With `https_hosts`, `http://` links to those hosts become `https://`,
+  so that proxied previews avoid mixed content.
+
+  With `drop_hidden: true`, elements the allowlist would otherwise keep
+  while removing what hides them are dropped with their content: images
+  0 or 1 pixel wide or high (tracking pixels, by `width`/`height` or
+  `style`), elements with a `hidden` attribute (except `until-found`), and
+  elements styled `display: none`, `visibility: hidden`, `opacity: 0`, or
+  `font-size: 0`. Newsletters bridged from RSS or email are full of them.
+
+  With `max_images: n`, only the first `n` images are kept, for articles
+  that embed hundreds of them. Each image after becomes a link to its
+  `src`, labeled with its alt text or else the URL (the label alone when the
+  image is already linked), and images without a `src` are dropped; custom
+  emoji do not count. The number of images replaced is returned after the
+  HTML: `{:ok, {html, images_removed}}`, or with `plaintext: true`,
+  `{:ok, {html, plaintext, images_removed}}`. The federation policy keeps
+  no images at all, so there the limit only decides which become links.
+
+  With `break_long_words: n`, words longer than `n` characters (grapheme
+  clusters) get a break opportunity every `n` characters, so that long URLs
+  and compounds wrap on narrow screens: a soft hyphen (U+00AD) in words of
+  letters alone, and `<wbr>` in anything else. Text in `<code>` and `<pre>`
+  is left alone, and the plain text is that of the HTML without the breaks.
+  `<wbr>` written by the sender is kept in every case.
+
+  Spans with the `greentext` class, Pleroma's markup for lines starting
+  with `>`, are kept in every case. With `greentext: true`, such lines sent
+  as plain text are wrapped the same way: a line runs from the start of a
+  block or a `<br>` to the next `<br>` or block. Text in `<code>`, `<pre>`,
+  and links is left alone, and the plain text is unchanged.
+
+  With a `source`, markup of that software is normalized before the common
+  policy applies, which still cleans the result:
+
+    * `:pleroma`, `:akkoma` — nothing, as greentext spans are kept
+    * `:misskey` — MFM functions are reduced to their text
+    * `:friendica` — leftover BBCode (`[b]`, `[url=...]`, `[quote]`, ...)
+      becomes HTML; tags without a counterpart are dropped
+    * `:lemmy` — spoiler summaries become bold paragraphs, and `::: spoiler`
+      fences left as text become bold titles
+    * `:mastodon` — nothing
+
+  ## Examples
+
+      iex> html = "<p>a &amp; b</p><p>c</p>"
+      iex> Baudrate.Sanitizer.Native.sanitize_federation(html, %{plaintext: true})
+      {:ok, {"<p>a &amp; b</p><p>c</p>", "a & b\\n\\nc"}}
+  """
+  @spec sanitize_federation(binary(), invalid_utf8() | federation_options()) ::
+          {:ok,
+           String.t()
+           | {String.t(), String.t()}
+           | {String.t(), non_neg_integer()}
+           | {String.t(), String.t(), non_neg_integer()}}
+          | {:error, :invalid_utf8}
+          | panic()
+  def sanitize_federation(_html, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Apply `sanitize_federation/1` to every document in `htmls`, returning the
+  results in the same order.
+
+  Runs on a dirty CPU scheduler and spreads the documents over the NIF's
+  thread pool (`BAUDRATE_NIF_THREADS` threads, default one per core), for
+  ingesting a page of remote objects at once. The batch counts as a single
+  `sanitize_federation` call in `stats/0`.
+  """
+  @spec sanitize_federation_batch([binary()]) :: [String.t()] | panic()
+  def sanitize_federation_batch(_htmls), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sanitize every variant of an ActivityStreams `contentMap`, a map of
+  language tag to HTML, like `sanitize_federation/1`, and return
+  `{content_map, language}`.
+
+  Keys are kept only if they are well-formed BCP 47 language tags, in
+  canonical case (`"en_us"` becomes `"en-US"`); variants with invalid keys,
+  `nil` values, or nothing left after sanitizing are dropped. `language` is
+  the primary language: the variant with the most text, preferring any tag
+  over `"und"`, or `nil` if no variant is left.
+
+      iex> Baudrate.Sanitizer.Native.sanitize_content_map(%{
+      ...>   "EN" => "<p>Hello, world!</p><script>x</script>",
+      ...>   "ja" => "<p>こんにちは</p>",
+      ...>   "not a tag" => "<p>dropped</p>"
+      ...> })
+      {%{"en" => "<p>Hello, world!</p>", "ja" => "<p>こんにちは</p>"}, "en"}
+
+  The whole map counts as a single `sanitize_federation` call in `stats/0`.
+  """
+  @spec sanitize_content_map(%{optional(String.t()) => binary() | nil}) ::
+          {%{String.t() => String.t()}, String.t() | nil} | panic()
+  def sanitize_content_map(_content_map), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Queue `html` for `sanitize_federation/1` on the NIF's thread pool and
+  return `:ok` immediately.
+
+  The result is sent to `caller` as `{ref, {:ok, html}}`, or
+  `{ref, {:error, {:panic, message}}}`, where `ref` is any term (usually
+  from `make_ref/0`) used to match replies. Large imports such as account
+  migration archives can queue thousands of documents and collect the
+  replies as they arrive, without blocking a scheduler per document.
+
+      ref = make_ref()
+      :ok = Native.sanitize_async(html, self(), ref)
+
+      receive do
+        {^ref, {:ok, sanitized}} -> sanitized
+      end
+  """
+  @spec sanitize_async(binary(), pid(), term()) :: :ok | panic()
+  def sanitize_async(_html, _caller, _ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sanitize Earmark-rendered Markdown HTML with a permissive allowlist.
+
+  Same-document links such as `<a href="#section-2">` are kept for an
+  article's table of contents and footnotes, with heading `id`s for them to
+  land on. Both are prefixed with `user-content-` (`#user-content-section-2`,
+  `id="user-content-section-2"`), so that a post cannot take an id the page
+  itself uses; values already carrying the prefix are kept as they are.
+  Fragments other than letters, digits, and `-_.:%` are removed, as are
+  `id`s anywhere but on headings.
+  """
+  @spec sanitize_markdown(binary()) :: String.t() | panic()
   def sanitize_markdown(_html), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Like `sanitize_markdown/1`, with an explicit `t:invalid_utf8/0` policy or
+  an `t:options/0` map.
+
+  With `max_images`, returns `{:ok, {html, images_removed}}`, as
+  `sanitize_federation/2` does.
+
+  ## Examples
+
+      iex> html = ~s(<img src="http://media.example/cat.png" alt="cat">)
+      iex> Baudrate.Sanitizer.Native.sanitize_markdown(html, %{https_hosts: ["*.example"]})
+      {:ok, ~s(<img src="https://media.example/cat.png" alt="cat">)}
+
+      iex> html = ~s(<img src="/1.png"><img src="/2.png" alt="two">)
+      iex> Baudrate.Sanitizer.Native.sanitize_markdown(html, %{max_images: 1})
+      {:ok, {~s(<img src="/1.png"><a href="/2.png" rel="nofollow noopener">two</a>), 1}}
+  """
+  @spec sanitize_markdown(binary(), invalid_utf8() | options()) ::
+          {:ok, String.t() | {String.t(), non_neg_integer()}}
+          | {:error, :invalid_utf8}
+          | panic()
+  def sanitize_markdown(_html, _options), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Strip all HTML tags, preserving only text content.
 
@@ -37,9 +456,14 @@ defmodule Baudrate.Sanitizer.Native do
   Interior `&nbsp;` entities remain (as literal `&nbsp;` strings) and can be
   decoded to spaces by `decode_html_entities/1`.
   """
-  @spec strip_tags(String.t()) :: String.t()
+  @spec strip_tags(binary()) :: String.t() | panic()
   def strip_tags(_html), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc "Like `strip_tags/1`, with an explicit `t:invalid_utf8/0` policy."
+  @spec strip_tags(binary(), invalid_utf8()) ::
+          {:ok, String.t()} | {:error, :invalid_utf8} | panic()
+  def strip_tags(_html, _invalid_utf8), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Sanitize and normalize HTML from an RSS/Atom feed body.
 
@@ -54,33 +478,667 @@ defmodule Baudrate.Sanitizer.Native do
 
   Use this instead of `sanitize_markdown/1` when processing feed content.
   """
-  @spec normalize_feed_html(String.t()) :: String.t()
+  @spec normalize_feed_html(binary()) :: String.t() | panic()
   def normalize_feed_html(_html), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  Decodes common HTML entities in `strip_tags/1` output.
+  Like `normalize_feed_html/1`, with an explicit `t:invalid_utf8/0` policy
+  or an `t:options/0` map.
+  """
+  @spec normalize_feed_html(binary(), invalid_utf8() | options()) ::
+          {:ok, String.t() | {String.t(), non_neg_integer()}}
+          | {:error, :invalid_utf8}
+          | panic()
+  def normalize_feed_html(_html, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Replace `:shortcode:` tokens in already-sanitized HTML with custom emoji images.
+
+  `emoji` maps shortcodes (without colons) to HTTP(S) image URLs. Matching
+  tokens in text nodes become
+  `<img class="custom-emoji" src="..." alt=":code:" title=":code:">`, which
+  `sanitize_markdown/1` preserves. Text inside `<code>` / `<pre>` is never
+  touched, and shortcodes missing from the map (or mapped to a non-HTTP(S)
+  URL) are left as text.
+  """
+  @spec emojify(String.t(), %{String.t() => String.t()}) :: String.t() | panic()
+  def emojify(_html, _emoji), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the distinct `:shortcode:` names used in `text`, without colons and
+  in order of first appearance.
+
+  Accepts sanitized HTML or raw composer text. Shortcodes inside
+  `<code>` / `<pre>` elements and Markdown backtick code spans / fences are
+  ignored. Used to build the `Emoji` tags of outgoing ActivityPub objects.
+  """
+  @spec extract_shortcodes(String.t()) :: [String.t()] | panic()
+  def extract_shortcodes(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the sanitizer's load counters, totals since the NIF library was loaded.
+
+    * `calls` — calls per NIF (both arities of a function are counted together)
+    * `bytes_processed` — total input size
+    * `elements_stripped` — elements removed by the sanitizers, counted by
+      start tag
+    * `errors` — calls that returned an error or panicked
+
+  Counters only grow; compute rates from the difference between two polls.
+  """
+  @spec stats() :: stats() | panic()
+  def stats, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Describe the allowlist enforced by `sanitize_federation/1` (`:federation`)
+  or by `sanitize_markdown/1` and `normalize_feed_html/1` (`:markdown`).
+
+    * `tags` — elements kept; any other element is unwrapped, keeping its text
+    * `generic_attributes` — attributes allowed on every tag
+    * `attributes` — further attributes allowed per tag
+    * `classes` — `class` values allowed per tag; for `code`, the patterns
+      of `set_code_classes/2`, where `*` stands for a language name
+    * `url_schemes` — schemes allowed in `href` / `src`
+    * `relative_urls` — `:deny` drops relative URLs, `:pass_through` keeps them
+    * `fragment_links` — whether `#fragment` links and heading `id`s are
+      kept, prefixed with `user-content-`
+    * `link_rel` — the `rel` value set on every link
+    * `clean_content_tags` — elements removed together with their content
+
+  The data is the same the sanitizer is configured from, so it cannot drift
+  from what is enforced; `attributes` and `classes` include what
+  `set_allowed_classes/2` allowed.
+  """
+  @spec describe_policy(:federation | :markdown) :: policy_description() | panic()
+  def describe_policy(_policy), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Allow `classes`, a map of tag to class names, on the elements of a
+  built-in policy in addition to its own classes, such as the markup of new
+  fediverse software. Replaces what a previous call allowed for the policy;
+  `%{}` removes it. The allowlist is global and applies to every sanitizer
+  using the policy, streams included.
+
+      :ok = Native.set_allowed_classes(:federation, %{p: ["quote-inline"]})
+
+  Returns `{:error, {:tag_not_allowed, tag}}` for a tag the policy does not
+  keep and `{:error, {:invalid_class, class}}` for an empty class or one
+  with whitespace, leaving the allowlist unchanged.
+  """
+  @spec set_allowed_classes(:federation | :markdown, class_allowlist()) ::
+          :ok | {:error, {:tag_not_allowed | :invalid_class, String.t()}} | panic()
+  def set_allowed_classes(_policy, _classes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Replace the patterns that find the language of a `<code>` element in its
+  classes, or restore the policy's own with `nil`: `["language-*"]` for
+  `:markdown`, none for `:federation`.
+
+  A pattern is class names, one of them ending in `*`: the others must all
+  be present, and the class starting with that prefix names the language,
+  of `[A-Za-z0-9_+-]` characters. The first pattern that matches wins, and
+  its language is kept as `language-*`; other classes are dropped unless
+  `set_allowed_classes/2` allows them.
+
+      :ok = Native.set_code_classes(:federation, ["language-*", "sourceCode *"])
+      Native.sanitize_federation(~s(<code class="sourceCode rust">x</code>))
+      #=> ~s(<code class="language-rust">x</code>)
+
+  Like `set_allowed_classes/2`, this is global. Returns
+  `{:error, {:invalid_pattern, pattern}}` for a pattern without exactly one
+  class ending in `*`, leaving the patterns unchanged.
+  """
+  @spec set_code_classes(:federation | :markdown, [String.t()] | nil) ::
+          :ok | {:error, {:invalid_pattern, String.t()}} | panic()
+  def set_code_classes(_policy, _patterns), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Start sanitizing a document that arrives in chunks, such as a
+  multi-megabyte article import, with the `:federation` or `:markdown`
+  rules.
+
+  Each `stream_push/2` hands a chunk to a thread owned by the stream;
+  chunks may split tags or UTF-8 sequences anywhere. Only a few chunks are
+  queued ahead of the thread, so the caller never holds the whole document
+  as one binary. `stream_finish/1` cleans the concatenation with the same
+  pipeline as `sanitize_federation/1` or `sanitize_markdown/1`, returning
+  the same HTML, with invalid UTF-8 replaced by U+FFFD, and logging and
+  counting the same removals.
+
+      {:ok, stream} = Native.stream_new(:federation)
+      Enum.each(File.stream!(path, 64 * 1024), &Native.stream_push(stream, &1))
+      {:ok, html} = Native.stream_finish(stream)
+
+  A stream that is garbage collected unfinished stops its thread. At most
+  64 streams run at once; until one is finished or collected,
+  `stream_new/1` returns `{:error, :busy}`. Returns
+  `{:error, :stream_failed}` if the thread cannot be started.
+  """
+  @spec stream_new(:federation | :markdown) ::
+          {:ok, stream()} | {:error, :busy | :stream_failed} | panic()
+  def stream_new(_policy), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Queue `chunk` for the stream's thread, blocking while the queue is full.
+
+  Returns `:ok`, or `{:error, :closed}` after `stream_finish/1`.
+  """
+  @spec stream_push(stream(), binary()) :: :ok | {:error, :closed} | panic()
+  def stream_push(_stream, _chunk), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  End the input and return `{:ok, html}`.
+
+  Returns `{:error, :closed}` if the stream was already finished.
+  """
+  @spec stream_finish(stream()) ::
+          {:ok, String.t()} | {:error, :closed | :stream_failed} | panic()
+  def stream_finish(_stream), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Send future sanitizer log events to `pid` as
+  `{:baudrate_sanitizer_log, event}`, or buffer them for `drain_log/0` when
+  `pid` is `nil`. See `Baudrate.Sanitizer.Log`.
+  """
+  @spec set_log_target(pid() | nil) :: :ok | panic()
+  def set_log_target(_pid), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc "Return and clear the buffered log events, oldest first (at most 1000)."
+  @spec drain_log() :: [Baudrate.Sanitizer.Log.event()] | panic()
+  def drain_log, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc "The log level the NIF was loaded with."
+  @spec log_level() :: :off | :warning | :info | :debug | panic()
+  def log_level, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decodes the HTML entities in `strip_tags/1` output.
 
   Handles:
   - The five XML entities that Ammonia re-encodes when serializing plain-text
-    output (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`/`&#39;`)
+    output (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`/`&#39;`), and every
+    other character reference, via `decode_entities/1`
   - `&nbsp;` — decoded as a regular space (Ammonia preserves `&nbsp;` as a
     literal entity in `strip_tags/1` output; leading/trailing ones are already
     trimmed by `strip_tags/1` itself)
 
   Call this after `strip_tags/1` so the result is plain text that Phoenix HEEx
   templates can escape correctly (without double-encoding `&` into `&amp;amp;`).
+  If `decode_entities/1` panics, the panic is logged and `""` returned.
   """
-  @entity_pattern ~r/&(amp|lt|gt|quot|apos|#39|nbsp);/
-
   @spec decode_html_entities(String.t()) :: String.t()
   def decode_html_entities(str) when is_binary(str) do
-    Regex.replace(@entity_pattern, str, fn
-      _, "amp" -> "&"
-      _, "lt" -> "<"
-      _, "gt" -> ">"
-      _, "quot" -> "\""
-      _, "nbsp" -> " "
-      _, _ -> "'"
-    end)
+    case decode_entities(str) do
+      decoded when is_binary(decoded) ->
+        String.replace(decoded, "\u00A0", " ")
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("decode_entities/1", message)
+        ""
+    end
+  end
+
+  @doc """
+  Returns the plain text of `html`: `strip_tags/1`, then
+  `decode_html_entities/1`. If `strip_tags/1` panics, the panic is logged
+  and `""` returned.
+  """
+  @spec html_to_text(binary()) :: String.t()
+  def html_to_text(html) when is_binary(html) do
+    case strip_tags(html) do
+      text when is_binary(text) ->
+        decode_html_entities(text)
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("strip_tags/1", message)
+        ""
+    end
   end
+
+  @doc """
+  Return the crate version, enabled Cargo features, and the `{name, arity}`
+  of every NIF in the loaded library.
+  """
+  @spec native_info() :: Baudrate.Native.info() | panic()
+  def native_info, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Compute cheap spam signals for HTML or plain text in a single walk.
+
+    * `links` — `<a href>` links other than mentions and hashtags, plus bare
+      `http(s)://` URLs in text; `domains` — their distinct hosts
+    * `mentions` — mention links plus `@name` tokens in text
+    * `uppercase_ratio` — uppercase letters as a share of all letters
+    * `longest_run`, `repeated_runs` — the longest run of one repeated
+      character, and the number of runs of five or more
+    * `emoji`, `emoji_density` — emoji characters and custom emoji images,
+      and their share of the `length` visible characters
+    * `invisible` — zero-width, bidi-control, and tag characters
+
+  `score` combines them from 0.0 to 1.0: links beyond two, domains beyond
+  one, mentions beyond three, shouting (over 70% capitals in at least 20
+  letters), repeated runs, emoji density over 30%, and any invisible
+  characters each add a capped amount. Script and style contents are
+  ignored.
+  """
+  @spec spam_score(binary()) :: spam_signals() | panic()
+  def spam_score(_html_or_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return Mastodon-style link text markup for `url`, to place inside its
+  `<a href>` in outgoing content.
+
+  The scheme (and any `www.`) goes in an `invisible` span, the next 30
+  characters — starting at the host — are displayed, and the rest goes in a
+  second `invisible` span; when something was cut, the displayed span has the
+  `ellipsis` class, which renders a trailing `…`. Copying the text still
+  yields the full URL. All parts are HTML-escaped, and the output passes
+  `sanitize_federation/1` unchanged.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.format_link_label("https://example.com/a")
+      ~s(<span class="invisible">https://</span><span>example.com/a</span><span class="invisible"></span>)
+  """
+  @spec format_link_label(String.t()) :: String.t() | panic()
+  def format_link_label(_url), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Build the HTML of an outgoing status from composer plain text.
+
+  The text is HTML-escaped and split into `<p>` paragraphs at blank lines,
+  with `<br>` for the line breaks within one. `http(s)://` URLs become
+  links labelled as `format_link_label/1` does; `@user` and `@user@host`
+  become `h-card` mention links when `mentions`, keyed by the lowercased
+  handle without the leading `@`, has their profile URL, and stay text
+  otherwise; `#tag` becomes a hashtag link to `tag_base` followed by the
+  tag's canonical form, as `Baudrate.Text.Native.normalize_hashtag/1`
+  computes it, with the tag as written for its text. `:shortcode:` emoji
+  stay text, as in Mastodon's markup, and those listed in `emoji` are
+  returned.
+
+      iex> Baudrate.Sanitizer.Native.build_status_html(
+      ...>   "Hi @alice! #Café",
+      ...>   %{"alice" => "https://example.com/users/alice"},
+      ...>   "https://example.com/tags/",
+      ...>   []
+      ...> ).tags
+      ["cafe"]
+
+  The result passes `sanitize_federation/1` unchanged.
+  """
+  @spec build_status_html(binary(), %{String.t() => String.t()}, String.t(), [String.t()]) ::
+          status_html() | panic()
+  def build_status_html(_text, _mentions, _tag_base, _emoji),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sanitize `html` like `sanitize_federation/1` and extract what ingestion
+  needs from the result, in one call.
+
+  Returns `{sanitized_html, plaintext, mentions, hashtags, urls, emoji_shortcodes}`:
+
+    * `sanitized_html` — the same as `sanitize_federation/1` returns
+    * `plaintext` — the decoded text, trimmed, with a newline for each
+      `<br>` or list item, a blank line between paragraphs and other blocks,
+      and no-break spaces turned into spaces
+    * `mentions` — `href`s of `mention` links
+    * `hashtags` — canonical forms of the texts of `hashtag` links, as
+      `Baudrate.Text.Native.normalize_hashtag/1` computes them, so that
+      they match local tags
+    * `urls` — `href`s of all other links
+    * `emoji_shortcodes` — as `extract_shortcodes/1` returns for the result
+
+  Lists are in order of first appearance, without duplicates. The sanitized
+  fragment is parsed once for all of them, instead of once per extraction.
+  Counted under `analyze_content` in `stats/0`.
+  """
+  @spec analyze_content(binary()) :: analysis() | panic()
+  def analyze_content(_html), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Derive a title for an HTML body, for objects that arrive without a `name`.
+
+  The title is the text of the first heading (`<h1>`–`<h6>`), or else the
+  first sentence of the first paragraph. A body without `<p>` elements is
+  taken as plain text, and its first line stands in for the paragraph.
+  Entities are decoded, whitespace is collapsed, a single closing period is
+  dropped, and the hidden `invisible` parts of Mastodon link labels are left
+  out.
+
+  Titles longer than 80 graphemes are cut at the last word boundary, or
+  between graphemes for text without spaces, and end in `…`. Returns `nil`
+  when the body has no text.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.derive_title("<p>Fish &amp; chips. Mostly chips.</p>")
+      "Fish & chips"
+  """
+  @spec derive_title(binary()) :: String.t() | nil | panic()
+  def derive_title(_html), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reduce `text` to one line of plain text, for short fields that are shown
+  as text and never as HTML: poll options, content warnings, and profile
+  field names.
+
+  All tags are stripped (`<script>` and `<style>` with their content), and
+  entities are decoded, so the result must be escaped when rendered. Block
+  elements, `<br>`, and line breaks become spaces and whitespace runs are
+  collapsed and trimmed. Control characters and bidi embedding, override,
+  and isolate controls are removed. `:shortcode:` tokens are kept for emoji
+  rendering.
+
+  The result is cut to at most `max_graphemes` graphemes, without an
+  ellipsis.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.sanitize_inline("<b>Yes</b> &amp; no :blobcat:", 100)
+      "Yes & no :blobcat:"
+  """
+  @spec sanitize_inline(binary(), non_neg_integer()) :: String.t() | panic()
+  def sanitize_inline(_text, _max_graphemes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Report the alt text of every image in `html`, in order, so the composer
+  can warn about undescribed images and admin stats can track coverage.
+
+  Each image is a map of:
+
+    * `src` — the `src` attribute, or `nil`
+    * `has_alt` — whether `alt` has any non-blank text; `alt=""` (a
+      decorative image) counts as no alt text
+    * `alt_length` — the trimmed alt text's length in graphemes
+    * `placeholder` — whether the alt text only looks like a description:
+      a file name (`photo.png`, or the `src`'s own file name with or without
+      its extension), a camera or screenshot default name (`IMG_1234`,
+      `PXL_20240101_123456`, `Screenshot 2024-01-01 at 10.00.00`), or a
+      generic word such as `image` or `photo`
+
+  Custom emoji (`img.custom-emoji`) are not content images and are left
+  out.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.image_accessibility(~s(<img src="/IMG_1.jpg" alt="IMG_1">))
+      [%{src: "/IMG_1.jpg", has_alt: true, alt_length: 5, placeholder: true}]
+  """
+  @spec image_accessibility(binary()) :: [image_accessibility()] | panic()
+  def image_accessibility(_html), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Handle the MFM (Misskey Flavored Markdown) functions in sanitized `html`,
+  such as `$[jelly text]`, `$[x2 text]`, or `$[flip.h,v text]`, which
+  Misskey and its forks federate as literal text.
+
+    * `:html` — a function with an `mfm-*` class in the Markdown allowlist
+      becomes `<span class="mfm-jelly">text</span>`; flags among its
+      arguments add classes (`mfm-flip mfm-flip-h mfm-flip-v`), and valued
+      arguments (`speed=2s`) are dropped
+    * `:strip` — every function is reduced to its text
+
+  In both modes, functions without a class (`fg`, `position`, `ruby`, ...),
+  functions whose text crosses a tag, and functions nested more than 32
+  deep are reduced to their text. A `$[` without its closing `]` is kept
+  as it is, and tags are never changed.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.render_mfm("<p>$[x2 $[jelly hi]]</p>", :html)
+      ~s(<p><span class="mfm-x2"><span class="mfm-jelly">hi</span></span></p>)
+
+      iex> Baudrate.Sanitizer.Native.render_mfm("<p>$[x2 $[jelly hi]]</p>", :strip)
+      "<p>hi</p>"
+  """
+  @spec render_mfm(binary(), :html | :strip) :: String.t() | panic()
+  def render_mfm(_html, _mode), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Report the structure of `html` as the parser builds it, without
+  sanitizing or changing it, so that moderators can spot servers that send
+  bizarre markup. The map holds:
+
+    * `bytes` — the input size
+    * `elements` — the count of each element name; prefixed names such as
+      Word's `o:p` are kept whole
+    * `element_count` and `max_depth` — all elements, and the deepest
+      nesting, with top-level elements at depth 1
+    * `attribute_count`, `max_attributes` (on one element),
+      `max_attribute_bytes` (the longest value), and
+      `total_attribute_bytes`
+    * `comments`
+    * `foreign_elements` — elements in the SVG or MathML namespace
+    * `prefixed_names` — element and attribute names with a namespace
+      prefix (`o:p`, `xmlns:v`)
+    * `parse_errors` — how many errors the parser recovered from
+      (misnested or stray tags, elements left open at the end of the
+      body), and `parse_error_messages`, up to 10 distinct ones
+
+  Runs on a dirty CPU scheduler, as the documents worth inspecting are
+  often large.
+
+  ## Examples
+
+      iex> report = Baudrate.Sanitizer.Native.inspect_html("<p><b>a</p><svg></svg>")
+      iex> {report.elements, report.max_depth, report.foreign_elements}
+      {%{"p" => 1, "b" => 1, "svg" => 1}, 2, 1}
+  """
+  @spec inspect_html(binary()) :: html_report() | panic()
+  def inspect_html(_html), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render Open Graph and Twitter Card tags as `<meta>` elements, one per
+  line, ready to embed in `<head>`.
+
+  `tags` is a list of `{property, content}` pairs, rendered in order, or a
+  map, rendered sorted by property. `twitter:*` and `profile:*` tags use
+  `name`, all others `property`.
+
+    * URL contents (`og:url`, `og:image`, `twitter:image`, `*:secure_url`,
+      ...) are kept as written, and skipped if blank or if they contain
+      whitespace or control characters.
+    * Other contents are reduced to one line of plain text as
+      `sanitize_inline/2` does: tags stripped, entities decoded, whitespace
+      collapsed, control characters removed. Descriptions are cut to 200
+      graphemes and other text to 300, with `…` marking the cut.
+    * Every content is escaped for a double-quoted attribute.
+
+  Tags with a `nil` or empty content, or a property that is not lowercase
+  ASCII letters, digits, and `:_-.`, are skipped.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.build_og_meta([
+      ...>   {"og:title", ~s(Say "hi"\n<b>now</b>)},
+      ...>   {"og:description", nil},
+      ...>   {"twitter:card", "summary"}
+      ...> ])
+      ~s(<meta property="og:title" content="Say &quot;hi&quot; now">\n) <>
+        ~s(<meta name="twitter:card" content="summary">)
+  """
+  @spec build_og_meta([{String.t(), String.t() | nil}] | %{String.t() => String.t() | nil}) ::
+          String.t() | panic()
+  def build_og_meta(_tags), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Build the HTML of a link preview card, for feeds and messages rendered
+  without the `link_preview` component.
+
+  The card is a `<blockquote>` with a paragraph linking to `url`, holding
+  the image, if any, and the title in `<strong>`; a paragraph with the
+  description; and one with the site name, else `domain`, else the URL's
+  host. Without a title the URL is shown instead.
+
+    * Text fields are reduced to one line of plain text as
+      `sanitize_inline/2` does, so titles with entities or markup in them
+      are escaped exactly once. Titles and descriptions are cut to 300
+      graphemes, site names to 100, with `…` marking the cut.
+    * The image is `image_path`, a path on this host or an `http(s)` URL,
+      with empty alt text, as the title beside it names the link. The
+      page's own `image_url` is never used, so that browsers only load the
+      proxied copy.
+
+  The markup uses only what the Markdown policy allows and is serialized
+  as the sanitizer serializes, so `sanitize_markdown/1` returns it
+  unchanged. Returns `{:error, :invalid_url}` if `url` is not an `http(s)`
+  URL. A `Baudrate.Content.LinkPreview` can be passed as it is.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.build_preview_card(%{
+      ...>   url: "https://example.com/",
+      ...>   title: "Tom &amp; Jerry"
+      ...> })
+      {:ok,
+       ~s(<blockquote><p><a href="https://example.com/" rel="nofollow noopener">) <>
+         ~s(<strong>Tom &amp; Jerry</strong></a></p><p>example.com</p></blockquote>)}
+  """
+  @spec build_preview_card(preview_card()) ::
+          {:ok, String.t()} | {:error, :invalid_url} | panic()
+  def build_preview_card(_card), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Build a profile field, for an actor's `PropertyValue` attachments, from
+  the `name` and `value` the user wrote.
+
+  Both are reduced to one line of plain text as `sanitize_inline/2` does,
+  so markup in them is dropped. In the value, `http(s)` URLs become links
+  with `rel="me nofollow noopener"` and the label `format_link_label/1`
+  builds, as Mastodon renders them; the rest is escaped. Mastodon shows a
+  link as verified when the linked page links back to the profile with
+  `rel="me"`, which `Baudrate.HtmlParser.Native.extract_rel_me/2` checks
+  for each of the returned `links`.
+
+  The value is meant to be sent as it is: `sanitize_federation/1` sets its
+  own `rel` on every link, which drops `me`.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.build_profile_field_html("Site", "https://example.com")
+      %{
+        name: "Site",
+        value:
+          ~s(<a href="https://example.com" rel="me nofollow noopener">) <>
+            ~s(<span class="invisible">https://</span><span>example.com</span>) <>
+            ~s(<span class="invisible"></span></a>),
+        links: ["https://example.com"]
+      }
+  """
+  @spec build_profile_field_html(String.t(), String.t()) :: profile_field() | panic()
+  def build_profile_field_html(_name, _value), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Find the post that `html` quotes, as software without native quote posts
+  writes it into the content, and return its URL with the content without
+  that line.
+
+  Recognized, in order:
+
+    * an element with the `quote-inline` class (Mastodon, Fedibird), whose
+      first `http(s)` link is the quoted post;
+    * a trailing `RE:` or `QT:` followed by a link as the last thing in the
+      post (Misskey, Akkoma, Pleroma, Fedibird), possibly inside the last
+      paragraph and after line breaks.
+
+  The line is removed together with the line breaks before it, and its
+  paragraph if nothing else is left in it; the rest of `html` is kept as
+  parsed. Returns `nil` if `html` quotes nothing. Callers that only want
+  the URL can keep their original content.
+
+  The `quote-inline` class does not survive sanitizing, so call this on
+  content as received and sanitize the returned `html`.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.extract_quote(
+      ...>   ~s(<p>So true<br><br>RE: <a href="https://misskey.example/notes/9a">link</a></p>)
+      ...> )
+      %{url: "https://misskey.example/notes/9a", html: "<p>So true</p>"}
+
+      iex> Baudrate.Sanitizer.Native.extract_quote("<p>No quote here</p>")
+      nil
+  """
+  @spec extract_quote(binary()) :: quote() | nil | panic()
+  def extract_quote(_html), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Compile content-warning rules for `suggest_cw/2`.
+
+  Each rule names a `category` and lists `keywords` and `patterns`:
+
+    * keywords match whole words, case-insensitively, after the text is
+      reduced to plain text. Keywords in scripts written without spaces
+      (Chinese, Japanese, Thai, ...) match anywhere in a word.
+    * patterns are regular expressions in the Rust `regex` syntax, matched
+      case-insensitively. They run in time linear in the text, so
+      instance admins cannot configure a catastrophic backtrack.
+
+  All keywords are compiled into one Aho-Corasick automaton and all
+  patterns into one regex set, so a post is checked in two passes however
+  many rules there are. Compile once when the configuration changes and
+  keep the result, for example in `:persistent_term`.
+
+  Returns `{:ok, rules}`, `{:error, {:invalid_pattern, pattern}}` for a
+  pattern that does not compile (or compiles too large), or
+  `{:error, {:rules_too_large, message}}` when the rules only fit one at a
+  time.
+  """
+  @spec compile_cw_rules([cw_rule()]) ::
+          {:ok, cw_rules()}
+          | {:error, {:invalid_pattern | :rules_too_large, String.t()}}
+          | panic()
+  def compile_cw_rules(_rules), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the categories of `rules` matched by `text`, HTML or plain text,
+  each once and in the order the categories were first given.
+
+  ## Examples
+
+      iex> {:ok, rules} =
+      ...>   Baudrate.Sanitizer.Native.compile_cw_rules([
+      ...>     %{category: "politics", keywords: ["election"]},
+      ...>     %{category: "spoilers", patterns: ["season [0-9]+ finale"]}
+      ...>   ])
+      iex> Baudrate.Sanitizer.Native.suggest_cw(rules, "<p>Season 3 finale, election</p>")
+      ["politics", "spoilers"]
+      iex> Baudrate.Sanitizer.Native.suggest_cw(rules, "Electionless")
+      []
+  """
+  @spec suggest_cw(cw_rules(), binary()) :: [String.t()] | panic()
+  def suggest_cw(_rules, _text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decode the character references in `text` as the HTML tokenizer does in
+  text content, using html5ever's entity tables. Tags are left as they are.
+
+  All 2,231 named references are known; the longest name wins, and legacy
+  names such as `&amp` and `&copy` decode without their `;`. Numeric
+  references to NUL, surrogates, or beyond U+10FFFF become U+FFFD, and
+  `&#128;`–`&#159;` map as Windows-1252 (`&#150;` is `–`). A `&` that starts
+  no reference is kept. Each reference is decoded once, so `&amp;lt;` gives
+  `&lt;`.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.decode_entities("&copy; 2024 &amp; &#x1F600; &#150; &bogus;")
+      "© 2024 & 😀 – &bogus;"
+  """
+  @spec decode_entities(binary()) :: String.t() | panic()
+  def decode_entities(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Escape `text` for HTML text content and quoted attribute values: `&`,
+  `<`, `>`, `"`, and `'` become `&amp;`, `&lt;`, `&gt;`, `&quot;`, and
+  `&#39;`, as `Phoenix.HTML.html_escape/1` writes them.
+
+  ## Examples
+
+      iex> Baudrate.Sanitizer.Native.escape_html(~s(<a title="it's">))
+      "&lt;a title=&quot;it&#39;s&quot;&gt;"
+  """
+  @spec escape_html(binary()) :: String.t() | panic()
+  def escape_html(_text), do: :erlang.nif_error(:nif_not_loaded)
 end
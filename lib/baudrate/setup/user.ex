@@ -298,8 +298,7 @@ defmodule Baudrate.Setup.User do
   defp sanitize_display_name(name) when is_binary(name) do
     result =
       name
-      |> Baudrate.Sanitizer.Native.strip_tags()
-      |> Baudrate.Sanitizer.Native.decode_html_entities()
+      |> Baudrate.Sanitizer.Native.html_to_text()
       # Remove control characters
       |> String.replace(~r/[\x00-\x08\x0B\x0C\x0E-\x1F\x7F]/, "")
       # Remove Unicode bidi override characters
@@ -0,0 +1,673 @@
+defmodule Baudrate.Text.Native do
+  @moduledoc """
+  Rustler NIF bindings to the `baudrate_text` Rust crate.
+
+  Provides Unicode-aware text utilities that are impractical to get right in
+  pure Elixir:
+
+    * `casefold/1` — Unicode full case folding (for storage / index keys)
+    * `caseless_equal/2` — canonical caseless comparison of two strings
+    * `normalize_hashtag/1` — a hashtag's canonical form, as Mastodon
+      computes it, and its display spelling
+    * `bidi_isolate/2` — neutralize directional overrides in untrusted text
+    * `replace_shortcodes/1` — convert `:joy:`-style shortcodes to Unicode emoji
+    * `parse_acct/1` — normalize a WebFinger account identifier
+    * `same_actor?/2` / `actor_url_forms/1` — match mentions written as
+      accounts or profile URLs
+    * `simhash/1` / `hamming_distance/2` — near-duplicate fingerprints
+    * `wrap_text/2` — reflow text to a fixed column width
+    * `figlet/2` — FIGlet banner text
+    * `uudecode/1` / `uuencode/2` — uuencoded attachments in archived messages
+    * `parse_ftn_message/1` — split a FidoNet message into body and control lines
+    * `to_flowed/2` / `from_flowed/2` — RFC 3676 `format=flowed` text
+    * `split_reply/1` — separate a mail reply's new text, quotes, and signature
+    * `parse_ics/1` / `build_ics/1` — iCalendar events, for ActivityPub `Event`s,
+      with `utc_ics_event/1` to convert zoned times for writing
+    * `build_vcard/1` — a downloadable vCard for a profile
+    * `parse_front_matter/1` — YAML / TOML metadata atop a Markdown article
+    * `annotate_code_fences/1` — language, filename, and highlighted lines of
+      Markdown code blocks
+    * `graphemes/1` / `grapheme_length/1` — grapheme clusters, as the NIFs segment them
+
+  `native_info/0` reports what the loaded library supports; see
+  `Baudrate.Native`.
+
+  A panic inside a NIF is returned as `{:error, {:panic, message}}` (the
+  `t:panic/0` type) instead of being raised.
+  """
+
+  use RustlerPrecompiled, Baudrate.Native.precompiled(crate: "baudrate_text")
+
+  @type panic :: {:error, {:panic, String.t()}}
+
+  @typedoc "A FIGlet font embedded in the library."
+  @type figlet_font :: :standard | :small | :slant | :big
+
+  @typedoc "A FidoNet message as returned by `parse_ftn_message/1`."
+  @type ftn_message :: %{
+          area: String.t() | nil,
+          kludges: [{String.t(), String.t()}],
+          charset: String.t() | nil,
+          body: binary(),
+          tearline: binary() | nil,
+          origin: binary() | nil,
+          address: String.t() | nil,
+          seen_by: [String.t()]
+        }
+
+  @typedoc """
+  A date or date-time as an ISO 8601 string: `"2024-05-01"` (all day),
+  `"2024-05-01T18:00:00Z"` (UTC), or `"2024-05-01T18:00:00"` (in the event's
+  `timezone`, or floating without one).
+  """
+  @type ics_time :: String.t()
+
+  @typedoc """
+  An `RRULE`. Parts without a key of their own (`BYSETPOS`, `WKST`,
+  `BYHOUR`, ...) are kept in `rest` as `{name, value}`.
+  """
+  @type ics_recurrence :: %{
+          freq: :secondly | :minutely | :hourly | :daily | :weekly | :monthly | :yearly,
+          interval: pos_integer(),
+          count: non_neg_integer() | nil,
+          until: ics_time() | nil,
+          by_day: [String.t()],
+          by_month_day: [integer()],
+          by_month: [1..12],
+          rest: [{String.t(), String.t()}]
+        }
+
+  @typedoc "An event as returned by `parse_ics/1` and accepted by `build_ics/1`."
+  @type ics_event :: %{
+          uid: String.t() | nil,
+          summary: String.t() | nil,
+          description: String.t() | nil,
+          location: String.t() | nil,
+          url: String.t() | nil,
+          starts_at: ics_time(),
+          ends_at: ics_time() | nil,
+          duration: String.t() | nil,
+          timezone: String.t() | nil,
+          all_day: boolean(),
+          rrule: ics_recurrence() | nil,
+          exdates: [ics_time()]
+        }
+
+  @typedoc "A profile for `build_vcard/1`; only `name` is required."
+  @type vcard_profile :: %{
+          required(:name) => String.t(),
+          optional(:nickname) => String.t() | nil,
+          optional(:note) => String.t() | nil,
+          optional(:photo) => String.t() | nil,
+          optional(:urls) => [String.t()],
+          optional(:uid) => String.t() | nil,
+          optional(:email) => String.t() | nil
+        }
+
+  @typedoc "A value in the front matter returned by `parse_front_matter/1`."
+  @type front_matter_value ::
+          String.t() | integer() | float() | boolean() | nil | [front_matter_value()]
+
+  @doc """
+  Apply Unicode full case folding to `text`.
+
+  Unlike `String.downcase/1`, multi-character folds are applied
+  (`"Straße"` → `"strasse"`) and all sigma forms fold to `"σ"`. The result is
+  NFC-normalized so folded values can be compared byte-for-byte.
+  """
+  @spec casefold(String.t()) :: String.t() | panic()
+  def casefold(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Compare two strings using Unicode canonical caseless matching.
+
+  Use this for login names, mention resolution, and hashtag matching instead
+  of comparing `String.downcase/1` results.
+  """
+  @spec caseless_equal(String.t(), String.t()) :: boolean() | panic()
+  def caseless_equal(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return `{canonical, display}` for a hashtag, written with or without its
+  `#`, or `nil` if no letter is left in it (`#123`, `#!!`).
+
+  `canonical` is the form Mastodon's `HashtagNormalizer` computes, which
+  tags are stored, matched, and linked under: NFKC-normalized, lowercased,
+  Latin letters with diacritics folded to ASCII, and everything but
+  letters, marks, digits, `_`, `·`, `・`, and ZWNJ removed. Case is
+  lowercased rather than folded as by `casefold/1`, as Mastodon does, so
+  `#Straße` stays `straße`. `display` is the tag as written, for showing.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.normalize_hashtag("#CaféCon")
+      {"cafecon", "CaféCon"}
+
+      iex> Baudrate.Text.Native.normalize_hashtag("ＥＬＩＸＩＲ")
+      {"elixir", "ＥＬＩＸＩＲ"}
+  """
+  @spec normalize_hashtag(String.t()) :: {String.t(), String.t()} | nil | panic()
+  def normalize_hashtag(_tag), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Strip explicit bidi embeddings / overrides / isolates from untrusted `text`
+  (e.g. a remote display name) and isolate the result for safe embedding.
+
+  Modes:
+
+    * `:text` (default) — wraps the text in FSI (U+2068) / PDI (U+2069)
+    * `:html` — HTML-escapes the text and wraps it in `<span dir="auto">`
+
+  Either way, RTL override characters cannot reorder the UI text around it.
+  """
+  @spec bidi_isolate(String.t(), :text | :html) :: String.t() | panic()
+  def bidi_isolate(text, mode \\ :text)
+  def bidi_isolate(_text, _mode), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Replace `:shortcode:` tokens with Unicode emoji using the embedded gemoji
+  table (e.g. `":joy:"` → `"😂"`, `":+1:"` → `"👍"`).
+
+  Unknown shortcodes (including instance custom emoji) are left untouched.
+  Intended for plain-text composer input, before sanitization.
+  """
+  @spec replace_shortcodes(String.t()) :: String.t() | panic()
+  def replace_shortcodes(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse a WebFinger account identifier into a normalized `{user, host}` pair.
+
+  Accepts `acct:user@host`, bare `user@host` (with or without a leading
+  `@`), and profile URLs of the form `https://host/@user`, with the user
+  percent-decoded. The user part is case-folded; the host is IDNA-encoded to
+  lowercase ASCII (`bücher.example` → `xn--bcher-kva.example`). An explicit
+  port is kept.
+
+  Returns `{:ok, {user, host}}` or `{:error, reason}` where `reason` is
+  `:invalid_resource` (unrecognized form), `:invalid_user`, or
+  `:invalid_host`.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.parse_acct("acct:Alice@Example.COM")
+      {:ok, {"alice", "example.com"}}
+
+      iex> Baudrate.Text.Native.parse_acct("https://mastodon.social/@Gargron")
+      {:ok, {"gargron", "mastodon.social"}}
+  """
+  @spec parse_acct(String.t()) :: {:ok, {String.t(), String.t()}} | {:error, atom()} | panic()
+  def parse_acct(_resource), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Whether `a` and `b` name the same actor.
+
+  Identifiers `parse_acct/1` accepts, and profile URLs ending in the user
+  after `/users/`, `/ap/users/`, `/u/`, `/profile/`, or `/accounts/`, are
+  equal when their user and host are: `@Gargron@mastodon.social`,
+  `https://mastodon.social/@gargron`, and
+  `https://mastodon.social/users/Gargron` all match. Other `http(s)` URLs
+  are compared as URLs, without their fragment and trailing `/`; anything
+  else matches nothing.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.same_actor?("alice@example.com", "https://example.com/u/Alice")
+      true
+  """
+  @spec same_actor?(String.t(), String.t()) :: boolean() | panic()
+  def same_actor?(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  The `https` profile and actor URLs an account is commonly found at, one
+  for each path `same_actor?/2` recognizes, for looking up a mention by URL.
+
+  Returns `{:error, reason}` as `parse_acct/1` does.
+
+  ## Examples
+
+      iex> {:ok, [url | _]} = Baudrate.Text.Native.actor_url_forms("alice@example.com")
+      iex> url
+      "https://example.com/@alice"
+  """
+  @spec actor_url_forms(String.t()) :: {:ok, [String.t()]} | {:error, atom()} | panic()
+  def actor_url_forms(_acct), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return a 64-bit SimHash fingerprint of `text`.
+
+  Near-identical texts get fingerprints that differ in few bits, so posts
+  mutated to evade exact-match dedup can be clustered by
+  `hamming_distance/2`: a one-word edit to a short post typically moves 3–8
+  bits, while unrelated texts differ in about 32. Case, punctuation, and
+  whitespace are ignored. Pass plain text (e.g. after
+  `Baudrate.Sanitizer.Native.strip_tags/1`); markup would be fingerprinted
+  too. Text without letters or digits returns `0`.
+
+  The result is unsigned; reinterpret it as signed
+  (`<<signed::signed-64>> = <<n::64>>`) to store it in a Postgres `bigint`.
+  """
+  @spec simhash(String.t()) :: non_neg_integer() | panic()
+  def simhash(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the number of bits in which two `simhash/1` fingerprints differ
+  (0–64).
+  """
+  @spec hamming_distance(non_neg_integer(), non_neg_integer()) :: 0..64 | panic()
+  def hamming_distance(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Wrap plain `text` to `cols` terminal columns, for fixed-width outputs such
+  as Gopher, Gemini, and finger.
+
+  East Asian wide and fullwidth characters count as two columns and
+  combining marks as none. Each line is reflowed on its own: runs of
+  whitespace collapse to one space, and text wider than a line (long URLs,
+  CJK text) is broken between characters. Quote prefixes (`> `) are repeated
+  on every wrapped line, list items (`- `, `* `, `+ `, `• `, `1. `, `1) `)
+  get a hanging indent past their marker, and indentation is kept, with
+  leading tabs expanded to four spaces. Trailing whitespace is removed.
+
+  Returns `{:error, :invalid_width}` when `cols` is 0.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.wrap_text("> - one two three four", 12)
+      {:ok, "> - one two\\n>   three\\n>   four"}
+  """
+  @spec wrap_text(String.t(), non_neg_integer()) ::
+          {:ok, String.t()} | {:error, :invalid_width} | panic()
+  def wrap_text(_text, _cols), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Render `text` as a FIGlet banner in one of the embedded classic fonts, for
+  board headers and welcome screens.
+
+  The banner is HTML-escaped, with trailing spaces and blank lines removed,
+  so it can be placed in a `<pre>` as is (`Phoenix.HTML.raw/1`). `text` is a
+  single line of at most 64 characters; characters the font lacks (outside
+  ASCII and Latin-1, or newlines) are skipped, and text with none it can
+  draw gives `""`.
+
+  ## Examples
+
+      iex> {:ok, banner} = Baudrate.Text.Native.figlet("Hi", :small)
+      iex> banner
+      " _  _ _\\n| || (_)\\n| __ | |\\n|_||_|_|"
+  """
+  @spec figlet(String.t(), figlet_font()) ::
+          {:ok, String.t()} | {:error, :text_too_long} | panic()
+  def figlet(_text, _font), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decode every uuencoded file in `text` (e.g. a message imported from a BBS
+  or FidoNet archive), returning `{filename, data}` in order of appearance.
+
+  Filenames are reduced to their last path component, so they cannot
+  escape an extraction directory; the file mode is ignored. Both space and
+  backtick zero characters, CRLF lines, and data lines missing trailing
+  spaces are accepted. Files that are truncated or corrupt are skipped.
+  Runs on a dirty CPU scheduler.
+  """
+  @spec uudecode(binary()) :: [{String.t(), binary()}] | panic()
+  def uudecode(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  uuencode `data` as a file named `filename` with mode 644, in 45-byte
+  lines as classic `uuencode(1)` writes them.
+
+  Returns `{:error, :invalid_filename}` for a blank name or one containing
+  a control character. Runs on a dirty CPU scheduler.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.uuencode("cat.txt", "Cat")
+      {:ok, "begin 644 cat.txt\\n#0V%T\\n`\\nend\\n"}
+  """
+  @spec uuencode(String.t(), binary()) ::
+          {:ok, String.t()} | {:error, :invalid_filename} | panic()
+  def uuencode(_filename, _data), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Split a FidoNet (FTN) message from an echomail archive into its body and
+  control lines.
+
+    * `area` — the echo tag of a leading `AREA:` line
+    * `kludges` — `^A`-prefixed lines as `{name, value}`, in order (e.g.
+      `{"MSGID", "2:5020/1 1234abcd"}`); `Via` and `PATH` may repeat
+    * `charset` — the first word of the `CHRS` or `CHARSET` kludge
+    * `body` — the remaining text, with LF line endings and no trailing
+      blank lines
+    * `tearline` — the text after a closing `---` line (`""` when bare)
+    * `origin` — the ` * Origin:` line's text, without its address
+    * `address` — the FTN address in parentheses ending the origin line
+    * `seen_by` — the values of trailing `SEEN-BY:` lines
+
+  CR, LF, and CRLF line endings are accepted. `body`, `tearline`, and
+  `origin` are the original bytes, usually in the legacy charset named by
+  `charset` (e.g. `"CP437"`, `"CP866"`), so the importer must convert them
+  to UTF-8; the other fields are ASCII control data.
+  """
+  @spec parse_ftn_message(binary()) :: ftn_message() | panic()
+  def parse_ftn_message(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Encode plain `text` as RFC 3676 `format=flowed` (`DelSp=no`), for email
+  notifications and NNTP-style gateways.
+
+  Lines are soft-broken at spaces to at most `width` characters (72 is
+  customary), with the breaking space left at the end of the line. Words
+  longer than a line are not split. Leading `>` marks (`> > ` or `>> `) set
+  the quote depth, repeated on every wrapped line as `>>` followed by a
+  space. Lines starting with a space, `>`, or `From ` are space-stuffed,
+  trailing spaces are removed so fixed lines stay fixed, and the signature
+  separator `-- ` is kept as is.
+
+  Returns `{:ok, text}` with CRLF line endings, or
+  `{:error, :invalid_width}` unless `width` is 1–998.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.to_flowed("> one two three", 10)
+      {:ok, "> one two \\r\\n> three"}
+  """
+  @spec to_flowed(String.t(), pos_integer()) ::
+          {:ok, String.t()} | {:error, :invalid_width} | panic()
+  def to_flowed(_text, _width), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decode RFC 3676 `format=flowed` `text` into plain text with LF line
+  endings.
+
+  Space-stuffing is removed and flowed lines are joined with the lines that
+  follow, but never across a change of quote depth. Quoted lines come out
+  as `>` marks and a space, as `to_flowed/2` expects them. Set `delsp` for
+  `DelSp=yes` bodies, where the space before each soft break is not part of
+  the text. CRLF and LF line endings are accepted.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.from_flowed("> one two \\r\\n> three\\r\\n")
+      "> one two three\\n"
+  """
+  @spec from_flowed(String.t(), boolean()) :: String.t() | panic()
+  def from_flowed(text, delsp \\ false)
+  def from_flowed(_text, _delsp), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Split a mail or news reply into `{new_content, quoted_content, signature}`,
+  so that gatewayed posts can show the new text and collapse the rest.
+
+    * `quoted_content` — the `>`-quoted lines, verbatim, each with the
+      attribution line before it (a line ending in `:`, such as
+      `"On Monday, Alice wrote:"`), and everything after an Outlook
+      `-----Original Message-----` line; separate quotes are separated by a
+      blank line
+    * `signature` — the lines after the first unquoted `-- ` separator,
+      except quotes below it (top-posted replies)
+    * `new_content` — everything else, in order, including answers between
+      inline quotes
+
+  Each part is trimmed of leading and trailing blank lines; `quoted_content`
+  and `signature` are `nil` when absent. Lines end in LF.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.split_reply("Agreed.\\n\\nAlice wrote:\\n> Ship it?\\n-- \\nBob")
+      {"Agreed.", "Alice wrote:\\n> Ship it?", "Bob"}
+  """
+  @spec split_reply(String.t()) ::
+          {String.t(), String.t() | nil, String.t() | nil} | panic()
+  def split_reply(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse the `VEVENT`s of an iCalendar (RFC 5545) document, such as a
+  Mobilizon event's `.ics` export.
+
+  Folded lines are joined, text values are unescaped (`\\,`, `\\;`, `\\n`,
+  `\\\\`), and times become `t:ics_time/0` strings, with the `TZID` of
+  `DTSTART` as `timezone`. Zones are not converted; `utc_ics_event/1` does
+  that with the time zone database. `duration` is only set for events without `DTEND`.
+  Alarms and other nested components are skipped, and so are events
+  without a valid `DTSTART`.
+
+  Returns `{:ok, events}`, or `{:error, :invalid_ics}` when there is no
+  `VCALENDAR` or its `BEGIN`/`END` lines do not match.
+
+  ## Examples
+
+      iex> ics = "BEGIN:VCALENDAR\\r\\nBEGIN:VEVENT\\r\\nDTSTART:20240501T180000Z\\r\\n" <>
+      ...>   "SUMMARY:Meetup\\\\, Paris\\r\\nEND:VEVENT\\r\\nEND:VCALENDAR\\r\\n"
+      iex> {:ok, [event]} = Baudrate.Text.Native.parse_ics(ics)
+      iex> {event.summary, event.starts_at}
+      {"Meetup, Paris", "2024-05-01T18:00:00Z"}
+  """
+  @spec parse_ics(String.t()) :: {:ok, [ics_event()]} | {:error, :invalid_ics} | panic()
+  def parse_ics(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Write `events` as an iCalendar document, with CRLF line endings and lines
+  folded at 75 octets.
+
+  Each event is a `t:ics_event/0` map; `uid` and `starts_at` are required
+  and every other key may be missing or `nil`. `all_day` is ignored, since
+  a date-only `starts_at` already makes an all-day event. `DTSTAMP` is the
+  current time, unless a UTC `dtstamp` is given.
+
+  No `TZID` is written, since RFC 5545 requires a `VTIMEZONE` definition
+  for each one: convert an event with a `timezone` with `utc_ics_event/1`
+  first.
+
+  Returns `{:ok, text}`, or `{:error, reason}` where `reason` is
+  `:invalid_time` (a malformed time or duration), `:invalid_timezone` (a
+  `timezone` is set), or `:invalid_rrule` (a zero interval or a malformed
+  `BY*` value).
+
+  ## Examples
+
+      iex> event = %{uid: "1", starts_at: "2024-05-01", summary: "A; B"}
+      iex> {:ok, ics} = Baudrate.Text.Native.build_ics([event])
+      iex> ics =~ "DTSTART;VALUE=DATE:20240501\\r\\n" and ics =~ "SUMMARY:A\\\\; B\\r\\n"
+      true
+  """
+  @spec build_ics([map()]) ::
+          {:ok, String.t()}
+          | {:error, :invalid_time | :invalid_timezone | :invalid_rrule}
+          | panic()
+  def build_ics(_events), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Convert the local times of an event in a `timezone` — `starts_at`,
+  `ends_at`, `exdates`, and the `rrule`'s `until` — to UTC and clear
+  `timezone`, as `build_ics/1` requires. Dates and UTC times are kept, and
+  an event without a `timezone` is returned unchanged.
+
+  A time skipped by a daylight saving change moves to just after the gap,
+  and a repeated one takes the earlier offset. A recurrence then keeps its
+  UTC time of day, so its local time shifts across a daylight saving
+  change.
+
+  Returns `{:ok, event}`, or `{:error, :invalid_timezone}` for an unknown
+  zone and `{:error, :invalid_time}` for a malformed time.
+
+  ## Examples
+
+      iex> event = %{uid: "1", starts_at: "2024-05-01T18:00:00", timezone: "Europe/Paris"}
+      iex> Baudrate.Text.Native.utc_ics_event(event)
+      {:ok, %{uid: "1", starts_at: "2024-05-01T16:00:00Z", timezone: nil}}
+  """
+  @spec utc_ics_event(map()) :: {:ok, map()} | {:error, :invalid_time | :invalid_timezone}
+  def utc_ics_event(%{timezone: zone} = event) when is_binary(zone) do
+    rrule = Map.get(event, :rrule)
+
+    with {:ok, starts_at} <- ics_time_to_utc(event.starts_at, zone),
+         {:ok, ends_at} <- ics_time_to_utc(Map.get(event, :ends_at), zone),
+         {:ok, exdates} <- ics_times_to_utc(Map.get(event, :exdates) || [], zone),
+         {:ok, until} <- ics_time_to_utc(rrule && Map.get(rrule, :until), zone) do
+      utc = %{event | starts_at: starts_at, timezone: nil}
+      utc = if Map.has_key?(event, :ends_at), do: %{utc | ends_at: ends_at}, else: utc
+      utc = if Map.has_key?(event, :exdates), do: %{utc | exdates: exdates}, else: utc
+      utc = if rrule && until, do: %{utc | rrule: Map.put(rrule, :until, until)}, else: utc
+      {:ok, utc}
+    end
+  end
+
+  def utc_ics_event(event) when is_map(event), do: {:ok, event}
+
+  defp ics_times_to_utc(times, zone) do
+    times
+    |> Enum.reverse()
+    |> Enum.reduce_while({:ok, []}, fn time, {:ok, acc} ->
+      case ics_time_to_utc(time, zone) do
+        {:ok, utc} -> {:cont, {:ok, [utc | acc]}}
+        error -> {:halt, error}
+      end
+    end)
+  end
+
+  defp ics_time_to_utc(nil, _zone), do: {:ok, nil}
+
+  defp ics_time_to_utc(time, zone) when is_binary(time) do
+    cond do
+      String.ends_with?(time, "Z") -> {:ok, time}
+      match?({:ok, _}, Date.from_iso8601(time)) -> {:ok, time}
+      true -> local_to_utc(NaiveDateTime.from_iso8601(time), zone)
+    end
+  end
+
+  defp ics_time_to_utc(_time, _zone), do: {:error, :invalid_time}
+
+  defp local_to_utc({:ok, naive}, zone) do
+    case DateTime.from_naive(naive, zone) do
+      {:ok, local} -> {:ok, utc_ics_time(local)}
+      {:ambiguous, earlier, _later} -> {:ok, utc_ics_time(earlier)}
+      {:gap, _before, just_after} -> {:ok, utc_ics_time(just_after)}
+      {:error, :time_zone_not_found} -> {:error, :invalid_timezone}
+      {:error, _} -> {:error, :invalid_time}
+    end
+  end
+
+  defp local_to_utc({:error, _}, _zone), do: {:error, :invalid_time}
+
+  defp utc_ics_time(local) do
+    local
+    |> DateTime.shift_zone!("Etc/UTC")
+    |> DateTime.truncate(:second)
+    |> DateTime.to_iso8601()
+  end
+
+  @doc """
+  Write `profile` as a vCard 4.0 (RFC 6350) card, with CRLF line endings
+  and lines folded at 75 octets.
+
+  `name` becomes `FN`, and `nickname`, `note`, and `email` their namesake
+  properties, all escaped (`\\`, `,`, `;`, and line breaks). `uid` (such as
+  the actor URI), `photo` (the avatar URL), and each of `urls` are URIs and
+  written as they are.
+
+  Returns `{:ok, text}`, `{:error, :missing_name}` unless `name` has text,
+  or `{:error, :invalid_uri}` for a URI containing whitespace or control
+  characters.
+
+  ## Examples
+
+      iex> profile = %{name: "Alice; Ops", note: "Hi\\nthere"}
+      iex> {:ok, vcard} = Baudrate.Text.Native.build_vcard(profile)
+      iex> vcard =~ "FN:Alice\\\\; Ops\\r\\n" and vcard =~ "NOTE:Hi\\\\nthere\\r\\n"
+      true
+  """
+  @spec build_vcard(vcard_profile()) ::
+          {:ok, String.t()} | {:error, :missing_name | :invalid_uri} | panic()
+  def build_vcard(_profile), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Split the front matter off a Markdown article: a block opening the text
+  between `---` lines (YAML) or `+++` lines (TOML), as static site
+  generators write title, tags, summary, and license.
+
+  Only flat blocks are read, one key per line. Values are strings,
+  integers, floats, booleans, `nil` (YAML `~`, `null`, or nothing), or
+  lists of them; YAML lists may also be `- item` lines and YAML strings
+  `|` or `>` blocks. Dates are kept as strings.
+
+  Returns `{:ok, {front_matter, body}}`, with string keys in
+  `front_matter` and the blank lines after the block dropped from `body`.
+  A text that does not open with a delimiter line, or never closes its
+  block, has no front matter and is returned whole with `%{}`. A block
+  with a nested map, a TOML table, a duplicate key, or a line that does
+  not parse gives `{:error, :invalid_front_matter}`.
+
+  ## Examples
+
+      iex> text = "---\\ntitle: Hello\\ntags: [a, b]\\n---\\n\\n# Hi\\n"
+      iex> Baudrate.Text.Native.parse_front_matter(text)
+      {:ok, {%{"title" => "Hello", "tags" => ["a", "b"]}, "# Hi\\n"}}
+  """
+  @spec parse_front_matter(String.t()) ::
+          {:ok, {%{String.t() => front_matter_value()}, String.t()}}
+          | {:error, :invalid_front_matter}
+          | panic()
+  def parse_front_matter(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Rewrite the fenced code blocks of `markdown` that have an info string as
+  HTML blocks the Markdown sanitizer keeps, before Earmark renders them.
+
+  From the info string, the first word is the language, as a
+  `language-*` class on `code`; `title=`, `filename=`, or a Qiita-style
+  `lang:file` suffix gives `data-filename`; and `{1,3-5}`, `hl_lines="1 3-5"`,
+  or `highlight=` give `data-highlight-lines` (normalized to `1,3-5`) on
+  `pre`. Other options, such as `linenos`, are dropped. Code is
+  HTML-escaped. Fences without an info string, indented fences, and the
+  rest of the text are left unchanged.
+
+  In `diff` and `patch` blocks, added and removed lines are wrapped in
+  `<span class="diff-ins">` and `<span class="diff-del">`, hunk headers
+  (`@@ ... @@`) in `diff-hunk`, and file headers (`diff`, `index`, `---`,
+  `+++`) in `diff-header`, so that patches are readable without client-side
+  highlighting.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.annotate_code_fences("```rust title=main.rs\\nmain()\\n```")
+      ~s[<pre data-filename="main.rs"><code class="language-rust">main()</code>\\n</pre>\\n\\n]
+  """
+  @spec annotate_code_fences(String.t()) :: String.t() | panic()
+  def annotate_code_fences(_markdown), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Split `text` into its extended grapheme clusters, as users see characters.
+
+  A ZWJ emoji sequence, a flag, or a letter with combining marks is one
+  grapheme. Segmentation is the one the sanitizer uses to truncate titles
+  and inline text, so it does not change with the Unicode version of the
+  running OTP release.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.graphemes("e\\u0301🇯🇵👍🏽")
+      ["e\\u0301", "🇯🇵", "👍🏽"]
+  """
+  @spec graphemes(String.t()) :: [String.t()] | panic()
+  def graphemes(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Count the extended grapheme clusters in `text`, as `graphemes/1` splits
+  them. Changeset length limits use `String.length/1`, which counts the
+  same clusters with OTP's Unicode data.
+
+  ## Examples
+
+      iex> Baudrate.Text.Native.grapheme_length("👩\\u200D👩\\u200D👧 family")
+      8
+  """
+  @spec grapheme_length(String.t()) :: non_neg_integer() | panic()
+  def grapheme_length(_text), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Return the crate version, enabled Cargo features, and the `{name, arity}`
+  of every NIF in the loaded library.
+  """
+  @spec native_info() :: Baudrate.Native.info() | panic()
+  def native_info, do: :erlang.nif_error(:nif_not_loaded)
+end
@@ -1,12 +1,12 @@
 defmodule BaudrateWeb.FeedController do
   @moduledoc """
-  Controller for RSS 2.0 and Atom 1.0 syndication feeds.
+  Controller for RSS 2.0, Atom 1.0, and JSON Feed 1.1 syndication feeds.
 
-  Provides feeds at three scopes:
+  Provides feeds at three scopes, each available as `rss`, `atom`, and `json`:
 
-    * **Site-wide** — all public boards (`/feeds/rss`, `/feeds/atom`)
-    * **Per-board** — single public board (`/feeds/boards/:slug/rss`, `/feeds/boards/:slug/atom`)
-    * **Per-user** — user's articles in public boards (`/feeds/users/:username/rss`, `/feeds/users/:username/atom`)
+    * **Site-wide** — all public boards (`/feeds/rss`, `/feeds/atom`, `/feeds/json`)
+    * **Per-board** — single public board (`/feeds/boards/:slug/rss`, …)
+    * **Per-user** — user's articles in public boards (`/feeds/users/:username/rss`, …)
 
   Only local articles are included (no remote/federated articles). Feeds include
   `Cache-Control` and `Last-Modified` headers, with `If-Modified-Since` → 304
@@ -50,6 +50,20 @@ defmodule BaudrateWeb.FeedController do
     })
   end
 
+  @doc "Renders the site-wide JSON Feed 1.1 of recent public articles."
+  def site_json(conn, _params) do
+    articles = Content.list_recent_public_articles()
+    site_name = Baudrate.Setup.get_setting("site_name") || "Baudrate"
+    base = BaudrateWeb.Endpoint.url()
+
+    render_feed(conn, :json, articles, %{
+      title: site_name,
+      link: base <> "/",
+      description: gettext("Recent articles on %{site_name}", site_name: site_name),
+      self_url: base <> "/feeds/json"
+    })
+  end
+
   # --- Board feeds ---
 
   @doc "Renders the RSS 2.0 feed for a single public board."
@@ -88,6 +102,25 @@ defmodule BaudrateWeb.FeedController do
     end
   end
 
+  @doc "Renders the JSON Feed 1.1 for a single public board."
+  def board_json(conn, %{"slug" => slug}) do
+    with true <- Regex.match?(@slug_re, slug),
+         board when not is_nil(board) <- get_public_board(slug),
+         {:ok, articles} <- Content.list_recent_articles_for_public_board(board) do
+      base = BaudrateWeb.Endpoint.url()
+
+      render_feed(conn, :json, articles, %{
+        title: board.name,
+        link: base <> "/boards/#{board.slug}",
+        description:
+          board.description || gettext("Articles in %{board_name}", board_name: board.name),
+        self_url: base <> "/feeds/boards/#{board.slug}/json"
+      })
+    else
+      _ -> send_resp(conn, 404, "Not Found")
+    end
+  end
+
   # --- User feeds ---
 
   @doc "Renders the RSS 2.0 feed of a user's articles in public boards."
@@ -127,6 +160,25 @@ defmodule BaudrateWeb.FeedController do
     end
   end
 
+  @doc "Renders the JSON Feed 1.1 of a user's articles in public boards."
+  def user_json(conn, %{"username" => username}) do
+    with true <- Regex.match?(@username_re, username),
+         user when not is_nil(user) <- Auth.get_user_by_username(username),
+         false <- user.status == "banned" do
+      articles = Content.list_recent_public_articles_by_user(user.id)
+      base = BaudrateWeb.Endpoint.url()
+
+      render_feed(conn, :json, articles, %{
+        title: gettext("%{username}'s articles", username: user.username),
+        link: base <> "/users/#{user.username}",
+        description: gettext("Recent articles by %{username}", username: user.username),
+        self_url: base <> "/feeds/users/#{user.username}/json"
+      })
+    else
+      _ -> send_resp(conn, 404, "Not Found")
+    end
+  end
+
   # --- Helpers ---
 
   defp get_public_board(slug) do
@@ -146,6 +198,7 @@ defmodule BaudrateWeb.FeedController do
         case format do
           :rss -> "application/rss+xml"
           :atom -> "application/atom+xml"
+          :json -> "application/feed+json"
         end
 
       assigns =
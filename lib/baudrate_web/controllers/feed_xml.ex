@@ -1,10 +1,13 @@
 defmodule BaudrateWeb.FeedXML do
   @moduledoc """
-  Renders RSS 2.0 and Atom 1.0 XML feeds from article data.
-
-  Uses compile-time EEx templates for efficiency. Provides helper functions
-  for XML escaping, date formatting (RFC 822 for RSS, RFC 3339 for Atom),
-  and article URL/content generation.
+  Renders RSS 2.0 and Atom 1.0 XML feeds, and JSON Feed 1.1, from article
+  data.
+
+  RSS uses a compile-time EEx template; Atom and JSON Feed are generated by
+  the `baudrate_feed_parser` NIF (`Baudrate.Bots.FeedParserNative`), which
+  handles escaping and date formatting. Provides helper functions for
+  XML escaping, date formatting (RFC 822 for RSS, RFC 3339 for Atom), and
+  article URL/content generation.
   """
 
   use Gettext, backend: BaudrateWeb.Gettext
@@ -14,26 +17,127 @@ defmodule BaudrateWeb.FeedXML do
   @template_dir Path.join(__DIR__, "feed_xml")
 
   EEx.function_from_file(:def, :render_rss, Path.join(@template_dir, "rss.xml.eex"), [:assigns])
-  EEx.function_from_file(:def, :render_atom, Path.join(@template_dir, "atom.xml.eex"), [:assigns])
 
   @doc """
-  Renders a feed in the given format (`:rss` or `:atom`).
+  Renders a feed in the given format (`:rss`, `:atom`, or `:json`).
   """
   def render(:rss, assigns), do: render_rss(assigns)
   def render(:atom, assigns), do: render_atom(assigns)
+  def render(:json, assigns), do: render_json_feed(assigns)
+
+  @doc """
+  Renders an Atom 1.0 feed.
+  """
+  def render_atom(assigns) do
+    meta = %{
+      title: assigns.title,
+      link: assigns.link,
+      self_url: assigns.self_url,
+      language: assigns[:language],
+      updated: assigns[:updated]
+    }
+
+    entries =
+      Enum.map(assigns.articles, fn article ->
+        url = article_url(article)
+
+        %{
+          id: url,
+          title: article.title,
+          link: url,
+          published: article.inserted_at,
+          updated: article.updated_at,
+          author: author_name(article),
+          content: Baudrate.Content.Markdown.to_html(article.body)
+        }
+      end)
+
+    case Baudrate.Bots.FeedParserNative.build_atom(meta, entries) do
+      xml when is_binary(xml) ->
+        xml
+
+      {:error, {:panic, message}} ->
+        raise Baudrate.Native.PanicError, call: "build_atom/2", message: message
+    end
+  end
+
+  @doc """
+  Renders a JSON Feed 1.1 document. `content_html` is the sanitized
+  Markdown rendering of each article, and its images are listed as
+  `attachments`.
+  """
+  def render_json_feed(assigns) do
+    meta = %{
+      title: assigns.title,
+      home_page_url: assigns.link,
+      feed_url: assigns.self_url,
+      description: assigns[:description],
+      language: assigns[:language]
+    }
+
+    entries =
+      Enum.map(assigns.articles, fn article ->
+        url = article_url(article)
+
+        %{
+          id: url,
+          url: url,
+          title: article.title,
+          content_html: Baudrate.Content.Markdown.to_html(article.body),
+          published: article.inserted_at,
+          updated: article.updated_at,
+          authors: [%{name: author_name(article)}],
+          attachments: attachments(article)
+        }
+      end)
+
+    case Baudrate.Bots.FeedParserNative.build_json_feed(meta, entries) do
+      json when is_binary(json) ->
+        json
+
+      {:error, {:panic, message}} ->
+        raise Baudrate.Native.PanicError, call: "build_json_feed/2", message: message
+    end
+  end
+
+  @doc """
+  Returns the JSON Feed attachments for an article's images: `url`,
+  `mime_type`, and `size_in_bytes` when the file is on disk. Articles
+  without `article_images` loaded have none.
+  """
+  def attachments(%{article_images: images}) when is_list(images) do
+    base = BaudrateWeb.Endpoint.url()
+
+    Enum.map(images, fn image ->
+      attachment = %{
+        url: base <> Baudrate.Content.ArticleImageStorage.image_url(image.filename),
+        mime_type: "image/webp"
+      }
+
+      case File.stat(image.storage_path || "") do
+        {:ok, %File.Stat{size: size}} -> Map.put(attachment, :size_in_bytes, size)
+        {:error, _} -> attachment
+      end
+    end)
+  end
+
+  def attachments(_article), do: []
 
   @doc """
-  Escapes a string for safe inclusion in XML text nodes and attributes.
+  Escapes a string for safe inclusion in XML text nodes and attributes,
+  dropping characters that XML cannot represent.
   """
   def xml_escape(nil), do: ""
 
   def xml_escape(text) when is_binary(text) do
-    text
-    |> String.replace("&", "&amp;")
-    |> String.replace("<", "&lt;")
-    |> String.replace(">", "&gt;")
-    |> String.replace("\"", "&quot;")
-    |> String.replace("'", "&apos;")
+    case Baudrate.Bots.FeedParserNative.xml_escape(text) do
+      escaped when is_binary(escaped) ->
+        escaped
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("xml_escape/1", message)
+        ""
+    end
   end
 
   @doc """
@@ -52,12 +156,20 @@ defmodule BaudrateWeb.FeedXML do
   @doc """
   Returns the article body rendered as sanitized HTML for feed content.
 
-  Escapes `]]>` sequences to prevent premature CDATA section termination.
+  Drops characters that XML cannot represent and escapes `]]>` sequences
+  to prevent premature CDATA section termination.
   """
   def article_html(article) do
-    article.body
-    |> Baudrate.Content.Markdown.to_html()
-    |> escape_cdata()
+    html = Baudrate.Content.Markdown.to_html(article.body)
+
+    case Baudrate.Bots.FeedParserNative.strip_invalid_xml_chars(html) do
+      stripped when is_binary(stripped) ->
+        escape_cdata(stripped)
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("strip_invalid_xml_chars/1", message)
+        ""
+    end
   end
 
   @doc """
@@ -310,8 +310,7 @@ defmodule BaudrateWeb.LinkedData do
 
   def excerpt(text) do
     text
-    |> Sanitizer.strip_tags()
-    |> Sanitizer.decode_html_entities()
+    |> Sanitizer.html_to_text()
     |> String.replace(~r/\s+/, " ")
     |> String.trim()
     |> then(fn plain ->
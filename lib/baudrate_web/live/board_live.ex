@@ -209,8 +209,7 @@ defmodule BaudrateWeb.BoardLive do
   defp digest(text) do
     plain =
       text
-      |> Baudrate.Sanitizer.Native.strip_tags()
-      |> Baudrate.Sanitizer.Native.decode_html_entities()
+      |> Baudrate.Sanitizer.Native.html_to_text()
       |> String.replace(~r/\s+/, " ")
       |> String.trim()
 
@@ -19,9 +19,11 @@ defmodule BaudrateWeb.TagLive do
 
   @impl true
   def handle_params(%{"tag" => raw_tag} = params, _uri, socket) do
-    tag = String.downcase(raw_tag)
+    hashtag = Regex.match?(@tag_re, raw_tag) && Content.normalize_hashtag(raw_tag)
+
+    if hashtag do
+      {tag, display} = hashtag
 
-    if Regex.match?(@tag_re, tag) do
       page =
         case Integer.parse(params["page"] || "1") do
           {n, ""} when n > 0 -> n
@@ -35,15 +37,17 @@ defmodule BaudrateWeb.TagLive do
       {:noreply,
        socket
        |> assign(:tag, tag)
+       |> assign(:display_tag, display)
        |> assign(:articles, result.articles)
        |> assign(:total, result.total)
        |> assign(:page, result.page)
        |> assign(:total_pages, result.total_pages)
-       |> assign(:page_title, gettext("Articles tagged #%{tag}", tag: tag))}
+       |> assign(:page_title, gettext("Articles tagged #%{tag}", tag: display))}
     else
       {:noreply,
        socket
        |> assign(:tag, raw_tag)
+       |> assign(:display_tag, raw_tag)
        |> assign(:articles, [])
        |> assign(:total, 0)
        |> assign(:page, 1)
@@ -12,7 +12,7 @@ defmodule BaudrateWeb.OpenGraph do
 
       assign(socket, :og_meta, OpenGraph.article_tags(article, article_images))
 
-  The root layout iterates `@og_meta` to emit the tags.
+  The root layout renders `@og_meta` with `to_html/1`.
   """
 
   use Gettext, backend: BaudrateWeb.Gettext
@@ -189,6 +189,25 @@ defmodule BaudrateWeb.OpenGraph do
     common_tags(base) ++ filter_nil(tags)
   end
 
+  @doc """
+  Renders tag tuples as `<meta>` elements for the root layout.
+
+  Contents are stripped of markup, truncated, and escaped by
+  `Baudrate.Sanitizer.Native.build_og_meta/1`, so quotes and line breaks in
+  user content cannot break the markup.
+  """
+  @spec to_html([{String.t(), String.t()}]) :: String.t()
+  def to_html(tags) do
+    case Baudrate.Sanitizer.Native.build_og_meta(tags) do
+      html when is_binary(html) ->
+        html
+
+      {:error, {:panic, message}} ->
+        Baudrate.Native.log_panic("build_og_meta/1", message)
+        ""
+    end
+  end
+
   # --- Private ---
 
   defp common_tags(base) do
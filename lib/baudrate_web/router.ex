@@ -198,7 +198,7 @@ defmodule BaudrateWeb.Router do
     post "/boards/:slug/inbox", ActivityPubController, :board_inbox
   end
 
-  # Syndication feeds (RSS 2.0 / Atom 1.0)
+  # Syndication feeds (RSS 2.0 / Atom 1.0 / JSON Feed 1.1)
   pipeline :feeds do
     plug BaudrateWeb.Plugs.RateLimit, action: :feeds
   end
@@ -208,10 +208,13 @@ defmodule BaudrateWeb.Router do
 
     get "/rss", FeedController, :site_rss
     get "/atom", FeedController, :site_atom
+    get "/json", FeedController, :site_json
     get "/boards/:slug/rss", FeedController, :board_rss
     get "/boards/:slug/atom", FeedController, :board_atom
+    get "/boards/:slug/json", FeedController, :board_json
     get "/users/:username/rss", FeedController, :user_rss
     get "/users/:username/atom", FeedController, :user_atom
+    get "/users/:username/json", FeedController, :user_json
   end
 
   # Public (redirect if already authenticated)
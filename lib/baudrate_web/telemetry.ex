@@ -3,8 +3,8 @@ defmodule BaudrateWeb.Telemetry do
   Telemetry supervisor for Phoenix, Ecto, and VM metrics.
 
   Collects endpoint latency, router dispatch timing, database query
-  performance, and BEAM VM memory/run-queue statistics. Polled every
-  10 seconds.
+  performance, HTML sanitizer load, and BEAM VM memory/run-queue statistics.
+  Polled every 10 seconds.
   """
 
   use Supervisor
@@ -92,6 +92,12 @@ defmodule BaudrateWeb.Telemetry do
         tags: [:status]
       ),
 
+      # Sanitizer Metrics (monotonic counters from the NIF)
+      last_value("baudrate.sanitizer.calls.count", tags: [:nif]),
+      last_value("baudrate.sanitizer.stats.bytes_processed", unit: :byte),
+      last_value("baudrate.sanitizer.stats.elements_stripped"),
+      last_value("baudrate.sanitizer.stats.errors"),
+
       # VM Metrics
       summary("vm.memory.total", unit: {:byte, :kilobyte}),
       summary("vm.total_run_queue_lengths.total"),
@@ -105,6 +111,32 @@ defmodule BaudrateWeb.Telemetry do
       # A module, function and arguments to be invoked periodically.
       # This function must call :telemetry.execute/3 and a metric must be added above.
       # {BaudrateWeb, :count_users, []}
+      {__MODULE__, :dispatch_sanitizer_stats, []}
     ]
   end
+
+  @doc """
+  Emit the sanitizer NIF counters from `Baudrate.Sanitizer.Native.stats/0`.
+
+  Executes `[:baudrate, :sanitizer, :calls]` once per NIF, with a `:count`
+  measurement and `:nif` metadata, and `[:baudrate, :sanitizer, :stats]`
+  with the `:bytes_processed`, `:elements_stripped`, and `:errors` totals.
+  """
+  def dispatch_sanitizer_stats do
+    case Baudrate.Sanitizer.Native.stats() do
+      %{calls: calls} = stats ->
+        for {nif, count} <- calls do
+          :telemetry.execute([:baudrate, :sanitizer, :calls], %{count: count}, %{nif: nif})
+        end
+
+        :telemetry.execute(
+          [:baudrate, :sanitizer, :stats],
+          Map.take(stats, [:bytes_processed, :elements_stripped, :errors]),
+          %{}
+        )
+
+      {:error, _reason} ->
+        :ok
+    end
+  end
 end
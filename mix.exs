@@ -67,11 +67,11 @@ defmodule Baudrate.MixProject do
       {:bandit, "~> 1.5"},
       {:bcrypt_elixir, "~> 3.0"},
       {:nimble_totp, "~> 1.0"},
-      {:eqrcode, "~> 0.2.1"},
       {:hammer, "~> 6.2"},
-      {:image, "~> 0.54"},
+      {:image, "~> 0.54", only: :test},
       {:earmark, "~> 1.4"},
       {:rustler, "~> 0.36", runtime: false},
+      {:rustler_precompiled, "~> 0.8"},
       {:tz, "~> 0.28"},
       {:wallaby, "~> 0.30", runtime: false, only: :test},
       {:credo, "~> 1.7", only: [:dev, :test], runtime: false},
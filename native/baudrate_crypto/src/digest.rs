@@ -0,0 +1,64 @@
+//! RFC 3230 `Digest` header values for request bodies.
+
+use baudrate_nif::guard::{guard, Guarded};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use rustler::{Binary, Encoder, Env, Term};
+use sha2::{Digest as _, Sha256};
+use subtle::ConstantTimeEq;
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+        digest_mismatch,
+        malformed_digest,
+        unsupported_algorithm,
+    }
+}
+
+const SHA256_PREFIX: &str = "SHA-256=";
+
+fn sha256_b64(body: &[u8]) -> String {
+    BASE64.encode(Sha256::digest(body))
+}
+
+/// Return the `Digest` header value (`SHA-256=<base64>`) for `body`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn digest_sha256(body: Binary) -> Guarded<String> {
+    guard(|| format!("{}{}", SHA256_PREFIX, sha256_b64(body.as_slice())))
+}
+
+/// Check a `Digest` header against `body`.  The header may list several
+/// comma-separated `algorithm=value` instances; only `SHA-256` (matched
+/// case-insensitively) is checked, and it must be present.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_digest<'a>(env: Env<'a>, header: &str, body: Binary) -> Guarded<Term<'a>> {
+    guard(|| {
+        let mut expected = None;
+
+        for instance in header.split(',') {
+            let Some((algorithm, value)) = instance.trim().split_once('=') else {
+                return (atoms::error(), atoms::malformed_digest()).encode(env);
+            };
+            if algorithm.eq_ignore_ascii_case("SHA-256") {
+                expected = Some(value);
+                break;
+            }
+        }
+
+        let Some(expected) = expected else {
+            return (atoms::error(), atoms::unsupported_algorithm()).encode(env);
+        };
+        let Ok(expected) = BASE64.decode(expected) else {
+            return (atoms::error(), atoms::malformed_digest()).encode(env);
+        };
+
+        let actual = Sha256::digest(body.as_slice());
+        if bool::from(actual.as_slice().ct_eq(&expected)) {
+            atoms::ok().encode(env)
+        } else {
+            (atoms::error(), atoms::digest_mismatch()).encode(env)
+        }
+    })
+}
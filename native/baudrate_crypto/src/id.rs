@@ -0,0 +1,83 @@
+//! Snowflake-style k-sortable IDs.
+//!
+//! An ID is a positive 63-bit integer, so it fits a Postgres `bigint`:
+//!
+//! ```text
+//! | 41 bits: ms since EPOCH_MS | 10 bits: node id | 12 bits: sequence |
+//! ```
+//!
+//! IDs from one node are strictly increasing.  When the wall clock steps
+//! backwards by up to `MAX_SKEW_MS`, or a millisecond's sequence runs out,
+//! generation carries on from the last timestamp used instead of repeating
+//! it; a larger step is refused rather than waited out on a scheduler.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::Atom;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod atoms {
+    rustler::atoms! {
+        invalid_node_id,
+        invalid_id,
+        clock_skew,
+    }
+}
+
+/// 2024-01-01T00:00:00Z; the 41-bit timestamp lasts until 2093.
+const EPOCH_MS: u64 = 1_704_067_200_000;
+const NODE_BITS: u32 = 10;
+const SEQUENCE_BITS: u32 = 12;
+const MAX_NODE_ID: u16 = (1 << NODE_BITS) - 1;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+const MAX_TIMESTAMP: u64 = (1 << (63 - NODE_BITS - SEQUENCE_BITS)) - 1;
+/// How far the clock may run behind the last ID before generation fails.
+const MAX_SKEW_MS: u64 = 5_000;
+
+/// The timestamp and sequence of the last ID generated.
+static LAST: Mutex<(u64, u64)> = Mutex::new((0, 0));
+
+/// Milliseconds since `EPOCH_MS`, or `None` for a clock set before it.
+fn now_ms() -> Option<u64> {
+    let unix_ms = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+    unix_ms.checked_sub(EPOCH_MS)
+}
+
+/// Return a new ID for `node_id` (0–1023).  Every node generating IDs must
+/// have its own node id.
+#[rustler::nif]
+fn generate_id(node_id: u16) -> Guarded<Result<u64, Atom>> {
+    guard(|| {
+        if node_id > MAX_NODE_ID {
+            return Err(atoms::invalid_node_id());
+        }
+
+        let now = now_ms().ok_or_else(atoms::clock_skew)?;
+        let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+        let (timestamp, sequence) = match *last {
+            (time, _) if now > time => (now, 0),
+            (time, _) if time - now > MAX_SKEW_MS => return Err(atoms::clock_skew()),
+            (time, sequence) if sequence < MAX_SEQUENCE => (time, sequence + 1),
+            (time, _) => (time + 1, 0),
+        };
+        if timestamp > MAX_TIMESTAMP {
+            return Err(atoms::clock_skew());
+        }
+        *last = (timestamp, sequence);
+
+        Ok(timestamp << (NODE_BITS + SEQUENCE_BITS)
+            | u64::from(node_id) << SEQUENCE_BITS
+            | sequence)
+    })
+}
+
+/// Return the Unix time in milliseconds at which `id` was generated.
+#[rustler::nif]
+fn id_timestamp(id: i64) -> Guarded<Result<u64, Atom>> {
+    guard(|| {
+        if id <= 0 {
+            return Err(atoms::invalid_id());
+        }
+        Ok((id as u64 >> (NODE_BITS + SEQUENCE_BITS)) + EPOCH_MS)
+    })
+}
@@ -0,0 +1,16 @@
+//! Build information, for detecting what a loaded library supports.
+
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::info::{self, Feature, NativeInfo};
+use rustler::Env;
+
+/// Cargo features compiled into this build; none are defined yet.
+const FEATURES: &[Feature] = &[];
+
+/// Return the crate version, its enabled Cargo features, and every
+/// NIF in the loaded library as `{name, arity}`, so that callers can degrade
+/// gracefully when an older build is deployed.
+#[rustler::nif]
+fn native_info(env: Env) -> Guarded<NativeInfo> {
+    guard(|| info::native_info(env, env!("CARGO_PKG_VERSION"), FEATURES))
+}
@@ -0,0 +1,63 @@
+//! JSON-LD → RDF dataset canonicalization (RDFC-1.0, formerly URDNA2015),
+//! the input to Linked Data Signatures on forwarded activities.
+
+use baudrate_nif::guard::{guard, Guarded};
+use futures_util::future::{BoxFuture, FutureExt};
+use rustler::Atom;
+use sophia_api::parser::QuadParser;
+use sophia_api::source::QuadSource;
+use sophia_inmem::dataset::LightDataset;
+use sophia_iri::Iri;
+use sophia_jsonld::loader::ClosureLoader;
+use sophia_jsonld::{JsonLdOptions, JsonLdParser};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+mod atoms {
+    rustler::atoms! {
+        invalid_jsonld,
+        canonicalization_failed,
+    }
+}
+
+/// Expand `json` to RDF using only the contexts in `context_cache` (remote
+/// contexts are never fetched) and return its canonical N-Quads.
+fn canonicalize(
+    json: &str,
+    context_cache: HashMap<String, String>,
+) -> Result<String, (Atom, String)> {
+    let cache = Arc::new(context_cache);
+
+    let options = JsonLdOptions::new().with_document_loader_closure(move || {
+        let cache = Arc::clone(&cache);
+        ClosureLoader::new(
+            move |iri: Iri<String>| -> BoxFuture<'static, Result<String, String>> {
+                let document = cache
+                    .get(iri.as_str())
+                    .cloned()
+                    .ok_or_else(|| format!("context not in cache: {}", iri.as_str()));
+                async move { document }.boxed()
+            },
+        )
+    });
+
+    let dataset: LightDataset = JsonLdParser::new_with_options(options)
+        .parse_str(json)
+        .collect_quads()
+        .map_err(|e| (atoms::invalid_jsonld(), e.to_string()))?;
+
+    let mut nquads = Vec::new();
+    sophia_c14n::rdfc10::normalize(&dataset, &mut nquads)
+        .map_err(|e| (atoms::canonicalization_failed(), e.to_string()))?;
+
+    String::from_utf8(nquads).map_err(|e| (atoms::canonicalization_failed(), e.to_string()))
+}
+
+/// Canonicalize a JSON-LD document to N-Quads (see [`canonicalize`]).
+#[rustler::nif(schedule = "DirtyCpu")]
+fn canonicalize_jsonld(
+    json: &str,
+    context_cache: HashMap<String, String>,
+) -> Guarded<Result<String, (Atom, String)>> {
+    guard(|| canonicalize(json, context_cache))
+}
@@ -0,0 +1,74 @@
+//! Actor keypair generation.
+
+use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey, KeypairBytes};
+use baudrate_nif::guard::{guard, Guarded};
+use crate::keys::ed25519_multibase;
+use rand_core::OsRng;
+use rsa::pkcs1::EncodeRsaPrivateKey;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use rustler::Atom;
+
+mod atoms {
+    rustler::atoms! {
+        invalid_key_size,
+        key_generation_failed,
+    }
+}
+
+const MIN_RSA_BITS: usize = 2048;
+const MAX_RSA_BITS: usize = 4096;
+
+/// Generate an RSA keypair and return `{public_pem, private_pem}`.
+///
+/// The private key is PKCS#1 PEM and the public key SubjectPublicKeyInfo
+/// PEM — the same encodings `KeyStore.generate_keypair/0` produces.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn generate_rsa_keypair(bits: usize) -> Guarded<Result<(String, String), Atom>> {
+    guard(|| {
+        if !(MIN_RSA_BITS..=MAX_RSA_BITS).contains(&bits) || !bits.is_multiple_of(8) {
+            return Err(atoms::invalid_key_size());
+        }
+
+        let private_key =
+            RsaPrivateKey::new(&mut OsRng, bits).map_err(|_| atoms::key_generation_failed())?;
+        let public_key = RsaPublicKey::from(&private_key);
+
+        let private_pem = private_key
+            .to_pkcs1_pem(LineEnding::LF)
+            .map_err(|_| atoms::key_generation_failed())?;
+        let public_pem = public_key
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|_| atoms::key_generation_failed())?;
+
+        Ok((public_pem, private_pem.to_string()))
+    })
+}
+
+/// Generate an Ed25519 keypair and return
+/// `{public_pem, private_pem, public_multibase}`.
+#[rustler::nif]
+fn generate_ed25519_keypair() -> Guarded<Result<(String, String, String), Atom>> {
+    guard(|| {
+        let signing_key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+
+        // PKCS#8 v1 (no embedded public key): OpenSSL and Erlang's
+        // `:public_key` reject the v2 encoding `SigningKey::to_pkcs8_pem` emits.
+        let private_pem = KeypairBytes {
+            secret_key: signing_key.to_bytes(),
+            public_key: None,
+        }
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|_| atoms::key_generation_failed())?;
+        let public_pem = verifying_key
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|_| atoms::key_generation_failed())?;
+
+        Ok((
+            public_pem,
+            private_pem.to_string(),
+            ed25519_multibase(verifying_key.as_bytes()),
+        ))
+    })
+}
@@ -0,0 +1,94 @@
+//! PEM key decoding shared by the signing and verification NIFs.
+
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs1v15::{SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::Sha256;
+
+/// A private key usable for HTTP Signatures / object proofs.
+pub(crate) enum PrivateKey {
+    Rsa(SigningKey<Sha256>),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+impl PrivateKey {
+    /// Decode a PEM private key: RSA as PKCS#1 (`BEGIN RSA PRIVATE KEY`, as
+    /// written by `KeyStore`) or PKCS#8, and Ed25519 as PKCS#8.
+    pub(crate) fn from_pem(pem: &str) -> Option<Self> {
+        if let Ok(key) = RsaPrivateKey::from_pkcs1_pem(pem) {
+            return Some(Self::rsa(key));
+        }
+        if let Ok(key) = RsaPrivateKey::from_pkcs8_pem(pem) {
+            return Some(Self::rsa(key));
+        }
+        ed25519_dalek::SigningKey::from_pkcs8_pem(pem)
+            .ok()
+            .map(Self::Ed25519)
+    }
+
+    fn rsa(key: RsaPrivateKey) -> Self {
+        Self::Rsa(SigningKey::<Sha256>::new(key))
+    }
+
+    /// RSASSA-PKCS1-v1_5 with SHA-256, or pure Ed25519.
+    pub(crate) fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Rsa(key) => key.sign(message).to_vec(),
+            Self::Ed25519(key) => key.sign(message).to_bytes().to_vec(),
+        }
+    }
+}
+
+/// Multicodec prefix for an Ed25519 public key (`ed25519-pub`, varint 0xed).
+const ED25519_PUB_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// Encode an Ed25519 public key as a Multikey `publicKeyMultibase` value
+/// (base58btc, `z` prefix), as used by FEP-521a and the data integrity specs.
+pub(crate) fn ed25519_multibase(public_key: &[u8; 32]) -> String {
+    let mut bytes = Vec::with_capacity(34);
+    bytes.extend_from_slice(&ED25519_PUB_MULTICODEC);
+    bytes.extend_from_slice(public_key);
+    format!("z{}", bs58::encode(bytes).into_string())
+}
+
+/// A public key for verifying HTTP Signatures / object proofs.
+pub(crate) enum PublicKey {
+    Rsa(VerifyingKey<Sha256>),
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl PublicKey {
+    /// Decode a PEM public key: SubjectPublicKeyInfo (`BEGIN PUBLIC KEY`) for
+    /// RSA or Ed25519, or PKCS#1 (`BEGIN RSA PUBLIC KEY`) for RSA.
+    pub(crate) fn from_pem(pem: &str) -> Option<Self> {
+        if let Ok(key) = RsaPublicKey::from_public_key_pem(pem) {
+            return Some(Self::Rsa(VerifyingKey::new(key)));
+        }
+        if let Ok(key) = RsaPublicKey::from_pkcs1_pem(pem) {
+            return Some(Self::Rsa(VerifyingKey::new(key)));
+        }
+        ed25519_dalek::VerifyingKey::from_public_key_pem(pem)
+            .ok()
+            .map(Self::Ed25519)
+    }
+
+    /// Decode an Ed25519 Multikey `publicKeyMultibase` value (`z6Mk…`).
+    pub(crate) fn from_multibase(multibase: &str) -> Option<Self> {
+        let bytes = bs58::decode(multibase.strip_prefix('z')?).into_vec().ok()?;
+        let key: [u8; 32] = bytes.strip_prefix(&ED25519_PUB_MULTICODEC)?.try_into().ok()?;
+        ed25519_dalek::VerifyingKey::from_bytes(&key)
+            .ok()
+            .map(Self::Ed25519)
+    }
+
+    pub(crate) fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match self {
+            Self::Rsa(key) => rsa::pkcs1v15::Signature::try_from(signature)
+                .is_ok_and(|sig| key.verify(message, &sig).is_ok()),
+            Self::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|sig| key.verify_strict(message, &sig).is_ok()),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod digest;
+mod id;
+mod info;
+mod jsonld;
+mod keygen;
+mod keys;
+mod password;
+mod pow;
+mod proof;
+mod proxy;
+mod signature;
+
+rustler::init!("Elixir.Baudrate.Crypto.Native");
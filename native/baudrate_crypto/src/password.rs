@@ -0,0 +1,41 @@
+//! Password strength estimation (zxcvbn).
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::NifMap;
+
+#[derive(NifMap)]
+struct Strength {
+    /// 0 (too guessable) to 4 (very unguessable).
+    score: u8,
+    guesses: u64,
+    guesses_log10: f64,
+    /// Time to crack offline against a slow hash such as bcrypt, e.g.
+    /// `"3 hours"`.
+    crack_time: String,
+    warning: Option<String>,
+    suggestions: Vec<String>,
+}
+
+/// Estimate the strength of `password`.  `user_inputs` are words an
+/// attacker would try first (username, display name, instance name); they
+/// are matched case-insensitively.  Only the first 100 characters count.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn password_strength(password: &str, user_inputs: Vec<String>) -> Guarded<Strength> {
+    guard(|| {
+        let user_inputs: Vec<&str> = user_inputs.iter().map(String::as_str).collect();
+        let entropy = zxcvbn::zxcvbn(password, &user_inputs);
+        let feedback = entropy.feedback();
+
+        Strength {
+            score: entropy.score().into(),
+            guesses: entropy.guesses(),
+            // The empty password reports -infinity, which has no Erlang float.
+            guesses_log10: entropy.guesses_log10().max(0.0),
+            crack_time: entropy.crack_times().offline_slow_hashing_1e4_per_second().to_string(),
+            warning: feedback.and_then(|f| f.warning()).map(|w| w.to_string()),
+            suggestions: feedback
+                .map(|f| f.suggestions().iter().map(|s| s.to_string()).collect())
+                .unwrap_or_default(),
+        }
+    })
+}
@@ -0,0 +1,91 @@
+//! Hashcash-style proof of work for rate-limiting anonymous forms.
+//!
+//! A challenge is `<difficulty>:<random>`, where the random part is 16
+//! bytes of unpadded URL-safe base64.  The client searches for a nonce such
+//! that `SHA-256(challenge ":" nonce)` starts with `difficulty` zero bits;
+//! checking it costs the server a single hash.
+
+use baudrate_nif::guard::{guard, Guarded};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine as _;
+use rand_core::{OsRng, RngCore};
+use rustler::{Atom, Encoder, Env, Term};
+use sha2::{Digest, Sha256};
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+        invalid_difficulty,
+        malformed_nonce,
+        insufficient_work,
+    }
+}
+
+const MIN_DIFFICULTY: u32 = 1;
+const MAX_DIFFICULTY: u32 = 32;
+const CHALLENGE_BYTES: usize = 16;
+const MAX_NONCE_LEN: usize = 64;
+
+fn check_difficulty(difficulty: u32) -> Result<(), Atom> {
+    if (MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&difficulty) {
+        Ok(())
+    } else {
+        Err(atoms::invalid_difficulty())
+    }
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for &byte in hash {
+        bits += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Return a fresh challenge requiring `difficulty` leading zero bits.
+#[rustler::nif]
+fn pow_challenge(difficulty: u32) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        check_difficulty(difficulty)?;
+        let mut random = [0u8; CHALLENGE_BYTES];
+        OsRng.fill_bytes(&mut random);
+        Ok(format!("{}:{}", difficulty, BASE64_URL.encode(random)))
+    })
+}
+
+/// Check that `nonce` solves `challenge` with at least `difficulty` leading
+/// zero bits.  The nonce must be 1–64 printable ASCII characters.
+#[rustler::nif]
+fn pow_verify<'a>(
+    env: Env<'a>,
+    challenge: &str,
+    nonce: &str,
+    difficulty: u32,
+) -> Guarded<Term<'a>> {
+    guard(|| {
+        if let Err(reason) = check_difficulty(difficulty) {
+            return (atoms::error(), reason).encode(env);
+        }
+        if nonce.is_empty()
+            || nonce.len() > MAX_NONCE_LEN
+            || !nonce.bytes().all(|b| b.is_ascii_graphic())
+        {
+            return (atoms::error(), atoms::malformed_nonce()).encode(env);
+        }
+
+        let hash = Sha256::new()
+            .chain_update(challenge)
+            .chain_update(":")
+            .chain_update(nonce)
+            .finalize();
+        if leading_zero_bits(&hash) >= difficulty {
+            atoms::ok().encode(env)
+        } else {
+            (atoms::error(), atoms::insufficient_work()).encode(env)
+        }
+    })
+}
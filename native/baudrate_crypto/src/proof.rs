@@ -0,0 +1,168 @@
+//! FEP-8b32 object integrity proofs (`eddsa-jcs-2022` Data Integrity).
+
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::time::UtcDateTime;
+use crate::keys::{PrivateKey, PublicKey};
+use rustler::{Atom, Encoder, Env, Term};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+        invalid_json,
+        invalid_private_key,
+        invalid_proof,
+        invalid_public_key,
+        invalid_signature,
+        missing_proof,
+        unsupported_cryptosuite,
+    }
+}
+
+const PROOF_TYPE: &str = "DataIntegrityProof";
+const CRYPTOSUITE: &str = "eddsa-jcs-2022";
+const DID_KEY_PREFIX: &str = "did:key:";
+
+/// `SHA-256(JCS(proof_config)) || SHA-256(JCS(document))` — the data that is
+/// signed under `eddsa-jcs-2022`.  `proof_config` gets the document's
+/// `@context`, as the cryptosuite's proof configuration step requires.
+fn hash_data(document: &Map<String, Value>, proof_config: &Map<String, Value>) -> Option<Vec<u8>> {
+    let mut config = proof_config.clone();
+    match document.get("@context") {
+        Some(context) => config.insert("@context".to_string(), context.clone()),
+        None => config.remove("@context"),
+    };
+
+    let config_jcs = serde_json_canonicalizer::to_vec(&Value::Object(config)).ok()?;
+    let document_jcs = serde_json_canonicalizer::to_vec(&Value::Object(document.clone())).ok()?;
+
+    let mut data = Sha256::digest(config_jcs).to_vec();
+    data.extend_from_slice(&Sha256::digest(document_jcs));
+    Some(data)
+}
+
+fn parse_object(json: &str) -> Result<Map<String, Value>, Atom> {
+    match serde_json::from_str(json) {
+        Ok(Value::Object(object)) => Ok(object),
+        _ => Err(atoms::invalid_json()),
+    }
+}
+
+/// Add an `eddsa-jcs-2022` proof to the JSON object `json` and return the
+/// secured document.  Any existing `proof` is replaced.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn sign_object(
+    json: &str,
+    key_pem: &str,
+    verification_method: &str,
+) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        let key = match PrivateKey::from_pem(key_pem) {
+            Some(key @ PrivateKey::Ed25519(_)) => key,
+            _ => return Err(atoms::invalid_private_key()),
+        };
+
+        let mut document = parse_object(json)?;
+        document.remove("proof");
+
+        let mut proof = Map::new();
+        proof.insert("type".into(), PROOF_TYPE.into());
+        proof.insert("cryptosuite".into(), CRYPTOSUITE.into());
+        proof.insert("verificationMethod".into(), verification_method.into());
+        proof.insert("proofPurpose".into(), "assertionMethod".into());
+        proof.insert("created".into(), UtcDateTime::now().to_rfc3339().into());
+
+        let data = hash_data(&document, &proof).ok_or_else(atoms::invalid_json)?;
+        let proof_value = format!("z{}", bs58::encode(key.sign(&data)).into_string());
+        proof.insert("proofValue".into(), proof_value.into());
+
+        document.insert("proof".into(), Value::Object(proof));
+        serde_json::to_string(&document).map_err(|_| atoms::invalid_json())
+    })
+}
+
+/// Pick the `eddsa-jcs-2022` proof out of `proof` (an object or an array).
+fn select_proof(proof: &Value) -> Result<&Map<String, Value>, Atom> {
+    let is_ours = |p: &&Map<String, Value>| {
+        p.get("type").and_then(Value::as_str) == Some(PROOF_TYPE)
+            && p.get("cryptosuite").and_then(Value::as_str) == Some(CRYPTOSUITE)
+    };
+
+    let candidates: Vec<&Map<String, Value>> = match proof {
+        Value::Object(p) => vec![p],
+        Value::Array(ps) => ps.iter().filter_map(Value::as_object).collect(),
+        _ => return Err(atoms::invalid_proof()),
+    };
+
+    candidates
+        .into_iter()
+        .find(is_ours)
+        .ok_or_else(atoms::unsupported_cryptosuite)
+}
+
+/// Resolve the verification key: an explicit multibase / PEM key if given,
+/// otherwise a self-describing `did:key` verification method.
+fn resolve_key(proof: &Map<String, Value>, public_key: Option<&str>) -> Result<PublicKey, Atom> {
+    let key = match public_key {
+        Some(key) if key.starts_with('z') => PublicKey::from_multibase(key),
+        Some(pem) => PublicKey::from_pem(pem),
+        None => proof
+            .get("verificationMethod")
+            .and_then(Value::as_str)
+            .and_then(|vm| vm.strip_prefix(DID_KEY_PREFIX))
+            .and_then(|rest| PublicKey::from_multibase(rest.split('#').next().unwrap_or(rest))),
+    };
+
+    match key {
+        Some(key @ PublicKey::Ed25519(_)) => Ok(key),
+        _ => Err(atoms::invalid_public_key()),
+    }
+}
+
+fn verify(json: &str, public_key: Option<&str>) -> Result<(), Atom> {
+    let mut document = parse_object(json)?;
+    let proof_value = document.remove("proof").ok_or_else(atoms::missing_proof)?;
+    let mut proof = select_proof(&proof_value)?.clone();
+
+    let signature = proof
+        .remove("proofValue")
+        .and_then(|v| v.as_str().and_then(|s| s.strip_prefix('z')).map(str::to_owned))
+        .and_then(|encoded| bs58::decode(encoded).into_vec().ok())
+        .ok_or_else(atoms::invalid_proof)?;
+
+    // A proof-level @context must be a prefix of the document's @context.
+    if let Some(proof_context) = proof.get("@context") {
+        let as_list = |v: &Value| match v {
+            Value::Array(items) => items.clone(),
+            other => vec![other.clone()],
+        };
+        let document_context = document.get("@context").map(as_list).unwrap_or_default();
+        if !document_context.starts_with(&as_list(proof_context)) {
+            return Err(atoms::invalid_proof());
+        }
+    }
+
+    let key = resolve_key(&proof, public_key)?;
+    let data = hash_data(&document, &proof).ok_or_else(atoms::invalid_json)?;
+
+    if key.verify(&data, &signature) {
+        Ok(())
+    } else {
+        Err(atoms::invalid_signature())
+    }
+}
+
+/// Verify the `eddsa-jcs-2022` proof on the JSON object `json`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_object_proof<'a>(
+    env: Env<'a>,
+    json: &str,
+    public_key: Option<&str>,
+) -> Guarded<Term<'a>> {
+    guard(|| match verify(json, public_key) {
+        Ok(()) => atoms::ok().encode(env),
+        Err(reason) => (atoms::error(), reason).encode(env),
+    })
+}
@@ -0,0 +1,34 @@
+//! HMAC-signed media proxy URLs (camo-style).
+
+use baudrate_nif::guard::{guard, Guarded};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use rustler::Binary;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac(secret: &[u8], url: &str) -> HmacSha256 {
+    // HMAC accepts keys of any length, so this cannot fail.
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(url.as_bytes());
+    mac
+}
+
+/// Return the unpadded URL-safe base64 HMAC-SHA256 of `url` under `secret`,
+/// suitable for embedding in a proxy path segment.
+#[rustler::nif]
+fn sign_proxy_url(secret: Binary, url: &str) -> Guarded<String> {
+    guard(|| BASE64_URL.encode(mac(secret.as_slice(), url).finalize().into_bytes()))
+}
+
+/// Check a signature produced by [`sign_proxy_url`] in constant time.
+/// Malformed signatures are simply invalid.
+#[rustler::nif]
+fn verify_proxy_url(secret: Binary, signature: &str, url: &str) -> Guarded<bool> {
+    guard(|| match BASE64_URL.decode(signature) {
+        Ok(bytes) => mac(secret.as_slice(), url).verify_slice(&bytes).is_ok(),
+        Err(_) => false,
+    })
+}
@@ -0,0 +1,286 @@
+//! draft-cavage-http-signatures-12 signing and verification.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::keys::{PrivateKey, PublicKey};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use rustler::{Atom, Encoder, Env, Term};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+        expired,
+        invalid_key_id,
+        invalid_private_key,
+        invalid_pseudo_header,
+        invalid_public_key,
+        invalid_signature,
+        invalid_signature_encoding,
+        key_mismatch,
+        malformed_signature,
+        missing_header,
+        not_yet_valid,
+        unsupported_algorithm,
+    }
+}
+
+const REQUEST_TARGET: &str = "(request-target)";
+const CREATED: &str = "(created)";
+const EXPIRES: &str = "(expires)";
+
+/// Allowed clock skew when checking `(created)` against the local clock.
+const MAX_CLOCK_SKEW_SECS: u64 = 300;
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Build the signing string covering `names` (in order).  Header names are
+/// matched case-insensitively and repeated headers are combined with `", "`
+/// as required by the draft.  Returns `None` if a covered header is absent.
+fn signing_string(
+    names: &[String],
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+) -> Option<String> {
+    let mut lines = Vec::with_capacity(names.len());
+
+    for name in names {
+        if name == REQUEST_TARGET {
+            lines.push(format!(
+                "{}: {} {}",
+                REQUEST_TARGET,
+                method.to_ascii_lowercase(),
+                path
+            ));
+            continue;
+        }
+
+        let values: Vec<&str> = headers
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        lines.push(format!("{}: {}", name, values.join(", ")));
+    }
+
+    Some(lines.join("\n"))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether `key_id` can be written as a quoted parameter value as it is: a
+/// quote or backslash would end or escape the string early, and a control
+/// character (a line break above all) would split the header.
+fn is_quotable(key_id: &str) -> bool {
+    !key_id.chars().any(|c| c == '"' || c == '\\' || c.is_control())
+}
+
+/// Sign an outgoing request and return the `Signature` header value.
+///
+/// `headers` is an ordered list of `{name, value}` pairs to cover, in
+/// addition to the implicit `(request-target)`.  The `(created)` and
+/// `(expires)` pseudo-headers may be included with Unix timestamp values;
+/// they are signed like any other header and also emitted as the
+/// `created` / `expires` signature parameters.  A `key_id` containing a
+/// quote, backslash, or control character is rejected.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn sign_request(
+    key_pem: &str,
+    key_id: &str,
+    method: &str,
+    path: &str,
+    headers: Vec<(String, String)>,
+) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        if !is_quotable(key_id) {
+            return Err(atoms::invalid_key_id());
+        }
+        let key = PrivateKey::from_pem(key_pem).ok_or_else(atoms::invalid_private_key)?;
+
+        let mut params = vec![
+            format!("keyId=\"{}\"", key_id),
+            "algorithm=\"hs2019\"".to_string(),
+        ];
+
+        for (pseudo, param) in [(CREATED, "created"), (EXPIRES, "expires")] {
+            if let Some(value) = find_header(&headers, pseudo) {
+                if value.parse::<u64>().is_err() {
+                    return Err(atoms::invalid_pseudo_header());
+                }
+                params.push(format!("{}={}", param, value));
+            }
+        }
+
+        let mut names = vec![REQUEST_TARGET.to_string()];
+        for (name, _) in &headers {
+            let name = name.to_ascii_lowercase();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        let message = signing_string(&names, method, path, &headers)
+            .expect("every covered header comes from the header list");
+        let signature = key.sign(message.as_bytes());
+
+        params.push(format!("headers=\"{}\"", names.join(" ")));
+        params.push(format!("signature=\"{}\"", BASE64.encode(signature)));
+
+        Ok(params.join(","))
+    })
+}
+
+/// Parse a `Signature` header into its parameters.  Values may be quoted
+/// strings or bare tokens (`created=1402170695`).
+fn parse_params(header: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut rest = header.trim();
+
+    while !rest.is_empty() {
+        let eq = rest.find('=')?;
+        let key = rest[..eq].trim();
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            return None;
+        }
+        rest = rest[eq + 1..].trim_start();
+
+        let value;
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"')?;
+            value = &quoted[..end];
+            rest = &quoted[end + 1..];
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            value = rest[..end].trim();
+            rest = &rest[end..];
+        }
+        params.insert(key.to_string(), value.to_string());
+
+        rest = rest.trim_start();
+        match rest.strip_prefix(',') {
+            Some(next) => rest = next.trim_start(),
+            None if rest.is_empty() => {}
+            None => return None,
+        }
+    }
+
+    Some(params)
+}
+
+fn check_algorithm(algorithm: Option<&str>, key: &PublicKey) -> Result<(), Atom> {
+    match (algorithm, key) {
+        (None | Some("hs2019"), _) => Ok(()),
+        (Some("rsa-sha256"), PublicKey::Rsa(_)) => Ok(()),
+        (Some("ed25519"), PublicKey::Ed25519(_)) => Ok(()),
+        (Some("rsa-sha256" | "ed25519"), _) => Err(atoms::key_mismatch()),
+        _ => Err(atoms::unsupported_algorithm()),
+    }
+}
+
+fn check_validity(params: &HashMap<String, String>, now: u64) -> Result<(), Atom> {
+    let timestamp = |name: &str| -> Result<Option<u64>, Atom> {
+        params
+            .get(name)
+            .map(|v| v.parse::<u64>().map_err(|_| atoms::malformed_signature()))
+            .transpose()
+    };
+
+    if let Some(created) = timestamp("created")? {
+        if created > now + MAX_CLOCK_SKEW_SECS {
+            return Err(atoms::not_yet_valid());
+        }
+    }
+    if let Some(expires) = timestamp("expires")? {
+        if expires < now {
+            return Err(atoms::expired());
+        }
+    }
+    Ok(())
+}
+
+fn verify(
+    header: &str,
+    method: &str,
+    path: &str,
+    mut headers: Vec<(String, String)>,
+    public_key_pem: &str,
+) -> Result<(), Atom> {
+    let params = parse_params(header).ok_or_else(atoms::malformed_signature)?;
+    let encoded = params
+        .get("signature")
+        .filter(|_| params.contains_key("keyId"))
+        .ok_or_else(atoms::malformed_signature)?;
+
+    let key = PublicKey::from_pem(public_key_pem).ok_or_else(atoms::invalid_public_key)?;
+    check_algorithm(params.get("algorithm").map(String::as_str), &key)?;
+    check_validity(&params, unix_now())?;
+
+    // Default covered headers to `date`, matching `HTTPSignature` on the
+    // Elixir side (and what Mastodon-era implementations assume).
+    let names: Vec<String> = params
+        .get("headers")
+        .map(String::as_str)
+        .unwrap_or("date")
+        .split_whitespace()
+        .map(str::to_ascii_lowercase)
+        .collect();
+
+    // Pseudo-headers are signed with the parameter values, never with
+    // caller-supplied header values.
+    headers.retain(|(n, _)| !n.starts_with('('));
+    for (pseudo, param) in [(CREATED, "created"), (EXPIRES, "expires")] {
+        if let Some(value) = params.get(param) {
+            headers.push((pseudo.to_string(), value.clone()));
+        }
+    }
+
+    let message =
+        signing_string(&names, method, path, &headers).ok_or_else(atoms::missing_header)?;
+    let signature = BASE64
+        .decode(encoded)
+        .map_err(|_| atoms::invalid_signature_encoding())?;
+
+    if key.verify(message.as_bytes(), &signature) {
+        Ok(())
+    } else {
+        Err(atoms::invalid_signature())
+    }
+}
+
+/// Verify an inbound request's `Signature` header against `public_key_pem`.
+///
+/// Returns `:ok` or `{:error, reason}`; see `Baudrate.Crypto.Native` for the
+/// reason atoms.  Date / Digest header checks remain the caller's concern.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_signature<'a>(
+    env: Env<'a>,
+    signature_header: &str,
+    method: &str,
+    path: &str,
+    headers: Vec<(String, String)>,
+    public_key_pem: &str,
+) -> Guarded<Term<'a>> {
+    guard(|| {
+        let verified = verify(signature_header, method, path, headers, public_key_pem);
+        match verified {
+            Ok(()) => atoms::ok().encode(env),
+            Err(reason) => (atoms::error(), reason).encode(env),
+        }
+    })
+}
@@ -0,0 +1,125 @@
+//! Atom 1.0 (RFC 4287) feed generation.
+
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::time::UtcDateTime;
+use crate::input::{get, optional_rfc3339, require};
+use crate::xml::push_escaped;
+use rustler::{NifResult, Term};
+
+fn push_element(out: &mut String, indent: &str, name: &str, attrs: &str, text: &str) {
+    out.push_str(indent);
+    out.push('<');
+    out.push_str(name);
+    out.push_str(attrs);
+    out.push('>');
+    push_escaped(out, text);
+    out.push_str("</");
+    out.push_str(name);
+    out.push_str(">\n");
+}
+
+fn push_link(out: &mut String, indent: &str, href: &str, rel: &str, media_type: &str) {
+    out.push_str(indent);
+    out.push_str("<link href=\"");
+    push_escaped(out, href);
+    out.push_str("\" rel=\"");
+    out.push_str(rel);
+    out.push_str("\" type=\"");
+    out.push_str(media_type);
+    out.push_str("\"/>\n");
+}
+
+fn push_entry(out: &mut String, entry: Term, feed_updated: &str) -> NifResult<()> {
+    let id: String = require(entry, "id")?;
+    let title: String = require(entry, "title")?;
+    let link: Option<String> = get(entry, "link")?;
+    let published = optional_rfc3339(entry, "published")?;
+    let updated = optional_rfc3339(entry, "updated")?
+        .or_else(|| published.clone())
+        .unwrap_or_else(|| feed_updated.to_string());
+    let author: Option<String> = get(entry, "author")?;
+    let summary: Option<String> = get(entry, "summary")?;
+    let content: Option<String> = get(entry, "content")?;
+    let categories: Vec<String> = get(entry, "categories")?.unwrap_or_default();
+
+    out.push_str("  <entry>\n");
+    push_element(out, "    ", "title", "", &title);
+    if let Some(link) = &link {
+        push_link(out, "    ", link, "alternate", "text/html");
+    }
+    push_element(out, "    ", "id", "", &id);
+    if let Some(published) = &published {
+        push_element(out, "    ", "published", "", published);
+    }
+    push_element(out, "    ", "updated", "", &updated);
+    if let Some(author) = &author {
+        out.push_str("    <author>\n");
+        push_element(out, "      ", "name", "", author);
+        out.push_str("    </author>\n");
+    }
+    for category in &categories {
+        out.push_str("    <category term=\"");
+        push_escaped(out, category);
+        out.push_str("\"/>\n");
+    }
+    if let Some(summary) = &summary {
+        push_element(out, "    ", "summary", " type=\"html\"", summary);
+    }
+    if let Some(content) = &content {
+        push_element(out, "    ", "content", " type=\"html\"", content);
+    }
+    out.push_str("  </entry>\n");
+
+    Ok(())
+}
+
+/// Build an Atom 1.0 document.
+///
+/// `feed_meta` is a map with `:title` and `:self_url` (required) and
+/// optionally `:id` (defaults to `:self_url`), `:link`, `:subtitle`,
+/// `:language`, and `:updated` (`DateTime`; defaults to now).  Each entry is
+/// a map with `:id` and `:title` (required) and optionally `:link`,
+/// `:published`, `:updated`, `:author`, `:summary` (HTML), `:content`
+/// (HTML), and `:categories`.  Missing required keys raise `ArgumentError`.
+#[rustler::nif]
+fn build_atom<'a>(feed_meta: Term<'a>, entries: Vec<Term<'a>>) -> NifResult<Guarded<String>> {
+    guard(|| {
+        let title: String = require(feed_meta, "title")?;
+        let self_url: String = require(feed_meta, "self_url")?;
+        let id: String = get(feed_meta, "id")?.unwrap_or_else(|| self_url.clone());
+        let link: Option<String> = get(feed_meta, "link")?;
+        let subtitle: Option<String> = get(feed_meta, "subtitle")?;
+        let language: Option<String> = get(feed_meta, "language")?;
+        let updated = optional_rfc3339(feed_meta, "updated")?
+            .unwrap_or_else(|| UtcDateTime::now().to_rfc3339());
+
+        let mut out = String::with_capacity(1024 + entries.len() * 2048);
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\"");
+        if let Some(language) = &language {
+            out.push_str(" xml:lang=\"");
+            push_escaped(&mut out, language);
+            out.push('"');
+        }
+        out.push_str(">\n");
+
+        push_element(&mut out, "  ", "title", "", &title);
+        if let Some(subtitle) = &subtitle {
+            push_element(&mut out, "  ", "subtitle", "", subtitle);
+        }
+        if let Some(link) = &link {
+            push_link(&mut out, "  ", link, "alternate", "text/html");
+        }
+        push_link(&mut out, "  ", &self_url, "self", "application/atom+xml");
+        push_element(&mut out, "  ", "id", "", &id);
+        push_element(&mut out, "  ", "updated", "", &updated);
+
+        for entry in entries {
+            push_entry(&mut out, entry, &updated)?;
+        }
+
+        out.push_str("</feed>\n");
+        Ok(out)
+    })
+    .transpose()
+}
@@ -0,0 +1,54 @@
+//! Decoding helpers for the map arguments of the feed builders.
+
+use rustler::types::atom::nil;
+use rustler::{Atom, Decoder, Error, NifResult, Term};
+
+/// Fetch an optional key from an Elixir map with atom keys.  Missing keys
+/// and `nil` values both decode as `None`.
+pub(crate) fn get<'a, T: Decoder<'a>>(map: Term<'a>, key: &str) -> NifResult<Option<T>> {
+    let key = Atom::from_str(map.get_env(), key)?;
+    match map.map_get(key) {
+        Ok(value) if value == nil().to_term(map.get_env()) => Ok(None),
+        Ok(value) => value.decode().map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+pub(crate) fn require<'a, T: Decoder<'a>>(map: Term<'a>, key: &str) -> NifResult<T> {
+    get(map, key)?.ok_or(Error::BadArg)
+}
+
+/// Format an Elixir `DateTime` (or `NaiveDateTime`, taken as UTC) as
+/// RFC 3339, e.g. `2026-02-23T05:57:22Z` or `2026-02-23T14:57:22+09:00`.
+fn rfc3339(datetime: Term) -> NifResult<String> {
+    let field = |key| -> NifResult<i64> { require(datetime, key) };
+    let offset: i64 = get::<i64>(datetime, "utc_offset")?.unwrap_or(0)
+        + get::<i64>(datetime, "std_offset")?.unwrap_or(0);
+
+    let date_time = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        field("year")?,
+        field("month")?,
+        field("day")?,
+        field("hour")?,
+        field("minute")?,
+        field("second")?
+    );
+
+    if offset == 0 {
+        return Ok(date_time + "Z");
+    }
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    Ok(format!(
+        "{}{}{:02}:{:02}",
+        date_time,
+        sign,
+        offset / 3600,
+        offset % 3600 / 60
+    ))
+}
+
+pub(crate) fn optional_rfc3339(map: Term, key: &str) -> NifResult<Option<String>> {
+    get::<Term>(map, key)?.map(rfc3339).transpose()
+}
@@ -0,0 +1,147 @@
+//! JSON Feed 1.1 (https://www.jsonfeed.org/version/1.1/) generation.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::input::{get, optional_rfc3339, require};
+use rustler::{NifResult, Term};
+use serde_json::{Map, Value};
+
+const VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// Copy optional string `key` from an Elixir map into a JSON object.
+fn put_string(object: &mut Map<String, Value>, term: Term, key: &str) -> NifResult<()> {
+    if let Some(value) = get::<String>(term, key)? {
+        object.insert(key.to_string(), Value::String(value));
+    }
+    Ok(())
+}
+
+fn put_date(object: &mut Map<String, Value>, term: Term, from: &str, to: &str) -> NifResult<()> {
+    if let Some(date) = optional_rfc3339(term, from)? {
+        object.insert(to.to_string(), Value::String(date));
+    }
+    Ok(())
+}
+
+/// `authors` is a list of maps with optional `:name`, `:url`, and `:avatar`.
+fn put_authors(object: &mut Map<String, Value>, term: Term) -> NifResult<()> {
+    let authors: Vec<Term> = get(term, "authors")?.unwrap_or_default();
+    let mut list = Vec::with_capacity(authors.len());
+
+    for author in authors {
+        let mut json = Map::new();
+        for key in ["name", "url", "avatar"] {
+            put_string(&mut json, author, key)?;
+        }
+        if !json.is_empty() {
+            list.push(Value::Object(json));
+        }
+    }
+
+    if !list.is_empty() {
+        object.insert("authors".to_string(), Value::Array(list));
+    }
+    Ok(())
+}
+
+fn attachment(term: Term) -> NifResult<Value> {
+    let mut json = Map::new();
+    json.insert("url".to_string(), Value::String(require(term, "url")?));
+    json.insert(
+        "mime_type".to_string(),
+        Value::String(require(term, "mime_type")?),
+    );
+    put_string(&mut json, term, "title")?;
+    if let Some(size) = get::<u64>(term, "size_in_bytes")? {
+        json.insert("size_in_bytes".to_string(), size.into());
+    }
+    if let Some(duration) = get::<u64>(term, "duration_in_seconds")? {
+        json.insert("duration_in_seconds".to_string(), duration.into());
+    }
+    Ok(Value::Object(json))
+}
+
+fn item(term: Term) -> NifResult<Value> {
+    let mut json = Map::new();
+    json.insert("id".to_string(), Value::String(require(term, "id")?));
+
+    for key in [
+        "url",
+        "external_url",
+        "title",
+        "content_html",
+        "content_text",
+        "summary",
+        "image",
+        "language",
+    ] {
+        put_string(&mut json, term, key)?;
+    }
+
+    // The spec requires at least one of content_html / content_text.
+    if !json.contains_key("content_html") && !json.contains_key("content_text") {
+        json.insert("content_text".to_string(), Value::String(String::new()));
+    }
+
+    put_date(&mut json, term, "published", "date_published")?;
+    put_date(&mut json, term, "updated", "date_modified")?;
+    put_authors(&mut json, term)?;
+
+    let tags: Vec<String> = get(term, "tags")?.unwrap_or_default();
+    if !tags.is_empty() {
+        json.insert("tags".to_string(), tags.into());
+    }
+
+    let attachments: Vec<Term> = get(term, "attachments")?.unwrap_or_default();
+    if !attachments.is_empty() {
+        let list = attachments
+            .into_iter()
+            .map(attachment)
+            .collect::<NifResult<Vec<_>>>()?;
+        json.insert("attachments".to_string(), Value::Array(list));
+    }
+
+    Ok(Value::Object(json))
+}
+
+/// Build a JSON Feed 1.1 document.
+///
+/// `feed_meta` requires `:title`; `:home_page_url`, `:feed_url`,
+/// `:description`, `:language`, `:icon`, `:favicon`, and `:authors` are
+/// optional.  Each entry requires `:id`; `:url`, `:external_url`, `:title`,
+/// `:content_html`, `:content_text`, `:summary`, `:image`, `:language`,
+/// `:published` / `:updated` (`DateTime`), `:authors`, `:tags`, and
+/// `:attachments` (maps with `:url` and `:mime_type`, optionally `:title`,
+/// `:size_in_bytes`, `:duration_in_seconds`) are optional.  Missing required
+/// keys raise `ArgumentError`.
+#[rustler::nif]
+fn build_json_feed<'a>(feed_meta: Term<'a>, entries: Vec<Term<'a>>) -> NifResult<Guarded<String>> {
+    guard(|| {
+        let mut json = Map::new();
+        json.insert("version".to_string(), Value::String(VERSION.to_string()));
+        json.insert(
+            "title".to_string(),
+            Value::String(require(feed_meta, "title")?),
+        );
+
+        for key in [
+            "home_page_url",
+            "feed_url",
+            "description",
+            "language",
+            "icon",
+            "favicon",
+        ] {
+            put_string(&mut json, feed_meta, key)?;
+        }
+        put_authors(&mut json, feed_meta)?;
+
+        let items = entries
+            .into_iter()
+            .map(item)
+            .collect::<NifResult<Vec<_>>>()?;
+        json.insert("items".to_string(), Value::Array(items));
+
+        Ok(Value::Object(json).to_string())
+    })
+    .transpose()
+}
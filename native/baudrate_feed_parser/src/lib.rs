@@ -1,4 +1,12 @@
-use feedparser_rs::{parse, Entry};
+mod atom;
+mod info;
+mod input;
+mod json_feed;
+mod opml;
+mod xml;
+
+use baudrate_nif::guard::{guard, Guarded};
+use feedparser_rs::{parse, Entry, FeedMeta, Link};
 
 /// Normalized feed entry returned to Elixir.
 ///
@@ -24,22 +32,60 @@ struct NifEntry {
     tags: Vec<String>,
     /// Publication date as RFC 3339 string, or `None`.
     published_rfc3339: Option<String>,
+    /// Last-updated date as RFC 3339 string, or `None`.
+    updated_rfc3339: Option<String>,
+}
+
+/// Feed-level metadata plus entries, returned by `parse_feed_document`.
+#[derive(rustler::NifStruct)]
+#[module = "Baudrate.Bots.FeedParserNative.Feed"]
+struct NifFeed {
+    /// Raw feed title (same caveats as `NifEntry::title`).
+    title: Option<String>,
+    /// Site URL (alternate link preferred; falls back to first link).
+    link: Option<String>,
+    /// Feed description / Atom subtitle.
+    description: Option<String>,
+    /// Feed last-updated date as RFC 3339 string, or `None`.
+    updated_rfc3339: Option<String>,
+    entries: Vec<NifEntry>,
 }
 
 /// Parse an RSS 2.0, Atom 1.0, RSS 1.0, or JSON feed.
 ///
 /// Returns `{:ok, entries}` or `{:error, reason}`.
 #[rustler::nif(schedule = "DirtyCpu")]
-fn parse_feed(data: rustler::Binary) -> Result<Vec<NifEntry>, String> {
-    let feed = parse(data.as_slice()).map_err(|e| e.to_string())?;
+fn parse_feed(data: rustler::Binary) -> Guarded<Result<Vec<NifEntry>, String>> {
+    guard(|| {
+        let feed = parse(data.as_slice()).map_err(|e| e.to_string())?;
 
-    let entries = feed
-        .entries
-        .iter()
-        .map(nif_entry_from)
-        .collect();
+        let entries = feed.entries.iter().map(nif_entry_from).collect();
+
+        Ok(entries)
+    })
+}
 
-    Ok(entries)
+/// Parse a feed like `parse_feed`, also returning feed-level metadata.
+///
+/// Returns `{:ok, feed}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_feed_document(data: rustler::Binary) -> Guarded<Result<NifFeed, String>> {
+    guard(|| {
+        let parsed = parse(data.as_slice()).map_err(|e| e.to_string())?;
+        let meta: &FeedMeta = &parsed.feed;
+
+        Ok(NifFeed {
+            title: meta.title.clone(),
+            link: non_empty(meta.link.as_deref()).or_else(|| pick_from_links(&meta.links)),
+            description: meta.subtitle.clone(),
+            updated_rfc3339: meta.updated.or(meta.published).map(|dt| dt.to_rfc3339()),
+            entries: parsed.entries.iter().map(nif_entry_from).collect(),
+        })
+    })
+}
+
+fn non_empty(s: Option<&str>) -> Option<String> {
+    s.filter(|s| !s.is_empty()).map(|s| s.to_owned())
 }
 
 fn nif_entry_from(entry: &Entry) -> NifEntry {
@@ -66,6 +112,8 @@ fn nif_entry_from(entry: &Entry) -> NifEntry {
         .or(entry.updated)
         .map(|dt| dt.to_rfc3339());
 
+    let updated_rfc3339 = entry.updated.map(|dt| dt.to_rfc3339());
+
     NifEntry {
         id,
         title,
@@ -74,6 +122,7 @@ fn nif_entry_from(entry: &Entry) -> NifEntry {
         summary,
         tags,
         published_rfc3339,
+        updated_rfc3339,
     }
 }
 
@@ -85,10 +134,14 @@ fn pick_link(entry: &Entry) -> Option<String> {
         }
     }
 
-    // 2. Walk `links` array: prefer rel="alternate", then no rel, then anything.
+    pick_from_links(&entry.links)
+}
+
+/// Walk a `links` array: prefer rel="alternate", then no rel, then anything.
+fn pick_from_links(links: &[Link]) -> Option<String> {
     let mut fallback: Option<String> = None;
 
-    for link in &entry.links {
+    for link in links {
         let href = link.href.as_str();
         if href.is_empty() {
             continue;
@@ -0,0 +1,242 @@
+//! OPML subscription list parsing.
+//!
+//! Real-world OPML exports are frequently not well-formed XML: unescaped `&`
+//! in query strings, HTML entities such as `&nbsp;` in titles, unclosed
+//! `<outline>` elements, mismatched end tags, and inconsistent attribute
+//! casing (`xmlUrl` vs. `xmlurl`).  The parser therefore reads events
+//! leniently, unescapes attribute values itself, and closes any outlines
+//! still open at the end of the document instead of failing.  Nesting is
+//! capped at [`MAX_DEPTH`] so that the outline tree stays shallow enough to
+//! encode and drop without exhausting the stack.
+
+use baudrate_nif::guard::{guard, Guarded};
+use quick_xml::escape::resolve_html5_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Deepest outline nesting kept; outlines nested further are attached to
+/// their ancestor at this depth, flattening the rest of the subtree.
+const MAX_DEPTH: usize = 32;
+
+/// A single `<outline>` element: either a subscription (has `xml_url`) or a
+/// folder (has `children`).
+#[derive(rustler::NifStruct)]
+#[module = "Baudrate.Bots.FeedParserNative.Outline"]
+struct NifOutline {
+    /// `text` attribute, falling back to `title`.
+    title: Option<String>,
+    /// Feed URL (`xmlUrl`).
+    xml_url: Option<String>,
+    /// Site URL (`htmlUrl`).
+    html_url: Option<String>,
+    /// Outline `type` attribute (usually `"rss"`), lowercased.
+    r#type: Option<String>,
+    children: Vec<NifOutline>,
+}
+
+/// Parsed OPML document returned by `parse_opml`.
+#[derive(rustler::NifStruct)]
+#[module = "Baudrate.Bots.FeedParserNative.Opml"]
+struct NifOpml {
+    /// `<head><title>` text.
+    title: Option<String>,
+    /// Top-level outlines of `<body>`.
+    outlines: Vec<NifOutline>,
+}
+
+/// Parse an OPML document into its outline tree.
+///
+/// Returns `{:ok, opml}`, or `{:error, reason}` when the input contains
+/// neither an `<opml>` element nor any outline.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_opml(xml: rustler::Binary) -> Guarded<Result<NifOpml, String>> {
+    guard(|| parse(xml.as_slice()))
+}
+
+fn parse(xml: &[u8]) -> Result<NifOpml, String> {
+    let mut reader = Reader::from_reader(xml);
+    let config = reader.config_mut();
+    config.check_end_names = false;
+    config.allow_unmatched_ends = true;
+
+    let mut buf = Vec::new();
+    let mut seen_opml = false;
+    let mut in_title = false;
+    let mut title = String::new();
+    // Open outlines, innermost last.  Closed outlines are attached to their
+    // parent, or to `outlines` when no parent is open.
+    let mut open: Vec<NifOutline> = Vec::new();
+    let mut outlines: Vec<NifOutline> = Vec::new();
+    // Outlines beyond `MAX_DEPTH` that are still open.
+    let mut flattened = 0usize;
+
+    loop {
+        let position = reader.buffer_position();
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            // Syntax errors such as a bare `&` in text are recoverable: the
+            // reader resumes after the offending markup.  Stop only when it
+            // can make no further progress.
+            Err(_) if reader.buffer_position() > position => {
+                buf.clear();
+                continue;
+            }
+            Err(e) if !seen_opml && outlines.is_empty() => return Err(e.to_string()),
+            Err(_) => break,
+        };
+
+        match event {
+            Event::Start(e) => match local_name(&e).as_str() {
+                "opml" => seen_opml = true,
+                "title" if open.is_empty() && outlines.is_empty() => in_title = true,
+                "outline" if open.len() >= MAX_DEPTH => {
+                    attach(&mut open, &mut outlines, outline_from(&e));
+                    flattened += 1;
+                }
+                "outline" => open.push(outline_from(&e)),
+                _ => {}
+            },
+            Event::Empty(e) => match local_name(&e).as_str() {
+                "opml" => seen_opml = true,
+                "outline" => attach(&mut open, &mut outlines, outline_from(&e)),
+                _ => {}
+            },
+            Event::End(e) => match lowercase(e.local_name().as_ref()).as_str() {
+                "title" => in_title = false,
+                "outline" if flattened > 0 => flattened -= 1,
+                "outline" => {
+                    if let Some(outline) = open.pop() {
+                        attach(&mut open, &mut outlines, outline);
+                    }
+                }
+                // A closing `</body>` or `</opml>` ends every outline that
+                // was left open.
+                "body" | "opml" => {
+                    close_all(&mut open, &mut outlines);
+                    flattened = 0;
+                }
+                _ => {}
+            },
+            Event::Text(e) if in_title => title.push_str(&String::from_utf8_lossy(&e)),
+            Event::CData(e) if in_title => title.push_str(&String::from_utf8_lossy(&e)),
+            Event::GeneralRef(e) if in_title => {
+                let name = String::from_utf8_lossy(&e);
+                title.push_str(&resolve_entity(&name).unwrap_or_else(|| format!("&{name};")));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    close_all(&mut open, &mut outlines);
+
+    if !seen_opml && outlines.is_empty() {
+        return Err("not an OPML document".to_owned());
+    }
+
+    Ok(NifOpml {
+        title: clean(&title),
+        outlines,
+    })
+}
+
+fn attach(open: &mut [NifOutline], outlines: &mut Vec<NifOutline>, outline: NifOutline) {
+    match open.last_mut() {
+        Some(parent) => parent.children.push(outline),
+        None => outlines.push(outline),
+    }
+}
+
+fn close_all(open: &mut Vec<NifOutline>, outlines: &mut Vec<NifOutline>) {
+    while let Some(outline) = open.pop() {
+        attach(open, outlines, outline);
+    }
+}
+
+fn outline_from(e: &BytesStart) -> NifOutline {
+    let mut text = None;
+    let mut title = None;
+    let mut xml_url = None;
+    let mut html_url = None;
+    let mut kind = None;
+
+    for attr in e.html_attributes().with_checks(false).flatten() {
+        let value = clean(&unescape(&String::from_utf8_lossy(&attr.value)));
+
+        match lowercase(attr.key.local_name().as_ref()).as_str() {
+            "text" => text = text.or(value),
+            "title" => title = title.or(value),
+            "xmlurl" => xml_url = xml_url.or(value),
+            "htmlurl" => html_url = html_url.or(value),
+            "type" => kind = kind.or(value.map(|v| v.to_lowercase())),
+            _ => {}
+        }
+    }
+
+    NifOutline {
+        title: text.or(title),
+        xml_url,
+        html_url,
+        r#type: kind,
+        children: Vec::new(),
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    lowercase(e.local_name().as_ref())
+}
+
+fn lowercase(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_ascii_lowercase()
+}
+
+/// Trim and collapse whitespace; `None` when nothing is left.
+fn clean(s: &str) -> Option<String> {
+    let collapsed = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!collapsed.is_empty()).then_some(collapsed)
+}
+
+/// Unescape character references and HTML5 named entities, leaving any `&`
+/// that does not start a recognised reference as a literal ampersand.
+fn unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let resolved = rest[1..]
+            .find(';')
+            .filter(|&end| end > 0 && end <= 32)
+            .and_then(|end| resolve_entity(&rest[1..=end]).map(|s| (s, end + 2)));
+
+        match resolved {
+            Some((s, consumed)) => {
+                out.push_str(&s);
+                rest = &rest[consumed..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn resolve_entity(name: &str) -> Option<String> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code).map(String::from);
+    }
+
+    resolve_html5_entity(name).map(str::to_owned)
+}
@@ -0,0 +1,52 @@
+//! XML text escaping, shared by the Atom builder and the Elixir feed
+//! templates so that every generated document filters the same characters.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::Binary;
+
+/// Characters allowed by the XML 1.0 `Char` production.  Anything else
+/// (NUL, most C0 controls, lone surrogates, U+FFFE/U+FFFF) makes the whole
+/// document ill-formed, so it is dropped rather than escaped.
+pub(crate) fn is_xml_char(c: char) -> bool {
+    matches!(c,
+        '\u{9}' | '\u{A}' | '\u{D}'
+        | '\u{20}'..='\u{D7FF}'
+        | '\u{E000}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{10FFFF}')
+}
+
+/// Append `text` to `out` for use as element content or a quoted attribute
+/// value, without its invalid characters.
+pub(crate) fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars().filter(|&c| is_xml_char(c)) {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Escape `text` for XML element content or attribute values: `&`, `<`,
+/// `>`, `"`, and `'` become entities, and characters XML cannot carry are
+/// dropped.  Invalid UTF-8 is replaced with U+FFFD.
+#[rustler::nif]
+fn xml_escape(text: Binary) -> Guarded<String> {
+    guard(|| {
+        let text = String::from_utf8_lossy(&text);
+        let mut out = String::with_capacity(text.len());
+        push_escaped(&mut out, &text);
+        out
+    })
+}
+
+/// Drop the characters XML cannot carry from `text`, leaving markup alone,
+/// for content already escaped or wrapped in a CDATA section.  Invalid
+/// UTF-8 is replaced with U+FFFD.
+#[rustler::nif]
+fn strip_invalid_xml_chars(text: Binary) -> Guarded<String> {
+    guard(|| String::from_utf8_lossy(&text).chars().filter(|&c| is_xml_char(c)).collect())
+}
@@ -0,0 +1,117 @@
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{find_meta_name, find_meta_property, find_tag_text};
+use scraper::{Html, Selector};
+use url::Url;
+
+const MAX_TITLE_CHARS: usize = 300;
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+const MAX_SITE_NAME_CHARS: usize = 200;
+const MAX_SHORT_FIELD_CHARS: usize = 100;
+const MAX_URL_BYTES: usize = 2048;
+
+#[derive(rustler::NifStruct)]
+#[module = "Baudrate.HtmlParser.Native.Card"]
+struct Card {
+    title: Option<String>,
+    description: Option<String>,
+    image_url: Option<String>,
+    site_name: Option<String>,
+    canonical_url: Option<String>,
+    twitter_card: Option<String>,
+    twitter_site: Option<String>,
+    twitter_creator: Option<String>,
+}
+
+/// `og:*` tags are specified with `property`, but `name` is common in the wild.
+fn find_og(document: &Html, key: &str) -> Option<String> {
+    find_meta_property(document, key).or_else(|| find_meta_name(document, key))
+}
+
+/// `twitter:*` tags are specified with `name`, but `property` is common too.
+fn find_twitter(document: &Html, key: &str) -> Option<String> {
+    find_meta_name(document, key).or_else(|| find_meta_property(document, key))
+}
+
+fn find_link_href(document: &Html, rel: &str) -> Option<String> {
+    let selector = Selector::parse("link[rel][href]").unwrap();
+
+    document
+        .select(&selector)
+        .find(|el| {
+            el.value().attr("rel").is_some_and(|r| {
+                r.split_ascii_whitespace()
+                    .any(|t| t.eq_ignore_ascii_case(rel))
+            })
+        })
+        .and_then(|el| el.value().attr("href"))
+        .map(|s| s.to_string())
+}
+
+/// Collapse whitespace runs, trim, and cap at `max` characters.
+fn clean_text(text: Option<String>, max: usize) -> Option<String> {
+    let collapsed = text?.split_whitespace().collect::<Vec<_>>().join(" ");
+    let truncated: String = collapsed.chars().take(max).collect();
+    (!truncated.is_empty()).then_some(truncated)
+}
+
+/// Resolve `href` against `base`, keeping only HTTP(S) URLs of sane length.
+fn resolve_url(base: Option<&Url>, href: Option<String>) -> Option<String> {
+    let href = href?;
+    let href = href.trim();
+    let url = match base {
+        Some(base) => base.join(href).ok()?,
+        None => Url::parse(href).ok()?,
+    };
+
+    let valid = matches!(url.scheme(), "http" | "https") && url.as_str().len() <= MAX_URL_BYTES;
+    valid.then(|| url.to_string())
+}
+
+/// Parse an HTML document and extract a link preview card.
+///
+/// Open Graph tags take priority, then Twitter Card tags, then `<title>` /
+/// `<meta name="description">`.  Image and canonical URLs are resolved
+/// against `base_url` (the fetched page's URL).
+#[rustler::nif]
+fn extract_card(html: &str, base_url: &str) -> Guarded<Card> {
+    guard(|| {
+        let document = Html::parse_document(html);
+        let base = Url::parse(base_url).ok();
+
+        let title = find_og(&document, "og:title")
+            .or_else(|| find_twitter(&document, "twitter:title"))
+            .or_else(|| find_tag_text(&document, "title"));
+
+        let description = find_og(&document, "og:description")
+            .or_else(|| find_twitter(&document, "twitter:description"))
+            .or_else(|| find_meta_name(&document, "description"));
+
+        let image_url = find_og(&document, "og:image")
+            .or_else(|| find_og(&document, "og:image:url"))
+            .or_else(|| find_twitter(&document, "twitter:image"))
+            .or_else(|| find_twitter(&document, "twitter:image:src"));
+
+        let canonical_url =
+            find_link_href(&document, "canonical").or_else(|| find_og(&document, "og:url"));
+
+        Card {
+            title: clean_text(title, MAX_TITLE_CHARS),
+            description: clean_text(description, MAX_DESCRIPTION_CHARS),
+            image_url: resolve_url(base.as_ref(), image_url),
+            site_name: clean_text(find_og(&document, "og:site_name"), MAX_SITE_NAME_CHARS),
+            canonical_url: resolve_url(base.as_ref(), canonical_url),
+            twitter_card: clean_text(
+                find_twitter(&document, "twitter:card"),
+                MAX_SHORT_FIELD_CHARS,
+            ),
+            twitter_site: clean_text(
+                find_twitter(&document, "twitter:site"),
+                MAX_SHORT_FIELD_CHARS,
+            ),
+            twitter_creator: clean_text(
+                find_twitter(&document, "twitter:creator"),
+                MAX_SHORT_FIELD_CHARS,
+            ),
+        }
+    })
+}
@@ -0,0 +1,99 @@
+use baudrate_nif::guard::{guard, Guarded};
+use scraper::{Html, Selector};
+use url::Url;
+
+const APPLE_TOUCH_ICON: &str = "apple-touch-icon";
+const ICON: &str = "icon";
+
+#[derive(rustler::NifStruct)]
+#[module = "Baudrate.HtmlParser.Native.Icon"]
+struct Icon {
+    url: String,
+    rel: String,
+    size: Option<u32>,
+    content_type: Option<String>,
+}
+
+/// Map a `rel` attribute to its icon kind.  `apple-touch-icon-precomposed`
+/// counts as `apple-touch-icon` and the legacy `shortcut icon` as `icon`;
+/// `mask-icon` (monochrome Safari pinned tabs) is not a usable icon.
+fn icon_rel(rel: &str) -> Option<&'static str> {
+    let mut kind = None;
+    for token in rel.split_ascii_whitespace() {
+        if token.eq_ignore_ascii_case("apple-touch-icon")
+            || token.eq_ignore_ascii_case("apple-touch-icon-precomposed")
+        {
+            return Some(APPLE_TOUCH_ICON);
+        }
+        if token.eq_ignore_ascii_case("icon") {
+            kind = Some(ICON);
+        }
+    }
+    kind
+}
+
+/// Largest width in a `sizes` attribute (`"16x16 32x32"` → 32).  `any` and
+/// malformed tokens are ignored.
+fn largest_size(sizes: &str) -> Option<u32> {
+    sizes
+        .split_ascii_whitespace()
+        .filter_map(|token| {
+            let (w, h) = token.split_once(['x', 'X'])?;
+            h.parse::<u32>().ok()?;
+            w.parse::<u32>().ok()
+        })
+        .max()
+}
+
+/// Parse an HTML document and return its icon candidates with URLs resolved
+/// against `base_url`, most preferred first: `apple-touch-icon` before
+/// `icon`, larger declared sizes first, document order otherwise.
+#[rustler::nif]
+fn extract_icons(html: &str, base_url: &str) -> Guarded<Vec<Icon>> {
+    guard(|| {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("link[rel][href]").unwrap();
+        let base = Url::parse(base_url).ok();
+
+        let mut icons: Vec<Icon> = Vec::new();
+
+        for element in document.select(&selector) {
+            let value = element.value();
+
+            let Some(rel) = value.attr("rel").and_then(icon_rel) else {
+                continue;
+            };
+
+            let href = value.attr("href").unwrap_or("").trim();
+            let resolved = match &base {
+                Some(base) => base.join(href),
+                None => Url::parse(href),
+            };
+            let Ok(url) = resolved else {
+                continue;
+            };
+            if !matches!(url.scheme(), "http" | "https") {
+                continue;
+            }
+
+            let url = url.to_string();
+            if icons.iter().any(|icon| icon.url == url) {
+                continue;
+            }
+
+            icons.push(Icon {
+                url,
+                rel: rel.to_string(),
+                size: value.attr("sizes").and_then(largest_size),
+                content_type: value
+                    .attr("type")
+                    .map(|t| t.trim().to_ascii_lowercase())
+                    .filter(|t| !t.is_empty()),
+            });
+        }
+
+        // Stable sort keeps document order among equally preferred candidates.
+        icons.sort_by_key(|icon| (icon.rel != APPLE_TOUCH_ICON, std::cmp::Reverse(icon.size)));
+        icons
+    })
+}
@@ -1,3 +1,11 @@
+mod card;
+mod icons;
+mod info;
+mod oembed;
+mod rel_me;
+mod robots;
+
+use baudrate_nif::guard::{guard, Guarded};
 use scraper::{Html, Selector};
 
 #[derive(rustler::NifStruct)]
@@ -11,28 +19,30 @@ struct OgMetadata {
 
 /// Parse an HTML document and extract Open Graph / Twitter Card / fallback metadata.
 #[rustler::nif]
-fn parse_og_metadata(html: &str) -> OgMetadata {
-    let document = Html::parse_document(html);
+fn parse_og_metadata(html: &str) -> Guarded<OgMetadata> {
+    guard(|| {
+        let document = Html::parse_document(html);
 
-    let title = find_meta_property(&document, "og:title")
-        .or_else(|| find_meta_name(&document, "twitter:title"))
-        .or_else(|| find_tag_text(&document, "title"));
+        let title = find_meta_property(&document, "og:title")
+            .or_else(|| find_meta_name(&document, "twitter:title"))
+            .or_else(|| find_tag_text(&document, "title"));
 
-    let description = find_meta_property(&document, "og:description")
-        .or_else(|| find_meta_name(&document, "twitter:description"))
-        .or_else(|| find_meta_name(&document, "description"));
+        let description = find_meta_property(&document, "og:description")
+            .or_else(|| find_meta_name(&document, "twitter:description"))
+            .or_else(|| find_meta_name(&document, "description"));
 
-    let image_url = find_meta_property(&document, "og:image")
-        .or_else(|| find_meta_name(&document, "twitter:image"));
+        let image_url = find_meta_property(&document, "og:image")
+            .or_else(|| find_meta_name(&document, "twitter:image"));
 
-    let site_name = find_meta_property(&document, "og:site_name");
+        let site_name = find_meta_property(&document, "og:site_name");
 
-    OgMetadata {
-        title,
-        description,
-        image_url,
-        site_name,
-    }
+        OgMetadata {
+            title,
+            description,
+            image_url,
+            site_name,
+        }
+    })
 }
 
 /// Parse an HTML fragment and extract the first external URL from `<a href="...">` tags.
@@ -43,37 +53,39 @@ fn parse_og_metadata(html: &str) -> OgMetadata {
 /// - Non-HTTP(S) URLs
 /// - URLs starting with the given origin
 #[rustler::nif]
-fn extract_first_url(html: &str, origin: &str) -> Option<String> {
-    let fragment = Html::parse_fragment(html);
-    let selector = Selector::parse("a[href]").unwrap();
-
-    for element in fragment.select(&selector) {
-        let href = match element.value().attr("href") {
-            Some(h) if !h.is_empty() => h,
-            _ => continue,
-        };
-
-        if href.starts_with('#') {
-            continue;
-        }
-
-        let classes = element.value().attr("class").unwrap_or("");
-        if classes.contains("hashtag") || classes.contains("mention") {
-            continue;
-        }
-
-        if !href.starts_with("http://") && !href.starts_with("https://") {
-            continue;
-        }
-
-        if href.starts_with(origin) {
-            continue;
+fn extract_first_url(html: &str, origin: &str) -> Guarded<Option<String>> {
+    guard(|| {
+        let fragment = Html::parse_fragment(html);
+        let selector = Selector::parse("a[href]").unwrap();
+
+        for element in fragment.select(&selector) {
+            let href = match element.value().attr("href") {
+                Some(h) if !h.is_empty() => h,
+                _ => continue,
+            };
+
+            if href.starts_with('#') {
+                continue;
+            }
+
+            let classes = element.value().attr("class").unwrap_or("");
+            if classes.contains("hashtag") || classes.contains("mention") {
+                continue;
+            }
+
+            if !href.starts_with("http://") && !href.starts_with("https://") {
+                continue;
+            }
+
+            if href.starts_with(origin) {
+                continue;
+            }
+
+            return Some(href.to_string());
         }
 
-        return Some(href.to_string());
-    }
-
-    None
+        None
+    })
 }
 
 fn find_meta_property(document: &Html, property: &str) -> Option<String> {
@@ -0,0 +1,62 @@
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::Atom;
+use scraper::{Html, Selector};
+
+mod atoms {
+    rustler::atoms! {
+        json,
+        xml,
+    }
+}
+
+fn format_for(content_type: &str) -> Option<Atom> {
+    match content_type.trim().to_ascii_lowercase().as_str() {
+        "application/json+oembed" => Some(atoms::json()),
+        "text/xml+oembed" | "application/xml+oembed" => Some(atoms::xml()),
+        _ => None,
+    }
+}
+
+/// Parse an HTML document and return its oEmbed discovery endpoints as
+/// `{format, url}` pairs, JSON endpoints first, each group in document order.
+///
+/// Only `<link rel="alternate">` elements with an oEmbed `type` and an
+/// absolute HTTP(S) `href` are considered (the spec requires absolute URLs).
+#[rustler::nif]
+fn extract_oembed_links(html: &str) -> Guarded<Vec<(Atom, String)>> {
+    guard(|| {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("link[rel][type][href]").unwrap();
+
+        let mut links: Vec<(Atom, String)> = Vec::new();
+
+        for element in document.select(&selector) {
+            let value = element.value();
+
+            let alternate = value.attr("rel").is_some_and(|rel| {
+                rel.split_ascii_whitespace()
+                    .any(|t| t.eq_ignore_ascii_case("alternate"))
+            });
+            if !alternate {
+                continue;
+            }
+
+            let Some(format) = value.attr("type").and_then(format_for) else {
+                continue;
+            };
+
+            let href = value.attr("href").unwrap_or("").trim();
+            if !href.starts_with("http://") && !href.starts_with("https://") {
+                continue;
+            }
+
+            if !links.iter().any(|(_, url)| url == href) {
+                links.push((format, href.to_string()));
+            }
+        }
+
+        // Stable sort keeps document order within each format.
+        links.sort_by_key(|(format, _)| *format != atoms::json());
+        links
+    })
+}
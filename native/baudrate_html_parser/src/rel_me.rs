@@ -0,0 +1,57 @@
+use baudrate_nif::guard::{guard, Guarded};
+use scraper::{Html, Selector};
+use url::Url;
+
+/// Normalize a URL for rel=me comparison: scheme and host are lowercased by
+/// the parser, the fragment is dropped, and a trailing `/` is ignored.
+fn normalize(url: &str) -> Option<String> {
+    let mut url = Url::parse(url).ok()?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return None;
+    }
+    url.set_fragment(None);
+
+    let normalized = url.to_string();
+    Some(normalized.trim_end_matches('/').to_string())
+}
+
+fn has_rel_me(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|token| token.eq_ignore_ascii_case("me"))
+}
+
+/// Parse an HTML document and collect the absolute HTTP(S) targets of all
+/// `<a rel="me">` and `<link rel="me">` elements (deduplicated, in document
+/// order), reporting whether any of them points at `expected_url`.
+#[rustler::nif]
+fn extract_rel_me(html: &str, expected_url: &str) -> Guarded<(bool, Vec<String>)> {
+    guard(|| {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("a[rel][href], link[rel][href]").unwrap();
+        let expected = normalize(expected_url);
+
+        let mut matched = false;
+        let mut targets: Vec<String> = Vec::new();
+
+        for element in document.select(&selector) {
+            let value = element.value();
+            if !value.attr("rel").is_some_and(has_rel_me) {
+                continue;
+            }
+
+            let href = value.attr("href").unwrap_or("").trim();
+            let Some(normalized) = normalize(href) else {
+                continue;
+            };
+
+            if expected.as_deref() == Some(normalized.as_str()) {
+                matched = true;
+            }
+            if !targets.iter().any(|t| t == href) {
+                targets.push(href.to_string());
+            }
+        }
+
+        (matched, targets)
+    })
+}
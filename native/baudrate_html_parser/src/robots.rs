@@ -0,0 +1,177 @@
+//! robots.txt evaluation per RFC 9309 (plus the common `Crawl-delay`
+//! extension).
+
+use baudrate_nif::guard::{guard, Guarded};
+
+/// Bytes of a robots.txt file parsed, the least RFC 9309 §2.5 requires
+/// crawlers to read; rules after it are ignored.
+const MAX_ROBOTS_BYTES: usize = 500 * 1024;
+
+#[derive(Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<f64>,
+}
+
+/// Parse the first [`MAX_ROBOTS_BYTES`] of `robots_txt` into groups, up to
+/// the last whole line.  Consecutive `User-agent` lines share one group;
+/// rule lines before any `User-agent` are ignored.
+fn parse(robots_txt: &str) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut in_agent_lines = false;
+
+    let robots_txt = if robots_txt.len() > MAX_ROBOTS_BYTES {
+        // A line break is never inside a multi-byte character.
+        let head = &robots_txt.as_bytes()[..MAX_ROBOTS_BYTES];
+        head.iter().rposition(|&b| b == b'\n').map_or("", |end| &robots_txt[..end])
+    } else {
+        robots_txt
+    };
+
+    for line in robots_txt.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+
+        if key == "user-agent" {
+            if !in_agent_lines {
+                groups.push(Group::default());
+                in_agent_lines = true;
+            }
+            if let Some(group) = groups.last_mut() {
+                group.agents.push(value.to_ascii_lowercase());
+            }
+            continue;
+        }
+
+        in_agent_lines = false;
+        let Some(group) = groups.last_mut() else {
+            continue;
+        };
+
+        match key.as_str() {
+            "allow" => group.rules.push((true, value.to_string())),
+            "disallow" if !value.is_empty() => group.rules.push((false, value.to_string())),
+            "crawl-delay" if group.crawl_delay.is_none() => {
+                group.crawl_delay = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|d| d.is_finite() && *d >= 0.0);
+            }
+            _ => {}
+        }
+    }
+
+    groups
+}
+
+/// The product token of a `User-Agent` header: `"Baudrate/1.0 (+url)"` → `"baudrate"`.
+fn product_token(user_agent: &str) -> String {
+    user_agent
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+/// Groups that apply to `user_agent`: every group naming its product token,
+/// or failing that every `*` group.
+fn matching_groups<'a>(groups: &'a [Group], user_agent: &str) -> Vec<&'a Group> {
+    let token = product_token(user_agent);
+
+    let named: Vec<&Group> = groups
+        .iter()
+        .filter(|g| !token.is_empty() && g.agents.contains(&token))
+        .collect();
+    if !named.is_empty() {
+        return named;
+    }
+
+    groups
+        .iter()
+        .filter(|g| g.agents.iter().any(|a| a == "*"))
+        .collect()
+}
+
+/// Match `path` against a rule pattern where `*` matches any sequence and a
+/// trailing `$` anchors the end; otherwise patterns are prefix matches.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p.as_bytes(), true),
+        None => (pattern.as_bytes(), false),
+    };
+    let path = path.as_bytes();
+
+    // Positions in `path` reachable after consuming the pattern so far.
+    let mut positions = vec![0usize];
+    for &c in pattern {
+        let mut next = Vec::new();
+        if c == b'*' {
+            let start = positions.iter().copied().min().unwrap_or(path.len());
+            next.extend(start..=path.len());
+        } else {
+            for &p in &positions {
+                if p < path.len() && path[p] == c {
+                    next.push(p + 1);
+                }
+            }
+            next.dedup();
+        }
+        if next.is_empty() {
+            return false;
+        }
+        positions = next;
+    }
+
+    !anchored || positions.contains(&path.len())
+}
+
+/// Decide whether `user_agent` may fetch `path` (including any query
+/// string).  The longest matching rule wins and `Allow` wins ties; with no
+/// matching rule the path is allowed.
+fn is_allowed(robots_txt: &str, user_agent: &str, path: &str) -> bool {
+    if path == "/robots.txt" {
+        return true;
+    }
+
+    let groups = parse(robots_txt);
+    let mut best: Option<(usize, bool)> = None;
+
+    for group in matching_groups(&groups, user_agent) {
+        for (allow, pattern) in &group.rules {
+            if !pattern_matches(pattern, path) {
+                continue;
+            }
+            let candidate = (pattern.len(), *allow);
+            if best.is_none_or(|b| candidate > b) {
+                best = Some(candidate);
+            }
+        }
+    }
+
+    best.is_none_or(|(_, allow)| allow)
+}
+
+/// Return the `Crawl-delay` (seconds) declared for `user_agent`, if any.
+fn crawl_delay(robots_txt: &str, user_agent: &str) -> Option<f64> {
+    let groups = parse(robots_txt);
+    matching_groups(&groups, user_agent)
+        .into_iter()
+        .find_map(|g| g.crawl_delay)
+}
+
+// Each rule costs up to its length times the path's, so a large file is
+// kept off the normal schedulers.
+#[rustler::nif(name = "robots_allowed?", schedule = "DirtyCpu")]
+fn robots_allowed(robots_txt: &str, user_agent: &str, path: &str) -> Guarded<bool> {
+    guard(|| is_allowed(robots_txt, user_agent, path))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn robots_crawl_delay(robots_txt: &str, user_agent: &str) -> Guarded<Option<f64>> {
+    guard(|| crawl_delay(robots_txt, user_agent))
+}
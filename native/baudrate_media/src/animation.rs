@@ -0,0 +1,168 @@
+//! Animation inspection and frame-count limits.
+//!
+//! Frame and loop counts are read from the container structure without
+//! decoding any frames, so decompression bombs made of thousands of frames
+//! can be rejected before the decoder touches them.
+
+use image::ImageFormat;
+use rustler::{Atom, Binary, Decoder, NifMap, NifResult, Term};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::probe::{color_table_len, gif_frames, png_frames, skip_sub_blocks, webp_frames};
+use crate::{atoms, dimensions};
+
+#[derive(NifMap)]
+pub(crate) struct Animation {
+    pub(crate) frames: u32,
+    /// Number of times the animation plays; 0 means forever.
+    pub(crate) loop_count: u32,
+    /// Estimated memory needed to decode every frame onto the canvas as
+    /// 8-bit RGBA.
+    pub(crate) decoded_bytes: u64,
+}
+
+/// Optional limits, decoded from a map with `:max_frames` and
+/// `:max_decoded_bytes` keys; a missing or `nil` key means no limit.
+pub(crate) struct AnimationLimits {
+    max_frames: Option<u32>,
+    max_decoded_bytes: Option<u64>,
+}
+
+impl<'a> Decoder<'a> for AnimationLimits {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(Self {
+            max_frames: term
+                .map_get(atoms::max_frames())
+                .map_or(Ok(None), |t| t.decode())?,
+            max_decoded_bytes: term
+                .map_get(atoms::max_decoded_bytes())
+                .map_or(Ok(None), |t| t.decode())?,
+        })
+    }
+}
+
+impl AnimationLimits {
+    /// Reject `data` with `:too_many_frames` or `:image_too_large` if it
+    /// exceeds the limits.
+    pub(crate) fn check(&self, data: &[u8]) -> Result<(), Atom> {
+        if self.max_frames.is_none() && self.max_decoded_bytes.is_none() {
+            return Ok(());
+        }
+
+        let animation = animation(data)?;
+        if self.max_frames.is_some_and(|max| animation.frames > max) {
+            return Err(atoms::too_many_frames());
+        }
+        if self
+            .max_decoded_bytes
+            .is_some_and(|max| animation.decoded_bytes > max)
+        {
+            return Err(atoms::image_too_large());
+        }
+        Ok(())
+    }
+}
+
+/// Read the frame count, loop count, and decoded size estimate of a GIF,
+/// APNG, or animated WebP.  Still images report one frame played once.
+///
+/// Returns `{:ok, map}` or `{:error, reason}`.
+#[rustler::nif]
+fn animation_info(bytes: Binary) -> Guarded<Result<Animation, Atom>> {
+    guard(|| animation(bytes.as_slice()))
+}
+
+pub(crate) fn animation(data: &[u8]) -> Result<Animation, Atom> {
+    let (width, height) = dimensions(data)?;
+
+    let (frames, loop_count) = match image::guess_format(data) {
+        Ok(ImageFormat::Gif) => gif_frames(data).zip(Some(gif_loop_count(data))),
+        Ok(ImageFormat::Png) => png_frames(data).zip(Some(png_loop_count(data))),
+        Ok(ImageFormat::WebP) => webp_frames(data).zip(Some(webp_loop_count(data))),
+        _ => Some((1, 1)),
+    }
+    .ok_or_else(atoms::decode_failed)?;
+
+    let frame_bytes = u64::from(width) * u64::from(height) * 4;
+
+    Ok(Animation {
+        frames,
+        loop_count,
+        decoded_bytes: frame_bytes.saturating_mul(u64::from(frames)),
+    })
+}
+
+/// Plays from the NETSCAPE2.0 application extension, which stores the
+/// number of repeats after the first play; GIFs without one play once.
+fn gif_loop_count(data: &[u8]) -> u32 {
+    match gif_repeats(data) {
+        Some(0) => 0,
+        Some(repeats) => u32::from(repeats) + 1,
+        None => 1,
+    }
+}
+
+fn gif_repeats(data: &[u8]) -> Option<u16> {
+    let mut pos = 13 + color_table_len(*data.get(10)?);
+
+    loop {
+        pos = match *data.get(pos)? {
+            0x21 => {
+                if let Some(block) = data.get(pos + 2..pos + 18) {
+                    if block.starts_with(b"\x0bNETSCAPE2.0\x03\x01") {
+                        return Some(u16::from_le_bytes([block[14], block[15]]));
+                    }
+                }
+                skip_sub_blocks(data, pos + 2)?
+            }
+            0x2C => skip_sub_blocks(data, pos + 10 + color_table_len(*data.get(pos + 9)?) + 1)?,
+            _ => return None,
+        };
+    }
+}
+
+/// `num_plays` from the APNG `acTL` chunk.
+fn png_loop_count(data: &[u8]) -> u32 {
+    png_plays(data).unwrap_or(1)
+}
+
+fn png_plays(data: &[u8]) -> Option<u32> {
+    let mut pos = 8;
+
+    loop {
+        let len = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        match data.get(pos + 4..pos + 8)? {
+            b"acTL" => {
+                return Some(u32::from_be_bytes(
+                    data.get(pos + 12..pos + 16)?.try_into().ok()?,
+                ));
+            }
+            // acTL must precede the image data.
+            b"IDAT" | b"IEND" => return None,
+            _ => {}
+        }
+        pos = pos.checked_add(12)?.checked_add(len)?;
+    }
+}
+
+/// Loop count from the WebP `ANIM` chunk.
+fn webp_loop_count(data: &[u8]) -> u32 {
+    webp_loops(data).unwrap_or(1)
+}
+
+fn webp_loops(data: &[u8]) -> Option<u32> {
+    let mut pos = 12;
+
+    loop {
+        let size = u32::from_le_bytes(data.get(pos + 4..pos + 8)?.try_into().ok()?) as usize;
+        if data.get(pos..pos + 4)? == b"ANIM" {
+            // Background color, then the 16-bit loop count.
+            let loops = data.get(pos + 12..pos + 14)?;
+            return Some(u32::from(u16::from_le_bytes([loops[0], loops[1]])));
+        }
+        pos = pos
+            .checked_add(8)?
+            .checked_add(size)?
+            .checked_add(size % 2)?;
+    }
+}
@@ -0,0 +1,130 @@
+//! Audio metadata extraction for audio attachments.
+//!
+//! Tags are read with lofty, which covers ID3v1/v2 (MP3, AAC, WAV, AIFF),
+//! Vorbis comments (FLAC, Ogg Vorbis, Opus), and MP4 `ilst` atoms (M4A).
+//! Only tags and stream headers are parsed; no audio is decoded.
+
+use std::io::Cursor;
+
+use lofty::config::ParseOptions;
+use lofty::file::{AudioFile, FileType, TaggedFile, TaggedFileExt};
+use lofty::picture::{Picture, PictureType};
+use lofty::probe::Probe;
+use lofty::tag::{Accessor, Tag};
+use rustler::{Atom, Binary, Env, NifMap, NifUnitEnum};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, to_binary};
+
+/// Audio containers that can be probed, passed to Elixir as atoms.
+#[derive(NifUnitEnum, Clone, Copy)]
+enum AudioFormat {
+    Aac,
+    Aiff,
+    Flac,
+    Mp3,
+    Mp4,
+    Opus,
+    Vorbis,
+    Wav,
+}
+
+impl AudioFormat {
+    fn from_file_type(file_type: FileType) -> Option<Self> {
+        match file_type {
+            FileType::Aac => Some(Self::Aac),
+            FileType::Aiff => Some(Self::Aiff),
+            FileType::Flac => Some(Self::Flac),
+            FileType::Mpeg => Some(Self::Mp3),
+            FileType::Mp4 => Some(Self::Mp4),
+            FileType::Opus => Some(Self::Opus),
+            FileType::Vorbis => Some(Self::Vorbis),
+            FileType::Wav => Some(Self::Wav),
+            _ => None,
+        }
+    }
+}
+
+/// Embedded cover art, returned as `%{data: bytes, mime_type: type}`.
+#[derive(NifMap)]
+struct Cover<'a> {
+    data: Binary<'a>,
+    /// MIME type declared by the tag, if any.
+    mime_type: Option<String>,
+}
+
+#[derive(NifMap)]
+struct AudioProbe<'a> {
+    format: AudioFormat,
+    duration_ms: u64,
+    /// Overall bitrate in kbit/s.
+    bitrate: Option<u32>,
+    sample_rate: Option<u32>,
+    channels: Option<u8>,
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    cover: Option<Cover<'a>>,
+}
+
+/// Read the stream properties, title, artist, album, and cover art of an
+/// audio file.
+///
+/// Tag fields fall back to secondary tags (e.g. ID3v1 behind ID3v2) when
+/// missing from the primary one.  Returns `{:ok, map}` or
+/// `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn probe_audio<'a>(env: Env<'a>, bytes: Binary) -> Guarded<Result<AudioProbe<'a>, Atom>> {
+    guard(|| {
+        let probe = Probe::new(Cursor::new(bytes.as_slice()))
+            .options(ParseOptions::new())
+            .guess_file_type()
+            .map_err(|_| atoms::decode_failed())?;
+
+        let format = probe
+            .file_type()
+            .and_then(AudioFormat::from_file_type)
+            .ok_or_else(atoms::unsupported_format)?;
+        let file = probe.read().map_err(|_| atoms::decode_failed())?;
+        let properties = file.properties();
+
+        Ok(AudioProbe {
+            format,
+            duration_ms: u64::try_from(properties.duration().as_millis()).unwrap_or(u64::MAX),
+            bitrate: properties.overall_bitrate(),
+            sample_rate: properties.sample_rate(),
+            channels: properties.channels(),
+            title: text(&file, |tag| tag.title().map(|s| s.into_owned())),
+            artist: text(&file, |tag| tag.artist().map(|s| s.into_owned())),
+            album: text(&file, |tag| tag.album().map(|s| s.into_owned())),
+            cover: cover(&file).map(|picture| Cover {
+                data: to_binary(env, picture.data()),
+                mime_type: picture.mime_type().map(|m| m.as_str().to_owned()),
+            }),
+        })
+    })
+}
+
+/// The first non-blank value of a text field, primary tag first.
+fn text(file: &TaggedFile, field: impl Fn(&Tag) -> Option<String>) -> Option<String> {
+    tags(file)
+        .filter_map(field)
+        .map(|value| value.trim().to_owned())
+        .find(|value| !value.is_empty())
+}
+
+/// The front cover, or else the first embedded picture of any type.
+fn cover(file: &TaggedFile) -> Option<&Picture> {
+    let pictures = || tags(file).flat_map(|tag| tag.pictures());
+    pictures()
+        .find(|p| p.pic_type() == PictureType::CoverFront)
+        .or_else(|| pictures().next())
+}
+
+fn tags(file: &TaggedFile) -> impl Iterator<Item = &Tag> {
+    file.primary_tag()
+        .into_iter()
+        .chain(file.tags().iter().filter(move |tag| {
+            Some(tag.tag_type()) != file.primary_tag().map(|primary| primary.tag_type())
+        }))
+}
@@ -0,0 +1,51 @@
+//! Pre-blurred previews for sensitive media.
+
+use image::imageops::FilterType;
+use rustler::{Atom, Binary, Env};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, encode, load, to_binary, Encoded, OutputFormat};
+
+/// Largest accepted blur radius (Gaussian standard deviation), in pixels of
+/// the downscaled image.
+const MAX_SIGMA: f64 = 100.0;
+
+/// Largest accepted output size, in pixels per side.
+const MAX_BLUR_DIMENSION: u32 = 2048;
+
+/// Downscale an image to fit within `max_dim` × `max_dim`, blur it with
+/// standard deviation `sigma`, and encode it as a JPEG.
+///
+/// Blurring happens after downscaling, so `sigma` is relative to the output
+/// size.  The blur approximates a Gaussian with repeated box blurs, whose
+/// cost does not grow with `sigma`.  Returns
+/// `{:ok, %{data: bytes, width: w, height: h}}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn blur_image<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    sigma: f64,
+    max_dim: u32,
+) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        if !(sigma > 0.0 && sigma <= MAX_SIGMA) {
+            return Err(atoms::invalid_sigma());
+        }
+        if !(1..=MAX_BLUR_DIMENSION).contains(&max_dim) {
+            return Err(atoms::invalid_dimensions());
+        }
+
+        let mut image = load(bytes.as_slice())?;
+
+        if image.width() > max_dim || image.height() > max_dim {
+            image = image.resize(max_dim, max_dim, FilterType::Triangle);
+        }
+        let image = image.fast_blur(sigma as f32);
+
+        Ok(Encoded {
+            data: to_binary(env, &encode(&image, OutputFormat::Jpeg)?),
+            width: image.width(),
+            height: image.height(),
+        })
+    })
+}
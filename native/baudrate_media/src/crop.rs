@@ -0,0 +1,121 @@
+//! Focal-point aware cropping for preview sizes, and cropping to a region
+//! chosen by the user.
+//!
+//! Focal points use the Mastodon convention: `x` and `y` range from -1.0 to
+//! 1.0 with `(0, 0)` at the center, `x` increasing to the right and `y`
+//! increasing upwards.
+
+use image::imageops::FilterType;
+use rustler::{Atom, Binary, Env};
+
+use crate::animation::AnimationLimits;
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, encode, load, to_binary, Encoded, OutputFormat};
+
+/// Crop an image to the aspect ratio of `target_width` × `target_height`,
+/// keeping the focal point as close to the center as the image bounds
+/// allow, then downscale it to the target size and encode it as `format`.
+///
+/// The crop is never upscaled, so an image smaller than the target produces
+/// a smaller output with the target aspect ratio.  Animations over the
+/// frame or decoded size `limits` are rejected before decoding.  Returns
+/// `{:ok, %{data: bytes, width: w, height: h}}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+#[allow(clippy::too_many_arguments)]
+fn crop_focal<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    focal_x: f64,
+    focal_y: f64,
+    target_width: u32,
+    target_height: u32,
+    format: OutputFormat,
+    limits: AnimationLimits,
+) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        if target_width == 0 || target_height == 0 {
+            return Err(atoms::invalid_dimensions());
+        }
+        if !(-1.0..=1.0).contains(&focal_x) || !(-1.0..=1.0).contains(&focal_y) {
+            return Err(atoms::invalid_focal_point());
+        }
+
+        limits.check(bytes.as_slice())?;
+        let image = load(bytes.as_slice())?;
+        let (width, height) = (image.width(), image.height());
+
+        // The largest region with the target aspect ratio that fits the image.
+        let aspect = f64::from(target_width) / f64::from(target_height);
+        let (crop_width, crop_height) = if f64::from(width) / f64::from(height) > aspect {
+            ((f64::from(height) * aspect).round() as u32, height)
+        } else {
+            (width, (f64::from(width) / aspect).round() as u32)
+        };
+        let (crop_width, crop_height) = (crop_width.clamp(1, width), crop_height.clamp(1, height));
+
+        let left = offset((focal_x + 1.0) / 2.0, width, crop_width);
+        let top = offset((1.0 - focal_y) / 2.0, height, crop_height);
+        let mut image = image.crop_imm(left, top, crop_width, crop_height);
+
+        if crop_width > target_width || crop_height > target_height {
+            image = image.resize_exact(target_width, target_height, FilterType::Lanczos3);
+        }
+
+        Ok(Encoded {
+            data: to_binary(env, &encode(&image, format)?),
+            width: image.width(),
+            height: image.height(),
+        })
+    })
+}
+
+/// Cut out the region at `x`, `y` measuring `width` × `height`, each a
+/// fraction (0.0–1.0) of the upright image, and encode it as `format`.
+///
+/// The region is clamped to the image and is at least one pixel on each
+/// side.  Animations over the frame or decoded size `limits` are rejected
+/// before decoding.  Returns `{:ok, %{data: bytes, width: w, height: h}}` or
+/// `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+#[allow(clippy::too_many_arguments)]
+fn crop_image<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    format: OutputFormat,
+    limits: AnimationLimits,
+) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        let in_range = [x, y, width, height].iter().all(|v| (0.0..=1.0).contains(v));
+        if !in_range || width == 0.0 || height == 0.0 {
+            return Err(atoms::invalid_region());
+        }
+
+        limits.check(bytes.as_slice())?;
+        let image = load(bytes.as_slice())?;
+        let (image_width, image_height) = (image.width(), image.height());
+        let scale = |fraction: f64, length: u32| (fraction * f64::from(length)).round() as u32;
+
+        let left = scale(x, image_width).min(image_width - 1);
+        let top = scale(y, image_height).min(image_height - 1);
+        let crop_width = scale(width, image_width).clamp(1, image_width - left);
+        let crop_height = scale(height, image_height).clamp(1, image_height - top);
+        let image = image.crop_imm(left, top, crop_width, crop_height);
+
+        Ok(Encoded {
+            data: to_binary(env, &encode(&image, format)?),
+            width: image.width(),
+            height: image.height(),
+        })
+    })
+}
+
+/// Start of a `crop`-long span centered on `position` (0.0–1.0 along a
+/// `length`-long axis), shifted to stay within the axis.
+fn offset(position: f64, length: u32, crop: u32) -> u32 {
+    let start = position * f64::from(length) - f64::from(crop) / 2.0;
+    (start.round().max(0.0) as u32).min(length - crop)
+}
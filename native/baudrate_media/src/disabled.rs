@@ -0,0 +1,23 @@
+//! Stand-ins for the NIFs of disabled Cargo features.
+//!
+//! Every build exports the same NIFs, so the Elixir stubs always match the
+//! loaded library.  Without its feature a NIF returns
+//! `{:error, :feature_disabled}`, and `native_info/0` leaves it out of the
+//! available NIFs.
+
+use rustler::{Atom, Binary};
+
+use crate::atoms;
+use baudrate_nif::guard::{guard, Guarded};
+
+#[cfg(not(feature = "audio"))]
+#[rustler::nif(schedule = "DirtyCpu")]
+fn probe_audio(_bytes: Binary) -> Guarded<Result<(), Atom>> {
+    guard(|| Err(atoms::feature_disabled()))
+}
+
+#[cfg(not(feature = "svg"))]
+#[rustler::nif(schedule = "DirtyCpu")]
+fn rasterize_svg(_bytes: Binary, _width: u32, _height: u32) -> Guarded<Result<(), Atom>> {
+    guard(|| Err(atoms::feature_disabled()))
+}
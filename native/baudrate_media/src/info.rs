@@ -0,0 +1,31 @@
+//! Build information, for detecting what a loaded library supports.
+
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::info::{self, Feature, NativeInfo};
+use rustler::Env;
+
+const FEATURES: &[Feature] = &[
+    Feature {
+        name: "audio",
+        enabled: cfg!(feature = "audio"),
+        nifs: &[("probe_audio", 1)],
+    },
+    Feature {
+        name: "avif",
+        enabled: cfg!(feature = "avif"),
+        nifs: &[],
+    },
+    Feature {
+        name: "svg",
+        enabled: cfg!(feature = "svg"),
+        nifs: &[("rasterize_svg", 3)],
+    },
+];
+
+/// Return the crate version, its enabled Cargo features, and every working
+/// NIF in the loaded library as `{name, arity}`, so that callers can degrade
+/// gracefully when an older or reduced build is deployed.
+#[rustler::nif]
+fn native_info(env: Env) -> Guarded<NativeInfo> {
+    guard(|| info::native_info(env, env!("CARGO_PKG_VERSION"), FEATURES))
+}
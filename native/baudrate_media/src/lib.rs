@@ -0,0 +1,235 @@
+//! Media processing NIFs for image and audio attachments.
+//!
+//! All functions take encoded image bytes as produced by uploads or remote
+//! fetches.  Decoding goes through [`load`], which enforces dimension and
+//! allocation limits so untrusted input cannot exhaust memory, and applies
+//! the EXIF orientation so every NIF sees the image upright.
+
+mod animation;
+#[cfg(feature = "audio")]
+mod audio;
+mod blur;
+mod crop;
+#[cfg(not(all(feature = "audio", feature = "svg")))]
+mod disabled;
+mod info;
+mod metadata;
+mod orient;
+mod palette;
+mod phash;
+mod placeholder;
+mod pool;
+mod probe;
+mod qr;
+mod resize;
+mod sniff;
+mod still;
+#[cfg(feature = "svg")]
+mod svg;
+mod transcode;
+
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::metadata::Orientation;
+use image::{
+    DynamicImage, ImageDecoder, ImageEncoder, ImageError, ImageFormat, ImageReader, Limits,
+};
+use rustler::{Atom, Binary, Env, NewBinary, NifMap, NifUnitEnum, Term};
+
+mod atoms {
+    rustler::atoms! {
+        background,
+        color,
+        data_too_long,
+        decode_failed,
+        ec_level,
+        encode_failed,
+        feature_disabled,
+        image_too_large,
+        input_too_large,
+        invalid_blurhash,
+        invalid_color_count,
+        invalid_components,
+        invalid_dimensions,
+        invalid_focal_point,
+        invalid_options,
+        invalid_quality,
+        invalid_region,
+        invalid_sigma,
+        invalid_svg,
+        invalid_thumbhash,
+        margin,
+        max_decoded_bytes,
+        max_frames,
+        size,
+        svg_too_complex,
+        too_many_frames,
+        unsupported_format,
+    }
+}
+
+/// Largest accepted width or height, in pixels.
+const MAX_DIMENSION: u32 = 16_384;
+
+/// Upper bound on decoder allocations (256 MiB).
+const MAX_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Quality used for JPEG output.
+const JPEG_QUALITY: u8 = 85;
+
+/// Output encodings, passed from Elixir as `:jpeg`, `:png`, or `:webp`.
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum OutputFormat {
+    Jpeg,
+    Png,
+    /// Lossless WebP.
+    Webp,
+}
+
+impl OutputFormat {
+    /// The output format matching a decoded input, if it can be re-encoded.
+    pub(crate) fn from_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Jpeg => Some(Self::Jpeg),
+            ImageFormat::Png => Some(Self::Png),
+            ImageFormat::WebP => Some(Self::Webp),
+            _ => None,
+        }
+    }
+}
+
+/// Encoded image plus its final dimensions, returned to Elixir as
+/// `%{data: bytes, width: w, height: h}`.
+#[derive(NifMap)]
+pub(crate) struct Encoded<'a> {
+    pub(crate) data: Binary<'a>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+/// A decoded, upright image plus its embedded ICC profile.
+pub(crate) struct Decoded {
+    pub(crate) image: DynamicImage,
+    pub(crate) icc_profile: Option<Vec<u8>>,
+}
+
+/// Decode image bytes, guessing the format from its magic bytes, and rotate
+/// or flip the pixels according to the EXIF orientation.
+///
+/// Errors map to `:unsupported_format`, `:image_too_large`, or
+/// `:decode_failed`.
+pub(crate) fn load(bytes: &[u8]) -> Result<DynamicImage, Atom> {
+    decode(bytes).map(|decoded| decoded.image)
+}
+
+/// Like [`load`], also returning the ICC profile.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Decoded, Atom> {
+    let mut decoder = decoder(bytes)?;
+    // Decoder limits cover internal buffers; the output buffer is checked
+    // here before it is allocated.
+    if decoder.total_bytes() > MAX_ALLOC {
+        return Err(atoms::image_too_large());
+    }
+    let icc_profile = decoder.icc_profile().unwrap_or(None);
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+
+    Ok(Decoded { image, icc_profile })
+}
+
+/// Read the EXIF orientation from the image headers without decoding pixels.
+pub(crate) fn orientation(bytes: &[u8]) -> Result<Orientation, Atom> {
+    let mut decoder = decoder(bytes)?;
+    Ok(decoder.orientation().unwrap_or(Orientation::NoTransforms))
+}
+
+/// Read the stored (pre-orientation) dimensions from the image headers
+/// without decoding pixels.
+pub(crate) fn dimensions(bytes: &[u8]) -> Result<(u32, u32), Atom> {
+    Ok(decoder(bytes)?.dimensions())
+}
+
+/// Open a limit-enforcing decoder, reading only the image headers.
+pub(crate) fn decoder(bytes: &[u8]) -> Result<impl ImageDecoder + '_, Atom> {
+    let mut reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|_| atoms::decode_failed())?;
+
+    if reader.format().is_none() {
+        return Err(atoms::unsupported_format());
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_ALLOC);
+    reader.limits(limits);
+
+    reader.into_decoder().map_err(decode_error)
+}
+
+fn decode_error(e: ImageError) -> Atom {
+    match e {
+        ImageError::Limits(_) => atoms::image_too_large(),
+        ImageError::Unsupported(_) => atoms::unsupported_format(),
+        _ => atoms::decode_failed(),
+    }
+}
+
+/// Encode an image in the given format.  Alpha is dropped for JPEG.
+pub(crate) fn encode(image: &DynamicImage, format: OutputFormat) -> Result<Vec<u8>, Atom> {
+    encode_with_icc(image, format, None)
+}
+
+/// Like [`encode`], embedding `icc_profile` when given.
+pub(crate) fn encode_with_icc(
+    image: &DynamicImage,
+    format: OutputFormat,
+    icc_profile: Option<Vec<u8>>,
+) -> Result<Vec<u8>, Atom> {
+    let mut out = Vec::new();
+
+    let result = match format {
+        OutputFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY);
+            DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(with_icc(encoder, icc_profile))
+        }
+        OutputFormat::Png => {
+            image.write_with_encoder(with_icc(PngEncoder::new(&mut out), icc_profile))
+        }
+        OutputFormat::Webp => {
+            let encoder = WebPEncoder::new_lossless(&mut out);
+            DynamicImage::ImageRgba8(image.to_rgba8())
+                .write_with_encoder(with_icc(encoder, icc_profile))
+        }
+    };
+
+    result.map_err(|_| atoms::encode_failed())?;
+    Ok(out)
+}
+
+fn with_icc<E: ImageEncoder>(mut encoder: E, icc_profile: Option<Vec<u8>>) -> E {
+    if let Some(icc_profile) = icc_profile {
+        // All of the encoders above support ICC profiles.
+        let _ = encoder.set_icc_profile(icc_profile);
+    }
+    encoder
+}
+
+/// Copy `bytes` into a new Erlang binary.
+pub(crate) fn to_binary<'a>(env: Env<'a>, bytes: &[u8]) -> Binary<'a> {
+    let mut binary = NewBinary::new(env, bytes.len());
+    binary.as_mut_slice().copy_from_slice(bytes);
+    binary.into()
+}
+
+fn on_load(_env: Env, _info: Term) -> bool {
+    pool::load()
+}
+
+rustler::init!("Elixir.Baudrate.Media.Native", load = on_load);
@@ -0,0 +1,220 @@
+//! Removal of privacy-sensitive metadata (EXIF, XMP, IPTC) from uploads.
+//!
+//! Metadata is removed at the container level, so the compressed image data
+//! is copied unchanged and no quality is lost.  Only when the EXIF
+//! orientation is not the identity is the image decoded, rotated, and
+//! re-encoded, since dropping the EXIF block would otherwise leave it
+//! displayed sideways.
+
+use image::metadata::Orientation;
+use rustler::{Atom, Binary, Env};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::orient::{output_format, reencode_upright};
+use crate::{atoms, orientation, to_binary, OutputFormat};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// VP8X feature flags for the chunks that may be removed.
+const VP8X_ICC: u8 = 0x20;
+const VP8X_EXIF: u8 = 0x08;
+const VP8X_XMP: u8 = 0x04;
+
+/// Remove EXIF, XMP, IPTC, and comment metadata from a JPEG, PNG, or WebP
+/// image, returning it in the same format.
+///
+/// The ICC color profile is kept when `keep_icc` is true.  Returns
+/// `{:ok, bytes}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn strip_metadata<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    keep_icc: bool,
+) -> Guarded<Result<Binary<'a>, Atom>> {
+    guard(|| {
+        let data = bytes.as_slice();
+        let format = output_format(data)?;
+
+        if orientation(data)? != Orientation::NoTransforms {
+            return Ok(to_binary(env, &reencode_upright(data, format, keep_icc)?));
+        }
+
+        let stripped = match format {
+            OutputFormat::Jpeg => strip_jpeg(data, keep_icc),
+            OutputFormat::Png => strip_png(data, keep_icc),
+            OutputFormat::Webp => strip_webp(data, keep_icc),
+        }
+        .ok_or_else(atoms::decode_failed)?;
+
+        Ok(to_binary(env, &stripped))
+    })
+}
+
+/// Copy JPEG segments, dropping APP1 (EXIF / XMP), APP13 (IPTC), comments,
+/// vendor APPn segments, and — unless kept — APP2 ICC profiles.  Anything
+/// after the end-of-image marker is discarded.
+fn strip_jpeg(data: &[u8], keep_icc: bool) -> Option<Vec<u8>> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&[0xFF, 0xD8]);
+    let mut pos = 2;
+
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        // Skip the marker prefix along with any fill bytes.
+        while data.get(pos) == Some(&0xFF) {
+            pos += 1;
+        }
+        let marker = *data.get(pos)?;
+        pos += 1;
+
+        match marker {
+            0xD9 => {
+                out.extend_from_slice(&[0xFF, 0xD9]);
+                return Some(out);
+            }
+            0x01 | 0xD0..=0xD7 => {
+                out.extend_from_slice(&[0xFF, marker]);
+                continue;
+            }
+            _ => {}
+        }
+
+        let len = u16::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?]) as usize;
+        let segment = data.get(pos..pos.checked_add(len)?).filter(|_| len >= 2)?;
+        let payload = &segment[2..];
+
+        let keep = match marker {
+            // APP1 (EXIF, XMP), APP13 (IPTC / Photoshop), COM.
+            0xE1 | 0xED | 0xFE => false,
+            0xE2 => keep_icc && payload.starts_with(b"ICC_PROFILE\0"),
+            // Remaining APPn except APP0 (JFIF) and APP14 (Adobe color
+            // transform, needed to decode correctly).
+            0xE3..=0xEC | 0xEF => false,
+            _ => true,
+        };
+
+        if keep {
+            out.extend_from_slice(&[0xFF, marker]);
+            out.extend_from_slice(segment);
+        }
+        pos += len;
+
+        if marker == 0xDA {
+            // Entropy-coded data runs until the next marker other than a
+            // stuffed 0xFF00 or a restart marker.
+            let start = pos;
+            while pos + 1 < data.len() {
+                let next = data[pos + 1];
+                if data[pos] == 0xFF
+                    && next != 0x00
+                    && next != 0xFF
+                    && !(0xD0..=0xD7).contains(&next)
+                {
+                    break;
+                }
+                pos += 1;
+            }
+
+            if pos + 1 >= data.len() {
+                // Truncated file: keep the scan data and terminate it.
+                out.extend_from_slice(&data[start..]);
+                out.extend_from_slice(&[0xFF, 0xD9]);
+                return Some(out);
+            }
+            out.extend_from_slice(&data[start..pos]);
+        }
+    }
+}
+
+/// Copy PNG chunks up to `IEND`, dropping `eXIf`, text chunks (which
+/// carry XMP), `tIME`, and — unless kept — `iCCP`.
+fn strip_png(data: &[u8], keep_icc: bool) -> Option<Vec<u8>> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut pos = PNG_SIGNATURE.len();
+
+    loop {
+        let len = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let end = pos.checked_add(12)?.checked_add(len)?;
+        let chunk = data.get(pos..end)?;
+        let kind = &chunk[4..8];
+
+        let keep = match kind {
+            b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME" => false,
+            b"iCCP" => keep_icc,
+            _ => true,
+        };
+
+        if keep {
+            out.extend_from_slice(chunk);
+        }
+        if kind == b"IEND" {
+            return Some(out);
+        }
+        pos = end;
+    }
+}
+
+/// Rebuild a WebP RIFF container without `EXIF` and `XMP ` chunks (and
+/// `ICCP` unless kept), clearing the matching VP8X feature flags.
+fn strip_webp(data: &[u8], keep_icc: bool) -> Option<Vec<u8>> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return None;
+    }
+
+    let riff_len = u32::from_le_bytes(data[4..8].try_into().ok()?) as usize;
+    let riff_end = riff_len.checked_add(8)?.min(data.len());
+
+    let mut body = Vec::with_capacity(data.len());
+    let mut vp8x = None;
+    let mut pos = 12;
+
+    while pos + 8 <= riff_end {
+        let fourcc = &data[pos..pos + 4];
+        let size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize;
+        let end = pos.checked_add(8)?.checked_add(size)?;
+        let chunk = data.get(pos..end)?;
+
+        let keep = match fourcc {
+            b"EXIF" | b"XMP " => false,
+            b"ICCP" => keep_icc,
+            _ => true,
+        };
+
+        if keep {
+            if fourcc == b"VP8X" {
+                vp8x = Some(body.len());
+            }
+            body.extend_from_slice(chunk);
+            if size % 2 == 1 {
+                body.push(0);
+            }
+        }
+        pos = end + size % 2;
+    }
+
+    if let Some(offset) = vp8x {
+        let flags = body.get_mut(offset + 8)?;
+        *flags &= !(VP8X_EXIF | VP8X_XMP);
+        if !keep_icc {
+            *flags &= !VP8X_ICC;
+        }
+    }
+
+    let mut out = Vec::with_capacity(body.len() + 12);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&u32::try_from(body.len() + 4).ok()?.to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    out.extend_from_slice(&body);
+    Some(out)
+}
@@ -0,0 +1,48 @@
+//! EXIF orientation handling.
+
+use image::metadata::Orientation;
+use rustler::{Atom, Binary, Env};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, decode, encode_with_icc, orientation, to_binary, OutputFormat};
+
+/// Rotate or flip an image so that it displays upright without its EXIF
+/// orientation tag.
+///
+/// Images whose orientation is already the identity are returned as-is;
+/// others are re-encoded in their original format without EXIF, keeping
+/// the ICC profile.  Returns `{:ok, bytes}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn auto_orient<'a>(env: Env<'a>, bytes: Binary<'a>) -> Guarded<Result<Binary<'a>, Atom>> {
+    guard(|| {
+        let data = bytes.as_slice();
+        let format = output_format(data)?;
+
+        if orientation(data)? == Orientation::NoTransforms {
+            return Ok(bytes);
+        }
+
+        Ok(to_binary(env, &reencode_upright(data, format, true)?))
+    })
+}
+
+/// The output format matching the input, for formats that can be
+/// re-encoded in place.
+pub(crate) fn output_format(data: &[u8]) -> Result<OutputFormat, Atom> {
+    image::guess_format(data)
+        .ok()
+        .and_then(OutputFormat::from_format)
+        .ok_or_else(atoms::unsupported_format)
+}
+
+/// Decode, apply the EXIF orientation, and re-encode as `format`.  The
+/// output carries no EXIF block, and the ICC profile only if `keep_icc`.
+pub(crate) fn reencode_upright(
+    data: &[u8],
+    format: OutputFormat,
+    keep_icc: bool,
+) -> Result<Vec<u8>, Atom> {
+    let decoded = decode(data)?;
+    let icc_profile = decoded.icc_profile.filter(|_| keep_icc);
+    encode_with_icc(&decoded.image, format, icc_profile)
+}
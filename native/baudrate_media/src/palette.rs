@@ -0,0 +1,129 @@
+//! Dominant color extraction for placeholder backgrounds and card themes.
+//!
+//! Colors are found by median-cut quantization of a downsampled copy of the
+//! image: the set of pixels is repeatedly split at the median of its widest
+//! channel, and each resulting box contributes its average color, ranked by
+//! how many pixels it holds.
+
+use rustler::{Atom, Binary};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, load};
+
+/// Images are downsampled to at most this many pixels per side; dominant
+/// colors do not depend on fine detail.
+const SAMPLE_SIZE: u32 = 64;
+
+/// Largest number of colors that may be requested.
+const MAX_COLORS: usize = 16;
+
+/// Pixels with lower alpha are ignored.
+const MIN_ALPHA: u8 = 128;
+
+/// Return up to `count` dominant colors of an image as `"#rrggbb"` strings,
+/// most prominent first.
+///
+/// Fewer colors are returned when the image has fewer distinct colors, and
+/// none when it is fully transparent.  Returns `{:ok, colors}` or
+/// `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn dominant_colors(bytes: Binary, count: usize) -> Guarded<Result<Vec<String>, Atom>> {
+    guard(|| {
+        if !(1..=MAX_COLORS).contains(&count) {
+            return Err(atoms::invalid_color_count());
+        }
+
+        let mut image = load(bytes.as_slice())?;
+        // `thumbnail` also scales up, which would blend edges for no benefit.
+        if image.width() > SAMPLE_SIZE || image.height() > SAMPLE_SIZE {
+            image = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE);
+        }
+        let sample = image.into_rgba8();
+
+        let pixels: Vec<[u8; 3]> = sample
+            .pixels()
+            .filter(|p| p[3] >= MIN_ALPHA)
+            .map(|p| [p[0], p[1], p[2]])
+            .collect();
+
+        Ok(median_cut(pixels, count)
+            .into_iter()
+            .map(|[r, g, b]| format!("#{r:02x}{g:02x}{b:02x}"))
+            .collect())
+    })
+}
+
+/// Split `pixels` into at most `count` boxes and return their average
+/// colors, largest box first.
+fn median_cut(pixels: Vec<[u8; 3]>, count: usize) -> Vec<[u8; 3]> {
+    let mut boxes = if pixels.is_empty() {
+        Vec::new()
+    } else {
+        vec![pixels]
+    };
+
+    while boxes.len() < count {
+        // Split the most populous box that still spans more than one color.
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| widest_channel(b).map(|channel| (i, channel, b.len())))
+            .max_by_key(|&(_, _, len)| len)
+            .map(|(i, channel, _)| (i, channel))
+        else {
+            break;
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|p| p[channel]);
+        // Split at the median, moved forward past equal values so both
+        // halves are non-empty and identical colors stay together.
+        let split = pixels.partition_point(|p| p[channel] < pixels[pixels.len() / 2][channel]);
+        let split = if split == 0 {
+            pixels.partition_point(|p| p[channel] <= pixels[0][channel])
+        } else {
+            split
+        };
+        let upper = pixels.split_off(split);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes.sort_by_key(|b| std::cmp::Reverse(b.len()));
+
+    let mut colors: Vec<[u8; 3]> = Vec::with_capacity(boxes.len());
+    for b in &boxes {
+        let color = average(b);
+        // Boxes with different pixels can still round to the same color.
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+    colors
+}
+
+/// The channel with the widest value range, or `None` when every pixel in
+/// the box has the same color.
+fn widest_channel(pixels: &[[u8; 3]]) -> Option<usize> {
+    (0..3)
+        .map(|channel| {
+            let (min, max) = pixels.iter().fold((u8::MAX, u8::MIN), |(min, max), p| {
+                (min.min(p[channel]), max.max(p[channel]))
+            });
+            (channel, max.saturating_sub(min))
+        })
+        .filter(|&(_, range)| range > 0)
+        .max_by_key(|&(_, range)| range)
+        .map(|(channel, _)| channel)
+}
+
+fn average(pixels: &[[u8; 3]]) -> [u8; 3] {
+    let len = pixels.len() as u64;
+    let mut sum = [0u64; 3];
+    for p in pixels {
+        for (s, &value) in sum.iter_mut().zip(p) {
+            *s += u64::from(value);
+        }
+    }
+    sum.map(|s| ((s + len / 2) / len) as u8)
+}
@@ -0,0 +1,98 @@
+//! Perceptual hashing for near-duplicate detection.
+//!
+//! The hash follows pHash: the image is reduced to a 32 × 32 grayscale
+//! thumbnail, transformed with a 2-D DCT, and the 8 × 8 lowest-frequency
+//! coefficients after the DC term are compared to their median.  Resizing,
+//! recompression, and small color or brightness changes flip few bits, so
+//! visually similar images have a small Hamming distance.
+
+use std::f64::consts::PI;
+
+use image::imageops::FilterType;
+use rayon::prelude::*;
+use rustler::{Atom, Binary};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{load, pool};
+
+/// Side of the grayscale thumbnail the DCT is computed over.
+const SAMPLE_SIZE: usize = 32;
+
+/// Side of the block of low-frequency coefficients that make up the hash.
+const HASH_SIZE: usize = 8;
+
+/// Compute the 64-bit perceptual hash of an image.
+///
+/// Returns `{:ok, hash}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn phash(bytes: Binary) -> Guarded<Result<u64, Atom>> {
+    guard(|| hash(bytes.as_slice()))
+}
+
+/// Hash many images at once, in parallel on the pool.
+///
+/// Returns one `{:ok, hash}` or `{:error, reason}` per image, in order.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn phash_batch(images: Vec<Binary>) -> Guarded<Vec<Result<u64, Atom>>> {
+    guard(|| {
+        let images: Vec<&[u8]> = images.iter().map(|bytes| bytes.as_slice()).collect();
+        pool::get().install(|| images.par_iter().map(|bytes| hash(bytes)).collect())
+    })
+}
+
+fn hash(bytes: &[u8]) -> Result<u64, Atom> {
+    let sample = load(bytes)?
+        .resize_exact(SAMPLE_SIZE as u32, SAMPLE_SIZE as u32, FilterType::Triangle)
+        .into_luma8();
+    let pixels: Vec<f64> = sample.as_raw().iter().map(|&p| f64::from(p)).collect();
+
+    // Rows and columns 1..=8, skipping the DC term, which only reflects the
+    // average brightness.
+    let coefficients = dct_low_frequencies(&pixels);
+    let mut sorted = coefficients.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0;
+
+    Ok(coefficients
+        .iter()
+        .fold(0, |hash, &c| (hash << 1) | u64::from(c > median)))
+}
+
+/// Number of bits that differ between two hashes; values up to about 10
+/// indicate the same image.
+#[rustler::nif]
+fn hamming_distance(a: u64, b: u64) -> Guarded<u32> {
+    guard(|| (a ^ b).count_ones())
+}
+
+/// The 2-D DCT-II coefficients for frequencies 1..=8 on both axes of a
+/// `SAMPLE_SIZE` × `SAMPLE_SIZE` grid, in row-major order.
+fn dct_low_frequencies(pixels: &[f64]) -> Vec<f64> {
+    let basis: Vec<Vec<f64>> = (1..=HASH_SIZE)
+        .map(|u| {
+            (0..SAMPLE_SIZE)
+                .map(|x| (PI * u as f64 * (2 * x + 1) as f64 / (2 * SAMPLE_SIZE) as f64).cos())
+                .collect()
+        })
+        .collect();
+
+    // Transform the rows first, keeping only the needed frequencies, then
+    // the columns of that intermediate result.
+    let rows: Vec<Vec<f64>> = pixels
+        .chunks(SAMPLE_SIZE)
+        .map(|row| {
+            basis
+                .iter()
+                .map(|b| row.iter().zip(b).map(|(p, c)| p * c).sum())
+                .collect()
+        })
+        .collect();
+
+    let mut coefficients = Vec::with_capacity(HASH_SIZE * HASH_SIZE);
+    for v in &basis {
+        for u in 0..HASH_SIZE {
+            coefficients.push(rows.iter().zip(v).map(|(row, c)| row[u] * c).sum());
+        }
+    }
+    coefficients
+}
@@ -0,0 +1,130 @@
+//! Compact image placeholders shown while the full image loads.
+
+use base64::alphabet::STANDARD;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use image::{DynamicImage, RgbaImage};
+use rustler::{Atom, Binary, Env};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, encode, load, to_binary, OutputFormat};
+
+/// Images are downsampled to at most this many pixels per side before
+/// encoding; blurhash only captures low frequencies, so more detail is
+/// wasted work.
+const BLURHASH_SAMPLE_SIZE: u32 = 64;
+
+/// Thumbhash encoding is limited to 100 × 100 pixels.
+const THUMBHASH_SAMPLE_SIZE: u32 = 100;
+
+/// Standard base64, accepting hashes with or without padding.
+const THUMBHASH_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Largest placeholder that may be rendered, in pixels per side.  Large
+/// enough for a 1200 × 630 Open Graph image.
+const MAX_RENDER_SIZE: u32 = 2048;
+
+/// Encode an image as a blurhash string.
+///
+/// `components_x` / `components_y` must be between 1 and 9 (Mastodon uses
+/// 4 × 3).  Returns `{:ok, hash}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn blurhash_encode(
+    bytes: Binary,
+    components_x: u32,
+    components_y: u32,
+) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        if !(1..=9).contains(&components_x) || !(1..=9).contains(&components_y) {
+            return Err(atoms::invalid_components());
+        }
+
+        let sample = load(bytes.as_slice())?
+            .thumbnail(BLURHASH_SAMPLE_SIZE, BLURHASH_SAMPLE_SIZE)
+            .into_rgba8();
+
+        blurhash::encode(
+            components_x,
+            components_y,
+            sample.width(),
+            sample.height(),
+            sample.as_raw(),
+        )
+        .map_err(|_| atoms::invalid_components())
+    })
+}
+
+/// Render a blurhash as a `width` × `height` PNG.
+///
+/// Returns `{:ok, png}`, or `{:error, :invalid_dimensions}` /
+/// `{:error, :invalid_blurhash}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn blurhash_decode<'a>(
+    env: Env<'a>,
+    hash: &str,
+    width: u32,
+    height: u32,
+) -> Guarded<Result<Binary<'a>, Atom>> {
+    guard(|| {
+        if !(1..=MAX_RENDER_SIZE).contains(&width) || !(1..=MAX_RENDER_SIZE).contains(&height) {
+            return Err(atoms::invalid_dimensions());
+        }
+
+        let pixels =
+            blurhash::decode(hash, width, height, 1.0).map_err(|_| atoms::invalid_blurhash())?;
+        let image =
+            RgbaImage::from_raw(width, height, pixels).ok_or_else(atoms::invalid_blurhash)?;
+
+        Ok(to_binary(
+            env,
+            &encode(&DynamicImage::ImageRgba8(image), OutputFormat::Png)?,
+        ))
+    })
+}
+
+/// Encode an image as a base64 thumbhash string.
+///
+/// Unlike blurhash, thumbhash preserves the aspect ratio and alpha channel.
+/// Returns `{:ok, hash}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn thumbhash_encode(bytes: Binary) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        let sample = load(bytes.as_slice())?
+            .thumbnail(THUMBHASH_SAMPLE_SIZE, THUMBHASH_SAMPLE_SIZE)
+            .into_rgba8();
+
+        let hash = thumbhash::rgba_to_thumb_hash(
+            sample.width() as usize,
+            sample.height() as usize,
+            sample.as_raw(),
+        );
+
+        Ok(THUMBHASH_BASE64.encode(hash))
+    })
+}
+
+/// Render a base64 thumbhash as a PNG at its native size (at most 32 pixels
+/// on the longer side, with the encoded aspect ratio).
+///
+/// Returns `{:ok, png}` or `{:error, :invalid_thumbhash}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn thumbhash_decode<'a>(env: Env<'a>, hash: &str) -> Guarded<Result<Binary<'a>, Atom>> {
+    guard(|| {
+        let hash = THUMBHASH_BASE64
+            .decode(hash)
+            .map_err(|_| atoms::invalid_thumbhash())?;
+        let (width, height, pixels) =
+            thumbhash::thumb_hash_to_rgba(&hash).map_err(|_| atoms::invalid_thumbhash())?;
+        let image = RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .ok_or_else(atoms::invalid_thumbhash)?;
+
+        Ok(to_binary(
+            env,
+            &encode(&DynamicImage::ImageRgba8(image), OutputFormat::Png)?,
+        ))
+    })
+}
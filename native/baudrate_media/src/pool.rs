@@ -0,0 +1,38 @@
+//! The rayon thread pool that batch NIFs spread their items over.
+//!
+//! The pool is built when the library loads, with `BAUDRATE_NIF_THREADS`
+//! threads, or one per core when that is unset or not a positive number.
+//! A batch NIF runs on a dirty CPU scheduler and blocks it until the pool
+//! has finished every item, so the VM's own schedulers are never tied up.
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::OnceLock;
+
+static POOL: OnceLock<ThreadPool> = OnceLock::new();
+
+fn threads() -> usize {
+    std::env::var("BAUDRATE_NIF_THREADS")
+        .ok()
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Build the pool; fails the load if its threads cannot be started.
+pub(crate) fn load() -> bool {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads())
+        .thread_name(|i| format!("baudrate_media-{i}"))
+        .build();
+    match pool {
+        Ok(pool) => {
+            // Only already set if this library was loaded before.
+            let _ = POOL.set(pool);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+pub(crate) fn get() -> &'static ThreadPool {
+    POOL.get().expect("the thread pool is built at load")
+}
@@ -0,0 +1,174 @@
+//! Header-only inspection of untrusted images.
+//!
+//! Nothing here decodes pixel data: dimensions come from the decoder's
+//! header parsing and frame counts from walking the container structure,
+//! so oversized or decompression-bomb inputs can be rejected cheaply.
+
+use image::metadata::Orientation;
+use image::{ImageDecoder, ImageFormat};
+use rustler::{Atom, Binary, NifMap, NifUnitEnum};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, decoder};
+
+/// Formats that can be probed, passed to Elixir as atoms.
+#[derive(NifUnitEnum, Clone, Copy)]
+enum ProbedFormat {
+    Jpeg,
+    Png,
+    Gif,
+    Webp,
+}
+
+#[derive(NifMap)]
+struct Probe {
+    format: ProbedFormat,
+    /// Displayed width, after applying the EXIF orientation.
+    width: u32,
+    /// Displayed height, after applying the EXIF orientation.
+    height: u32,
+    animated: bool,
+    /// Bytes needed to hold one decoded frame.
+    decoded_bytes: u64,
+}
+
+/// Read an image's format, dimensions, animation flag, and decoded frame
+/// size from its headers.
+///
+/// Returns `{:ok, map}` or `{:error, reason}`.
+#[rustler::nif]
+fn probe_image(bytes: Binary) -> Guarded<Result<Probe, Atom>> {
+    guard(|| {
+        let data = bytes.as_slice();
+        let format = match image::guess_format(data) {
+            Ok(ImageFormat::Jpeg) => ProbedFormat::Jpeg,
+            Ok(ImageFormat::Png) => ProbedFormat::Png,
+            Ok(ImageFormat::Gif) => ProbedFormat::Gif,
+            Ok(ImageFormat::WebP) => ProbedFormat::Webp,
+            _ => return Err(atoms::unsupported_format()),
+        };
+
+        let mut decoder = decoder(data)?;
+        let (width, height) = decoder.dimensions();
+        let decoded_bytes = decoder.total_bytes();
+        let rotated = matches!(
+            decoder.orientation().unwrap_or(Orientation::NoTransforms),
+            Orientation::Rotate90
+                | Orientation::Rotate270
+                | Orientation::Rotate90FlipH
+                | Orientation::Rotate270FlipH
+        );
+        let (width, height) = if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        };
+
+        let frames = match format {
+            ProbedFormat::Jpeg => Some(1),
+            ProbedFormat::Png => png_frames(data),
+            ProbedFormat::Gif => gif_frames(data),
+            ProbedFormat::Webp => webp_frames(data),
+        }
+        .ok_or_else(atoms::decode_failed)?;
+
+        Ok(Probe {
+            format,
+            width,
+            height,
+            animated: frames > 1,
+            decoded_bytes,
+        })
+    })
+}
+
+/// Frame count from the APNG `acTL` chunk; 1 for still PNGs.
+pub(crate) fn png_frames(data: &[u8]) -> Option<u32> {
+    let mut pos = 8;
+
+    loop {
+        let len = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let kind = data.get(pos + 4..pos + 8)?;
+
+        match kind {
+            b"acTL" => {
+                let frames = data.get(pos + 8..pos + 12)?;
+                return Some(u32::from_be_bytes(frames.try_into().ok()?));
+            }
+            // acTL must precede the image data.
+            b"IDAT" | b"IEND" => return Some(1),
+            _ => {}
+        }
+        pos = pos.checked_add(12)?.checked_add(len)?;
+    }
+}
+
+/// Count GIF image descriptors, skipping over the LZW data without
+/// decompressing it.
+pub(crate) fn gif_frames(data: &[u8]) -> Option<u32> {
+    let packed = *data.get(10)?;
+    let mut pos = 13 + color_table_len(packed);
+    let mut frames = 0;
+
+    // Stop at the trailer, or count what was seen if the file is truncated
+    // or has trailing garbage.
+    loop {
+        let next = match data.get(pos) {
+            // Extension: label byte, then data sub-blocks.
+            Some(0x21) => skip_sub_blocks(data, pos + 2),
+            Some(0x2C) => {
+                frames += 1;
+                // Descriptor, local color table, LZW minimum code size.
+                data.get(pos + 9).and_then(|&packed| {
+                    skip_sub_blocks(data, pos + 10 + color_table_len(packed) + 1)
+                })
+            }
+            _ => None,
+        };
+
+        match next {
+            Some(next) => pos = next,
+            None => return Some(frames.max(1)),
+        }
+    }
+}
+
+pub(crate) fn color_table_len(packed: u8) -> usize {
+    if packed & 0x80 == 0 {
+        0
+    } else {
+        3 << ((packed & 0x07) + 1)
+    }
+}
+
+/// Return the position after a run of GIF sub-blocks starting at `pos`.
+pub(crate) fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let size = *data.get(pos)? as usize;
+        pos += 1;
+        if size == 0 {
+            return Some(pos);
+        }
+        pos += size;
+    }
+}
+
+/// Count `ANMF` chunks in a WebP container; 1 for still images.
+pub(crate) fn webp_frames(data: &[u8]) -> Option<u32> {
+    let mut pos = 12;
+    let mut frames = 0;
+
+    while pos + 8 <= data.len() {
+        let fourcc = &data[pos..pos + 4];
+        let size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize;
+        if fourcc == b"ANMF" {
+            frames += 1;
+        }
+        pos = pos
+            .checked_add(8)?
+            .checked_add(size)?
+            .checked_add(size % 2)?;
+    }
+
+    Some(frames.max(1))
+}
@@ -0,0 +1,136 @@
+//! QR codes rendered as static SVG.
+//!
+//! The output is a single `<svg>` holding at most a background `<rect>` and
+//! one `<path>`, with no styles, scripts, links, or text, so it survives a
+//! strict SVG sanitizer and can be inlined or served as a data URI.
+
+use qrcode::{Color, EcLevel, QrCode};
+use rustler::{Atom, Binary, Decoder, Error, NifResult, NifUnitEnum, Term};
+use std::fmt::Write as _;
+
+use crate::atoms;
+use baudrate_nif::guard::{guard, Guarded};
+
+/// Largest rendered width and height, in pixels.
+const MAX_SIZE: u32 = 4096;
+
+/// Largest quiet zone, in modules.
+const MAX_MARGIN: u32 = 16;
+
+/// Error correction levels, passed from Elixir as atoms.
+#[derive(NifUnitEnum, Clone, Copy)]
+enum Level {
+    /// Recovers about 7% damage.
+    Low,
+    /// About 15%.
+    Medium,
+    /// About 25%.
+    Quartile,
+    /// About 30%.
+    High,
+}
+
+/// Options decoded from a map; every key is optional.
+struct QrOptions {
+    size: u32,
+    margin: u32,
+    ec_level: Level,
+    color: String,
+    /// `nil` leaves the background transparent.
+    background: Option<String>,
+}
+
+/// The value under `key`, treating a missing key like `nil`.
+fn get<'a, T: Decoder<'a>>(term: Term<'a>, key: Atom) -> NifResult<Option<T>> {
+    term.map_get(key).map_or(Ok(None), |t| t.decode())
+}
+
+impl<'a> Decoder<'a> for QrOptions {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if !term.is_map() {
+            return Err(Error::BadArg);
+        }
+        Ok(Self {
+            size: get(term, atoms::size())?.unwrap_or(264),
+            margin: get(term, atoms::margin())?.unwrap_or(4),
+            ec_level: get(term, atoms::ec_level())?.unwrap_or(Level::Medium),
+            color: get(term, atoms::color())?.unwrap_or_else(|| "#000000".to_string()),
+            background: match term.map_get(atoms::background()) {
+                Ok(t) => t.decode()?,
+                Err(_) => Some("#ffffff".to_string()),
+            },
+        })
+    }
+}
+
+/// `#rgb` or `#rrggbb`; anything else could escape the attribute.
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+fn render(code: &QrCode, options: &QrOptions) -> String {
+    let modules = code.width() as u32;
+    let extent = modules + 2 * options.margin;
+    let colors = code.to_colors();
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {extent} {extent}" shape-rendering="crispEdges">"#,
+        size = options.size,
+    );
+    if let Some(background) = &options.background {
+        let _ = write!(
+            svg,
+            r#"<rect width="{extent}" height="{extent}" fill="{background}"/>"#
+        );
+    }
+
+    // One subpath per horizontal run of dark modules.
+    svg.push_str(r#"<path d=""#);
+    for (y, row) in colors.chunks(modules as usize).enumerate() {
+        let mut x = 0;
+        while x < row.len() {
+            if row[x] == Color::Dark {
+                let start = x;
+                while x < row.len() && row[x] == Color::Dark {
+                    x += 1;
+                }
+                let run = x - start;
+                let (left, top) = (start as u32 + options.margin, y as u32 + options.margin);
+                let _ = write!(svg, "M{left} {top}h{run}v1h-{run}z");
+            } else {
+                x += 1;
+            }
+        }
+    }
+    let _ = write!(svg, r#"" fill="{}"/></svg>"#, options.color);
+    svg
+}
+
+/// Encode `data` as a QR code and return it as an SVG document.
+///
+/// Returns `{:ok, svg}`, `{:error, :invalid_options}`, or
+/// `{:error, :data_too_long}`.
+#[rustler::nif]
+fn qr_svg(data: Binary, options: QrOptions) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        let colors_valid =
+            is_hex_color(&options.color) && options.background.as_deref().is_none_or(is_hex_color);
+        if !(1..=MAX_SIZE).contains(&options.size) || options.margin > MAX_MARGIN || !colors_valid {
+            return Err(atoms::invalid_options());
+        }
+
+        let level = match options.ec_level {
+            Level::Low => EcLevel::L,
+            Level::Medium => EcLevel::M,
+            Level::Quartile => EcLevel::Q,
+            Level::High => EcLevel::H,
+        };
+        let code = QrCode::with_error_correction_level(data.as_slice(), level)
+            .map_err(|_| atoms::data_too_long())?;
+        Ok(render(&code, &options))
+    })
+}
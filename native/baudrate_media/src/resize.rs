@@ -0,0 +1,44 @@
+//! Thumbnail and variant generation.
+
+use image::imageops::FilterType;
+use rustler::{Atom, Binary, Env};
+
+use crate::animation::AnimationLimits;
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, encode, load, to_binary, Encoded, OutputFormat};
+
+/// Downscale an image to fit within `max_width` × `max_height`, preserving
+/// the aspect ratio, and encode it as `format`.
+///
+/// The EXIF orientation is applied first and images that already fit are
+/// never upscaled.  Resampling uses a Lanczos3 filter.  Animations over the
+/// frame or decoded size `limits` are rejected before decoding.  Returns
+/// `{:ok, %{data: bytes, width: w, height: h}}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn resize_image<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    max_width: u32,
+    max_height: u32,
+    format: OutputFormat,
+    limits: AnimationLimits,
+) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        if max_width == 0 || max_height == 0 {
+            return Err(atoms::invalid_dimensions());
+        }
+
+        limits.check(bytes.as_slice())?;
+        let mut image = load(bytes.as_slice())?;
+
+        if image.width() > max_width || image.height() > max_height {
+            image = image.resize(max_width, max_height, FilterType::Lanczos3);
+        }
+
+        Ok(Encoded {
+            data: to_binary(env, &encode(&image, format)?),
+            width: image.width(),
+            height: image.height(),
+        })
+    })
+}
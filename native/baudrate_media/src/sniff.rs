@@ -0,0 +1,225 @@
+//! Content sniffing for uploads and proxied media.
+//!
+//! Implements the image and audio/video pattern matching of the WHATWG MIME
+//! Sniffing standard (sections 6.1 and 6.2), including its MP4, WebM, and
+//! ID3-less MP3 signature algorithms, so the type of remote content can be
+//! decided from its bytes rather than from a `Content-Type` header.  Two
+//! types we accept but the standard does not list are recognised as well:
+//! AVIF (an `ftyp` box with an `avif` / `avis` brand) and FLAC.
+
+use baudrate_nif::guard::{guard, Guarded};
+
+/// The standard only inspects this many leading bytes (the resource header).
+const RESOURCE_HEADER_LEN: usize = 1445;
+
+/// A byte pattern with its mask; pattern bytes are compared after masking
+/// the input with the corresponding mask byte.
+struct Signature {
+    pattern: &'static [u8],
+    mask: &'static [u8],
+    mime: &'static str,
+}
+
+const fn exact(pattern: &'static [u8], mime: &'static str) -> Signature {
+    Signature {
+        pattern,
+        mask: &[0xFF; 16],
+        mime,
+    }
+}
+
+const IMAGE_SIGNATURES: &[Signature] = &[
+    exact(b"\x00\x00\x01\x00", "image/x-icon"),
+    exact(b"\x00\x00\x02\x00", "image/x-icon"),
+    exact(b"BM", "image/bmp"),
+    exact(b"GIF87a", "image/gif"),
+    exact(b"GIF89a", "image/gif"),
+    Signature {
+        pattern: b"RIFF\x00\x00\x00\x00WEBPVP",
+        mask: b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF\xFF\xFF",
+        mime: "image/webp",
+    },
+    exact(b"\x89PNG\r\n\x1a\n", "image/png"),
+    exact(b"\xFF\xD8\xFF", "image/jpeg"),
+];
+
+const AUDIO_VIDEO_SIGNATURES: &[Signature] = &[
+    exact(b".snd", "audio/basic"),
+    Signature {
+        pattern: b"FORM\x00\x00\x00\x00AIFF",
+        mask: b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF",
+        mime: "audio/aiff",
+    },
+    exact(b"ID3", "audio/mpeg"),
+    exact(b"OggS\x00", "application/ogg"),
+    exact(b"MThd\x00\x00\x00\x06", "audio/midi"),
+    Signature {
+        pattern: b"RIFF\x00\x00\x00\x00AVI ",
+        mask: b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF",
+        mime: "video/avi",
+    },
+    Signature {
+        pattern: b"RIFF\x00\x00\x00\x00WAVE",
+        mask: b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF",
+        mime: "audio/wave",
+    },
+    exact(b"fLaC", "audio/flac"),
+];
+
+/// Detect the MIME type of image, audio, or video content from its leading
+/// bytes.
+///
+/// Returns the type as a string, or `nil` when no rule matches.
+#[rustler::nif]
+fn sniff_mime(bytes: rustler::Binary) -> Guarded<Option<&'static str>> {
+    guard(|| {
+        let data = bytes.as_slice();
+        sniff(&data[..data.len().min(RESOURCE_HEADER_LEN)])
+    })
+}
+
+fn sniff(header: &[u8]) -> Option<&'static str> {
+    if let Some(mime) = find_signature(IMAGE_SIGNATURES, header) {
+        return Some(mime);
+    }
+    if is_avif(header) {
+        return Some("image/avif");
+    }
+    if let Some(mime) = find_signature(AUDIO_VIDEO_SIGNATURES, header) {
+        return Some(mime);
+    }
+    if is_mp4(header) {
+        return Some("video/mp4");
+    }
+    if is_webm(header) {
+        return Some("video/webm");
+    }
+    if is_mp3_without_id3(header) {
+        return Some("audio/mpeg");
+    }
+    None
+}
+
+fn find_signature(signatures: &[Signature], header: &[u8]) -> Option<&'static str> {
+    signatures
+        .iter()
+        .find(|sig| {
+            header.len() >= sig.pattern.len()
+                && sig
+                    .pattern
+                    .iter()
+                    .zip(sig.mask)
+                    .zip(header)
+                    .all(|((&p, &m), &b)| b & m == p)
+        })
+        .map(|sig| sig.mime)
+}
+
+/// The `ftyp` box at the start of an ISO base media file, if well formed:
+/// its major brand followed by the compatible brands.
+fn ftyp_brands(header: &[u8]) -> Option<&[u8]> {
+    if header.len() < 12 {
+        return None;
+    }
+    let box_size = u32::from_be_bytes(header[0..4].try_into().ok()?) as usize;
+    if header.len() < box_size || !box_size.is_multiple_of(4) || &header[4..8] != b"ftyp" {
+        return None;
+    }
+    Some(&header[8..box_size.max(12)])
+}
+
+/// The standard's MP4 signature: an `mp4` major or compatible brand.  The
+/// four bytes after the major brand are the minor version, not a brand.
+fn is_mp4(header: &[u8]) -> bool {
+    let Some(brands) = ftyp_brands(header) else {
+        return false;
+    };
+    brands.starts_with(b"mp4")
+        || brands
+            .get(8..)
+            .is_some_and(|compatible| compatible.chunks(4).any(|b| b.starts_with(b"mp4")))
+}
+
+fn is_avif(header: &[u8]) -> bool {
+    ftyp_brands(header)
+        .is_some_and(|brands| brands.starts_with(b"avif") || brands.starts_with(b"avis"))
+}
+
+/// The standard's WebM signature: an EBML header whose `DocType` element
+/// (ID `0x4282`) is `webm`, within the first 38 bytes.
+fn is_webm(header: &[u8]) -> bool {
+    if !header.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return false;
+    }
+
+    let mut iter = 4;
+    while iter < header.len() && iter < 38 {
+        if header[iter..].starts_with(&[0x42, 0x82]) {
+            iter += 2;
+            if iter >= header.len() {
+                return false;
+            }
+            iter += vint_size(header[iter]);
+            if iter + 4 >= header.len() {
+                return false;
+            }
+            // The element size may be followed by zero padding.
+            let value = &header[iter..];
+            let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+            return value[start..].starts_with(b"webm");
+        }
+        iter += 1;
+    }
+    false
+}
+
+/// Length in bytes of an EBML variable-size integer, from its first byte.
+fn vint_size(first: u8) -> usize {
+    (first.leading_zeros() as usize + 1).min(8)
+}
+
+/// The standard's MP3-without-ID3 signature: two consecutive MPEG audio
+/// Layer III frame headers, the second where the first frame's size says.
+fn is_mp3_without_id3(header: &[u8]) -> bool {
+    let Some(size) = mp3_frame_size(header) else {
+        return false;
+    };
+    size >= 4 && header.get(size..).and_then(mp3_frame_size).is_some()
+}
+
+/// Size of the MP3 frame whose header starts `data`, or `None` if `data`
+/// does not start with a valid frame header.
+fn mp3_frame_size(data: &[u8]) -> Option<usize> {
+    let header = data.get(0..4)?;
+    if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+
+    let version = (header[1] & 0x18) >> 3;
+    let layer = (header[1] & 0x06) >> 1;
+    let bitrate_index = ((header[2] & 0xF0) >> 4) as usize;
+    let sample_rate_index = ((header[2] & 0x0C) >> 2) as usize;
+    let padding = ((header[2] & 0x02) >> 1) as usize;
+
+    // Reserved version, Layer III only, no free-format or invalid bitrates,
+    // no reserved sample rate.
+    if version == 1 || layer != 1 || bitrate_index == 0 || bitrate_index == 15 {
+        return None;
+    }
+    let sample_rate = *[44_100, 48_000, 32_000].get(sample_rate_index)?;
+
+    const MPEG1_KBPS: [usize; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const MPEG2_KBPS: [usize; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+    // Version 3 is MPEG-1; 2 is MPEG-2 and 0 is MPEG-2.5, which halve and
+    // quarter the sample rate and carry half as many samples per frame.
+    let (kbps, sample_rate, scale) = match version {
+        3 => (MPEG1_KBPS[bitrate_index], sample_rate, 144),
+        2 => (MPEG2_KBPS[bitrate_index], sample_rate / 2, 72),
+        _ => (MPEG2_KBPS[bitrate_index], sample_rate / 4, 72),
+    };
+
+    Some(scale * kbps * 1000 / sample_rate + padding)
+}
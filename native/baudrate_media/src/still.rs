@@ -0,0 +1,28 @@
+//! Static previews of animated images.
+
+use rustler::{Atom, Binary, Env};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{encode, load, to_binary, Encoded, OutputFormat};
+
+/// Extract the first frame of an animated GIF, APNG, or WebP and encode it
+/// as a PNG.
+///
+/// Decoding a GIF or WebP yields its first frame composited onto the full
+/// canvas.  For an APNG it yields the default image, which is the first
+/// frame or, when the file declares it outside the animation, the image
+/// intended as its static fallback.  Still images are re-encoded as they
+/// are.  Returns `{:ok, %{data: bytes, width: w, height: h}}` or
+/// `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn still_frame<'a>(env: Env<'a>, bytes: Binary) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        let image = load(bytes.as_slice())?;
+
+        Ok(Encoded {
+            data: to_binary(env, &encode(&image, OutputFormat::Png)?),
+            width: image.width(),
+            height: image.height(),
+        })
+    })
+}
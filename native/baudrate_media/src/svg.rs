@@ -0,0 +1,134 @@
+//! SVG rasterization, so that remote SVG avatars and emoji are never served
+//! as-is.
+//!
+//! SVG is untrusted markup, so parsing is locked down: external and
+//! embedded images are never loaded (the default resolver would read local
+//! files), DTDs and gzip-compressed SVGZ are rejected, text is not rendered,
+//! and both the source and the expanded render tree have node limits.
+
+use image::{DynamicImage, RgbaImage};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::roxmltree::{Document, ParsingOptions};
+use resvg::usvg::{self, ImageHrefResolver, Node, Options, Tree};
+use rustler::{Atom, Binary, Env};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, encode, to_binary, Encoded, OutputFormat};
+
+/// Largest accepted SVG source, in bytes.
+const MAX_SVG_BYTES: usize = 1024 * 1024;
+
+/// Largest number of XML nodes in the source document.
+const MAX_XML_NODES: u32 = 100_000;
+
+/// Largest number of nodes in the render tree, after `<use>` references
+/// have been expanded.
+const MAX_RENDER_NODES: usize = 20_000;
+
+/// Largest output canvas, in pixels per side.
+const MAX_CANVAS_SIZE: u32 = 2048;
+
+/// Render an SVG document as a PNG scaled to fit within `width` × `height`,
+/// preserving its aspect ratio.
+///
+/// Returns `{:ok, %{data: png, width: w, height: h}}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn rasterize_svg<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    width: u32,
+    height: u32,
+) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        if !(1..=MAX_CANVAS_SIZE).contains(&width) || !(1..=MAX_CANVAS_SIZE).contains(&height) {
+            return Err(atoms::invalid_dimensions());
+        }
+        if bytes.len() > MAX_SVG_BYTES {
+            return Err(atoms::input_too_large());
+        }
+
+        let tree = parse(bytes.as_slice())?;
+        if render_nodes(tree.root().children(), MAX_RENDER_NODES + 1) > MAX_RENDER_NODES {
+            return Err(atoms::svg_too_complex());
+        }
+
+        let size = tree.size();
+        let scale = (width as f32 / size.width()).min(height as f32 / size.height());
+        let out_width = ((size.width() * scale).round() as u32).clamp(1, width);
+        let out_height = ((size.height() * scale).round() as u32).clamp(1, height);
+
+        let mut pixmap =
+            Pixmap::new(out_width, out_height).ok_or_else(atoms::invalid_dimensions)?;
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+
+        // tiny-skia stores premultiplied alpha.
+        let rgba: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        let image =
+            RgbaImage::from_raw(out_width, out_height, rgba).ok_or_else(atoms::encode_failed)?;
+
+        Ok(Encoded {
+            data: to_binary(
+                env,
+                &encode(&DynamicImage::ImageRgba8(image), OutputFormat::Png)?,
+            ),
+            width: out_width,
+            height: out_height,
+        })
+    })
+}
+
+fn parse(data: &[u8]) -> Result<Tree, Atom> {
+    let text = std::str::from_utf8(data).map_err(|_| atoms::invalid_svg())?;
+
+    let xml_options = ParsingOptions {
+        allow_dtd: false,
+        nodes_limit: MAX_XML_NODES,
+    };
+    let document = Document::parse_with_options(text, xml_options).map_err(|e| match e {
+        usvg::roxmltree::Error::NodesLimitReached => atoms::svg_too_complex(),
+        _ => atoms::invalid_svg(),
+    })?;
+
+    let options = Options {
+        image_href_resolver: ImageHrefResolver {
+            resolve_data: Box::new(|_, _, _| None),
+            resolve_string: Box::new(|_, _| None),
+        },
+        ..Options::default()
+    };
+
+    // Expanding `<use>` references can also hit usvg's own node limit.
+    Tree::from_xmltree(&document, &options).map_err(|e| match e {
+        usvg::Error::ElementsLimitReached
+        | usvg::Error::ParsingFailed(usvg::roxmltree::Error::NodesLimitReached) => {
+            atoms::svg_too_complex()
+        }
+        _ => atoms::invalid_svg(),
+    })
+}
+
+/// Count render tree nodes, stopping once `limit` is reached.
+fn render_nodes(nodes: &[Node], limit: usize) -> usize {
+    let mut count = 0;
+    for node in nodes {
+        count += 1;
+        if let Node::Group(group) = node {
+            count += render_nodes(group.children(), limit - count.min(limit));
+        }
+        if count >= limit {
+            break;
+        }
+    }
+    count
+}
@@ -0,0 +1,92 @@
+//! Lossy transcoding to modern formats for bandwidth-efficient variants.
+
+#[cfg(feature = "avif")]
+use image::codecs::avif::AvifEncoder;
+use image::DynamicImage;
+use rustler::{Atom, Binary, Env, NifUnitEnum};
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{atoms, dimensions, load, to_binary, Encoded};
+
+/// Largest accepted input, in bytes (32 MiB).
+const MAX_INPUT_BYTES: usize = 32 * 1024 * 1024;
+
+/// Largest width or height that will be transcoded.  AVIF encoding time
+/// grows quickly with image size, so this is stricter than the decode limit.
+const MAX_TRANSCODE_DIMENSION: u32 = 8192;
+
+/// rav1e speed preset (1 = slowest / smallest, 10 = fastest).
+#[cfg(feature = "avif")]
+const AVIF_SPEED: u8 = 6;
+
+/// Target formats, passed from Elixir as `:webp` or `:avif`.
+#[derive(NifUnitEnum, Clone, Copy)]
+enum TranscodeFormat {
+    Webp,
+    Avif,
+}
+
+/// Re-encode an image as lossy WebP or AVIF at `quality` (1–100).  AVIF
+/// needs the `avif` feature and otherwise fails with `:feature_disabled`.
+///
+/// Inputs over 32 MiB or 8192 pixels on either side are rejected from the
+/// headers before any pixels are decoded.  Returns
+/// `{:ok, %{data: bytes, width: w, height: h}}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn transcode_image<'a>(
+    env: Env<'a>,
+    bytes: Binary,
+    format: TranscodeFormat,
+    quality: u8,
+) -> Guarded<Result<Encoded<'a>, Atom>> {
+    guard(|| {
+        if !(1..=100).contains(&quality) {
+            return Err(atoms::invalid_quality());
+        }
+
+        let data = bytes.as_slice();
+        if data.len() > MAX_INPUT_BYTES {
+            return Err(atoms::input_too_large());
+        }
+
+        let (width, height) = dimensions(data)?;
+        if width > MAX_TRANSCODE_DIMENSION || height > MAX_TRANSCODE_DIMENSION {
+            return Err(atoms::image_too_large());
+        }
+
+        let image = load(data)?;
+        let encoded = match format {
+            TranscodeFormat::Webp => encode_webp(&image, quality)?,
+            TranscodeFormat::Avif => encode_avif(&image, quality)?,
+        };
+
+        Ok(Encoded {
+            data: to_binary(env, &encoded),
+            width: image.width(),
+            height: image.height(),
+        })
+    })
+}
+
+fn encode_webp(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, Atom> {
+    let rgba = image.to_rgba8();
+    webp::Encoder::from_rgba(rgba.as_raw(), rgba.width(), rgba.height())
+        .encode_simple(false, f32::from(quality))
+        .map(|memory| memory.to_vec())
+        .map_err(|_| atoms::encode_failed())
+}
+
+#[cfg(feature = "avif")]
+fn encode_avif(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, Atom> {
+    let mut out = Vec::new();
+    let encoder = AvifEncoder::new_with_speed_quality(&mut out, AVIF_SPEED, quality);
+    DynamicImage::ImageRgba8(image.to_rgba8())
+        .write_with_encoder(encoder)
+        .map_err(|_| atoms::encode_failed())?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "avif"))]
+fn encode_avif(_image: &DynamicImage, _quality: u8) -> Result<Vec<u8>, Atom> {
+    Err(atoms::feature_disabled())
+}
@@ -0,0 +1,61 @@
+//! Panic containment for NIF bodies.
+//!
+//! Rustler already stops a panic from unwinding into the VM, but raises it
+//! as an opaque `:nif_panicked` error.  Bodies run through [`guard`]
+//! instead return `{:error, {:panic, message}}`, which callers can match
+//! like any other error.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+use rustler::{Encoder, Env, Term};
+
+mod atoms {
+    rustler::atoms! {
+        error,
+        panic,
+    }
+}
+
+/// The outcome of a guarded NIF body: its value, or the panic message.
+pub enum Guarded<T> {
+    Returned(T),
+    Panicked(String),
+}
+
+/// Run `body`, catching any panic.
+pub fn guard<T>(body: impl FnOnce() -> T) -> Guarded<T> {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => Guarded::Returned(value),
+        Err(payload) => Guarded::Panicked(message(payload)),
+    }
+}
+
+impl<T, E> Guarded<Result<T, E>> {
+    /// Move an error out of the guard, so that `NifResult` bodies still
+    /// raise on invalid arguments.
+    pub fn transpose(self) -> Result<Guarded<T>, E> {
+        match self {
+            Self::Returned(result) => result.map(Guarded::Returned),
+            Self::Panicked(message) => Ok(Guarded::Panicked(message)),
+        }
+    }
+}
+
+fn message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "unknown panic".to_owned(), |s| (*s).to_owned()),
+    }
+}
+
+impl<T: Encoder> Encoder for Guarded<T> {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            Self::Returned(value) => value.encode(env),
+            Self::Panicked(message) => (atoms::error(), (atoms::panic(), message)).encode(env),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Hashtag canonical forms, as Mastodon's `HashtagNormalizer` computes them.
+//!
+//! Two spellings are the same hashtag when their canonical forms are equal:
+//! NFKC-normalized (so full-width `＃ＦＯＯ` is `#FOO`), lowercased,
+//! NFC-normalized, Latin letters with diacritics folded to ASCII
+//! (`#CaféCon` is `cafecon`), and everything but letters, marks, digits,
+//! and the separators Mastodon allows removed.  Matching Mastodon exactly
+//! keeps local tags and the `/tags/...` links of remote posts in step.
+//!
+//! Case is lowercased, not folded as `casefold/1` does, as Mastodon does:
+//! `#Straße` is `straße`.
+//!
+//! `normalize_hashtag/1` returns these forms to Elixir, and the sanitizer
+//! links and reports hashtags under them, so tags stored, linked, and
+//! looked up always agree.
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Lowercase Latin letters and the ASCII letter each folds to, as in
+/// Mastodon's `ASCIIFolding`.
+const ASCII_FOLDING: &[(&str, char)] = &[
+    ("àáâãäåāăą", 'a'),
+    ("çćĉċč", 'c'),
+    ("ðďđ", 'd'),
+    ("èéêëēĕėęě", 'e'),
+    ("ĝğġģ", 'g'),
+    ("ĥħ", 'h'),
+    ("ìíîïĩīĭįı", 'i'),
+    ("ĵ", 'j'),
+    ("ķĸ", 'k'),
+    ("ĺļľŀł", 'l'),
+    ("ñńņňŉŋ", 'n'),
+    ("òóôõöøōŏő", 'o'),
+    ("ŕŗř", 'r'),
+    ("śŝşšſ", 's'),
+    ("ţťŧ", 't'),
+    ("ùúûüũūŭůűų", 'u'),
+    ("ŵ", 'w'),
+    ("ýÿŷ", 'y'),
+    ("źżž", 'z'),
+];
+
+/// Characters other than letters, marks, and digits kept in a hashtag:
+/// underscore, middle dot, katakana middle dot, and zero-width non-joiner.
+const SEPARATORS: [char; 4] = ['_', '\u{B7}', '\u{30FB}', '\u{200C}'];
+
+fn fold(c: char) -> char {
+    if c.is_ascii() {
+        return c;
+    }
+    ASCII_FOLDING.iter().find(|(letters, _)| letters.contains(c)).map_or(c, |&(_, ascii)| ascii)
+}
+
+fn is_kept(c: char) -> bool {
+    c.is_alphanumeric() || is_combining_mark(c) || SEPARATORS.contains(&c)
+}
+
+/// The canonical form of `tag`, written without its `#`.
+pub fn canonical(tag: &str) -> String {
+    let lowercase: String = tag.nfkc().collect::<String>().to_lowercase().nfc().collect();
+    lowercase.chars().map(fold).filter(|&c| is_kept(c)).collect()
+}
+
+/// `(canonical, display)` for a hashtag, with or without its `#`: the key
+/// to match and store it under, and the spelling to show, as written.
+/// `None` if no letter is left, as in `#123` or `#!!`.
+pub fn normalize(tag: &str) -> Option<(String, String)> {
+    let display = tag.trim();
+    let display = display.strip_prefix(['#', '＃']).unwrap_or(display).trim_start();
+    let canonical = canonical(display);
+    canonical.chars().any(char::is_alphabetic).then(|| (canonical, display.to_string()))
+}
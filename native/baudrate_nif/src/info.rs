@@ -0,0 +1,57 @@
+//! Build information, for detecting what a loaded library supports.
+//!
+//! Each NIF crate exports its own `native_info/0`, so that the version is
+//! its own, and builds the result with [`native_info`].
+
+use std::ffi::CStr;
+
+use rustler::codegen_runtime::inventory;
+use rustler::{Atom, Env, Nif, NifMap};
+
+/// An optional Cargo feature.
+pub struct Feature {
+    pub name: &'static str,
+    pub enabled: bool,
+    /// NIFs that only return `{:error, :feature_disabled}` without it.
+    pub nifs: &'static [(&'static str, u32)],
+}
+
+#[derive(NifMap)]
+pub struct NativeInfo {
+    version: String,
+    features: Vec<String>,
+    nifs: Vec<(Atom, u32)>,
+}
+
+/// The `version`, the enabled `features`, and every working NIF in the
+/// loaded library as `{name, arity}`: those of disabled features are left
+/// out.
+pub fn native_info(env: Env, version: &str, features: &[Feature]) -> NativeInfo {
+    let mut nifs: Vec<(&str, u32)> = inventory::iter::<Nif>()
+        .filter_map(|nif| {
+            // SAFETY: `rustler::nif` stores each name as a NUL-terminated
+            // static string.
+            let name = unsafe { CStr::from_ptr(nif.name) }.to_str().ok()?;
+            Some((name, nif.arity))
+        })
+        .filter(|nif| {
+            !features
+                .iter()
+                .any(|feature| !feature.enabled && feature.nifs.contains(nif))
+        })
+        .collect();
+    nifs.sort_unstable();
+
+    NativeInfo {
+        version: version.to_string(),
+        features: features
+            .iter()
+            .filter(|feature| feature.enabled)
+            .map(|feature| feature.name.to_string())
+            .collect(),
+        nifs: nifs
+            .into_iter()
+            .filter_map(|(name, arity)| Some((Atom::from_str(env, name).ok()?, arity)))
+            .collect(),
+    }
+}
@@ -0,0 +1,7 @@
+//! Support code shared by the Baudrate NIF crates, linked into each of
+//! them as a path dependency.
+
+pub mod guard;
+pub mod hashtag;
+pub mod info;
+pub mod time;
@@ -0,0 +1,112 @@
+//! Alt text coverage of the images in a post.
+//!
+//! The composer warns about images without a description, and admin stats
+//! track how many have one.  Besides a missing or blank `alt`, the check
+//! catches text that only looks like one: a file name, a camera's default
+//! name (`IMG_1234`, `Screenshot 2024-01-01 at 10.00.00`), or a generic word
+//! like `image`.  Custom emoji are not content and are skipped.
+
+use crate::dom::{self, Walk};
+use crate::CUSTOM_EMOJI_CLASS;
+use baudrate_nif::guard::{guard, Guarded};
+use markup5ever_rcdom::NodeData;
+use rustler::{Binary, NifMap};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Extensions of image files, as they end up in placeholder alt text.
+const EXTENSIONS: &[&str] =
+    &["png", "jpg", "jpeg", "gif", "webp", "avif", "heic", "heif", "bmp", "svg", "tif", "tiff"];
+
+/// Default file name prefixes of cameras, phones, and screenshot tools.
+const CAMERA_PREFIXES: &[&str] =
+    &["img", "dsc", "dscn", "dscf", "pxl", "photo", "image", "screenshot", "screen shot", "scan"];
+
+/// Words that describe nothing on their own.
+const GENERIC: &[&str] = &[
+    "image", "img", "photo", "picture", "pic", "screenshot", "alt", "alt text", "attachment",
+    "media", "untitled", "file", "graphic",
+];
+
+#[derive(NifMap)]
+struct Image {
+    src: Option<String>,
+    /// Whether `alt` has any text; `alt=""` marks a decorative image, which
+    /// still counts as undescribed.
+    has_alt: bool,
+    /// Graphemes of the trimmed alt text.
+    alt_length: usize,
+    /// Whether the alt text is a file name or a word that describes nothing.
+    placeholder: bool,
+}
+
+fn has_extension(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(stem, ext)| !stem.is_empty() && EXTENSIONS.contains(&ext))
+}
+
+/// `IMG_1234`, `PXL_20240101_123456`, `Screenshot 2024-01-01 at 10.00.00`.
+fn is_camera_name(alt: &str) -> bool {
+    CAMERA_PREFIXES.iter().any(|prefix| {
+        alt.strip_prefix(prefix).is_some_and(|rest| {
+            let rest = rest.replace(" at ", " ");
+            rest.chars().any(|c| c.is_ascii_digit())
+                && rest.chars().all(|c| c.is_ascii_digit() || " _-.:()".contains(c))
+        })
+    })
+}
+
+/// The last path segment of `src`, without its query or fragment.
+fn file_name(src: &str) -> &str {
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or_default()
+}
+
+fn is_placeholder(alt: &str, src: Option<&str>) -> bool {
+    let alt = alt.to_lowercase();
+    let matches_src = src.map(|src| file_name(src).to_lowercase()).is_some_and(|name| {
+        let stem = name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem);
+        !name.is_empty() && (name == alt || stem == alt)
+    });
+    matches_src || has_extension(&alt) || is_camera_name(&alt) || GENERIC.contains(&alt.as_str())
+}
+
+fn images(html: &str) -> Vec<Image> {
+    let mut images = Vec::new();
+    if !html.contains('<') {
+        return images;
+    }
+
+    let fragment = dom::Fragment::parse(html);
+    dom::walk(&fragment.root(), (), |node, ()| {
+        if let NodeData::Element { name, attrs, .. } = &node.data {
+            if &*name.local == "img" {
+                let attrs = attrs.borrow();
+                let attr = |key: &str| {
+                    attrs.iter().find(|a| &*a.name.local == key).map(|a| a.value.to_string())
+                };
+                let emoji = attr("class").is_some_and(|class| {
+                    class.split_whitespace().any(|c| c == CUSTOM_EMOJI_CLASS)
+                });
+                if !emoji {
+                    let src = attr("src");
+                    let alt = attr("alt").unwrap_or_default();
+                    let alt = alt.trim();
+                    images.push(Image {
+                        has_alt: !alt.is_empty(),
+                        alt_length: alt.graphemes(true).count(),
+                        placeholder: !alt.is_empty() && is_placeholder(alt, src.as_deref()),
+                        src,
+                    });
+                }
+            }
+        }
+        Walk::Children(())
+    });
+    images
+}
+
+/// Return, for each image in `html` in order, its `src`, whether it has alt
+/// text, the text's length, and whether the text is a placeholder.
+#[rustler::nif]
+fn image_accessibility(html: Binary) -> Guarded<Vec<Image>> {
+    guard(|| images(&String::from_utf8_lossy(&html)))
+}
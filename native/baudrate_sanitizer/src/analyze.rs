@@ -0,0 +1,197 @@
+//! Everything ingestion needs from a status body, in one NIF call.
+//!
+//! The body is sanitized with the federation rules, and the sanitized
+//! fragment is walked once to collect its plain text, mention and hashtag
+//! links, other links, and custom emoji shortcodes.  That is two parses in
+//! all, one inside Ammonia and one of its output, where ingestion used to
+//! parse the body again for every extraction.
+//!
+//! [`plaintext`] is the same walk without the extraction, used by
+//! `sanitize_federation/2` when asked for plain text.
+
+use crate::dom::{self, Visit, Walk};
+use crate::emoji;
+use crate::log;
+use crate::stats::{self, Nif, Outcome};
+use crate::FEDERATION;
+use markup5ever_rcdom::NodeData;
+use rustler::{Binary, Env, NifTuple};
+
+use baudrate_nif::guard::Guarded;
+use baudrate_nif::hashtag;
+
+#[derive(NifTuple)]
+struct Analysis {
+    sanitized_html: String,
+    plaintext: String,
+    /// `href`s of mention links.
+    mentions: Vec<String>,
+    /// Canonical forms of the hashtag link texts.
+    hashtags: Vec<String>,
+    /// `href`s of all other links.
+    urls: Vec<String>,
+    emoji_shortcodes: Vec<String>,
+}
+
+/// What the walk collects from a sanitized fragment.
+#[derive(Default)]
+struct Extracted {
+    plaintext: String,
+    mentions: Vec<String>,
+    hashtags: Vec<String>,
+    urls: Vec<String>,
+    emoji_shortcodes: Vec<String>,
+}
+
+impl Outcome for Analysis {
+    fn is_error(&self) -> bool {
+        false
+    }
+}
+
+/// Elements that are lines of plain text of their own, and how many
+/// newlines separate them from what follows.
+fn breaks_after(name: &str) -> usize {
+    match name {
+        "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre" | "blockquote" | "ul" | "ol" => 2,
+        "br" | "li" | "hr" => 1,
+        _ => 0,
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+fn break_line(text: &mut String, newlines: usize) {
+    if newlines == 0 {
+        return;
+    }
+    let line_end = text.trim_end_matches([' ', '\t']).len();
+    text.truncate(line_end);
+    let present = text.len() - text.trim_end_matches('\n').len();
+    if !text.is_empty() && present < newlines {
+        text.push_str(&"\n".repeat(newlines - present));
+    }
+}
+
+/// Where the walk is, as each node's parent passes it down.
+#[derive(Clone, Copy, Default)]
+struct Context {
+    in_code: bool,
+    in_link: bool,
+    /// Set by a hashtag link for its children: where its text starts.
+    hashtag: Option<usize>,
+}
+
+/// Walk sanitized `html` for its plain text and, when `extract` is set, its
+/// links and emoji shortcodes.
+fn walk(html: &str, extract: bool) -> Extracted {
+    let mut analysis = Extracted::default();
+
+    // Without markup or entities the text is the HTML itself.
+    if !html.contains(['<', '&']) {
+        analysis.plaintext = html.trim().to_string();
+        if extract {
+            emoji::collect_text_shortcodes(html, &mut analysis.emoji_shortcodes);
+        }
+        return analysis;
+    }
+
+    let fragment = dom::Fragment::parse(html);
+    let text = &mut analysis.plaintext;
+    dom::walk_with_leave(&fragment.root(), Context::default(), |visit| {
+        let (node, context) = match visit {
+            Visit::Enter(node, context) => (node, context),
+            Visit::Leave(node, context) => {
+                if let Some(start) = context.hashtag {
+                    // Stored and looked up as local tags are, by canonical form.
+                    let tag = text.get(start..).unwrap_or_default();
+                    if let Some((canonical, _display)) = hashtag::normalize(tag) {
+                        push_unique(&mut analysis.hashtags, canonical);
+                    }
+                }
+                break_line(text, dom::element_name(node).map_or(0, breaks_after));
+                return Walk::Skip;
+            }
+        };
+
+        match &node.data {
+            NodeData::Text { contents } => {
+                let contents = contents.borrow();
+                // No-break spaces become spaces, as in `decode_html_entities/1`.
+                text.extend(contents.chars().map(|c| if c == '\u{A0}' { ' ' } else { c }));
+                if extract && !context.in_code {
+                    emoji::collect_text_shortcodes(&contents, &mut analysis.emoji_shortcodes);
+                }
+                Walk::Skip
+            }
+            NodeData::Element { name, attrs, .. } => {
+                let name: &str = &name.local;
+                // Blocks also start on a line of their own.
+                if name != "br" {
+                    break_line(text, breaks_after(name));
+                }
+
+                let mut hashtag = None;
+                if extract && name == "a" && !context.in_link {
+                    let attrs = attrs.borrow();
+                    let attr = |key: &str| {
+                        attrs.iter().find(|a| &*a.name.local == key).map(|a| a.value.to_string())
+                    };
+                    let class = attr("class").unwrap_or_default();
+                    let has_class = |c: &str| class.split_whitespace().any(|x| x == c);
+                    if has_class("hashtag") {
+                        hashtag = Some(text.len());
+                    } else if let Some(href) = attr("href") {
+                        if has_class("mention") {
+                            push_unique(&mut analysis.mentions, href);
+                        } else {
+                            push_unique(&mut analysis.urls, href);
+                        }
+                    }
+                }
+
+                Walk::Children(Context {
+                    in_code: context.in_code || emoji::is_code_element(node),
+                    in_link: context.in_link || name == "a",
+                    hashtag,
+                })
+            }
+            _ => Walk::Skip,
+        }
+    });
+
+    analysis.plaintext = analysis.plaintext.trim().to_string();
+    analysis
+}
+
+fn analyze(sanitized_html: String) -> Analysis {
+    let extracted = walk(&sanitized_html, true);
+    Analysis {
+        sanitized_html,
+        plaintext: extracted.plaintext,
+        mentions: extracted.mentions,
+        hashtags: extracted.hashtags,
+        urls: extracted.urls,
+        emoji_shortcodes: extracted.emoji_shortcodes,
+    }
+}
+
+/// The plain text of sanitized `html`, as `analyze_content/1` returns it.
+pub(crate) fn plaintext(html: &str) -> String {
+    walk(html, false).plaintext
+}
+
+/// Sanitize `html` with the federation rules and return
+/// `{sanitized_html, plaintext, mentions, hashtags, urls, emoji_shortcodes}`.
+#[rustler::nif]
+fn analyze_content(env: Env, html: Binary) -> Guarded<Analysis> {
+    let result = stats::track(Nif::AnalyzeContent, html.len(), || {
+        analyze(FEDERATION.clean_lossy(&html))
+    });
+    log::flush(env);
+    result
+}
@@ -0,0 +1,109 @@
+//! Break opportunities in long words, inserted on request.
+//!
+//! A URL or a German compound longer than the screen is wide pushes a
+//! mobile layout sideways, as browsers only wrap lines at spaces and
+//! hyphens.  With `break_long_words: n`, words of more than `n` characters
+//! get a break opportunity every `n` characters: soft hyphens in words of
+//! letters alone, which show as `-` only where the line breaks, and `<wbr>`
+//! in anything else, such as URLs, where a hyphen would read as part of it.
+//! Code is left alone, as a break there would change what is copied.
+//!
+//! Characters are counted as grapheme clusters, so no break lands inside
+//! an emoji sequence or between a letter and its combining marks.
+
+use crate::dom::{self, Walk};
+use crate::emoji::is_code_element;
+use markup5ever_rcdom::{Handle, NodeData};
+use unicode_segmentation::UnicodeSegmentation;
+
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/// Whether a line can already break at `c`, so that a word ends there and
+/// running the pass twice breaks nothing more.
+fn is_break(c: char) -> bool {
+    c.is_whitespace() || c == SOFT_HYPHEN
+}
+
+fn is_letter(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphabetic)
+}
+
+/// The nodes `text` becomes, or `None` if it has no word longer than `max`.
+fn break_text(text: &str, max: usize) -> Option<Vec<Handle>> {
+    let mut nodes = Vec::new();
+    let mut current = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = rest.find(|c: char| !is_break(c)).unwrap_or(rest.len());
+        let end = rest[start..].find(is_break).map_or(rest.len(), |end| start + end);
+        current.push_str(&rest[..start]);
+        let graphemes: Vec<&str> = rest[start..end].graphemes(true).collect();
+        if graphemes.len() <= max {
+            current.push_str(&rest[start..end]);
+        } else {
+            changed = true;
+            let letters = graphemes.iter().all(|g| is_letter(g));
+            for (i, chunk) in graphemes.chunks(max).enumerate() {
+                if i > 0 && letters {
+                    current.push(SOFT_HYPHEN);
+                } else if i > 0 {
+                    nodes.push(dom::new_text(&std::mem::take(&mut current)));
+                    nodes.push(dom::new_element("wbr", &[]));
+                }
+                current.extend(chunk.iter().copied());
+            }
+        }
+        rest = &rest[end..];
+    }
+    if !changed {
+        return None;
+    }
+    if !current.is_empty() {
+        nodes.push(dom::new_text(&current));
+    }
+    Some(nodes)
+}
+
+/// Break the long words of the text children of `node`, returning whether
+/// there were any.
+fn break_children(node: &Handle, max: usize) -> bool {
+    let children = node.children.borrow().clone();
+    let mut rebuilt = Vec::with_capacity(children.len());
+    let mut broken = false;
+    for child in children {
+        if let NodeData::Text { contents } = &child.data {
+            if let Some(nodes) = break_text(&contents.borrow(), max) {
+                rebuilt.extend(nodes);
+                broken = true;
+                continue;
+            }
+        }
+        rebuilt.push(child);
+    }
+    if broken {
+        dom::set_children(node, rebuilt);
+    }
+    broken
+}
+
+/// Whether sanitized `html` can have a word longer than `max` characters.
+pub(crate) fn has_long_word(html: &str, max: usize) -> bool {
+    html.split(|c: char| is_break(c) || c == '<' || c == '>')
+        .any(|word| word.chars().count() > max)
+}
+
+/// Insert break opportunities in the words under `root` longer than `max`
+/// characters, outside `<code>` and `<pre>`, returning whether there were
+/// any.
+pub(crate) fn break_long_words(root: &Handle, max: usize) -> bool {
+    let mut changed = break_children(root, max);
+    dom::walk(root, (), |node, ()| {
+        if !matches!(node.data, NodeData::Element { .. }) || is_code_element(node) {
+            return Walk::Skip;
+        }
+        changed |= break_children(node, max);
+        Walk::Children(())
+    });
+    changed
+}
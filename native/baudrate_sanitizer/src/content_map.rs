@@ -0,0 +1,168 @@
+//! ActivityStreams `contentMap`: a post's content in several languages.
+//!
+//! Each variant is sanitized with the federation rules, as `content` is.
+//! Keys are language tags as the sender wrote them, and are kept only if
+//! they are well-formed BCP 47 (RFC 5646), in its canonical case: `en-US`,
+//! `zh-Hant-TW`, `sr-Latn`.  Some software writes `en_US`, which is read as
+//! `en-US`; the irregular grandfathered tags such as `i-klingon` are not
+//! accepted.
+//!
+//! The primary language is the one with the most text, as translations
+//! added to a post are usually shorter than what was written.  `und` is
+//! only primary when no other language is left.
+
+use crate::analyze;
+use crate::log;
+use crate::stats::{self, Nif, Outcome};
+use crate::FEDERATION;
+use rustler::{Binary, Decoder, Env, MapIterator, NifResult, NifTuple, Term};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use baudrate_nif::guard::Guarded;
+
+/// The variants of a `contentMap`; entries whose key is not a string or
+/// whose value is not a binary (JSON `null`) are skipped.
+struct ContentMap<'a>(Vec<(String, Binary<'a>)>);
+
+impl<'a> Decoder<'a> for ContentMap<'a> {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let entries = MapIterator::new(term).ok_or(rustler::Error::BadArg)?;
+        let entries =
+            entries.filter_map(|(key, value)| Some((key.decode().ok()?, value.decode().ok()?)));
+        Ok(Self(entries.collect()))
+    }
+}
+
+#[derive(NifTuple)]
+struct Cleaned {
+    content_map: HashMap<String, String>,
+    language: Option<String>,
+}
+
+impl Outcome for Cleaned {
+    fn is_error(&self) -> bool {
+        false
+    }
+}
+
+fn is_alpha(subtag: &str) -> bool {
+    subtag.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+fn is_digit(subtag: &str) -> bool {
+    subtag.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn titlecase(subtag: &str) -> String {
+    let (first, rest) = subtag.split_at(1);
+    first.to_ascii_uppercase() + rest
+}
+
+/// `key` as a canonical BCP 47 language tag, or `None` if it is not one.
+pub(crate) fn language_tag(key: &str) -> Option<String> {
+    let lowercase = key.trim().to_ascii_lowercase().replace('_', "-");
+    let subtags: Vec<&str> = lowercase.split('-').collect();
+    let is_subtag = |s: &str| s.len() <= 8 && s.bytes().all(|b| b.is_ascii_alphanumeric());
+    if subtags.iter().any(|s| s.is_empty() || !is_subtag(s)) {
+        return None;
+    }
+
+    let mut tag: Vec<String> = Vec::with_capacity(subtags.len());
+    let mut i = 0;
+    if subtags[0] != "x" {
+        let language = subtags[0];
+        if language.len() < 2 || !is_alpha(language) {
+            return None;
+        }
+        tag.push(language.to_string());
+        i = 1;
+        // Up to three extended language subtags, after a short language.
+        let is_extlang = |s: &&str| s.len() == 3 && is_alpha(s);
+        while language.len() <= 3 && i <= 3 && subtags.get(i).is_some_and(is_extlang) {
+            tag.push(subtags[i].to_string());
+            i += 1;
+        }
+        if let Some(script) = subtags.get(i).filter(|s| s.len() == 4 && is_alpha(s)) {
+            tag.push(titlecase(script));
+            i += 1;
+        }
+        let is_region = |s: &&&str| (s.len() == 2 && is_alpha(s)) || (s.len() == 3 && is_digit(s));
+        if let Some(region) = subtags.get(i).filter(is_region) {
+            tag.push(region.to_ascii_uppercase());
+            i += 1;
+        }
+        let is_variant =
+            |s: &&&str| s.len() >= 5 || (s.len() == 4 && s.as_bytes()[0].is_ascii_digit());
+        while let Some(variant) = subtags.get(i).filter(is_variant) {
+            if tag.iter().any(|t| t == variant) {
+                return None;
+            }
+            tag.push(variant.to_string());
+            i += 1;
+        }
+        let mut singletons = Vec::new();
+        while let Some(&singleton) = subtags.get(i).filter(|s| s.len() == 1 && **s != "x") {
+            if singletons.contains(&singleton) {
+                return None;
+            }
+            singletons.push(singleton);
+            let start = i;
+            i += 1;
+            while subtags.get(i).is_some_and(|s| s.len() >= 2) {
+                i += 1;
+            }
+            if i == start + 1 {
+                return None;
+            }
+            tag.extend(subtags[start..i].iter().map(|s| s.to_string()));
+        }
+    }
+    // A private use part: `x` and at least one subtag, to the end.
+    if subtags.get(i) == Some(&"x") {
+        if i + 1 == subtags.len() {
+            return None;
+        }
+        tag.extend(subtags[i..].iter().map(|s| s.to_string()));
+        i = subtags.len();
+    }
+    (i == subtags.len()).then(|| tag.join("-"))
+}
+
+fn clean(map: &ContentMap) -> Cleaned {
+    let mut content_map = HashMap::new();
+    // Language tag and plain text length, in the order of the map.
+    let mut lengths: Vec<(String, usize)> = Vec::new();
+    for (key, html) in &map.0 {
+        let Some(language) = language_tag(key) else {
+            continue;
+        };
+        if content_map.contains_key(&language) {
+            continue;
+        }
+        let html = FEDERATION.clean_lossy(html);
+        if html.trim().is_empty() {
+            continue;
+        }
+        let length = analyze::plaintext(&html).trim().chars().count();
+        lengths.push((language.clone(), length));
+        content_map.insert(language, html);
+    }
+    let language = lengths
+        .into_iter()
+        .max_by_key(|(language, length)| (language != "und", *length, Reverse(language.clone())))
+        .map(|(language, _)| language);
+    Cleaned { content_map, language }
+}
+
+/// Sanitize every variant of a `contentMap` like `sanitize_federation/1`
+/// and return `{content_map, language}`: the variants under their canonical
+/// language tags, without invalid tags and empty variants, and the primary
+/// language, `nil` if none is left.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn sanitize_content_map<'a>(env: Env<'a>, map: ContentMap<'a>) -> Guarded<Cleaned> {
+    let bytes = map.0.iter().map(|(_, html)| html.len()).sum();
+    let result = stats::track(Nif::SanitizeFederation, bytes, || clean(&map));
+    log::flush(env);
+    result
+}
@@ -0,0 +1,177 @@
+//! Content-warning suggestions from instance-configured topics.
+//!
+//! Each rule names a CW category and lists keywords, matched as whole
+//! words and case-insensitively, and regular expressions for what keywords
+//! cannot express.  Rules are compiled once into a resource: all keywords
+//! of all categories into one Aho-Corasick automaton and all patterns into
+//! one `RegexSet`, so checking a post is two passes over its text however
+//! many rules there are.
+//!
+//! Text in scripts written without spaces (Chinese, Japanese, Thai) has no
+//! word boundaries, so keywords in those scripts match anywhere.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::inline;
+use aho_corasick::AhoCorasick;
+use regex::{RegexSet, RegexSetBuilder};
+use rustler::{Atom, Binary, Decoder, NifResult, Resource, ResourceArc, Term};
+
+mod atoms {
+    rustler::atoms! {
+        category,
+        keywords,
+        patterns,
+        invalid_pattern,
+        rules_too_large,
+    }
+}
+
+/// Compiled size allowed for all patterns together, in bytes.
+const PATTERN_SIZE_LIMIT: usize = 4 * 1024 * 1024;
+
+/// One rule as given: a category with its keywords and patterns.
+struct Rule {
+    category: String,
+    keywords: Vec<String>,
+    patterns: Vec<String>,
+}
+
+impl<'a> Decoder<'a> for Rule {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let get = |key: Atom| term.map_get(key).ok();
+        Ok(Self {
+            category: term.map_get(atoms::category())?.decode()?,
+            keywords: get(atoms::keywords()).map_or(Ok(Vec::new()), Term::decode)?,
+            patterns: get(atoms::patterns()).map_or(Ok(Vec::new()), Term::decode)?,
+        })
+    }
+}
+
+pub(crate) struct CwRules {
+    categories: Vec<String>,
+    keywords: Option<AhoCorasick>,
+    /// The category of each keyword, by automaton pattern index.
+    keyword_categories: Vec<usize>,
+    patterns: RegexSet,
+    /// The category of each pattern, by set index.
+    pattern_categories: Vec<usize>,
+}
+
+#[rustler::resource_impl]
+impl Resource for CwRules {}
+
+/// Characters of scripts written without spaces between words.
+fn is_unspaced(c: char) -> bool {
+    matches!(c,
+        '\u{0E00}'..='\u{0EFF}'      // Thai, Lao
+        | '\u{1000}'..='\u{109F}'    // Myanmar
+        | '\u{1780}'..='\u{17FF}'    // Khmer
+        | '\u{3040}'..='\u{30FF}'    // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}'    // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'    // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}'    // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{3FFFF}') // CJK Extensions B and later
+}
+
+fn is_word_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && !is_unspaced(c)
+}
+
+/// Whether `text[start..end]` is not part of a longer word.
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let first = text[start..end].chars().next();
+    let last = text[start..end].chars().next_back();
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    let joined = |edge: Option<char>, next: Option<char>| {
+        edge.is_some_and(is_word_char) && next.is_some_and(is_word_char)
+    };
+    !joined(first, before) && !joined(last, after)
+}
+
+fn pattern_set(patterns: &[String]) -> Result<RegexSet, regex::Error> {
+    RegexSetBuilder::new(patterns)
+        .case_insensitive(true)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+}
+
+fn compile(rules: Vec<Rule>) -> Result<CwRules, (Atom, String)> {
+    let mut categories = Vec::new();
+    let mut keywords = Vec::new();
+    let mut keyword_categories = Vec::new();
+    let mut patterns = Vec::new();
+    let mut pattern_categories = Vec::new();
+
+    for rule in rules {
+        let index = match categories.iter().position(|c| *c == rule.category) {
+            Some(index) => index,
+            None => {
+                categories.push(rule.category);
+                categories.len() - 1
+            }
+        };
+        for keyword in rule.keywords {
+            let keyword = keyword.trim().to_lowercase();
+            if !keyword.is_empty() {
+                keywords.push(keyword);
+                keyword_categories.push(index);
+            }
+        }
+        for pattern in rule.patterns.into_iter().filter(|p| !p.trim().is_empty()) {
+            // Checked one at a time, so that the error names the pattern.
+            if pattern_set(std::slice::from_ref(&pattern)).is_err() {
+                return Err((atoms::invalid_pattern(), pattern));
+            }
+            patterns.push(pattern);
+            pattern_categories.push(index);
+        }
+    }
+
+    let keywords = if keywords.is_empty() {
+        None
+    } else {
+        let automaton = AhoCorasick::new(&keywords);
+        Some(automaton.map_err(|e| (atoms::rules_too_large(), e.to_string()))?)
+    };
+    // Each pattern fits on its own, but together they may not.
+    let patterns = pattern_set(&patterns).map_err(|e| (atoms::rules_too_large(), e.to_string()))?;
+    Ok(CwRules { categories, keywords, keyword_categories, patterns, pattern_categories })
+}
+
+fn suggest(rules: &CwRules, text: &str) -> Vec<String> {
+    let text = inline::line(text);
+    let mut matched = vec![false; rules.categories.len()];
+
+    if let Some(keywords) = &rules.keywords {
+        let lowercase = text.to_lowercase();
+        for found in keywords.find_overlapping_iter(&lowercase) {
+            let category = rules.keyword_categories[found.pattern().as_usize()];
+            if !matched[category] && is_whole_word(&lowercase, found.start(), found.end()) {
+                matched[category] = true;
+            }
+        }
+    }
+    for index in rules.patterns.matches(&text).iter() {
+        matched[rules.pattern_categories[index]] = true;
+    }
+
+    let categories = rules.categories.iter().zip(matched);
+    categories.filter(|(_, matched)| *matched).map(|(category, _)| category.clone()).collect()
+}
+
+/// Compile CW `rules`, maps of `category` with `keywords` and `patterns`,
+/// for `suggest_cw/2`.  Returns `{:error, {:invalid_pattern, pattern}}` for
+/// a pattern that is not a valid regular expression or is too large, and
+/// `{:error, {:rules_too_large, message}}` if together they are too large.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn compile_cw_rules(rules: Vec<Rule>) -> Guarded<Result<ResourceArc<CwRules>, (Atom, String)>> {
+    guard(|| compile(rules).map(ResourceArc::new))
+}
+
+/// The categories of `rules` that match `text`, HTML or plain, in the order
+/// they were first given.
+#[rustler::nif]
+fn suggest_cw(rules: ResourceArc<CwRules>, text: Binary) -> Guarded<Vec<String>> {
+    guard(|| suggest(&rules, &String::from_utf8_lossy(&text)))
+}
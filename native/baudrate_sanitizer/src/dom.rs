@@ -0,0 +1,179 @@
+//! Helpers for DOM-aware post-processing of already-sanitized HTML.
+//!
+//! Fragments are parsed with the same rules Ammonia uses (HTML5 fragment
+//! parsing in a `<div>` context) into an `rcdom` tree, so serializing an
+//! unmodified tree reproduces the sanitizer's own output.
+
+use markup5ever_rcdom::{Handle, Node, NodeData, RcDom, SerializableHandle};
+use html5ever::serialize::{serialize, SerializeOpts};
+use html5ever::tendril::TendrilSink;
+use html5ever::{local_name, ns, parse_fragment, Attribute, LocalName, ParseOpts, QualName};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A parsed HTML fragment.
+///
+/// The `RcDom` must outlive every handle taken from it: dropping the
+/// document tears down the whole tree, even nodes still referenced elsewhere.
+pub(crate) struct Fragment {
+    dom: RcDom,
+}
+
+impl Fragment {
+    pub(crate) fn parse(html: &str) -> Self {
+        let dom = parse_fragment(
+            RcDom::default(),
+            ParseOpts::default(),
+            QualName::new(None, ns!(html), local_name!("div")),
+            vec![],
+            false,
+        )
+        .one(html);
+
+        Fragment { dom }
+    }
+
+    /// The synthetic root element whose children are the fragment's
+    /// top-level nodes.
+    pub(crate) fn root(&self) -> Handle {
+        self.dom.document.children.borrow()[0].clone()
+    }
+
+    /// The errors the parser recovered from, in order.
+    pub(crate) fn errors(&self) -> Vec<String> {
+        self.dom.errors.borrow().iter().map(|error| error.to_string()).collect()
+    }
+
+    /// Serialize the fragment back to HTML.
+    pub(crate) fn serialize(&self) -> String {
+        let mut buf = Vec::new();
+        let handle: SerializableHandle = self.root().into();
+        serialize(&mut buf, &handle, SerializeOpts::default())
+            .expect("serializing to a Vec cannot fail");
+        String::from_utf8(buf).expect("html5ever always serializes valid UTF-8")
+    }
+}
+
+/// Local name of an element node, or `None` for non-element nodes.
+pub(crate) fn element_name(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&name.local),
+        _ => None,
+    }
+}
+
+pub(crate) fn new_element(name: &str, attrs: &[(&str, &str)]) -> Handle {
+    let attrs = attrs
+        .iter()
+        .map(|(k, v)| Attribute {
+            name: QualName::new(None, ns!(), LocalName::from(*k)),
+            value: (*v).into(),
+        })
+        .collect();
+
+    Node::new(NodeData::Element {
+        name: QualName::new(None, ns!(html), LocalName::from(name)),
+        attrs: RefCell::new(attrs),
+        template_contents: RefCell::new(None),
+        mathml_annotation_xml_integration_point: false,
+    })
+}
+
+pub(crate) fn new_text(text: &str) -> Handle {
+    Node::new(NodeData::Text {
+        contents: RefCell::new(text.into()),
+    })
+}
+
+/// Replace all children of `parent`, fixing up the parent back-references.
+pub(crate) fn set_children(parent: &Handle, children: Vec<Handle>) {
+    for child in &children {
+        child.parent.set(Some(Rc::downgrade(parent)));
+    }
+    *parent.children.borrow_mut() = children;
+}
+
+/// What [`walk`] does once a node is entered.
+pub(crate) enum Walk<S> {
+    /// Walk its children, each entered with this state.
+    Children(S),
+    /// Go on with its next sibling.
+    Skip,
+    /// End the walk.
+    Stop,
+}
+
+/// A node reached by [`walk_with_leave`].
+pub(crate) enum Visit<'a, S> {
+    /// Entered, with the state its parent gave its children.
+    Enter(&'a Handle, S),
+    /// Left after its children, with the state it gave them; only nodes
+    /// whose children were walked are left.
+    Leave(&'a Handle, S),
+}
+
+/// Walk the descendants of `root`, depth first and in document order,
+/// calling `enter` on each with the state its parent passed down.
+///
+/// The children of a node are read once `enter` returns, so it may replace
+/// them.  Iterative, so deeply nested input cannot exhaust the NIF stack.
+pub(crate) fn walk<S: Clone>(
+    root: &Handle,
+    state: S,
+    mut enter: impl FnMut(&Handle, S) -> Walk<S>,
+) {
+    traverse(root, state, false, |visit| match visit {
+        Visit::Enter(node, state) => enter(node, state),
+        Visit::Leave(..) => Walk::Skip,
+    });
+}
+
+/// Like [`walk`], also calling `visit` to leave each node whose children
+/// were walked.
+pub(crate) fn walk_with_leave<S: Clone>(
+    root: &Handle,
+    state: S,
+    visit: impl FnMut(Visit<'_, S>) -> Walk<S>,
+) {
+    traverse(root, state, true, visit);
+}
+
+fn traverse<S: Clone>(
+    root: &Handle,
+    state: S,
+    leave: bool,
+    mut visit: impl FnMut(Visit<'_, S>) -> Walk<S>,
+) {
+    enum Pending<S> {
+        Enter(Handle, S),
+        Leave(Handle, S),
+    }
+    let push_children = |stack: &mut Vec<Pending<S>>, node: &Handle, state: &S| {
+        let children = node.children.borrow();
+        let enter = |child: &Handle| Pending::Enter(child.clone(), state.clone());
+        stack.extend(children.iter().rev().map(enter));
+    };
+
+    let mut stack = Vec::new();
+    push_children(&mut stack, root, &state);
+    while let Some(pending) = stack.pop() {
+        match pending {
+            Pending::Enter(node, state) => match visit(Visit::Enter(&node, state)) {
+                Walk::Children(state) => {
+                    // Under the children, so that it is popped after them.
+                    if leave {
+                        stack.push(Pending::Leave(node.clone(), state.clone()));
+                    }
+                    push_children(&mut stack, &node, &state);
+                }
+                Walk::Skip => {}
+                Walk::Stop => return,
+            },
+            Pending::Leave(node, state) => {
+                if let Walk::Stop = visit(Visit::Leave(&node, state)) {
+                    return;
+                }
+            }
+        }
+    }
+}
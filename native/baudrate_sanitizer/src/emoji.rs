@@ -0,0 +1,214 @@
+use baudrate_nif::guard::Guarded;
+use crate::stats::{self, Nif};
+use crate::dom::{self, Walk};
+use crate::CUSTOM_EMOJI_CLASS;
+use markup5ever_rcdom::{Handle, NodeData};
+use std::collections::HashMap;
+
+fn is_shortcode_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Byte ranges of `:shortcode:` tokens in `text` (Mastodon's shortcode
+/// syntax: two or more of `[A-Za-z0-9_]` between colons).  A token must not
+/// be glued to surrounding alphanumerics, so `12:30:45` and `a:bc:d` never
+/// match.  Returned ranges include both colons.
+pub(crate) fn shortcode_ranges(text: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b':' || (i > 0 && bytes[i - 1].is_ascii_alphanumeric()) {
+            i += 1;
+            continue;
+        }
+
+        let body_end = (i + 1..bytes.len())
+            .find(|&j| !is_shortcode_byte(bytes[j]))
+            .unwrap_or(bytes.len());
+
+        let closed = body_end < bytes.len() && bytes[body_end] == b':';
+        let long_enough = body_end - (i + 1) >= 2;
+        let detached = bytes
+            .get(body_end + 1)
+            .is_none_or(|b| !b.is_ascii_alphanumeric());
+
+        if closed && long_enough && detached {
+            ranges.push((i, body_end + 1));
+            // The closing colon may open the next token (":a::b:" is two).
+            i = body_end + 1;
+        } else {
+            i += 1;
+        }
+    }
+
+    ranges
+}
+
+/// Remove Markdown code spans and fences (backtick runs closed by a run of
+/// the same length) so shortcodes in raw composer text inside code are
+/// ignored, mirroring `Markdown.extract_mentions/1`.
+fn strip_backtick_code(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find('`') {
+        out.push_str(&rest[..open]);
+        let ticks = rest[open..].bytes().take_while(|&b| b == b'`').count();
+        let after = &rest[open + ticks..];
+        let fence = &rest[open..open + ticks];
+
+        let close = after.match_indices(fence).find(|(i, _)| {
+            after.as_bytes().get(i + ticks) != Some(&b'`')
+                && (*i == 0 || after.as_bytes()[i - 1] != b'`')
+        });
+
+        match close {
+            Some((i, _)) => rest = &after[i + ticks..],
+            None => {
+                // Unterminated — the backticks are literal text.
+                out.push_str(fence);
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Add the shortcodes in one text node to `found`, skipping duplicates.
+pub(crate) fn collect_text_shortcodes(text: &str, found: &mut Vec<String>) {
+    let text = strip_backtick_code(text);
+    for (start, end) in shortcode_ranges(&text) {
+        let code = &text[start + 1..end - 1];
+        if !found.iter().any(|c| c == code) {
+            found.push(code.to_string());
+        }
+    }
+}
+
+fn collect_shortcodes(root: &Handle, found: &mut Vec<String>) {
+    dom::walk(root, (), |node, ()| match &node.data {
+        NodeData::Text { contents } => {
+            collect_text_shortcodes(&contents.borrow(), found);
+            Walk::Skip
+        }
+        NodeData::Element { .. } if !is_code_element(node) => Walk::Children(()),
+        _ => Walk::Skip,
+    });
+}
+
+pub(crate) fn is_code_element(node: &Handle) -> bool {
+    matches!(dom::element_name(node), Some("code" | "pre"))
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+fn emojify_text(text: &str, emoji: &HashMap<String, String>) -> Option<Vec<Handle>> {
+    let mut nodes = Vec::new();
+    let mut copied = 0;
+
+    for (start, end) in shortcode_ranges(text) {
+        let token = &text[start..end];
+        let Some(url) = emoji.get(&token[1..token.len() - 1]) else {
+            continue;
+        };
+        if !is_http_url(url) {
+            continue;
+        }
+
+        if start > copied {
+            nodes.push(dom::new_text(&text[copied..start]));
+        }
+        nodes.push(dom::new_element(
+            "img",
+            &[
+                ("class", CUSTOM_EMOJI_CLASS),
+                ("src", url),
+                ("alt", token),
+                ("title", token),
+            ],
+        ));
+        copied = end;
+    }
+
+    if nodes.is_empty() {
+        return None;
+    }
+    if copied < text.len() {
+        nodes.push(dom::new_text(&text[copied..]));
+    }
+    Some(nodes)
+}
+
+/// Replace the shortcodes in the text children of `node`.
+fn emojify_children(node: &Handle, emoji: &HashMap<String, String>) {
+    let children = node.children.borrow().clone();
+    let mut changed = false;
+    let mut rebuilt = Vec::with_capacity(children.len());
+
+    for child in children {
+        if let NodeData::Text { contents } = &child.data {
+            if let Some(nodes) = emojify_text(&contents.borrow(), emoji) {
+                rebuilt.extend(nodes);
+                changed = true;
+                continue;
+            }
+        }
+        rebuilt.push(child);
+    }
+
+    if changed {
+        dom::set_children(node, rebuilt);
+    }
+}
+
+fn emojify_node(root: &Handle, emoji: &HashMap<String, String>) {
+    emojify_children(root, emoji);
+    dom::walk(root, (), |node, ()| {
+        if !matches!(node.data, NodeData::Element { .. }) || is_code_element(node) {
+            return Walk::Skip;
+        }
+        emojify_children(node, emoji);
+        Walk::Children(())
+    });
+}
+
+/// Replace `:shortcode:` tokens in the text nodes of already-sanitized `html`
+/// with `<img class="custom-emoji">` elements, skipping `<code>` / `<pre>`
+/// content.  Shortcodes missing from `emoji`, or mapped to a non-HTTP(S) URL,
+/// are left as text.
+#[rustler::nif]
+fn emojify(html: &str, emoji: HashMap<String, String>) -> Guarded<String> {
+    stats::track(Nif::Emojify, html.len(), || {
+        if emoji.is_empty() || !html.contains(':') {
+            return html.to_string();
+        }
+
+        let fragment = dom::Fragment::parse(html);
+        emojify_node(&fragment.root(), &emoji);
+        fragment.serialize()
+    })
+}
+
+/// Return the distinct `:shortcode:` names (without colons, in order of first
+/// appearance) used in `text` — either sanitized HTML or raw composer text.
+/// Shortcodes inside `<code>` / `<pre>` elements and Markdown backtick code
+/// are ignored.
+#[rustler::nif]
+fn extract_shortcodes(text: &str) -> Guarded<Vec<String>> {
+    stats::track(Nif::ExtractShortcodes, text.len(), || {
+        let mut found = Vec::new();
+        if !text.contains(':') {
+            return found;
+        }
+
+        let fragment = dom::Fragment::parse(text);
+        collect_shortcodes(&fragment.root(), &mut found);
+        found
+    })
+}
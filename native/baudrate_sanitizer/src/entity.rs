@@ -0,0 +1,112 @@
+//! Character references decoded and text escaped outside a document.
+//!
+//! Plain-text conversion, feed building, and Gopher output handle text
+//! that is not parsed as HTML but may carry entities.  Decoding follows the
+//! HTML tokenizer in text content, with html5ever's own tables: the longest
+//! named reference wins, legacy names such as `&amp` and `&copy` decode
+//! without their `;`, and numeric references out of range, to surrogates,
+//! or to NUL become U+FFFD, with C1 controls mapped as Windows-1252.
+
+use baudrate_nif::guard::{guard, Guarded};
+use html5ever::data::{C1_REPLACEMENTS, NAMED_ENTITIES};
+use rustler::Binary;
+use std::borrow::Cow;
+
+/// The character referenced by `&#` + `digits`, as the tokenizer maps it.
+fn numeric(value: Option<u32>) -> char {
+    match value {
+        Some(n @ 0x80..=0x9F) => {
+            C1_REPLACEMENTS[(n - 0x80) as usize].unwrap_or_else(|| char::from_u32(n).unwrap())
+        }
+        Some(0) | None => '\u{FFFD}',
+        Some(n) => char::from_u32(n).unwrap_or('\u{FFFD}'),
+    }
+}
+
+/// Decode the reference at the start of `rest`, just after its `&`, into
+/// `out`, returning the bytes it took, or 0 if `rest` starts none.
+fn reference(rest: &str, out: &mut String) -> usize {
+    if let Some(number) = rest.strip_prefix('#') {
+        let (radix, digits_at) = match number.as_bytes().first() {
+            Some(b'x' | b'X') => (16, 2),
+            _ => (10, 1),
+        };
+        let digits = &rest[digits_at..];
+        let len = digits.find(|c: char| !c.is_digit(radix)).unwrap_or(digits.len());
+        if len == 0 {
+            return 0;
+        }
+        // Too many digits for a `u32` is out of range all the same.
+        out.push(numeric(u32::from_str_radix(&digits[..len], radix).ok()));
+        let semicolon = usize::from(digits[len..].starts_with(';'));
+        return digits_at + len + semicolon;
+    }
+
+    // Every prefix of a name is in the table, mapped to `(0, 0)`.
+    let mut found = None;
+    for (end, c) in rest.char_indices().map(|(i, c)| (i + c.len_utf8(), c)) {
+        if !c.is_ascii_alphanumeric() && c != ';' {
+            break;
+        }
+        match NAMED_ENTITIES.get(&rest[..end]) {
+            Some(&(0, 0)) => {}
+            Some(&codes) => found = Some((end, codes)),
+            None => break,
+        }
+        if c == ';' {
+            break;
+        }
+    }
+    let Some((end, (first, second))) = found else {
+        return 0;
+    };
+    out.extend([first, second].into_iter().filter(|&c| c != 0).filter_map(char::from_u32));
+    end
+}
+
+pub(crate) fn decode(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        let taken = reference(&rest[at + 1..], &mut out);
+        if taken == 0 {
+            out.push('&');
+        }
+        rest = &rest[at + 1 + taken..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Decode the character references in `text` as the HTML tokenizer does in
+/// text content.  Tags are left as they are.
+#[rustler::nif]
+fn decode_entities(text: Binary) -> Guarded<String> {
+    guard(|| decode(&String::from_utf8_lossy(&text)).into_owned())
+}
+
+/// Escape `text` for HTML text and double- or single-quoted attribute
+/// values, as Phoenix does.
+#[rustler::nif]
+fn escape_html(text: Binary) -> Guarded<String> {
+    guard(|| escape(&String::from_utf8_lossy(&text)))
+}
@@ -0,0 +1,90 @@
+//! Greentext: lines starting with `>`, styled as imageboards show them.
+//!
+//! Pleroma and Akkoma send such lines as `<span class="greentext">`, which
+//! the federation policy keeps.  Other software sends the `>` as plain
+//! text, and with `greentext: true` those lines are wrapped the same way
+//! after sanitizing.  A line runs from the start of a block or a `<br>` to
+//! the next `<br>` or block; the text of `<code>`, `<pre>`, and links is
+//! left as it is.
+
+use crate::dom::{self, element_name, Walk};
+use crate::emoji::is_code_element;
+use markup5ever_rcdom::{Handle, NodeData};
+
+pub(crate) const GREENTEXT_CLASS: &str = "greentext";
+
+/// Elements that start and end lines; `html` is the fragment root.
+const BLOCKS: &[&str] = &[
+    "html", "p", "div", "blockquote", "pre", "ul", "ol", "li", "h1", "h2", "h3", "h4", "h5", "h6",
+    "hr", "table", "thead", "tbody", "tr", "td", "th", "details", "summary", "figure", "figcaption",
+    "dl", "dt", "dd",
+];
+
+fn is_block(node: &Handle) -> bool {
+    element_name(node).is_some_and(|name| BLOCKS.contains(&name))
+}
+
+/// Whether the nodes of a line make a greentext line not yet wrapped.
+fn starts_greentext(line: &[Handle]) -> bool {
+    let first = line.iter().find(|node| match &node.data {
+        NodeData::Text { contents } => !contents.borrow().trim().is_empty(),
+        _ => true,
+    });
+    first.is_some_and(|node| match &node.data {
+        NodeData::Text { contents } => contents.borrow().trim_start().starts_with('>'),
+        _ => false,
+    })
+}
+
+/// Wrap the greentext lines among the children of `node`, returning whether
+/// any were.
+fn wrap_lines(node: &Handle) -> bool {
+    let children = node.children.borrow().clone();
+    let mut rebuilt = Vec::with_capacity(children.len());
+    let mut line: Vec<Handle> = Vec::new();
+    let mut changed = false;
+    let mut end_line = |line: &mut Vec<Handle>, rebuilt: &mut Vec<Handle>| {
+        if starts_greentext(line) {
+            let span = dom::new_element("span", &[("class", GREENTEXT_CLASS)]);
+            dom::set_children(&span, std::mem::take(line));
+            rebuilt.push(span);
+            changed = true;
+        } else {
+            rebuilt.append(line);
+        }
+    };
+    for child in children {
+        if element_name(&child) == Some("br") || is_block(&child) {
+            end_line(&mut line, &mut rebuilt);
+            rebuilt.push(child);
+        } else {
+            line.push(child);
+        }
+    }
+    end_line(&mut line, &mut rebuilt);
+    if changed {
+        dom::set_children(node, rebuilt);
+    }
+    changed
+}
+
+/// Whether sanitized `html` can have a greentext line.
+pub(crate) fn has_greentext(html: &str) -> bool {
+    html.contains("&gt;")
+}
+
+/// Wrap the lines under `root` starting with `>` in
+/// `<span class="greentext">`, returning whether there were any.
+pub(crate) fn wrap_greentext(root: &Handle) -> bool {
+    let mut changed = wrap_lines(root);
+    // Lines belong to blocks; the text of a link starting with `>` is not
+    // one.
+    dom::walk(root, (), |node, ()| {
+        if !is_block(node) || is_code_element(node) {
+            return Walk::Skip;
+        }
+        changed |= wrap_lines(node);
+        Walk::Children(())
+    });
+    changed
+}
@@ -0,0 +1,100 @@
+//! Tracking pixels and hidden elements, dropped on request.
+//!
+//! Newsletters bridged from RSS or email carry 1×1 images that report
+//! each view, and blocks hidden with `display:none` (preheaders, tracking
+//! beacons, text meant only for spam filters).  The allowlist removes the
+//! `style` and `hidden` attributes but keeps the elements, so hidden text
+//! would show.  With `drop_hidden: true` such elements are removed together
+//! with their content before the allowlist runs.
+
+use crate::dom::{self, element_name, Walk};
+use markup5ever_rcdom::{Handle, NodeData};
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+static HIDING_RE: OnceLock<Regex> = OnceLock::new();
+
+// A quick test for whether anything in the document can hide an element.
+fn hiding_regex() -> &'static Regex {
+    HIDING_RE.get_or_init(|| {
+        Regex::new(r"(?i)hidden|display|visibility|opacity|font-size|width|height").unwrap()
+    })
+}
+
+/// Whether a length is at most one pixel: `0`, `1`, `1px`, `0.5px`.
+fn is_tiny(length: &str) -> bool {
+    let length = length.trim();
+    let number = length.strip_suffix("px").unwrap_or(length).trim();
+    number.parse::<f32>().is_ok_and(|n| (0.0..=1.0).contains(&n))
+}
+
+/// Whether a length or number is zero, in any unit: `0`, `0px`, `0.0em`.
+fn is_zero(value: &str) -> bool {
+    let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+    number.parse::<f32>().is_ok_and(|n| n == 0.0)
+}
+
+/// The `property: value` pairs of a `style` attribute, lowercased.
+fn declarations(style: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    style.split(';').filter_map(|declaration| {
+        let (property, value) = declaration.split_once(':')?;
+        let value = value.trim().trim_end_matches("!important").trim();
+        Some((property.trim().to_ascii_lowercase(), value.to_ascii_lowercase()))
+    })
+}
+
+fn is_hidden(node: &Handle) -> bool {
+    let NodeData::Element { attrs, .. } = &node.data else {
+        return false;
+    };
+    let is_image = element_name(node) == Some("img");
+    let attrs = attrs.borrow();
+    attrs.iter().any(|attr| {
+        let value = &*attr.value;
+        match &*attr.name.local {
+            // `until-found` content is revealed by find-in-page.
+            "hidden" => !value.eq_ignore_ascii_case("until-found"),
+            "width" | "height" => is_image && is_tiny(value),
+            "style" => declarations(value).any(|(property, value)| match property.as_str() {
+                "display" => value == "none",
+                "visibility" => value == "hidden" || value == "collapse",
+                "opacity" | "font-size" => is_zero(&value),
+                "width" | "height" => is_image && is_tiny(&value),
+                _ => false,
+            }),
+            _ => false,
+        }
+    })
+}
+
+/// Remove the hidden children of `node`, returning whether there were any.
+fn drop_hidden_children(node: &Handle) -> bool {
+    let children = node.children.borrow().clone();
+    let kept: Vec<Handle> = children.iter().filter(|child| !is_hidden(child)).cloned().collect();
+    let changed = kept.len() != children.len();
+    if changed {
+        dom::set_children(node, kept);
+    }
+    changed
+}
+
+/// `html` without its tracking images and hidden elements, or unchanged if
+/// nothing in it can hide an element.
+pub(crate) fn drop_hidden(html: &str) -> Cow<'_, str> {
+    if !hiding_regex().is_match(html) {
+        return Cow::Borrowed(html);
+    }
+    let fragment = dom::Fragment::parse(html);
+    let root = fragment.root();
+    let mut changed = drop_hidden_children(&root);
+    dom::walk(&root, (), |node, ()| {
+        changed |= drop_hidden_children(node);
+        Walk::Children(())
+    });
+    if changed {
+        Cow::Owned(fragment.serialize())
+    } else {
+        Cow::Borrowed(html)
+    }
+}
@@ -0,0 +1,72 @@
+//! `http://` links and images upgraded for hosts known to serve HTTPS.
+//!
+//! Remote posts still link `http://` URLs of hosts that redirect to HTTPS,
+//! and their images break as mixed content once proxied or previewed from
+//! a page served over HTTPS.  Given the caller's set of such hosts, in the
+//! spirit of an HSTS list, the `href` of links and the `src` of images on
+//! those hosts are rewritten to `https://` in sanitized HTML.
+//!
+//! An entry matches its host exactly, and `*.example.com` every subdomain
+//! of `example.com`.  URLs with a port other than 80 are left alone, as the
+//! HTTPS server would listen elsewhere.
+
+use regex::{Captures, Regex};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+static HTTP_URL_RE: OnceLock<Regex> = OnceLock::new();
+
+// Sanitized HTML quotes every attribute value and escapes `"` inside them,
+// so `href="` can only start an attribute.
+fn http_url_regex() -> &'static Regex {
+    HTTP_URL_RE.get_or_init(|| {
+        Regex::new(r#"(<(?:a|img)\s[^>]*?\b(?:href|src)=")http://([^/"?#]*)"#).unwrap()
+    })
+}
+
+/// The hosts to upgrade, lowercased.
+#[derive(Default)]
+pub(crate) struct HttpsHosts(HashSet<String>);
+
+impl HttpsHosts {
+    pub(crate) fn new(hosts: Vec<String>) -> Self {
+        Self(hosts.into_iter().map(|host| host.trim().to_ascii_lowercase()).collect())
+    }
+
+    fn contains(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        if self.0.contains(&host) {
+            return true;
+        }
+        // `a.b.example.com` is matched by `*.b.example.com`, `*.example.com`,
+        // and `*.com`.
+        let mut rest = host.as_str();
+        while let Some((_, parent)) = rest.split_once('.') {
+            if self.0.contains(&format!("*.{parent}")) {
+                return true;
+            }
+            rest = parent;
+        }
+        false
+    }
+}
+
+/// `html` with the `http://` URLs of links and images on `hosts` upgraded.
+pub(crate) fn upgrade(html: String, hosts: &HttpsHosts) -> String {
+    if hosts.0.is_empty() || !html.contains("http://") {
+        return html;
+    }
+    let upgraded = http_url_regex().replace_all(&html, |caps: &Captures| {
+        let authority = &caps[2];
+        let host = match authority.rsplit_once(':') {
+            Some((host, "80")) => host,
+            Some(_) => return caps[0].to_string(),
+            None => authority,
+        };
+        if authority.contains('@') || !hosts.contains(host) {
+            return caps[0].to_string();
+        }
+        format!("{}https://{host}", &caps[1])
+    });
+    upgraded.into_owned()
+}
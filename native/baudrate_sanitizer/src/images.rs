@@ -0,0 +1,79 @@
+//! Images beyond a limit, replaced by links on request.
+//!
+//! Some remote articles embed hundreds of inline images, and a timeline
+//! showing them all crawls.  With `max_images: n` the first `n` images are
+//! kept and each one after becomes a link to its `src`, labeled with its alt
+//! text, before the allowlist runs; the caller is told how many there were.
+//! An image already inside a link becomes its label alone, and one without
+//! a `src` is dropped.  Custom emoji are not media and do not count.
+
+use crate::dom::{self, element_name, Walk};
+use crate::CUSTOM_EMOJI_CLASS;
+use markup5ever_rcdom::{Handle, NodeData};
+use std::borrow::Cow;
+use std::rc::Rc;
+
+fn attribute(node: &Handle, name: &str) -> Option<String> {
+    let NodeData::Element { attrs, .. } = &node.data else {
+        return None;
+    };
+    let attrs = attrs.borrow();
+    attrs.iter().find(|attr| &*attr.name.local == name).map(|attr| attr.value.to_string())
+}
+
+fn is_media(node: &Handle) -> bool {
+    element_name(node) == Some("img")
+        && !attribute(node, "class")
+            .is_some_and(|class| class.split_whitespace().any(|c| c == CUSTOM_EMOJI_CLASS))
+}
+
+/// What an image past the limit becomes: a link to its `src`, or its label
+/// alone if it is `linked` already; nothing if it has no `src`.
+fn replacement(image: &Handle, linked: bool) -> Option<Handle> {
+    let src = attribute(image, "src").filter(|src| !src.trim().is_empty())?;
+    let alt = attribute(image, "alt").unwrap_or_default();
+    let label = dom::new_text(if alt.trim().is_empty() { src.trim() } else { alt.trim() });
+    if linked {
+        return Some(label);
+    }
+    let link = dom::new_element("a", &[("href", src.trim())]);
+    dom::set_children(&link, vec![label]);
+    Some(link)
+}
+
+/// `html` with the images after the first `max` replaced, and how many
+/// were; unchanged if it has no more than `max`.
+pub(crate) fn limit_images(html: &str, max: usize) -> (Cow<'_, str>, usize) {
+    if html.to_ascii_lowercase().matches("<img").count() <= max {
+        return (Cow::Borrowed(html), 0);
+    }
+    let fragment = dom::Fragment::parse(html);
+    // Each image with its parent and whether it is inside a link.
+    let mut images: Vec<(Handle, Handle, bool)> = Vec::new();
+    let root = fragment.root();
+    dom::walk(&root, (root.clone(), false), |node, (parent, linked)| {
+        if is_media(node) {
+            images.push((node.clone(), parent, linked));
+        }
+        Walk::Children((node.clone(), linked || element_name(node) == Some("a")))
+    });
+    if images.len() <= max {
+        return (Cow::Borrowed(html), 0);
+    }
+    for (image, parent, linked) in &images[max..] {
+        let mut children = parent.children.borrow_mut();
+        let Some(index) = children.iter().position(|child| Rc::ptr_eq(child, image)) else {
+            continue;
+        };
+        match replacement(image, *linked) {
+            Some(replacement) => {
+                replacement.parent.set(Some(Rc::downgrade(parent)));
+                children[index] = replacement;
+            }
+            None => {
+                children.remove(index);
+            }
+        }
+    }
+    (Cow::Owned(fragment.serialize()), images.len() - max)
+}
@@ -0,0 +1,84 @@
+//! Single-line plain text for short fields: poll options, content warnings,
+//! and profile field names.
+//!
+//! These are shown as text, never as HTML, so every tag goes and entities
+//! are decoded rather than kept.  `:shortcode:` tokens are plain text and
+//! survive for emoji rendering.  Line breaks and other whitespace become
+//! single spaces; control characters and bidi overrides, which could only
+//! garble the line or disguise its text, are removed.
+
+use crate::dom::{self, Visit, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use markup5ever_rcdom::NodeData;
+use rustler::Binary;
+use unicode_segmentation::UnicodeSegmentation;
+
+fn is_removed(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Whether the content of an element is text, unlike a script's.
+fn holds_text(name: &str) -> bool {
+    !matches!(name, "script" | "style" | "template")
+}
+
+/// Whether an element separates the text before, in, and after it.
+fn separates(name: &str) -> bool {
+    !matches!(
+        name,
+        "a" | "abbr" | "b" | "bdi" | "big" | "cite" | "code" | "data" | "del" | "dfn" | "em"
+            | "font" | "i" | "img" | "ins" | "kbd" | "mark" | "q" | "s" | "samp" | "small"
+            | "span" | "strong" | "sub" | "sup" | "time" | "u" | "var" | "wbr"
+    )
+}
+
+/// The text of an HTML fragment, with elements that separate text (blocks,
+/// `br`, cells) replaced by a space.
+fn text(html: &str) -> String {
+    let fragment = dom::Fragment::parse(html);
+    let mut out = String::with_capacity(html.len());
+    dom::walk_with_leave(&fragment.root(), (), |visit| {
+        match visit {
+            Visit::Enter(node, ()) => match &node.data {
+                NodeData::Text { contents } => out.push_str(&contents.borrow()),
+                NodeData::Element { name, .. } if holds_text(&name.local) => {
+                    if separates(&name.local) {
+                        out.push(' ');
+                    }
+                    return Walk::Children(());
+                }
+                _ => {}
+            },
+            Visit::Leave(node, ()) => {
+                if dom::element_name(node).is_some_and(separates) {
+                    out.push(' ');
+                }
+            }
+        }
+        Walk::Skip
+    });
+    out
+}
+
+/// `input` as one line of plain text, of any length.
+pub(crate) fn line(input: &str) -> String {
+    let text = if input.contains(['<', '&']) { text(input) } else { input.to_string() };
+    let text: String = text.chars().filter(|&c| c.is_whitespace() || !is_removed(c)).collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn sanitize(input: &str, max_graphemes: usize) -> String {
+    let line = line(input);
+    match line.grapheme_indices(true).nth(max_graphemes) {
+        Some((cut, _)) => line[..cut].trim_end().to_string(),
+        None => line,
+    }
+}
+
+/// Reduce `text` to one line of plain text of at most `max_graphemes`
+/// graphemes: tags stripped, entities decoded, whitespace collapsed, and
+/// control characters removed.
+#[rustler::nif]
+fn sanitize_inline(text: Binary, max_graphemes: usize) -> Guarded<String> {
+    guard(|| sanitize(&String::from_utf8_lossy(&text), max_graphemes))
+}
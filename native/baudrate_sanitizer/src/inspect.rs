@@ -0,0 +1,102 @@
+//! Structural diagnostics for moderators.
+//!
+//! Some remote servers send HTML that sanitizes fine but is bizarre on the
+//! way in: hundreds of unclosed tags, attributes megabytes long, Office or
+//! SVG namespaces.  `inspect_html/1` reports the shape of such a document
+//! as the parser builds it, without changing anything, so that moderation
+//! tools can flag the sending server.
+
+use crate::dom::{self, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use html5ever::ns;
+use markup5ever_rcdom::NodeData;
+use rustler::{Binary, NifMap};
+use std::collections::HashMap;
+
+/// Most distinct parse error messages reported.
+const MAX_ERROR_MESSAGES: usize = 10;
+
+#[derive(NifMap, Default)]
+struct Report {
+    bytes: usize,
+    /// Elements by local name, with any prefix (`o:p`).
+    elements: HashMap<String, usize>,
+    element_count: usize,
+    /// Deepest element nesting; top-level elements are at depth 1.
+    max_depth: usize,
+    attribute_count: usize,
+    /// Most attributes on one element.
+    max_attributes: usize,
+    /// The longest attribute value, in bytes.
+    max_attribute_bytes: usize,
+    total_attribute_bytes: usize,
+    comments: usize,
+    /// Elements in the SVG or MathML namespace.
+    foreign_elements: usize,
+    /// Element and attribute names with a namespace prefix, such as Word's
+    /// `o:p` or `xmlns:v`.
+    prefixed_names: usize,
+    /// Errors the parser recovered from: unclosed or misnested tags, stray
+    /// end tags, bad character references.
+    parse_errors: usize,
+    parse_error_messages: Vec<String>,
+}
+
+fn inspect(html: &str) -> Report {
+    let mut report = Report { bytes: html.len(), ..Report::default() };
+    if !html.contains('<') && !html.contains('&') {
+        return report;
+    }
+
+    let fragment = dom::Fragment::parse(html);
+    dom::walk(&fragment.root(), 1, |node, depth| {
+        match &node.data {
+            NodeData::Element { name, attrs, .. } => {
+                let local = &*name.local;
+                let prefixed = local.contains(':');
+                *report.elements.entry(local.to_string()).or_default() += 1;
+                report.element_count += 1;
+                report.max_depth = report.max_depth.max(depth);
+                if name.ns == ns!(svg) || name.ns == ns!(mathml) {
+                    report.foreign_elements += 1;
+                }
+
+                let attrs = attrs.borrow();
+                report.attribute_count += attrs.len();
+                report.max_attributes = report.max_attributes.max(attrs.len());
+                report.prefixed_names += usize::from(prefixed);
+                for attr in attrs.iter() {
+                    let bytes = attr.value.len();
+                    report.max_attribute_bytes = report.max_attribute_bytes.max(bytes);
+                    report.total_attribute_bytes += bytes;
+                    let prefixed = attr.name.prefix.is_some() || attr.name.local.contains(':');
+                    report.prefixed_names += usize::from(prefixed);
+                }
+                return Walk::Children(depth + 1);
+            }
+            NodeData::Comment { .. } => report.comments += 1,
+            _ => {}
+        }
+        Walk::Skip
+    });
+
+    let errors = fragment.errors();
+    report.parse_errors = errors.len();
+    for error in errors {
+        if report.parse_error_messages.len() == MAX_ERROR_MESSAGES {
+            break;
+        }
+        if !report.parse_error_messages.contains(&error) {
+            report.parse_error_messages.push(error);
+        }
+    }
+    report
+}
+
+/// Report the structure of `html` as parsed: an element histogram, the
+/// deepest nesting, attribute counts and sizes, comments, foreign and
+/// prefixed names, and the parse errors recovered from.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn inspect_html(html: Binary) -> Guarded<Report> {
+    guard(|| inspect(&String::from_utf8_lossy(&html)))
+}
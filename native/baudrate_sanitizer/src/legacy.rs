@@ -0,0 +1,112 @@
+//! Presentational HTML rewritten to its semantic equivalents.
+//!
+//! Old posts, feeds, and some gateways still emit `<b>`, `<i>`, `<strike>`,
+//! `<tt>`, and `<center>`, which no allowlist keeps: without this pass they
+//! are unwrapped and their emphasis is lost.  Before the allowlist runs,
+//! `b`, `i`, `s`/`strike`, and `tt` become `strong`, `em`, `del`, and
+//! `code`, keeping their attributes for the allowlist to filter.
+//!
+//! No policy allows `style`, so centering cannot be kept; a `<center>`
+//! still separates its text from what surrounds it, as a paragraph, or is
+//! unwrapped if it holds blocks of its own.  `<font>` has no equivalent and
+//! is left to the allowlist, which unwraps it.
+
+use crate::dom::{self, element_name, Fragment, Walk};
+use markup5ever_rcdom::{Handle, NodeData};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+static LEGACY_TAG_RE: OnceLock<Regex> = OnceLock::new();
+
+/// Elements that make a `<center>` a container rather than a paragraph.
+const BLOCKS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "center", "details", "div", "dl", "fieldset",
+    "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li",
+    "main", "nav", "ol", "p", "pre", "section", "table", "ul",
+];
+
+// A quick test for whether the document needs parsing at all.
+fn legacy_tag_regex() -> &'static Regex {
+    LEGACY_TAG_RE
+        .get_or_init(|| Regex::new(r"(?i)<(b|i|s|strike|tt|center)[\s/>]").unwrap())
+}
+
+fn semantic_name(name: &str) -> Option<&'static str> {
+    match name {
+        "b" => Some("strong"),
+        "i" => Some("em"),
+        "s" | "strike" => Some("del"),
+        "tt" => Some("code"),
+        _ => None,
+    }
+}
+
+/// An element named `name` with the attributes and children of `node`.
+fn renamed(node: &Handle, name: &str) -> Handle {
+    let element = dom::new_element(name, &[]);
+    if let (NodeData::Element { attrs, .. }, NodeData::Element { attrs: to, .. }) =
+        (&node.data, &element.data)
+    {
+        *to.borrow_mut() = attrs.borrow().clone();
+    }
+    dom::set_children(&element, node.children.take());
+    element
+}
+
+/// Rewrite the presentational children of `parent`.
+fn rewrite_children(parent: &Handle) {
+    let mut pending: VecDeque<Handle> = parent.children.borrow().iter().cloned().collect();
+    let mut children = Vec::with_capacity(pending.len());
+    let mut changed = false;
+    while let Some(child) = pending.pop_front() {
+        let Some(name) = element_name(&child) else {
+            children.push(child);
+            continue;
+        };
+        let child = if let Some(semantic) = semantic_name(name) {
+            changed = true;
+            renamed(&child, semantic)
+        } else if name == "center" {
+            changed = true;
+            let blocks = child.children.borrow().iter().any(|node| {
+                element_name(node).is_some_and(|name| BLOCKS.contains(&name))
+            });
+            if blocks {
+                // Its children are read again in its place.
+                for node in child.children.take().into_iter().rev() {
+                    pending.push_front(node);
+                }
+                continue;
+            }
+            renamed(&child, "p")
+        } else {
+            child
+        };
+        children.push(child);
+    }
+    if changed {
+        dom::set_children(parent, children);
+    }
+}
+
+fn rewrite(fragment: &Fragment) {
+    let root = fragment.root();
+    rewrite_children(&root);
+    dom::walk(&root, (), |node, ()| {
+        rewrite_children(node);
+        Walk::Children(())
+    });
+}
+
+/// `html` with its presentational elements rewritten, or unchanged if it
+/// has none.
+pub(crate) fn normalize(html: &str) -> Cow<'_, str> {
+    if !legacy_tag_regex().is_match(html) {
+        return Cow::Borrowed(html);
+    }
+    let fragment = Fragment::parse(html);
+    rewrite(&fragment);
+    Cow::Owned(fragment.serialize())
+}
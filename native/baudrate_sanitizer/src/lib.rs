@@ -1,10 +1,69 @@
-use ammonia::{Builder, UrlRelative};
+mod alt;
+mod analyze;
+mod breaks;
+mod content_map;
+mod cw;
+mod dom;
+mod emoji;
+mod entity;
+mod greentext;
+mod hidden;
+mod https;
+mod images;
+mod info;
+mod inline;
+mod inspect;
+mod legacy;
+mod link;
+mod log;
+mod mfm;
+mod og;
+mod plain;
+mod policy;
+mod pool;
+mod preview;
+mod profile;
+mod quirks;
+mod quote;
+mod spam;
+mod stats;
+mod status;
+mod stream;
+mod title;
+mod utf8;
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::https::HttpsHosts;
+use crate::policy::PolicyName;
+use crate::quirks::Source;
+use crate::stats::Nif;
+use crate::utf8::{decode, InvalidUtf8};
+use ammonia::Builder;
+use rayon::prelude::*;
 use regex::Regex;
+use rustler::{Atom, Binary, Decoder, Encoder, Env, LocalPid, NifResult, OwnedEnv, Term};
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
 
-static LANGUAGE_CLASS_RE: OnceLock<Regex> = OnceLock::new();
+mod atoms {
+    rustler::atoms! {
+        ok,
+        invalid_utf8,
+        plaintext,
+        source,
+        https_hosts,
+        drop_hidden,
+        max_images,
+        break_long_words,
+        greentext,
+    }
+}
+
+// Matches `data-highlight-lines` values: line numbers and ranges, `1,3-5`.
+static HIGHLIGHT_LINES_RE: OnceLock<Regex> = OnceLock::new();
+// Matches `time[datetime]` values: an RFC 3339 date, date-time, or time, or
+// a year or month alone.
+static DATETIME_RE: OnceLock<Regex> = OnceLock::new();
 // Matches <p> elements whose content is entirely whitespace and/or &nbsp; entities —
 // these are common artefacts left behind when surrounding <div>/<span> wrappers are
 // stripped by Ammonia.
@@ -13,13 +72,69 @@ static EMPTY_PARA_RE: OnceLock<Regex> = OnceLock::new();
 // whitespace / &nbsp; between them.
 static EXCESS_BR_RE: OnceLock<Regex> = OnceLock::new();
 
-const SAFE_SPAN_CLASSES: &[&str] = &["h-card", "hashtag", "mention", "invisible"];
+// The Ammonia builders are configured once; each call only runs the clean
+// pass.
+static FEDERATION_BUILDER: OnceLock<Builder<'static>> = OnceLock::new();
+static MARKDOWN_BUILDER: OnceLock<Builder<'static>> = OnceLock::new();
+static STRIP_BUILDER: OnceLock<Builder<'static>> = OnceLock::new();
+
+const SAFE_SPAN_CLASSES: &[&str] =
+    &["h-card", "hashtag", "mention", "invisible", "ellipsis", greentext::GREENTEXT_CLASS];
 const SAFE_ANCHOR_CLASSES: &[&str] = &["hashtag", "mention", "u-url"];
+// Class applied by `emojify` to custom emoji images; the Markdown allowlist
+// keeps it so emojified HTML survives re-sanitization unchanged.
+const CUSTOM_EMOJI_CLASS: &str = "custom-emoji";
+// Span classes the Markdown allowlist keeps: those `annotate_code_fences` (in
+// `baudrate_text`) puts on the lines of `diff` code blocks, and those
+// `render_mfm` gives MFM functions, as `mfm-{name}` and `mfm-{name}-{arg}`.
+const MARKDOWN_SPAN_CLASSES: &[&str] = &[
+    "diff-ins", "diff-del", "diff-hunk", "diff-header", "mfm-tada", "mfm-jelly", "mfm-twitch",
+    "mfm-shake", "mfm-spin", "mfm-spin-left", "mfm-spin-alternate", "mfm-spin-x", "mfm-spin-y",
+    "mfm-jump", "mfm-bounce", "mfm-flip", "mfm-flip-h", "mfm-flip-v", "mfm-x2", "mfm-x3",
+    "mfm-x4", "mfm-font", "mfm-font-serif", "mfm-font-monospace", "mfm-font-cursive",
+    "mfm-font-fantasy", "mfm-blur", "mfm-rainbow", "mfm-sparkle", "mfm-rotate",
+];
+
+fn highlight_lines_regex() -> &'static Regex {
+    HIGHLIGHT_LINES_RE
+        .get_or_init(|| Regex::new(r"^[0-9]+(-[0-9]+)?(,[0-9]+(-[0-9]+)?)*$").unwrap())
+}
+
+fn datetime_regex() -> &'static Regex {
+    DATETIME_RE.get_or_init(|| {
+        let time = r"(?:[01][0-9]|2[0-3]):[0-5][0-9](?::(?:[0-5][0-9]|60)(?:\.[0-9]{1,9})?)?";
+        let offset = r"(?:[Zz]|[+-](?:[01][0-9]|2[0-3]):?[0-5][0-9])";
+        let date = r"([0-9]{4})-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])";
+        let month = r"[0-9]{4}(?:-(?:0[1-9]|1[0-2]))?";
+        Regex::new(&format!(r"^(?:{month}|{date}(?:[Tt ]{time}{offset}?)?|{time}{offset}?)$"))
+            .unwrap()
+    })
+}
 
-fn language_class_regex() -> &'static Regex {
-    LANGUAGE_CLASS_RE.get_or_init(|| Regex::new(r"^language-[a-zA-Z0-9_+\-]+$").unwrap())
+/// Whether `value` is a valid `datetime` on a `time` element: a date that
+/// exists, with or without a time and offset, or a time alone.
+fn is_datetime(value: &str) -> bool {
+    let Some(caps) = datetime_regex().captures(value) else {
+        return false;
+    };
+    let (Some(year), Some(month), Some(day)) = (caps.get(1), caps.get(2), caps.get(3)) else {
+        return true;
+    };
+    let [year, month, day]: [u32; 3] =
+        [year, month, day].map(|m| m.as_str().parse().unwrap_or_default());
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    day <= days
 }
 
+/// Longest `data-filename` kept on a code block.
+const MAX_FILENAME_LEN: usize = 255;
+
 fn empty_para_regex() -> &'static Regex {
     EMPTY_PARA_RE.get_or_init(|| Regex::new(r"<p>(\s|&nbsp;)*</p>").unwrap())
 }
@@ -28,121 +143,95 @@ fn excess_br_regex() -> &'static Regex {
     EXCESS_BR_RE.get_or_init(|| Regex::new(r"(<br\s*/?>(\s|&nbsp;)*){3,}").unwrap())
 }
 
-fn federation_tags() -> HashSet<&'static str> {
-    [
-        "p", "br", "hr", "h1", "h2", "h3", "h4", "h5", "h6", "em", "strong", "del", "code",
-        "pre", "blockquote", "ul", "ol", "li", "a", "span",
-    ]
-    .into_iter()
-    .collect()
+fn federation_builder() -> &'static Builder<'static> {
+    FEDERATION_BUILDER.get_or_init(|| {
+        let mut builder = policy::FEDERATION.builder();
+        builder.attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("code", "class") => policy::filter_code_classes(PolicyName::Federation, value),
+            (_, "class") => {
+                let safe: &[&str] = match element {
+                    "a" => SAFE_ANCHOR_CLASSES,
+                    "span" => SAFE_SPAN_CLASSES,
+                    _ => &[],
+                };
+                policy::filter_classes(PolicyName::Federation, element, value, |c| {
+                    safe.contains(&c)
+                })
+            }
+            ("time", "datetime") => is_datetime(value).then_some(Cow::Borrowed(value)),
+            _ => Some(Cow::Borrowed(value)),
+        });
+        builder
+    })
 }
 
-fn clean_content_tags() -> HashSet<&'static str> {
-    [
-        "script", "style", "iframe", "object", "embed", "form", "input", "textarea", "svg",
-        "math",
-    ]
-    .into_iter()
-    .collect()
+fn sanitize_with_federation_rules(html: &str) -> String {
+    match plain::escape(html) {
+        Some(text) => text,
+        None => {
+            let html = legacy::normalize(html);
+            federation_builder().clean(&html).to_string()
+        }
+    }
 }
 
-#[rustler::nif]
-fn sanitize_federation(html: &str) -> String {
-    let tags = federation_tags();
-
-    let mut tag_attributes: HashMap<&str, HashSet<&str>> = HashMap::new();
-    tag_attributes.insert("a", ["href", "class"].into_iter().collect());
-    tag_attributes.insert("span", ["class"].into_iter().collect());
-
-    let url_schemes: HashSet<&str> = ["http", "https"].into_iter().collect();
-
-    Builder::new()
-        .tags(tags)
-        .tag_attributes(tag_attributes)
-        .url_schemes(url_schemes)
-        .url_relative(UrlRelative::Deny)
-        .link_rel(Some("nofollow noopener noreferrer"))
-        .clean_content_tags(clean_content_tags())
-        .strip_comments(true)
-        .attribute_filter(|element, attribute, value| match (element, attribute) {
-            ("a", "class") => {
-                let filtered: Vec<&str> = value
-                    .split_whitespace()
-                    .filter(|c| SAFE_ANCHOR_CLASSES.contains(c))
-                    .collect();
-                if filtered.is_empty() {
-                    None
+fn markdown_builder() -> &'static Builder<'static> {
+    MARKDOWN_BUILDER.get_or_init(|| {
+        let lines_re = highlight_lines_regex();
+        let mut builder = policy::MARKDOWN.builder();
+        builder.attribute_filter(move |element, attribute, value| match (element, attribute) {
+            ("code", "class") => policy::filter_code_classes(PolicyName::Markdown, value),
+            ("a", "href") if value.starts_with('#') => policy::filter_fragment(value),
+            (_, "id") => policy::filter_id(value),
+            ("time", "datetime") => is_datetime(value).then_some(Cow::Borrowed(value)),
+            ("pre", "data-highlight-lines") => {
+                if lines_re.is_match(value) {
+                    Some(Cow::Borrowed(value))
                 } else {
-                    Some(Cow::Owned(filtered.join(" ")))
+                    None
                 }
             }
-            ("span", "class") => {
-                let filtered: Vec<&str> = value
-                    .split_whitespace()
-                    .filter(|c| SAFE_SPAN_CLASSES.contains(c))
-                    .collect();
-                if filtered.is_empty() {
+            ("pre", "data-filename") => {
+                let name = value.trim();
+                if name.is_empty()
+                    || name.chars().count() > MAX_FILENAME_LEN
+                    || name.chars().any(char::is_control)
+                {
                     None
                 } else {
-                    Some(Cow::Owned(filtered.join(" ")))
+                    Some(Cow::Borrowed(name))
                 }
             }
-            _ => Some(Cow::Borrowed(value)),
-        })
-        .clean(html)
-        .to_string()
-}
-
-fn sanitize_with_markdown_rules(html: &str) -> String {
-    let mut tags = federation_tags();
-    for tag in ["table", "thead", "tbody", "tr", "th", "td", "img"] {
-        tags.insert(tag);
-    }
-
-    let mut tag_attributes: HashMap<&str, HashSet<&str>> = HashMap::new();
-    tag_attributes.insert("a", ["href"].into_iter().collect());
-    tag_attributes.insert("code", ["class"].into_iter().collect());
-    tag_attributes.insert("img", ["src", "alt"].into_iter().collect());
-
-    let url_schemes: HashSet<&str> = ["http", "https", "mailto"].into_iter().collect();
-
-    let re = language_class_regex();
-
-    Builder::new()
-        .tags(tags)
-        .tag_attributes(tag_attributes)
-        .url_schemes(url_schemes)
-        .url_relative(UrlRelative::PassThrough)
-        .link_rel(Some("nofollow noopener"))
-        .clean_content_tags(clean_content_tags())
-        .strip_comments(true)
-        .attribute_filter(move |element, attribute, value| match (element, attribute) {
-            ("code", "class") => {
-                if re.is_match(value) {
-                    Some(Cow::Borrowed(value))
-                } else {
-                    None
-                }
+            ("img", "class") if value == CUSTOM_EMOJI_CLASS => Some(Cow::Borrowed(value)),
+            (_, "class") => {
+                let safe: &[&str] = if element == "span" { MARKDOWN_SPAN_CLASSES } else { &[] };
+                policy::filter_classes(PolicyName::Markdown, element, value, |c| safe.contains(&c))
             }
             _ => Some(Cow::Borrowed(value)),
-        })
-        .clean(html)
-        .to_string()
+        });
+        builder
+    })
 }
 
-#[rustler::nif]
-fn sanitize_markdown(html: &str) -> String {
-    sanitize_with_markdown_rules(html)
+fn sanitize_with_markdown_rules(html: &str) -> String {
+    match plain::escape(html) {
+        Some(text) => text,
+        None => markdown_builder().clean(&legacy::normalize(html)).to_string(),
+    }
 }
 
 const NBSP: &str = "&nbsp;";
 
-#[rustler::nif]
-fn strip_tags(html: &str) -> String {
-    let text = Builder::empty()
-        .strip_comments(true)
-        .clean(html)
-        .to_string();
+fn strip_builder() -> &'static Builder<'static> {
+    STRIP_BUILDER.get_or_init(|| {
+        let mut builder = Builder::empty();
+        builder.strip_comments(true);
+        builder
+    })
+}
+
+fn strip_all_tags(html: &str) -> String {
+    let text = plain::escape(html).unwrap_or_else(|| strip_builder().clean(html).to_string());
     let mut s = text.as_str();
     while let Some(rest) = s.strip_prefix(NBSP) {
         s = rest;
@@ -153,8 +242,7 @@ fn strip_tags(html: &str) -> String {
     s.to_string()
 }
 
-#[rustler::nif]
-fn normalize_feed_html(html: &str) -> String {
+fn normalize_feed(html: &str) -> String {
     // Sanitize with the same allowlist as sanitize_markdown, then clean up
     // common RSS/Atom artefacts produced by stripping disallowed elements.
     let sanitized = sanitize_with_markdown_rules(html);
@@ -176,4 +264,458 @@ fn normalize_feed_html(html: &str) -> String {
     cleaned.trim().to_string()
 }
 
-rustler::init!("Elixir.Baudrate.Sanitizer.Native");
+// Each sanitizer takes a binary rather than a `&str`, so that remote bodies
+// with broken UTF-8 never fail with a badarg.  The single-argument forms
+// replace invalid sequences with U+FFFD; the two-argument forms let the
+// caller choose with an `InvalidUtf8` policy.
+
+/// One sanitizer NIF: its counter, the policy its removals are logged
+/// against, the cleaning function, and the passes always run after it.
+struct Sanitizer {
+    nif: Nif,
+    policy: Option<PolicyName>,
+    rules: fn(&str) -> String,
+    finish: Finish,
+}
+
+const FEDERATION: Sanitizer = Sanitizer {
+    nif: Nif::SanitizeFederation,
+    policy: Some(PolicyName::Federation),
+    rules: sanitize_with_federation_rules,
+    finish: Finish { confine_links: true, ..Finish::NONE },
+};
+
+const MARKDOWN: Sanitizer = Sanitizer {
+    nif: Nif::SanitizeMarkdown,
+    policy: Some(PolicyName::Markdown),
+    rules: sanitize_with_markdown_rules,
+    finish: Finish::NONE,
+};
+
+// Everything is stripped, so there is nothing useful to log.
+const STRIP: Sanitizer = Sanitizer {
+    nif: Nif::StripTags,
+    policy: None,
+    rules: strip_all_tags,
+    finish: Finish::NONE,
+};
+
+const FEED: Sanitizer = Sanitizer {
+    nif: Nif::NormalizeFeedHtml,
+    policy: Some(PolicyName::Markdown),
+    rules: normalize_feed,
+    finish: Finish::NONE,
+};
+
+impl Sanitizer {
+    /// Apply the rules and the finishing passes to `html`, counting and
+    /// logging what they remove.
+    fn clean(&self, html: &str) -> String {
+        self.finish.apply(self.allow(html))
+    }
+
+    /// Apply the rules alone, counting and logging what they remove.
+    fn allow(&self, html: &str) -> String {
+        // Plain text has nothing to remove, audit, or count.
+        if plain::is_plain(html) {
+            return (self.rules)(html);
+        }
+        if let Some(name) = self.policy {
+            log::audit(html, name, name.policy());
+        }
+        let cleaned = (self.rules)(html);
+        stats::stripped(html, &cleaned);
+        cleaned
+    }
+
+    fn clean_lossy(&self, html: &[u8]) -> String {
+        let html = String::from_utf8_lossy(html);
+        if let Cow::Owned(_) = html {
+            log::invalid_utf8(self.policy);
+        }
+        self.clean(&html)
+    }
+
+    fn lossy(&self, env: Env, html: Binary) -> Guarded<String> {
+        let result = stats::track(self.nif, html.len(), || self.clean_lossy(&html));
+        log::flush(env);
+        result
+    }
+
+    /// Like `lossy` for a list of documents, cleaned in parallel on the
+    /// pool.  The batch counts as one call.
+    fn batch(&self, env: Env, htmls: Vec<Binary>) -> Guarded<Vec<String>> {
+        let bytes = htmls.iter().map(|html| html.len()).sum();
+        let result = stats::track(self.nif, bytes, || {
+            let htmls: Vec<&[u8]> = htmls.iter().map(|html| html.as_slice()).collect();
+            let cleaned: Vec<(String, Vec<log::Event>)> = pool::get().install(|| {
+                htmls
+                    .par_iter()
+                    .map(|html| log::collect(|| self.clean_lossy(html)))
+                    .collect()
+            });
+            cleaned
+                .into_iter()
+                .map(|(html, events)| {
+                    log::adopt(events);
+                    html
+                })
+                .collect()
+        });
+        log::flush(env);
+        result
+    }
+
+    fn checked(
+        &self,
+        env: Env,
+        html: Binary,
+        invalid_utf8: InvalidUtf8,
+    ) -> Guarded<Result<String, Atom>> {
+        let finish = self.finish;
+        self.checked_with(env, html, invalid_utf8, Prepare::default(), |html, _| finish.apply(html))
+    }
+
+    /// Like `checked`, first running the passes of `prepare`, and passing
+    /// the HTML through `then` with the number of images replaced, if
+    /// `max_images` was given.  `then` gets it before the finishing passes,
+    /// and runs them itself.
+    fn checked_with<T>(
+        &self,
+        env: Env,
+        html: Binary,
+        invalid_utf8: InvalidUtf8,
+        prepare: Prepare,
+        then: impl FnOnce(String, Option<usize>) -> T,
+    ) -> Guarded<Result<T, Atom>> {
+        let result = stats::track(self.nif, html.len(), || {
+            let html = decode(&html, invalid_utf8);
+            if !matches!(html, Ok(Cow::Borrowed(_))) {
+                log::invalid_utf8(self.policy);
+            }
+            html.map(|html| {
+                let (html, images_removed) = prepare.apply(&html);
+                then(self.allow(&html), images_removed)
+            })
+        });
+        log::flush(env);
+        result
+    }
+}
+
+/// Optional passes over a document before the allowlist.
+#[derive(Default, Clone, Copy)]
+struct Prepare {
+    /// The software whose quirks are normalized.
+    source: Option<Source>,
+    /// Whether tracking images and hidden elements are dropped.
+    drop_hidden: bool,
+    /// How many images are kept before the rest become links.
+    max_images: Option<usize>,
+}
+
+/// `html` after `pass`, borrowed from the input only if neither changed it.
+fn then_pass<'a>(html: Cow<'a, str>, pass: impl FnOnce(&str) -> Cow<'_, str>) -> Cow<'a, str> {
+    match html {
+        Cow::Borrowed(html) => pass(html),
+        Cow::Owned(html) => Cow::Owned(pass(&html).into_owned()),
+    }
+}
+
+impl Prepare {
+    fn decode(term: Term) -> NifResult<Self> {
+        let get = |key: Atom| term.map_get(key).ok();
+        Ok(Self {
+            source: get(atoms::source()).map_or(Ok(None), Term::decode)?,
+            drop_hidden: get(atoms::drop_hidden()).map_or(Ok(false), Term::decode)?,
+            max_images: get(atoms::max_images()).map_or(Ok(None), Term::decode)?,
+        })
+    }
+
+    /// Run the passes over `html`, returning it with the number of images
+    /// replaced if `max_images` is set.
+    fn apply<'a>(&self, html: &'a str) -> (Cow<'a, str>, Option<usize>) {
+        let mut html = match self.source {
+            Some(source) => quirks::normalize(html, source),
+            None => Cow::Borrowed(html),
+        };
+        if self.drop_hidden {
+            html = then_pass(html, hidden::drop_hidden);
+        }
+        let Some(max) = self.max_images else {
+            return (html, None);
+        };
+        let mut removed = 0;
+        let html = then_pass(html, |html| {
+            let (html, count) = images::limit_images(html, max);
+            removed = count;
+            html
+        });
+        (html, Some(removed))
+    }
+}
+
+/// Optional passes over a sanitized document that need its tree, run over
+/// one parse of it.
+#[derive(Clone, Copy)]
+struct Finish {
+    /// Whether the link span classes are dropped from spans outside links.
+    confine_links: bool,
+    /// Whether lines starting with `>` are wrapped as greentext.
+    greentext: bool,
+    /// How many characters a word has before it gets break opportunities.
+    break_long_words: Option<usize>,
+}
+
+impl Finish {
+    const NONE: Finish = Finish { confine_links: false, greentext: false, break_long_words: None };
+
+    fn apply(&self, html: String) -> String {
+        // Each pass first checks the text for anything it could change, so
+        // that most documents are not parsed at all.
+        let confine_links = self.confine_links && link::has_link_classes(&html);
+        let greentext = self.greentext && greentext::has_greentext(&html);
+        let break_long_words = self
+            .break_long_words
+            .filter(|&max| max > 0 && breaks::has_long_word(&html, max));
+        if !confine_links && !greentext && break_long_words.is_none() {
+            return html;
+        }
+
+        let fragment = dom::Fragment::parse(&html);
+        let root = fragment.root();
+        let mut changed = false;
+        if confine_links {
+            changed |= link::confine_link_classes(&root);
+        }
+        if greentext {
+            changed |= greentext::wrap_greentext(&root);
+        }
+        if let Some(max) = break_long_words {
+            changed |= breaks::break_long_words(&root, max);
+        }
+        if changed {
+            fragment.serialize()
+        } else {
+            html
+        }
+    }
+}
+
+/// The second argument of `sanitize_federation/2`: an `InvalidUtf8` atom,
+/// or a map of `invalid_utf8` (default `:lossy`), `plaintext` (default
+/// `false`), `source` (default `nil`, no quirks), `https_hosts` (default
+/// `[]`), `drop_hidden` (default `false`), `max_images` (default `nil`, no
+/// limit), `break_long_words` (default `nil`, no breaks), and `greentext`
+/// (default `false`).
+struct FederationOptions {
+    invalid_utf8: InvalidUtf8,
+    plaintext: bool,
+    prepare: Prepare,
+    https_hosts: HttpsHosts,
+    break_long_words: Option<usize>,
+    greentext: bool,
+}
+
+/// The `https_hosts` option of `term`, if it is a map.
+fn https_hosts(term: Term) -> NifResult<HttpsHosts> {
+    let hosts = term.map_get(atoms::https_hosts()).ok();
+    Ok(HttpsHosts::new(hosts.map_or(Ok(Vec::new()), Term::decode)?))
+}
+
+/// The `break_long_words` option of `term`, if it is a map.
+fn break_long_words(term: Term) -> NifResult<Option<usize>> {
+    term.map_get(atoms::break_long_words()).map_or(Ok(None), Term::decode)
+}
+
+impl<'a> Decoder<'a> for FederationOptions {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if !term.is_map() {
+            return Ok(Self {
+                invalid_utf8: term.decode()?,
+                plaintext: false,
+                prepare: Prepare::default(),
+                https_hosts: HttpsHosts::default(),
+                break_long_words: None,
+                greentext: false,
+            });
+        }
+        let get = |key: Atom| term.map_get(key).ok();
+        Ok(Self {
+            invalid_utf8: get(atoms::invalid_utf8()).map_or(Ok(InvalidUtf8::Lossy), Term::decode)?,
+            plaintext: get(atoms::plaintext()).map_or(Ok(false), Term::decode)?,
+            prepare: Prepare::decode(term)?,
+            https_hosts: https_hosts(term)?,
+            break_long_words: break_long_words(term)?,
+            greentext: get(atoms::greentext()).map_or(Ok(false), Term::decode)?,
+        })
+    }
+}
+
+/// The second argument of `sanitize_markdown/2` and `normalize_feed_html/2`:
+/// an `InvalidUtf8` atom, or a map of `invalid_utf8` (default `:lossy`),
+/// `https_hosts` (default `[]`), `drop_hidden` (default `false`),
+/// `max_images` (default `nil`), and `break_long_words` (default `nil`).
+struct Options {
+    invalid_utf8: InvalidUtf8,
+    https_hosts: HttpsHosts,
+    prepare: Prepare,
+    break_long_words: Option<usize>,
+}
+
+impl<'a> Decoder<'a> for Options {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if !term.is_map() {
+            return Ok(Self {
+                invalid_utf8: term.decode()?,
+                https_hosts: HttpsHosts::default(),
+                prepare: Prepare::default(),
+                break_long_words: None,
+            });
+        }
+        let get = |key: Atom| term.map_get(key).ok();
+        // Quirks are only normalized for federated content.
+        let prepare = Prepare { source: None, ..Prepare::decode(term)? };
+        Ok(Self {
+            invalid_utf8: get(atoms::invalid_utf8()).map_or(Ok(InvalidUtf8::Lossy), Term::decode)?,
+            https_hosts: https_hosts(term)?,
+            prepare,
+            break_long_words: break_long_words(term)?,
+        })
+    }
+}
+
+/// Sanitized HTML, with its plain text and the number of images replaced
+/// when those were asked for: `html`, `{html, plaintext}`,
+/// `{html, images_removed}`, or `{html, plaintext, images_removed}`.
+struct Sanitized {
+    html: String,
+    plaintext: Option<String>,
+    images_removed: Option<usize>,
+}
+
+impl Encoder for Sanitized {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let html = &self.html;
+        match (&self.plaintext, self.images_removed) {
+            (None, None) => html.encode(env),
+            (Some(text), None) => (html, text).encode(env),
+            (None, Some(removed)) => (html, removed).encode(env),
+            (Some(text), Some(removed)) => (html, text, removed).encode(env),
+        }
+    }
+}
+
+#[rustler::nif]
+fn sanitize_federation(env: Env, html: Binary) -> Guarded<String> {
+    FEDERATION.lossy(env, html)
+}
+
+#[rustler::nif(name = "sanitize_federation")]
+fn sanitize_federation_checked(
+    env: Env,
+    html: Binary,
+    options: FederationOptions,
+) -> Guarded<Result<Sanitized, Atom>> {
+    let prepare = options.prepare;
+    FEDERATION.checked_with(env, html, options.invalid_utf8, prepare, |html, images_removed| {
+        let html = https::upgrade(html, &options.https_hosts);
+        let plaintext = options.plaintext.then(|| analyze::plaintext(&html));
+        let finish = Finish {
+            greentext: options.greentext,
+            break_long_words: options.break_long_words,
+            ..FEDERATION.finish
+        };
+        Sanitized { html: finish.apply(html), plaintext, images_removed }
+    })
+}
+
+/// `sanitize_federation/1` for many documents at once, in the same order.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn sanitize_federation_batch(env: Env, htmls: Vec<Binary>) -> Guarded<Vec<String>> {
+    FEDERATION.batch(env, htmls)
+}
+
+/// Sanitize `html` like `sanitize_federation/1` on the pool, and send the
+/// result to `caller` as `{reference, {:ok, html}}`.
+///
+/// Returns `:ok` at once.  The input is copied, so the caller need not keep
+/// it alive; a result for a process that has exited is dropped.
+#[rustler::nif]
+fn sanitize_async(html: Binary, caller: LocalPid, reference: Term) -> Guarded<Atom> {
+    guard(|| {
+        let mut owned = OwnedEnv::new();
+        let reference = owned.save(reference);
+        let html = html.as_slice().to_vec();
+
+        pool::get().spawn(move || {
+            let (result, events) = log::collect(|| {
+                stats::track(FEDERATION.nif, html.len(), || {
+                    Ok::<_, Atom>(FEDERATION.clean_lossy(&html))
+                })
+            });
+            let _ = owned.send_and_clear(&caller, |env| {
+                log::adopt(events);
+                log::flush(env);
+                (reference.load(env), result)
+            });
+        });
+        atoms::ok()
+    })
+}
+
+#[rustler::nif]
+fn sanitize_markdown(env: Env, html: Binary) -> Guarded<String> {
+    MARKDOWN.lossy(env, html)
+}
+
+#[rustler::nif(name = "sanitize_markdown")]
+fn sanitize_markdown_checked(
+    env: Env,
+    html: Binary,
+    options: Options,
+) -> Guarded<Result<Sanitized, Atom>> {
+    MARKDOWN.checked_with(env, html, options.invalid_utf8, options.prepare, |html, images_removed| {
+        let html = https::upgrade(html, &options.https_hosts);
+        let finish = Finish { break_long_words: options.break_long_words, ..MARKDOWN.finish };
+        Sanitized { html: finish.apply(html), plaintext: None, images_removed }
+    })
+}
+
+#[rustler::nif]
+fn strip_tags(env: Env, html: Binary) -> Guarded<String> {
+    STRIP.lossy(env, html)
+}
+
+#[rustler::nif(name = "strip_tags")]
+fn strip_tags_checked(
+    env: Env,
+    html: Binary,
+    invalid_utf8: InvalidUtf8,
+) -> Guarded<Result<String, Atom>> {
+    STRIP.checked(env, html, invalid_utf8)
+}
+
+#[rustler::nif]
+fn normalize_feed_html(env: Env, html: Binary) -> Guarded<String> {
+    FEED.lossy(env, html)
+}
+
+#[rustler::nif(name = "normalize_feed_html")]
+fn normalize_feed_html_checked(
+    env: Env,
+    html: Binary,
+    options: Options,
+) -> Guarded<Result<Sanitized, Atom>> {
+    FEED.checked_with(env, html, options.invalid_utf8, options.prepare, |html, images_removed| {
+        let html = https::upgrade(html, &options.https_hosts);
+        let finish = Finish { break_long_words: options.break_long_words, ..FEED.finish };
+        Sanitized { html: finish.apply(html), plaintext: None, images_removed }
+    })
+}
+
+fn on_load(env: Env, info: Term) -> bool {
+    pool::load() && log::load(env, info)
+}
+
+rustler::init!("Elixir.Baudrate.Sanitizer.Native", load = on_load);
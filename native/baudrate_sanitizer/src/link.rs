@@ -0,0 +1,100 @@
+//! Mastodon-style link labels.
+//!
+//! Mastodon renders a link's text as three spans: the scheme (and any
+//! `www.`) in an `invisible` span, the next `DISPLAY_CHARS` characters
+//! starting at the host, and whatever remains in a second `invisible` span.
+//! When anything remains, the displayed span gets the `ellipsis` class,
+//! whose CSS appends `…`.  Copying the link text still yields the full URL.
+//!
+//! Outgoing labels are built by `format_link_label/1`.  Incoming ones pass
+//! the federation allowlist, after which [`confine_link_classes`] drops the
+//! two classes from spans outside a link, where hidden text could only
+//! disguise a post.
+
+use crate::dom::{self, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use markup5ever_rcdom::{Handle, NodeData};
+
+/// Span classes only meaningful inside an `<a>`.
+const LINK_SPAN_CLASSES: &[&str] = &["invisible", "ellipsis"];
+
+/// Characters shown after the prefix, as in Mastodon's `TextFormatter`.
+const DISPLAY_CHARS: usize = 30;
+
+const PREFIXES: &[&str] = &["https://www.", "http://www.", "https://", "http://", "xmpp:"];
+
+/// Escape text the way html5ever serializes it, so sanitizing the label
+/// leaves it byte-for-byte unchanged.
+pub(crate) fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\u{A0}' => out.push_str("&nbsp;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+pub(crate) fn label(url: &str) -> String {
+    let prefix = PREFIXES.iter().find(|p| url.starts_with(*p)).map_or("", |p| *p);
+    let rest = &url[prefix.len()..];
+    let split = rest.char_indices().nth(DISPLAY_CHARS).map_or(rest.len(), |(i, _)| i);
+    let (display, suffix) = rest.split_at(split);
+
+    let mut out = String::with_capacity(url.len() + 96);
+    out.push_str(r#"<span class="invisible">"#);
+    escape(prefix, &mut out);
+    out.push_str(if suffix.is_empty() { "</span><span>" } else { r#"</span><span class="ellipsis">"# });
+    escape(display, &mut out);
+    out.push_str(r#"</span><span class="invisible">"#);
+    escape(suffix, &mut out);
+    out.push_str("</span>");
+    out
+}
+
+/// Whether sanitized `html` can have spans for [`confine_link_classes`].
+pub(crate) fn has_link_classes(html: &str) -> bool {
+    LINK_SPAN_CLASSES.iter().any(|class| html.contains(class))
+}
+
+/// Remove [`LINK_SPAN_CLASSES`] from the spans under `root` that are not
+/// inside an `<a>`, removing `class` once it is empty, and return whether
+/// any were.
+pub(crate) fn confine_link_classes(root: &Handle) -> bool {
+    let mut changed = false;
+    dom::walk(root, false, |node, in_link| {
+        let NodeData::Element { name, attrs, .. } = &node.data else {
+            return Walk::Skip;
+        };
+        if &*name.local == "span" && !in_link {
+            let mut attrs = attrs.borrow_mut();
+            if let Some(i) = attrs.iter().position(|a| &*a.name.local == "class") {
+                let kept: Vec<&str> = attrs[i]
+                    .value
+                    .split_whitespace()
+                    .filter(|c| !LINK_SPAN_CLASSES.contains(c))
+                    .collect();
+                let kept = kept.join(" ");
+                if kept.len() != attrs[i].value.len() {
+                    changed = true;
+                    if kept.is_empty() {
+                        attrs.remove(i);
+                    } else {
+                        attrs[i].value = kept.into();
+                    }
+                }
+            }
+        }
+        Walk::Children(in_link || &*name.local == "a")
+    });
+    changed
+}
+
+/// Return the link text markup for `url`, HTML-escaped, to be placed inside
+/// an `<a href>` for it.
+#[rustler::nif]
+fn format_link_label(url: &str) -> Guarded<String> {
+    guard(|| label(url))
+}
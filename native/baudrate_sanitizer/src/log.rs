@@ -0,0 +1,268 @@
+//! Optional reporting of sanitizer decisions to Elixir.
+//!
+//! The level comes from the load data (see `Baudrate.Sanitizer.Log`) and is
+//! `:off` unless configured.  While it is set, each sanitizer call audits its
+//! input against the policy and records one event per removed element,
+//! attribute, or URL.  Events are sent to the process registered with
+//! `set_log_target/1`, or else buffered until `drain_log/0`, keeping only
+//! the newest [`MAX_BUFFERED`].
+//!
+//! Events are collected per call on the calling thread and delivered once
+//! the call has finished, so a sanitizer body never touches the target.
+//! Batch NIFs collect each item's events on its pool thread and hand them
+//! back to the calling thread.
+
+use crate::dom::{self, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use crate::policy::{self, Policy, PolicyName, RelativeUrls, CLEAN_CONTENT_TAGS, GENERIC_ATTRIBUTES};
+use markup5ever_rcdom::NodeData;
+use rustler::{Atom, Env, LocalPid, NifMap, NifUnitEnum, Term};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use url::Url;
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        baudrate_sanitizer_log,
+    }
+}
+
+/// Most events kept for `drain_log/0` when no target is set.
+const MAX_BUFFERED: usize = 1_000;
+
+/// Longest attribute value included in an event, in bytes.
+const MAX_VALUE_LEN: usize = 256;
+
+/// Verbosity, passed from Elixir as an atom.  Each level includes the ones
+/// before it.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, PartialOrd)]
+pub(crate) enum Level {
+    Off,
+    /// Rejected URLs and elements removed with their content.
+    Warning,
+    /// Input that was not valid UTF-8.
+    Info,
+    /// Unwrapped elements and removed attributes.
+    Debug,
+}
+
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum Kind {
+    ElementRemoved,
+    ElementUnwrapped,
+    AttributeRemoved,
+    UrlRejected,
+    InvalidUtf8,
+}
+
+#[derive(NifMap)]
+pub(crate) struct Event {
+    level: Level,
+    event: Kind,
+    policy: Option<PolicyName>,
+    tag: Option<String>,
+    attribute: Option<String>,
+    value: Option<String>,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static TARGET: Mutex<Option<LocalPid>> = Mutex::new(None);
+static BUFFER: Mutex<VecDeque<Event>> = Mutex::new(VecDeque::new());
+
+thread_local! {
+    static PENDING: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Set the level from the load data; anything but a level atom means `:off`.
+pub(crate) fn load(_env: Env, info: Term) -> bool {
+    let level = info.decode::<Level>().unwrap_or(Level::Off);
+    LEVEL.store(level as u8, Ordering::Relaxed);
+    true
+}
+
+pub(crate) fn enabled(level: Level) -> bool {
+    level != Level::Off && level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+fn record(
+    level: Level,
+    event: Kind,
+    policy: Option<PolicyName>,
+    tag: Option<&str>,
+    attribute: Option<&str>,
+    value: Option<&str>,
+) {
+    if !enabled(level) {
+        return;
+    }
+    let value = value.map(|v| {
+        let mut end = v.len().min(MAX_VALUE_LEN);
+        while !v.is_char_boundary(end) {
+            end -= 1;
+        }
+        v[..end].to_string()
+    });
+    PENDING.with(|pending| {
+        pending.borrow_mut().push(Event {
+            level,
+            event,
+            policy,
+            tag: tag.map(str::to_string),
+            attribute: attribute.map(str::to_string),
+            value,
+        })
+    });
+}
+
+pub(crate) fn invalid_utf8(policy: Option<PolicyName>) {
+    record(Level::Info, Kind::InvalidUtf8, policy, None, None, None);
+}
+
+/// Record what `policy` will remove from `html`.
+///
+/// This is a separate parse with the same fragment rules Ammonia uses, and
+/// only runs while logging is enabled.
+pub(crate) fn audit(html: &str, name: PolicyName, policy: &Policy) {
+    if !enabled(Level::Warning) {
+        return;
+    }
+
+    let fragment = dom::Fragment::parse(html);
+    dom::walk(&fragment.root(), (), |node, ()| {
+        let NodeData::Element {
+            name: qual, attrs, ..
+        } = &node.data
+        else {
+            return Walk::Skip;
+        };
+        let tag: &str = &qual.local;
+        let report = |level, kind, attribute, value| {
+            record(level, kind, Some(name), Some(tag), attribute, value);
+        };
+
+        if CLEAN_CONTENT_TAGS.contains(&tag) {
+            report(Level::Warning, Kind::ElementRemoved, None, None);
+            return Walk::Skip;
+        }
+
+        if !policy.tags.contains(&tag) {
+            report(Level::Debug, Kind::ElementUnwrapped, None, None);
+        } else {
+            for attr in attrs.borrow().iter() {
+                let (attribute, value): (&str, &str) = (&attr.name.local, &attr.value);
+                if !attribute_allowed(name, policy, tag, attribute) {
+                    report(Level::Debug, Kind::AttributeRemoved, Some(attribute), None);
+                } else if matches!(attribute, "href" | "src")
+                    && !url_allowed(policy, attribute, value)
+                {
+                    report(
+                        Level::Warning,
+                        Kind::UrlRejected,
+                        Some(attribute),
+                        Some(value),
+                    );
+                }
+            }
+        }
+        Walk::Children(())
+    });
+}
+
+fn attribute_allowed(name: PolicyName, policy: &Policy, tag: &str, attribute: &str) -> bool {
+    GENERIC_ATTRIBUTES.contains(&attribute)
+        || (attribute == "class" && policy::has_extra_classes(name, tag))
+        || policy
+            .tag_attributes
+            .iter()
+            .any(|&(t, attributes)| t == tag && attributes.contains(&attribute))
+}
+
+/// Ammonia's URL check: an allowed scheme, or a relative URL where the
+/// policy keeps them, and a safe name for an `href` fragment where it keeps
+/// those.
+fn url_allowed(policy: &Policy, attribute: &str, value: &str) -> bool {
+    if attribute == "href" && policy.fragment_links && value.starts_with('#') {
+        return policy::filter_fragment(value).is_some();
+    }
+    match Url::parse(value) {
+        Ok(url) => policy.url_schemes.contains(&url.scheme()),
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            matches!(policy.relative_urls, RelativeUrls::PassThrough)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Run `body` on a pool thread, returning the events it recorded so that
+/// the calling NIF can [`adopt`] them.
+pub(crate) fn collect<T>(body: impl FnOnce() -> T) -> (T, Vec<Event>) {
+    // Drop anything left behind by an earlier item that panicked.
+    PENDING.with(|pending| pending.borrow_mut().clear());
+    let value = body();
+    (value, PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut())))
+}
+
+/// Add events recorded on another thread to the current call.
+pub(crate) fn adopt(events: Vec<Event>) {
+    PENDING.with(|pending| pending.borrow_mut().extend(events));
+}
+
+/// Deliver the events recorded during the current call.
+pub(crate) fn flush(env: Env) {
+    let events = PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut()));
+    if events.is_empty() {
+        return;
+    }
+
+    let target = *TARGET.lock().unwrap_or_else(|e| e.into_inner());
+    match target {
+        Some(pid) => {
+            for event in events {
+                // A dead target simply loses the event.
+                let _ = env.send(&pid, (atoms::baudrate_sanitizer_log(), event));
+            }
+        }
+        None => {
+            let mut buffer = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+            buffer.extend(events);
+            let excess = buffer.len().saturating_sub(MAX_BUFFERED);
+            buffer.drain(..excess);
+        }
+    }
+}
+
+/// Send future events to `pid` as `{:baudrate_sanitizer_log, event}`, or
+/// buffer them again when `pid` is `nil`.
+#[rustler::nif]
+fn set_log_target(pid: Option<LocalPid>) -> Guarded<Atom> {
+    guard(|| {
+        *TARGET.lock().unwrap_or_else(|e| e.into_inner()) = pid;
+        atoms::ok()
+    })
+}
+
+/// Return and clear the buffered events, oldest first.
+#[rustler::nif]
+fn drain_log() -> Guarded<Vec<Event>> {
+    guard(|| {
+        BUFFER
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect()
+    })
+}
+
+/// The level set at load time.
+#[rustler::nif]
+fn log_level() -> Guarded<Level> {
+    guard(|| match LEVEL.load(Ordering::Relaxed) {
+        l if l == Level::Warning as u8 => Level::Warning,
+        l if l == Level::Info as u8 => Level::Info,
+        l if l == Level::Debug as u8 => Level::Debug,
+        _ => Level::Off,
+    })
+}
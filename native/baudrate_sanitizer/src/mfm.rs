@@ -0,0 +1,164 @@
+//! MFM (Misskey Flavored Markdown) functions in federated content.
+//!
+//! Misskey and its forks let posts apply effects with `$[name.args text]`
+//! (`$[jelly hi]`, `$[x2 big]`, `$[flip.h,v upside down]`), and other
+//! software shows the syntax as literal text.  In sanitized HTML, each
+//! function is either turned into a `span` with `mfm-*` classes the Markdown
+//! allowlist keeps, or reduced to its text.
+//!
+//! Functions without a class of their own (`fg`, `position`, `ruby`, ...)
+//! and functions whose text crosses a tag are always reduced to their text,
+//! so the output is as well formed as the input.  A `$[` without its `]` is
+//! not MFM and is kept.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::MARKDOWN_SPAN_CLASSES;
+use rustler::{Binary, NifUnitEnum};
+
+/// Most functions open at once; a deeper `$[` is kept as text.  Opening
+/// spans are inserted when a function closes, and the cap keeps the cost
+/// of that linear.
+const MAX_DEPTH: usize = 32;
+
+#[derive(NifUnitEnum, Clone, Copy, PartialEq)]
+enum MfmMode {
+    /// Functions in the allowlist become classed spans.
+    Html,
+    /// Every function is reduced to its text.
+    Strip,
+}
+
+/// An open `$[` in the output.
+struct Frame {
+    /// Where the function's text starts in the output.
+    at: usize,
+    /// The opening syntax, `$[flip.h `, restored if the function is never
+    /// closed.
+    syntax: String,
+    /// The span classes, or `None` if the function has none.
+    classes: Option<String>,
+    /// Whether a tag occurs in the function's text.
+    tagged: bool,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// The classes of function `name` with `args`, or `None` if it has none.
+/// Arguments without a class of their own (`speed=2s`) are ignored.
+fn classes(name: &str, args: &str) -> Option<String> {
+    let class = format!("mfm-{name}");
+    if !MARKDOWN_SPAN_CLASSES.contains(&class.as_str()) {
+        return None;
+    }
+    let mut classes = vec![class];
+    for arg in args.split(',').filter(|arg| !arg.contains('=')) {
+        let class = format!("mfm-{name}-{arg}");
+        if MARKDOWN_SPAN_CLASSES.contains(&class.as_str()) && !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    Some(classes.join(" "))
+}
+
+/// The function opening at the start of `text` (after `$[`): its name, its
+/// arguments, and the length of the syntax up to its text.
+fn opening(text: &str) -> Option<(&str, &str, usize)> {
+    let name_len = text.find(|c: char| !is_name_char(c)).unwrap_or(text.len());
+    let name = &text[..name_len];
+    let rest = &text[name_len..];
+    let args = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let end = rest
+                .find(|c: char| !(is_name_char(c) || matches!(c, '.' | ',' | '=' | '-' | '+')))
+                .unwrap_or(rest.len());
+            &rest[..end]
+        }
+        None => "",
+    };
+    let syntax_len = name_len + if rest.starts_with('.') { args.len() + 1 } else { 0 };
+    let after = &text[syntax_len..];
+    // The text is separated by one space, or a line break.
+    let space = after.chars().next().filter(|&c| c == ' ' || c == '\n')?;
+    (!name.is_empty()).then_some((name, args, syntax_len + space.len_utf8()))
+}
+
+/// The length of the tag at the start of `html`, minding quoted attribute
+/// values, which may contain `>`.
+pub(crate) fn tag_len(html: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in html.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+fn render(html: &str, mode: MfmMode) -> String {
+    if !html.contains("$[") {
+        return html.to_string();
+    }
+
+    let mut out = String::with_capacity(html.len());
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut rest = html;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            let len = tag_len(rest);
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            frames.iter_mut().for_each(|frame| frame.tagged = true);
+            continue;
+        }
+        let opened = rest.strip_prefix("$[").filter(|_| frames.len() < MAX_DEPTH);
+        if let Some((name, args, len)) = opened.and_then(opening) {
+            frames.push(Frame {
+                at: out.len(),
+                syntax: rest[..len + 2].to_string(),
+                classes: classes(name, args),
+                tagged: false,
+            });
+            rest = &rest[len + 2..];
+            continue;
+        }
+        if c == ']' {
+            if let Some(frame) = frames.pop() {
+                match (&frame.classes, frame.tagged) {
+                    (Some(classes), false) if mode == MfmMode::Html => {
+                        out.insert_str(frame.at, &format!("<span class=\"{classes}\">"));
+                        out.push_str("</span>");
+                    }
+                    _ => {}
+                }
+                rest = &rest[1..];
+                continue;
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    // Later frames start later, so restoring them first leaves the offsets
+    // of earlier ones valid.
+    while let Some(frame) = frames.pop() {
+        out.insert_str(frame.at, &frame.syntax);
+    }
+    out
+}
+
+/// `html` with every MFM function reduced to its text.
+pub(crate) fn strip(html: &str) -> String {
+    render(html, MfmMode::Strip)
+}
+
+/// Convert the MFM functions in sanitized `html` to classed spans
+/// (`:html`), or reduce them to their text (`:strip`).
+#[rustler::nif]
+fn render_mfm(html: Binary, mode: MfmMode) -> Guarded<String> {
+    guard(|| render(&String::from_utf8_lossy(&html), mode))
+}
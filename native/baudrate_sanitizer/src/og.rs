@@ -0,0 +1,118 @@
+//! Open Graph and Twitter Card `<meta>` tags.
+//!
+//! Titles and descriptions come from user content, with markup, entities,
+//! quotes, and line breaks in them.  Each text value is reduced to one line
+//! of plain text as `sanitize_inline/2` does, truncated, and escaped for a
+//! double-quoted attribute; URL values are kept as written but dropped if
+//! they could not be a URL.  The result is embedded in `<head>` as it is.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::inline;
+use rustler::{Decoder, NifResult, Term};
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Longest description kept, in graphemes, as in `LinkedData.excerpt/1`.
+const MAX_DESCRIPTION: usize = 200;
+
+/// Longest other text value kept, in graphemes.
+const MAX_TEXT: usize = 300;
+
+/// `{property, content}` pairs, from a list in order or from a map sorted by
+/// property; `nil` contents are skipped.
+struct Tags(Vec<(String, Option<String>)>);
+
+impl<'a> Decoder<'a> for Tags {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if !term.is_map() {
+            return Ok(Self(term.decode()?));
+        }
+        let mut tags: Vec<(String, Option<String>)> =
+            term.decode::<HashMap<String, Option<String>>>()?.into_iter().collect();
+        tags.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Ok(Self(tags))
+    }
+}
+
+fn is_property_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, ':' | '_' | '-' | '.')
+}
+
+fn is_property(property: &str) -> bool {
+    property.starts_with(|c: char| c.is_ascii_lowercase()) && property.chars().all(is_property_char)
+}
+
+/// `og:url`, `og:image`, `og:image:secure_url`, `twitter:image`, ...
+fn is_url_property(property: &str) -> bool {
+    let last = property.rsplit(':').next().unwrap_or_default();
+    matches!(property, "og:image" | "og:video" | "og:audio" | "twitter:image" | "twitter:player")
+        || matches!(last, "url" | "secure_url")
+}
+
+fn is_description(property: &str) -> bool {
+    property.rsplit(':').next() == Some("description")
+}
+
+/// `text` cut to `max` graphemes, marked with `…` when cut.
+pub(crate) fn truncate(text: String, max: usize) -> String {
+    match text.grapheme_indices(true).nth(max) {
+        Some((cut, _)) => format!("{}…", text[..cut].trim_end()),
+        None => text,
+    }
+}
+
+fn escape(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// The content of `property`, or `None` if nothing of it is left.
+fn content(property: &str, value: &str) -> Option<String> {
+    if is_url_property(property) {
+        let url = value.trim();
+        let valid = !url.is_empty() && !url.chars().any(|c| c.is_whitespace() || c.is_control());
+        return valid.then(|| url.to_string());
+    }
+    let max = if is_description(property) { MAX_DESCRIPTION } else { MAX_TEXT };
+    let text = truncate(inline::line(value), max);
+    (!text.is_empty()).then_some(text)
+}
+
+fn build(tags: &Tags) -> String {
+    let mut out = String::new();
+    for (property, value) in &tags.0 {
+        let Some(value) = value else { continue };
+        let Some(content) = is_property(property).then(|| content(property, value)).flatten()
+        else {
+            continue;
+        };
+        // Twitter reads `name`; `profile:*` tags have always been written so.
+        let attribute = if property.starts_with("twitter:") || property.starts_with("profile:") {
+            "name"
+        } else {
+            "property"
+        };
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("<meta {attribute}=\"{property}\" content=\""));
+        escape(&content, &mut out);
+        out.push_str("\">");
+    }
+    out
+}
+
+/// Render `tags`, a list of `{property, content}` pairs or a map, as
+/// `<meta>` tags, one per line.  Invalid properties, `nil` contents, and
+/// contents left empty are skipped.
+#[rustler::nif]
+fn build_og_meta(tags: Tags) -> Guarded<String> {
+    guard(|| build(&tags))
+}
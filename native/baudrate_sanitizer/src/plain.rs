@@ -0,0 +1,40 @@
+//! Fast path for markup-free input.
+//!
+//! Most short statuses are plain text.  Text without `<`, `&`, NUL, CR, or
+//! a leading byte order mark parses to a single text node that every policy keeps, so the sanitized
+//! result is the input with `>` and U+00A0 escaped the way html5ever
+//! serializes them.  Such input is recognised with memchr and escaped
+//! directly, skipping the parse.
+
+use memchr::{memchr, memchr2, memchr3};
+
+/// Whether `html` can take the fast path.  `&` is excluded so that entity
+/// decoding and re-escaping stay with the parser.
+pub(crate) fn is_plain(html: &str) -> bool {
+    let bytes = html.as_bytes();
+    memchr3(b'<', b'&', b'\0', bytes).is_none()
+        && memchr(b'\r', bytes).is_none()
+        && !html.starts_with('\u{feff}')
+}
+
+/// Escape markup-free `html` as Ammonia would serialize it, or `None` for
+/// input that needs the parser.
+pub(crate) fn escape(html: &str) -> Option<String> {
+    if !is_plain(html) {
+        return None;
+    }
+    // 0xC2 is the lead byte of U+00A0 (and of other U+0080..U+00BF).
+    if memchr2(b'>', 0xC2, html.as_bytes()).is_none() {
+        return Some(html.to_string());
+    }
+
+    let mut escaped = String::with_capacity(html.len() + 8);
+    for c in html.chars() {
+        match c {
+            '>' => escaped.push_str("&gt;"),
+            '\u{a0}' => escaped.push_str("&nbsp;"),
+            c => escaped.push(c),
+        }
+    }
+    Some(escaped)
+}
@@ -0,0 +1,443 @@
+//! The sanitizer allowlists as data.
+//!
+//! The Ammonia builders in `lib.rs` are configured from these tables, and
+//! `describe_policy/1` returns them to Elixir, so what the admin UI and
+//! tests display is exactly what the sanitizer enforces.
+//!
+//! Classes can also be allowed at runtime with `set_allowed_classes/2`, so
+//! that new markup conventions of other fediverse software can be kept
+//! without a release.  Every tag of a policy may carry `class`; the
+//! attribute filters keep only the classes of the tables below and of the
+//! runtime allowlist.
+//!
+//! `code` classes name the language of a block, and renderers spell that
+//! differently: `language-rust`, `hljs language-rust`, `sourceCode rust`.
+//! Each policy has a list of code class patterns, replaceable with
+//! `set_code_classes/2`, and the language a pattern finds is kept as
+//! `language-*`, the class the Markdown renderer and the stylesheets use.
+//!
+//! Long-form articles link to their own sections with `href="#section-2"`.
+//! Where a policy keeps such links, heading `id`s are kept for them to land
+//! on, both prefixed with [`FRAGMENT_PREFIX`] so that a post cannot take an
+//! id the page itself uses.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::{CUSTOM_EMOJI_CLASS, MARKDOWN_SPAN_CLASSES, SAFE_ANCHOR_CLASSES, SAFE_SPAN_CLASSES};
+use ammonia::{Builder, UrlRelative};
+use rustler::{Atom, Decoder, MapIterator, NifMap, NifResult, NifUnitEnum, Term};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{PoisonError, RwLock};
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        tag_not_allowed,
+        invalid_class,
+        invalid_pattern,
+    }
+}
+
+/// The built-in policies, passed from Elixir as `:federation` or `:markdown`.
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum PolicyName {
+    Federation,
+    Markdown,
+}
+
+impl PolicyName {
+    pub(crate) fn policy(self) -> &'static Policy {
+        match self {
+            PolicyName::Federation => &FEDERATION,
+            PolicyName::Markdown => &MARKDOWN,
+        }
+    }
+
+    fn extra_classes(self) -> &'static RwLock<Classes> {
+        &EXTRA_CLASSES[self as usize]
+    }
+
+    fn code_classes(self) -> &'static RwLock<Option<Vec<String>>> {
+        &CODE_CLASSES[self as usize]
+    }
+
+    /// The code class patterns in effect: those set with
+    /// `set_code_classes/2`, or the policy's own.
+    fn code_patterns(self) -> Vec<String> {
+        let patterns = self.code_classes().read().unwrap_or_else(PoisonError::into_inner);
+        patterns.clone().unwrap_or_else(|| list(self.policy().code_classes))
+    }
+}
+
+/// Allowed classes by tag.
+type Classes = BTreeMap<String, BTreeSet<String>>;
+
+/// The classes allowed by `set_allowed_classes/2`, by policy.
+static EXTRA_CLASSES: [RwLock<Classes>; 2] = [const { RwLock::new(BTreeMap::new()) }; 2];
+
+/// The code class patterns set with `set_code_classes/2`, by policy; `None`
+/// for the policy's own.
+static CODE_CLASSES: [RwLock<Option<Vec<String>>>; 2] = [const { RwLock::new(None) }; 2];
+
+/// Whether classes allowed at runtime can keep `class` on `tag` under `name`.
+pub(crate) fn has_extra_classes(name: PolicyName, tag: &str) -> bool {
+    let extra = name.extra_classes().read().unwrap_or_else(PoisonError::into_inner);
+    extra.contains_key(tag) || (tag == "code" && !name.code_patterns().is_empty())
+}
+
+/// The `class` attribute `value` of a `tag` element with only the classes
+/// that `allowed` or the runtime allowlist of `name` permit, or `None` if
+/// none are left.
+pub(crate) fn filter_classes<'a>(
+    name: PolicyName,
+    tag: &str,
+    value: &'a str,
+    allowed: impl Fn(&str) -> bool,
+) -> Option<Cow<'a, str>> {
+    let extra = name.extra_classes().read().unwrap_or_else(PoisonError::into_inner);
+    let extra = extra.get(tag);
+    let kept: Vec<&str> = value
+        .split_whitespace()
+        .filter(|&c| allowed(c) || extra.is_some_and(|extra| extra.contains(c)))
+        .collect();
+    if kept.is_empty() {
+        None
+    } else {
+        Some(Cow::Owned(kept.join(" ")))
+    }
+}
+
+fn is_language_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || "_+-".contains(c))
+}
+
+/// The language `classes` name under `pattern`, if they match it: every
+/// class of the pattern but the one ending in `*` must be present, and that
+/// one's prefix, possibly empty, starts the class naming the language.
+fn pattern_language<'a>(pattern: &str, classes: &[&'a str]) -> Option<&'a str> {
+    let mut markers = Vec::new();
+    let mut prefix = None;
+    for token in pattern.split_whitespace() {
+        match token.strip_suffix('*') {
+            Some(start) => prefix = Some(start),
+            None if classes.contains(&token) => markers.push(token),
+            None => return None,
+        }
+    }
+    let prefix = prefix?;
+    classes
+        .iter()
+        .filter(|c| !markers.contains(c))
+        .filter_map(|c| c.strip_prefix(prefix))
+        .find(|language| is_language_name(language))
+}
+
+/// The `class` attribute `value` of a `code` element under `name`: the
+/// language the first matching code class pattern finds, as `language-*`,
+/// then the classes of the runtime allowlist; `None` if neither is left.
+pub(crate) fn filter_code_classes<'a>(name: PolicyName, value: &'a str) -> Option<Cow<'a, str>> {
+    let classes: Vec<&str> = value.split_whitespace().collect();
+    let patterns = name.code_patterns();
+    let language = patterns.iter().find_map(|p| pattern_language(p, &classes));
+    let language = language.map(|language| format!("language-{language}"));
+    let extra = filter_classes(name, "code", value, |_| false);
+    let extra = extra.as_deref().unwrap_or_default().split_whitespace();
+    let kept: Vec<&str> = language
+        .as_deref()
+        .into_iter()
+        .chain(extra.filter(|&c| Some(c) != language.as_deref()))
+        .collect();
+    if kept.is_empty() {
+        None
+    } else if kept.join(" ") == value {
+        Some(Cow::Borrowed(value))
+    } else {
+        Some(Cow::Owned(kept.join(" ")))
+    }
+}
+
+/// How relative URLs in `href` / `src` are treated.
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum RelativeUrls {
+    /// The attribute is removed.
+    Deny,
+    /// The URL is kept as written.
+    PassThrough,
+}
+
+pub(crate) struct Policy {
+    pub(crate) tags: &'static [&'static str],
+    pub(crate) tag_attributes: &'static [(&'static str, &'static [&'static str])],
+    /// Allowed `class` values per tag, for display; the filters themselves
+    /// live with each builder.
+    pub(crate) classes: &'static [(&'static str, &'static [&'static str])],
+    /// Patterns for the language class of `code`, where `*` stands for a
+    /// language name of `[A-Za-z0-9_+-]` characters.
+    pub(crate) code_classes: &'static [&'static str],
+    pub(crate) url_schemes: &'static [&'static str],
+    pub(crate) relative_urls: RelativeUrls,
+    /// Whether same-document `#fragment` links and heading `id`s are kept.
+    pub(crate) fragment_links: bool,
+    pub(crate) link_rel: &'static str,
+}
+
+/// Prepended to kept `id`s and to the fragments linking to them.
+pub(crate) const FRAGMENT_PREFIX: &str = "user-content-";
+
+/// Longest fragment kept, in characters, without the prefix.
+const MAX_FRAGMENT_CHARS: usize = 128;
+
+/// Whether `name` is safe as an `id` and fragment: letters, digits, and
+/// `-_.:%`, as heading slugs and percent-encoded fragments are written.
+fn is_fragment_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_FRAGMENT_CHARS
+        && name.chars().all(|c| c.is_alphanumeric() || "-_.:%".contains(c))
+}
+
+/// An `id` already carrying [`FRAGMENT_PREFIX`], if its name is safe.
+pub(crate) fn filter_id(value: &str) -> Option<Cow<'_, str>> {
+    let name = value.strip_prefix(FRAGMENT_PREFIX)?;
+    is_fragment_name(name).then_some(Cow::Borrowed(value))
+}
+
+/// A same-document link `#name` as `#` + [`FRAGMENT_PREFIX`] + `name`, if
+/// the name is safe; links already carrying the prefix, and `#` alone for
+/// the top of the page, are kept as they are.
+pub(crate) fn filter_fragment(value: &str) -> Option<Cow<'_, str>> {
+    let fragment = value.strip_prefix('#')?;
+    let name = fragment.strip_prefix(FRAGMENT_PREFIX).unwrap_or(fragment);
+    if fragment.is_empty() {
+        Some(Cow::Borrowed(value))
+    } else if !is_fragment_name(name) {
+        None
+    } else if name.len() < fragment.len() {
+        Some(Cow::Borrowed(value))
+    } else {
+        Some(Cow::Owned(format!("#{FRAGMENT_PREFIX}{name}")))
+    }
+}
+
+/// Attributes allowed on every element (Ammonia's default set).
+pub(crate) const GENERIC_ATTRIBUTES: &[&str] = &["lang", "title"];
+
+/// Elements removed together with their content, in every policy.
+pub(crate) const CLEAN_CONTENT_TAGS: &[&str] = &[
+    "script", "style", "iframe", "object", "embed", "form", "input", "textarea", "svg", "math",
+];
+
+/// Incoming ActivityPub content.
+pub(crate) const FEDERATION: Policy = Policy {
+    tags: &[
+        "p", "br", "wbr", "hr", "h1", "h2", "h3", "h4", "h5", "h6", "em", "strong", "del",
+        "code", "pre", "blockquote", "ul", "ol", "li", "a", "span", "time",
+    ],
+    tag_attributes: &[("a", &["href", "class"]), ("span", &["class"]), ("time", &["datetime"])],
+    classes: &[("a", SAFE_ANCHOR_CLASSES), ("span", SAFE_SPAN_CLASSES)],
+    code_classes: &[],
+    url_schemes: &["http", "https"],
+    relative_urls: RelativeUrls::Deny,
+    fragment_links: false,
+    link_rel: "nofollow noopener noreferrer",
+};
+
+/// Locally rendered Markdown: the federation tags plus tables and images.
+pub(crate) const MARKDOWN: Policy = Policy {
+    tags: &[
+        "p", "br", "wbr", "hr", "h1", "h2", "h3", "h4", "h5", "h6", "em", "strong", "del",
+        "code", "pre", "blockquote", "ul", "ol", "li", "a", "span", "time", "table", "thead",
+        "tbody", "tr", "th", "td", "img",
+    ],
+    tag_attributes: &[
+        ("a", &["href"]),
+        ("code", &["class"]),
+        // Targets of fragment links.
+        ("h1", &["id"]),
+        ("h2", &["id"]),
+        ("h3", &["id"]),
+        ("h4", &["id"]),
+        ("h5", &["id"]),
+        ("h6", &["id"]),
+        ("img", &["src", "alt", "title", "class"]),
+        // Set from fence info strings; see `annotate_code_fences` in
+        // `baudrate_text`.
+        ("pre", &["data-filename", "data-highlight-lines"]),
+        ("span", &["class"]),
+        ("time", &["datetime"]),
+    ],
+    classes: &[("img", &[CUSTOM_EMOJI_CLASS]), ("span", MARKDOWN_SPAN_CLASSES)],
+    code_classes: &["language-*"],
+    url_schemes: &["http", "https", "mailto"],
+    relative_urls: RelativeUrls::PassThrough,
+    fragment_links: true,
+    link_rel: "nofollow noopener",
+};
+
+impl Policy {
+    /// An Ammonia builder with everything but the class filter configured.
+    /// `class` is allowed on every tag, for the filter to narrow down.
+    pub(crate) fn builder(&self) -> Builder<'static> {
+        let mut tag_attributes: HashMap<&str, HashSet<&str>> =
+            self.tags.iter().map(|&tag| (tag, HashSet::from(["class"]))).collect();
+        for &(tag, attributes) in self.tag_attributes {
+            tag_attributes.entry(tag).or_default().extend(attributes);
+        }
+
+        let mut builder = Builder::new();
+        builder
+            .tags(self.tags.iter().copied().collect())
+            .generic_attributes(GENERIC_ATTRIBUTES.iter().copied().collect())
+            .tag_attributes(tag_attributes)
+            .url_schemes(self.url_schemes.iter().copied().collect())
+            .url_relative(match self.relative_urls {
+                RelativeUrls::Deny => UrlRelative::Deny,
+                RelativeUrls::PassThrough => UrlRelative::PassThrough,
+            })
+            .link_rel(Some(self.link_rel))
+            .id_prefix(self.fragment_links.then_some(FRAGMENT_PREFIX))
+            .clean_content_tags(CLEAN_CONTENT_TAGS.iter().copied().collect())
+            .strip_comments(true);
+        builder
+    }
+}
+
+#[derive(NifMap)]
+struct Description {
+    tags: Vec<String>,
+    generic_attributes: Vec<String>,
+    attributes: HashMap<String, Vec<String>>,
+    classes: HashMap<String, Vec<String>>,
+    url_schemes: Vec<String>,
+    relative_urls: RelativeUrls,
+    fragment_links: bool,
+    link_rel: String,
+    clean_content_tags: Vec<String>,
+    strip_comments: bool,
+}
+
+fn list(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn table(rows: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+    rows.iter()
+        .map(|&(tag, values)| (tag.to_string(), list(values)))
+        .collect()
+}
+
+/// Return the effective allowlist of a built-in policy as a map, with the
+/// code class patterns and the classes allowed at runtime.
+#[rustler::nif]
+fn describe_policy(name: PolicyName) -> Guarded<Description> {
+    guard(|| {
+        let policy = name.policy();
+        let mut attributes = table(policy.tag_attributes);
+        let mut classes = table(policy.classes);
+        let mut allow = |tag: &str, allowed: &mut dyn Iterator<Item = &String>| {
+            let tag_attributes = attributes.entry(tag.to_string()).or_default();
+            if !tag_attributes.iter().any(|a| a == "class") {
+                tag_attributes.push("class".to_string());
+            }
+            let tag_classes = classes.entry(tag.to_string()).or_default();
+            for class in allowed {
+                if !tag_classes.contains(class) {
+                    tag_classes.push(class.clone());
+                }
+            }
+        };
+        let code_patterns = name.code_patterns();
+        if !code_patterns.is_empty() {
+            allow("code", &mut code_patterns.iter());
+        }
+        let extra = name.extra_classes().read().unwrap_or_else(PoisonError::into_inner);
+        for (tag, extra) in extra.iter() {
+            allow(tag, &mut extra.iter());
+        }
+
+        Description {
+            tags: list(policy.tags),
+            generic_attributes: list(GENERIC_ATTRIBUTES),
+            attributes,
+            classes,
+            url_schemes: list(policy.url_schemes),
+            relative_urls: policy.relative_urls,
+            fragment_links: policy.fragment_links,
+            link_rel: policy.link_rel.to_string(),
+            clean_content_tags: list(CLEAN_CONTENT_TAGS),
+            strip_comments: true,
+        }
+    })
+}
+
+/// A map of tag, as an atom or a string, to class names.
+struct ClassTable(Vec<(String, Vec<String>)>);
+
+impl<'a> Decoder<'a> for ClassTable {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let entries = MapIterator::new(term).ok_or(rustler::Error::BadArg)?;
+        let entries = entries.map(|(tag, classes)| {
+            let tag = if tag.is_atom() { tag.atom_to_string()? } else { tag.decode()? };
+            Ok((tag, classes.decode()?))
+        });
+        Ok(Self(entries.collect::<NifResult<_>>()?))
+    }
+}
+
+fn is_class_name(class: &str) -> bool {
+    !class.is_empty() && !class.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Allow `classes`, a map of tag to class names, on `name` in addition to
+/// its own, replacing what a previous call allowed.  Returns
+/// `{:error, {:tag_not_allowed, tag}}` for a tag the policy does not keep
+/// and `{:error, {:invalid_class, class}}` for an empty class or one with
+/// whitespace, leaving the allowlist unchanged.
+#[rustler::nif]
+fn set_allowed_classes(
+    name: PolicyName,
+    classes: ClassTable,
+) -> Guarded<Result<Atom, (Atom, String)>> {
+    guard(|| {
+        let policy = name.policy();
+        let mut allowed = Classes::new();
+        for (tag, tag_classes) in classes.0 {
+            let tag = tag.to_ascii_lowercase();
+            if !policy.tags.contains(&tag.as_str()) {
+                return Err((atoms::tag_not_allowed(), tag));
+            }
+            if let Some(class) = tag_classes.iter().find(|class| !is_class_name(class)) {
+                return Err((atoms::invalid_class(), class.clone()));
+            }
+            if !tag_classes.is_empty() {
+                allowed.entry(tag).or_default().extend(tag_classes);
+            }
+        }
+        *name.extra_classes().write().unwrap_or_else(PoisonError::into_inner) = allowed;
+        Ok(atoms::ok())
+    })
+}
+
+/// Whether `pattern` is class names with exactly one ending in `*`.
+fn is_code_pattern(pattern: &str) -> bool {
+    let tokens: Vec<&str> = pattern.split_whitespace().collect();
+    let wildcards = tokens.iter().filter(|t| t.ends_with('*')).count();
+    let names = tokens.iter().map(|t| t.strip_suffix('*').unwrap_or(t));
+    wildcards == 1 && names.filter(|n| !n.is_empty()).all(|n| is_class_name(n) && !n.contains('*'))
+}
+
+/// Replace the code class patterns of `name`, or restore its own with
+/// `nil`.  Returns `{:error, {:invalid_pattern, pattern}}` for a pattern
+/// without exactly one class ending in `*`, leaving the patterns unchanged.
+#[rustler::nif]
+fn set_code_classes(
+    name: PolicyName,
+    patterns: Option<Vec<String>>,
+) -> Guarded<Result<Atom, (Atom, String)>> {
+    guard(|| {
+        if let Some(pattern) = patterns.iter().flatten().find(|p| !is_code_pattern(p)) {
+            return Err((atoms::invalid_pattern(), pattern.clone()));
+        }
+        *name.code_classes().write().unwrap_or_else(PoisonError::into_inner) = patterns;
+        Ok(atoms::ok())
+    })
+}
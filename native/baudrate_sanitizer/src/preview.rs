@@ -0,0 +1,144 @@
+//! Link preview cards as HTML, for feeds and messages built without
+//! templates.
+//!
+//! The fields come from `extract_card/2` of the fetched page: text as the
+//! page wrote it, entities and stray markup included.  Each text field is
+//! reduced to one line of plain text as `sanitize_inline/2` does, truncated,
+//! and escaped once.  The image is only taken from `image_path`, the proxied
+//! copy, so that the remote `image_url` never reaches a browser.
+//!
+//! The markup uses the Markdown policy's tags alone and is serialized as
+//! html5ever would, so `sanitize_markdown/1` returns it unchanged.  Without
+//! the whitespace:
+//!
+//! ```html
+//! <blockquote>
+//!   <p><a href="…" rel="nofollow noopener"><img src="…" alt=""><br><strong>Title</strong></a></p>
+//!   <p>Description</p>
+//!   <p>Site</p>
+//! </blockquote>
+//! ```
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::inline;
+use crate::og::truncate;
+use crate::status::escape_attribute;
+use rustler::{Atom, Decoder, NifResult, Term};
+use url::Url;
+
+mod atoms {
+    rustler::atoms! {
+        url,
+        title,
+        description,
+        site_name,
+        domain,
+        image_path,
+        invalid_url,
+    }
+}
+
+/// The `rel` of the link, as the Markdown policy sets it.
+const LINK_REL: &str = "nofollow noopener";
+
+/// Longest title kept, in graphemes.
+const MAX_TITLE: usize = 300;
+
+/// Longest description kept, in graphemes.
+const MAX_DESCRIPTION: usize = 300;
+
+/// Longest site name kept, in graphemes.
+const MAX_SITE: usize = 100;
+
+/// The fields of a card; any may be missing or `nil`.
+struct Card {
+    url: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    site_name: Option<String>,
+    domain: Option<String>,
+    image_path: Option<String>,
+}
+
+impl<'a> Decoder<'a> for Card {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let get = |key: Atom| term.map_get(key).ok().map_or(Ok(None), Term::decode);
+        Ok(Self {
+            url: get(atoms::url())?,
+            title: get(atoms::title())?,
+            description: get(atoms::description())?,
+            site_name: get(atoms::site_name())?,
+            domain: get(atoms::domain())?,
+            image_path: get(atoms::image_path())?,
+        })
+    }
+}
+
+/// Escape text content the way html5ever serializes it.
+fn escape_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\u{A0}' => out.push_str("&nbsp;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// `value` as one line of at most `max` graphemes, or `None` if empty.
+fn text(value: Option<&String>, max: usize) -> Option<String> {
+    let line = truncate(inline::line(value?), max);
+    (!line.is_empty()).then_some(line)
+}
+
+/// An absolute `http(s)` URL, or a path on this host: what the proxy
+/// serves.  Protocol-relative URLs would load from elsewhere.
+fn is_image_src(src: &str) -> bool {
+    if src.starts_with('/') {
+        return !src.starts_with("//") && !src.contains('\\');
+    }
+    Url::parse(src).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn build(card: &Card) -> Result<String, Atom> {
+    let href = card.url.as_deref().map(str::trim).unwrap_or_default();
+    let url = Url::parse(href).map_err(|_| atoms::invalid_url())?;
+    if !matches!(url.scheme(), "http" | "https") || href.chars().any(char::is_whitespace) {
+        return Err(atoms::invalid_url());
+    }
+    let title = text(card.title.as_ref(), MAX_TITLE).unwrap_or_else(|| href.to_string());
+    let site = text(card.site_name.as_ref(), MAX_SITE)
+        .or_else(|| text(card.domain.as_ref(), MAX_SITE))
+        .or_else(|| url.host_str().map(str::to_string));
+    let image = card.image_path.as_deref().map(str::trim).filter(|src| is_image_src(src));
+
+    let mut out = String::from("<blockquote><p><a href=\"");
+    escape_attribute(href, &mut out);
+    out.push_str(&format!("\" rel=\"{LINK_REL}\">"));
+    if let Some(src) = image {
+        out.push_str("<img src=\"");
+        escape_attribute(src, &mut out);
+        // The title follows in the same link, so the image is decorative.
+        out.push_str("\" alt=\"\"><br>");
+    }
+    out.push_str("<strong>");
+    escape_text(&title, &mut out);
+    out.push_str("</strong></a></p>");
+    for line in [text(card.description.as_ref(), MAX_DESCRIPTION), site].into_iter().flatten() {
+        out.push_str("<p>");
+        escape_text(&line, &mut out);
+        out.push_str("</p>");
+    }
+    out.push_str("</blockquote>");
+    Ok(out)
+}
+
+/// Build the HTML of a link preview card from `card`'s `url`, `title`,
+/// `description`, `site_name` (or `domain`, or the URL's host), and
+/// proxied `image_path`; `{:error, :invalid_url}` without an `http(s)` URL.
+#[rustler::nif]
+fn build_preview_card(card: Card) -> Guarded<Result<String, Atom>> {
+    guard(|| build(&card))
+}
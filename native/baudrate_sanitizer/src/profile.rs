@@ -0,0 +1,68 @@
+//! Profile field values with verifiable links.
+//!
+//! Mastodon marks a profile link verified when the linked page links back
+//! with `rel="me"` and the field's own link carries `rel="me"` as well.
+//! The value is written as plain text, so it is reduced to one line as
+//! `sanitize_inline/2` does, with any markup in it dropped, and its
+//! `http(s)` URLs are linked with the labels `format_link_label/1` builds.
+//! The name is plain text, as in an ActivityPub `PropertyValue`.
+//!
+//! The `rel` is the one verification needs, `me nofollow noopener`, not
+//! the federation policy's, so the value is sent as it is built rather than
+//! passed through `sanitize_federation/1`.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::inline;
+use crate::link;
+use crate::status::{escape_attribute, token_start, url_len};
+use rustler::NifMap;
+
+/// The `rel` of every link in a value.
+const LINK_REL: &str = "me nofollow noopener";
+
+#[derive(NifMap)]
+struct ProfileField {
+    name: String,
+    value: String,
+    /// The URLs linked in `value`, in order, for `extract_rel_me/2`.
+    links: Vec<String>,
+}
+
+fn build(name: &str, value: &str) -> ProfileField {
+    let text = inline::line(value);
+    let mut field = ProfileField { name: inline::line(name), value: String::new(), links: vec![] };
+    let mut text_start = 0;
+    let mut previous = None;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if c == 'h' && token_start(previous) {
+            if let Some(len) = url_len(&text[i..]) {
+                let url = &text[i..i + len];
+                link::escape(&text[text_start..i], &mut field.value);
+                field.value.push_str("<a href=\"");
+                escape_attribute(url, &mut field.value);
+                field.value.push_str(&format!("\" rel=\"{LINK_REL}\">"));
+                field.value.push_str(&link::label(url));
+                field.value.push_str("</a>");
+                if !field.links.iter().any(|link| link == url) {
+                    field.links.push(url.to_string());
+                }
+                i += len;
+                text_start = i;
+                previous = text[..i].chars().next_back();
+                continue;
+            }
+        }
+        previous = Some(c);
+        i += c.len_utf8();
+    }
+    link::escape(&text[text_start..], &mut field.value);
+    field
+}
+
+/// Build a profile field from what the user wrote: `name` as one line of
+/// plain text, and `value` as HTML with its URLs linked with `rel="me"`.
+#[rustler::nif]
+fn build_profile_field_html(name: &str, value: &str) -> Guarded<ProfileField> {
+    guard(|| build(name, value))
+}
@@ -0,0 +1,153 @@
+//! Normalizations for the HTML of particular fediverse software.
+//!
+//! Servers other than Mastodon send markup that the federation policy
+//! alone turns into noise: Misskey's MFM syntax, BBCode that Friendica
+//! leaves in its HTML, and the spoilers of Lemmy, whose `details` elements
+//! the policy unwraps into running text.  Pleroma's greentext spans need
+//! nothing, as the policy keeps their class.
+//! Given the sending software, `sanitize_federation/2` rewrites these into
+//! plain structure first.  The output is raw HTML that the common policy
+//! still cleans, so a quirk can never let anything through that the policy
+//! would not.
+
+use crate::mfm;
+use regex::{Captures, Regex};
+use rustler::NifUnitEnum;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+static BBCODE_RE: OnceLock<Regex> = OnceLock::new();
+static BBCODE_URL_RE: OnceLock<Regex> = OnceLock::new();
+static SUMMARY_RE: OnceLock<Regex> = OnceLock::new();
+static SPOILER_RE: OnceLock<Regex> = OnceLock::new();
+
+/// The software that sent a document, passed as `source:` to
+/// `sanitize_federation/2`.
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum Source {
+    Mastodon,
+    Pleroma,
+    Akkoma,
+    Misskey,
+    Friendica,
+    Lemmy,
+}
+
+fn bbcode_regex() -> &'static Regex {
+    BBCODE_RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\[(/?)(b|i|u|s|quote|code|url|img|size|color|font|center|spoiler)(=[^\]]*)?\]",
+        )
+        .unwrap()
+    })
+}
+
+// `[url]https://...[/url]`, whose text is also its target.
+fn bbcode_url_regex() -> &'static Regex {
+    BBCODE_URL_RE.get_or_init(|| Regex::new(r"(?i)\[url\]([^\[\s]+)\[/url\]").unwrap())
+}
+
+fn summary_regex() -> &'static Regex {
+    SUMMARY_RE.get_or_init(|| Regex::new(r"(?i)<summary(\s[^>]*)?>(.*?)</summary>").unwrap())
+}
+
+// A Markdown spoiler fence left as text: `::: spoiler Title`, or `:::`.
+fn spoiler_regex() -> &'static Regex {
+    SPOILER_RE.get_or_init(|| {
+        Regex::new(r"(?m)^[ \t]*:::[ \t]*(?:spoiler[ \t]+(.*?))?[ \t]*$").unwrap()
+    })
+}
+
+/// `html` with `f` applied to the text between its tags.
+fn map_text(html: &str, f: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while !rest.is_empty() {
+        let text_len = rest.find('<').unwrap_or(rest.len());
+        out.push_str(&f(&rest[..text_len]));
+        rest = &rest[text_len..];
+        if !rest.is_empty() {
+            let tag_len = mfm::tag_len(rest);
+            out.push_str(&rest[..tag_len]);
+            rest = &rest[tag_len..];
+        }
+    }
+    out
+}
+
+fn attribute(value: &str) -> String {
+    value.trim().replace('"', "&quot;")
+}
+
+/// One BBCode tag as HTML, or as nothing for tags without an allowed
+/// counterpart (`[u]`, `[size=...]`, `[img]`), whose text is kept.
+fn bbcode_tag(caps: &Captures, is_closed: &impl Fn(&str) -> bool) -> String {
+    let name = caps[2].to_ascii_lowercase();
+    let close = !caps[1].is_empty();
+    // An opening tag never closed is text, such as the index in `a[i]`.
+    if !close && !is_closed(&name) {
+        return caps[0].to_string();
+    }
+    let element = match name.as_str() {
+        "b" => "strong",
+        "i" => "em",
+        "s" => "del",
+        "quote" => "blockquote",
+        "code" => "code",
+        "url" => match (close, caps.get(3)) {
+            (true, _) => "a",
+            (false, Some(target)) => {
+                return format!(r#"<a href="{}">"#, attribute(&target.as_str()[1..]))
+            }
+            (false, None) => return String::new(),
+        },
+        _ => return String::new(),
+    };
+    if close {
+        format!("</{element}>")
+    } else {
+        format!("<{element}>")
+    }
+}
+
+/// Friendica BBCode as HTML.  Each tag is replaced on its own, and the
+/// parser pairs them up.
+fn friendica(html: &str) -> String {
+    if !html.contains('[') {
+        return html.to_string();
+    }
+    let lower = html.to_ascii_lowercase();
+    let is_closed = |name: &str| lower.contains(&format!("[/{name}]"));
+    map_text(html, |text| {
+        let text = bbcode_url_regex().replace_all(text, |caps: &Captures| {
+            format!(r#"<a href="{}">{}</a>"#, attribute(&caps[1]), &caps[1])
+        });
+        bbcode_regex()
+            .replace_all(&text, |caps: &Captures| bbcode_tag(caps, &is_closed))
+            .into_owned()
+    })
+}
+
+fn lemmy(html: &str) -> String {
+    let html = summary_regex().replace_all(html, "<p><strong>$2</strong></p>");
+    map_text(&html, |text| {
+        spoiler_regex()
+            .replace_all(text, |caps: &Captures| match caps.get(1) {
+                Some(title) if !title.as_str().is_empty() => {
+                    format!("<strong>{}</strong>", title.as_str())
+                }
+                _ => String::new(),
+            })
+            .into_owned()
+    })
+}
+
+/// Rewrite the quirks of `source` in `html`.
+pub(crate) fn normalize(html: &str, source: Source) -> Cow<'_, str> {
+    match source {
+        Source::Mastodon | Source::Pleroma | Source::Akkoma => Cow::Borrowed(html),
+        Source::Misskey => Cow::Owned(mfm::strip(html)),
+        Source::Friendica => Cow::Owned(friendica(html)),
+        Source::Lemmy => Cow::Owned(lemmy(html)),
+    }
+}
@@ -0,0 +1,186 @@
+//! Quote posts written into federated content.
+//!
+//! Software without native quotes puts them in the text: Misskey, Akkoma,
+//! and Pleroma end the post with `RE: <a href="...">...</a>`, Fedibird with
+//! `QT:`, and Mastodon wraps that line in a `quote-inline` span or paragraph
+//! so that clients rendering the quote can hide it.  `extract_quote/1`
+//! returns the quoted post's URL with the content without that line, so the
+//! quote can be shown as a card rather than as a bare link.
+//!
+//! The `quote-inline` class does not survive sanitizing, so this runs on
+//! content as received; the HTML it returns is sanitized as usual.
+
+use crate::dom::{self, element_name, Fragment, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use markup5ever_rcdom::{Handle, NodeData};
+use rustler::{Binary, NifMap};
+use std::rc::Rc;
+
+/// Markers that start a trailing quote line, matched case-insensitively.
+const MARKERS: &[&str] = &["re:", "qt:"];
+
+#[derive(NifMap)]
+struct Quote {
+    /// The quoted post's URL, as linked.
+    url: String,
+    /// The content without its quote line.
+    html: String,
+}
+
+fn attribute(node: &Handle, name: &str) -> Option<String> {
+    let NodeData::Element { attrs, .. } = &node.data else {
+        return None;
+    };
+    let attrs = attrs.borrow();
+    attrs.iter().find(|attr| &*attr.name.local == name).map(|attr| attr.value.to_string())
+}
+
+fn has_class(node: &Handle, class: &str) -> bool {
+    attribute(node, "class").is_some_and(|value| value.split_ascii_whitespace().any(|c| c == class))
+}
+
+/// The `href` of `node` if it is a link to an `http` or `https` URL.
+fn link_url(node: &Handle) -> Option<String> {
+    if element_name(node) != Some("a") {
+        return None;
+    }
+    let href = attribute(node, "href")?;
+    let href = href.trim();
+    let scheme = href.split_once("://")?.0;
+    (scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("http"))
+        .then(|| href.to_string())
+}
+
+/// Whitespace, `&nbsp;`, and comments, which end a post without showing.
+fn is_blank(node: &Handle) -> bool {
+    match &node.data {
+        NodeData::Text { contents } => contents.borrow().trim().is_empty(),
+        NodeData::Comment { .. } => true,
+        _ => false,
+    }
+}
+
+fn is_blank_or_break(node: &Handle) -> bool {
+    is_blank(node) || element_name(node) == Some("br")
+}
+
+/// Remove `node` from its parent, returning the parent.
+fn detach(node: &Handle) -> Option<Handle> {
+    let parent = node.parent.take()?.upgrade()?;
+    parent.children.borrow_mut().retain(|child| !Rc::ptr_eq(child, node));
+    Some(parent)
+}
+
+/// Drop the line breaks and blanks that end `node`, then `node` itself and
+/// its ancestors below `root` if nothing else is left in them.
+fn tidy(node: &Handle, root: &Handle) {
+    let mut node = node.clone();
+    loop {
+        let mut children = node.children.borrow_mut();
+        while children.last().is_some_and(is_blank_or_break) {
+            children.pop();
+        }
+        let empty = children.iter().all(is_blank_or_break);
+        drop(children);
+        if !empty || Rc::ptr_eq(&node, root) {
+            return;
+        }
+        let Some(parent) = detach(&node) else {
+            return;
+        };
+        node = parent;
+    }
+}
+
+/// The URL of the first link inside `node`.
+fn first_link_url(node: &Handle) -> Option<String> {
+    let mut url = None;
+    dom::walk(node, (), |link, ()| match link_url(link) {
+        Some(found) => {
+            url = Some(found);
+            Walk::Stop
+        }
+        None => Walk::Children(()),
+    });
+    url
+}
+
+/// The first `quote-inline` element holding a link, and the link's URL.
+fn quote_inline(root: &Handle) -> Option<(Handle, String)> {
+    let mut quote = None;
+    dom::walk(root, (), |node, ()| {
+        if has_class(node, "quote-inline") {
+            if let Some(url) = first_link_url(node) {
+                quote = Some((node.clone(), url));
+                return Walk::Stop;
+            }
+        }
+        Walk::Children(())
+    });
+    quote
+}
+
+/// `text` without a trailing `RE:` or `QT:` marker, or `None` if it does not
+/// end with one.
+fn strip_marker(text: &str) -> Option<&str> {
+    let text = text.trim_end();
+    let split = text.len().checked_sub(3)?;
+    let marker = text.get(split..)?;
+    let rest = &text[..split];
+    let standalone = rest.is_empty() || rest.ends_with(char::is_whitespace);
+    (standalone && MARKERS.iter().any(|m| marker.eq_ignore_ascii_case(m))).then_some(rest)
+}
+
+/// The URL of a trailing `RE: <a href>` line, removed from the tree.
+fn trailing_quote(root: &Handle) -> Option<String> {
+    let mut parent = root.clone();
+    loop {
+        let children = parent.children.borrow().clone();
+        let index = children.iter().rposition(|child| !is_blank(child))?;
+        let last = &children[index];
+        let Some(url) = link_url(last) else {
+            if !matches!(element_name(last), Some("p" | "div" | "span")) {
+                return None;
+            }
+            parent = last.clone();
+            continue;
+        };
+
+        let marker = &children[index.checked_sub(1)?];
+        let NodeData::Text { contents } = &marker.data else {
+            return None;
+        };
+        let rest = strip_marker(&contents.borrow())?.trim_end().to_string();
+        *contents.borrow_mut() = rest.into();
+        detach(last);
+        tidy(&parent, root);
+        return Some(url);
+    }
+}
+
+fn extract(html: &str) -> Option<Quote> {
+    let lowercase = html.to_ascii_lowercase();
+    if !lowercase.contains("quote-inline") && !MARKERS.iter().any(|m| lowercase.contains(m)) {
+        return None;
+    }
+    let fragment = Fragment::parse(html);
+    let root = fragment.root();
+    let url = match quote_inline(&root) {
+        Some((node, url)) => {
+            if let Some(parent) = detach(&node) {
+                tidy(&parent, &root);
+            }
+            url
+        }
+        None => trailing_quote(&root)?,
+    };
+    Some(Quote { url, html: fragment.serialize() })
+}
+
+/// Find the post quoted by `html`, from a `quote-inline` element or a
+/// trailing `RE:` / `QT:` link, and return its URL with `html` without
+/// that line; `None` if `html` quotes nothing.
+#[rustler::nif]
+fn extract_quote(html: Binary) -> Guarded<Option<Quote>> {
+    guard(|| extract(&String::from_utf8_lossy(&html)))
+}
@@ -0,0 +1,219 @@
+//! Cheap spam signals for incoming content, computed in one walk.
+//!
+//! Input may be HTML or plain text.  Elements are inspected for links,
+//! mentions, and custom emoji; text is scanned character by character for
+//! bare URLs, `@mentions`, capitals, repeated characters, emoji, and
+//! invisible characters.  The signals are combined into a `score` from 0.0
+//! to 1.0 with the weights below, so a policy can threshold either the
+//! score or the individual signals.
+
+use crate::dom::{self, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use crate::plain;
+use markup5ever_rcdom::{Handle, NodeData};
+use rustler::{Binary, NifMap};
+use std::collections::HashSet;
+use url::Url;
+
+/// Links allowed before they add to the score.
+const FREE_LINKS: u32 = 2;
+/// Mentions allowed before they add to the score.
+const FREE_MENTIONS: u32 = 3;
+/// Shortest run of one character counted in `repeated_runs`.
+const MIN_RUN: u32 = 5;
+/// Letters needed before the uppercase ratio counts.
+const MIN_LETTERS: u32 = 20;
+
+#[derive(NifMap, Default)]
+struct Signals {
+    score: f64,
+    /// `<a href>` links (other than mentions and hashtags) and bare URLs.
+    links: u32,
+    /// Distinct hosts among the links.
+    domains: u32,
+    /// Mention links and `@name` tokens in text.
+    mentions: u32,
+    /// Uppercase letters as a share of all letters.
+    uppercase_ratio: f64,
+    /// Longest run of one repeated non-whitespace character.
+    longest_run: u32,
+    /// Runs of at least `MIN_RUN` repeated characters.
+    repeated_runs: u32,
+    /// Emoji characters and custom emoji images.
+    emoji: u32,
+    /// Emoji as a share of the visible characters.
+    emoji_density: f64,
+    /// Zero-width, bidi-control, and tag characters.
+    invisible: u32,
+    /// Visible (non-whitespace) characters of text.
+    length: u32,
+}
+
+#[derive(Default)]
+struct Scan {
+    signals: Signals,
+    hosts: HashSet<String>,
+    letters: u32,
+    uppercase: u32,
+}
+
+impl Scan {
+    fn link(&mut self, url: &str) {
+        self.signals.links += 1;
+        if let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_lowercase)) {
+            self.hosts.insert(host);
+        }
+    }
+
+    fn element(&mut self, name: &str, attr: impl Fn(&str) -> Option<String>) {
+        let class = attr("class").unwrap_or_default();
+        let has_class = |c: &str| class.split_whitespace().any(|x| x == c);
+        match name {
+            "a" if has_class("hashtag") => {}
+            "a" if has_class("mention") => self.signals.mentions += 1,
+            "a" => {
+                if let Some(href) = attr("href") {
+                    self.link(&href);
+                }
+            }
+            "img" if has_class("custom-emoji") => self.signals.emoji += 1,
+            _ => {}
+        }
+    }
+
+    /// Scan a text node; `in_link` text is not searched for URLs or
+    /// mentions, which its element already accounted for.
+    fn text(&mut self, text: &str, in_link: bool) {
+        let mut run: (Option<char>, u32) = (None, 0);
+        let mut previous: Option<char> = None;
+
+        for (i, c) in text.char_indices() {
+            if !in_link && token_start(previous) {
+                if c == '@' && text[i + 1..].starts_with(is_name_char) {
+                    self.signals.mentions += 1;
+                } else if c == 'h' && is_url_start(&text[i..]) {
+                    let end = text[i..].find(is_url_end).map_or(text.len(), |n| i + n);
+                    self.link(&text[i..end]);
+                }
+            }
+            previous = Some(c);
+
+            if is_invisible(c) {
+                self.signals.invisible += 1;
+                continue;
+            }
+            if c.is_whitespace() {
+                run = (None, 0);
+                continue;
+            }
+
+            self.signals.length += 1;
+            if c.is_alphabetic() {
+                self.letters += 1;
+                if c.is_uppercase() {
+                    self.uppercase += 1;
+                }
+            }
+            if is_emoji(c) {
+                self.signals.emoji += 1;
+            }
+
+            run = if run.0 == Some(c) { (run.0, run.1 + 1) } else { (Some(c), 1) };
+            if run.1 == MIN_RUN {
+                self.signals.repeated_runs += 1;
+            }
+            self.signals.longest_run = self.signals.longest_run.max(run.1);
+        }
+    }
+
+    fn finish(mut self) -> Signals {
+        let signals = &mut self.signals;
+        signals.domains = self.hosts.len() as u32;
+        if self.letters > 0 {
+            signals.uppercase_ratio = f64::from(self.uppercase) / f64::from(self.letters);
+        }
+        if signals.length > 0 {
+            signals.emoji_density = f64::from(signals.emoji) / f64::from(signals.length);
+        }
+
+        let shouting = self.letters >= MIN_LETTERS && signals.uppercase_ratio > 0.7;
+        let score = (0.15 * f64::from(signals.links.saturating_sub(FREE_LINKS))).min(0.3)
+            + (0.1 * f64::from(signals.domains.saturating_sub(1))).min(0.2)
+            + (0.05 * f64::from(signals.mentions.saturating_sub(FREE_MENTIONS))).min(0.2)
+            + if shouting { 0.15 } else { 0.0 }
+            + (0.05 * f64::from(signals.repeated_runs)).min(0.1)
+            + if signals.emoji_density > 0.3 { 0.1 } else { 0.0 }
+            + if signals.invisible > 0 { 0.2 } else { 0.0 };
+        signals.score = score.min(1.0);
+        self.signals
+    }
+}
+
+fn token_start(previous: Option<char>) -> bool {
+    previous.is_none_or(|p| !p.is_alphanumeric() && p != '_' && p != '/')
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_url_start(text: &str) -> bool {
+    text.starts_with("https://") || text.starts_with("http://")
+}
+
+fn is_url_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'')
+}
+
+/// Characters that render as nothing.  ZWJ and variation selectors are
+/// left out, as they are part of ordinary emoji sequences.
+fn is_invisible(c: char) -> bool {
+    matches!(c,
+        '\u{00AD}' | '\u{034F}' | '\u{180E}' | '\u{200B}' | '\u{200C}' | '\u{200E}' | '\u{200F}'
+        | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2064}' | '\u{2066}'..='\u{2069}'
+        | '\u{FEFF}' | '\u{E0000}'..='\u{E007F}')
+}
+
+/// Pictographic emoji blocks; approximate, but without a table.
+fn is_emoji(c: char) -> bool {
+    matches!(c, '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' | '\u{1F000}'..='\u{1FAFF}')
+}
+
+fn scan_tree(scan: &mut Scan, root: &Handle) {
+    dom::walk(root, false, |node, in_link| match &node.data {
+        NodeData::Text { contents } => {
+            scan.text(&contents.borrow(), in_link);
+            Walk::Skip
+        }
+        NodeData::Element { name, attrs, .. } => {
+            let name: &str = &name.local;
+            if matches!(name, "script" | "style") {
+                return Walk::Skip;
+            }
+            let attrs = attrs.borrow();
+            scan.element(name, |key| {
+                attrs.iter().find(|a| &*a.name.local == key).map(|a| a.value.to_string())
+            });
+            Walk::Children(in_link || name == "a")
+        }
+        _ => Walk::Children(in_link),
+    });
+}
+
+/// Compute spam signals for `html_or_text`.
+///
+/// Returns a map of every signal plus a combined `score` from 0.0 to 1.0.
+#[rustler::nif]
+fn spam_score(html_or_text: Binary) -> Guarded<Signals> {
+    guard(|| {
+        let input = String::from_utf8_lossy(&html_or_text);
+        let mut scan = Scan::default();
+        if plain::is_plain(&input) {
+            scan.text(&input, false);
+        } else {
+            let fragment = dom::Fragment::parse(&input);
+            scan_tree(&mut scan, &fragment.root());
+        }
+        scan.finish()
+    })
+}
@@ -0,0 +1,122 @@
+//! Process-wide counters of sanitizer load, polled through `stats/0`.
+//!
+//! Counters are relaxed atomics: they are monotonic totals since the NIF
+//! library was loaded, read independently of each other, and never reset.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::NifMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The NIFs whose calls are counted.  Both arities of a function share one
+/// counter.
+#[derive(Clone, Copy)]
+pub(crate) enum Nif {
+    SanitizeFederation,
+    SanitizeMarkdown,
+    StripTags,
+    NormalizeFeedHtml,
+    Emojify,
+    ExtractShortcodes,
+    AnalyzeContent,
+}
+
+static CALLS: [AtomicU64; 7] = [const { AtomicU64::new(0) }; 7];
+static BYTES_PROCESSED: AtomicU64 = AtomicU64::new(0);
+static ELEMENTS_STRIPPED: AtomicU64 = AtomicU64::new(0);
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+
+/// Whether a NIF result is returned to Elixir as an error.
+pub(crate) trait Outcome {
+    fn is_error(&self) -> bool;
+}
+
+impl Outcome for String {
+    fn is_error(&self) -> bool {
+        false
+    }
+}
+
+impl Outcome for Vec<String> {
+    fn is_error(&self) -> bool {
+        false
+    }
+}
+
+impl<T, E> Outcome for Result<T, E> {
+    fn is_error(&self) -> bool {
+        self.is_err()
+    }
+}
+
+/// Run a NIF body through [`guard`], counting the call, its input size, and
+/// an error if it fails or panics.
+pub(crate) fn track<T: Outcome>(nif: Nif, bytes: usize, body: impl FnOnce() -> T) -> Guarded<T> {
+    CALLS[nif as usize].fetch_add(1, Ordering::Relaxed);
+    BYTES_PROCESSED.fetch_add(bytes as u64, Ordering::Relaxed);
+
+    let guarded = guard(body);
+    if !matches!(&guarded, Guarded::Returned(value) if !value.is_error()) {
+        ERRORS.fetch_add(1, Ordering::Relaxed);
+    }
+    guarded
+}
+
+/// Count the elements removed between `input` and the sanitized `output`.
+///
+/// Elements are counted by their start tags, a `<` followed by a letter.
+/// Sanitized output escapes every other `<`, so its count is exact; raw
+/// input can overcount slightly (e.g. `a<b` in broken markup).
+pub(crate) fn stripped(input: &str, output: &str) {
+    let removed = start_tags(input).saturating_sub(start_tags(output));
+    ELEMENTS_STRIPPED.fetch_add(removed, Ordering::Relaxed);
+}
+
+fn start_tags(html: &str) -> u64 {
+    html.as_bytes()
+        .windows(2)
+        .filter(|w| w[0] == b'<' && w[1].is_ascii_alphabetic())
+        .count() as u64
+}
+
+#[derive(NifMap)]
+struct Calls {
+    sanitize_federation: u64,
+    sanitize_markdown: u64,
+    strip_tags: u64,
+    normalize_feed_html: u64,
+    emojify: u64,
+    extract_shortcodes: u64,
+    analyze_content: u64,
+}
+
+#[derive(NifMap)]
+struct Stats {
+    calls: Calls,
+    bytes_processed: u64,
+    elements_stripped: u64,
+    errors: u64,
+}
+
+fn load(counter: &AtomicU64) -> u64 {
+    counter.load(Ordering::Relaxed)
+}
+
+/// Return the counters as `%{calls: %{nif => count}, bytes_processed: n,
+/// elements_stripped: n, errors: n}`.
+#[rustler::nif]
+fn stats() -> Guarded<Stats> {
+    guard(|| Stats {
+        calls: Calls {
+            sanitize_federation: load(&CALLS[Nif::SanitizeFederation as usize]),
+            sanitize_markdown: load(&CALLS[Nif::SanitizeMarkdown as usize]),
+            strip_tags: load(&CALLS[Nif::StripTags as usize]),
+            normalize_feed_html: load(&CALLS[Nif::NormalizeFeedHtml as usize]),
+            emojify: load(&CALLS[Nif::Emojify as usize]),
+            extract_shortcodes: load(&CALLS[Nif::ExtractShortcodes as usize]),
+            analyze_content: load(&CALLS[Nif::AnalyzeContent as usize]),
+        },
+        bytes_processed: load(&BYTES_PROCESSED),
+        elements_stripped: load(&ELEMENTS_STRIPPED),
+        errors: load(&ERRORS),
+    })
+}
@@ -0,0 +1,253 @@
+//! Outgoing status HTML from composer plain text.
+//!
+//! The text is escaped, split into paragraphs at blank lines with `<br>`
+//! for the line breaks within one, and its URLs, mentions, and hashtags
+//! become links in Mastodon's markup: link labels as `format_link_label/1`
+//! builds them, mentions as `h-card` spans, hashtags with the `hashtag`
+//! class.  `:shortcode:` emoji stay as text, as Mastodon sends them, and
+//! are reported for the `tag` array with the mentions and hashtags linked.
+//!
+//! Everything is serialized as html5ever would, with the `rel` the
+//! federation policy sets, so `sanitize_federation/1` returns the HTML
+//! unchanged and what is sent is what is shown.
+
+use crate::emoji;
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::hashtag;
+use crate::link;
+use rustler::{Binary, NifMap};
+use std::collections::HashMap;
+
+/// The `rel` of every link, as the federation policy sets it.
+const LINK_REL: &str = "nofollow noopener noreferrer";
+
+/// Longest hashtag linked, in characters, as in `Markdown.linkify_hashtags/1`.
+const MAX_TAG_CHARS: usize = 64;
+
+#[derive(NifMap)]
+struct Status {
+    html: String,
+    /// Profile URLs of the mentions linked, in order of first appearance.
+    mentions: Vec<String>,
+    /// Canonical forms of the hashtags linked, in order of first appearance.
+    tags: Vec<String>,
+    /// Shortcodes of the known emoji used, without colons.
+    emoji: Vec<String>,
+}
+
+struct Context<'a> {
+    /// Profile URLs by lowercased `user` or `user@host`.
+    mentions: &'a HashMap<String, String>,
+    /// Prefix of hashtag URLs, to which the canonical tag is appended.
+    tag_base: &'a str,
+    emoji: &'a [String],
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+/// Escape an attribute value the way html5ever serializes it.
+pub(crate) fn escape_attribute(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\u{A0}' => out.push_str("&nbsp;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn open_link(href: &str, class: Option<&str>, out: &mut String) {
+    out.push_str("<a href=\"");
+    escape_attribute(href, out);
+    out.push('"');
+    if let Some(class) = class {
+        out.push_str(&format!(" class=\"{class}\""));
+    }
+    out.push_str(&format!(" rel=\"{LINK_REL}\">"));
+}
+
+pub(crate) fn token_start(previous: Option<char>) -> bool {
+    previous.is_none_or(|p| !p.is_alphanumeric() && !matches!(p, '_' | '/' | '@' | '#'))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// The length of the URL starting `rest`, without trailing punctuation and
+/// closing parentheses it does not open.
+pub(crate) fn url_len(rest: &str) -> Option<usize> {
+    let scheme = ["https://", "http://"].into_iter().find(|s| rest.starts_with(s))?;
+    let end = rest.find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'));
+    let end = end.unwrap_or(rest.len());
+    let mut url = &rest[..end];
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ':', ';', '!', '?', '\'', '*']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(open) if open.matches('(').count() < trimmed.matches(')').count() => open,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            break;
+        }
+        url = trimmed;
+    }
+    (url.len() > scheme.len()).then_some(url.len())
+}
+
+/// The length of the `@user` or `@user@host` starting `rest`, and its
+/// username and lookup key.
+fn mention(rest: &str) -> Option<(usize, &str, String)> {
+    let name = rest.strip_prefix('@')?;
+    let name_len = name.find(|c: char| !is_name_char(c)).unwrap_or(name.len());
+    if name_len == 0 {
+        return None;
+    }
+    let mut len = 1 + name_len;
+    if let Some(host) = name[name_len..].strip_prefix('@') {
+        let host_len = host
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '-')
+            .unwrap_or(host.len());
+        let host = host[..host_len].trim_end_matches(['.', '-']);
+        if !host.contains('.') || host.starts_with(['.', '-']) {
+            return None;
+        }
+        len += 1 + host.len();
+    }
+    if rest[len..].starts_with(is_name_char) {
+        return None;
+    }
+    Some((len, &name[..name_len], rest[1..len].to_lowercase()))
+}
+
+/// The length of the `#tag` starting `rest`, and the tag.
+fn hashtag(rest: &str) -> Option<(usize, &str)> {
+    let tag = rest.strip_prefix('#')?;
+    if !tag.starts_with(char::is_alphabetic) {
+        return None;
+    }
+    let len = tag.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(tag.len());
+    let tag = &tag[..len];
+    (tag.chars().count() <= MAX_TAG_CHARS).then_some((1 + len, tag))
+}
+
+/// Escape plain text, collecting the known emoji in it.
+fn push_text(text: &str, context: &Context, status: &mut Status) {
+    for (start, end) in emoji::shortcode_ranges(text) {
+        let shortcode = &text[start + 1..end - 1];
+        if context.emoji.iter().any(|e| e == shortcode) {
+            push_unique(&mut status.emoji, shortcode.to_string());
+        }
+    }
+    for c in text.chars() {
+        match c {
+            '&' => status.html.push_str("&amp;"),
+            '<' => status.html.push_str("&lt;"),
+            '>' => status.html.push_str("&gt;"),
+            '\u{A0}' => status.html.push_str("&nbsp;"),
+            _ => status.html.push(c),
+        }
+    }
+}
+
+/// The markup of the link starting `rest`, if one does, and its length.
+fn link_at(rest: &str, context: &Context, status: &mut Status) -> Option<(usize, String)> {
+    let mut out = String::new();
+    if let Some(len) = url_len(rest) {
+        let url = &rest[..len];
+        open_link(url, None, &mut out);
+        out.push_str(&link::label(url));
+        out.push_str("</a>");
+        return Some((len, out));
+    }
+    if let Some((len, name, key)) = mention(rest) {
+        let url = context.mentions.get(&key)?;
+        out.push_str("<span class=\"h-card\">");
+        open_link(url, Some("u-url mention"), &mut out);
+        out.push_str("@<span>");
+        out.push_str(name);
+        out.push_str("</span></a></span>");
+        push_unique(&mut status.mentions, url.clone());
+        return Some((len, out));
+    }
+    let (len, tag) = hashtag(rest)?;
+    let (canonical, display) = hashtag::normalize(tag)?;
+    open_link(&format!("{}{canonical}", context.tag_base), Some("mention hashtag"), &mut out);
+    out.push_str("#<span>");
+    out.push_str(&display);
+    out.push_str("</span></a>");
+    push_unique(&mut status.tags, canonical);
+    Some((len, out))
+}
+
+fn push_line(line: &str, context: &Context, status: &mut Status) {
+    let mut text_start = 0;
+    let mut previous = None;
+    let mut i = 0;
+    while let Some(c) = line[i..].chars().next() {
+        if token_start(previous) && matches!(c, 'h' | '@' | '#') {
+            if let Some((len, html)) = link_at(&line[i..], context, status) {
+                push_text(&line[text_start..i], context, status);
+                status.html.push_str(&html);
+                i += len;
+                text_start = i;
+                previous = line[..i].chars().next_back();
+                continue;
+            }
+        }
+        previous = Some(c);
+        i += c.len_utf8();
+    }
+    push_text(&line[text_start..], context, status);
+}
+
+fn build(text: &str, context: &Context) -> Status {
+    let mut status = Status { html: String::new(), mentions: vec![], tags: vec![], emoji: vec![] };
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut paragraph: Vec<&str> = Vec::new();
+    let lines = text.split('\n').map(str::trim_end);
+    for line in lines.chain([""]) {
+        if !line.trim().is_empty() {
+            paragraph.push(line);
+            continue;
+        }
+        if paragraph.is_empty() {
+            continue;
+        }
+        status.html.push_str("<p>");
+        for (i, line) in paragraph.drain(..).enumerate() {
+            if i > 0 {
+                status.html.push_str("<br>");
+            }
+            push_line(line, context, &mut status);
+        }
+        status.html.push_str("</p>");
+    }
+    status
+}
+
+/// Build the HTML of an outgoing status from composer plain text, with
+/// `mentions` (lowercased `user` or `user@host` to profile URL) linked,
+/// hashtags linked to `tag_base` followed by the canonical tag, and the
+/// shortcodes of `emoji` that it uses reported.
+#[rustler::nif]
+fn build_status_html(
+    text: Binary,
+    mentions: HashMap<String, String>,
+    tag_base: String,
+    emoji: Vec<String>,
+) -> Guarded<Status> {
+    guard(|| {
+        let mentions = mentions
+            .into_iter()
+            .map(|(handle, url)| (handle.trim_start_matches('@').to_lowercase(), url))
+            .collect();
+        let context = Context { mentions: &mentions, tag_base: &tag_base, emoji: &emoji };
+        build(&String::from_utf8_lossy(&text), &context)
+    })
+}
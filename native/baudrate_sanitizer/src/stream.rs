@@ -0,0 +1,153 @@
+//! Chunked sanitization of documents too large to pass as one binary.
+//!
+//! Each stream owns a thread that gathers the chunks as they are pushed.
+//! At most [`MAX_QUEUED_CHUNKS`] chunks wait between the caller and the
+//! thread, so the caller never holds the whole document as one binary.
+//! Once the input ends, the thread cleans the document with the same
+//! [`Sanitizer`] as `sanitize_federation/1` or `sanitize_markdown/1`, so
+//! results, log events, and counters are identical to theirs.
+//!
+//! Dropping a stream without finishing it ends the input; its thread then
+//! exits on its own.  At most [`MAX_STREAMS`] threads run at once, so a
+//! flood of streams cannot exhaust the host's threads.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::policy::PolicyName;
+use crate::stats::{self, Nif};
+use crate::{log, Sanitizer, FEDERATION, MARKDOWN};
+use rustler::{Atom, Binary, Encoder, Env, Resource, ResourceArc, Term};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::thread;
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+        busy,
+        closed,
+        stream_failed,
+    }
+}
+
+/// Chunks that may be queued ahead of the thread before a push blocks.
+const MAX_QUEUED_CHUNKS: usize = 4;
+
+/// Stream threads that may run at once.
+const MAX_STREAMS: usize = 64;
+
+/// Stream threads currently running.
+static LIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// One of the [`MAX_STREAMS`] stream threads, given back when dropped.
+struct Slot;
+
+impl Slot {
+    fn take() -> Option<Self> {
+        LIVE_STREAMS
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |live| {
+                (live < MAX_STREAMS).then_some(live + 1)
+            })
+            .ok()
+            .map(|_| Slot)
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        LIVE_STREAMS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+pub(crate) struct Stream {
+    nif: Nif,
+    /// `None` once the stream has been finished.
+    chunks: Mutex<Option<SyncSender<Vec<u8>>>>,
+    result: Mutex<Receiver<(Guarded<String>, Vec<log::Event>)>>,
+    bytes: AtomicUsize,
+}
+
+#[rustler::resource_impl]
+impl Resource for Stream {}
+
+/// Start a stream sanitized with the `policy` rules, or `{:error, :busy}`
+/// while [`MAX_STREAMS`] streams are running.
+#[rustler::nif]
+fn stream_new(policy: PolicyName) -> Guarded<Result<ResourceArc<Stream>, Atom>> {
+    guard(|| {
+        let slot = Slot::take().ok_or_else(atoms::busy)?;
+        let sanitizer: &'static Sanitizer = match policy {
+            PolicyName::Federation => &FEDERATION,
+            PolicyName::Markdown => &MARKDOWN,
+        };
+        let (sender, receiver) = sync_channel::<Vec<u8>>(MAX_QUEUED_CHUNKS);
+        let (result_sender, result) = sync_channel(1);
+
+        thread::Builder::new()
+            .name("baudrate_sanitizer-stream".to_string())
+            .spawn(move || {
+                // The input ends when the sender is dropped.
+                let mut html = Vec::new();
+                for chunk in receiver {
+                    html.extend_from_slice(&chunk);
+                }
+                let cleaned = log::collect(|| guard(|| sanitizer.clean_lossy(&html)));
+                // Given back first, so that a finished stream no longer counts.
+                drop(slot);
+                let _ = result_sender.send(cleaned);
+            })
+            .map_err(|_| atoms::stream_failed())?;
+
+        Ok(ResourceArc::new(Stream {
+            nif: sanitizer.nif,
+            chunks: Mutex::new(Some(sender)),
+            result: Mutex::new(result),
+            bytes: AtomicUsize::new(0),
+        }))
+    })
+}
+
+/// Queue `chunk` for the stream's thread, blocking while the queue is full.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn stream_push<'a>(env: Env<'a>, stream: ResourceArc<Stream>, chunk: Binary) -> Guarded<Term<'a>> {
+    guard(|| {
+        let chunks = stream.chunks.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sender) = chunks.as_ref() else {
+            return (atoms::error(), atoms::closed()).encode(env);
+        };
+        // The thread takes chunks until the input ends, so this only fails
+        // if it is gone; `stream_finish/1` reports that.
+        if sender.send(chunk.as_slice().to_vec()).is_ok() {
+            stream.bytes.fetch_add(chunk.len(), Ordering::Relaxed);
+        }
+        atoms::ok().encode(env)
+    })
+}
+
+/// End the input and return the sanitized document.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn stream_finish(env: Env, stream: ResourceArc<Stream>) -> Guarded<Result<String, Atom>> {
+    let sender = stream.chunks.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let Some(sender) = sender else {
+        return Guarded::Returned(Err(atoms::closed()));
+    };
+    drop(sender);
+
+    let bytes = stream.bytes.load(Ordering::Relaxed);
+    let result = stats::track(stream.nif, bytes, || {
+        let received = stream.result.lock().unwrap_or_else(|e| e.into_inner()).recv();
+        let Ok((cleaned, events)) = received else {
+            return Err(atoms::stream_failed());
+        };
+        log::adopt(events);
+        match cleaned {
+            Guarded::Returned(html) => Ok(html),
+            // Re-raised so that it is counted and returned like any NIF panic.
+            Guarded::Panicked(message) => panic::resume_unwind(Box::new(message)),
+        }
+    });
+    log::flush(env);
+    result
+}
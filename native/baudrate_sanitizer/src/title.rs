@@ -0,0 +1,165 @@
+//! Titles for posts that arrive without one.
+//!
+//! Article-type objects should carry a `name`, but not all do.  The title
+//! is then the text of the first heading, or else the first sentence of the
+//! first paragraph.  Bodies without paragraphs are taken as plain text, with
+//! the first line standing in for the paragraph.  Entities are decoded by
+//! the parser, whitespace is collapsed, and the hidden parts of
+//! Mastodon-style link labels are left out.
+
+use crate::dom::{self, Visit, Walk};
+use baudrate_nif::guard::{guard, Guarded};
+use crate::plain;
+use markup5ever_rcdom::{Handle, NodeData};
+use rustler::Binary;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Longest title, in graphemes, before `…`; as in `TitleDeriver`.
+const MAX_GRAPHEMES: usize = 80;
+
+/// Elements whose text is never shown.
+fn is_hidden(node: &Handle) -> bool {
+    let NodeData::Element { name, attrs, .. } = &node.data else {
+        return false;
+    };
+    match &*name.local {
+        "script" | "style" | "template" => true,
+        "span" => attrs.borrow().iter().any(|a| {
+            &*a.name.local == "class" && a.value.split_whitespace().any(|c| c == "invisible")
+        }),
+        _ => false,
+    }
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre" | "blockquote" | "ul" | "ol"
+            | "li" | "hr" | "table" | "tr"
+    )
+}
+
+/// The text of `node`, with `br` and blocks as line breaks.  Newlines in the
+/// text itself are breaks too when `newlines` is set, and spaces otherwise.
+fn text(node: &Handle, newlines: bool) -> String {
+    let mut out = String::new();
+    let breaks = |node: &Handle| {
+        dom::element_name(node).is_some_and(|name| name == "br" || is_block(name))
+    };
+    dom::walk_with_leave(node, (), |visit| {
+        match visit {
+            Visit::Enter(node, ()) => match &node.data {
+                NodeData::Text { contents } => {
+                    let contents = contents.borrow();
+                    let space = |c| if c == '\n' && !newlines { ' ' } else { c };
+                    out.extend(contents.chars().map(space));
+                }
+                NodeData::Element { .. } if !is_hidden(node) => {
+                    if breaks(node) {
+                        out.push('\n');
+                    }
+                    return Walk::Children(());
+                }
+                _ => {}
+            },
+            Visit::Leave(node, ()) => {
+                if breaks(node) {
+                    out.push('\n');
+                }
+            }
+        }
+        Walk::Skip
+    });
+    out
+}
+
+/// `text` with runs of whitespace, no-break spaces included, as one space.
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn first_line(text: &str) -> Option<String> {
+    text.split('\n').map(collapse).find(|line| !line.is_empty())
+}
+
+/// `line` up to the end of its first sentence: `.`, `!`, or `?` before a
+/// space, or a CJK full stop or mark.  A single closing period is dropped.
+fn first_sentence(line: &str) -> &str {
+    let mut chars = line.char_indices().peekable();
+    let mut end = line.len();
+    while let Some((i, c)) = chars.next() {
+        let next_is_space = chars.peek().is_some_and(|&(_, next)| next == ' ');
+        if matches!(c, '。' | '！' | '？') || (matches!(c, '.' | '!' | '?') && next_is_space) {
+            end = i + c.len_utf8();
+            break;
+        }
+    }
+    let sentence = &line[..end];
+    match sentence.strip_suffix(['.', '。']) {
+        Some(rest) if !rest.ends_with('.') && !rest.is_empty() => rest,
+        _ => sentence,
+    }
+}
+
+/// Cut `title` to [`MAX_GRAPHEMES`], at the last space when it has one, and
+/// mark the cut with `…`.
+fn truncate(title: &str) -> String {
+    let Some((cut, _)) = title.grapheme_indices(true).nth(MAX_GRAPHEMES) else {
+        return title.to_string();
+    };
+    let chunk = &title[..cut];
+    let chunk = match chunk.rfind(' ') {
+        Some(space) if !title[cut..].starts_with(' ') => &chunk[..space],
+        _ => chunk,
+    };
+    let chunk = chunk.trim_end_matches([' ', ',', ';', ':', '、', '，']);
+    format!("{chunk}…")
+}
+
+fn derive(html: &str) -> Option<String> {
+    let line = if plain::is_plain(html) {
+        first_line(html)?
+    } else {
+        let fragment = dom::Fragment::parse(html);
+        let root = fragment.root();
+        let mut heading = None;
+        let mut paragraph = None;
+        dom::walk(&root, (), |node, ()| {
+            if is_hidden(node) {
+                return Walk::Skip;
+            }
+            match dom::element_name(node) {
+                Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                    let text = collapse(&text(node, false));
+                    if !text.is_empty() {
+                        heading = Some(text);
+                        return Walk::Stop;
+                    }
+                }
+                Some("p") => {
+                    if paragraph.is_none() {
+                        paragraph = first_line(&text(node, false));
+                    }
+                }
+                _ => return Walk::Children(()),
+            }
+            Walk::Skip
+        });
+        if let Some(heading) = heading {
+            return Some(truncate(&heading));
+        }
+        match paragraph {
+            Some(paragraph) => paragraph,
+            None => first_line(&text(&root, true))?,
+        }
+    };
+    Some(truncate(first_sentence(&line)))
+}
+
+/// A title for an HTML (or plain text) body: the first heading, else the
+/// first sentence of the first paragraph, at most 80 graphemes.  Returns
+/// `nil` for a body without text.
+#[rustler::nif]
+fn derive_title(html: Binary) -> Guarded<Option<String>> {
+    guard(|| derive(&String::from_utf8_lossy(&html)))
+}
@@ -0,0 +1,30 @@
+//! Decoding of HTML bodies that may not be valid UTF-8.
+
+use rustler::{Atom, NifUnitEnum};
+use std::borrow::Cow;
+
+mod atoms {
+    rustler::atoms! {
+        invalid_utf8,
+    }
+}
+
+/// What to do with input that is not valid UTF-8, passed from Elixir as
+/// `:lossy` or `:error`.
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum InvalidUtf8 {
+    /// Replace each invalid sequence with U+FFFD.
+    Lossy,
+    /// Reject the input with `:invalid_utf8`.
+    Error,
+}
+
+/// Decode `bytes` as UTF-8 according to `policy`.
+pub(crate) fn decode(bytes: &[u8], policy: InvalidUtf8) -> Result<Cow<'_, str>, Atom> {
+    match policy {
+        InvalidUtf8::Lossy => Ok(String::from_utf8_lossy(bytes)),
+        InvalidUtf8::Error => std::str::from_utf8(bytes)
+            .map(Cow::Borrowed)
+            .map_err(|_| atoms::invalid_utf8()),
+    }
+}
@@ -0,0 +1,142 @@
+//! WebFinger account identifier parsing, and whether two identifiers or
+//! profile URLs name the same actor.
+
+use baudrate_nif::guard::{guard, Guarded};
+use caseless::default_case_fold_str;
+use percent_encoding::percent_decode_str;
+use rustler::Atom;
+use unicode_normalization::UnicodeNormalization;
+use url::Url;
+
+mod atoms {
+    rustler::atoms! {
+        invalid_resource,
+        invalid_user,
+        invalid_host,
+    }
+}
+
+const MAX_USER_LEN: usize = 255;
+
+/// Profile and actor URL paths that end in the username: Mastodon and
+/// GoToSocial (`/@`, `/users/`), Baudrate (`/ap/users/`), Lemmy (`/u/`),
+/// Friendica (`/profile/`), and PeerTube (`/accounts/`).
+const PROFILE_PATHS: &[&str] = &["/@", "/users/", "/ap/users/", "/u/", "/profile/", "/accounts/"];
+
+/// Split `resource` into raw `(user, host)` parts.  Accepted forms:
+/// `acct:user@host`, `user@host`, `@user@host`, and `http(s)://host` with
+/// one of `paths` and the user.
+fn split(resource: &str, paths: &[&str]) -> Result<(String, String), Atom> {
+    if resource.starts_with("https://") || resource.starts_with("http://") {
+        let url = Url::parse(resource).map_err(|_| atoms::invalid_host())?;
+        let host = url.host_str().ok_or_else(atoms::invalid_host)?;
+        let host = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_owned(),
+        };
+        let path = url.path();
+        let user = paths
+            .iter()
+            .find_map(|prefix| path.strip_prefix(prefix))
+            .map(|user| user.trim_end_matches('/'))
+            .ok_or_else(atoms::invalid_resource)?;
+        let user = percent_decode_str(user).decode_utf8().map_err(|_| atoms::invalid_user())?;
+        return Ok((user.into_owned(), host));
+    }
+
+    let acct = match resource.strip_prefix("acct:") {
+        Some(acct) => acct,
+        None => resource.strip_prefix('@').unwrap_or(resource),
+    };
+    let (user, host) = acct.rsplit_once('@').ok_or_else(atoms::invalid_resource)?;
+    Ok((user.to_owned(), host.to_owned()))
+}
+
+fn normalize_user(user: &str) -> Result<String, Atom> {
+    let valid = !user.is_empty()
+        && user.len() <= MAX_USER_LEN
+        && !user
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '@' | '/' | ':'));
+
+    if valid {
+        Ok(default_case_fold_str(user).nfc().collect())
+    } else {
+        Err(atoms::invalid_user())
+    }
+}
+
+/// IDNA-encode (and thereby lowercase) the host, keeping any explicit port.
+fn normalize_host(host: &str) -> Result<String, Atom> {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) => {
+            port.parse::<u16>().map_err(|_| atoms::invalid_host())?;
+            (name, Some(port))
+        }
+        None => (host, None),
+    };
+
+    if name.is_empty() {
+        return Err(atoms::invalid_host());
+    }
+
+    let ascii = idna::domain_to_ascii_strict(name).map_err(|_| atoms::invalid_host())?;
+
+    Ok(match port {
+        Some(port) => format!("{ascii}:{port}"),
+        None => ascii,
+    })
+}
+
+fn parse(resource: &str, paths: &[&str]) -> Result<(String, String), Atom> {
+    let (user, host) = split(resource.trim(), paths)?;
+    Ok((normalize_user(&user)?, normalize_host(&host)?))
+}
+
+/// What an identifier is compared by: its user and host if it has them,
+/// or else the URL itself, normalized.
+#[derive(PartialEq)]
+enum Actor {
+    Acct(String, String),
+    Url(String),
+}
+
+fn actor(resource: &str) -> Option<Actor> {
+    if let Ok((user, host)) = parse(resource, PROFILE_PATHS) {
+        return Some(Actor::Acct(user, host));
+    }
+    let mut url = Url::parse(resource.trim()).ok()?;
+    if !matches!(url.scheme(), "https" | "http") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(Actor::Url(url.as_str().trim_end_matches('/').to_owned()))
+}
+
+/// Parse a WebFinger resource into a normalized `{user, host}` pair.
+#[rustler::nif]
+fn parse_acct(resource: &str) -> Guarded<Result<(String, String), Atom>> {
+    guard(|| parse(resource, &["/@"]))
+}
+
+/// Whether `a` and `b` name the same actor: the same user on the same host,
+/// as an account or a URL with any of [`PROFILE_PATHS`], or else the same
+/// URL.
+#[rustler::nif(name = "same_actor?")]
+fn same_actor(a: &str, b: &str) -> Guarded<bool> {
+    guard(|| actor(a).is_some_and(|a| actor(b).is_some_and(|b| a == b)))
+}
+
+/// The profile and actor URLs an account is commonly found at, one for
+/// each of [`PROFILE_PATHS`].
+#[rustler::nif]
+fn actor_url_forms(acct: &str) -> Guarded<Result<Vec<String>, Atom>> {
+    guard(|| {
+        let (user, host) = parse(acct, &["/@"])?;
+        let urls = PROFILE_PATHS.iter().filter_map(|path| {
+            let url = Url::parse(&format!("https://{host}{path}{user}")).ok()?;
+            Some(url.to_string())
+        });
+        Ok(urls.collect())
+    })
+}
@@ -0,0 +1,61 @@
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::Atom;
+
+mod atoms {
+    rustler::atoms! {
+        html,
+    }
+}
+
+/// First Strong Isolate — direction is inferred from the isolated content.
+const FSI: char = '\u{2068}';
+/// Pop Directional Isolate — terminates the isolate opened by `FSI`.
+const PDI: char = '\u{2069}';
+
+/// Explicit directional formatting characters (embeddings, overrides, and
+/// isolates).  Implicit marks (LRM / RLM / ALM) are left alone because they
+/// cannot affect text outside the isolate.
+fn is_explicit_directional(c: char) -> bool {
+    matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Strip explicit directional overrides from untrusted `text` and isolate it.
+///
+/// In `:text` mode the result is wrapped in FSI / PDI so that the embedded
+/// text cannot reorder the surrounding UI text.  In `:html` mode the text is
+/// HTML-escaped and wrapped in `<span dir="auto">`, which browsers render as
+/// a bidi isolate (`unicode-bidi: isolate` in the UA stylesheet).
+#[rustler::nif]
+fn bidi_isolate(text: &str, mode: Atom) -> Guarded<String> {
+    guard(|| {
+        let stripped: String = text
+            .chars()
+            .filter(|&c| !is_explicit_directional(c))
+            .collect();
+
+        if mode == atoms::html() {
+            format!("<span dir=\"auto\">{}</span>", escape_html(&stripped))
+        } else {
+            let mut out = String::with_capacity(stripped.len() + 6);
+            out.push(FSI);
+            out.push_str(&stripped);
+            out.push(PDI);
+            out
+        }
+    })
+}
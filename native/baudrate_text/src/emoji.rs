@@ -0,0 +1,60 @@
+use baudrate_nif::guard::{guard, Guarded};
+
+/// Longest shortcode we try to resolve; longer `:...:` runs are left as-is.
+const MAX_SHORTCODE_LEN: usize = 64;
+
+fn is_shortcode_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'+' | b'-')
+}
+
+/// Find the end (exclusive, pointing at the closing `:`) of a shortcode body
+/// starting at `start`, or `None` if the bytes do not form a shortcode.
+fn shortcode_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start;
+    while i < bytes.len() && i - start <= MAX_SHORTCODE_LEN {
+        match bytes[i] {
+            b':' if i > start => return Some(i),
+            b if is_shortcode_char(b) => i += 1,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Replace `:shortcode:` tokens (gemoji names, e.g. `:joy:`, `:+1:`) with
+/// the corresponding Unicode emoji.  Unknown shortcodes are left untouched.
+fn replace(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b':' {
+            i += 1;
+            continue;
+        }
+
+        if let Some(end) = shortcode_end(bytes, i + 1) {
+            if let Some(emoji) = emojis::get_by_shortcode(&text[i + 1..end]) {
+                out.push_str(&text[copied..i]);
+                out.push_str(emoji.as_str());
+                i = end + 1;
+                copied = i;
+                continue;
+            }
+        }
+
+        // Not a known shortcode — the colon may still open the next one
+        // (e.g. "10:30:joy:").
+        i += 1;
+    }
+
+    out.push_str(&text[copied..]);
+    out
+}
+
+#[rustler::nif]
+fn replace_shortcodes(text: &str) -> Guarded<String> {
+    guard(|| replace(text))
+}
@@ -0,0 +1,260 @@
+//! Info strings of fenced code blocks in Markdown.
+//!
+//! Authors annotate fences the way their static site generator reads them:
+//! ```` ```rust linenos title=main.rs ````, ```` ```py hl_lines="1 3-5" ````,
+//! ```` ```js {1,3-5} ````, or Qiita's ```` ```ruby:app.rb ````.  Earmark
+//! only keeps the first word, as a class the sanitizer does not allow, so
+//! fences with an info string are written out as HTML blocks before it
+//! runs:
+//!
+//! ```text
+//! <pre data-filename="main.rs" data-highlight-lines="1,3-5"><code class="language-rust">…
+//! ```
+//!
+//! The `language-*` class and both `data-*` attributes pass the Markdown
+//! sanitizer; other options (`linenos`) are dropped.  Only fences at the
+//! margin are rewritten; indented ones, as in list items and blockquotes,
+//! are left to Earmark.
+//!
+//! In `diff` and `patch` blocks each added, removed, hunk, and file header
+//! line is wrapped in a `span` with a class from [`DIFF_CLASSES`], so that
+//! patches are readable without a highlighter on the client.
+
+use crate::bidi::escape_html;
+use baudrate_nif::guard::{guard, Guarded};
+
+/// Most ranges kept in `data-highlight-lines`.
+const MAX_RANGES: usize = 64;
+
+/// Span classes of diff lines: added, removed, hunk header, file header.
+/// The Markdown sanitizer allows exactly these.
+const DIFF_CLASSES: [&str; 4] = ["diff-ins", "diff-del", "diff-hunk", "diff-header"];
+
+#[derive(Default)]
+struct Info {
+    language: Option<String>,
+    filename: Option<String>,
+    highlight_lines: Option<String>,
+}
+
+/// The opening fence of a code block at the margin: its character, its
+/// length, and the info string.
+fn opening(line: &str) -> Option<(char, usize, &str)> {
+    let fence = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let length = line.len() - line.trim_start_matches(fence).len();
+    let info = line[length..].trim();
+    // A backtick fence's info string may not contain backticks, or the line
+    // would be an inline code span.
+    if length < 3 || (fence == '`' && info.contains('`')) {
+        return None;
+    }
+    Some((fence, length, info))
+}
+
+fn is_closing(line: &str, fence: char, length: usize) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    let run = trimmed.len() - trimmed.trim_start_matches(fence).len();
+    line.len() - trimmed.len() <= 3 && run >= length && trimmed[run..].trim().is_empty()
+}
+
+fn is_language(word: &str) -> bool {
+    !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'))
+}
+
+/// Split an info string into words, keeping quoted values and `{...}`
+/// groups whole.
+fn words(info: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut until = None;
+    for c in info.chars() {
+        match until {
+            Some(end) if c == end => {
+                until = None;
+                if end == '}' {
+                    word.push(c);
+                }
+            }
+            Some(_) => word.push(c),
+            None if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            None if c == '"' || c == '\'' => until = Some(c),
+            None if c == '{' => {
+                until = Some('}');
+                word.push(c);
+            }
+            None => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Line numbers and ranges (`1,3-5`, `1 3-5`) as `1,3-5`, or `None` if any
+/// of them is malformed.
+fn highlight_lines(value: &str) -> Option<String> {
+    let mut ranges = Vec::new();
+    for part in value.split([',', ' ']).filter(|part| !part.is_empty()) {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let start: u32 = start.parse().ok().filter(|&n| n > 0)?;
+        let end: u32 = end.parse().ok().filter(|&n| n >= start)?;
+        ranges.push(if start == end { start.to_string() } else { format!("{start}-{end}") });
+    }
+    (!ranges.is_empty()).then(|| ranges.into_iter().take(MAX_RANGES).collect::<Vec<_>>().join(","))
+}
+
+fn parse_info(info: &str) -> Info {
+    let mut parsed = Info::default();
+    for (i, word) in words(info).into_iter().enumerate() {
+        if let Some(group) = word.strip_prefix('{').and_then(|word| word.strip_suffix('}')) {
+            parsed.highlight_lines = highlight_lines(group).or(parsed.highlight_lines);
+            continue;
+        }
+        match word.split_once('=') {
+            Some(("title" | "filename" | "file", value)) if !value.trim().is_empty() => {
+                parsed.filename = Some(value.trim().to_string());
+            }
+            Some(("hl_lines" | "highlight" | "hl" | "mark", value)) => {
+                parsed.highlight_lines = highlight_lines(value).or(parsed.highlight_lines);
+            }
+            Some(_) => {}
+            None if i == 0 => {
+                let (language, filename) = match word.split_once(':') {
+                    Some((language, filename)) => (language, Some(filename)),
+                    None => (word.as_str(), None),
+                };
+                parsed.language = is_language(language).then(|| language.to_string());
+                parsed.filename = filename.filter(|f| !f.is_empty()).map(str::to_string);
+            }
+            None => {}
+        }
+    }
+    parsed
+}
+
+/// The class of each line of a unified (or bare `+`/`-`) diff, if any.
+fn diff_classes(lines: &[&str]) -> Vec<Option<&'static str>> {
+    let [ins, del, hunk, header] = DIFF_CLASSES;
+    // Until a `diff`/`index` line, lines are read as hunk lines, so that a
+    // bare list of `+`/`-` lines is marked up too.
+    let mut in_hunk = true;
+    let mut classes = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let next = lines.get(i + 1).copied().unwrap_or_default();
+        let class = if line.starts_with("diff ") || line.starts_with("index ") {
+            in_hunk = false;
+            Some(header)
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+            Some(hunk)
+        } else if line.starts_with("--- ") && next.starts_with("+++ ") {
+            in_hunk = false;
+            Some(header)
+        } else if !in_hunk {
+            Some(header)
+        } else if line.starts_with('+') {
+            Some(ins)
+        } else if line.starts_with('-') {
+            Some(del)
+        } else {
+            None
+        };
+        classes.push(class);
+    }
+    classes
+}
+
+fn push_block(out: &mut String, info: &Info, lines: &[&str]) {
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+    out.push_str("<pre");
+    if let Some(filename) = &info.filename {
+        out.push_str(&format!(" data-filename=\"{}\"", escape_html(filename)));
+    }
+    if let Some(lines) = &info.highlight_lines {
+        out.push_str(&format!(" data-highlight-lines=\"{lines}\""));
+    }
+    out.push_str("><code");
+    if let Some(language) = &info.language {
+        out.push_str(&format!(" class=\"language-{language}\""));
+    }
+    out.push('>');
+    if matches!(info.language.as_deref(), Some("diff" | "patch")) {
+        for (i, (line, class)) in lines.iter().zip(diff_classes(lines)).enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            match class {
+                Some(class) => {
+                    out.push_str(&format!("<span class=\"{class}\">{}</span>", escape_html(line)))
+                }
+                None => out.push_str(&escape_html(line)),
+            }
+        }
+    } else {
+        out.push_str(&escape_html(&lines.join("\n")));
+    }
+    // Earmark ends an HTML block at a line opening with its closing tag.
+    out.push_str("</code>\n</pre>\n\n");
+}
+
+fn annotate(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.split('\n').collect();
+    let mut out = String::with_capacity(markdown.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((fence, length, info)) = opening(lines[i]) else {
+            out.push_str(lines[i]);
+            i += 1;
+            if i < lines.len() {
+                out.push('\n');
+            }
+            continue;
+        };
+
+        // Unclosed fences run to the end of the text.
+        let start = i + 1;
+        let end = lines[start..]
+            .iter()
+            .position(|line| is_closing(line, fence, length))
+            .map_or(lines.len(), |n| start + n);
+        let next = (end + 1).min(lines.len());
+        if info.is_empty() {
+            // Kept as it is, so that its lines are not taken for fences.
+            out.push_str(&lines[i..next].join("\n"));
+            if next < lines.len() {
+                out.push('\n');
+            }
+        } else {
+            let mut body: Vec<&str> =
+                lines[start..end].iter().map(|l| l.strip_suffix('\r').unwrap_or(l)).collect();
+            if end == lines.len() && body.last() == Some(&"") {
+                body.pop();
+            }
+            push_block(&mut out, &parse_info(info), &body);
+        }
+        i = next;
+    }
+    out
+}
+
+/// Rewrite the fenced code blocks of `markdown` that have an info string
+/// as HTML `pre` blocks carrying their language, filename, and highlighted
+/// lines.  Other text is returned unchanged.
+#[rustler::nif]
+fn annotate_code_fences(markdown: &str) -> Guarded<String> {
+    guard(|| {
+        if markdown.contains("```") || markdown.contains("~~~") {
+            annotate(markdown)
+        } else {
+            markdown.to_string()
+        }
+    })
+}
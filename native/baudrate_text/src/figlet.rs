@@ -0,0 +1,69 @@
+//! FIGlet banners for board headers and welcome screens.
+//!
+//! The fonts are the classic ones embedded in `figlet-rs`, each parsed once
+//! on first use.
+
+use crate::bidi::escape_html;
+use baudrate_nif::guard::{guard, Guarded};
+use figlet_rs::FIGlet;
+use rustler::{Atom, NifUnitEnum};
+use std::sync::OnceLock;
+
+mod atoms {
+    rustler::atoms! {
+        text_too_long,
+    }
+}
+
+/// Longest banner text, in characters; wider banners no longer fit a
+/// screen.
+const MAX_CHARS: usize = 64;
+
+/// Embedded fonts, passed from Elixir as atoms.
+#[derive(NifUnitEnum, Clone, Copy)]
+enum Font {
+    Standard,
+    Small,
+    Slant,
+    Big,
+}
+
+static FONTS: [OnceLock<FIGlet>; 4] = [const { OnceLock::new() }; 4];
+
+impl Font {
+    fn get(self) -> &'static FIGlet {
+        FONTS[self as usize].get_or_init(|| {
+            match self {
+                Font::Standard => FIGlet::standard(),
+                Font::Small => FIGlet::small(),
+                Font::Slant => FIGlet::slant(),
+                Font::Big => FIGlet::big(),
+            }
+            .expect("embedded FIGlet font parses")
+        })
+    }
+}
+
+fn render(text: &str, font: Font) -> String {
+    let Some(figure) = font.get().convert(text) else {
+        return String::new();
+    };
+    let figure = figure.to_string();
+    let lines: Vec<&str> = figure.lines().map(str::trim_end).collect();
+    let height = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+    escape_html(&lines[..height].join("\n"))
+}
+
+/// Render `text` as a FIGlet banner in `font`, HTML-escaped for a `<pre>`.
+///
+/// Returns `{:ok, banner}` or `{:error, :text_too_long}`.  Characters the
+/// font lacks are skipped, so text with none it can draw gives `""`.
+#[rustler::nif]
+fn figlet(text: &str, font: Font) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        if text.chars().count() > MAX_CHARS {
+            return Err(atoms::text_too_long());
+        }
+        Ok(render(text, font))
+    })
+}
@@ -0,0 +1,156 @@
+//! RFC 3676 `format=flowed` plain text, for email and NNTP-style gateways.
+//!
+//! A flowed line ends in a space: the reader joins it with the next line
+//! and may rewrap the paragraph to its screen.  Quote depth is the number
+//! of leading `>`, never joined across.  A line that starts with a space,
+//! `>`, or `From ` after its quote marks is space-stuffed (one more space
+//! in front, removed on reading), and `-- ` (the signature separator) is
+//! never flowed.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::Atom;
+
+mod atoms {
+    rustler::atoms! {
+        invalid_width,
+    }
+}
+
+/// RFC 5322's limit on a line, excluding the CRLF.
+const MAX_WIDTH: usize = 998;
+
+const SIGNATURE_SEPARATOR: &str = "-- ";
+
+/// Split plain `line` into its quote depth and text.  `> > a` and `>> a`
+/// both have depth 2, and one space after the marks is dropped.
+fn unquote(line: &str) -> (usize, &str) {
+    let mut depth = 0;
+    let mut rest = line;
+    while let Some(after) = rest.strip_prefix('>') {
+        depth += 1;
+        rest = after;
+        if rest.starts_with(" >") {
+            rest = &rest[1..];
+        }
+    }
+    if depth > 0 {
+        rest = rest.strip_prefix(' ').unwrap_or(rest);
+    }
+    (depth, rest)
+}
+
+/// Append one physical line of `text` at `depth` to `out`.
+fn push_line(out: &mut String, depth: usize, text: &str) {
+    out.extend(std::iter::repeat_n('>', depth));
+    // Quoted text always gets the conventional space, which is stuffing.
+    if (depth > 0 && !text.is_empty()) || text.starts_with([' ', '>']) || text.starts_with("From ") {
+        out.push(' ');
+    }
+    out.push_str(text);
+    out.push_str("\r\n");
+}
+
+fn encode(text: &str, width: usize) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / width + 2);
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (depth, text) = unquote(line);
+        if text == SIGNATURE_SEPARATOR {
+            push_line(&mut out, depth, text);
+            continue;
+        }
+
+        // Trailing spaces would make a fixed line flowed.
+        let mut rest = text.trim_end_matches(' ');
+        let prefix = depth + usize::from(depth > 0);
+        let room = width.saturating_sub(prefix).max(1);
+        loop {
+            // Break after the last space that keeps the line within
+            // `room` characters, or after the first one if a word is
+            // longer than that; never inside a word.
+            let limit = rest.char_indices().nth(room).map_or(rest.len(), |(i, _)| i);
+            if limit == rest.len() {
+                push_line(&mut out, depth, rest);
+                break;
+            }
+            let end = rest[..limit]
+                .rfind(' ')
+                .filter(|&i| i > 0)
+                .or_else(|| rest[limit..].find(' ').map(|i| limit + i));
+            let Some(end) = end.map(|i| i + 1) else {
+                push_line(&mut out, depth, rest);
+                break;
+            };
+            push_line(&mut out, depth, &rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    // The split yields one line more than there are line breaks.
+    out.truncate(out.len() - 2);
+    out
+}
+
+fn decode(text: &str, delsp: bool) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    // The logical line being joined, and its depth.
+    let mut paragraph: Option<(usize, String)> = None;
+
+    let finish = |lines: &mut Vec<String>, depth: usize, text: &str| {
+        let mut line = ">".repeat(depth);
+        if depth > 0 && !text.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(text);
+        lines.push(line);
+    };
+
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let depth = line.bytes().take_while(|&b| b == b'>').count();
+        let content = &line[depth..];
+        let content = content.strip_prefix(' ').unwrap_or(content);
+        let flowed = content.ends_with(' ') && content != SIGNATURE_SEPARATOR;
+
+        // A change of depth ends the paragraph even after a flowed line.
+        if let Some((open_depth, open)) = paragraph.take() {
+            if open_depth == depth {
+                paragraph = Some((depth, open));
+            } else {
+                finish(&mut lines, open_depth, &open);
+            }
+        }
+        let (_, joined) = paragraph.get_or_insert_with(|| (depth, String::new()));
+        let content = if flowed && delsp { &content[..content.len() - 1] } else { content };
+        joined.push_str(content);
+
+        if !flowed {
+            if let Some((depth, joined)) = paragraph.take() {
+                finish(&mut lines, depth, &joined);
+            }
+        }
+    }
+    if let Some((depth, joined)) = paragraph {
+        finish(&mut lines, depth, &joined);
+    }
+    lines.join("\n")
+}
+
+/// Encode plain `text` as `format=flowed` with lines of at most `width`
+/// characters (where words allow).  Returns `{:ok, text}` with CRLF line
+/// endings, or `{:error, :invalid_width}` unless `width` is 1–998.
+#[rustler::nif]
+fn to_flowed(text: &str, width: usize) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        if !(1..=MAX_WIDTH).contains(&width) {
+            return Err(atoms::invalid_width());
+        }
+        Ok(encode(text, width))
+    })
+}
+
+/// Decode `format=flowed` `text` into plain text with LF line endings,
+/// removing the space before each soft break when `delsp` (`DelSp=yes`).
+#[rustler::nif]
+fn from_flowed(text: &str, delsp: bool) -> Guarded<String> {
+    guard(|| decode(text, delsp))
+}
@@ -0,0 +1,329 @@
+//! Front matter of long-form Markdown posts.
+//!
+//! An article may open with a block of metadata (title, tags, summary,
+//! license) between `---` lines, in YAML, or between `+++` lines, in TOML,
+//! as static site generators write it.  Only the flat subset that such
+//! blocks use is read: one `key: value` (or `key = value`) per line, with
+//! strings, numbers, booleans, and lists of them.  YAML lists may be
+//! written inline (`[a, b]`) or as `- item` lines, and YAML strings as `|`
+//! or `>` blocks.  Nested maps and TOML tables are rejected.
+//!
+//! A text whose first line is not a delimiter, or whose block is never
+//! closed, has no front matter: `---` alone is a Markdown thematic break.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::types::atom;
+use rustler::{Atom, Encoder, Env, Term};
+use std::collections::HashMap;
+
+mod atoms {
+    rustler::atoms! {
+        invalid_front_matter,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Yaml,
+    Toml,
+}
+
+enum Value {
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Encoder for Value {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            Value::Nil => atom::nil().encode(env),
+            Value::Bool(value) => value.encode(env),
+            Value::Integer(value) => value.encode(env),
+            Value::Float(value) => value.encode(env),
+            Value::String(value) => value.encode(env),
+            Value::List(values) => values.encode(env),
+        }
+    }
+}
+
+type Invalid = ();
+
+type FrontMatter = HashMap<String, Value>;
+
+/// The lines of `text` with their byte offset, without line endings.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n').map(move |line| {
+        let start = offset;
+        offset += line.len();
+        (start, line.trim_end_matches(['\n', '\r']))
+    })
+}
+
+/// Split `text` into its format, front matter lines, and body, or `None`
+/// if it has no front matter.
+fn split(text: &str) -> Option<(Format, Vec<&str>, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lines = lines(text);
+    let (format, closers): (Format, &[&str]) = match lines.next()?.1.trim_end() {
+        "---" => (Format::Yaml, &["---", "..."]),
+        "+++" => (Format::Toml, &["+++"]),
+        _ => return None,
+    };
+    let mut block = Vec::new();
+    for (start, line) in lines {
+        if closers.contains(&line.trim_end()) {
+            let rest = &text[start..];
+            let body = rest.split_once('\n').map_or("", |(_, body)| body);
+            return Some((format, block, body.trim_start_matches(['\n', '\r'])));
+        }
+        block.push(line);
+    }
+    None
+}
+
+fn is_comment_or_blank(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// A number or, failing that, `None`.
+fn number(text: &str) -> Option<Value> {
+    let digits = text.replace('_', "");
+    if let Ok(value) = digits.parse::<i64>() {
+        return Some(Value::Integer(value));
+    }
+    let finite = digits.bytes().any(|b| b.is_ascii_digit())
+        && digits.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b));
+    finite.then(|| digits.parse().ok().map(Value::Float)).flatten()
+}
+
+/// `text` up to the closing `quote`, unescaped, and what follows it.
+fn quoted(text: &str, quote: char, escapes: bool) -> Result<(String, &str), Invalid> {
+    let mut out = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if escapes => match chars.next().map(|(_, c)| c) {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(c @ ('"' | '\\' | '\'' | '/')) => out.push(c),
+                _ => return Err(()),
+            },
+            // YAML writes a single quote in a single-quoted string as `''`.
+            '\'' if quote == '\'' && !escapes && text[i + 1..].starts_with('\'') => {
+                out.push('\'');
+                chars.next();
+            }
+            c if c == quote => return Ok((out, &text[i + 1..])),
+            c => out.push(c),
+        }
+    }
+    Err(())
+}
+
+/// The value at the start of `text` and what follows it.  An unquoted
+/// value ends at any of `stops`.
+fn scalar<'a>(text: &'a str, format: Format, stops: &[char]) -> Result<(Value, &'a str), Invalid> {
+    let text = text.trim_start();
+    if let Some(rest) = text.strip_prefix('"') {
+        let (value, rest) = quoted(rest, '"', true)?;
+        return Ok((Value::String(value), rest));
+    }
+    if let Some(rest) = text.strip_prefix('\'') {
+        let (value, rest) = quoted(rest, '\'', format == Format::Toml)?;
+        return Ok((Value::String(value), rest));
+    }
+
+    let end = text.find(stops).unwrap_or(text.len());
+    let (word, rest) = text.split_at(end);
+    // A comment starts at `#` after a space.
+    let (word, rest) = match word.find(" #") {
+        Some(hash) => (&word[..hash], ""),
+        None => (word, rest),
+    };
+    let word = word.trim();
+    let value = match (format, word) {
+        (_, "true") => Value::Bool(true),
+        (_, "false") => Value::Bool(false),
+        (Format::Yaml, "" | "~" | "null") => Value::Nil,
+        (Format::Toml, "") => return Err(()),
+        _ => match number(word) {
+            Some(number) => number,
+            // TOML strings are always quoted; dates and times are not.
+            None if format == Format::Toml && !word.starts_with(|c: char| c.is_ascii_digit()) => {
+                return Err(())
+            }
+            None => Value::String(word.to_string()),
+        },
+    };
+    Ok((value, rest))
+}
+
+/// An inline list, `[a, "b", 3]`, after its `[`.
+fn list(mut text: &str, format: Format) -> Result<(Value, &str), Invalid> {
+    let mut items = Vec::new();
+    loop {
+        text = text.trim_start();
+        if let Some(rest) = text.strip_prefix(']') {
+            return Ok((Value::List(items), rest));
+        }
+        let (item, rest) = scalar(text, format, &[',', ']'])?;
+        items.push(item);
+        text = rest.trim_start();
+        if let Some(rest) = text.strip_prefix(',') {
+            text = rest;
+        } else if !text.starts_with(']') {
+            return Err(());
+        }
+    }
+}
+
+/// A whole value: a list or a scalar, with nothing but a comment after it.
+fn value(text: &str, format: Format) -> Result<Value, Invalid> {
+    let text = text.trim();
+    let (value, rest) = match text.strip_prefix('[') {
+        Some(rest) => list(rest, format)?,
+        None if format == Format::Yaml && text.starts_with(['{', '&', '*', '!']) => return Err(()),
+        None => scalar(text, format, &[])?,
+    };
+    if is_comment_or_blank(rest) {
+        Ok(value)
+    } else {
+        Err(())
+    }
+}
+
+fn toml(block: &[&str]) -> Result<FrontMatter, Invalid> {
+    let mut map = HashMap::new();
+    for line in block.iter().filter(|line| !is_comment_or_blank(line)) {
+        let (key, rest) = line.split_once('=').ok_or(())?;
+        let key = key.trim();
+        let key = match key.strip_prefix('"').and_then(|key| key.strip_suffix('"')) {
+            Some(key) => key,
+            None if is_key(key) => key,
+            None => return Err(()),
+        };
+        if map.insert(key.to_string(), value(rest, Format::Toml)?).is_some() {
+            return Err(());
+        }
+    }
+    Ok(map)
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// A `|` (literal) or `>` (folded) block scalar, from its indented lines.
+fn block_scalar(style: &str, lines: &[&str]) -> Result<Value, Invalid> {
+    let (fold, chomp) = style.split_at(1);
+    if !matches!(chomp, "" | "-" | "+") {
+        return Err(());
+    }
+    let margin = lines.iter().filter(|line| !line.trim().is_empty()).map(|l| indent(l)).min();
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.get(margin.unwrap_or(0)..).unwrap_or_default())
+        .collect();
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            let paragraph = line.is_empty() || lines[i - 1].is_empty();
+            text.push(if fold == ">" && !paragraph { ' ' } else { '\n' });
+        }
+        text.push_str(line);
+    }
+    if fold == ">" {
+        text = text.replace("\n\n", "\n");
+    }
+    let text = match chomp {
+        "+" => format!("{text}\n"),
+        "-" => text.trim_end_matches('\n').to_string(),
+        _ => format!("{}\n", text.trim_end_matches('\n')),
+    };
+    Ok(Value::String(text))
+}
+
+fn yaml(block: &[&str]) -> Result<FrontMatter, Invalid> {
+    let mut map = HashMap::new();
+    let mut i = 0;
+    while i < block.len() {
+        let line = block[i];
+        i += 1;
+        if is_comment_or_blank(line) {
+            continue;
+        }
+        if indent(line) > 0 || line.starts_with('\t') {
+            return Err(());
+        }
+        let (key, rest) = line.split_once(':').ok_or(())?;
+        if !is_key(key) || !(rest.is_empty() || rest.starts_with(' ')) {
+            return Err(());
+        }
+
+        // The lines that belong to this key: indented ones, and `- item`s.
+        let start = i;
+        while i < block.len() {
+            let next = block[i];
+            if !next.trim().is_empty() && indent(next) == 0 && !next.starts_with("- ") {
+                break;
+            }
+            i += 1;
+        }
+        let end = block[start..i].iter().rposition(|line| !line.trim().is_empty());
+        let nested = &block[start..end.map_or(start, |end| start + end + 1)];
+
+        let rest = rest.trim();
+        let value = if rest.starts_with(['|', '>']) {
+            block_scalar(rest, nested)?
+        } else if !nested.is_empty() && !is_comment_or_blank(rest) {
+            return Err(());
+        } else if nested.iter().any(|line| !is_comment_or_blank(line)) {
+            let items = nested.iter().filter(|line| !is_comment_or_blank(line)).map(|line| {
+                let item = line.trim_start().strip_prefix('-').ok_or(())?;
+                if !(item.is_empty() || item.starts_with(' ')) {
+                    return Err(());
+                }
+                value(item, Format::Yaml)
+            });
+            Value::List(items.collect::<Result<_, _>>()?)
+        } else {
+            value(rest, Format::Yaml)?
+        };
+        if map.insert(key.to_string(), value).is_some() {
+            return Err(());
+        }
+    }
+    Ok(map)
+}
+
+fn parse(text: &str) -> Result<(FrontMatter, String), Atom> {
+    let Some((format, block, body)) = split(text) else {
+        return Ok((HashMap::new(), text.to_string()));
+    };
+    let map = match format {
+        Format::Yaml => yaml(&block),
+        Format::Toml => toml(&block),
+    };
+    let map = map.map_err(|()| atoms::invalid_front_matter())?;
+    Ok((map, body.to_string()))
+}
+
+/// Split `text` into its front matter, as a map with string keys, and the
+/// Markdown body after it.  Returns `{:ok, {front_matter, body}}`, with an
+/// empty map for a text without front matter, or
+/// `{:error, :invalid_front_matter}`.
+#[rustler::nif]
+fn parse_front_matter(text: &str) -> Guarded<Result<(FrontMatter, String), Atom>> {
+    guard(|| parse(text))
+}
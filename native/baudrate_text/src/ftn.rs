@@ -0,0 +1,159 @@
+//! FidoNet (FTN) message text, as found in echomail archives.
+//!
+//! Besides the body, a message carries control lines (FTS-0004):
+//!
+//! ```text
+//! AREA:FIDO.GENERAL                 echo tag, first line of echomail
+//! ^AMSGID: 2:5020/1 12345678        kludges, ^A (0x01) prefixed, anywhere
+//! body...
+//! --- GoldED+ 1.1.5                 tearline
+//!  * Origin: Some BBS (2:5020/1)    origin line, with the sender's address
+//! SEEN-BY: 5020/1 2                 distribution lines
+//! ```
+//!
+//! Lines end in CR, LF, or CRLF.  Archives are mostly in legacy charsets
+//! (the `CHRS` kludge names them), so the body, tearline, and origin are
+//! returned as the original bytes for the importer to decode; the control
+//! lines themselves are ASCII.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::{Binary, Env, NewBinary, NifMap};
+
+#[derive(NifMap)]
+struct Message<'a> {
+    area: Option<String>,
+    /// `{name, value}`, in order; names such as `Via` and `PATH` repeat.
+    kludges: Vec<(String, String)>,
+    /// The first word of the `CHRS` (or `CHARSET`) kludge, e.g. `"CP866"`.
+    charset: Option<String>,
+    body: Binary<'a>,
+    /// The text after `---`, empty for a bare tearline.
+    tearline: Option<Binary<'a>>,
+    origin: Option<Binary<'a>>,
+    /// The address in parentheses at the end of the origin line.
+    address: Option<String>,
+    seen_by: Vec<String>,
+}
+
+fn lines(text: &[u8]) -> Vec<&[u8]> {
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while let Some(end) = rest.iter().position(|&b| b == b'\r' || b == b'\n') {
+        lines.push(&rest[..end]);
+        let skip = if rest[end..].starts_with(b"\r\n") { 2 } else { 1 };
+        rest = &rest[end + skip..];
+    }
+    lines.push(rest);
+    lines
+}
+
+fn ascii(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim().to_string()
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Split a kludge after its `^A` into name and value, at `: ` or a space.
+fn kludge(line: &[u8]) -> (String, String) {
+    let end = line.iter().position(|&b| b == b':' || b == b' ').unwrap_or(line.len());
+    let value = line[end..].strip_prefix(b":").unwrap_or(&line[end..]);
+    (ascii(&line[..end]), ascii(value))
+}
+
+/// Split origin text into its description and trailing `(address)`.
+fn split_origin(origin: &[u8]) -> (&[u8], Option<String>) {
+    let origin = origin.trim_ascii_end();
+    if origin.ends_with(b")") {
+        if let Some(open) = origin.iter().rposition(|&b| b == b'(') {
+            let address = ascii(&origin[open + 1..origin.len() - 1]);
+            return (origin[..open].trim_ascii_end(), Some(address));
+        }
+    }
+    (origin, None)
+}
+
+fn binary<'a>(env: Env<'a>, bytes: &[u8]) -> Binary<'a> {
+    let mut binary = NewBinary::new(env, bytes.len());
+    binary.as_mut_slice().copy_from_slice(bytes);
+    binary.into()
+}
+
+fn parse<'a>(env: Env<'a>, text: &[u8]) -> Message<'a> {
+    let mut kludges = Vec::new();
+    let mut content: Vec<&[u8]> = Vec::new();
+    for line in lines(text) {
+        match line.strip_prefix(b"\x01") {
+            Some(line) => kludges.push(kludge(line)),
+            None => content.push(line),
+        }
+    }
+
+    let area = match content.first().and_then(|line| line.strip_prefix(b"AREA:")) {
+        Some(tag) => {
+            let tag = ascii(tag);
+            content.remove(0);
+            Some(tag)
+        }
+        None => None,
+    };
+
+    // The trailer, read from the end: SEEN-BY lines, the origin, the tearline.
+    let mut seen_by = Vec::new();
+    while let Some(line) = content.last() {
+        if let Some(nodes) = line.strip_prefix(b"SEEN-BY:") {
+            seen_by.push(ascii(nodes));
+        } else if !is_blank(line) {
+            break;
+        }
+        content.pop();
+    }
+    seen_by.reverse();
+
+    let mut origin = None;
+    let mut address = None;
+    if let Some(text) = content.last().and_then(|line| line.strip_prefix(b" * Origin:")) {
+        let (text, origin_address) = split_origin(text.trim_ascii_start());
+        origin = Some(binary(env, text));
+        address = origin_address;
+        content.pop();
+    }
+    while content.last().is_some_and(|line| is_blank(line)) {
+        content.pop();
+    }
+
+    let mut tearline = None;
+    if let Some(&line) = content.last() {
+        if line == b"---" || line.starts_with(b"--- ") {
+            tearline = Some(binary(env, line[3..].trim_ascii()));
+            content.pop();
+        }
+    }
+    while content.last().is_some_and(|line| is_blank(line)) {
+        content.pop();
+    }
+
+    let charset = kludges
+        .iter()
+        .find(|(name, _)| name == "CHRS" || name == "CHARSET")
+        .and_then(|(_, value)| value.split_whitespace().next())
+        .map(str::to_string);
+
+    Message {
+        area,
+        kludges,
+        charset,
+        body: binary(env, &content.join(&b'\n')),
+        tearline,
+        origin,
+        address,
+        seen_by,
+    }
+}
+
+/// Split FidoNet message `text` into its body and control lines.
+#[rustler::nif]
+fn parse_ftn_message<'a>(env: Env<'a>, text: Binary) -> Guarded<Message<'a>> {
+    guard(|| parse(env, &text))
+}
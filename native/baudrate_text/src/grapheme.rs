@@ -0,0 +1,22 @@
+//! Grapheme clusters, for length limits that match what users see.
+//!
+//! A flag, a skin-toned emoji, or a family joined with ZWJs is one
+//! grapheme however many code points it has.  The sanitizer truncates
+//! titles and inline text with the same segmentation, so a limit checked
+//! here agrees with where those NIFs cut, whatever Unicode version the
+//! running OTP release ships.
+
+use baudrate_nif::guard::{guard, Guarded};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Split `text` into its extended grapheme clusters.
+#[rustler::nif]
+fn graphemes(text: &str) -> Guarded<Vec<String>> {
+    guard(|| text.graphemes(true).map(str::to_string).collect())
+}
+
+/// Count the extended grapheme clusters in `text`.
+#[rustler::nif]
+fn grapheme_length(text: &str) -> Guarded<usize> {
+    guard(|| text.graphemes(true).count())
+}
@@ -0,0 +1,13 @@
+//! Hashtag canonical forms, as Mastodon's `HashtagNormalizer` computes them;
+//! the rules are in `baudrate_nif::hashtag`, shared with the sanitizer.
+
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::hashtag;
+
+/// Return `{canonical, display}` for a hashtag, with or without its `#`:
+/// the key to match and store it under, and the spelling to show, as
+/// written.  `nil` if no letter is left, as in `#123` or `#!!`.
+#[rustler::nif]
+fn normalize_hashtag(tag: &str) -> Guarded<Option<(String, String)>> {
+    guard(|| hashtag::normalize(tag))
+}
@@ -0,0 +1,708 @@
+//! iCalendar (RFC 5545) events, for ActivityPub `Event` objects.
+//!
+//! Mobilizon-style events federate their start and end times and location,
+//! and are re-exposed as `.ics` files.  Only `VEVENT` components are read;
+//! alarms and other nested components are skipped.
+//!
+//! Times are passed to and from Elixir as ISO 8601 strings: `2024-05-01`
+//! for all-day events, `2024-05-01T18:00:00Z` in UTC, and
+//! `2024-05-01T18:00:00` in the event's `timezone` (an IANA name, the
+//! `TZID` parameter) or, without one, floating local time.  Conversion
+//! between zones is left to Elixir's time zone database.
+//!
+//! Written calendars carry no `TZID`: each one would need a `VTIMEZONE`
+//! (§3.2.19), which takes time zone rules this crate does not have.  Events
+//! in a zone are converted to UTC in Elixir first, and a `timezone` is
+//! rejected.
+//!
+//! Content lines are unfolded on reading and folded at 75 octets on
+//! writing, and text values are escaped and unescaped per §3.3.11.
+
+use baudrate_nif::guard::{guard, Guarded};
+use baudrate_nif::time::UtcDateTime;
+use rustler::{Atom, Decoder, Error, NifMap, NifResult, NifUnitEnum, Term};
+
+mod atoms {
+    rustler::atoms! {
+        invalid_ics,
+        invalid_time,
+        invalid_timezone,
+        invalid_rrule,
+        uid,
+        summary,
+        description,
+        location,
+        url,
+        starts_at,
+        ends_at,
+        duration,
+        timezone,
+        rrule,
+        exdates,
+        dtstamp,
+        freq,
+        interval,
+        count,
+        until,
+        by_day,
+        by_month_day,
+        by_month,
+        rest,
+    }
+}
+
+/// Longest content line, in octets, without the CRLF.
+const LINE_OCTETS: usize = 75;
+
+#[derive(NifUnitEnum, Clone, Copy, PartialEq)]
+enum Freq {
+    Secondly,
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+const FREQS: [(Freq, &str); 7] = [
+    (Freq::Secondly, "SECONDLY"),
+    (Freq::Minutely, "MINUTELY"),
+    (Freq::Hourly, "HOURLY"),
+    (Freq::Daily, "DAILY"),
+    (Freq::Weekly, "WEEKLY"),
+    (Freq::Monthly, "MONTHLY"),
+    (Freq::Yearly, "YEARLY"),
+];
+
+/// An `RRULE`.  Parts without a field of their own (`BYSETPOS`, `WKST`,
+/// `BYHOUR`, ...) are kept in `rest` as `{name, value}`, so that a parsed
+/// rule is written back unchanged.
+#[derive(NifMap)]
+struct Recurrence {
+    freq: Freq,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<String>,
+    /// `"MO"`, `"1FR"`, `"-1SU"`.
+    by_day: Vec<String>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    rest: Vec<(String, String)>,
+}
+
+#[derive(NifMap)]
+struct Event {
+    uid: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    starts_at: String,
+    ends_at: Option<String>,
+    /// An ISO 8601 duration (`PT2H`), when the event has no end time.
+    duration: Option<String>,
+    timezone: Option<String>,
+    all_day: bool,
+    rrule: Option<Recurrence>,
+    exdates: Vec<String>,
+}
+
+/// A date or date-time as iCalendar writes it, without its zone.
+#[derive(Clone, Copy)]
+struct Stamp {
+    date: (u32, u32, u32),
+    time: Option<(u32, u32, u32)>,
+    utc: bool,
+}
+
+fn digits(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+impl Stamp {
+    fn valid(self) -> Option<Self> {
+        let (_, month, day) = self.date;
+        let (hour, minute, second) = self.time.unwrap_or_default();
+        let valid = (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second <= 60;
+        valid.then_some(self)
+    }
+
+    /// `20240501`, `20240501T180000`, or `20240501T180000Z`.
+    fn from_ics(value: &str) -> Option<Self> {
+        // The lengths below are in bytes, and so are the slices.
+        if !value.is_ascii() {
+            return None;
+        }
+        let (value, utc) = match value.strip_suffix('Z') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let (date, time) = match value.split_once('T') {
+            Some((date, time)) => (date, Some(time)),
+            None => (value, None),
+        };
+        if date.len() != 8 || time.is_some_and(|time| time.len() != 6) || (utc && time.is_none()) {
+            return None;
+        }
+        let date = (digits(&date[..4])?, digits(&date[4..6])?, digits(&date[6..])?);
+        let time = match time {
+            Some(t) => Some((digits(&t[..2])?, digits(&t[2..4])?, digits(&t[4..])?)),
+            None => None,
+        };
+        Stamp { date, time, utc }.valid()
+    }
+
+    /// `2024-05-01`, `2024-05-01T18:00:00`, or `2024-05-01T18:00:00Z`;
+    /// fractional seconds are dropped.
+    fn from_iso(value: &str) -> Option<Self> {
+        let (value, utc) = match value.strip_suffix('Z') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let (date, time) = match value.split_once('T') {
+            Some((date, time)) => (date, Some(time.split('.').next().unwrap_or_default())),
+            None => (value, None),
+        };
+        let date: Vec<&str> = date.split('-').collect();
+        let [year, month, day] = date[..] else {
+            return None;
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 || (utc && time.is_none()) {
+            return None;
+        }
+        let date = (digits(year)?, digits(month)?, digits(day)?);
+        let time = match time.map(|time| time.split(':').collect::<Vec<_>>()) {
+            Some(parts) => {
+                let [hour, minute, second] = parts[..] else {
+                    return None;
+                };
+                if [hour, minute, second].iter().any(|part| part.len() != 2) {
+                    return None;
+                }
+                Some((digits(hour)?, digits(minute)?, digits(second)?))
+            }
+            None => None,
+        };
+        Stamp { date, time, utc }.valid()
+    }
+
+    fn to_ics(self) -> String {
+        let (year, month, day) = self.date;
+        let mut out = format!("{year:04}{month:02}{day:02}");
+        if let Some((hour, minute, second)) = self.time {
+            out.push_str(&format!("T{hour:02}{minute:02}{second:02}"));
+            if self.utc {
+                out.push('Z');
+            }
+        }
+        out
+    }
+
+    fn to_iso(self) -> String {
+        let (year, month, day) = self.date;
+        let mut out = format!("{year:04}-{month:02}-{day:02}");
+        if let Some((hour, minute, second)) = self.time {
+            out.push_str(&format!("T{hour:02}:{minute:02}:{second:02}"));
+            if self.utc {
+                out.push('Z');
+            }
+        }
+        out
+    }
+}
+
+/// The current time as a UTC iCalendar date-time, for `DTSTAMP`.
+fn utc_now() -> String {
+    let now = UtcDateTime::now();
+    let time = Some((now.hour, now.minute, now.second));
+    Stamp { date: (now.year as u32, now.month, now.day), time, utc: true }.to_ics()
+}
+
+/// One unfolded content line: `NAME;PARAM=value:value`.
+struct Line<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Line<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| n == name).map(|(_, value)| *value)
+    }
+}
+
+/// Join folded lines: a line starting with a space or tab continues the
+/// previous one.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_line(line: &str) -> Option<Line<'_>> {
+    // The value starts at the first `:` outside a quoted parameter value.
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(i),
+            _ => {}
+        }
+        None
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    quoted = false;
+    for (i, c) in head.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parts.push(&head[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&head[start..]);
+
+    let name = parts[0].to_ascii_uppercase();
+    let params = parts[1..]
+        .iter()
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+            Some((name.to_ascii_uppercase(), value))
+        })
+        .collect();
+    Some(Line { name, params, value })
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Escape a text value; vCard (RFC 6350) uses the same rules.
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let text = text.replace("\r\n", "\n");
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            // Other control characters are not allowed in text values.
+            c if c.is_control() && c != '\t' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str) -> Option<Vec<T>> {
+    value.split(',').map(|part| part.parse().ok()).collect()
+}
+
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut rule = Recurrence {
+        freq: Freq::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+        rest: Vec::new(),
+    };
+    let mut freq = None;
+    for part in value.split(';').filter(|part| !part.is_empty()) {
+        let (name, value) = part.split_once('=')?;
+        match name.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                let value = value.to_ascii_uppercase();
+                freq = FREQS.iter().find(|(_, name)| *name == value).map(|(freq, _)| *freq);
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|&n| n > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => rule.until = Some(Stamp::from_ics(value)?.to_iso()),
+            "BYDAY" => rule.by_day = value.split(',').map(str::to_ascii_uppercase).collect(),
+            "BYMONTHDAY" => rule.by_month_day = parse_list(value)?,
+            "BYMONTH" => rule.by_month = parse_list(value)?,
+            name => rule.rest.push((name.to_string(), value.to_string())),
+        }
+    }
+    rule.freq = freq?;
+    Some(rule)
+}
+
+/// The properties of one `VEVENT`, as read.
+#[derive(Default)]
+struct Properties {
+    uid: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    start: Option<(Stamp, Option<String>)>,
+    end: Option<Stamp>,
+    duration: Option<String>,
+    rrule: Option<Recurrence>,
+    exdates: Vec<String>,
+}
+
+/// A date or date-time property's value and `TZID`.
+fn stamp(line: &Line) -> Option<(Stamp, Option<String>)> {
+    let stamp = Stamp::from_ics(line.value)?;
+    let timezone = line.param("TZID").map(|tzid| tzid.trim_start_matches('/').to_string());
+    Some((stamp, timezone.filter(|_| stamp.time.is_some() && !stamp.utc)))
+}
+
+impl Properties {
+    fn read(&mut self, line: &Line) {
+        let text = || Some(unescape(line.value)).filter(|text| !text.is_empty());
+        match line.name.as_str() {
+            "UID" => self.uid = text(),
+            "SUMMARY" => self.summary = text(),
+            "DESCRIPTION" => self.description = text(),
+            "LOCATION" => self.location = text(),
+            "URL" => self.url = Some(line.value.to_string()),
+            "DTSTART" => self.start = stamp(line),
+            "DTEND" => self.end = stamp(line).map(|(stamp, _)| stamp),
+            "DURATION" => self.duration = Some(line.value.to_ascii_uppercase()),
+            "RRULE" => self.rrule = parse_rrule(line.value),
+            "EXDATE" => self.exdates.extend(
+                line.value.split(',').filter_map(Stamp::from_ics).map(Stamp::to_iso),
+            ),
+            _ => {}
+        }
+    }
+
+    /// The event, or `None` without a valid `DTSTART`.
+    fn finish(self) -> Option<Event> {
+        let (start, timezone) = self.start?;
+        Some(Event {
+            uid: self.uid,
+            summary: self.summary,
+            description: self.description,
+            location: self.location,
+            url: self.url,
+            starts_at: start.to_iso(),
+            ends_at: self.end.map(Stamp::to_iso),
+            duration: self.duration.filter(|_| self.end.is_none()),
+            timezone,
+            all_day: start.time.is_none(),
+            rrule: self.rrule,
+            exdates: self.exdates,
+        })
+    }
+}
+
+fn parse(text: &str) -> Result<Vec<Event>, Atom> {
+    let mut events = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut event: Option<Properties> = None;
+    let mut calendar = false;
+
+    for line in unfold(text) {
+        let Some(line) = parse_line(&line) else {
+            continue;
+        };
+        match line.name.as_str() {
+            "BEGIN" => {
+                let name = line.value.trim().to_ascii_uppercase();
+                if name == "VCALENDAR" {
+                    calendar = true;
+                }
+                if name == "VEVENT" && components.last().is_some_and(|c| c == "VCALENDAR") {
+                    event = Some(Properties::default());
+                }
+                components.push(name);
+            }
+            "END" => {
+                let name = line.value.trim().to_ascii_uppercase();
+                if components.pop().as_deref() != Some(name.as_str()) {
+                    return Err(atoms::invalid_ics());
+                }
+                if name == "VEVENT" && components.last().is_some_and(|c| c == "VCALENDAR") {
+                    events.extend(event.take().and_then(Properties::finish));
+                }
+            }
+            _ if components.last().is_some_and(|c| c == "VEVENT") => {
+                if let Some(event) = event.as_mut() {
+                    event.read(&line);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !calendar || !components.is_empty() {
+        return Err(atoms::invalid_ics());
+    }
+    Ok(events)
+}
+
+/// An event to write, decoded from an `t:ics_event/0` map.  `uid` and
+/// `starts_at` are required; other keys may be missing or `nil`.
+struct NewEvent {
+    uid: String,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    starts_at: String,
+    ends_at: Option<String>,
+    duration: Option<String>,
+    timezone: Option<String>,
+    rrule: Option<NewRecurrence>,
+    exdates: Vec<String>,
+    dtstamp: Option<String>,
+}
+
+struct NewRecurrence {
+    freq: Freq,
+    interval: Option<u32>,
+    count: Option<u32>,
+    until: Option<String>,
+    by_day: Vec<String>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    rest: Vec<(String, String)>,
+}
+
+/// The value under `key` in map `term`, or `None` when it is missing or
+/// `nil`.
+pub(crate) fn get<'a, T: Decoder<'a>>(term: Term<'a>, key: Atom) -> NifResult<Option<T>> {
+    term.map_get(key).map_or(Ok(None), |value| value.decode())
+}
+
+impl<'a> Decoder<'a> for NewRecurrence {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(Self {
+            freq: get(term, atoms::freq())?.ok_or(Error::BadArg)?,
+            interval: get(term, atoms::interval())?,
+            count: get(term, atoms::count())?,
+            until: get(term, atoms::until())?,
+            by_day: get(term, atoms::by_day())?.unwrap_or_default(),
+            by_month_day: get(term, atoms::by_month_day())?.unwrap_or_default(),
+            by_month: get(term, atoms::by_month())?.unwrap_or_default(),
+            rest: get(term, atoms::rest())?.unwrap_or_default(),
+        })
+    }
+}
+
+impl<'a> Decoder<'a> for NewEvent {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(Self {
+            uid: get(term, atoms::uid())?.ok_or(Error::BadArg)?,
+            summary: get(term, atoms::summary())?,
+            description: get(term, atoms::description())?,
+            location: get(term, atoms::location())?,
+            url: get(term, atoms::url())?,
+            starts_at: get(term, atoms::starts_at())?.ok_or(Error::BadArg)?,
+            ends_at: get(term, atoms::ends_at())?,
+            duration: get(term, atoms::duration())?,
+            timezone: get(term, atoms::timezone())?,
+            rrule: get(term, atoms::rrule())?,
+            exdates: get(term, atoms::exdates())?.unwrap_or_default(),
+            dtstamp: get(term, atoms::dtstamp())?,
+        })
+    }
+}
+
+/// Append `line` to `out`, folded at [`LINE_OCTETS`] on character
+/// boundaries.
+pub(crate) fn push_folded(out: &mut String, line: &str) {
+    let mut rest = line;
+    let mut room = LINE_OCTETS;
+    loop {
+        if rest.len() <= room {
+            out.push_str(rest);
+            out.push_str("\r\n");
+            return;
+        }
+        let mut end = room;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.push_str(&rest[..end]);
+        out.push_str("\r\n ");
+        rest = &rest[end..];
+        // The leading space of a continuation counts towards its length.
+        room = LINE_OCTETS - 1;
+    }
+}
+
+fn time(value: &str) -> Result<Stamp, Atom> {
+    Stamp::from_iso(value).ok_or_else(atoms::invalid_time)
+}
+
+/// `;VALUE=DATE` or nothing, then `:` and the value.
+fn stamp_value(stamp: Stamp) -> String {
+    match stamp.time {
+        None => format!(";VALUE=DATE:{}", stamp.to_ics()),
+        Some(_) => format!(":{}", stamp.to_ics()),
+    }
+}
+
+fn rrule_value(rule: &NewRecurrence) -> Result<String, Atom> {
+    let freq = FREQS.iter().find(|(freq, _)| *freq == rule.freq).map_or("", |(_, name)| name);
+    let mut parts = vec![format!("FREQ={freq}")];
+    match rule.interval {
+        Some(0) => return Err(atoms::invalid_rrule()),
+        Some(interval) if interval > 1 => parts.push(format!("INTERVAL={interval}")),
+        _ => {}
+    }
+    if let Some(count) = rule.count {
+        parts.push(format!("COUNT={count}"));
+    }
+    if let Some(until) = &rule.until {
+        parts.push(format!("UNTIL={}", time(until)?.to_ics()));
+    }
+    let weekday = |day: &str| {
+        let Some((ordinal, name)) = day.split_at_checked(day.len().saturating_sub(2)) else {
+            return false;
+        };
+        let week = ordinal.strip_prefix(['+', '-']).unwrap_or(ordinal);
+        ["MO", "TU", "WE", "TH", "FR", "SA", "SU"].contains(&name.to_ascii_uppercase().as_str())
+            && (ordinal.is_empty() || digits(week).is_some_and(|n| (1..=53).contains(&n)))
+    };
+    if !rule.by_day.iter().all(|day| weekday(day))
+        || !rule.by_month_day.iter().all(|&day| (1..=31).contains(&day.abs()))
+        || !rule.by_month.iter().all(|month| (1..=12).contains(month))
+    {
+        return Err(atoms::invalid_rrule());
+    }
+    let list = |values: Vec<String>| values.join(",");
+    if !rule.by_day.is_empty() {
+        parts.push(format!("BYDAY={}", rule.by_day.join(",").to_ascii_uppercase()));
+    }
+    if !rule.by_month_day.is_empty() {
+        let days = rule.by_month_day.iter().map(i32::to_string).collect();
+        parts.push(format!("BYMONTHDAY={}", list(days)));
+    }
+    if !rule.by_month.is_empty() {
+        let months = rule.by_month.iter().map(u32::to_string).collect();
+        parts.push(format!("BYMONTH={}", list(months)));
+    }
+    for (name, value) in &rule.rest {
+        let valid = |text: &str| !text.is_empty() && !text.contains([';', '=', ':', '\r', '\n']);
+        if !valid(name) || !valid(value) {
+            return Err(atoms::invalid_rrule());
+        }
+        parts.push(format!("{}={value}", name.to_ascii_uppercase()));
+    }
+    Ok(parts.join(";"))
+}
+
+fn build_event(out: &mut String, event: &NewEvent) -> Result<(), Atom> {
+    // Zoned times are converted to UTC first; see the module docs.
+    if event.timezone.is_some() {
+        return Err(atoms::invalid_timezone());
+    }
+    let start = time(&event.starts_at)?;
+    // `DTSTAMP` is always in UTC.
+    let dtstamp = match &event.dtstamp {
+        Some(dtstamp) => match time(dtstamp)? {
+            stamp if stamp.utc => stamp.to_ics(),
+            _ => return Err(atoms::invalid_time()),
+        },
+        None => utc_now(),
+    };
+
+    push_folded(out, "BEGIN:VEVENT");
+    push_folded(out, &format!("UID:{}", escape(&event.uid)));
+    push_folded(out, &format!("DTSTAMP:{dtstamp}"));
+    push_folded(out, &format!("DTSTART{}", stamp_value(start)));
+    if let Some(end) = &event.ends_at {
+        push_folded(out, &format!("DTEND{}", stamp_value(time(end)?)));
+    } else if let Some(duration) = &event.duration {
+        let valid = duration.starts_with(['P', 'p', '+', '-'])
+            && duration.chars().all(|c| c.is_ascii_alphanumeric() || "+-".contains(c));
+        if !valid {
+            return Err(atoms::invalid_time());
+        }
+        push_folded(out, &format!("DURATION:{}", duration.to_ascii_uppercase()));
+    }
+    if let Some(rule) = &event.rrule {
+        push_folded(out, &format!("RRULE:{}", rrule_value(rule)?));
+    }
+    for exdate in &event.exdates {
+        push_folded(out, &format!("EXDATE{}", stamp_value(time(exdate)?)));
+    }
+    let texts = [
+        ("SUMMARY", &event.summary),
+        ("DESCRIPTION", &event.description),
+        ("LOCATION", &event.location),
+    ];
+    for (name, text) in texts {
+        if let Some(text) = text {
+            push_folded(out, &format!("{name}:{}", escape(text)));
+        }
+    }
+    if let Some(url) = &event.url {
+        let url: String = url.chars().filter(|c| !c.is_control()).collect();
+        push_folded(out, &format!("URL:{url}"));
+    }
+    push_folded(out, "END:VEVENT");
+    Ok(())
+}
+
+fn build(events: &[NewEvent]) -> Result<String, Atom> {
+    let mut out = String::new();
+    push_folded(&mut out, "BEGIN:VCALENDAR");
+    push_folded(&mut out, "VERSION:2.0");
+    push_folded(&mut out, "PRODID:-//Baudrate//Events//EN");
+    push_folded(&mut out, "CALSCALE:GREGORIAN");
+    for event in events {
+        build_event(&mut out, event)?;
+    }
+    push_folded(&mut out, "END:VCALENDAR");
+    Ok(out)
+}
+
+/// Parse the `VEVENT`s of iCalendar `text`.  Returns `{:ok, events}`, or
+/// `{:error, :invalid_ics}` when there is no `VCALENDAR` or its components
+/// do not nest.  Events without a valid start are skipped.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_ics(text: &str) -> Guarded<Result<Vec<Event>, Atom>> {
+    guard(|| parse(text))
+}
+
+/// Write `events` as an iCalendar document with CRLF line endings.
+/// Returns `{:ok, text}` or `{:error, reason}` for an invalid time, time
+/// zone, or recurrence rule.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn build_ics(events: Vec<NewEvent>) -> Guarded<Result<String, Atom>> {
+    guard(|| build(&events))
+}
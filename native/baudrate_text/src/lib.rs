@@ -0,0 +1,42 @@
+mod acct;
+mod bidi;
+mod emoji;
+mod fence;
+mod figlet;
+mod flowed;
+mod front;
+mod ftn;
+mod grapheme;
+mod hashtag;
+mod ics;
+mod info;
+mod reply;
+mod simhash;
+mod uu;
+mod vcard;
+mod wrap;
+
+use baudrate_nif::guard::{guard, Guarded};
+use caseless::{canonical_caseless_match_str, default_case_fold_str};
+use unicode_normalization::UnicodeNormalization;
+
+/// Apply Unicode full case folding (CaseFolding.txt statuses C + F) to `text`.
+///
+/// The result is NFC-normalized so that folded strings can be stored and
+/// compared byte-for-byte (e.g. as a unique index key).
+#[rustler::nif]
+fn casefold(text: &str) -> Guarded<String> {
+    guard(|| default_case_fold_str(text).nfc().collect())
+}
+
+/// Compare two strings using Unicode canonical caseless matching.
+///
+/// Unlike `String.downcase/1` comparison, this handles multi-character folds
+/// (`"Straße"` vs `"STRASSE"`), final sigma, and canonically equivalent
+/// composed / decomposed forms.
+#[rustler::nif]
+fn caseless_equal(a: &str, b: &str) -> Guarded<bool> {
+    guard(|| canonical_caseless_match_str(a, b))
+}
+
+rustler::init!("Elixir.Baudrate.Text.Native");
@@ -0,0 +1,108 @@
+//! Splitting mail and news replies into new text, quotes, and signature.
+//!
+//! Quoted lines start with `>`.  A non-quoted line ending in `:` just
+//! before a quote ("On Monday, Alice wrote:") is its attribution and goes
+//! with it, and everything after an Outlook `-----Original Message-----`
+//! line is quoted.  The signature starts after the first unquoted `-- `
+//! line; quotes below it (a top-posted reply) still count as quoted.
+
+use baudrate_nif::guard::{guard, Guarded};
+
+const SIGNATURE_SEPARATOR: &str = "-- ";
+const ORIGINAL_MESSAGE: &str = "-----original message-----";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    New,
+    Signature,
+    /// After an `Original Message` line.
+    Original,
+}
+
+#[derive(Default)]
+struct Parts<'a> {
+    new: Vec<&'a str>,
+    quoted: Vec<&'a str>,
+    signature: Vec<&'a str>,
+}
+
+impl<'a> Parts<'a> {
+    fn unquoted(&mut self, section: Section) -> &mut Vec<&'a str> {
+        match section {
+            Section::Signature => &mut self.signature,
+            _ => &mut self.new,
+        }
+    }
+
+    /// Start a quote block, taking its attribution line from `section`.
+    fn open_quote(&mut self, section: Section) {
+        let text = self.unquoted(section);
+        let blanks = text.iter().rev().take_while(|line| line.trim().is_empty()).count();
+        let attribution = text
+            .len()
+            .checked_sub(blanks + 1)
+            .filter(|&i| text[i].trim_end().ends_with(':'))
+            .map(|i| {
+                let line = text[i];
+                text.truncate(i);
+                line
+            });
+
+        // Blocks are separated by a blank line.
+        if !self.quoted.is_empty() {
+            self.quoted.push("");
+        }
+        self.quoted.extend(attribution);
+    }
+}
+
+/// `lines` without leading and trailing blank lines, or `None` if empty.
+fn join(lines: &[&str]) -> Option<String> {
+    let start = lines.iter().position(|line| !line.trim().is_empty())?;
+    let end = lines.iter().rposition(|line| !line.trim().is_empty())?;
+    Some(lines[start..=end].join("\n"))
+}
+
+fn split(text: &str) -> (String, Option<String>, Option<String>) {
+    let mut parts = Parts::default();
+    let mut section = Section::New;
+    let mut in_quote = false;
+
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if section == Section::Original {
+            parts.quoted.push(line);
+            continue;
+        }
+        if line.trim().eq_ignore_ascii_case(ORIGINAL_MESSAGE) {
+            parts.open_quote(section);
+            parts.quoted.push(line);
+            section = Section::Original;
+            continue;
+        }
+        if line.starts_with('>') {
+            if !in_quote {
+                parts.open_quote(section);
+                in_quote = true;
+            }
+            parts.quoted.push(line);
+            continue;
+        }
+
+        in_quote = false;
+        if section == Section::New && line == SIGNATURE_SEPARATOR {
+            section = Section::Signature;
+        } else {
+            parts.unquoted(section).push(line);
+        }
+    }
+
+    (join(&parts.new).unwrap_or_default(), join(&parts.quoted), join(&parts.signature))
+}
+
+/// Split a mail or news reply into `{new_content, quoted_content, signature}`;
+/// the last two are `nil` when absent.
+#[rustler::nif]
+fn split_reply(text: &str) -> Guarded<(String, Option<String>, Option<String>)> {
+    guard(|| split(text))
+}
@@ -0,0 +1,81 @@
+//! 64-bit SimHash fingerprints for near-duplicate detection.
+//!
+//! Text is case-folded, punctuation and whitespace collapse to single
+//! spaces, and every run of `SHINGLE` characters becomes a feature.
+//! Character shingles need no word segmentation, so CJK text works as
+//! well as space-separated text, and a small edit only changes the few
+//! shingles that overlap it: mutated copies of a post keep fingerprints a
+//! small Hamming distance apart.
+//!
+//! Features are hashed with FNV-1a plus a SplitMix64 finalizer rather than
+//! `std`'s hasher, whose output may change between Rust releases:
+//! fingerprints are meant to be stored and compared later.
+
+use baudrate_nif::guard::{guard, Guarded};
+use caseless::default_case_fold_str;
+use unicode_normalization::UnicodeNormalization;
+
+/// Characters per feature.
+const SHINGLE: usize = 4;
+
+fn hash(feature: &str) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in feature.as_bytes() {
+        h = (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+    }
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// Case-fold `text` and reduce it to alphanumeric runs separated by single
+/// spaces.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in default_case_fold_str(text).nfc() {
+        if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+    if out.ends_with(' ') {
+        out.pop();
+    }
+    out
+}
+
+/// Return the SimHash of `text` as an unsigned 64-bit integer.
+///
+/// Text shorter than one shingle is hashed as a single feature; text with
+/// no letters or digits at all hashes to 0.
+#[rustler::nif]
+fn simhash(text: &str) -> Guarded<u64> {
+    guard(|| {
+        let text = normalize(text);
+        let offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).chain([text.len()]).collect();
+        if offsets.len() == 1 {
+            return 0;
+        }
+
+        let mut weights = [0i64; 64];
+        let n = SHINGLE.min(offsets.len() - 1);
+        for bounds in offsets.windows(n + 1) {
+            let h = hash(&text[bounds[0]..bounds[n]]);
+            for (bit, weight) in weights.iter_mut().enumerate() {
+                *weight += if (h >> bit) & 1 == 1 { 1 } else { -1 };
+            }
+        }
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, &weight)| weight > 0)
+            .fold(0, |acc, (bit, _)| acc | 1 << bit)
+    })
+}
+
+/// Return the number of bits in which two fingerprints differ.
+#[rustler::nif]
+fn hamming_distance(a: u64, b: u64) -> Guarded<u32> {
+    guard(|| (a ^ b).count_ones())
+}
@@ -0,0 +1,153 @@
+//! uuencoding, for attachments in imported BBS and FidoNet archives.
+//!
+//! A file is a `begin <mode> <name>` line, data lines, and `end`.  Each data
+//! line starts with a character encoding its byte count (at most 45),
+//! followed by four characters per three bytes, every character holding six
+//! bits offset by 32.  Encoders differ on whether zero is a space or a
+//! backtick; both are decoded, and a backtick is written.
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::{Atom, Binary, Env, NewBinary};
+
+mod atoms {
+    rustler::atoms! {
+        invalid_filename,
+    }
+}
+
+/// Bytes per line written by `uuencode`, as in classic `uuencode(1)`.
+const LINE_BYTES: usize = 45;
+
+fn encode_char(bits: u8) -> char {
+    match bits & 0x3f {
+        0 => '`',
+        bits => char::from(bits + 32),
+    }
+}
+
+fn decode_char(c: u8) -> Option<u8> {
+    (b' '..=b'`').contains(&c).then_some((c - 32) & 0x3f)
+}
+
+/// Decode one data line into `out`, or `None` if it is corrupt.  Returns the
+/// line's byte count; 0 ends the data.
+fn decode_line(line: &[u8], out: &mut Vec<u8>) -> Option<usize> {
+    let (&count, chars) = line.split_first()?;
+    let count = usize::from(decode_char(count)?);
+
+    let start = out.len();
+    // Some encoders strip trailing spaces, so missing characters are zeros.
+    for group in (0..count.div_ceil(3)).map(|i| i * 4) {
+        let mut bits = [0u8; 4];
+        for (j, bit) in bits.iter_mut().enumerate() {
+            if let Some(&c) = chars.get(group + j) {
+                *bit = decode_char(c)?;
+            }
+        }
+        out.extend_from_slice(&[
+            bits[0] << 2 | bits[1] >> 4,
+            bits[1] << 4 | bits[2] >> 2,
+            bits[2] << 6 | bits[3],
+        ]);
+    }
+    out.truncate(start + count);
+    Some(count)
+}
+
+/// The name from a `begin <mode> <name>` line, reduced to its last path
+/// component so that it cannot escape an extraction directory.
+fn begin_name(line: &[u8]) -> Option<String> {
+    let rest = line.strip_prefix(b"begin ")?;
+    let (mode, name) = rest.split_at(rest.iter().position(|&b| b == b' ')?);
+    if mode.is_empty() || !mode.iter().all(|b| (b'0'..=b'7').contains(b)) {
+        return None;
+    }
+    let name = String::from_utf8_lossy(&name[1..]);
+    let name = name.trim().rsplit(['/', '\\']).next().unwrap_or_default();
+    Some(if matches!(name, "" | "." | "..") { "unnamed".to_string() } else { name.to_string() })
+}
+
+fn decode(text: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut files = Vec::new();
+    let mut lines =
+        text.split(|&b| b == b'\n').map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+
+    while let Some(line) = lines.next() {
+        let Some(name) = begin_name(line) else {
+            continue;
+        };
+        let mut data = Vec::new();
+        let mut complete = false;
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                continue;
+            }
+            // `end` normally follows a zero-length line, but not always.
+            if line == b"end" {
+                complete = true;
+                break;
+            }
+            match decode_line(line, &mut data) {
+                Some(0) => {
+                    complete = true;
+                    break;
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        if complete {
+            files.push((name, data));
+        }
+    }
+    files
+}
+
+fn encode(name: &str, data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 4 / 3 + data.len() / LINE_BYTES * 2 + 32);
+    out.push_str("begin 644 ");
+    out.push_str(name);
+    out.push('\n');
+    for line in data.chunks(LINE_BYTES) {
+        out.push(encode_char(line.len() as u8));
+        for group in line.chunks(3) {
+            let b = [group[0], *group.get(1).unwrap_or(&0), *group.get(2).unwrap_or(&0)];
+            out.push(encode_char(b[0] >> 2));
+            out.push(encode_char(b[0] << 4 | b[1] >> 4));
+            out.push(encode_char(b[1] << 2 | b[2] >> 6));
+            out.push(encode_char(b[2]));
+        }
+        out.push('\n');
+    }
+    out.push_str("`\nend\n");
+    out
+}
+
+/// Decode every uuencoded file in `text` as `{filename, data}`, in order.
+/// Files that are truncated or corrupt are skipped.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn uudecode<'a>(env: Env<'a>, text: Binary) -> Guarded<Vec<(String, Binary<'a>)>> {
+    guard(|| {
+        decode(&text)
+            .into_iter()
+            .map(|(name, data)| {
+                let mut binary = NewBinary::new(env, data.len());
+                binary.as_mut_slice().copy_from_slice(&data);
+                (name, binary.into())
+            })
+            .collect()
+    })
+}
+
+/// uuencode `data` as a file named `filename`, with mode 644.  Returns
+/// `{:ok, text}`, or `{:error, :invalid_filename}` for a name that is empty
+/// or contains a control character.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn uuencode(filename: &str, data: Binary) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        if filename.trim().is_empty() || filename.chars().any(char::is_control) {
+            return Err(atoms::invalid_filename());
+        }
+        Ok(encode(filename, &data))
+    })
+}
@@ -0,0 +1,96 @@
+//! vCard 4.0 (RFC 6350) cards for user profiles.
+//!
+//! Content lines are folded and text values escaped as in iCalendar, so the
+//! helpers are shared with [`crate::ics`].  URI values (`PHOTO`, `URL`,
+//! `UID`) are not escaped, and are rejected if they contain whitespace or
+//! control characters, which no URI may.
+
+use baudrate_nif::guard::{guard, Guarded};
+use crate::ics::{escape, get, push_folded};
+use rustler::{Atom, Decoder, NifResult, Term};
+
+mod atoms {
+    rustler::atoms! {
+        missing_name,
+        invalid_uri,
+        name,
+        nickname,
+        note,
+        photo,
+        urls,
+        uid,
+        email,
+    }
+}
+
+/// A profile, decoded from a map; every key may be missing or `nil` except
+/// `name`.
+struct Profile {
+    name: Option<String>,
+    nickname: Option<String>,
+    note: Option<String>,
+    photo: Option<String>,
+    urls: Vec<String>,
+    uid: Option<String>,
+    email: Option<String>,
+}
+
+impl<'a> Decoder<'a> for Profile {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(Self {
+            name: get(term, atoms::name())?,
+            nickname: get(term, atoms::nickname())?,
+            note: get(term, atoms::note())?,
+            photo: get(term, atoms::photo())?,
+            urls: get(term, atoms::urls())?.unwrap_or_default(),
+            uid: get(term, atoms::uid())?,
+            email: get(term, atoms::email())?,
+        })
+    }
+}
+
+fn uri(value: &str) -> Result<&str, Atom> {
+    if value.is_empty() || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(atoms::invalid_uri());
+    }
+    Ok(value)
+}
+
+fn build(profile: &Profile) -> Result<String, Atom> {
+    let name = profile.name.as_deref().map(str::trim).filter(|name| !name.is_empty());
+    let name = name.ok_or_else(atoms::missing_name)?;
+
+    let mut out = String::new();
+    push_folded(&mut out, "BEGIN:VCARD");
+    push_folded(&mut out, "VERSION:4.0");
+    push_folded(&mut out, "KIND:individual");
+    push_folded(&mut out, &format!("FN:{}", escape(name)));
+    if let Some(nickname) = profile.nickname.as_deref().filter(|n| !n.trim().is_empty()) {
+        push_folded(&mut out, &format!("NICKNAME:{}", escape(nickname)));
+    }
+    if let Some(uid) = &profile.uid {
+        push_folded(&mut out, &format!("UID:{}", uri(uid)?));
+    }
+    if let Some(photo) = &profile.photo {
+        push_folded(&mut out, &format!("PHOTO:{}", uri(photo)?));
+    }
+    for url in &profile.urls {
+        push_folded(&mut out, &format!("URL:{}", uri(url)?));
+    }
+    if let Some(email) = &profile.email {
+        push_folded(&mut out, &format!("EMAIL:{}", escape(email)));
+    }
+    if let Some(note) = profile.note.as_deref().filter(|note| !note.trim().is_empty()) {
+        push_folded(&mut out, &format!("NOTE:{}", escape(note)));
+    }
+    push_folded(&mut out, "END:VCARD");
+    Ok(out)
+}
+
+/// Write `profile` as a vCard 4.0 card with CRLF line endings.  Returns
+/// `{:ok, text}`, `{:error, :missing_name}` without a non-blank `name`, or
+/// `{:error, :invalid_uri}` for a malformed `uid`, `photo`, or URL.
+#[rustler::nif]
+fn build_vcard(profile: Profile) -> Guarded<Result<String, Atom>> {
+    guard(|| build(&profile))
+}
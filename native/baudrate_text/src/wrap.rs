@@ -0,0 +1,171 @@
+//! Fixed-width text reflow for the Gopher, Gemini, and finger views.
+//!
+//! Widths are terminal columns: East Asian wide and fullwidth characters
+//! count as 2 and combining marks as 0.  Lines break at whitespace, and
+//! text wider than a whole line (a long URL, or CJK text, which has no
+//! spaces) breaks between characters.
+//!
+//! Each input line is reflowed on its own.  A quote prefix (`>`, `> >`) is
+//! repeated on every line it wraps onto, and a list item's continuation
+//! lines are indented past its marker (`-`, `*`, `+`, `•`, `1.`, `1)`).
+
+use baudrate_nif::guard::{guard, Guarded};
+use rustler::Atom;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+mod atoms {
+    rustler::atoms! {
+        invalid_width,
+    }
+}
+
+/// Columns a leading tab expands to.
+const TAB_WIDTH: usize = 4;
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// The length of a list marker and its space at the start of `s`.
+fn list_marker(s: &str) -> Option<usize> {
+    for bullet in ["- ", "* ", "+ ", "• "] {
+        if s.starts_with(bullet) {
+            return Some(bullet.len());
+        }
+    }
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    let rest = s.as_bytes().get(digits..digits + 2)?;
+    ((1..=9).contains(&digits) && matches!(rest, b". " | b") ")).then_some(digits + 2)
+}
+
+/// Split `line` into the prefix of its first output line, the prefix of
+/// the lines it wraps onto, and its text.
+fn split_prefix(line: &str) -> (String, String, &str) {
+    let mut prefix = String::new();
+    let mut rest = line;
+
+    // Indentation, then any quote markers with their spacing.
+    loop {
+        if let Some(after) = rest.strip_prefix('\t') {
+            prefix.push_str(&" ".repeat(TAB_WIDTH));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix([' ', '>']) {
+            prefix.push_str(&rest[..1]);
+            rest = after;
+        } else {
+            break;
+        }
+    }
+
+    match list_marker(rest) {
+        Some(len) => {
+            let hanging = format!("{prefix}{}", " ".repeat(rest[..len].width()));
+            prefix.push_str(&rest[..len]);
+            (prefix, hanging, &rest[len..])
+        }
+        None => (prefix.clone(), prefix, rest),
+    }
+}
+
+/// The output being built: finished lines in `out`, and the current line.
+struct Lines<'a> {
+    cols: usize,
+    hanging: &'a str,
+    out: &'a mut String,
+    line: String,
+    width: usize,
+    /// Whether the current line holds only its prefix.
+    empty: bool,
+}
+
+impl Lines<'_> {
+    fn break_line(&mut self) {
+        self.out.push_str(self.line.trim_end());
+        self.out.push('\n');
+        self.line.clear();
+        self.line.push_str(self.hanging);
+        self.width = self.hanging.width();
+        self.empty = true;
+    }
+
+    fn push(&mut self, word: &str) {
+        let width = word.width();
+        let space = usize::from(!self.empty);
+        if self.width + space + width <= self.cols {
+            if !self.empty {
+                self.line.push(' ');
+            }
+            self.line.push_str(word);
+            self.width += space + width;
+            self.empty = false;
+            return;
+        }
+
+        // Move the word to the next line if it fits there; CJK text breaks
+        // anywhere, so it fills the current line first.
+        let fits = self.hanging.width() + width <= self.cols;
+        let wide = word.chars().any(|c| char_width(c) > 1);
+        if !self.empty && fits && !wide {
+            self.break_line();
+            self.push(word);
+            return;
+        }
+
+        if !self.empty {
+            if self.width + 1 < self.cols {
+                self.line.push(' ');
+                self.width += 1;
+            } else {
+                self.break_line();
+            }
+        }
+        for c in word.chars() {
+            let width = char_width(c);
+            // A zero-width character stays with the one before it.
+            if width > 0 && !self.empty && self.width + width > self.cols {
+                self.break_line();
+            }
+            self.line.push(c);
+            self.width += width;
+            self.empty = false;
+        }
+    }
+}
+
+fn wrap(text: &str, cols: usize) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / cols.max(1));
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (first, hanging, body) = split_prefix(line);
+
+        let mut lines = Lines {
+            cols,
+            hanging: &hanging,
+            out: &mut out,
+            width: first.width(),
+            line: first,
+            empty: true,
+        };
+        for word in body.split_whitespace() {
+            lines.push(word);
+        }
+        let last = lines.line;
+        out.push_str(last.trim_end());
+    }
+    out
+}
+
+/// Wrap `text` to `cols` terminal columns.  Returns `{:ok, text}`, or
+/// `{:error, :invalid_width}` when `cols` is 0.
+#[rustler::nif]
+fn wrap_text(text: &str, cols: usize) -> Guarded<Result<String, Atom>> {
+    guard(|| {
+        if cols == 0 {
+            return Err(atoms::invalid_width());
+        }
+        Ok(wrap(text, cols))
+    })
+}
@@ -0,0 +1,130 @@
+defmodule Baudrate.Repo.Migrations.NormalizeArticleTags do
+  use Ecto.Migration
+
+  import Ecto.Query
+
+  require Logger
+
+  @max_tag_length 64
+
+  # Lowercase Latin letters and the ASCII letter each folds to, as in
+  # native/baudrate_nif/src/hashtag.rs when this migration was written.
+  @ascii_folding [
+    {"àáâãäåāăą", ?a},
+    {"çćĉċč", ?c},
+    {"ðďđ", ?d},
+    {"èéêëēĕėęě", ?e},
+    {"ĝğġģ", ?g},
+    {"ĥħ", ?h},
+    {"ìíîïĩīĭįı", ?i},
+    {"ĵ", ?j},
+    {"ķĸ", ?k},
+    {"ĺļľŀł", ?l},
+    {"ñńņňŉŋ", ?n},
+    {"òóôõöøōŏő", ?o},
+    {"ŕŗř", ?r},
+    {"śŝşšſ", ?s},
+    {"ţťŧ", ?t},
+    {"ùúûüũūŭůűų", ?u},
+    {"ŵ", ?w},
+    {"ýÿŷ", ?y},
+    {"źżž", ?z}
+  ]
+
+  @kept_re ~r/^[\p{L}\p{N}\p{M}_\x{B7}\x{30FB}\x{200C}]$/u
+  @letter_re ~r/[\p{L}\p{Nl}]/u
+
+  # Tags were stored lowercased; they are now stored in the canonical form of
+  # `Baudrate.Text.Native.normalize_hashtag/1`, so `café` and `cafe` become
+  # one tag. Rows merge into the canonical tag where an article has both.
+  #
+  # The rule is copied rather than called, as it stood when this migration
+  # was written: NFKC, lowercase, NFC, the ASCII folding table, and only
+  # letters, marks, digits, and `_ · ・ ZWNJ` kept. Calling the NIF would make
+  # the backfill change with later changes to the rule, which ship their own
+  # migration to re-normalize.
+  #
+  # Rows without a canonical form of at most 64 characters could never be
+  # reached from a tag page again, so they are deleted (the next edit of
+  # their article would drop them anyway) and counted in the log.
+  def up do
+    dropped =
+      from(at in "article_tags", distinct: true, select: at.tag)
+      |> repo().all()
+      |> Enum.reduce(0, fn tag, dropped ->
+        case canonical(tag) do
+          ^tag ->
+            dropped
+
+          nil ->
+            dropped + delete(tag)
+
+          canonical ->
+            if String.length(canonical) <= @max_tag_length do
+              merge(tag, canonical)
+              dropped
+            else
+              dropped + delete(tag)
+            end
+        end
+      end)
+
+    if dropped > 0 do
+      Logger.warning("Deleted #{dropped} article tag(s) without a canonical form")
+    end
+  end
+
+  # Merged and deleted tags cannot be told apart from the rows they merged
+  # into, so the lowercased spellings cannot be restored.
+  def down do
+    raise Ecto.MigrationError,
+      message: "#{inspect(__MODULE__)} merges tags into their canonical form and is irreversible"
+  end
+
+  defp canonical(tag) do
+    display =
+      case String.trim(tag) do
+        "#" <> rest -> String.trim_leading(rest)
+        "＃" <> rest -> String.trim_leading(rest)
+        trimmed -> trimmed
+      end
+
+    canonical =
+      display
+      |> :unicode.characters_to_nfkc_binary()
+      |> String.downcase()
+      |> :unicode.characters_to_nfc_binary()
+      |> String.to_charlist()
+      |> Enum.map(&fold/1)
+      |> Enum.filter(&Regex.match?(@kept_re, <<&1::utf8>>))
+      |> List.to_string()
+
+    if Regex.match?(@letter_re, canonical), do: canonical
+  end
+
+  defp fold(c) when c < 0x80, do: c
+
+  defp fold(c) do
+    Enum.find_value(@ascii_folding, c, fn {letters, ascii} ->
+      if String.contains?(letters, <<c::utf8>>), do: ascii
+    end)
+  end
+
+  defp merge(tag, canonical) do
+    repo().query!(
+      """
+      INSERT INTO article_tags (article_id, tag, inserted_at)
+      SELECT article_id, $1, inserted_at FROM article_tags WHERE tag = $2
+      ON CONFLICT (article_id, tag) DO NOTHING
+      """,
+      [canonical, tag]
+    )
+
+    delete(tag)
+  end
+
+  defp delete(tag) do
+    {count, _} = repo().delete_all(from(at in "article_tags", where: at.tag == ^tag))
+    count
+  end
+end
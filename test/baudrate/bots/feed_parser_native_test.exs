@@ -0,0 +1,318 @@
+defmodule Baudrate.Bots.FeedParserNativeTest do
+  use ExUnit.Case, async: true
+
+  alias Baudrate.Bots.FeedParserNative
+
+  @meta %{
+    title: "Baudrate",
+    link: "https://local.example/",
+    self_url: "https://local.example/feeds/atom",
+    updated: ~U[2026-02-23 05:57:22Z]
+  }
+
+  @entry %{
+    id: "https://local.example/articles/hello",
+    title: "Hello",
+    link: "https://local.example/articles/hello",
+    published: ~U[2026-02-22 10:00:00Z],
+    updated: ~U[2026-02-23 05:57:22Z],
+    author: "alice",
+    content: "<p>Hello <b>world</b></p>"
+  }
+
+  defp well_formed?(xml) do
+    {_doc, []} = :xmerl_scan.string(String.to_charlist(xml), quiet: true)
+    true
+  catch
+    :exit, _ -> false
+  end
+
+  describe "build_atom/2" do
+    test "builds feed metadata" do
+      xml = FeedParserNative.build_atom(Map.put(@meta, :language, "zh_TW"), [])
+
+      assert xml =~ ~s(<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="zh_TW">)
+      assert xml =~ "<title>Baudrate</title>"
+      assert xml =~ ~s(<link href="https://local.example/" rel="alternate" type="text/html"/>)
+
+      assert xml =~
+               ~s(<link href="https://local.example/feeds/atom" rel="self" type="application/atom+xml"/>)
+
+      assert xml =~ "<id>https://local.example/feeds/atom</id>"
+      assert xml =~ "<updated>2026-02-23T05:57:22Z</updated>"
+      assert well_formed?(xml)
+    end
+
+    test "builds entries with escaped HTML content" do
+      xml = FeedParserNative.build_atom(@meta, [@entry])
+
+      assert xml =~ "<entry>"
+      assert xml =~ "<id>https://local.example/articles/hello</id>"
+      assert xml =~ "<published>2026-02-22T10:00:00Z</published>"
+      assert xml =~ "<author>\n      <name>alice</name>"
+
+      assert xml =~
+               ~s(<content type="html">&lt;p&gt;Hello &lt;b&gt;world&lt;/b&gt;&lt;/p&gt;</content>)
+
+      assert well_formed?(xml)
+    end
+
+    test "stays well-formed for edge-case content" do
+      entry = %{
+        @entry
+        | title: "Tom & Jerry <3 \"quotes\" 'apos'",
+          content: "<p>]]> & \u0000\u0008\uFFFE control chars</p>"
+      }
+
+      xml = FeedParserNative.build_atom(@meta, [entry])
+
+      assert xml =~ "<title>Tom &amp; Jerry &lt;3 &quot;quotes&quot; &apos;apos&apos;</title>"
+      assert xml =~ "]]&gt; &amp;  control chars"
+      refute xml =~ "\u0000"
+      assert well_formed?(xml)
+    end
+
+    test "formats non-UTC offsets" do
+      dt = %{
+        DateTime.from_naive!(~N[2026-02-23 14:57:22], "Etc/UTC")
+        | utc_offset: 32_400,
+          time_zone: "Asia/Tokyo",
+          zone_abbr: "JST"
+      }
+
+      xml = FeedParserNative.build_atom(%{@meta | updated: dt}, [])
+
+      assert xml =~ "<updated>2026-02-23T14:57:22+09:00</updated>"
+    end
+
+    test "fills in optional fields" do
+      entry = %{id: "urn:x:1", title: "Minimal", categories: ["elixir", "rust"]}
+      xml = FeedParserNative.build_atom(Map.delete(@meta, :updated), [entry])
+
+      assert xml =~ ~r{<updated>\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z</updated>}
+      assert xml =~ ~s(<category term="elixir"/>)
+      refute xml =~ "<author>"
+      refute xml =~ "<content"
+      assert well_formed?(xml)
+    end
+
+    test "raises on missing required keys" do
+      assert_raise ArgumentError, fn -> FeedParserNative.build_atom(%{title: "x"}, []) end
+      assert_raise ArgumentError, fn -> FeedParserNative.build_atom(@meta, [%{title: "x"}]) end
+    end
+  end
+
+  describe "build_json_feed/2" do
+    test "builds a JSON Feed 1.1 document" do
+      meta = %{
+        title: "Baudrate",
+        home_page_url: "https://local.example/",
+        feed_url: "https://local.example/feeds/json",
+        language: "en"
+      }
+
+      entry = %{
+        id: "https://local.example/articles/hello",
+        url: "https://local.example/articles/hello",
+        title: "Hello",
+        content_html: "<p>Hello</p>",
+        published: ~U[2026-02-22 10:00:00Z],
+        updated: ~U[2026-02-23 05:57:22Z],
+        authors: [%{name: "alice", url: "https://local.example/users/alice"}],
+        tags: ["intro"]
+      }
+
+      feed = FeedParserNative.build_json_feed(meta, [entry]) |> Jason.decode!()
+
+      assert feed["version"] == "https://jsonfeed.org/version/1.1"
+      assert feed["title"] == "Baudrate"
+      assert feed["home_page_url"] == "https://local.example/"
+      assert feed["feed_url"] == "https://local.example/feeds/json"
+      assert feed["language"] == "en"
+
+      assert [item] = feed["items"]
+      assert item["id"] == "https://local.example/articles/hello"
+      assert item["content_html"] == "<p>Hello</p>"
+      assert item["date_published"] == "2026-02-22T10:00:00Z"
+      assert item["date_modified"] == "2026-02-23T05:57:22Z"
+      assert item["authors"] == [%{"name" => "alice", "url" => "https://local.example/users/alice"}]
+      assert item["tags"] == ["intro"]
+    end
+
+    test "maps attachments" do
+      entry = %{
+        id: "1",
+        content_text: "podcast",
+        attachments: [
+          %{url: "https://cdn.example/ep1.mp3", mime_type: "audio/mpeg", size_in_bytes: 1234},
+          %{url: "https://cdn.example/a.png", mime_type: "image/png", title: "Cover"}
+        ]
+      }
+
+      assert %{"items" => [%{"attachments" => attachments}]} =
+               FeedParserNative.build_json_feed(%{title: "x"}, [entry]) |> Jason.decode!()
+
+      assert attachments == [
+               %{
+                 "url" => "https://cdn.example/ep1.mp3",
+                 "mime_type" => "audio/mpeg",
+                 "size_in_bytes" => 1234
+               },
+               %{"url" => "https://cdn.example/a.png", "mime_type" => "image/png", "title" => "Cover"}
+             ]
+    end
+
+    test "always includes content and an items list" do
+      feed = FeedParserNative.build_json_feed(%{title: "x"}, [%{id: "1"}]) |> Jason.decode!()
+      assert feed["items"] == [%{"id" => "1", "content_text" => ""}]
+
+      assert FeedParserNative.build_json_feed(%{title: "x"}, []) |> Jason.decode!() |> Map.get("items") ==
+               []
+    end
+
+    test "raises on missing required keys" do
+      assert_raise ArgumentError, fn -> FeedParserNative.build_json_feed(%{}, []) end
+      assert_raise ArgumentError, fn -> FeedParserNative.build_json_feed(%{title: "x"}, [%{}]) end
+
+      assert_raise ArgumentError, fn ->
+        FeedParserNative.build_json_feed(%{title: "x"}, [%{id: "1", attachments: [%{url: "u"}]}])
+      end
+    end
+  end
+
+  describe "parse_opml/1" do
+    alias Baudrate.Bots.FeedParserNative.{Opml, Outline}
+
+    test "parses the folder and subscription tree" do
+      xml = """
+      <?xml version="1.0" encoding="UTF-8"?>
+      <opml version="2.0">
+        <head><title>My subscriptions</title></head>
+        <body>
+          <outline text="Tech" title="Tech">
+            <outline type="rss" text="Example" xmlUrl="https://example.com/feed.xml" htmlUrl="https://example.com/"/>
+          </outline>
+          <outline type="rss" title="Loose" xmlUrl="https://loose.example/rss"/>
+        </body>
+      </opml>
+      """
+
+      assert {:ok, %Opml{title: "My subscriptions", outlines: [folder, loose]}} =
+               FeedParserNative.parse_opml(xml)
+
+      assert %Outline{title: "Tech", xml_url: nil, children: [feed]} = folder
+
+      assert %Outline{
+               title: "Example",
+               type: "rss",
+               xml_url: "https://example.com/feed.xml",
+               html_url: "https://example.com/",
+               children: []
+             } = feed
+
+      assert %Outline{title: "Loose", xml_url: "https://loose.example/rss"} = loose
+    end
+
+    test "tolerates bare ampersands, HTML entities, and attribute casing" do
+      xml = """
+      <opml><body>
+        <outline TEXT="News&nbsp;&amp; Views" XMLURL="https://example.com/feed?a=1&b=2"/>
+      </body></opml>
+      """
+
+      assert {:ok, %Opml{outlines: [outline]}} = FeedParserNative.parse_opml(xml)
+      assert outline.title == "News & Views"
+      assert outline.xml_url == "https://example.com/feed?a=1&b=2"
+    end
+
+    test "closes unclosed outlines and keeps truncated documents" do
+      xml = """
+      <opml><body>
+        <outline text="Folder">
+          <outline text="A" xmlUrl="https://a.example/feed"/>
+        <outline text="Other"><outline text="B" xmlUrl="https://b.example/feed"/>
+      """
+
+      assert {:ok, %Opml{outlines: [folder]}} = FeedParserNative.parse_opml(xml)
+      assert [%Outline{title: "A"}, %Outline{title: "Other", children: [b]}] = folder.children
+      assert b.xml_url == "https://b.example/feed"
+    end
+
+    test "returns an error for non-OPML input" do
+      assert {:error, _} = FeedParserNative.parse_opml("not opml at all")
+      assert {:error, _} = FeedParserNative.parse_opml("")
+    end
+  end
+
+  describe "xml_escape/1" do
+    test "escapes markup and quotes" do
+      assert FeedParserNative.xml_escape(~s(<a href="x">Tom & Jerry's</a>)) ==
+               "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+    end
+
+    test "drops characters XML cannot represent" do
+      text = "a\u0000b\u0008c\u000Bd\u001Fe\uFFFEf\uFFFFg\t\n\r"
+
+      assert FeedParserNative.xml_escape(text) == "abcdefg\t\n\r"
+      assert well_formed?("<t>#{FeedParserNative.xml_escape(text)}</t>")
+    end
+
+    test "keeps non-ASCII text and replaces invalid UTF-8" do
+      assert FeedParserNative.xml_escape("日本語 🎉") == "日本語 🎉"
+      assert FeedParserNative.xml_escape(<<"a", 0xFF, "b">>) == "a\uFFFDb"
+    end
+  end
+
+  describe "strip_invalid_xml_chars/1" do
+    test "drops invalid characters and leaves markup alone" do
+      assert FeedParserNative.strip_invalid_xml_chars("<p>a\u0001 &amp; b</p>\u0000") ==
+               "<p>a &amp; b</p>"
+    end
+
+    test "returns valid text unchanged" do
+      text = "<p>Hello\tworld</p>\n"
+      assert FeedParserNative.strip_invalid_xml_chars(text) == text
+    end
+  end
+
+  describe "pathological input" do
+    @entity_bomb """
+    <?xml version="1.0"?>
+    <!DOCTYPE rss [
+      <!ENTITY a "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa">
+      <!ENTITY b "&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;&a;">
+      <!ENTITY c "&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;&b;">
+    ]>
+    <rss version="2.0"><channel><title>&c;</title></channel></rss>
+    """
+
+    test "returns results instead of panicking" do
+      deeply_nested = String.duplicate(~s(<outline text="x">), 2_000)
+      truncated = binary_part(@entity_bomb, 0, 120)
+
+      for data <- [@entity_bomb, deeply_nested, truncated, <<0xFF, 0xFE, 0>>, ""] do
+        for parse <- [
+              &FeedParserNative.parse_feed/1,
+              &FeedParserNative.parse_feed_document/1,
+              &FeedParserNative.parse_opml/1
+            ] do
+          case parse.(data) do
+            {:ok, _} -> :ok
+            {:error, reason} -> assert is_binary(reason)
+          end
+        end
+      end
+    end
+
+    test "caps OPML nesting" do
+      xml = "<opml><body>" <> String.duplicate(~s(<outline text="x">), 2_000) <> "</body></opml>"
+
+      assert {:ok, %{outlines: [outline]}} = FeedParserNative.parse_opml(xml)
+      # 32 levels of folders; deeper outlines are attached to the last one.
+      assert depth(outline) == 33
+    end
+
+    defp depth(%{children: []}), do: 1
+    defp depth(%{children: children}), do: 1 + Enum.max(Enum.map(children, &depth/1))
+  end
+end
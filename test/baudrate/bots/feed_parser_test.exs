@@ -260,6 +260,44 @@ defmodule Baudrate.Bots.FeedParserTest do
     end
   end
 
+  describe "parse_document/1" do
+    test "returns RSS channel metadata with entries" do
+      assert {:ok, feed} = FeedParser.parse_document(@rss_feed)
+      assert feed.title == "Test Feed"
+      assert feed.link =~ "https://example.com"
+      assert length(feed.entries) == 2
+      assert Enum.all?(feed.entries, &is_binary(&1.body))
+    end
+
+    test "returns Atom feed metadata and entry updated dates" do
+      assert {:ok, feed} = FeedParser.parse_document(@atom_feed)
+      assert feed.title == "Atom Test Feed"
+      assert feed.link =~ "https://atom.example.com"
+
+      assert [entry] = feed.entries
+      assert entry.guid == "https://atom.example.com/entry/1"
+      assert %DateTime{year: 2024, month: 3, day: 1} = entry.updated_at
+    end
+
+    test "normalizes HTML in feed titles and descriptions" do
+      xml = """
+      <?xml version="1.0" encoding="UTF-8"?>
+      <rss version="2.0">
+        <channel>
+          <title>Rust &amp;amp; Ruby</title>
+          <link>https://example.com</link>
+          <description>&lt;b&gt;Weekly&lt;/b&gt; notes</description>
+        </channel>
+      </rss>
+      """
+
+      assert {:ok, feed} = FeedParser.parse_document(xml)
+      assert feed.title == "Rust & Ruby"
+      assert feed.description == "Weekly notes"
+      assert feed.entries == []
+    end
+  end
+
   describe "parse/1 error handling" do
     test "returns empty list for unrecognized content" do
       # feedparser-rs operates in lenient "bozo" mode: unrecognized content is
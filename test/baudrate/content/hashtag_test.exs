@@ -88,6 +88,10 @@ defmodule Baudrate.Content.HashtagTest do
       assert Content.extract_tags("#Elixir #PHOENIX") == ["elixir", "phoenix"]
     end
 
+    test "normalizes diacritics and full-width forms as Mastodon does" do
+      assert Content.extract_tags("#CaféCon #cafecon #ＥＬＩＸＩＲ") == ["cafecon", "elixir"]
+    end
+
     test "returns empty list for nil" do
       assert Content.extract_tags(nil) == []
     end
@@ -101,6 +105,20 @@ defmodule Baudrate.Content.HashtagTest do
     end
   end
 
+  # --- extract_hashtags/1 ---
+
+  describe "extract_hashtags/1" do
+    test "pairs canonical forms with the first spelling" do
+      assert Content.extract_hashtags("#Café, #cafe and #ＥＬＩＸＩＲ") ==
+               [{"cafe", "Café"}, {"elixir", "ＥＬＩＸＩＲ"}]
+    end
+
+    test "skips tags without a canonical form" do
+      assert Content.extract_hashtags("#123 `#code`") == []
+      assert Content.extract_hashtags(nil) == []
+    end
+  end
+
   # --- sync_article_tags/1 ---
 
   describe "sync_article_tags/1" do
@@ -258,6 +276,16 @@ defmodule Baudrate.Content.HashtagTest do
       assert length(results) == 3
     end
 
+    test "normalizes the prefix like the tags" do
+      user = create_user()
+      board = create_board()
+
+      a = create_article(user, board, %{body: "#CaféCon"})
+      Content.sync_article_tags(a)
+
+      assert Content.search_tags("Café") == ["cafecon"]
+    end
+
     test "returns empty list when no tags match" do
       assert Content.search_tags("zzz") == []
     end
@@ -124,4 +124,58 @@ defmodule Baudrate.Content.LinkPreview.FetcherTest do
       assert preview.description == "Meta description"
     end
   end
+
+  describe "fetch_or_get/2 with robots.txt" do
+    setup do
+      host = "robots-#{System.unique_integer([:positive])}.example.com"
+      test_pid = self()
+
+      stub = fn robots_txt ->
+        fn conn ->
+          if conn.request_path == "/robots.txt" do
+            send(test_pid, :robots_fetched)
+
+            case robots_txt do
+              nil -> Plug.Conn.send_resp(conn, 404, "Not Found")
+              robots_txt -> Req.Test.text(conn, robots_txt)
+            end
+          else
+            Req.Test.html(conn, @og_html)
+          end
+        end
+      end
+
+      %{host: host, stub: stub}
+    end
+
+    test "skips disallowed paths", %{host: host, stub: stub} do
+      Req.Test.stub(Baudrate.Federation.HTTPClient, stub.("User-agent: *\nDisallow: /private\n"))
+
+      assert {:error, :robots_disallowed} = Fetcher.fetch_or_get("https://#{host}/private/page")
+      assert {:ok, %LinkPreview{}} = Fetcher.fetch_or_get("https://#{host}/public")
+    end
+
+    test "fetches robots.txt once per origin", %{host: host, stub: stub} do
+      Req.Test.stub(Baudrate.Federation.HTTPClient, stub.("User-agent: *\nDisallow: /private\n"))
+
+      assert {:ok, _} = Fetcher.fetch_or_get("https://#{host}/one")
+      assert {:ok, _} = Fetcher.fetch_or_get("https://#{host}/two")
+
+      assert_received :robots_fetched
+      refute_received :robots_fetched
+    end
+
+    test "keeps the crawl delay between fetches", %{host: host, stub: stub} do
+      Req.Test.stub(Baudrate.Federation.HTTPClient, stub.("User-agent: *\nCrawl-delay: 60\n"))
+
+      assert {:ok, _} = Fetcher.fetch_or_get("https://#{host}/one")
+      assert {:error, :crawl_delay} = Fetcher.fetch_or_get("https://#{host}/two")
+    end
+
+    test "allows everything when robots.txt is missing", %{host: host, stub: stub} do
+      Req.Test.stub(Baudrate.Federation.HTTPClient, stub.(nil))
+
+      assert {:ok, %LinkPreview{}} = Fetcher.fetch_or_get("https://#{host}/private/page")
+    end
+  end
 end
@@ -23,6 +23,14 @@ defmodule Baudrate.Content.MarkdownTest do
       assert html =~ "<code"
     end
 
+    test "renders fence info strings as code block attributes" do
+      html = Markdown.to_html("```elixir title=lib/app.ex {2}\ndef run, do: :ok\n#not_a_tag\n```")
+
+      assert html =~ ~s[<pre data-filename="lib/app.ex" data-highlight-lines="2">]
+      assert html =~ ~s[<code class="language-elixir">def run, do: :ok\n#not_a_tag</code>]
+      refute html =~ ~s[class="hashtag"]
+    end
+
     test "returns empty string for nil" do
       assert Markdown.to_html(nil) == ""
     end
@@ -12,6 +12,13 @@ defmodule Baudrate.Content.TitleDeriverTest do
       assert "First line" == TitleDeriver.derive_title(%{}, "First line\nSecond line")
     end
 
+    test "prefers a heading, else the first sentence of the first paragraph" do
+      assert "Release notes" ==
+               TitleDeriver.derive_title(%{}, "<p>Intro.</p><h1>Release notes</h1>")
+
+      assert "Fish & chips" == TitleDeriver.derive_title(%{}, "<p>Fish &amp; chips. Yum</p>")
+    end
+
     test "truncates long first line" do
       long = String.duplicate("word ", 20)
       title = TitleDeriver.derive_title(%{}, long)
@@ -361,6 +361,29 @@ defmodule Baudrate.Federation.PublisherTest do
       assert tag["href"] =~ "/tags/elixir"
     end
 
+    test "Hashtag objects are named as written and link the canonical tag" do
+      user = create_user()
+      board = create_board()
+
+      {:ok, %{article: article}} =
+        Content.create_article(
+          %{
+            title: "Café Article",
+            body: "Meet at the #Café",
+            slug: "art-cafe-#{System.unique_integer([:positive])}",
+            user_id: user.id
+          },
+          [board.id]
+        )
+
+      article = Repo.preload(article, [:boards, :user])
+
+      assert [%{"name" => "#Café", "href" => href}] =
+               Baudrate.Federation.article_object(article)["tag"]
+
+      assert String.ends_with?(href, "/tags/cafe")
+    end
+
     test "Article without hashtags has no tag key" do
       user = create_user()
       board = create_board()
@@ -169,12 +169,19 @@ defmodule Baudrate.Federation.SanitizerTest do
       assert result =~ ~s(<span class="mention">)
     end
 
-    test "preserves span with invisible class" do
-      html = ~s(<span class="invisible">hidden</span>)
+    test "preserves span with invisible class inside a link" do
+      html = ~s(<a href="https://example.com/"><span class="invisible">https://</span>example</a>)
       result = Sanitizer.sanitize(html)
       assert result =~ ~s(<span class="invisible">)
     end
 
+    test "strips invisible class from span outside a link" do
+      html = ~s(<span class="invisible">hidden</span>)
+      result = Sanitizer.sanitize(html)
+      refute result =~ "class"
+      assert result =~ "hidden"
+    end
+
     test "strips unsafe class from span" do
       html = ~s(<span class="malicious-class">text</span>)
       result = Sanitizer.sanitize(html)
@@ -263,6 +270,98 @@ defmodule Baudrate.Federation.SanitizerTest do
     end
   end
 
+  describe "sanitize/2" do
+    test "normalizes the quirks of the source before sanitizing" do
+      assert Sanitizer.sanitize("<p>[b]hi[/b]<script>x</script></p>", :friendica) ==
+               "<p><strong>hi</strong></p>"
+
+      assert Sanitizer.sanitize("<p>[b]hi[/b]</p>", nil) == "<p>[b]hi[/b]</p>"
+      assert Sanitizer.sanitize(nil, :misskey) == ""
+    end
+  end
+
+  describe "sanitize_with_text/1" do
+    test "returns the sanitized HTML and its plain text" do
+      html = ~s[<p>Hello <a href="https://example.com" onclick="x()">world</a></p><p>again</p>]
+      {body_html, body} = Sanitizer.sanitize_with_text(html)
+
+      assert body_html == Sanitizer.sanitize(html)
+      assert body == "Hello world\n\nagain"
+    end
+
+    test "returns empty strings for nil and empty input" do
+      assert Sanitizer.sanitize_with_text(nil) == {"", ""}
+      assert Sanitizer.sanitize_with_text("") == {"", ""}
+    end
+  end
+
+  describe "sanitize_feed/1" do
+    test "keeps images and drops scripts and empty paragraphs" do
+      html = ~s[<p>Hi</p><p> </p><img src="https://example.com/a.png"><script>x()</script>]
+
+      result = Sanitizer.sanitize_feed(html)
+
+      assert result =~ ~s[<p>Hi</p><img src="https://example.com/a.png"]
+      refute result =~ "<p> </p>"
+      refute result =~ "script"
+    end
+
+    test "returns an empty string for nil and empty input" do
+      assert Sanitizer.sanitize_feed(nil) == ""
+      assert Sanitizer.sanitize_feed("") == ""
+    end
+  end
+
+  describe "sanitize_inline/2" do
+    test "passes through nil as nil" do
+      assert Sanitizer.sanitize_inline(nil, 200) == nil
+    end
+
+    test "returns one line of decoded plain text within the limit" do
+      assert Sanitizer.sanitize_inline("<p>Pizza &amp; pasta</p>\n<p>:pizza:</p>", 200) ==
+               "Pizza & pasta :pizza:"
+
+      assert Sanitizer.sanitize_inline("Option\x00 two", 4) == "Opti"
+    end
+  end
+
+  describe "render_mfm/2" do
+    test "renders MFM functions as spans by default, or strips them" do
+      html = Sanitizer.sanitize("<p>$[tada Congrats]!</p>")
+
+      assert Sanitizer.render_mfm(html) == ~s(<p><span class="mfm-tada">Congrats</span>!</p>)
+      assert Sanitizer.render_mfm(html, :strip) == "<p>Congrats!</p>"
+    end
+  end
+
+  describe "extract_quote/1" do
+    test "returns the quoted URL and the content to sanitize" do
+      html = ~s(<p>Agreed</p><p class="quote-inline">RE: <a href="https://a.example/1">x</a></p>)
+
+      assert {"https://a.example/1", rest} = Sanitizer.extract_quote(html)
+      assert Sanitizer.sanitize(rest) == "<p>Agreed</p>"
+    end
+
+    test "returns nil without a quote" do
+      assert Sanitizer.extract_quote("<p>Hello</p>") == nil
+      assert Sanitizer.extract_quote(nil) == nil
+    end
+  end
+
+  describe "sanitize_content_map/1" do
+    test "returns the sanitized variants and the primary language" do
+      map = %{"en" => "<p>Hello<script>x</script></p>", "ja" => "<p>やあ</p>"}
+
+      assert Sanitizer.sanitize_content_map(map) ==
+               {%{"en" => "<p>Hello</p>", "ja" => "<p>やあ</p>"}, "en"}
+    end
+
+    test "treats anything but a map as empty" do
+      assert Sanitizer.sanitize_content_map(nil) == {%{}, nil}
+      assert Sanitizer.sanitize_content_map("<p>x</p>") == {%{}, nil}
+    end
+  end
+
   describe "sanitize_display_name/1" do
     test "passes through nil as nil" do
       assert Sanitizer.sanitize_display_name(nil) == nil
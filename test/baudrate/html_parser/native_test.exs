@@ -73,6 +73,98 @@ defmodule Baudrate.HtmlParser.NativeTest do
     end
   end
 
+  describe "extract_card/2" do
+    @base "https://example.com/articles/1"
+
+    test "extracts OG and Twitter Card fields" do
+      html = """
+      <html><head>
+        <meta property="og:title" content="OG Title">
+        <meta property="og:description" content="OG Description">
+        <meta property="og:image" content="https://cdn.example.com/og.jpg">
+        <meta property="og:site_name" content="Example">
+        <meta name="twitter:card" content="summary_large_image">
+        <meta name="twitter:site" content="@example">
+        <meta name="twitter:creator" content="@alice">
+        <link rel="canonical" href="https://example.com/articles/1-slug">
+      </head><body></body></html>
+      """
+
+      card = HtmlParser.extract_card(html, @base)
+      assert card.title == "OG Title"
+      assert card.description == "OG Description"
+      assert card.image_url == "https://cdn.example.com/og.jpg"
+      assert card.site_name == "Example"
+      assert card.twitter_card == "summary_large_image"
+      assert card.twitter_site == "@example"
+      assert card.twitter_creator == "@alice"
+      assert card.canonical_url == "https://example.com/articles/1-slug"
+    end
+
+    test "falls back to Twitter Card then <title> and meta description" do
+      html = """
+      <html><head>
+        <title>Page Title</title>
+        <meta name="description" content="Meta desc">
+        <meta name="twitter:image" content="/tw.jpg">
+      </head><body></body></html>
+      """
+
+      card = HtmlParser.extract_card(html, @base)
+      assert card.title == "Page Title"
+      assert card.description == "Meta desc"
+      assert card.image_url == "https://example.com/tw.jpg"
+    end
+
+    test "accepts og:* tags declared with name" do
+      html = ~s(<html><head><meta name="og:title" content="Named"></head></html>)
+      assert HtmlParser.extract_card(html, @base).title == "Named"
+    end
+
+    test "resolves relative URLs against the base and falls back to og:url" do
+      html = """
+      <html><head>
+        <meta property="og:image" content="../img/a.png">
+        <meta property="og:url" content="/articles/1">
+      </head></html>
+      """
+
+      card = HtmlParser.extract_card(html, @base)
+      assert card.image_url == "https://example.com/img/a.png"
+      assert card.canonical_url == "https://example.com/articles/1"
+    end
+
+    test "drops non-HTTP(S) URLs" do
+      html = ~s(<html><head><meta property="og:image" content="javascript:alert(1)"></head></html>)
+      assert HtmlParser.extract_card(html, @base).image_url == nil
+    end
+
+    test "collapses whitespace and applies length limits" do
+      long = String.duplicate("a", 400)
+
+      html = """
+      <html><head>
+        <meta property="og:title" content="#{long}">
+        <meta property="og:description" content="  spaced
+          out   text ">
+      </head></html>
+      """
+
+      card = HtmlParser.extract_card(html, @base)
+      assert String.length(card.title) == 300
+      assert card.description == "spaced out text"
+    end
+
+    test "returns nil fields for empty HTML" do
+      card = HtmlParser.extract_card("", @base)
+      assert card.title == nil
+      assert card.description == nil
+      assert card.image_url == nil
+      assert card.canonical_url == nil
+      assert card.twitter_card == nil
+    end
+  end
+
   describe "extract_first_url/2" do
     test "extracts first external URL" do
       html = ~s(<a href="https://example.com/page">Link</a>)
@@ -138,4 +230,257 @@ defmodule Baudrate.HtmlParser.NativeTest do
       assert HtmlParser.extract_first_url("", "https://localhost") == nil
     end
   end
+
+  describe "extract_rel_me/2" do
+    @profile "https://local.example/@alice"
+
+    test "finds a matching <a rel=\"me\"> link" do
+      html = ~s(<html><body><a rel="me" href="https://local.example/@alice">Me</a></body></html>)
+      assert HtmlParser.extract_rel_me(html, @profile) == {true, [@profile]}
+    end
+
+    test "finds <link rel=\"me\"> and multi-token rel values" do
+      html = """
+      <html><head><link rel="me" href="https://other.example/~alice"></head>
+      <body><a rel="noopener ME" href="https://local.example/@alice/">Fediverse</a></body></html>
+      """
+
+      assert {true, targets} = HtmlParser.extract_rel_me(html, @profile)
+      assert targets == ["https://other.example/~alice", "https://local.example/@alice/"]
+    end
+
+    test "ignores fragments and host case when matching" do
+      html = ~s(<a rel="me" href="https://LOCAL.example/@alice#profile">Me</a>)
+      assert {true, _} = HtmlParser.extract_rel_me(html, @profile)
+    end
+
+    test "reports no match when no target points back" do
+      html = ~s(<a rel="me" href="https://local.example/@bob">Bob</a>)
+      assert HtmlParser.extract_rel_me(html, @profile) == {false, ["https://local.example/@bob"]}
+    end
+
+    test "ignores links without rel=me" do
+      html = ~s(<a href="https://local.example/@alice">Me</a><a rel="nofollow" href="#{@profile}">x</a>)
+      assert HtmlParser.extract_rel_me(html, @profile) == {false, []}
+    end
+
+    test "ignores relative and non-HTTP(S) targets" do
+      html = ~s(<a rel="me" href="/@alice">a</a><a rel="me" href="mailto:alice@local.example">b</a>)
+      assert HtmlParser.extract_rel_me(html, @profile) == {false, []}
+    end
+
+    test "deduplicates targets" do
+      html = String.duplicate(~s(<a rel="me" href="#{@profile}">Me</a>), 2)
+      assert HtmlParser.extract_rel_me(html, @profile) == {true, [@profile]}
+    end
+  end
+
+  describe "extract_oembed_links/1" do
+    test "returns JSON endpoints before XML ones" do
+      html = """
+      <html><head>
+        <link rel="alternate" type="text/xml+oembed" href="https://video.example/oembed?format=xml&amp;url=x">
+        <link rel="alternate" type="application/json+oembed" href="https://video.example/oembed?format=json&amp;url=x">
+      </head></html>
+      """
+
+      assert HtmlParser.extract_oembed_links(html) == [
+               {:json, "https://video.example/oembed?format=json&url=x"},
+               {:xml, "https://video.example/oembed?format=xml&url=x"}
+             ]
+    end
+
+    test "ignores non-oEmbed alternates and other rels" do
+      html = """
+      <html><head>
+        <link rel="alternate" type="application/rss+xml" href="https://example.com/feed">
+        <link rel="preload" type="application/json+oembed" href="https://example.com/oembed">
+      </head></html>
+      """
+
+      assert HtmlParser.extract_oembed_links(html) == []
+    end
+
+    test "ignores relative endpoints" do
+      html = ~s(<link rel="alternate" type="application/json+oembed" href="/oembed?url=x">)
+      assert HtmlParser.extract_oembed_links(html) == []
+    end
+
+    test "returns an empty list for empty HTML" do
+      assert HtmlParser.extract_oembed_links("") == []
+    end
+  end
+
+  describe "extract_icons/2" do
+    @base "https://example.com/blog/"
+
+    test "sorts touch icons first, then by size" do
+      html = """
+      <html><head>
+        <link rel="icon" href="/favicon-16.png" sizes="16x16" type="image/png">
+        <link rel="icon" href="/favicon-32.png" sizes="32x32" type="image/png">
+        <link rel="apple-touch-icon" href="/touch.png">
+        <link rel="apple-touch-icon" href="/touch-180.png" sizes="180x180">
+      </head></html>
+      """
+
+      assert Enum.map(HtmlParser.extract_icons(html, @base), & &1.url) == [
+               "https://example.com/touch-180.png",
+               "https://example.com/touch.png",
+               "https://example.com/favicon-32.png",
+               "https://example.com/favicon-16.png"
+             ]
+    end
+
+    test "reports rel, size, and content type" do
+      html = """
+      <link rel="shortcut icon" href="favicon.ico" sizes="16x16 48x48" type="Image/X-Icon">
+      <link rel="apple-touch-icon-precomposed" href="https://cdn.example.com/t.png">
+      """
+
+      assert [touch, icon] = HtmlParser.extract_icons(html, @base)
+
+      assert touch == %HtmlParser.Icon{
+               url: "https://cdn.example.com/t.png",
+               rel: "apple-touch-icon",
+               size: nil,
+               content_type: nil
+             }
+
+      assert icon == %HtmlParser.Icon{
+               url: "https://example.com/blog/favicon.ico",
+               rel: "icon",
+               size: 48,
+               content_type: "image/x-icon"
+             }
+    end
+
+    test "ignores mask icons, other rels, and non-HTTP(S) URLs" do
+      html = """
+      <link rel="mask-icon" href="/mask.svg">
+      <link rel="stylesheet" href="/style.css">
+      <link rel="icon" href="data:image/png;base64,AAAA">
+      """
+
+      assert HtmlParser.extract_icons(html, @base) == []
+    end
+
+    test "treats sizes=\"any\" as unknown" do
+      html = ~s(<link rel="icon" href="/icon.svg" sizes="any" type="image/svg+xml">)
+      assert [%{size: nil, content_type: "image/svg+xml"}] = HtmlParser.extract_icons(html, @base)
+    end
+
+    test "returns an empty list for empty HTML" do
+      assert HtmlParser.extract_icons("", @base) == []
+    end
+  end
+
+  describe "robots_allowed?/3" do
+    @ua "Baudrate/1.0 (+https://local.example)"
+    @robots """
+    # Example
+    User-agent: *
+    Disallow: /private
+    Allow: /private/public
+    Disallow: /*.pdf$
+    Crawl-delay: 5
+
+    User-agent: baudrate
+    User-agent: otherbot
+    Disallow: /no-bots
+    Crawl-delay: 1.5
+    """
+
+    test "uses the group matching the product token" do
+      assert HtmlParser.robots_allowed?(@robots, @ua, "/private")
+      refute HtmlParser.robots_allowed?(@robots, @ua, "/no-bots/page")
+    end
+
+    test "falls back to the * group" do
+      refute HtmlParser.robots_allowed?(@robots, "SomeBot/2.0", "/private/page")
+      assert HtmlParser.robots_allowed?(@robots, "SomeBot/2.0", "/no-bots")
+    end
+
+    test "longest match wins" do
+      assert HtmlParser.robots_allowed?(@robots, "SomeBot", "/private/public/page")
+    end
+
+    test "supports wildcards and end anchors" do
+      refute HtmlParser.robots_allowed?(@robots, "SomeBot", "/files/doc.pdf")
+      assert HtmlParser.robots_allowed?(@robots, "SomeBot", "/files/doc.pdf?download=1")
+    end
+
+    test "allow wins ties" do
+      robots = "User-agent: *\nDisallow: /page\nAllow: /page\n"
+      assert HtmlParser.robots_allowed?(robots, @ua, "/page")
+    end
+
+    test "empty Disallow allows everything" do
+      assert HtmlParser.robots_allowed?("User-agent: *\nDisallow:\n", @ua, "/anything")
+    end
+
+    test "allows everything when no group applies or robots.txt is empty" do
+      assert HtmlParser.robots_allowed?("User-agent: otherbot\nDisallow: /\n", @ua, "/")
+      assert HtmlParser.robots_allowed?("", @ua, "/")
+    end
+
+    test "always allows /robots.txt" do
+      assert HtmlParser.robots_allowed?("User-agent: *\nDisallow: /\n", @ua, "/robots.txt")
+    end
+
+    test "ignores rules past the first 500 KiB" do
+      padding = String.duplicate("# padding\n", 52_000)
+      robots = "User-agent: *\nDisallow: /early\n" <> padding <> "Disallow: /late\n"
+
+      refute HtmlParser.robots_allowed?(robots, @ua, "/early")
+      assert HtmlParser.robots_allowed?(robots, @ua, "/late")
+    end
+  end
+
+  describe "robots_crawl_delay/2" do
+    @robots """
+    User-agent: *
+    Crawl-delay: 5
+
+    User-agent: baudrate
+    Crawl-delay: 1.5
+    """
+
+    test "returns the delay for the matching group" do
+      assert HtmlParser.robots_crawl_delay(@robots, "Baudrate/1.0") == 1.5
+      assert HtmlParser.robots_crawl_delay(@robots, "SomeBot") == 5.0
+    end
+
+    test "returns nil when no delay is declared" do
+      assert HtmlParser.robots_crawl_delay("User-agent: *\nDisallow: /x\n", "SomeBot") == nil
+    end
+  end
+
+  describe "pathological input" do
+    test "returns results instead of panicking" do
+      inputs = [
+        String.duplicate(~s(<div><a href="//">), 2_000),
+        ~s(<meta property="og:title" content="<link rel=icon) <> String.duplicate("%", 10_000),
+        "\0",
+        ""
+      ]
+
+      for html <- inputs do
+        assert %HtmlParser.OgMetadata{} = HtmlParser.parse_og_metadata(html)
+        assert %HtmlParser.Card{} = HtmlParser.extract_card(html, "https://example.com/")
+        assert is_list(HtmlParser.extract_icons(html, "not a url"))
+        assert is_list(HtmlParser.extract_oembed_links(html))
+        assert {_, _} = HtmlParser.extract_rel_me(html, "::")
+        refute match?({:error, {:panic, _}}, HtmlParser.extract_first_url(html, ""))
+      end
+    end
+
+    test "evaluates malformed robots.txt" do
+      robots = String.duplicate("User-agent: *\nDisallow: /*$*$*\nCrawl-delay: 1e400\n", 1_000)
+
+      assert HtmlParser.robots_allowed?(robots, "Baudrate", String.duplicate("/a/b", 100))
+      # Out-of-range delays are ignored rather than returned as infinity.
+      assert HtmlParser.robots_crawl_delay(robots, "Baudrate") == nil
+    end
+  end
 end
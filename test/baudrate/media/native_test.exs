@@ -0,0 +1,821 @@
+defmodule Baudrate.Media.NativeTest do
+  use ExUnit.Case, async: true
+
+  alias Baudrate.Media.Native
+
+  defp png(width, height, color) do
+    {:ok, image} = Image.new(width, height, color: color)
+    Image.write!(image, :memory, suffix: ".png")
+  end
+
+  # PNG headers claiming 10000 × 10000 RGB pixels (300 MB decoded),
+  # followed by an empty IDAT chunk.
+  defp huge_png_header do
+    ihdr = <<"IHDR", 10_000::32, 10_000::32, 8, 2, 0, 0, 0>>
+
+    <<0x89, "PNG\r\n", 0x1A, "\n", 13::32, ihdr::binary, :erlang.crc32(ihdr)::32, 0::32, "IDAT",
+      :erlang.crc32("IDAT")::32>>
+  end
+
+  # A 1 × 1 GIF with two frames.
+  @animated_gif Base.decode64!(
+                  "R0lGODlhAQABAIAAAAAAAP///yH5BAAKAAAALAAAAAABAAEAAAICRAEAIfkEAAoAAAAsAAAAAAEAAQAAAgJEAQA7"
+                )
+
+  # An 8 × 8 animation with a red frame followed by a blue one, encoded by
+  # libvips as a stack of pages.
+  defp red_then_blue(suffix) do
+    {:ok, red} = Image.new(8, 8, color: [255, 0, 0])
+    {:ok, blue} = Image.new(8, 8, color: [0, 0, 255])
+    {:ok, pages} = Vix.Vips.Operation.arrayjoin([red, blue], across: 1)
+
+    {:ok, pages} =
+      Vix.Vips.Image.mutate(pages, fn mut ->
+        :ok = Vix.Vips.MutableImage.set(mut, "page-height", :gint, 8)
+      end)
+
+    Image.write!(pages, :memory, suffix: suffix)
+  end
+
+  # A landscape JPEG whose EXIF orientation (6) says "rotate 90° clockwise",
+  # as produced by phone cameras held upright.
+  defp rotated_jpeg(width, height) do
+    {:ok, image} = Image.new(width, height, color: [255, 0, 0])
+
+    {:ok, image} =
+      Vix.Vips.Image.mutate(image, fn mut ->
+        :ok = Vix.Vips.MutableImage.set(mut, "orientation", :gint, 6)
+      end)
+
+    Image.write!(image, :memory, suffix: ".jpg")
+  end
+
+  describe "blurhash_encode/3" do
+    test "encodes an image with the requested components" do
+      assert {:ok, hash} = Native.blurhash_encode(png(120, 80, [255, 100, 50]), 4, 3)
+      # 1 size flag + 1 max AC + 4 DC + 2 per AC component
+      assert String.length(hash) == 4 + 2 * 4 * 3
+    end
+
+    test "is deterministic for the same image" do
+      bytes = png(64, 64, [0, 128, 255])
+      assert Native.blurhash_encode(bytes, 4, 4) == Native.blurhash_encode(bytes, 4, 4)
+    end
+
+    test "rejects out-of-range components" do
+      bytes = png(16, 16, [0, 0, 0])
+      assert {:error, :invalid_components} = Native.blurhash_encode(bytes, 0, 3)
+      assert {:error, :invalid_components} = Native.blurhash_encode(bytes, 4, 10)
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.blurhash_encode("not an image", 4, 3)
+    end
+  end
+
+  describe "blurhash_decode/3" do
+    @hash "LEHV6nWB2yk8pyo0adR*.7kCMdnj"
+
+    test "renders a PNG of the requested size" do
+      assert {:ok, <<0x89, "PNG", _::binary>> = png} = Native.blurhash_decode(@hash, 40, 30)
+      assert {:ok, image} = Image.from_binary(png)
+      assert {40, 30, _bands} = Image.shape(image)
+    end
+
+    test "round-trips with blurhash_encode/3" do
+      {:ok, hash} = Native.blurhash_encode(png(32, 32, [255, 0, 0]), 4, 3)
+      assert {:ok, png} = Native.blurhash_decode(hash, 8, 8)
+      assert {:ok, image} = Image.from_binary(png)
+      assert {:ok, [r, g, b | _]} = Image.get_pixel(image, 4, 4)
+      assert r > 200 and g < 50 and b < 50
+    end
+
+    test "rejects invalid hashes" do
+      assert {:error, :invalid_blurhash} = Native.blurhash_decode("abc", 8, 8)
+      assert {:error, :invalid_blurhash} = Native.blurhash_decode("LEHV6nWB2yk8pyo0adR*.7kCMdn€", 8, 8)
+    end
+
+    test "rejects out-of-range dimensions" do
+      assert {:error, :invalid_dimensions} = Native.blurhash_decode(@hash, 0, 8)
+      assert {:error, :invalid_dimensions} = Native.blurhash_decode(@hash, 8, 4096)
+    end
+  end
+
+  describe "thumbhash_encode/1 and thumbhash_decode/1" do
+    test "round-trips and preserves the aspect ratio" do
+      assert {:ok, hash} = Native.thumbhash_encode(png(200, 100, [0, 128, 255]))
+      assert {:ok, png} = Native.thumbhash_decode(hash)
+      assert {:ok, image} = Image.from_binary(png)
+      assert {w, h, _bands} = Image.shape(image)
+      assert w == 32 and h < w
+    end
+
+    test "accepts unpadded hashes" do
+      {:ok, hash} = Native.thumbhash_encode(png(50, 50, [255, 255, 0]))
+      assert {:ok, _png} = Native.thumbhash_decode(String.trim_trailing(hash, "="))
+    end
+
+    test "rejects invalid hashes" do
+      assert {:error, :invalid_thumbhash} = Native.thumbhash_decode("not base64!")
+      assert {:error, :invalid_thumbhash} = Native.thumbhash_decode(Base.encode64("abc"))
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.thumbhash_encode("not an image")
+    end
+  end
+
+  describe "resize_image/5" do
+    test "downscales preserving the aspect ratio" do
+      assert {:ok, %{data: data, width: 100, height: 50}} =
+               Native.resize_image(png(400, 200, [0, 0, 255]), 100, 100, :jpeg)
+
+      assert <<0xFF, 0xD8, 0xFF, _::binary>> = data
+    end
+
+    test "does not upscale images that already fit" do
+      assert {:ok, %{width: 40, height: 30}} =
+               Native.resize_image(png(40, 30, [0, 0, 255]), 100, 100, :png)
+    end
+
+    test "encodes each output format" do
+      bytes = png(64, 64, [0, 255, 0])
+
+      assert {:ok, %{data: <<0xFF, 0xD8, _::binary>>}} = Native.resize_image(bytes, 32, 32, :jpeg)
+      assert {:ok, %{data: <<0x89, "PNG", _::binary>>}} = Native.resize_image(bytes, 32, 32, :png)
+
+      assert {:ok, %{data: <<"RIFF", _::32, "WEBP", _::binary>>}} =
+               Native.resize_image(bytes, 32, 32, :webp)
+    end
+
+    test "applies the EXIF orientation" do
+      assert {:ok, %{width: 50, height: 100}} =
+               Native.resize_image(rotated_jpeg(200, 100), 100, 100, :png)
+    end
+
+    test "rejects images that would decode to too much memory" do
+      assert {:error, :image_too_large} = Native.resize_image(huge_png_header(), 100, 100, :png)
+    end
+
+    test "rejects zero dimensions" do
+      assert {:error, :invalid_dimensions} =
+               Native.resize_image(png(16, 16, [0, 0, 0]), 0, 100, :png)
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.resize_image("nope", 10, 10, :png)
+    end
+
+    test "rejects animations over the limits" do
+      assert {:error, :too_many_frames} =
+               Native.resize_image(@animated_gif, 10, 10, :png, %{max_frames: 1})
+
+      limits = %{max_decoded_bytes: 300}
+
+      assert {:error, :image_too_large} =
+               Native.resize_image(red_then_blue(".gif"), 10, 10, :png, limits)
+
+      assert {:ok, _} =
+               Native.resize_image(@animated_gif, 10, 10, :png, %{
+                 max_frames: 2,
+                 max_decoded_bytes: nil
+               })
+    end
+  end
+
+  describe "strip_metadata/2" do
+    # Splices an APP1 EXIF segment and a comment into a JPEG.
+    defp jpeg_with_metadata do
+      {:ok, image} = Image.new(40, 20, color: [0, 0, 255])
+      <<0xFF, 0xD8, rest::binary>> = Image.write!(image, :memory, suffix: ".jpg")
+
+      exif = "Exif\0\0MM\0*\0\0\0\x08\0\0\0\0\0\0GPS-SECRET"
+      comment = "camera serial 12345"
+
+      <<0xFF, 0xD8, 0xFF, 0xE1, byte_size(exif) + 2::16, exif::binary, 0xFF, 0xFE,
+        byte_size(comment) + 2::16, comment::binary, rest::binary>>
+    end
+
+    # Inserts a tEXt chunk right after the PNG IHDR chunk.
+    defp png_with_text do
+      <<signature::binary-8, ihdr::binary-25, rest::binary>> = png(20, 20, [255, 0, 0])
+      body = "tEXtComment\0GPS-SECRET"
+      chunk = <<byte_size(body) - 4::32, body::binary, :erlang.crc32(body)::32>>
+      signature <> ihdr <> chunk <> rest
+    end
+
+    test "removes EXIF and comments from JPEG" do
+      assert {:ok, stripped} = Native.strip_metadata(jpeg_with_metadata())
+      refute stripped =~ "GPS-SECRET"
+      refute stripped =~ "camera serial"
+      assert {:ok, image} = Image.from_binary(stripped)
+      assert {40, 20, _} = Image.shape(image)
+    end
+
+    test "removes text chunks from PNG" do
+      assert {:ok, stripped} = Native.strip_metadata(png_with_text(), false)
+      refute stripped =~ "GPS-SECRET"
+      assert <<0x89, "PNG", _::binary>> = stripped
+      assert {:ok, _image} = Image.from_binary(stripped)
+    end
+
+    test "leaves images without metadata decodable" do
+      {:ok, image} = Image.new(30, 30, color: [0, 200, 0])
+      webp = Image.write!(image, :memory, suffix: ".webp")
+
+      assert {:ok, <<"RIFF", _::32, "WEBP", _::binary>> = stripped} =
+               Native.strip_metadata(webp)
+
+      assert {:ok, _image} = Image.from_binary(stripped)
+    end
+
+    test "bakes the EXIF orientation into the pixels" do
+      assert {:ok, stripped} = Native.strip_metadata(rotated_jpeg(200, 100))
+      assert {:ok, image} = Image.from_binary(stripped)
+      assert {100, 200, _} = Image.shape(image)
+      assert {:ok, %{width: 100, height: 200}} = Native.resize_image(stripped, 500, 500, :png)
+    end
+
+    test "rejects unsupported formats" do
+      {:ok, image} = Image.new(10, 10, color: [0, 0, 0])
+      gif = Image.write!(image, :memory, suffix: ".gif")
+
+      assert {:error, :unsupported_format} = Native.strip_metadata(gif)
+      assert {:error, :unsupported_format} = Native.strip_metadata("nope")
+    end
+  end
+
+  describe "auto_orient/1" do
+    test "rotates images with an EXIF orientation" do
+      assert {:ok, oriented} = Native.auto_orient(rotated_jpeg(200, 100))
+      assert <<0xFF, 0xD8, _::binary>> = oriented
+      assert {:ok, image} = Image.from_binary(oriented)
+      assert {100, 200, _} = Image.shape(image)
+      # No orientation tag is left behind to rotate it a second time.
+      assert {:ok, ^oriented} = Native.auto_orient(oriented)
+    end
+
+    test "returns upright images unchanged" do
+      bytes = png(30, 20, [1, 2, 3])
+      assert {:ok, ^bytes} = Native.auto_orient(bytes)
+    end
+
+    test "rejects unsupported input" do
+      assert {:error, :unsupported_format} = Native.auto_orient("nope")
+    end
+  end
+
+  describe "transcode_image/3" do
+    test "encodes lossy WebP" do
+      assert {:ok, %{data: <<"RIFF", _::32, "WEBP", "VP8 ", _::binary>> = data, width: 64, height: 48}} =
+               Native.transcode_image(png(64, 48, [200, 30, 30]), :webp, 80)
+
+      assert {:ok, _image} = Image.from_binary(data)
+    end
+
+    test "encodes AVIF" do
+      assert {:ok, %{data: <<_::32, "ftypavif", _::binary>>, width: 16, height: 16}} =
+               Native.transcode_image(png(16, 16, [30, 30, 200]), :avif, 60)
+    end
+
+    test "applies the EXIF orientation" do
+      assert {:ok, %{width: 100, height: 200}} =
+               Native.transcode_image(rotated_jpeg(200, 100), :webp, 75)
+    end
+
+    test "rejects out-of-range quality" do
+      bytes = png(16, 16, [0, 0, 0])
+      assert {:error, :invalid_quality} = Native.transcode_image(bytes, :webp, 0)
+      assert {:error, :invalid_quality} = Native.transcode_image(bytes, :avif, 101)
+    end
+
+    test "rejects oversized images before decoding" do
+      assert {:error, :image_too_large} = Native.transcode_image(huge_png_header(), :webp, 80)
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.transcode_image("nope", :webp, 80)
+    end
+  end
+
+  describe "probe_image/1" do
+    test "reports format, dimensions, and decoded size" do
+      assert {:ok, %{format: :png, width: 120, height: 80, animated: false, decoded_bytes: bytes}} =
+               Native.probe_image(png(120, 80, [1, 2, 3]))
+
+      assert bytes >= 120 * 80 * 3
+    end
+
+    test "detects animated GIFs" do
+      assert {:ok, %{format: :gif, width: 1, height: 1, animated: true}} =
+               Native.probe_image(@animated_gif)
+
+      {:ok, image} = Image.new(8, 8, color: [0, 0, 0])
+
+      assert {:ok, %{format: :gif, animated: false}} =
+               Native.probe_image(Image.write!(image, :memory, suffix: ".gif"))
+    end
+
+    test "reports displayed dimensions for rotated JPEGs" do
+      assert {:ok, %{format: :jpeg, width: 100, height: 200}} =
+               Native.probe_image(rotated_jpeg(200, 100))
+    end
+
+    test "reads only the headers" do
+      assert {:ok, %{width: 10_000, height: 10_000, decoded_bytes: 300_000_000}} =
+               Native.probe_image(huge_png_header())
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.probe_image("nope")
+    end
+  end
+
+  describe "sniff_mime/1" do
+    test "detects image types" do
+      {:ok, image} = Image.new(8, 8, color: [0, 0, 0])
+
+      assert Native.sniff_mime(png(8, 8, [0, 0, 0])) == "image/png"
+      assert Native.sniff_mime(Image.write!(image, :memory, suffix: ".jpg")) == "image/jpeg"
+      assert Native.sniff_mime(@animated_gif) == "image/gif"
+      assert Native.sniff_mime(Image.write!(image, :memory, suffix: ".webp")) == "image/webp"
+
+      {:ok, %{data: avif}} = Native.transcode_image(png(16, 16, [0, 0, 0]), :avif, 50)
+      assert Native.sniff_mime(avif) == "image/avif"
+    end
+
+    test "detects audio and video containers" do
+      assert Native.sniff_mime(<<"ID3", 4, 0, 0, 0, 0, 0, 0>>) == "audio/mpeg"
+      assert Native.sniff_mime(<<"OggS", 0, 2, 0::48>>) == "application/ogg"
+      assert Native.sniff_mime(<<"RIFF", 36::little-32, "WAVEfmt ">>) == "audio/wave"
+      assert Native.sniff_mime(<<"fLaC", 0, 0, 0, 34>>) == "audio/flac"
+
+      assert Native.sniff_mime(<<24::32, "ftypisom", 512::32, "isommp41">>) == "video/mp4"
+
+      webm =
+        <<0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x86, 0x81, 0x01, 0x42, 0xF7, 0x81, 0x01, 0x42,
+          0x82, 0x84, "webm", 0x42, 0x87, 0x81, 0x04>>
+
+      assert Native.sniff_mime(webm) == "video/webm"
+    end
+
+    test "detects MP3 frames without an ID3 tag" do
+      # Two 417-byte MPEG-1 Layer III frames at 128 kbps / 44.1 kHz.
+      frame = <<0xFF, 0xFB, 0x90, 0x00, 0::413*8>>
+      assert Native.sniff_mime(frame <> frame) == "audio/mpeg"
+
+      assert Native.sniff_mime(frame <> :binary.copy(<<0>>, 417)) == nil
+    end
+
+    test "ignores ISO media files without a matching brand" do
+      assert Native.sniff_mime(<<24::32, "ftypqt  ", 512::32, "qt  avc1">>) == nil
+    end
+
+    test "returns nil for unknown content" do
+      assert Native.sniff_mime("<!DOCTYPE html><html></html>") == nil
+      assert Native.sniff_mime("") == nil
+    end
+  end
+
+  describe "still_frame/1" do
+    test "returns the first frame of an animated GIF or WebP as a PNG" do
+      for suffix <- [".gif", ".webp"] do
+        animation = red_then_blue(suffix)
+        assert {:ok, %{animated: true}} = Native.probe_image(animation)
+
+        assert {:ok, %{data: <<0x89, "PNG", _::binary>> = png, width: 8, height: 8}} =
+                 Native.still_frame(animation)
+
+        assert {:ok, %{animated: false}} = Native.probe_image(png)
+        assert {:ok, [r, _g, b | _]} = Image.get_pixel(Image.open!(png), 4, 4)
+        assert r > 200 and b < 50
+      end
+    end
+
+    test "re-encodes still images" do
+      assert {:ok, %{width: 30, height: 20}} = Native.still_frame(png(30, 20, [0, 0, 0]))
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.still_frame("nope")
+    end
+  end
+
+  describe "crop_focal/7" do
+    # 200 × 100: a red square on the left, a blue one on the right.
+    defp red_blue_png do
+      {:ok, red} = Image.new(100, 100, color: [255, 0, 0])
+      {:ok, blue} = Image.new(100, 100, color: [0, 0, 255])
+      {:ok, image} = Vix.Vips.Operation.arrayjoin([red, blue], across: 2)
+      Image.write!(image, :memory, suffix: ".png")
+    end
+
+    defp center_pixel(png) do
+      {:ok, pixel} = Image.get_pixel(Image.open!(png), 25, 25)
+      pixel
+    end
+
+    test "crops around the focal point" do
+      assert {:ok, %{data: right, width: 50, height: 50}} =
+               Native.crop_focal(red_blue_png(), 0.5, 0.0, 50, 50, :png)
+
+      assert [0, 0, 255 | _] = center_pixel(right)
+
+      assert {:ok, %{data: left}} = Native.crop_focal(red_blue_png(), -0.5, 0.0, 50, 50, :png)
+      assert [255, 0, 0 | _] = center_pixel(left)
+    end
+
+    test "keeps the crop inside the image" do
+      assert {:ok, %{data: png, width: 50, height: 50}} =
+               Native.crop_focal(red_blue_png(), 1.0, 1.0, 50, 50, :png)
+
+      assert [0, 0, 255 | _] = center_pixel(png)
+    end
+
+    test "defaults to JPEG and never upscales" do
+      assert {:ok, %{data: <<0xFF, 0xD8, _::binary>>, width: 100, height: 50}} =
+               Native.crop_focal(png(100, 100, [0, 0, 0]), 0.0, 0.0, 400, 200)
+    end
+
+    test "rejects invalid arguments" do
+      assert {:error, :invalid_focal_point} =
+               Native.crop_focal(red_blue_png(), 1.5, 0.0, 50, 50, :png)
+
+      assert {:error, :invalid_dimensions} =
+               Native.crop_focal(red_blue_png(), 0.0, 0.0, 0, 50, :png)
+    end
+
+    test "rejects animations over the limits" do
+      assert {:error, :too_many_frames} =
+               Native.crop_focal(@animated_gif, 0.0, 0.0, 1, 1, :png, %{max_frames: 1})
+
+      assert {:error, :image_too_large} =
+               Native.crop_focal(red_blue_png(), 0.0, 0.0, 50, 50, :png, %{
+                 max_decoded_bytes: 300
+               })
+
+      assert {:ok, _} = Native.crop_focal(@animated_gif, 0.0, 0.0, 1, 1, :png, %{max_frames: 2})
+    end
+  end
+
+  describe "crop_image/7" do
+    test "cuts out the region" do
+      assert {:ok, %{data: png, width: 100, height: 50}} =
+               Native.crop_image(red_blue_png(), 0.5, 0.0, 0.5, 0.5, :png)
+
+      assert [0, 0, 255 | _] = center_pixel(png)
+    end
+
+    test "clamps the region to the image" do
+      assert {:ok, %{width: 20, height: 100}} =
+               Native.crop_image(red_blue_png(), 0.9, 0.0, 0.5, 1.0, :png)
+    end
+
+    test "rejects regions outside 0.0..1.0 or empty" do
+      assert {:error, :invalid_region} =
+               Native.crop_image(red_blue_png(), -0.1, 0.0, 0.5, 0.5, :png)
+
+      assert {:error, :invalid_region} =
+               Native.crop_image(red_blue_png(), 0.0, 0.0, 0.0, 0.5, :png)
+    end
+
+    test "rejects animations over the limits" do
+      assert {:error, :too_many_frames} =
+               Native.crop_image(@animated_gif, 0.0, 0.0, 1.0, 1.0, :png, %{max_frames: 1})
+
+      assert {:error, :image_too_large} =
+               Native.crop_image(red_blue_png(), 0.0, 0.0, 1.0, 1.0, :png, %{
+                 max_decoded_bytes: 300
+               })
+    end
+  end
+
+  describe "dominant_colors/2" do
+    test "returns colors ordered by prominence" do
+      {:ok, red} = Image.new(90, 30, color: [255, 0, 0])
+      {:ok, blue} = Image.new(30, 30, color: [0, 0, 255])
+      {:ok, image} = Vix.Vips.Operation.arrayjoin([red, blue], across: 2)
+
+      assert {:ok, ["#ff0000", "#0000ff"]} =
+               Native.dominant_colors(Image.write!(image, :memory, suffix: ".png"), 2)
+    end
+
+    test "returns fewer colors than requested for flat images" do
+      assert {:ok, ["#102030"]} = Native.dominant_colors(png(40, 40, [16, 32, 48]), 5)
+    end
+
+    test "ignores transparent pixels" do
+      assert {:ok, []} = Native.dominant_colors(png(10, 10, [255, 255, 255, 0]), 3)
+    end
+
+    test "rejects invalid counts" do
+      assert {:error, :invalid_color_count} = Native.dominant_colors(png(10, 10, [0, 0, 0]), 0)
+      assert {:error, :invalid_color_count} = Native.dominant_colors(png(10, 10, [0, 0, 0]), 17)
+    end
+  end
+
+  describe "phash/1 and hamming_distance/2" do
+    defp icon do
+      "priv/static/images/icon-512.png" |> Image.open!() |> Image.flatten!()
+    end
+
+    test "hashes re-encoded copies to nearby values" do
+      assert {:ok, original} = Native.phash(Image.write!(icon(), :memory, suffix: ".png"))
+      assert is_integer(original) and original in 0..0xFFFFFFFFFFFFFFFF
+
+      {:ok, smaller} = Image.resize(icon(), 0.5)
+
+      assert {:ok, copy} =
+               Native.phash(Image.write!(smaller, :memory, suffix: ".jpg", quality: 60))
+
+      assert Native.hamming_distance(original, copy) <= 6
+
+      {:ok, flipped} = Image.flip(icon(), :vertical)
+      assert {:ok, other} = Native.phash(Image.write!(flipped, :memory, suffix: ".png"))
+      assert Native.hamming_distance(original, other) > 16
+    end
+
+    test "hamming_distance/2 counts differing bits" do
+      assert Native.hamming_distance(0, 0) == 0
+      assert Native.hamming_distance(0b1011, 0b0001) == 2
+      assert Native.hamming_distance(0, 0xFFFFFFFFFFFFFFFF) == 64
+    end
+
+    test "rejects non-image input" do
+      assert {:error, :unsupported_format} = Native.phash("nope")
+    end
+  end
+
+  describe "phash_batch/1" do
+    test "matches phash/1 for each image, in order" do
+      png = Image.write!(icon(), :memory, suffix: ".png")
+      {:ok, flipped} = Image.flip(icon(), :vertical)
+      flipped = Image.write!(flipped, :memory, suffix: ".png")
+      images = [png, "nope", flipped, png]
+
+      assert Native.phash_batch(images) == Enum.map(images, &Native.phash/1)
+    end
+
+    test "returns an empty list for no images" do
+      assert Native.phash_batch([]) == []
+    end
+  end
+
+  describe "rasterize_svg/3" do
+    @svg ~s(<svg xmlns="http://www.w3.org/2000/svg" width="10" height="20">) <>
+           ~s(<rect width="10" height="20" fill="#ff0000"/></svg>)
+
+    test "renders to a PNG fitted within the requested size" do
+      assert {:ok, %{data: <<0x89, "PNG", _::binary>> = png, width: 50, height: 100}} =
+               Native.rasterize_svg(@svg, 100, 100)
+
+      assert {:ok, [255, 0, 0, 255]} = Image.get_pixel(Image.open!(png), 25, 50)
+    end
+
+    test "does not load external images" do
+      svg =
+        ~s(<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">) <>
+          ~s(<image href="priv/static/images/icon-192.png" width="10" height="10"/></svg>)
+
+      assert {:ok, %{data: png}} = Native.rasterize_svg(svg, 10, 10)
+      assert {:ok, [_, _, _, 0]} = Image.get_pixel(Image.open!(png), 5, 5)
+    end
+
+    test "rejects exponential <use> expansion" do
+      # Each group uses the previous one ten times: 10^5 rectangles.
+      levels =
+        for level <- 1..5, into: "" do
+          uses = String.duplicate(~s(<use href="#a#{level - 1}"/>), 10)
+          ~s(<g id="a#{level}">#{uses}</g>)
+        end
+
+      svg =
+        ~s(<svg xmlns="http://www.w3.org/2000/svg"><defs><rect id="a0" width="1" height="1"/>) <>
+          levels <> ~s(</defs><use href="#a5"/></svg>)
+
+      assert {:error, :svg_too_complex} = Native.rasterize_svg(svg, 100, 100)
+    end
+
+    test "rejects DTDs and non-SVG input" do
+      dtd = ~s(<!DOCTYPE svg [<!ENTITY a "b">]><svg xmlns="http://www.w3.org/2000/svg"/>)
+      assert {:error, :invalid_svg} = Native.rasterize_svg(dtd, 10, 10)
+      assert {:error, :invalid_svg} = Native.rasterize_svg("not svg", 10, 10)
+    end
+
+    test "enforces size limits" do
+      assert {:error, :invalid_dimensions} = Native.rasterize_svg(@svg, 0, 10)
+      assert {:error, :invalid_dimensions} = Native.rasterize_svg(@svg, 4096, 10)
+
+      huge = @svg <> String.duplicate(" ", 1024 * 1024)
+      assert {:error, :input_too_large} = Native.rasterize_svg(huge, 10, 10)
+    end
+  end
+
+  describe "probe_audio/1" do
+    defp id3_frame(id, body), do: <<id::binary, byte_size(body)::32, 0::16, body::binary>>
+
+    # One second of 8 kHz mono PCM with an ID3v2.3 tag in an `id3 ` chunk.
+    defp tagged_wav do
+      cover = png(4, 4, [0, 0, 0])
+
+      frames =
+        id3_frame("TIT2", <<0, "Song">>) <>
+          id3_frame("TPE1", <<0, "Band">>) <>
+          id3_frame("APIC", <<0, "image/png", 0, 3, 0, cover::binary>>)
+
+      size = byte_size(frames)
+
+      id3 =
+        <<"ID3", 3, 0, 0, 0::1, size >>> 21::7, 0::1, size >>> 14::7, 0::1, size >>> 7::7, 0::1,
+          size::7, frames::binary>>
+
+      padding = if rem(byte_size(id3), 2) == 1, do: <<0>>, else: <<>>
+
+      riff =
+        <<"WAVE", "fmt ", 16::little-32, 1::little-16, 1::little-16, 8000::little-32,
+          8000::little-32, 1::little-16, 8::little-16, "data", 8000::little-32,
+          :binary.copy(<<128>>, 8000)::binary, "id3 ", byte_size(id3)::little-32, id3::binary,
+          padding::binary>>
+
+      {<<"RIFF", byte_size(riff)::little-32, riff::binary>>, cover}
+    end
+
+    test "reads stream properties, tags, and cover art" do
+      {wav, cover} = tagged_wav()
+
+      assert {:ok,
+              %{
+                format: :wav,
+                duration_ms: 1000,
+                sample_rate: 8000,
+                channels: 1,
+                title: "Song",
+                artist: "Band",
+                album: nil,
+                cover: %{data: ^cover, mime_type: "image/png"}
+              }} = Native.probe_audio(wav)
+    end
+
+    test "returns nil cover and tags for untagged audio" do
+      {wav, _cover} = tagged_wav()
+      <<"RIFF", _::little-32, riff::binary-size(8036), _::binary>> = wav
+      untagged = <<"RIFF", byte_size(riff)::little-32, riff::binary>>
+
+      assert {:ok, %{format: :wav, title: nil, artist: nil, cover: nil}} =
+               Native.probe_audio(untagged)
+    end
+
+    test "rejects non-audio input" do
+      assert {:error, :unsupported_format} = Native.probe_audio("hello world, not audio")
+    end
+  end
+
+  describe "blur_image/3" do
+    test "downscales and blurs into a JPEG" do
+      {:ok, white} = Image.new(200, 400, color: [255, 255, 255])
+      {:ok, black} = Image.new(200, 400, color: [0, 0, 0])
+      {:ok, image} = Vix.Vips.Operation.arrayjoin([white, black], across: 2)
+
+      assert {:ok, %{data: <<0xFF, 0xD8, _::binary>> = jpeg, width: 64, height: 64}} =
+               Native.blur_image(Image.write!(image, :memory, suffix: ".png"), 8.0, 64)
+
+      # The hard edge in the middle is gone.
+      assert {:ok, [edge | _]} = Image.get_pixel(Image.open!(jpeg), 31, 32)
+      assert edge in 60..200
+    end
+
+    test "does not upscale" do
+      assert {:ok, %{width: 30, height: 20}} =
+               Native.blur_image(png(30, 20, [10, 20, 30]), 4.0, 64)
+    end
+
+    test "rejects invalid arguments" do
+      image = png(10, 10, [0, 0, 0])
+      assert {:error, :invalid_sigma} = Native.blur_image(image, 0.0, 64)
+      assert {:error, :invalid_sigma} = Native.blur_image(image, 101.0, 64)
+      assert {:error, :invalid_dimensions} = Native.blur_image(image, 4.0, 0)
+    end
+  end
+
+  describe "animation_info/1" do
+    test "counts frames and decoded bytes" do
+      for suffix <- [".gif", ".webp"] do
+        assert {:ok, %{frames: 2, decoded_bytes: 512}} =
+                 Native.animation_info(red_then_blue(suffix))
+      end
+    end
+
+    test "reads the GIF loop count" do
+      assert {:ok, %{frames: 2, loop_count: 1}} = Native.animation_info(@animated_gif)
+
+      # NETSCAPE2.0 extension with 5 repeats after the first play.
+      <<header::binary-size(19), rest::binary>> = @animated_gif
+      looping = header <> <<0x21, 0xFF, 11, "NETSCAPE2.0", 3, 1, 5::little-16, 0>> <> rest
+
+      assert {:ok, %{loop_count: 6}} = Native.animation_info(looping)
+    end
+
+    test "reports still images as a single frame" do
+      assert {:ok, %{frames: 1, loop_count: 1, decoded_bytes: 1200}} =
+               Native.animation_info(png(20, 15, [0, 0, 0]))
+    end
+  end
+
+  describe "qr_svg/2" do
+    @otpauth "otpauth://totp/Baudrate:alice?secret=JBSWY3DPEHPK3PXP&issuer=Baudrate"
+
+    test "renders a plain SVG document" do
+      assert {:ok, svg} = Native.qr_svg(@otpauth)
+
+      assert svg =~ ~r/\A<svg xmlns="http:\/\/www.w3.org\/2000\/svg" width="264" height="264"/
+      assert svg =~ ~s(<rect width="45" height="45" fill="#ffffff"/>)
+      assert svg =~ ~r/<path d="(M\d+ \d+h\d+v1h-\d+z)+" fill="#000000"\/><\/svg>\z/
+      refute svg =~ ~r/style|script|href|<text/
+    end
+
+    test "is rasterizable" do
+      {:ok, svg} = Native.qr_svg(@otpauth)
+
+      if Baudrate.Native.feature?(Native, "svg") do
+        assert {:ok, %{width: 90, height: 90}} = Native.rasterize_svg(svg, 90, 90)
+      end
+    end
+
+    test "applies options" do
+      assert {:ok, svg} =
+               Native.qr_svg("hi", %{size: 100, margin: 0, color: "#123", background: nil})
+
+      assert svg =~ ~s(width="100")
+      assert svg =~ ~s(viewBox="0 0 21 21")
+      assert svg =~ ~s(fill="#123")
+      refute svg =~ "<rect"
+
+      {:ok, low} = Native.qr_svg(@otpauth, %{ec_level: :low})
+      {:ok, high} = Native.qr_svg(@otpauth, %{ec_level: :high})
+      assert byte_size(high) > byte_size(low)
+    end
+
+    test "rejects invalid options" do
+      for options <- [
+            %{size: 0},
+            %{margin: 17},
+            %{color: "red"},
+            %{color: ~s(#000" onload="x)},
+            %{background: "#12345"}
+          ] do
+        assert {:error, :invalid_options} == Native.qr_svg("x", options)
+      end
+
+      assert_raise ArgumentError, fn -> Native.qr_svg("x", %{ec_level: :extreme}) end
+    end
+
+    test "rejects oversized data" do
+      assert {:error, :data_too_long} == Native.qr_svg(:binary.copy("x", 3_000))
+    end
+  end
+
+  describe "pathological input" do
+    test "returns results instead of panicking" do
+      png = png(64, 64, [1, 2, 3])
+
+      inputs = [
+        binary_part(png, 0, div(byte_size(png), 2)),
+        binary_part(@animated_gif, 0, 40),
+        huge_png_header(),
+        "GIF89a" <> :binary.copy(<<0xFF>>, 64),
+        "RIFF" <> <<0xFFFFFFFF::little-32>> <> "WEBPVP8X" <> :binary.copy(<<0>>, 16),
+        <<0xFF, 0xD8, 0xFF>>,
+        ""
+      ]
+
+      for bytes <- inputs do
+        for result <- [
+              Native.probe_image(bytes),
+              Native.animation_info(bytes),
+              Native.resize_image(bytes, 16, 16, :png),
+              Native.strip_metadata(bytes),
+              Native.phash(bytes),
+              Native.dominant_colors(bytes, 4),
+              Native.blurhash_encode(bytes, 4, 3),
+              Native.rasterize_svg(bytes, 16, 16),
+              Native.probe_audio(bytes)
+            ] do
+          case result do
+            {:ok, _} -> :ok
+            {:error, reason} -> assert is_atom(reason)
+          end
+        end
+      end
+    end
+  end
+
+  describe "native_info/0" do
+    test "lists the optional features of the default build" do
+      assert %{features: features, nifs: nifs} = Native.native_info()
+
+      assert Enum.sort(features) == ["audio", "avif", "svg"]
+      assert {:probe_audio, 1} in nifs
+      assert {:rasterize_svg, 3} in nifs
+    end
+  end
+end
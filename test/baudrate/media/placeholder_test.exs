@@ -0,0 +1,30 @@
+defmodule Baudrate.Media.PlaceholderTest do
+  use ExUnit.Case, async: false
+
+  alias Baudrate.Media.Placeholder
+
+  setup do
+    {:ok, image} = Image.new(40, 30, color: [10, 200, 120])
+    previous = Application.get_env(:baudrate, Baudrate.Media)
+    on_exit(fn -> restore_env(previous) end)
+    %{bytes: Image.write!(image, :memory, suffix: ".png")}
+  end
+
+  defp restore_env(nil), do: Application.delete_env(:baudrate, Baudrate.Media)
+  defp restore_env(value), do: Application.put_env(:baudrate, Baudrate.Media, value)
+
+  test "defaults to blurhash", %{bytes: bytes} do
+    Application.delete_env(:baudrate, Baudrate.Media)
+
+    assert Placeholder.format() == :blurhash
+    assert {:ok, {:blurhash, hash}} = Placeholder.encode(bytes)
+    assert {:ok, <<0x89, "PNG", _::binary>>} = Placeholder.render(:blurhash, hash, 16, 12)
+  end
+
+  test "uses the configured format", %{bytes: bytes} do
+    Application.put_env(:baudrate, Baudrate.Media, placeholder_format: :thumbhash)
+
+    assert {:ok, {:thumbhash, hash}} = Placeholder.encode(bytes)
+    assert {:ok, <<0x89, "PNG", _::binary>>} = Placeholder.render(:thumbhash, hash, 16, 12)
+  end
+end
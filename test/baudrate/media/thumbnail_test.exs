@@ -0,0 +1,55 @@
+defmodule Baudrate.Media.ThumbnailTest do
+  use ExUnit.Case, async: false
+
+  alias Baudrate.Media.Thumbnail
+
+  # A 1 × 1 GIF with two frames.
+  @animated_gif Base.decode64!(
+                  "R0lGODlhAQABAIAAAAAAAP///yH5BAAKAAAALAAAAAABAAEAAAICRAEAIfkEAAoAAAAsAAAAAAEAAQAAAgJEAQA7"
+                )
+
+  setup do
+    previous = Application.get_env(:baudrate, Baudrate.Media)
+    on_exit(fn -> restore_env(previous) end)
+  end
+
+  defp restore_env(nil), do: Application.delete_env(:baudrate, Baudrate.Media)
+  defp restore_env(value), do: Application.put_env(:baudrate, Baudrate.Media, value)
+
+  test "has default limits" do
+    Application.delete_env(:baudrate, Baudrate.Media)
+
+    assert Thumbnail.limits() == %{max_frames: 1_000, max_decoded_bytes: 512 * 1024 * 1024}
+    assert {:ok, %{width: 1, height: 1}} = Thumbnail.resize(@animated_gif, 10, 10, :png)
+  end
+
+  test "enforces the configured limits" do
+    Application.put_env(:baudrate, Baudrate.Media, max_animation_frames: 1)
+    assert {:error, :too_many_frames} = Thumbnail.resize(@animated_gif, 10, 10, :png)
+
+    Application.put_env(:baudrate, Baudrate.Media, max_animation_frames: nil)
+    assert {:ok, _} = Thumbnail.resize(@animated_gif, 10, 10, :png)
+  end
+
+  test "square_webp/2 enforces the configured limits" do
+    Application.put_env(:baudrate, Baudrate.Media, max_animation_frames: 1)
+    assert {:error, :too_many_frames} = Thumbnail.square_webp(@animated_gif, 10)
+  end
+
+  defp png(width, height) do
+    {:ok, image} = Image.new(width, height, color: [10, 200, 120])
+    Image.write!(image, :memory, suffix: ".png")
+  end
+
+  test "resize_webp/3 downscales to lossy WebP" do
+    assert {:ok, %{data: <<"RIFF", _::binary-4, "WEBP", _::binary>>, width: 100, height: 50}} =
+             Thumbnail.resize_webp(png(400, 200), 100, 100)
+  end
+
+  test "square_webp/2 crops the center square" do
+    assert {:ok, %{data: <<"RIFF", _::binary-4, "WEBP", _::binary>>, width: 48, height: 48}} =
+             Thumbnail.square_webp(png(300, 200), 48)
+
+    assert {:ok, %{width: 20, height: 20}} = Thumbnail.square_webp(png(20, 30), 48)
+  end
+end
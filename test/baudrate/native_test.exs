@@ -0,0 +1,104 @@
+defmodule Baudrate.NativeTest do
+  use ExUnit.Case, async: true
+
+  import ExUnit.CaptureLog
+
+  alias Baudrate.Native
+
+  describe "info/1" do
+    test "reports the version, features, and NIFs of every library" do
+      for module <- Native.modules() do
+        assert {:ok, info} = Native.info(module)
+        assert info.version =~ ~r/^\d+\.\d+\.\d+/
+        assert is_list(info.features)
+        assert {:native_info, 0} in info.nifs
+      end
+    end
+
+    test "lists every NIF stub defined by the module" do
+      {:ok, %{nifs: nifs}} = Native.info(Baudrate.Sanitizer.Native)
+
+      assert {:sanitize_federation, 1} in nifs
+      assert {:sanitize_federation, 2} in nifs
+      assert {:describe_policy, 1} in nifs
+      assert nifs == Enum.sort(nifs)
+    end
+
+    test "every reported NIF is exported by its module" do
+      for module <- Native.modules(), {:ok, %{nifs: nifs}} <- [Native.info(module)] do
+        for {name, arity} <- nifs do
+          assert function_exported?(module, name, arity), "#{inspect(module)}.#{name}/#{arity}"
+        end
+      end
+    end
+  end
+
+  test "info/0 covers every module" do
+    assert Map.keys(Native.info()) |> Enum.sort() == Enum.sort(Native.modules())
+  end
+
+  describe "available?/3" do
+    test "is true for exported NIFs" do
+      assert Native.available?(Baudrate.Text.Native, :casefold, 1)
+    end
+
+    test "is false for unknown NIFs" do
+      refute Native.available?(Baudrate.Text.Native, :no_such_nif, 1)
+      refute Native.available?(Baudrate.Text.Native, :casefold, 3)
+    end
+  end
+
+  describe "feature?/2" do
+    test "is true for features the library was built with" do
+      assert Native.feature?(Baudrate.Media.Native, "svg")
+    end
+
+    test "is false for unknown features" do
+      refute Native.feature?(Baudrate.Media.Native, "no_such_feature")
+      refute Native.feature?(Baudrate.Text.Native, "svg")
+    end
+  end
+
+  describe "precompiled/1" do
+    test "builds from source unless precompiled NIFs are requested" do
+      opts = Native.precompiled(crate: "baudrate_text")
+      source? = System.get_env("BAUDRATE_PRECOMPILED_NIFS") not in ["1", "true"]
+
+      assert opts[:crate] == "baudrate_text"
+      assert opts[:otp_app] == :baudrate
+      assert opts[:version] == Mix.Project.config()[:version]
+      assert opts[:base_url] =~ "/releases/download/v#{opts[:version]}"
+      assert opts[:force_build] == source?
+    end
+
+    test "lets the module override the defaults" do
+      assert Native.precompiled(crate: "baudrate_text", force_build: true)[:force_build]
+    end
+  end
+
+  describe "log_report/0" do
+    test "does not warn when every library reports all of its features" do
+      log = capture_log([level: :warning], fn -> assert Native.log_report() == :ok end)
+
+      refute log =~ "built without"
+      refute log =~ "cannot report"
+    end
+  end
+
+  describe "log_panic/2" do
+    test "logs the call and the panic message" do
+      log = capture_log(fn -> assert Native.log_panic("strip_tags/1", "boom") == :ok end)
+
+      assert log =~ "strip_tags/1 panicked: boom"
+    end
+  end
+
+  describe "PanicError" do
+    test "names the call that panicked" do
+      error = Native.PanicError.exception(call: "build_atom/2", message: "boom")
+
+      assert error.call == "build_atom/2"
+      assert Exception.message(error) == "build_atom/2 panicked: boom"
+    end
+  end
+end
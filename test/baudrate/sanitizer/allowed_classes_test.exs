@@ -0,0 +1,113 @@
+defmodule Baudrate.Sanitizer.AllowedClassesTest do
+  # The class allowlist and the code class patterns are global to the NIF.
+  use ExUnit.Case, async: false
+
+  alias Baudrate.Sanitizer.Native
+
+  setup do
+    on_exit(fn ->
+      Native.set_allowed_classes(:federation, %{})
+      Native.set_allowed_classes(:markdown, %{})
+      Native.set_code_classes(:federation, nil)
+      Native.set_code_classes(:markdown, nil)
+    end)
+  end
+
+  test "keeps allowed classes on federated content" do
+    html = ~s(<p class="quote-inline other">RE: <a href="https://a.example/1">post</a></p>)
+
+    assert Native.sanitize_federation(html) =~ "<p>RE:"
+
+    assert :ok = Native.set_allowed_classes(:federation, %{p: ["quote-inline"]})
+    assert Native.sanitize_federation(html) =~ ~s(<p class="quote-inline">RE:)
+  end
+
+  test "adds to the built-in classes" do
+    assert :ok = Native.set_allowed_classes(:federation, %{"a" => ["status-link"]})
+
+    assert Native.sanitize_federation(
+             ~s(<a href="https://a.example/" class="mention status-link x">a</a>)
+           ) =~ ~s(class="mention status-link")
+  end
+
+  test "applies to one policy only" do
+    :ok = Native.set_allowed_classes(:markdown, %{blockquote: ["note"]})
+    html = ~s(<blockquote class="note">b</blockquote>)
+
+    assert Native.sanitize_markdown(html) == html
+    assert Native.sanitize_federation(html) == "<blockquote>b</blockquote>"
+  end
+
+  test "replaces the previous allowlist" do
+    :ok = Native.set_allowed_classes(:federation, %{p: ["quote-inline"]})
+    :ok = Native.set_allowed_classes(:federation, %{})
+
+    assert Native.sanitize_federation(~s(<p class="quote-inline">q</p>)) == "<p>q</p>"
+  end
+
+  test "is reflected by describe_policy/1" do
+    :ok = Native.set_allowed_classes(:federation, %{p: ["quote-inline"], span: ["x"]})
+    policy = Native.describe_policy(:federation)
+
+    assert policy.attributes["p"] == ["class"]
+    assert policy.classes["p"] == ["quote-inline"]
+    assert "mention" in policy.classes["span"]
+    assert "x" in policy.classes["span"]
+  end
+
+  test "rejects tags the policy does not keep" do
+    assert Native.set_allowed_classes(:federation, %{img: ["emoji"]}) ==
+             {:error, {:tag_not_allowed, "img"}}
+  end
+
+  test "rejects invalid class names and keeps the allowlist" do
+    :ok = Native.set_allowed_classes(:federation, %{p: ["quote-inline"]})
+
+    assert Native.set_allowed_classes(:federation, %{p: ["a b"]}) ==
+             {:error, {:invalid_class, "a b"}}
+
+    assert Native.set_allowed_classes(:federation, %{p: [""]}) == {:error, {:invalid_class, ""}}
+    assert Native.describe_policy(:federation).classes["p"] == ["quote-inline"]
+  end
+
+  describe "set_code_classes/2" do
+    test "finds the language with the configured patterns" do
+      :ok = Native.set_code_classes(:markdown, ["language-*", "sourceCode *", "lang-*"])
+
+      for class <- ["sourceCode rust", "lang-rust", "hljs language-rust"] do
+        assert Native.sanitize_markdown(~s(<code class="#{class}">x</code>)) ==
+                 ~s(<code class="language-rust">x</code>)
+      end
+    end
+
+    test "keeps language classes on federated content once configured" do
+      html = ~s(<pre><code class="language-elixir">x</code></pre>)
+
+      assert Native.sanitize_federation(html) == "<pre><code>x</code></pre>"
+
+      :ok = Native.set_code_classes(:federation, ["language-*"])
+      assert Native.sanitize_federation(html) == html
+    end
+
+    test "replaces the default patterns and restores them with nil" do
+      html = ~s(<code class="language-rust">x</code>)
+
+      :ok = Native.set_code_classes(:markdown, ["lang-*"])
+      assert Native.sanitize_markdown(html) == "<code>x</code>"
+      assert Native.describe_policy(:markdown).classes["code"] == ["lang-*"]
+
+      :ok = Native.set_code_classes(:markdown, nil)
+      assert Native.sanitize_markdown(html) == html
+      assert Native.describe_policy(:markdown).classes["code"] == ["language-*"]
+    end
+
+    test "rejects patterns without exactly one wildcard class" do
+      for pattern <- ["hljs", "a* b*", "", "a*b *"] do
+        assert Native.set_code_classes(:markdown, ["language-*", pattern]) ==
+                 {:error, {:invalid_pattern, pattern}}
+      end
+
+      assert Native.describe_policy(:markdown).classes["code"] == ["language-*"]
+    end
+  end
+end
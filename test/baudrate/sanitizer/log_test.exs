@@ -0,0 +1,118 @@
+defmodule Baudrate.Sanitizer.LogTest do
+  # The NIF has a single, global log target.
+  use ExUnit.Case, async: false
+
+  import ExUnit.CaptureLog
+
+  alias Baudrate.Sanitizer.{Log, Native}
+
+  setup do
+    Native.set_log_target(self())
+    on_exit(fn -> Native.set_log_target(nil) end)
+  end
+
+  test "the NIF is loaded with the configured level" do
+    assert Native.log_level() == Log.configured_level()
+    assert Native.log_level() == :debug
+  end
+
+  test "reports rejected URLs" do
+    Native.sanitize_federation(~s[<a href="javascript:reject_me()">x</a>])
+
+    assert_receive {:baudrate_sanitizer_log,
+                    %{
+                      level: :warning,
+                      event: :url_rejected,
+                      policy: :federation,
+                      tag: "a",
+                      attribute: "href",
+                      value: "javascript:reject_me()"
+                    }}
+  end
+
+  test "reports relative URLs only where the policy denies them" do
+    Native.sanitize_federation(~s(<a href="/relative-federation">x</a>))
+    assert_receive {:baudrate_sanitizer_log, %{value: "/relative-federation"}}
+
+    Native.sanitize_markdown(~s(<a href="/relative-markdown">x</a>))
+    refute_receive {:baudrate_sanitizer_log, %{value: "/relative-markdown"}}, 50
+  end
+
+  test "reports what batches remove from each document" do
+    htmls = for i <- 1..20, do: ~s[<a href="javascript:batch_#{i}()">x</a>]
+    Native.sanitize_federation_batch(htmls)
+
+    for i <- 1..20 do
+      value = "javascript:batch_#{i}()"
+      assert_receive {:baudrate_sanitizer_log, %{event: :url_rejected, value: ^value}}
+    end
+  end
+
+  test "reports what streams remove" do
+    {:ok, stream} = Native.stream_new(:federation)
+    :ok = Native.stream_push(stream, ~s[<a href="javascript:str])
+    :ok = Native.stream_push(stream, ~s[eam()">x</a>])
+    {:ok, _html} = Native.stream_finish(stream)
+
+    assert_receive {:baudrate_sanitizer_log,
+                    %{event: :url_rejected, policy: :federation, value: "javascript:stream()"}}
+  end
+
+  test "reports elements removed with their content" do
+    Native.sanitize_markdown("<p>ok</p><script>alert(1)</script>")
+
+    assert_receive {:baudrate_sanitizer_log,
+                    %{level: :warning, event: :element_removed, tag: "script", policy: :markdown}}
+  end
+
+  test "reports unwrapped elements and removed attributes at debug level" do
+    Native.normalize_feed_html(~s[<div><p onclick="x()">text</p></div>])
+
+    assert_receive {:baudrate_sanitizer_log,
+                    %{level: :debug, event: :element_unwrapped, tag: "div"}}
+
+    assert_receive {:baudrate_sanitizer_log,
+                    %{level: :debug, event: :attribute_removed, tag: "p", attribute: "onclick"}}
+  end
+
+  test "reports invalid UTF-8" do
+    Native.strip_tags(<<"caf", 0xE9>>)
+
+    assert_receive {:baudrate_sanitizer_log,
+                    %{level: :info, event: :invalid_utf8, policy: nil}}
+  end
+
+  test "truncates long values" do
+    Native.sanitize_federation(~s[<a href="javascript:#{String.duplicate("a", 1_000)}">x</a>])
+
+    assert_receive {:baudrate_sanitizer_log, %{event: :url_rejected, value: value}}
+    assert byte_size(value) == 256
+  end
+
+  test "reports nothing for allowed content" do
+    Native.sanitize_federation(~s(<p>fine <a href="https://example.com/">link</a></p>))
+    refute_receive {:baudrate_sanitizer_log, _}, 50
+  end
+
+  test "buffers events while no target is set" do
+    Native.set_log_target(nil)
+    Log.drain()
+
+    Native.sanitize_federation(~s(<a href="data:buffered">x</a>))
+
+    assert Enum.any?(Log.drain(), &(&1.value == "data:buffered"))
+  end
+
+  test "forwards events to Logger when started with the :logger target" do
+    pid = start_supervised!({Log, target: :logger})
+
+    log =
+      capture_log(fn ->
+        Native.sanitize_federation(~s[<a href="javascript:logged()">x</a>])
+        :sys.get_state(pid)
+      end)
+
+    assert log =~
+             ~s[sanitizer: url_rejected href="javascript:logged()" on <a> (federation policy)]
+  end
+end
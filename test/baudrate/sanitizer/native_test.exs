@@ -50,14 +50,50 @@ defmodule Baudrate.Sanitizer.NativeTest do
       assert result =~ "http://example.com"
     end
 
-    test "preserves span with safe classes: h-card, hashtag, mention, invisible" do
-      for class <- ~w[h-card hashtag mention invisible] do
+    test "preserves span with safe classes: h-card, hashtag, mention" do
+      for class <- ~w[h-card hashtag mention] do
         html = ~s[<span class="#{class}">text</span>]
         result = Native.sanitize_federation(html)
         assert result =~ ~s[class="#{class}"], "Expected #{class} to be preserved"
       end
     end
 
+    test "preserves Mastodon link shortening spans inside anchors" do
+      html =
+        ~s[<a href="https://example.com/a/long/path">] <>
+          ~s[<span class="invisible">https://</span>] <>
+          ~s[<span class="ellipsis">example.com/a/long</span>] <>
+          ~s[<span class="invisible">/path</span></a>]
+
+      result = Native.sanitize_federation(html)
+      assert result =~ ~s[<span class="invisible">https://</span>]
+      assert result =~ ~s[<span class="ellipsis">example.com/a/long</span>]
+      assert result =~ ~s[<span class="invisible">/path</span>]
+    end
+
+    test "keeps invisible and ellipsis in spans nested deeper inside anchors" do
+      html =
+        ~s[<a href="https://x.example/"><strong><span class="invisible">x</span></strong></a>]
+
+      assert Native.sanitize_federation(html) =~ ~s[<span class="invisible">x</span>]
+    end
+
+    test "strips invisible and ellipsis from spans outside anchors" do
+      html =
+        ~s[<p>A <span class="invisible">hidden</span> <span class="ellipsis mention">x</span></p>]
+
+      assert Native.sanitize_federation(html) ==
+               ~s[<p>A <span>hidden</span> <span class="mention">x</span></p>]
+    end
+
+    test "strips link span classes outside anchors when streaming" do
+      {:ok, stream} = Native.stream_new(:federation)
+      :ok = Native.stream_push(stream, ~s[<p><span class="invis])
+      :ok = Native.stream_push(stream, ~s[ible">hidden</span></p>])
+
+      assert {:ok, ~s[<p><span>hidden</span></p>]} == Native.stream_finish(stream)
+    end
+
     test "strips unsafe class from span but keeps tag" do
       html = ~s[<span class="malicious">text</span>]
       result = Native.sanitize_federation(html)
@@ -203,6 +239,220 @@ defmodule Baudrate.Sanitizer.NativeTest do
     test "handles plain text" do
       assert "Hello World" == Native.sanitize_federation("Hello World")
     end
+
+    test "rewrites presentational tags to their semantic equivalents" do
+      html = ~s(<p><b>bold</b> <I>it</I> <strike>old</strike> <s>x</s> <tt>mono</tt></p>)
+
+      assert Native.sanitize_federation(html) ==
+               "<p><strong>bold</strong> <em>it</em> <del>old</del> <del>x</del> " <>
+                 "<code>mono</code></p>"
+    end
+
+    test "keeps time[datetime] only for valid RFC 3339 values" do
+      for datetime <- ~w[2024-05-01T12:00:00Z 2024-05-01 2024-02-29 2024-05 12:30:05.5+09:00] do
+        html = ~s(<time datetime="#{datetime}">then</time>)
+        assert Native.sanitize_federation(html) == html
+        assert Native.sanitize_markdown(html) == html
+      end
+
+      for datetime <- ~w[tomorrow 2023-02-29 2024-13-01 2024-05-01T24:00Z javascript:x()] do
+        assert Native.sanitize_federation(~s(<time datetime="#{datetime}">then</time>)) ==
+                 "<time>then</time>"
+      end
+    end
+
+    test "keeps <center> as a paragraph, or unwraps it around blocks" do
+      assert Native.sanitize_federation("a<center>b</center>c") == "a<p>b</p>c"
+
+      assert Native.sanitize_federation("<center><p>one</p><b>two</b></center>") ==
+               "<p>one</p><strong>two</strong>"
+
+      assert Native.sanitize_federation(~s(<font color="red">red</font>)) == "red"
+    end
+  end
+
+  describe "sanitize_async/3" do
+    test "sends the sanitized HTML tagged with the reference" do
+      ref = make_ref()
+
+      assert Native.sanitize_async("<p>hi<script>alert(1)</script></p>", self(), ref) == :ok
+      assert_receive {^ref, {:ok, "<p>hi</p>"}}
+    end
+
+    test "replies to every queued document" do
+      refs =
+        for i <- 1..200 do
+          ref = make_ref()
+          :ok = Native.sanitize_async("<p>#{i}<div>x</div></p>", self(), ref)
+          {ref, i}
+        end
+
+      for {ref, i} <- refs do
+        expected = Native.sanitize_federation("<p>#{i}<div>x</div></p>")
+        assert_receive {^ref, {:ok, ^expected}}, 5_000
+      end
+    end
+  end
+
+  describe "stream_new/1, stream_push/2, and stream_finish/1" do
+    defp stream(policy, chunks) do
+      {:ok, stream} = Native.stream_new(policy)
+      Enum.each(chunks, &(:ok = Native.stream_push(stream, &1)))
+      Native.stream_finish(stream)
+    end
+
+    defp chunks(binary, size) when byte_size(binary) <= size, do: [binary]
+
+    defp chunks(binary, size) do
+      <<chunk::binary-size(size), rest::binary>> = binary
+      [chunk | chunks(rest, size)]
+    end
+
+    test "matches the one-shot sanitizers for any chunk size" do
+      html =
+        String.duplicate(
+          ~s[<p>caf\u00E9 <a href="https://example.com/" class="mention evil">@日本</a>] <>
+            ~s[<script>alert(1)</script></p><span class="h-card">😀</span> &amp; &lt;],
+          40
+        )
+
+      for size <- [1, 3, 7, 1024] do
+        assert stream(:federation, chunks(html, size)) == {:ok, Native.sanitize_federation(html)}
+        assert stream(:markdown, chunks(html, size)) == {:ok, Native.sanitize_markdown(html)}
+      end
+    end
+
+    test "rewrites legacy markup like the one-shot sanitizers" do
+      html = ~s[<center><font color="red">big</font> <b>bold</b> <tt>x</tt></center>]
+
+      for size <- [1, 5, 1024] do
+        assert stream(:federation, chunks(html, size)) == {:ok, Native.sanitize_federation(html)}
+        assert stream(:markdown, chunks(html, size)) == {:ok, Native.sanitize_markdown(html)}
+      end
+    end
+
+    test "replaces invalid UTF-8" do
+      assert stream(:federation, ["<p>caf", <<0xE9>>, "</p>"]) == {:ok, "<p>caf\uFFFD</p>"}
+    end
+
+    test "handles an empty document" do
+      assert stream(:markdown, []) == {:ok, ""}
+    end
+
+    test "rejects use after finishing" do
+      {:ok, stream} = Native.stream_new(:federation)
+      assert {:ok, _} = Native.stream_finish(stream)
+
+      assert Native.stream_push(stream, "<p>late</p>") == {:error, :closed}
+      assert Native.stream_finish(stream) == {:error, :closed}
+    end
+
+    test "caps the streams running at once" do
+      streams =
+        for _ <- 1..64 do
+          {:ok, stream} = Native.stream_new(:markdown)
+          stream
+        end
+
+      assert Native.stream_new(:markdown) == {:error, :busy}
+
+      assert {:ok, ""} = Native.stream_finish(hd(streams))
+      assert {:ok, stream} = Native.stream_new(:markdown)
+
+      Enum.each([stream | tl(streams)], &Native.stream_finish/1)
+    end
+  end
+
+  describe "markup-free input" do
+    test "is escaped exactly as the parser would" do
+      for text <- ["a > b", "caf\u00E9 \u00A0 \"quoted\" 'single'", "  spaced\n\tout  ", ""] do
+        # A trailing empty element forces the full parse without changing the text.
+        parsed = Native.sanitize_federation(text <> "<u></u>") |> String.replace("<u></u>", "")
+
+        assert Native.sanitize_federation(text) == parsed
+        assert Native.sanitize_markdown(text) == parsed
+      end
+    end
+
+    test "escapes > and non-breaking spaces" do
+      assert Native.sanitize_federation("1 > 0\u00A0ok") == "1 &gt; 0&nbsp;ok"
+    end
+
+    test "strip_tags/1 still trims non-breaking spaces" do
+      assert Native.strip_tags("\u00A0\u00A0text\u00A0") == "text"
+    end
+  end
+
+  describe "sanitize_federation_batch/1" do
+    test "matches sanitize_federation/1 for each document, in order" do
+      htmls =
+        [
+          "<p>Hello <script>alert(1)</script></p>",
+          ~s(<a href="javascript:x" class="mention evil">@a</a>),
+          "plain",
+          <<"<p>caf", 0xE9, "</p>">>
+        ] ++ for(i <- 1..50, do: "<p><span class=\"hashtag\">##{i}</span></p>")
+
+      assert Native.sanitize_federation_batch(htmls) ==
+               Enum.map(htmls, &Native.sanitize_federation/1)
+    end
+
+    test "returns an empty list for no documents" do
+      assert Native.sanitize_federation_batch([]) == []
+    end
+  end
+
+  # --- sanitize_content_map/1 ---
+
+  describe "sanitize_content_map/1" do
+    test "sanitizes every language like sanitize_federation/1" do
+      map = %{
+        "en" => ~s(<p>Hi <a href="javascript:x">there</a></p>),
+        "fr" => "<p>Salut<script>x</script></p>"
+      }
+
+      {cleaned, _language} = Native.sanitize_content_map(map)
+
+      assert cleaned == Map.new(map, fn {k, v} -> {k, Native.sanitize_federation(v)} end)
+    end
+
+    test "canonicalizes language tags" do
+      map = %{"EN_us" => "a", "zh-hant-tw" => "b", "sr-latn" => "c", "es-419" => "d"}
+
+      assert {cleaned, _} = Native.sanitize_content_map(map)
+      assert Map.keys(cleaned) |> Enum.sort() == ~w[en-US es-419 sr-Latn zh-Hant-TW]
+    end
+
+    test "drops invalid tags, nil values, and empty variants" do
+      map = %{
+        "english" => "<p>a</p>",
+        "en-" => "<p>b</p>",
+        "i-klingon" => "<p>c</p>",
+        "de" => nil,
+        "fr" => "<script>x</script>",
+        "ja" => "<p>テスト</p>"
+      }
+
+      assert Native.sanitize_content_map(map) == {%{"ja" => "<p>テスト</p>"}, "ja"}
+    end
+
+    test "picks the language with the most text as primary" do
+      map = %{"de" => "<p>Ein langer Beitrag</p>", "en" => "<p>A post</p>"}
+
+      assert {_, "de"} = Native.sanitize_content_map(map)
+    end
+
+    test "prefers any language over und" do
+      map = %{"und" => "<p>Something rather long</p>", "en" => "<p>Short</p>"}
+
+      assert {_, "en"} = Native.sanitize_content_map(map)
+      assert {_, "und"} = Native.sanitize_content_map(%{"und" => "x"})
+    end
+
+    test "returns nil as the language of an empty map" do
+      assert Native.sanitize_content_map(%{}) == {%{}, nil}
+      assert Native.sanitize_content_map(%{"bad tag" => "x"}) == {%{}, nil}
+    end
   end
 
   # --- sanitize_markdown/1 ---
@@ -236,6 +486,12 @@ defmodule Baudrate.Sanitizer.NativeTest do
       assert result =~ ~s[class="language-elixir"]
     end
 
+    test "keeps the language class among other code classes" do
+      html = ~s[<code class="hljs language-rust">code</code>]
+
+      assert Native.sanitize_markdown(html) == ~s[<code class="language-rust">code</code>]
+    end
+
     test "strips code[class] not matching language pattern" do
       html = ~s[<code class="malicious-class">code</code>]
       result = Native.sanitize_markdown(html)
@@ -277,6 +533,32 @@ defmodule Baudrate.Sanitizer.NativeTest do
       refute result =~ "h-card"
     end
 
+    test "preserves custom-emoji class and title on img, strips other classes" do
+      html =
+        ~s[<img class="custom-emoji" src="https://example.com/e.png" alt=":blob:" title=":blob:"><img class="evil" src="https://example.com/x.png">]
+
+      result = Native.sanitize_markdown(html)
+      assert result =~ ~s[class="custom-emoji"]
+      assert result =~ ~s[title=":blob:"]
+      refute result =~ "evil"
+    end
+
+    test "keeps well-formed data attributes of code blocks" do
+      html = ~s[<pre data-filename="main.rs" data-highlight-lines="1,3-5"><code>x</code></pre>]
+      assert Native.sanitize_markdown(html) == html
+
+      html = ~s[<pre data-filename=" " data-highlight-lines="1;alert(1)" data-x="y">x</pre>]
+
+      assert Native.sanitize_markdown(html) == "<pre>x</pre>"
+    end
+
+    test "keeps only the diff line classes on spans" do
+      html = ~s[<code><span class="diff-ins">+a</span>\n<span class="mention">b</span></code>]
+
+      assert Native.sanitize_markdown(html) ==
+               ~s[<code><span class="diff-ins">+a</span>\n<span>b</span></code>]
+    end
+
     test "handles language class with special chars" do
       for class <- ~w[language-c++ language-c_sharp language-f-sharp] do
         html = ~s[<code class="#{class}">code</code>]
@@ -284,6 +566,28 @@ defmodule Baudrate.Sanitizer.NativeTest do
         assert result =~ class, "Expected #{class} to be preserved"
       end
     end
+
+    test "keeps fragment links and heading ids, prefixed" do
+      html = ~s[<p><a href="#section-2">Jump</a> <a href="#">Top</a></p><h2 id="section-2">Two</h2>]
+
+      assert Native.sanitize_markdown(html) ==
+               ~s[<p><a href="#user-content-section-2" rel="nofollow noopener">Jump</a> ] <>
+                 ~s[<a href="#" rel="nofollow noopener">Top</a></p>] <>
+                 ~s[<h2 id="user-content-section-2">Two</h2>]
+
+      sanitized = Native.sanitize_markdown(html)
+      assert Native.sanitize_markdown(sanitized) == sanitized
+    end
+
+    test "drops unsafe fragments and ids off headings" do
+      html = ~s[<a href="#a b">x</a><h3 id="a&quot;b">y</h3><p id="intro">z</p>]
+
+      assert Native.sanitize_markdown(html) ==
+               ~s[<a rel="nofollow noopener">x</a><h3>y</h3><p>z</p>]
+
+      assert Native.sanitize_federation(~s[<a href="#section-2">x</a><h2 id="a">y</h2>]) ==
+               ~s[<a rel="nofollow noopener noreferrer">x</a><h2>y</h2>]
+    end
   end
 
   # --- decode_html_entities/1 ---
@@ -309,6 +613,61 @@ defmodule Baudrate.Sanitizer.NativeTest do
     test "handles empty string" do
       assert "" == Native.decode_html_entities("")
     end
+
+    test "decodes every entity once" do
+      assert "© – &lt;" == Native.decode_html_entities("&copy; &ndash; &amp;lt;")
+    end
+  end
+
+  describe "html_to_text/1" do
+    test "strips tags and decodes the entities left" do
+      assert Native.html_to_text("<p>Tom &amp; <b>Jerry</b>&nbsp;&lt;3</p>") == "Tom & Jerry <3"
+    end
+  end
+
+  # --- decode_entities/1 and escape_html/1 ---
+
+  describe "decode_entities/1" do
+    test "decodes named references, longest name first" do
+      assert Native.decode_entities("&eacute;&notin; &notit; &ampx &copy 2024") ==
+               "é∉ ¬it; &x © 2024"
+    end
+
+    test "decodes references to two code points" do
+      assert Native.decode_entities("&NotNestedGreaterGreater;&fjlig;") == "⪢\u0338fj"
+    end
+
+    test "decodes numeric references as the tokenizer does" do
+      assert Native.decode_entities("&#x1F600;&#128512&#65;&#X42;") == "😀😀AB"
+      assert Native.decode_entities("&#150;&#x80;") == "–€"
+
+      assert Native.decode_entities("&#0;&#xD800;&#x110000;&#99999999999;") ==
+               "\uFFFD\uFFFD\uFFFD\uFFFD"
+    end
+
+    test "keeps text that is no reference" do
+      for text <- ["&bogus;", "a & b", "&#;", "&#x;", "&", "<p>a</p>", ""] do
+        assert Native.decode_entities(text) == text
+      end
+    end
+
+    test "keeps &nbsp; as U+00A0" do
+      assert Native.decode_entities("a&nbsp;b") == "a\u00A0b"
+    end
+  end
+
+  describe "escape_html/1" do
+    test "escapes markup and both quotes as Phoenix does" do
+      text = ~s(<a href="x">Tom & 'Jerry'</a>)
+
+      assert Native.escape_html(text) ==
+               Phoenix.HTML.html_escape(text) |> Phoenix.HTML.safe_to_string()
+    end
+
+    test "round-trips through decode_entities/1" do
+      text = ~s(&amp; <b>"it's"</b> ©)
+      assert text |> Native.escape_html() |> Native.decode_entities() == text
+    end
   end
 
   # --- strip_tags/1 ---
@@ -376,6 +735,11 @@ defmodule Baudrate.Sanitizer.NativeTest do
       assert result =~ "<em>"
     end
 
+    test "rewrites presentational tags" do
+      assert Native.normalize_feed_html("<center><b>News</b> in <tt>brief</tt></center>") ==
+               "<p><strong>News</strong> in <code>brief</code></p>"
+    end
+
     test "strips disallowed tags like sanitize_markdown" do
       html = "<div><p>content</p></div><script>evil()</script>"
       result = Native.normalize_feed_html(html)
@@ -445,4 +809,1129 @@ defmodule Baudrate.Sanitizer.NativeTest do
       assert result == "<p>a  b</p>"
     end
   end
+
+  # --- emojify/2 ---
+
+  describe "emojify/2" do
+    @emoji %{"blobcat" => "https://example.com/emoji/blobcat.png"}
+
+    test "replaces known shortcodes with custom emoji images" do
+      result = Native.emojify("<p>hi :blobcat:</p>", @emoji)
+
+      assert result ==
+               ~s[<p>hi <img class="custom-emoji" src="https://example.com/emoji/blobcat.png" alt=":blobcat:" title=":blobcat:"></p>]
+    end
+
+    test "leaves unknown shortcodes as text" do
+      assert Native.emojify("<p>:unknown:</p>", @emoji) == "<p>:unknown:</p>"
+    end
+
+    test "skips code and pre content" do
+      html = "<p><code>:blobcat:</code></p><pre>:blobcat:</pre>"
+      assert Native.emojify(html, @emoji) == html
+    end
+
+    test "does not match shortcodes glued to alphanumerics" do
+      assert Native.emojify("<p>12:blobcat:34</p>", @emoji) == "<p>12:blobcat:34</p>"
+    end
+
+    test "ignores non-HTTP(S) emoji URLs" do
+      result = Native.emojify("<p>:bad:</p>", %{"bad" => "javascript:alert(1)"})
+      refute result =~ "<img"
+    end
+
+    test "escapes emoji URLs in the generated attribute" do
+      result = Native.emojify("<p>:quote:</p>", %{"quote" => ~s[https://example.com/"onerror="x]})
+      assert result =~ "&quot;onerror=&quot;"
+      refute result =~ ~s["onerror="]
+    end
+
+    test "output survives sanitize_markdown/1 unchanged" do
+      result = Native.emojify("<p>hi :blobcat:</p>", @emoji)
+      assert Native.sanitize_markdown(result) == result
+    end
+  end
+
+  # --- extract_shortcodes/1 ---
+
+  describe "extract_shortcodes/1" do
+    test "returns distinct shortcodes in order of appearance" do
+      assert Native.extract_shortcodes("<p>:blobcat: :neko_happy: :blobcat:</p>") ==
+               ["blobcat", "neko_happy"]
+    end
+
+    test "ignores shortcodes inside code and pre elements" do
+      html = "<p>:used: <code>:inline:</code></p><pre>:block:</pre>"
+      assert Native.extract_shortcodes(html) == ["used"]
+    end
+
+    test "ignores shortcodes inside Markdown backtick code in raw text" do
+      text = "hi :used: `:inline:`\n```\n:fenced:\n```"
+      assert Native.extract_shortcodes(text) == ["used"]
+    end
+
+    test "ignores clock times and single-character codes" do
+      assert Native.extract_shortcodes("at 10:30:45 :a:") == []
+    end
+
+    test "returns an empty list for text without shortcodes" do
+      assert Native.extract_shortcodes("no emoji here") == []
+    end
+
+    test "walks deeply nested markup without exhausting the stack" do
+      html = String.duplicate("<span>", 100_000) <> ":deep:"
+      emoji = %{"deep" => "https://example.com/deep.png"}
+
+      assert Native.extract_shortcodes(html) == ["deep"]
+      assert Native.emojify(html, emoji) =~ ~s(alt=":deep:")
+    end
+  end
+
+  # --- invalid UTF-8 ---
+
+  describe "invalid UTF-8" do
+    @invalid <<"<p>caf", 0xE9, " au lait</p>">>
+
+    test "single-argument forms replace invalid sequences with U+FFFD" do
+      assert Native.sanitize_federation(@invalid) == "<p>caf\uFFFD au lait</p>"
+      assert Native.sanitize_markdown(@invalid) == "<p>caf\uFFFD au lait</p>"
+      assert Native.strip_tags(@invalid) == "caf\uFFFD au lait"
+      assert Native.normalize_feed_html(@invalid) == "<p>caf\uFFFD au lait</p>"
+    end
+
+    test ":lossy returns the replaced result in an ok tuple" do
+      assert Native.sanitize_federation(@invalid, :lossy) == {:ok, "<p>caf\uFFFD au lait</p>"}
+      assert Native.strip_tags(@invalid, :lossy) == {:ok, "caf\uFFFD au lait"}
+    end
+
+    test ":error rejects invalid input" do
+      for sanitize <- [
+            &Native.sanitize_federation/2,
+            &Native.sanitize_markdown/2,
+            &Native.strip_tags/2,
+            &Native.normalize_feed_html/2
+          ] do
+        assert sanitize.(@invalid, :error) == {:error, :invalid_utf8}
+      end
+    end
+
+    test ":error accepts valid input" do
+      assert Native.sanitize_federation("<p>café</p>", :error) == {:ok, "<p>café</p>"}
+      assert Native.normalize_feed_html("<p>café</p><p></p>", :error) == {:ok, "<p>café</p>"}
+    end
+
+    test "truncated multi-byte sequence at the end is replaced" do
+      assert Native.strip_tags(<<"caf", 0xC3>>) == "caf\uFFFD"
+    end
+  end
+
+  # --- sanitize_federation/2 options ---
+
+  describe "sanitize_federation/2 with options" do
+    test "returns the plain text of the sanitized HTML" do
+      html = ~s[<p>Tom &amp; Jerry<script>x</script></p><p>line<br>two&nbsp;words</p>]
+
+      assert {:ok, {sanitized, "Tom & Jerry\n\nline\ntwo words"}} =
+               Native.sanitize_federation(html, %{plaintext: true})
+
+      assert sanitized == Native.sanitize_federation(html)
+    end
+
+    test "takes plain-text input through the fast path" do
+      assert Native.sanitize_federation("  a > b ", %{plaintext: true}) ==
+               {:ok, {"  a &gt; b ", "a > b"}}
+    end
+
+    test "defaults to html only and :lossy" do
+      invalid = <<"<p>caf", 0xE9, "</p>">>
+      assert Native.sanitize_federation(invalid, %{}) == {:ok, "<p>caf\uFFFD</p>"}
+
+      assert Native.sanitize_federation(invalid, %{invalid_utf8: :error, plaintext: true}) ==
+               {:error, :invalid_utf8}
+    end
+
+    test "normalizes the quirks of the source" do
+      greentext = ~s(<p><span class="greentext">&gt;be me</span></p>)
+      assert Native.sanitize_federation(greentext, %{source: :pleroma}) == {:ok, greentext}
+
+      assert Native.sanitize_federation("<p>$[x2 big] news</p>", %{source: :misskey}) ==
+               {:ok, "<p>big news</p>"}
+
+      lemmy = "<details><summary>Spoiler</summary><p>ending</p></details>"
+
+      assert Native.sanitize_federation(lemmy, %{source: :lemmy}) ==
+               {:ok, "<p><strong>Spoiler</strong></p><p>ending</p>"}
+
+      assert Native.sanitize_federation("<p>$[x2 big]</p>", %{source: :mastodon}) ==
+               {:ok, "<p>$[x2 big]</p>"}
+    end
+
+    test "converts Friendica BBCode only where the policy allows it" do
+      html = "<p>[b]bold[/b] a[i] [url=javascript:x()]x[/url] [url]https://a.example[/url]</p>"
+
+      assert Native.sanitize_federation(html, %{source: :friendica}) ==
+               {:ok,
+                ~s(<p><strong>bold</strong> a[i] <a rel="nofollow noopener noreferrer">x</a> ) <>
+                  ~s(<a href="https://a.example" rel="nofollow noopener noreferrer">) <>
+                  "https://a.example</a></p>"}
+    end
+
+    test "upgrades links to HTTPS hosts" do
+      html =
+        ~s(<a href="http://Example.com:80/a">http://example.com/a</a> ) <>
+          ~s(<a href="http://example.com:8080/">b</a> <a href="http://www.example.com/">c</a>)
+
+      assert Native.sanitize_federation(html, %{https_hosts: ["example.com"]}) ==
+               {:ok,
+                ~s(<a href="https://Example.com/a" rel="nofollow noopener noreferrer">) <>
+                  "http://example.com/a</a> " <>
+                  ~s(<a href="http://example.com:8080/" rel="nofollow noopener noreferrer">b</a> ) <>
+                  ~s(<a href="http://www.example.com/" rel="nofollow noopener noreferrer">c</a>)}
+    end
+  end
+
+  describe "drop_hidden" do
+    test "drops tracking pixels and hidden elements with their content" do
+      html =
+        ~s(<p>Hi<img src="https://t.example/p.gif" width="1" height="1"></p>) <>
+          ~s(<div style="display: none !important">preheader</div><p hidden>secret</p>) <>
+          ~s(<span style="font-size:0">spam words</span><p style="opacity: 0.5">faint</p>)
+
+      assert Native.sanitize_federation(html, %{drop_hidden: true}) ==
+               {:ok, "<p>Hi</p><p>faint</p>"}
+
+      assert {:ok, kept} = Native.sanitize_federation(html, %{})
+      assert kept =~ "preheader"
+    end
+
+    test "keeps images of real size" do
+      html =
+        ~s(<img src="https://a.example/cat.png" width="600" height="400">) <>
+          ~s(<img src="https://a.example/t.gif" style="width:0px; height:0">)
+
+      for sanitize <- [&Native.sanitize_markdown/2, &Native.normalize_feed_html/2] do
+        assert sanitize.(html, %{drop_hidden: true}) ==
+                 {:ok, ~s(<img src="https://a.example/cat.png">)}
+      end
+    end
+  end
+
+  describe "max_images" do
+    @images ~s(<p><img src="https://a.example/1.png" alt="one">) <>
+              ~s(<img class="custom-emoji" src="https://a.example/e.png" alt=":e:">) <>
+              ~s(<img src="https://a.example/2.png"></p><p><img alt="no source"></p>) <>
+              ~s(<p><a href="https://a.example/"><img src="https://a.example/3.png" alt="three"></a></p>)
+
+    test "replaces images past the limit with links and counts them" do
+      expected =
+        ~s(<p><img src="https://a.example/1.png" alt="one">) <>
+          ~s(<img class="custom-emoji" src="https://a.example/e.png" alt=":e:">) <>
+          ~s(<a href="https://a.example/2.png" rel="nofollow noopener">https://a.example/2.png</a></p>) <>
+          ~s(<p></p><p><a href="https://a.example/" rel="nofollow noopener">three</a></p>)
+
+      assert Native.sanitize_markdown(@images, %{max_images: 1}) == {:ok, {expected, 3}}
+    end
+
+    test "reports nothing removed under the limit" do
+      assert {:ok, {html, 0}} = Native.normalize_feed_html(@images, %{max_images: 4})
+      assert html =~ ~s(<img src="https://a.example/3.png" alt="three">)
+    end
+
+    test "follows the plain text from sanitize_federation/2" do
+      html = ~s(<p>a<img src="https://a.example/1.png" alt="cat"></p>)
+
+      assert Native.sanitize_federation(html, %{max_images: 0, plaintext: true}) ==
+               {:ok,
+                {~s(<p>a<a href="https://a.example/1.png" rel="nofollow noopener noreferrer">cat</a></p>),
+                 "acat", 1}}
+
+      assert Native.sanitize_federation(html, %{max_images: nil}) == {:ok, "<p>a</p>"}
+    end
+  end
+
+  describe "break_long_words" do
+    test "soft-hyphenates long words of letters" do
+      html = "<p>Donaudampfschifffahrt fährt</p>"
+
+      assert Native.sanitize_federation(html, %{break_long_words: 8}) ==
+               {:ok, "<p>Donaudam\u00ADpfschiff\u00ADfahrt fährt</p>"}
+    end
+
+    test "puts wbr in URLs and leaves code alone" do
+      html = "<p>https://example.com/path</p><pre><code>a_very_long_identifier</code></pre>"
+
+      expected =
+        "<p>https://<wbr>example.<wbr>com/path</p><pre><code>a_very_long_identifier</code></pre>"
+
+      assert Native.sanitize_markdown(html, %{break_long_words: 8}) == {:ok, expected}
+      assert Native.sanitize_markdown(expected, %{break_long_words: 8}) == {:ok, expected}
+    end
+
+    test "never splits grapheme clusters" do
+      family = "👨‍👩‍👧‍👦"
+      html = "<p>#{String.duplicate(family, 3)}</p>"
+
+      assert Native.normalize_feed_html(html, %{break_long_words: 2}) ==
+               {:ok, "<p>#{family}#{family}<wbr>#{family}</p>"}
+    end
+
+    test "returns the plain text without breaks" do
+      options = %{break_long_words: 4, plaintext: true}
+
+      assert Native.sanitize_federation("<p>abcdefgh</p>", options) ==
+               {:ok, {"<p>abcd\u00ADefgh</p>", "abcdefgh"}}
+    end
+
+    test "keeps wbr from the sender" do
+      html = "<p>long<wbr>word</p>"
+
+      assert Native.sanitize_federation(html) == html
+      assert Native.sanitize_markdown(html) == html
+    end
+  end
+
+  describe "greentext" do
+    test "keeps greentext spans" do
+      html = ~s(<p><span class="greentext">&gt;be me</span></p>)
+      assert Native.sanitize_federation(html) == html
+    end
+
+    test "wraps lines starting with > when asked" do
+      html = "<p>&gt;be me<br>&gt;sanitize <em>html</em><br>it works</p>"
+
+      expected =
+        ~s(<p><span class="greentext">&gt;be me</span><br>) <>
+          ~s(<span class="greentext">&gt;sanitize <em>html</em></span><br>it works</p>)
+
+      assert Native.sanitize_federation(html, %{greentext: true}) == {:ok, expected}
+      assert Native.sanitize_federation(expected, %{greentext: true}) == {:ok, expected}
+      assert Native.sanitize_federation(html) == html
+    end
+
+    test "leaves code and links alone" do
+      html =
+        "<pre><code>&gt; prompt</code></pre><p>a <a href=\"https://example.com\">&gt;b</a></p>"
+
+      {:ok, sanitized} = Native.sanitize_federation(html, %{greentext: true})
+      refute sanitized =~ "greentext"
+    end
+
+    test "ends lines at nested blocks" do
+      html = "<ul><li>&gt;a<ul><li>b</li></ul></li></ul>"
+      expected = ~s(<ul><li><span class="greentext">&gt;a</span><ul><li>b</li></ul></li></ul>)
+      assert Native.sanitize_federation(html, %{greentext: true}) == {:ok, expected}
+    end
+
+    test "returns the plain text unchanged" do
+      options = %{greentext: true, plaintext: true}
+
+      assert Native.sanitize_federation("<p>&gt;be me</p>", options) ==
+               {:ok, {~s(<p><span class="greentext">&gt;be me</span></p>), ">be me"}}
+    end
+  end
+
+  describe "sanitize_markdown/2 and normalize_feed_html/2 with options" do
+    test "upgrade images and links to HTTPS hosts, including subdomains" do
+      html = ~s(<img src="http://img.cdn.example/a.png"><img src="http://cdn.example/b.png">)
+      options = %{https_hosts: ["*.cdn.example"]}
+
+      expected =
+        ~s(<img src="https://img.cdn.example/a.png"><img src="http://cdn.example/b.png">)
+
+      assert Native.sanitize_markdown(html, options) == {:ok, expected}
+      assert Native.normalize_feed_html(html, options) == {:ok, expected}
+    end
+
+    test "default to no upgrades and :lossy" do
+      html = ~s(<a href="http://example.com/">x</a>)
+
+      assert Native.sanitize_markdown(html, %{}) ==
+               {:ok, ~s(<a href="http://example.com/" rel="nofollow noopener">x</a>)}
+
+      assert Native.normalize_feed_html(<<0xE9>>, %{invalid_utf8: :error}) ==
+               {:error, :invalid_utf8}
+    end
+  end
+
+  # --- describe_policy/1 ---
+
+  describe "describe_policy/1" do
+    test "describes the federation allowlist" do
+      policy = Native.describe_policy(:federation)
+
+      assert "a" in policy.tags
+      refute "img" in policy.tags
+      assert policy.attributes["a"] == ["href", "class"]
+      assert policy.attributes["time"] == ["datetime"]
+      assert "mention" in policy.classes["span"]
+      assert policy.url_schemes == ["http", "https"]
+      assert policy.relative_urls == :deny
+      refute policy.fragment_links
+      assert policy.link_rel == "nofollow noopener noreferrer"
+      assert "script" in policy.clean_content_tags
+      assert policy.strip_comments
+    end
+
+    test "describes the markdown allowlist" do
+      policy = Native.describe_policy(:markdown)
+
+      assert "img" in policy.tags
+      assert "table" in policy.tags
+      assert "mailto" in policy.url_schemes
+      assert policy.relative_urls == :pass_through
+      assert policy.fragment_links
+      assert policy.attributes["h2"] == ["id"]
+      assert policy.classes["code"] == ["language-*"]
+      assert policy.classes["img"] == ["custom-emoji"]
+      assert Enum.take(policy.classes["span"], 4) == ~w[diff-ins diff-del diff-hunk diff-header]
+      assert "mfm-jelly" in policy.classes["span"]
+    end
+
+    test "markdown allows every federation tag" do
+      federation = Native.describe_policy(:federation)
+      markdown = Native.describe_policy(:markdown)
+
+      assert federation.tags -- markdown.tags == []
+    end
+
+    test "matches what the sanitizer enforces" do
+      policy = Native.describe_policy(:federation)
+
+      for tag <- policy.tags, tag not in ["br", "hr"] do
+        assert Native.sanitize_federation("<#{tag}>x</#{tag}>") =~ "<#{tag}"
+      end
+
+      # Void elements have no content to remove.
+      for tag <- policy.clean_content_tags, tag not in ["input", "embed"] do
+        refute Native.sanitize_federation("<#{tag}>x</#{tag}>") =~ "x"
+      end
+    end
+
+    test "rejects unknown policies" do
+      assert_raise ArgumentError, fn -> Native.describe_policy(:other) end
+    end
+  end
+
+  # --- stats/0 ---
+
+  describe "stats/0" do
+    # Other tests run concurrently, so counters are compared with >=.
+    test "counts calls, input bytes, and stripped elements" do
+      before = Native.stats()
+      html = "<p>ok</p><script>x</script><div>y</div>"
+
+      Native.sanitize_federation(html)
+      Native.sanitize_federation(html, :error)
+
+      after_calls = Native.stats()
+      assert after_calls.calls.sanitize_federation >= before.calls.sanitize_federation + 2
+      assert after_calls.bytes_processed >= before.bytes_processed + 2 * byte_size(html)
+      assert after_calls.elements_stripped >= before.elements_stripped + 4
+    end
+
+    test "counts the elements streams strip" do
+      before = Native.stats()
+      {:ok, stream} = Native.stream_new(:markdown)
+      :ok = Native.stream_push(stream, "<p>ok</p><script>x</scr")
+      :ok = Native.stream_push(stream, "ipt><div>y</div>")
+      {:ok, _html} = Native.stream_finish(stream)
+
+      assert Native.stats().elements_stripped >= before.elements_stripped + 2
+    end
+
+    test "counts errors" do
+      before = Native.stats()
+      assert Native.strip_tags(<<0xFF>>, :error) == {:error, :invalid_utf8}
+      assert Native.stats().errors >= before.errors + 1
+    end
+
+    test "returns every counter as a non-negative integer" do
+      stats = Native.stats()
+
+      for {_nif, count} <- stats.calls, do: assert(is_integer(count) and count >= 0)
+
+      for key <- [:bytes_processed, :elements_stripped, :errors] do
+        assert is_integer(stats[key]) and stats[key] >= 0
+      end
+    end
+  end
+
+  describe "spam_score/1" do
+    test "scores ordinary statuses zero" do
+      html =
+        ~s[<p><span class="h-card"><a href="https://example.social/@a" class="u-url mention">] <>
+          ~s[@<span>a</span></a></span> see <a href="https://example.com/x">this</a> ] <>
+          ~s[<a href="https://example.social/tags/t" class="mention hashtag">#t</a></p>]
+
+      assert %{score: +0.0, links: 1, domains: 1, mentions: 1} = Native.spam_score(html)
+      assert %{score: +0.0, links: 0, mentions: 0} = Native.spam_score("Hello world, nice day")
+    end
+
+    test "counts bare URLs, their domains, and @mentions in text" do
+      signals =
+        Native.spam_score("win https://a.com https://b.net/x http://c.org @x @y @z @w me@host.com")
+
+      assert %{links: 3, domains: 3, mentions: 4} = signals
+      assert signals.score > 0.3
+    end
+
+    test "detects shouting and repeated characters" do
+      signals = Native.spam_score("BUY NOW CHEAP PILLS TODAY ONLY!!!!!!!!")
+
+      assert signals.uppercase_ratio == 1.0
+      assert signals.longest_run == 8
+      assert signals.repeated_runs == 1
+      assert signals.score >= 0.2
+    end
+
+    test "counts emoji and invisible characters" do
+      assert %{emoji: 5, emoji_density: density} = Native.spam_score("😀😀😀😀😀 wow")
+      assert density > 0.5
+
+      assert %{invisible: 2, score: score} = Native.spam_score("hid\u200Bden t\u202Eext")
+      assert score >= 0.2
+    end
+
+    test "ignores script contents and counts custom emoji images" do
+      html = ~s[<script>https://x.com</script><img class="custom-emoji" src="https://e/x.png">]
+      assert %{links: 0, emoji: 1} = Native.spam_score(html)
+    end
+  end
+
+  describe "format_link_label/1" do
+    test "hides the scheme and www. prefix" do
+      assert Native.format_link_label("https://www.example.com/") ==
+               ~s[<span class="invisible">https://www.</span><span>example.com/</span>] <>
+                 ~s[<span class="invisible"></span>]
+    end
+
+    test "elides past 30 characters" do
+      url = "https://example.com/statuses/1234567890/activity"
+
+      assert Native.format_link_label(url) ==
+               ~s[<span class="invisible">https://</span>] <>
+                 ~s[<span class="ellipsis">example.com/statuses/123456789</span>] <>
+                 ~s[<span class="invisible">0/activity</span>]
+    end
+
+    test "counts characters, not bytes" do
+      url = "https://example.com/" <> String.duplicate("é", 20)
+      label = Native.format_link_label(url)
+
+      assert label =~ ~s[<span class="ellipsis">example.com/#{String.duplicate("é", 18)}</span>]
+    end
+
+    test "escapes the URL" do
+      label = Native.format_link_label(~s[https://example.com/?a=1&b="<x>"])
+
+      assert label =~ ~s[example.com/?a=1&amp;b="&lt;x&gt;"]
+      refute label =~ "<x>"
+    end
+
+    test "keeps unknown schemes visible" do
+      assert Native.format_link_label("gopher://example.com") =~
+               ~s[<span class="invisible"></span><span>gopher://example.com</span>]
+    end
+
+    test "survives the federation sanitizer" do
+      for url <- [
+            "https://example.com/a",
+            "http://example.com/" <> String.duplicate("x", 40),
+            "https://example.com/?q=<b>&amp;\u00A0"
+          ] do
+        label = Native.format_link_label(url)
+        html = ~s[<p><a href="#{url}">#{label}</a></p>]
+
+        assert Native.sanitize_federation(html) =~ label
+      end
+    end
+  end
+
+  @mentions %{
+    "alice" => "https://local.example/users/alice",
+    "bob@remote.example" => "https://remote.example/@bob"
+  }
+
+  defp build_status(text, emoji \\ []),
+    do: Native.build_status_html(text, @mentions, "https://local.example/tags/", emoji)
+
+  describe "build_status_html/4" do
+    test "wraps paragraphs and line breaks, escaping the text" do
+      assert build_status("a < b & c\nsecond\r\n\n\nthird\n").html ==
+               "<p>a &lt; b &amp; c<br>second</p><p>third</p>"
+
+      assert build_status("  \n\n").html == ""
+    end
+
+    test "links mentions with a known profile URL" do
+      status = build_status("Hi @Alice and @bob@remote.example, not @carol")
+
+      assert status.html ==
+               ~s(<p>Hi <span class="h-card"><a href="https://local.example/users/alice" ) <>
+                 ~s(class="u-url mention" rel="nofollow noopener noreferrer">@<span>Alice</span>) <>
+                 ~s(</a></span> and <span class="h-card"><a href="https://remote.example/@bob" ) <>
+                 ~s(class="u-url mention" rel="nofollow noopener noreferrer">@<span>bob</span>) <>
+                 ~s(</a></span>, not @carol</p>)
+
+      assert status.mentions == [@mentions["alice"], @mentions["bob@remote.example"]]
+    end
+
+    test "leaves addresses and unresolved hosts alone" do
+      status = build_status("mail me@alice.example or @alice@nowhere")
+
+      assert status.html == "<p>mail me@alice.example or @alice@nowhere</p>"
+      assert status.mentions == []
+    end
+
+    test "links hashtags" do
+      status = build_status("#Elixir and #日本語, not a#b or #1")
+
+      assert status.html =~
+               ~s(<a href="https://local.example/tags/elixir" class="mention hashtag" ) <>
+                 ~s(rel="nofollow noopener noreferrer">#<span>Elixir</span></a>)
+
+      assert status.html =~ "not a#b or #1"
+      assert status.tags == ["elixir", "日本語"]
+    end
+
+    test "links hashtags under their canonical form" do
+      status = build_status("#Café and #cafe")
+
+      assert status.html =~
+               ~s(<a href="https://local.example/tags/cafe" class="mention hashtag" ) <>
+                 ~s(rel="nofollow noopener noreferrer">#<span>Café</span></a>)
+
+      assert status.tags == ["cafe"]
+    end
+
+    test "links URLs without trailing punctuation" do
+      status = build_status("See https://example.com/a_(b)?x=1&y=2).")
+
+      assert status.html ==
+               ~s(<p>See <a href="https://example.com/a_(b)?x=1&amp;y=2" ) <>
+                 ~s(rel="nofollow noopener noreferrer">) <>
+                 Native.format_link_label("https://example.com/a_(b)?x=1&y=2") <> "</a>).</p>"
+    end
+
+    test "reports the known emoji used" do
+      status = build_status(":blobcat: :unknown: :blobcat:", ["blobcat", "other"])
+
+      assert status.html == "<p>:blobcat: :unknown: :blobcat:</p>"
+      assert status.emoji == ["blobcat"]
+    end
+
+    test "passes sanitize_federation/1 unchanged" do
+      for text <- [
+            "Hi @alice #tag https://www.example.com/a/very/long/path/that/goes/on",
+            "café\u00A0ok \"quoted\" <b>not bold</b>\nline two",
+            "(@bob@remote.example) https://example.com/?q=\"x\""
+          ] do
+        html = build_status(text).html
+
+        assert Native.sanitize_federation(html) == html
+      end
+    end
+  end
+
+  describe "analyze_content/1" do
+    test "sanitizes and extracts in one call" do
+      html =
+        ~s[<p>Hi <span class="h-card">] <>
+          ~s[<a href="https://ex.social/@bob" class="u-url mention">@<span>bob</span></a>] <>
+          ~s[</span> :wave: <script>x</script></p>] <>
+          ~s[<p>see <a href="https://example.com/a">this</a> ] <>
+          ~s[<a href="https://ex.social/tags/Rust" class="mention hashtag">] <>
+          ~s[#<span>Rust</span></a>] <>
+          ~s[<br>and <a href="https://ex.social/tags/rust" class="hashtag">#rust</a></p>]
+
+      {sanitized, plaintext, mentions, hashtags, urls, shortcodes} = Native.analyze_content(html)
+
+      assert sanitized == Native.sanitize_federation(html)
+      assert plaintext == "Hi @bob :wave:\n\nsee this #Rust\nand #rust"
+      assert mentions == ["https://ex.social/@bob"]
+      assert hashtags == ["rust"]
+      assert urls == ["https://example.com/a"]
+      assert shortcodes == ["wave"]
+    end
+
+    test "reports hashtags in canonical form" do
+      html =
+        ~s[<p><a href="https://ex.social/tags/caf%C3%A9" class="mention hashtag">] <>
+          ~s[#<span>Café</span></a> <a href="https://ex.social/tags/cafe" class="hashtag">#cafe</a> ] <>
+          ~s[<a href="https://ex.social/tags/1" class="hashtag">#123</a></p>]
+
+      assert {_, _, [], ["cafe"], [], []} = Native.analyze_content(html)
+    end
+
+    test "decodes entities and separates list items" do
+      html = "<p>a &amp; b</p><ul><li>x</li><li>y</li></ul>"
+      {_, plaintext, _, _, _, _} = Native.analyze_content(html)
+
+      assert plaintext == "a & b\n\nx\ny"
+    end
+
+    test "ignores shortcodes in code and deduplicates links" do
+      html =
+        ~s[<p><code>:skip:</code> :ok: :ok: <a href="https://a.example/">1</a>] <>
+          ~s[ <a href="https://a.example/">2</a></p><pre>:pre:</pre>]
+
+      assert {_, _, [], [], ["https://a.example/"], ["ok"]} = Native.analyze_content(html)
+    end
+
+    test "handles plain text and invalid UTF-8" do
+      assert {"hello", "hello", [], [], [], []} = Native.analyze_content("hello")
+      assert {"\uFFFD", "\uFFFD", [], [], [], []} = Native.analyze_content(<<0xFF>>)
+    end
+
+    test "is counted in stats/0" do
+      before = Native.stats().calls.analyze_content
+      Native.analyze_content("<p>x</p>")
+      assert Native.stats().calls.analyze_content >= before + 1
+    end
+  end
+
+  describe "derive_title/1" do
+    test "prefers the first heading, wherever it is" do
+      assert Native.derive_title("<p>Intro text.</p><h2>The &amp; Heading</h2>") ==
+               "The & Heading"
+    end
+
+    test "takes the first sentence of the first paragraph" do
+      assert Native.derive_title("<p></p><p>Hello world. Second sentence.</p>") == "Hello world"
+      assert Native.derive_title("<p>Version 3.5 is out! Get it now</p>") == "Version 3.5 is out!"
+      assert Native.derive_title("<p>Line one<br>line two</p>") == "Line one"
+      assert Native.derive_title("<p>日本語の文章です。次の文。</p>") == "日本語の文章です"
+    end
+
+    test "uses the first line of plain text" do
+      assert Native.derive_title("Hello world\nMore text") == "Hello world"
+      assert Native.derive_title("Fish &amp; chips\nmore") == "Fish & chips"
+      assert Native.derive_title("<div>No paragraphs</div><div>here</div>") == "No paragraphs"
+    end
+
+    test "leaves out hidden link label parts" do
+      html =
+        ~s[<p>Check <a href="https://www.example.com/long/path">] <>
+          ~s[<span class="invisible">https://www.</span>] <>
+          ~s[<span class="ellipsis">example.com/long</span>] <>
+          ~s[<span class="invisible">/path</span></a></p>]
+
+      assert Native.derive_title(html) == "Check example.com/long"
+    end
+
+    test "truncates at a word boundary or between graphemes" do
+      words = Native.derive_title("<p>" <> String.duplicate("word ", 30) <> "</p>")
+      assert words == String.duplicate("word ", 15) <> "word…"
+
+      cjk = Native.derive_title("<p>" <> String.duplicate("漢", 100) <> "</p>")
+      assert cjk == String.duplicate("漢", 80) <> "…"
+
+      accented = Native.derive_title(String.duplicate("e\u0301", 100))
+      assert String.length(accented) == 81
+      assert String.ends_with?(accented, "e\u0301…")
+    end
+
+    test "returns nil without text" do
+      assert Native.derive_title("") == nil
+      assert Native.derive_title("<script>alert(1)</script><p> </p>") == nil
+    end
+  end
+
+  describe "sanitize_inline/2" do
+    test "strips tags and decodes entities" do
+      assert Native.sanitize_inline("<b>Yes</b> &amp; <i>no</i> :blobcat:", 100) ==
+               "Yes & no :blobcat:"
+
+      assert Native.sanitize_inline("&lt;b&gt; is not a tag", 100) == "<b> is not a tag"
+      html = ~s[<script>alert(1)</script><a href="https://x">x</a>]
+      assert Native.sanitize_inline(html, 100) == "x"
+    end
+
+    test "puts everything on one line" do
+      assert Native.sanitize_inline("  Line one\nline\ttwo  ", 100) == "Line one line two"
+      assert Native.sanitize_inline("<p>a</p><p>b</p>c<br>d<b>e</b>", 100) == "a b c de"
+    end
+
+    test "removes control characters and bidi overrides" do
+      assert Native.sanitize_inline("x\u202Eevil\a\u0000", 100) == "xevil"
+      assert Native.sanitize_inline("👨\u200D👩\u200D👧", 100) == "👨\u200D👩\u200D👧"
+    end
+
+    test "cuts to max_graphemes" do
+      assert Native.sanitize_inline("👨\u200D👩\u200D👧 family e\u0301", 3) == "👨\u200D👩\u200D👧 f"
+      assert Native.sanitize_inline("abc def", 4) == "abc"
+      assert Native.sanitize_inline("abc", 0) == ""
+    end
+  end
+
+  describe "image_accessibility/1" do
+    test "reports each image's alt text in order" do
+      html =
+        ~s[<p><img src="/a/cat.jpg" alt=" A tabby cat on a rug "></p>] <>
+          ~s[<img src="/b/x.png"><img src="/c/y.png" alt="">]
+
+      assert Native.image_accessibility(html) == [
+               %{src: "/a/cat.jpg", has_alt: true, alt_length: 20, placeholder: false},
+               %{src: "/b/x.png", has_alt: false, alt_length: 0, placeholder: false},
+               %{src: "/c/y.png", has_alt: false, alt_length: 0, placeholder: false}
+             ]
+    end
+
+    test "flags file names and generic words as placeholders" do
+      for alt <- [
+            "IMG_1234",
+            "photo.PNG",
+            "PXL_20240101_123456",
+            "Screenshot 2024-01-01 at 10.00.00",
+            "Image",
+            "w"
+          ] do
+        html = ~s[<img src="https://cdn.example/w.webp?size=large" alt="#{alt}">]
+        assert [%{placeholder: true}] = Native.image_accessibility(html), alt
+      end
+
+      assert [%{placeholder: false}] =
+               Native.image_accessibility(~s[<img src="/k.png" alt="IMG of my cat">])
+    end
+
+    test "counts graphemes and skips custom emoji" do
+      html =
+        ~s[<img class="custom-emoji" src="/e.png" alt=":blob:">] <>
+          ~s[<img alt="猫がいます e\u0301">]
+
+      assert Native.image_accessibility(html) == [
+               %{src: nil, has_alt: true, alt_length: 7, placeholder: false}
+             ]
+    end
+
+    test "returns an empty list without images" do
+      assert Native.image_accessibility("no markup at all") == []
+      assert Native.image_accessibility("<p>text</p>") == []
+    end
+  end
+
+  describe "render_mfm/2" do
+    test "converts functions with a class to spans" do
+      html = "<p>$[jelly hi] $[x2 $[flip.h,v,speed=2s upside]] $[fg.color=f00 red]</p>"
+
+      assert Native.render_mfm(html, :html) ==
+               ~s(<p><span class="mfm-jelly">hi</span> ) <>
+                 ~s(<span class="mfm-x2">) <>
+                 ~s(<span class="mfm-flip mfm-flip-h mfm-flip-v">upside</span></span>) <>
+                 " red</p>"
+    end
+
+    test "strips functions to their text" do
+      html = "<p>$[jelly hi] $[x2 $[flip.h upside]] $[ruby 漢字 かんじ]</p>"
+      assert Native.render_mfm(html, :strip) == "<p>hi upside 漢字 かんじ</p>"
+    end
+
+    test "strips functions whose text crosses a tag" do
+      assert Native.render_mfm("<p>$[x2 a</p><p>b]</p>", :html) == "<p>a</p><p>b</p>"
+      assert Native.render_mfm("<p>$[x2 <b>a</b>]</p>", :html) == "<p><b>a</b></p>"
+    end
+
+    test "keeps what is not MFM" do
+      for html <- [
+            "<p>Costs $[5] or $[] each</p>",
+            "<p>$[x2 never closed</p>",
+            ~s(<a href="https://example.com/$[x2 y]" title="a>b">link</a>),
+            "plain [brackets]"
+          ] do
+        assert Native.render_mfm(html, :html) == html
+        assert Native.render_mfm(html, :strip) == html
+      end
+    end
+
+    test "produces classes the markdown sanitizer keeps" do
+      html = Native.render_mfm("<p>$[spin.left,x $[rainbow $[font.serif text]]]</p>", :html)
+      assert Native.sanitize_markdown(html) == html
+    end
+  end
+
+  describe "inspect_html/1" do
+    test "reports elements, depth, and attributes without changing anything" do
+      html = ~s(<div><p title="hello" lang="en">a <b>b</b></p><p>c</p></div><!-- note -->)
+      report = Native.inspect_html(html)
+
+      assert report.bytes == byte_size(html)
+      assert report.elements == %{"div" => 1, "p" => 2, "b" => 1}
+      assert report.element_count == 4
+      assert report.max_depth == 3
+      assert report.attribute_count == 2
+      assert report.max_attributes == 2
+      assert report.max_attribute_bytes == 5
+      assert report.total_attribute_bytes == 7
+      assert report.comments == 1
+      assert report.parse_errors == 0
+      assert report.parse_error_messages == []
+    end
+
+    test "counts foreign and prefixed names and parse recoveries" do
+      html = ~s(<p>a<b>b</p></span><o:p xmlns:o="urn:office"></o:p><svg><circle r="1"/></svg>)
+      report = Native.inspect_html(html)
+
+      assert report.foreign_elements == 2
+      assert report.prefixed_names == 2
+      assert report.parse_errors > 0
+      assert length(report.parse_error_messages) in 1..report.parse_errors
+    end
+
+    test "reports long attributes and deep nesting" do
+      html = ~s(<a title="#{String.duplicate("x", 100_000)}">x</a>) <> String.duplicate("<i>", 500)
+      report = Native.inspect_html(html)
+
+      assert report.max_attribute_bytes == 100_000
+      assert report.max_depth == 500
+    end
+
+    test "reports plain text as empty" do
+      assert %{element_count: 0, bytes: 5, parse_errors: 0} = Native.inspect_html("plain")
+    end
+  end
+
+  describe "build_og_meta/1" do
+    test "strips markup and escapes quotes and line breaks" do
+      tags = [
+        {"og:title", ~s(Tom & "Jerry"\n<script>x</script><em>again</em>)},
+        {"og:url", "https://a.example/?q=1&r=2"},
+        {"twitter:title", "it's <b>bold</b> &amp; &quot;fine&quot;"},
+        {"profile:username", "alice"}
+      ]
+
+      assert Native.build_og_meta(tags) ==
+               Enum.join(
+                 [
+                   ~s(<meta property="og:title" content="Tom &amp; &quot;Jerry&quot; again">),
+                   ~s(<meta property="og:url" content="https://a.example/?q=1&amp;r=2">),
+                   ~s(<meta name="twitter:title" content="it's bold &amp; &quot;fine&quot;">),
+                   ~s(<meta name="profile:username" content="alice">)
+                 ],
+                 "\n"
+               )
+    end
+
+    test "truncates descriptions and other text" do
+      long = String.duplicate("word ", 100)
+
+      assert Native.build_og_meta([{"og:description", long}]) ==
+               ~s(<meta property="og:description" content="#{String.slice(long, 0, 199)}…">)
+
+      assert Native.build_og_meta(%{"og:title" => String.duplicate("a", 400)}) ==
+               ~s(<meta property="og:title" content="#{String.duplicate("a", 300)}…">)
+    end
+
+    test "skips nil and empty contents, invalid URLs, and invalid properties" do
+      assert Native.build_og_meta([
+               {"og:description", nil},
+               {"og:site_name", "  <br> "},
+               {"og:image", "https://a.example/a b.png"},
+               {~s(og:title" onload="x), "x"},
+               {"OG:TITLE", "x"}
+             ]) == ""
+    end
+
+    test "renders maps sorted by property" do
+      assert Native.build_og_meta(%{"og:type" => "website", "og:image" => "https://a/i.png"}) ==
+               ~s(<meta property="og:image" content="https://a/i.png">\n) <>
+                 ~s(<meta property="og:type" content="website">)
+    end
+  end
+
+  describe "build_preview_card/1" do
+    test "escapes the text once and links the proxied image" do
+      card = %{
+        url: "https://a.example/post?x=1&y=2",
+        title: ~s(Tom &amp; <b>"Jerry"</b>),
+        description: "a < b\n\nc",
+        domain: "a.example",
+        image_path: "/uploads/link_previews/ab.webp",
+        image_url: "https://a.example/og.png"
+      }
+
+      assert {:ok, html} = Native.build_preview_card(card)
+
+      assert html ==
+               ~s(<blockquote><p><a href="https://a.example/post?x=1&amp;y=2" ) <>
+                 ~s(rel="nofollow noopener"><img src="/uploads/link_previews/ab.webp" alt="">) <>
+                 ~s(<br><strong>Tom &amp; "Jerry"</strong></a></p><p>a &lt; b c</p>) <>
+                 "<p>a.example</p></blockquote>"
+
+      assert Native.sanitize_markdown(html) == html
+    end
+
+    test "prefers the site name and falls back to the URL as title" do
+      card = %{url: "https://a.example/", title: " <br> ", site_name: "A & Co", domain: "a"}
+
+      assert Native.build_preview_card(card) ==
+               {:ok,
+                ~s(<blockquote><p><a href="https://a.example/" rel="nofollow noopener">) <>
+                  ~s(<strong>https://a.example/</strong></a></p><p>A &amp; Co</p></blockquote>)}
+    end
+
+    test "drops images that would load from elsewhere" do
+      for src <- ["//evil.example/x.png", "javascript:alert(1)", "data:image/png;base64,AA"] do
+        card = %{url: "https://a.example/", image_path: src}
+        assert {:ok, html} = Native.build_preview_card(card)
+        refute html =~ "<img"
+      end
+    end
+
+    test "rejects URLs that are not http(s)" do
+      for url <- [nil, "", "javascript:alert(1)", "/relative", "https://a.example/a b"] do
+        assert Native.build_preview_card(%{url: url}) == {:error, :invalid_url}
+      end
+    end
+
+    test "accepts a stored link preview" do
+      preview = %Baudrate.Content.LinkPreview{url: "https://a.example/", title: "A"}
+      assert {:ok, html} = Native.build_preview_card(preview)
+      assert Native.sanitize_markdown(html) == html
+    end
+  end
+
+  describe "build_profile_field_html/2" do
+    test "links URLs with rel=me and escapes the rest" do
+      field = Native.build_profile_field_html("Blog", "mine: https://a.example/?x=1&y=2 & more")
+
+      assert field.name == "Blog"
+
+      assert field.value ==
+               ~s(mine: <a href="https://a.example/?x=1&amp;y=2" rel="me nofollow noopener">) <>
+                 Native.format_link_label("https://a.example/?x=1&y=2") <> "</a> &amp; more"
+
+      assert field.links == ["https://a.example/?x=1&y=2"]
+    end
+
+    test "drops markup from the name and the value" do
+      field =
+        Native.build_profile_field_html(
+          "<b>Site</b>",
+          ~s(<a href="https://evil.example">text</a><script>x</script>)
+        )
+
+      assert field == %{name: "Site", value: "text", links: []}
+    end
+
+    test "lists each URL once" do
+      field = Native.build_profile_field_html("Code", "https://a.example https://a.example")
+      assert field.links == ["https://a.example"]
+      assert length(String.split(field.value, "<a ")) == 3
+    end
+  end
+
+  describe "extract_quote/1" do
+    test "finds a trailing RE: link and removes the line" do
+      html =
+        ~s(<p>So true<br><br>RE: <a href="https://misskey.example/notes/9abc">) <>
+          ~s(https://misskey.example/notes/9abc</a></p>)
+
+      assert Native.extract_quote(html) ==
+               %{url: "https://misskey.example/notes/9abc", html: "<p>So true</p>"}
+    end
+
+    test "finds a quote-inline element" do
+      html =
+        ~s(<p>Look at this</p><p class="quote-inline">RE: ) <>
+          ~s(<a href="https://m.example/@a/1"><span class="invisible">https://</span>) <>
+          ~s(m.example/@a/1</a></p>)
+
+      assert Native.extract_quote(html) ==
+               %{url: "https://m.example/@a/1", html: "<p>Look at this</p>"}
+
+      html =
+        ~s(<p>Hi<span class="quote-inline"><br>QT: ) <>
+          ~s(<a href="https://fedibird.example/@b/2">x</a></span></p>)
+
+      assert Native.extract_quote(html) ==
+               %{url: "https://fedibird.example/@b/2", html: "<p>Hi</p>"}
+    end
+
+    test "drops a paragraph left empty" do
+      html = ~s(<p>Text</p><p>re: <a href="http://a.example/4">x</a> </p>\n)
+
+      assert Native.extract_quote(html) == %{url: "http://a.example/4", html: "<p>Text</p>"}
+    end
+
+    test "ignores links that are not quote lines" do
+      for html <- [
+            ~s(<p>See <a href="https://a.example/3">link</a></p>),
+            ~s(<p>Here are: <a href="https://a.example/3">link</a></p>),
+            ~s(<p>RE: <a href="https://a.example/3">link</a> and more</p>),
+            ~s(<p>RE: <a href="javascript:alert(1)">x</a></p>),
+            "<p>RE: nothing</p>",
+            ""
+          ] do
+        assert Native.extract_quote(html) == nil
+      end
+    end
+  end
+
+  describe "compile_cw_rules/1 and suggest_cw/2" do
+    setup do
+      {:ok, rules} =
+        Native.compile_cw_rules([
+          %{category: "politics", keywords: ["election", "Vote"]},
+          %{category: "spoilers", keywords: ["ネタバレ"], patterns: ["season [0-9]+ finale"]},
+          %{category: "food", keywords: ["pizza"]},
+          %{category: "politics", keywords: ["senate"]}
+        ])
+
+      %{rules: rules}
+    end
+
+    test "matches keywords as whole words, ignoring case and markup", %{rules: rules} do
+      assert Native.suggest_cw(rules, "<p>Who will you <b>VOTE</b> for?</p>") == ["politics"]
+      assert Native.suggest_cw(rules, "Devoted fans of pizzas") == []
+      assert Native.suggest_cw(rules, "election&amp;more") == ["politics"]
+    end
+
+    test "matches keywords inside text written without spaces", %{rules: rules} do
+      assert Native.suggest_cw(rules, "今日はネタバレ注意") == ["spoilers"]
+    end
+
+    test "matches patterns case-insensitively", %{rules: rules} do
+      assert Native.suggest_cw(rules, "The Season 3 Finale was wild") == ["spoilers"]
+    end
+
+    test "returns each category once, in rule order", %{rules: rules} do
+      text = "Pizza after the senate election, season 2 finale"
+      assert Native.suggest_cw(rules, text) == ["politics", "spoilers", "food"]
+      assert Native.suggest_cw(rules, "") == []
+    end
+
+    test "rejects invalid patterns" do
+      assert {:error, {:invalid_pattern, "(unclosed"}} =
+               Native.compile_cw_rules([%{category: "x", patterns: ["(unclosed"]}])
+
+      assert {:ok, rules} = Native.compile_cw_rules([])
+      assert Native.suggest_cw(rules, "anything") == []
+    end
+  end
+
+  describe "pathological input" do
+    test "returns strings instead of panicking" do
+      inputs = [
+        String.duplicate("<div><span>", 2_000) <> "x",
+        ~s(<a href="https://example.com/" title="<!--) <> String.duplicate("<", 10_000),
+        String.duplicate("&", 100_000),
+        "<p>\0</p>",
+        ""
+      ]
+
+      for html <- inputs do
+        for sanitize <- [
+              &Native.sanitize_federation/1,
+              &Native.sanitize_markdown/1,
+              &Native.strip_tags/1,
+              &Native.normalize_feed_html/1
+            ] do
+          assert is_binary(sanitize.(html))
+        end
+
+        assert is_binary(Native.emojify(html, %{"x" => "https://example.com/x.png"}))
+        assert is_list(Native.extract_shortcodes(html))
+      end
+    end
+  end
 end
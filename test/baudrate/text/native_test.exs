@@ -0,0 +1,923 @@
+defmodule Baudrate.Text.NativeTest do
+  use ExUnit.Case, async: true
+
+  alias Baudrate.Text.Native
+
+  describe "casefold/1" do
+    test "folds ASCII to lowercase" do
+      assert Native.casefold("Hello World") == "hello world"
+    end
+
+    test "applies multi-character folds" do
+      assert Native.casefold("Straße") == "strasse"
+    end
+
+    test "folds all sigma forms to the same character" do
+      assert Native.casefold("ΣΑΣ") == Native.casefold("σας")
+    end
+
+    test "returns NFC-normalized output" do
+      assert Native.casefold("E\u0301") == "é"
+    end
+  end
+
+  describe "caseless_equal/2" do
+    test "matches ASCII case variants" do
+      assert Native.caseless_equal("Alice", "aLICE")
+    end
+
+    test "matches full case folding variants" do
+      assert Native.caseless_equal("STRASSE", "straße")
+    end
+
+    test "matches canonically equivalent composed and decomposed forms" do
+      assert Native.caseless_equal("Cafe\u0301", "CAFÉ")
+    end
+
+    test "rejects different strings" do
+      refute Native.caseless_equal("alice", "alicia")
+    end
+  end
+
+  describe "normalize_hashtag/1" do
+    test "folds case and Latin diacritics, keeping the spelling to display" do
+      assert Native.normalize_hashtag("#CaféCon") == {"cafecon", "CaféCon"}
+      assert Native.normalize_hashtag("cafecon") == {"cafecon", "cafecon"}
+      assert Native.normalize_hashtag("Cafe\u0301Con") == {"cafecon", "Cafe\u0301Con"}
+    end
+
+    test "applies NFKC to full-width and half-width forms" do
+      assert {"elixir", _} = Native.normalize_hashtag("＃ＥＬＩＸＩＲ")
+      assert {"エリクサー", _} = Native.normalize_hashtag("ｴﾘｸｻｰ")
+    end
+
+    test "lowercases as Mastodon does instead of case folding" do
+      assert {"straße", _} = Native.normalize_hashtag("Straße")
+      assert {"σίσυφος", _} = Native.normalize_hashtag("ΣΊΣΥΦΟΣ")
+    end
+
+    test "keeps other scripts and marks, dropping punctuation" do
+      assert {"台灣", _} = Native.normalize_hashtag("台灣")
+      assert {"हिन्दी", _} = Native.normalize_hashtag("हिन्दी")
+      assert {"foobar", _} = Native.normalize_hashtag("foo-bar")
+      assert {"a·b_c", _} = Native.normalize_hashtag("a·b_c")
+    end
+
+    test "returns nil without a letter" do
+      assert Native.normalize_hashtag("#123") == nil
+      assert Native.normalize_hashtag("#!!") == nil
+      assert Native.normalize_hashtag("") == nil
+    end
+  end
+
+  describe "bidi_isolate/2" do
+    test "wraps text in FSI/PDI isolates by default" do
+      assert Native.bidi_isolate("alice") == "\u2068alice\u2069"
+    end
+
+    test "strips explicit directional overrides" do
+      result = Native.bidi_isolate("evil\u202Egnp.exe")
+      refute result =~ "\u202E"
+      assert result == "\u2068evilgnp.exe\u2069"
+    end
+
+    test "strips embedded isolate characters so the wrapper cannot be closed early" do
+      result = Native.bidi_isolate("a\u2069b\u2067c")
+      assert result == "\u2068abc\u2069"
+    end
+
+    test "html mode wraps escaped text in a dir=auto span" do
+      assert Native.bidi_isolate("<b>\u202Ebob</b>", :html) ==
+               ~s(<span dir="auto">&lt;b&gt;bob&lt;/b&gt;</span>)
+    end
+  end
+
+  describe "replace_shortcodes/1" do
+    test "replaces known shortcodes with Unicode emoji" do
+      assert Native.replace_shortcodes("so funny :joy:") == "so funny 😂"
+    end
+
+    test "handles shortcodes containing + and -" do
+      assert Native.replace_shortcodes(":+1:") == "👍"
+    end
+
+    test "leaves unknown shortcodes untouched" do
+      assert Native.replace_shortcodes(":blobcat: hi") == ":blobcat: hi"
+    end
+
+    test "does not treat clock times as shortcodes" do
+      assert Native.replace_shortcodes("meet at 10:30:smile:") == "meet at 10:30😄"
+    end
+  end
+
+  describe "parse_acct/1" do
+    test "parses acct: URIs" do
+      assert Native.parse_acct("acct:alice@example.com") == {:ok, {"alice", "example.com"}}
+    end
+
+    test "parses bare and @-prefixed handles" do
+      assert Native.parse_acct("alice@example.com") == {:ok, {"alice", "example.com"}}
+      assert Native.parse_acct("@alice@example.com") == {:ok, {"alice", "example.com"}}
+    end
+
+    test "parses profile URLs" do
+      assert Native.parse_acct("https://example.com/@alice") == {:ok, {"alice", "example.com"}}
+      assert Native.parse_acct("https://example.com/@alice/") == {:ok, {"alice", "example.com"}}
+    end
+
+    test "case-folds the user and host" do
+      assert Native.parse_acct("acct:Alice@Example.COM") == {:ok, {"alice", "example.com"}}
+    end
+
+    test "IDNA-encodes internationalized hosts" do
+      assert Native.parse_acct("bob@bücher.example") == {:ok, {"bob", "xn--bcher-kva.example"}}
+    end
+
+    test "keeps explicit ports" do
+      assert Native.parse_acct("acct:dave@localhost:4001") == {:ok, {"dave", "localhost:4001"}}
+    end
+
+    test "keeps Lemmy-style board prefixes" do
+      assert Native.parse_acct("acct:!general@lemmy.example") ==
+               {:ok, {"!general", "lemmy.example"}}
+    end
+
+    test "rejects unrecognized forms" do
+      assert Native.parse_acct("alice") == {:error, :invalid_resource}
+      assert Native.parse_acct("https://example.com/users/alice") == {:error, :invalid_resource}
+    end
+
+    test "rejects invalid users" do
+      assert Native.parse_acct("acct:@example.com") == {:error, :invalid_user}
+      assert Native.parse_acct("acct:a@b@example.com") == {:error, :invalid_user}
+      assert Native.parse_acct("acct:a b@example.com") == {:error, :invalid_user}
+    end
+
+    test "rejects invalid hosts" do
+      assert Native.parse_acct("alice@") == {:error, :invalid_host}
+      assert Native.parse_acct("alice@exa mple.com") == {:error, :invalid_host}
+      assert Native.parse_acct("alice@example.com:http") == {:error, :invalid_host}
+    end
+
+    test "percent-decodes the user of profile URLs" do
+      assert Native.parse_acct("https://example.com/@%C3%BCn%C3%AF") ==
+               {:ok, {"ünï", "example.com"}}
+    end
+  end
+
+  describe "same_actor?/2" do
+    test "matches accounts and profile URLs of the same user" do
+      assert Native.same_actor?("https://example.com/@Alice", "https://example.com/users/alice")
+      assert Native.same_actor?("acct:alice@example.com", "https://example.com/users/alice/")
+      assert Native.same_actor?("@alice@example.com", "https://example.com/u/alice")
+      assert Native.same_actor?("alice@example.com", "https://example.com/ap/users/Alice")
+    end
+
+    test "folds case and IDNA-encodes hosts" do
+      assert Native.same_actor?("@ALICE@Bücher.example", "https://xn--bcher-kva.example/@alice")
+      assert Native.same_actor?("Straße@example.com", "strasse@example.com")
+    end
+
+    test "distinguishes users, hosts, and ports" do
+      refute Native.same_actor?("alice@example.com", "bob@example.com")
+      refute Native.same_actor?("alice@example.com", "alice@other.example")
+      refute Native.same_actor?("alice@example.com", "alice@example.com:8080")
+    end
+
+    test "compares other URLs without their fragment and trailing slash" do
+      assert Native.same_actor?("https://example.com/actor/1", "https://EXAMPLE.com/actor/1#key")
+      assert Native.same_actor?("https://example.com/actor/1/", "https://example.com/actor/1")
+      refute Native.same_actor?("https://example.com/actor/1", "https://example.com/actor/2")
+      refute Native.same_actor?("https://example.com/@alice/1", "alice@example.com")
+    end
+
+    test "matches nothing that is not an account or http(s) URL" do
+      refute Native.same_actor?("alice", "alice")
+      refute Native.same_actor?("ftp://example.com/@alice", "ftp://example.com/@alice")
+    end
+  end
+
+  describe "actor_url_forms/1" do
+    test "lists the URLs of each profile path" do
+      assert Native.actor_url_forms("acct:Alice@Bücher.example") ==
+               {:ok,
+                [
+                  "https://xn--bcher-kva.example/@alice",
+                  "https://xn--bcher-kva.example/users/alice",
+                  "https://xn--bcher-kva.example/ap/users/alice",
+                  "https://xn--bcher-kva.example/u/alice",
+                  "https://xn--bcher-kva.example/profile/alice",
+                  "https://xn--bcher-kva.example/accounts/alice"
+                ]}
+    end
+
+    test "percent-encodes the user and keeps ports" do
+      assert {:ok, ["https://localhost:4001/@%C3%BCn%C3%AF" | _]} =
+               Native.actor_url_forms("ünï@localhost:4001")
+    end
+
+    test "returns parse_acct/1 errors" do
+      assert Native.actor_url_forms("alice") == {:error, :invalid_resource}
+      assert Native.actor_url_forms("a b@example.com") == {:error, :invalid_user}
+    end
+  end
+
+  describe "simhash/1 and hamming_distance/2" do
+    @post "Buy cheap watches now at our amazing online store, best prices guaranteed for everyone"
+
+    test "near-identical texts are close" do
+      mutated = String.replace(@post, "store", "shop") <> "!!"
+
+      assert Native.hamming_distance(Native.simhash(@post), Native.simhash(mutated)) <= 10
+    end
+
+    test "unrelated texts are far apart" do
+      other = "The quick brown fox jumps over the lazy dog while the cat sleeps in the sun"
+
+      assert Native.hamming_distance(Native.simhash(@post), Native.simhash(other)) >= 20
+    end
+
+    test "ignores case, punctuation, and whitespace" do
+      assert Native.simhash(@post) == Native.simhash("  " <> String.upcase(@post) <> ".")
+      assert Native.simhash("Straße, Köln") == Native.simhash("STRASSE köln")
+    end
+
+    test "is stable and fits in 64 bits" do
+      hash = Native.simhash(@post)
+
+      assert hash == Native.simhash(@post)
+      assert hash in 0..(2 ** 64 - 1)
+      assert Native.simhash("") == 0
+      assert Native.simhash("?! ...") == 0
+      assert Native.simhash("hi") != 0
+    end
+
+    test "hamming_distance counts differing bits" do
+      assert Native.hamming_distance(0, 0) == 0
+      assert Native.hamming_distance(0b1011, 0b0001) == 2
+      assert Native.hamming_distance(0, 2 ** 64 - 1) == 64
+    end
+  end
+
+  describe "wrap_text/2" do
+    test "wraps at whitespace" do
+      assert Native.wrap_text("The quick brown fox jumps over the lazy dog", 15) ==
+               {:ok, "The quick brown\nfox jumps over\nthe lazy dog"}
+    end
+
+    test "counts wide characters as two columns and breaks CJK text anywhere" do
+      assert Native.wrap_text("日本語のテキスト", 6) == {:ok, "日本語\nのテキ\nスト"}
+      assert Native.wrap_text("abc 中文字", 6) == {:ok, "abc 中\n文字"}
+    end
+
+    test "keeps combining marks with their base character" do
+      assert Native.wrap_text("e\u0301e\u0301e\u0301", 2) ==
+               {:ok, "e\u0301e\u0301\ne\u0301"}
+    end
+
+    test "repeats quote prefixes and indents list continuations" do
+      assert Native.wrap_text("> > quoted text that wraps", 14) ==
+               {:ok, "> > quoted\n> > text that\n> > wraps"}
+
+      assert Native.wrap_text("- item that wraps around", 12) ==
+               {:ok, "- item that\n  wraps\n  around"}
+
+      assert Native.wrap_text("12. numbered item wraps", 14) ==
+               {:ok, "12. numbered\n    item wraps"}
+    end
+
+    test "breaks words longer than a line" do
+      assert Native.wrap_text("see https://example.com/long", 10) ==
+               {:ok, "see https:\n//example.\ncom/long"}
+    end
+
+    test "reflows each line on its own" do
+      assert Native.wrap_text("  one two\r\n\n>\nlast  \n", 6) ==
+               {:ok, "  one\n  two\n\n>\nlast\n"}
+    end
+
+    test "rejects a zero width" do
+      assert Native.wrap_text("text", 0) == {:error, :invalid_width}
+    end
+  end
+
+  describe "figlet/2" do
+    test "renders a banner without trailing whitespace" do
+      banner =
+        " ____  ____ ____\n| __ )| __ ) ___|\n|  _ \\|  _ \\___ \\\n" <>
+          "| |_) | |_) |__) |\n|____/|____/____/"
+
+      assert Native.figlet("BBS", :standard) == {:ok, banner}
+    end
+
+    test "supports every embedded font" do
+      for font <- [:standard, :small, :slant, :big] do
+        assert {:ok, banner} = Native.figlet("Baudrate", font)
+        assert length(String.split(banner, "\n")) >= 4
+        refute banner =~ ~r/ $/m
+      end
+    end
+
+    test "escapes HTML" do
+      assert {:ok, banner} = Native.figlet("<&>", :standard)
+      assert banner =~ "&lt;"
+      assert banner =~ "&gt;"
+      refute banner =~ "<"
+    end
+
+    test "skips characters the font lacks" do
+      assert Native.figlet("日本", :standard) == {:ok, ""}
+      assert Native.figlet("", :small) == {:ok, ""}
+    end
+
+    test "rejects long text" do
+      assert Native.figlet(String.duplicate("x", 65), :small) == {:error, :text_too_long}
+    end
+  end
+
+  describe "uuencode/2 and uudecode/1" do
+    test "round-trips binary data" do
+      data = for i <- 0..999, into: <<>>, do: <<rem(i, 256)>>
+      assert {:ok, text} = Native.uuencode("bin.dat", data)
+
+      assert text =~ ~r/\Abegin 644 bin.dat\nM/
+      assert String.ends_with?(text, "\n`\nend\n")
+      assert Native.uudecode(text) == [{"bin.dat", data}]
+    end
+
+    test "decodes every file in a message" do
+      message = """
+      Here are the files.
+
+      begin 644 cat.txt
+      #0V%T
+      `
+      end
+
+      begin 600 dog.txt
+      #1&]G
+      \s
+      end
+      """
+
+      assert Native.uudecode(message) == [{"cat.txt", "Cat"}, {"dog.txt", "Dog"}]
+    end
+
+    test "accepts CRLF lines and a missing zero-length line" do
+      assert Native.uudecode("begin 644 a\r\n#0V%T\r\nend\r\n") == [{"a", "Cat"}]
+    end
+
+    test "strips directories from filenames" do
+      assert [{"passwd", "Cat"}] = Native.uudecode("begin 644 ../../etc/passwd\n#0V%T\n`\nend\n")
+      assert [{"unnamed", _}] = Native.uudecode("begin 644 ..\n#0V%T\n`\nend\n")
+    end
+
+    test "skips truncated and corrupt files" do
+      assert Native.uudecode("begin 644 a\n#0V%T\n") == []
+      assert Native.uudecode("begin 644 a\n#0V~T\n`\nend\n") == []
+      assert Native.uudecode("no attachments here") == []
+    end
+
+    test "rejects invalid filenames" do
+      assert Native.uuencode("", "x") == {:error, :invalid_filename}
+      assert Native.uuencode("a\nb", "x") == {:error, :invalid_filename}
+    end
+  end
+
+  describe "parse_ftn_message/1" do
+    test "separates kludges, tearline, origin, and SEEN-BY lines from the body" do
+      message =
+        Enum.join(
+          [
+            "AREA:FIDO.GENERAL",
+            "\x01MSGID: 2:5020/1 1234abcd",
+            "\x01CHRS: CP866 2",
+            "Hello all,",
+            "",
+            "line two",
+            "",
+            "--- GoldED+ 1.1.5",
+            " * Origin: Some BBS (2:5020/1.0)",
+            "SEEN-BY: 5020/1 2",
+            "SEEN-BY: 5030/3",
+            "\x01PATH: 5020/1",
+            ""
+          ],
+          "\r"
+        )
+
+      assert Native.parse_ftn_message(message) == %{
+               area: "FIDO.GENERAL",
+               kludges: [
+                 {"MSGID", "2:5020/1 1234abcd"},
+                 {"CHRS", "CP866 2"},
+                 {"PATH", "5020/1"}
+               ],
+               charset: "CP866",
+               body: "Hello all,\n\nline two",
+               tearline: "GoldED+ 1.1.5",
+               origin: "Some BBS",
+               address: "2:5020/1.0",
+               seen_by: ["5020/1 2", "5030/3"]
+             }
+    end
+
+    test "splits kludges without a colon at the first space" do
+      assert %{kludges: [{"INTL", "1:2/3 2:5020/1"}, {"Via", "2:5020/1 @20240101"}]} =
+               Native.parse_ftn_message("\x01INTL 1:2/3 2:5020/1\nhi\n\x01Via 2:5020/1 @20240101")
+    end
+
+    test "handles messages without control lines" do
+      assert %{body: "just text", tearline: "", origin: nil, area: nil, kludges: []} =
+               Native.parse_ftn_message("just text\r\n---\r\n")
+
+      assert %{body: "", kludges: [], seen_by: []} = Native.parse_ftn_message("")
+    end
+
+    test "keeps body bytes in their original charset" do
+      assert %{body: <<0x8F, 0xE0, 0xA8>>} = Native.parse_ftn_message(<<0x8F, 0xE0, 0xA8>>)
+    end
+  end
+
+  describe "to_flowed/2 and from_flowed/2" do
+    test "soft-breaks long lines at spaces" do
+      assert Native.to_flowed("The quick brown fox jumps over the lazy dog", 20) ==
+               {:ok, "The quick brown fox \r\njumps over the lazy \r\ndog"}
+    end
+
+    test "repeats quote marks and keeps depths apart" do
+      assert {:ok, flowed} = Native.to_flowed("> quoted text that wraps\n>> deeper\n>\nplain", 14)
+      assert flowed == "> quoted text \r\n> that wraps\r\n>> deeper\r\n>\r\nplain"
+      assert Native.from_flowed(flowed) == "> quoted text that wraps\n>> deeper\n>\nplain"
+    end
+
+    test "space-stuffs and keeps the signature separator fixed" do
+      assert Native.to_flowed("From here\n leading\n-- \ntrailing   ", 72) ==
+               {:ok, " From here\r\n  leading\r\n-- \r\ntrailing"}
+
+      assert Native.from_flowed(" From here\r\n  leading\r\n-- \r\nsig") ==
+               "From here\n leading\n-- \nsig"
+    end
+
+    test "does not split long words" do
+      assert Native.to_flowed("averyverylongword and more", 10) ==
+               {:ok, "averyverylongword \r\nand more"}
+    end
+
+    test "round-trips text" do
+      text = "two  spaces  between  words  that  wrap\n\n> a quoted paragraph here\nend\n"
+
+      for width <- [8, 20, 72] do
+        assert {:ok, flowed} = Native.to_flowed(text, width)
+        assert Native.from_flowed(flowed) == text
+      end
+    end
+
+    test "ends a paragraph at a change of quote depth" do
+      assert Native.from_flowed("> quoted \r\n>> deeper\r\n") == "> quoted \n>> deeper\n"
+    end
+
+    test "removes the soft-break space with delsp" do
+      assert Native.from_flowed("Hel \r\nlo\r\n", true) == "Hello\n"
+      assert Native.from_flowed("Hel \r\nlo\r\n") == "Hel lo\n"
+    end
+
+    test "rejects invalid widths" do
+      assert Native.to_flowed("x", 0) == {:error, :invalid_width}
+      assert Native.to_flowed("x", 999) == {:error, :invalid_width}
+    end
+  end
+
+  describe "split_reply/1" do
+    test "separates new text, the attributed quote, and the signature" do
+      text = """
+      Thanks, that works!
+
+      On Mon, 1 Jan 2024, Alice wrote:
+      > Try this:
+      >> older text
+      > ok
+
+      --\s
+      Bob
+      """
+
+      assert Native.split_reply(text) ==
+               {"Thanks, that works!",
+                "On Mon, 1 Jan 2024, Alice wrote:\n> Try this:\n>> older text\n> ok", "Bob"}
+    end
+
+    test "keeps answers between inline quotes" do
+      text = "> question one\nanswer one\n\n> question two\nanswer two\n"
+
+      assert Native.split_reply(text) ==
+               {"answer one\n\nanswer two", "> question one\n\n> question two", nil}
+    end
+
+    test "quotes everything after an Original Message line" do
+      text = "Reply\r\n-- \r\nBob\r\n\r\n-----Original Message-----\r\nhello\r\n-- \r\nAlice"
+
+      assert Native.split_reply(text) ==
+               {"Reply", "-----Original Message-----\nhello\n-- \nAlice", "Bob"}
+    end
+
+    test "only treats a line ending in a colon as an attribution before a quote" do
+      assert Native.split_reply("Note: no quotes\n") == {"Note: no quotes", nil, nil}
+      assert Native.split_reply("") == {"", nil, nil}
+    end
+  end
+
+  describe "parse_ics/1 and build_ics/1" do
+    @ics Enum.join(
+           [
+             "BEGIN:VCALENDAR",
+             "VERSION:2.0",
+             "BEGIN:VTIMEZONE",
+             "TZID:Europe/Paris",
+             "END:VTIMEZONE",
+             "BEGIN:VEVENT",
+             "UID:abc@mobilizon.example",
+             "DTSTART;TZID=Europe/Paris:20240501T180000",
+             "DTEND;TZID=Europe/Paris:20240501T200000",
+             "SUMMARY:Meetup\\, with friends\\; and \\\\ more",
+             "DESCRIPTION:Line one\\nLine two\\, folded across sev",
+             " eral lines",
+             ~s[LOCATION;LANGUAGE="fr":Café Paris],
+             "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20240601T000000Z;WKST=SU",
+             "EXDATE;TZID=Europe/Paris:20240515T180000,20240522T180000",
+             "BEGIN:VALARM",
+             "DESCRIPTION:alarm",
+             "END:VALARM",
+             "END:VEVENT",
+             "BEGIN:VEVENT",
+             "UID:all-day",
+             "DTSTART;VALUE=DATE:20240601",
+             "DURATION:P1D",
+             "END:VEVENT",
+             "BEGIN:VEVENT",
+             "UID:no-start",
+             "END:VEVENT",
+             "END:VCALENDAR",
+             ""
+           ],
+           "\r\n"
+         )
+
+    test "parses events, unfolding and unescaping" do
+      assert {:ok, [event, all_day]} = Native.parse_ics(@ics)
+
+      assert %{
+               uid: "abc@mobilizon.example",
+               summary: "Meetup, with friends; and \\ more",
+               description: "Line one\nLine two, folded across several lines",
+               location: "Café Paris",
+               starts_at: "2024-05-01T18:00:00",
+               ends_at: "2024-05-01T20:00:00",
+               duration: nil,
+               timezone: "Europe/Paris",
+               all_day: false,
+               exdates: ["2024-05-15T18:00:00", "2024-05-22T18:00:00"]
+             } = event
+
+      assert event.rrule == %{
+               freq: :weekly,
+               interval: 2,
+               count: nil,
+               until: "2024-06-01T00:00:00Z",
+               by_day: ["MO", "WE"],
+               by_month_day: [],
+               by_month: [],
+               rest: [{"WKST", "SU"}]
+             }
+
+      assert %{starts_at: "2024-06-01", all_day: true, duration: "P1D", timezone: nil} = all_day
+    end
+
+    test "rejects input that is not a calendar" do
+      assert Native.parse_ics("hello") == {:error, :invalid_ics}
+      assert Native.parse_ics("BEGIN:VCALENDAR\nBEGIN:VEVENT\nEND:VCALENDAR\n") ==
+               {:error, :invalid_ics}
+    end
+
+    test "builds a calendar that parses back to the same events" do
+      {:ok, events} = Native.parse_ics(@ics)
+
+      utc =
+        Enum.map(events, fn event ->
+          {:ok, utc} = Native.utc_ics_event(event)
+          utc
+        end)
+
+      stamped = Enum.map(utc, &Map.put(&1, :dtstamp, "2024-04-01T12:00:00Z"))
+      assert {:ok, ics} = Native.build_ics(stamped)
+
+      assert ics =~ "DTSTAMP:20240401T120000Z\r\n"
+      assert ics =~ "DTSTART:20240501T160000Z\r\n"
+      assert ics =~ "EXDATE:20240515T160000Z\r\n"
+      refute ics =~ "TZID"
+      assert ics =~ "SUMMARY:Meetup\\, with friends\\; and \\\\ more\r\n"
+      assert ics =~ "DTSTART;VALUE=DATE:20240601\r\n"
+      assert ics |> String.split("\r\n") |> Enum.all?(&(byte_size(&1) <= 75))
+      assert Native.parse_ics(ics) == {:ok, utc}
+    end
+
+    test "converts zoned times to UTC" do
+      event = %{
+        uid: "1",
+        starts_at: "2024-03-31T02:30:00",
+        ends_at: "2024-10-27T02:30:00",
+        timezone: "Europe/Paris",
+        exdates: ["2024-05-01", "2024-05-08T18:00:00"],
+        rrule: %{freq: :weekly, until: "2024-12-31T23:00:00"}
+      }
+
+      assert {:ok, utc} = Native.utc_ics_event(event)
+
+      # 02:30 is skipped in March and repeated in October.
+      assert %{starts_at: "2024-03-31T01:00:00Z", ends_at: "2024-10-27T00:30:00Z"} = utc
+      assert utc.exdates == ["2024-05-01", "2024-05-08T16:00:00Z"]
+      assert utc.rrule.until == "2024-12-31T22:00:00Z"
+      assert utc.timezone == nil
+
+      assert Native.utc_ics_event(%{event | timezone: "Mars/Olympus"}) ==
+               {:error, :invalid_timezone}
+
+      assert Native.utc_ics_event(%{event | starts_at: "soon"}) == {:error, :invalid_time}
+    end
+
+    test "skips non-ASCII times" do
+      # Eight and six bytes long, with a character across the slice points.
+      ics =
+        "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART:202é501\r\nEXDATE:20240501T1é000\r\n" <>
+          "END:VEVENT\r\nEND:VCALENDAR\r\n"
+
+      assert Native.parse_ics(ics) == {:ok, []}
+    end
+
+    test "folds long lines on character boundaries" do
+      description = String.duplicate("é", 60)
+      event = %{uid: "1", starts_at: "2024-05-01T18:00:00Z", description: description}
+      {:ok, ics} = Native.build_ics([event])
+
+      assert ics =~ "\r\n é"
+      assert {:ok, [%{description: ^description}]} = Native.parse_ics(ics)
+    end
+
+    test "rejects invalid times, zones, and rules" do
+      event = %{uid: "1", starts_at: "2024-05-01T18:00:00"}
+
+      assert Native.build_ics([%{event | starts_at: "May 1st"}]) == {:error, :invalid_time}
+      assert Native.build_ics([Map.put(event, :timezone, "Europe/Paris")]) ==
+               {:error, :invalid_timezone}
+
+      assert Native.build_ics([Map.put(event, :rrule, %{freq: :weekly, by_day: ["XX"]})]) ==
+               {:error, :invalid_rrule}
+    end
+  end
+
+  describe "build_vcard/1" do
+    test "writes an escaped, folded vCard 4.0 card" do
+      profile = %{
+        name: "Alice, the \"Admin\"; Ops",
+        nickname: "alice",
+        note: "Hi!\nI run a BBS since 1994, with 天气 and much more text to force folding.",
+        photo: "https://example.com/avatars/alice.png",
+        urls: ["https://example.com/@alice", "https://alice.example"],
+        uid: "https://example.com/users/alice"
+      }
+
+      assert {:ok, vcard} = Native.build_vcard(profile)
+
+      assert vcard ==
+               Enum.join(
+                 [
+                   "BEGIN:VCARD",
+                   "VERSION:4.0",
+                   "KIND:individual",
+                   ~S[FN:Alice\, the "Admin"\; Ops],
+                   "NICKNAME:alice",
+                   "UID:https://example.com/users/alice",
+                   "PHOTO:https://example.com/avatars/alice.png",
+                   "URL:https://example.com/@alice",
+                   "URL:https://alice.example",
+                   ~S[NOTE:Hi!\nI run a BBS since 1994\, with 天气 and much more text to force ],
+                   " folding.",
+                   "END:VCARD",
+                   ""
+                 ],
+                 "\r\n"
+               )
+    end
+
+    test "leaves out missing and blank properties" do
+      assert {:ok, vcard} = Native.build_vcard(%{name: "Bob", note: "  ", urls: []})
+      assert vcard == "BEGIN:VCARD\r\nVERSION:4.0\r\nKIND:individual\r\nFN:Bob\r\nEND:VCARD\r\n"
+    end
+
+    test "requires a name and well-formed URIs" do
+      assert Native.build_vcard(%{name: " "}) == {:error, :missing_name}
+      assert Native.build_vcard(%{nickname: "bob"}) == {:error, :missing_name}
+
+      assert Native.build_vcard(%{name: "Bob", photo: "https://x/a b.png"}) ==
+               {:error, :invalid_uri}
+    end
+  end
+
+  describe "parse_front_matter/1" do
+    test "parses YAML front matter" do
+      text = """
+      ---
+      title: "Notes on \\"flow\\""
+      tags: [bbs, 'fido''net']
+      summary: >
+        A look back at
+        store-and-forward mail.
+      license: CC-BY-4.0
+      draft: false
+      weight: 3
+      date: 2024-05-01
+      ---
+
+      # Notes
+      """
+
+      assert {:ok, {front_matter, "# Notes\n"}} = Native.parse_front_matter(text)
+
+      assert front_matter == %{
+               "title" => ~S[Notes on "flow"],
+               "tags" => ["bbs", "fido'net"],
+               "summary" => "A look back at store-and-forward mail.\n",
+               "license" => "CC-BY-4.0",
+               "draft" => false,
+               "weight" => 3,
+               "date" => "2024-05-01"
+             }
+    end
+
+    test "parses YAML block lists and comments" do
+      text = "---\n# metadata\ntags:\n  - a\n  - b\nsummary: ~\n---\nBody"
+
+      assert Native.parse_front_matter(text) ==
+               {:ok, {%{"tags" => ["a", "b"], "summary" => nil}, "Body"}}
+    end
+
+    test "parses TOML front matter" do
+      text = "+++\r\ntitle = 'Hello'\r\ntags = [\"a\", \"b\"]\r\nrating = 4.5\r\n+++\r\nBody\r\n"
+
+      assert Native.parse_front_matter(text) ==
+               {:ok, {%{"title" => "Hello", "tags" => ["a", "b"], "rating" => 4.5}, "Body\r\n"}}
+    end
+
+    test "returns texts without front matter whole" do
+      for text <- ["# Title\n\nBody", "---\n\nA thematic break, not front matter", ""] do
+        assert Native.parse_front_matter(text) == {:ok, {%{}, text}}
+      end
+    end
+
+    test "rejects what it cannot read" do
+      for text <- [
+            "---\nauthor:\n  name: Alice\n---\n",
+            "---\nlinks: {a: 1}\n---\n",
+            "---\ntitle: a\ntitle: b\n---\n",
+            "---\njust a line\n---\n",
+            "+++\ntitle = bare\n+++\n",
+            "+++\n[extra]\nkey = 1\n+++\n"
+          ] do
+        assert Native.parse_front_matter(text) == {:error, :invalid_front_matter}
+      end
+    end
+  end
+
+  describe "annotate_code_fences/1" do
+    test "writes the language, filename, and highlighted lines as attributes" do
+      markdown =
+        "Intro\n```rust linenos title=main.rs {1,3-5}\nif a < b {\n    run(\"x\");\n}\n```\nAfter"
+
+      assert Native.annotate_code_fences(markdown) ==
+               "Intro\n\n" <>
+                 ~s[<pre data-filename="main.rs" data-highlight-lines="1,3-5">] <>
+                 ~s[<code class="language-rust">if a &lt; b {\n    run(&quot;x&quot;);\n}] <>
+                 "</code>\n</pre>\n\nAfter"
+    end
+
+    test "reads other info string styles" do
+      html = Native.annotate_code_fences(~s[```py hl_lines="1 3-5" title="my app.py"\nx\n```])
+      assert html =~ ~s[<pre data-filename="my app.py" data-highlight-lines="1,3-5">]
+      assert html =~ ~s[<code class="language-py">]
+
+      assert Native.annotate_code_fences("```ruby:app.rb\nputs 1\n```") =~
+               ~s[<pre data-filename="app.rb"><code class="language-ruby">]
+
+      assert Native.annotate_code_fences("~~~c++\nx\n~~~") =~
+               ~s[<pre><code class="language-c++">]
+    end
+
+    test "drops malformed values" do
+      assert Native.annotate_code_fences("```<script> {0,2-1}\nx\n```") ==
+               "<pre><code>x</code>\n</pre>\n\n"
+    end
+
+    test "leaves other text and fences without an info string alone" do
+      for markdown <- [
+            "No code here",
+            "```\n```rust\nnot a fence\n```\n",
+            "- item\n\n  ```rust\n  indented\n  ```\n",
+            "Inline ```rust``` code"
+          ] do
+        assert Native.annotate_code_fences(markdown) == markdown
+      end
+    end
+
+    test "marks up the lines of diff blocks" do
+      markdown = """
+      ```diff
+      --- a/app.ex
+      +++ b/app.ex
+      @@ -1,2 +1,2 @@
+       defmodule App do
+      -  def run, do: :error
+      +  def run, do: :ok
+      ```
+      """
+
+      assert Native.annotate_code_fences(markdown) ==
+               ~s[<pre><code class="language-diff">] <>
+                 ~s[<span class="diff-header">--- a/app.ex</span>\n] <>
+                 ~s[<span class="diff-header">+++ b/app.ex</span>\n] <>
+                 ~s[<span class="diff-hunk">@@ -1,2 +1,2 @@</span>\n] <>
+                 ~s[ defmodule App do\n] <>
+                 ~s[<span class="diff-del">-  def run, do: :error</span>\n] <>
+                 ~s[<span class="diff-ins">+  def run, do: :ok</span>] <>
+                 "</code>\n</pre>\n\n"
+    end
+
+    test "marks up bare +/- lines in patch blocks" do
+      assert Native.annotate_code_fences("```patch\n+a\n-b <c>\n d\n```") ==
+               ~s[<pre><code class="language-patch"><span class="diff-ins">+a</span>\n] <>
+                 ~s[<span class="diff-del">-b &lt;c&gt;</span>\n d</code>\n</pre>\n\n]
+    end
+
+    test "keeps the fence length, so shorter fences stay in the code" do
+      assert Native.annotate_code_fences("````md\n```rust\ninner\n```\n````\n") ==
+               ~s[<pre><code class="language-md">```rust\ninner\n```</code>\n</pre>\n\n]
+    end
+  end
+
+  describe "graphemes/1 and grapheme_length/1" do
+    test "counts ZWJ sequences, flags, and combining marks as one grapheme" do
+      family = "\u{1F469}\u200D\u{1F469}\u200D\u{1F467}"
+      text = "e\u0301" <> family <> "\u{1F1EF}\u{1F1F5}\u{1F44D}\u{1F3FD}"
+
+      assert Native.graphemes(text) ==
+               ["e\u0301", family, "\u{1F1EF}\u{1F1F5}", "\u{1F44D}\u{1F3FD}"]
+
+      assert Native.grapheme_length(text) == 4
+    end
+
+    test "keeps CRLF together" do
+      assert Native.graphemes("a\r\nb") == ["a", "\r\n", "b"]
+    end
+
+    test "handles empty and ASCII text" do
+      assert Native.graphemes("") == []
+      assert Native.grapheme_length("") == 0
+      assert Native.grapheme_length("hello") == 5
+    end
+
+    test "agrees with String.length/1 on common text" do
+      for text <- ["日本語", "Ünïcödé", "👍🏽👍🏽", "한국어"] do
+        assert Native.grapheme_length(text) == String.length(text)
+      end
+    end
+  end
+
+  describe "pathological input" do
+    # Long runs of combining marks, joiners, and directional controls.
+    @inputs [
+      "a" <> String.duplicate("\u0301", 50_000),
+      String.duplicate("\u200D", 50_000),
+      String.duplicate("\u202E\u2066", 20_000),
+      String.duplicate(":", 100_000),
+      ""
+    ]
+
+    test "returns results instead of panicking" do
+      for text <- @inputs do
+        assert is_binary(Native.casefold(text))
+        assert is_boolean(Native.caseless_equal(text, text))
+        assert is_binary(Native.bidi_isolate(text, :html))
+        assert is_binary(Native.replace_shortcodes(text))
+        assert is_integer(Native.simhash(text))
+        assert Native.grapheme_length(text) == length(Native.graphemes(text))
+        assert {:ok, _} = Native.wrap_text(text, 1)
+
+        for resource <- [text, text <> "@" <> text] do
+          case Native.parse_acct(resource) do
+            {:ok, {_user, _host}} -> :ok
+            {:error, reason} -> assert is_atom(reason)
+          end
+        end
+      end
+    end
+  end
+end
@@ -113,6 +113,31 @@ defmodule BaudrateWeb.FeedControllerTest do
     end
   end
 
+  # --- Site-wide JSON Feed ---
+
+  describe "GET /feeds/json" do
+    test "returns JSON Feed 1.1 with correct content type", %{
+      conn: conn,
+      user: user,
+      public_board: board
+    } do
+      {:ok, _} = insert_article(user, board, "json-article")
+
+      conn = get(conn, "/feeds/json")
+
+      assert [content_type] = get_resp_header(conn, "content-type")
+      assert content_type =~ "application/feed+json"
+
+      feed = conn |> response(200) |> Jason.decode!()
+      assert feed["version"] == "https://jsonfeed.org/version/1.1"
+      assert feed["title"] == "Test Forum"
+      assert feed["feed_url"] =~ "/feeds/json"
+      assert [item] = feed["items"]
+      assert item["url"] =~ "/articles/json-article"
+      assert is_binary(item["content_html"])
+    end
+  end
+
   # --- Board RSS ---
 
   describe "GET /feeds/boards/:slug/rss" do
@@ -168,6 +193,25 @@ defmodule BaudrateWeb.FeedControllerTest do
     end
   end
 
+  # --- Board JSON Feed ---
+
+  describe "GET /feeds/boards/:slug/json" do
+    test "returns JSON Feed for a public board", %{conn: conn, user: user, public_board: board} do
+      {:ok, _} = insert_article(user, board, "board-json-article")
+
+      conn = get(conn, "/feeds/boards/#{board.slug}/json")
+
+      feed = conn |> response(200) |> Jason.decode!()
+      assert feed["title"] == board.name
+      assert Enum.any?(feed["items"], &(&1["url"] =~ "board-json-article"))
+    end
+
+    test "returns 404 for private boards", %{conn: conn, private_board: board} do
+      conn = get(conn, "/feeds/boards/#{board.slug}/json")
+      assert response(conn, 404)
+    end
+  end
+
   # --- User RSS ---
 
   describe "GET /feeds/users/:username/rss" do
@@ -310,4 +354,19 @@ defmodule BaudrateWeb.FeedControllerTest do
 
     article
   end
+
+  # --- User JSON Feed ---
+
+  describe "GET /feeds/users/:username/json" do
+    test "returns JSON Feed for a user", %{conn: conn, user: user, public_board: board} do
+      {:ok, _} = insert_article(user, board, "user-json-article")
+
+      conn = get(conn, "/feeds/users/#{user.username}/json")
+
+      feed = conn |> response(200) |> Jason.decode!()
+      assert [item] = feed["items"]
+      assert item["url"] =~ "user-json-article"
+      assert item["authors"] == [%{"name" => user.username}]
+    end
+  end
 end
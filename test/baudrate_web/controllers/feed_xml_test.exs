@@ -47,6 +47,99 @@ defmodule BaudrateWeb.FeedXMLTest do
     test "passes through plain text unchanged" do
       assert FeedXML.xml_escape("Hello world") == "Hello world"
     end
+
+    test "drops control characters" do
+      assert FeedXML.xml_escape("a\u0000b\u001Bc\td") == "abc\td"
+    end
+  end
+
+  describe "render/2 with :atom" do
+    test "renders articles as Atom entries" do
+      article = %{
+        title: "Escaping & <things>",
+        slug: "escaping-things",
+        body: "Some **bold** text",
+        inserted_at: ~U[2026-02-22 10:00:00Z],
+        updated_at: ~U[2026-02-23 05:57:22Z],
+        user: %{username: "alice"}
+      }
+
+      xml =
+        FeedXML.render(:atom, %{
+          title: "Baudrate",
+          link: "https://local.example/",
+          self_url: "https://local.example/feeds/atom",
+          language: "en",
+          updated: ~U[2026-02-23 05:57:22Z],
+          articles: [article]
+        })
+
+      assert xml =~ ~s(xmlns="http://www.w3.org/2005/Atom")
+      assert xml =~ "<title>Escaping &amp; &lt;things&gt;</title>"
+      assert xml =~ "<name>alice</name>"
+      assert xml =~ "&lt;strong&gt;bold&lt;/strong&gt;"
+      assert xml =~ "<updated>2026-02-23T05:57:22Z</updated>"
+    end
+  end
+
+  describe "render/2 with :json" do
+    test "lists article images as attachments" do
+      path = Path.join(System.tmp_dir!(), "feed_xml_test_#{System.unique_integer()}.webp")
+      File.write!(path, "RIFF0000WEBP")
+      on_exit(fn -> File.rm(path) end)
+
+      article = %{
+        title: "With images",
+        slug: "with-images",
+        body: "Text",
+        inserted_at: ~U[2026-02-22 10:00:00Z],
+        updated_at: ~U[2026-02-23 05:57:22Z],
+        user: %{username: "alice"},
+        article_images: [
+          %{filename: "a.webp", storage_path: path},
+          %{filename: "gone.webp", storage_path: "/nonexistent/gone.webp"}
+        ]
+      }
+
+      json =
+        FeedXML.render(:json, %{
+          title: "Baudrate",
+          link: "https://local.example/",
+          self_url: "https://local.example/feeds/json",
+          articles: [article]
+        })
+
+      %{"items" => [item]} = Jason.decode!(json)
+      [present, missing] = item["attachments"]
+
+      assert present["url"] =~ "/uploads/article_images/a.webp"
+      assert present["mime_type"] == "image/webp"
+      assert present["size_in_bytes"] == 12
+      assert missing["url"] =~ "/uploads/article_images/gone.webp"
+      refute Map.has_key?(missing, "size_in_bytes")
+    end
+
+    test "omits attachments when images are not loaded" do
+      article = %{
+        title: "Plain",
+        slug: "plain",
+        body: "Text",
+        inserted_at: ~U[2026-02-22 10:00:00Z],
+        updated_at: ~U[2026-02-23 05:57:22Z],
+        user: %{username: "alice"}
+      }
+
+      json =
+        FeedXML.render(:json, %{
+          title: "Baudrate",
+          link: "https://local.example/",
+          self_url: "https://local.example/feeds/json",
+          articles: [article]
+        })
+
+      %{"items" => [item]} = Jason.decode!(json)
+      refute Map.has_key?(item, "attachments")
+    end
   end
 
   describe "rfc822/1" do
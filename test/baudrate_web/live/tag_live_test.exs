@@ -95,6 +95,30 @@ defmodule BaudrateWeb.TagLiveTest do
     assert html =~ "elixir"
   end
 
+  test "displays the tag as written but finds it by canonical form", %{
+    conn: conn,
+    user: user,
+    board: board
+  } do
+    {:ok, %{article: article}} =
+      Content.create_article(
+        %{
+          title: "Coffee Meetup",
+          body: "See you at the #cafe",
+          slug: "cafe-#{System.unique_integer([:positive])}",
+          user_id: user.id
+        },
+        [board.id]
+      )
+
+    Content.sync_article_tags(article)
+
+    {:ok, _lv, html} = live(conn, ~p"/tags/#{"Café"}")
+
+    assert html =~ "Café"
+    assert html =~ "Coffee Meetup"
+  end
+
   test "shows result count when articles exist", %{conn: conn, user: user, board: board} do
     {:ok, %{article: article}} =
       Content.create_article(
@@ -0,0 +1,42 @@
+defmodule BaudrateWeb.TelemetryTest do
+  use ExUnit.Case, async: true
+
+  alias BaudrateWeb.Telemetry
+
+  describe "dispatch_sanitizer_stats/0" do
+    test "emits the sanitizer counters" do
+      test_pid = self()
+      ref = make_ref()
+      handler_id = "test-sanitizer-telemetry-#{inspect(ref)}"
+
+      :telemetry.attach_many(
+        handler_id,
+        [[:baudrate, :sanitizer, :calls], [:baudrate, :sanitizer, :stats]],
+        fn event, measurements, metadata, _ ->
+          send(test_pid, {ref, event, measurements, metadata})
+        end,
+        nil
+      )
+
+      on_exit(fn -> :telemetry.detach(handler_id) end)
+
+      Baudrate.Sanitizer.Native.strip_tags("<p>counted</p>")
+      Telemetry.dispatch_sanitizer_stats()
+
+      assert_receive {^ref, [:baudrate, :sanitizer, :calls], %{count: count}, %{nif: :strip_tags}}
+      assert count >= 1
+
+      assert_receive {^ref, [:baudrate, :sanitizer, :stats], measurements, %{}}
+      assert measurements.bytes_processed >= byte_size("<p>counted</p>")
+      assert measurements.elements_stripped >= 1
+      assert is_integer(measurements.errors)
+    end
+  end
+
+  test "metrics include the sanitizer counters" do
+    names = Enum.map(Telemetry.metrics(), & &1.name)
+
+    assert [:baudrate, :sanitizer, :calls, :count] in names
+    assert [:baudrate, :sanitizer, :stats, :errors] in names
+  end
+end